@@ -1,3 +1,39 @@
 #!/bin/bash
 glslc ./shaders/shader.vert -o ./shaders/vert.spv
 glslc ./shaders/shader.frag -o ./shaders/frag.spv
+glslc ./shaders/line.vert -o ./shaders/line_vert.spv
+glslc ./shaders/line.frag -o ./shaders/line_frag.spv
+glslc ./shaders/mesh.vert -o ./shaders/mesh_vert.spv
+glslc ./shaders/mesh.frag -o ./shaders/mesh_frag.spv
+glslc ./shaders/mesh_multiview.vert -o ./shaders/mesh_multiview_vert.spv
+glslc ./shaders/fullscreen.vert -o ./shaders/fullscreen_vert.spv
+glslc ./shaders/tonemap.frag -o ./shaders/tonemap_frag.spv
+glslc ./shaders/exposure_histogram.comp -o ./shaders/exposure_histogram_comp.spv
+glslc ./shaders/exposure_average.comp -o ./shaders/exposure_average_comp.spv
+glslc ./shaders/copy.frag -o ./shaders/copy_frag.spv
+glslc ./shaders/fxaa.frag -o ./shaders/fxaa_frag.spv
+glslc ./shaders/vignette.frag -o ./shaders/vignette_frag.spv
+glslc ./shaders/bloom_downsample.frag -o ./shaders/bloom_downsample_frag.spv
+glslc ./shaders/bloom_upsample.frag -o ./shaders/bloom_upsample_frag.spv
+glslc ./shaders/bloom.frag -o ./shaders/bloom_frag.spv
+glslc ./shaders/sprite.vert -o ./shaders/sprite_vert.spv
+glslc ./shaders/sprite.frag -o ./shaders/sprite_frag.spv
+glslc ./shaders/pbr.vert -o ./shaders/pbr_vert.spv
+glslc ./shaders/pbr.frag -o ./shaders/pbr_frag.spv
+glslc ./shaders/ibl_cube.comp -o ./shaders/ibl_cube_comp.spv
+glslc ./shaders/ibl_irradiance.comp -o ./shaders/ibl_irradiance_comp.spv
+glslc ./shaders/ibl_prefilter.comp -o ./shaders/ibl_prefilter_comp.spv
+glslc ./shaders/ibl_brdf.comp -o ./shaders/ibl_brdf_comp.spv
+glslc ./shaders/particle.vert -o ./shaders/particle_vert.spv
+glslc ./shaders/particle.frag -o ./shaders/particle_frag.spv
+glslc -DMULTISAMPLED ./shaders/particle.frag -o ./shaders/particle_ms_frag.spv
+glslc ./shaders/particle_update.comp -o ./shaders/particle_update_comp.spv
+glslc ./shaders/particle_system.vert -o ./shaders/particle_system_vert.spv
+glslc ./shaders/particle_system.frag -o ./shaders/particle_system_frag.spv
+glslc ./shaders/image_blur.comp -o ./shaders/image_blur_comp.spv
+glslc ./shaders/image_downsample.comp -o ./shaders/image_downsample_comp.spv
+glslc ./shaders/image_histogram.comp -o ./shaders/image_histogram_comp.spv
+glslc ./shaders/video_yuv.comp -o ./shaders/video_yuv_comp.spv
+glslc ./tests/shaders/triangle.vert -o ./tests/shaders/triangle_vert.spv
+glslc ./tests/shaders/solid.frag -o ./tests/shaders/solid_frag.spv
+glslc ./tests/shaders/double.comp -o ./tests/shaders/double_comp.spv
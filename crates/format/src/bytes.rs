@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: MIT
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::FormatError;
+
+// little endian writing of pack data.
+pub(crate) struct Writer {
+    pub bytes: Vec<u8>,
+}
+
+impl Writer {
+    pub fn new(magic: &[u8; 4], version: u32) -> Self {
+        let mut writer = Self { bytes: Vec::new() };
+        writer.bytes.extend_from_slice(magic);
+        writer.u32(version);
+        writer
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn f32(&mut self, value: f32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn f32s(&mut self, values: &[f32]) {
+        values.iter().for_each(|v| self.f32(*v));
+    }
+
+    pub fn blob(&mut self, bytes: &[u8]) {
+        self.u32(bytes.len() as u32);
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    pub fn string(&mut self, value: &str) {
+        self.blob(value.as_bytes());
+    }
+
+    pub fn optional_string(&mut self, value: &Option<String>) {
+        match value {
+            Some(value) => {
+                self.u8(1);
+                self.string(value);
+            }
+            None => self.u8(0),
+        }
+    }
+}
+
+// little endian reading of pack data, every read checks the remaining length.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// check the magic and return the version.
+    pub fn new(bytes: &'a [u8], magic: &[u8; 4]) -> Result<(Self, u32), FormatError> {
+        let mut reader = Self { bytes };
+        if reader.take(4)? != magic {
+            return Err(FormatError::BadMagic);
+        }
+        let version = reader.u32()?;
+        Ok((reader, version))
+    }
+
+    /// read bytes without a header, e.g. one vertex of a pack.
+    pub fn raw(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    pub fn take(&mut self, count: usize) -> Result<&'a [u8], FormatError> {
+        if self.bytes.len() < count {
+            return Err(FormatError::UnexpectedEnd);
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, FormatError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn u32(&mut self) -> Result<u32, FormatError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub fn f32(&mut self) -> Result<f32, FormatError> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+
+    pub fn f32s<const N: usize>(&mut self) -> Result<[f32; N], FormatError> {
+        let mut values = [0.0; N];
+        for value in values.iter_mut() {
+            *value = self.f32()?;
+        }
+        Ok(values)
+    }
+
+    pub fn blob(&mut self) -> Result<&'a [u8], FormatError> {
+        let count = self.u32()? as usize;
+        self.take(count)
+    }
+
+    pub fn string(&mut self) -> Result<String, FormatError> {
+        let bytes = self.blob()?;
+        core::str::from_utf8(bytes)
+            .map(String::from)
+            .map_err(|_| FormatError::Invalid("string is not utf-8"))
+    }
+
+    pub fn optional_string(&mut self) -> Result<Option<String>, FormatError> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(self.string()?)),
+            _ => Err(FormatError::Invalid("bad optional tag")),
+        }
+    }
+
+    /// packs end with their data, anything after is an error.
+    pub fn finish(&self) -> Result<(), FormatError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(FormatError::Invalid("trailing data"))
+        }
+    }
+}
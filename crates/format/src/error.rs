@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: MIT
+
+use core::fmt;
+
+/// Why a pack couldn't be decoded.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FormatError {
+    /// the data ends before the pack does.
+    UnexpectedEnd,
+    /// the data doesn't start with the magic of the expected pack.
+    BadMagic,
+    /// the pack was written by a newer version of the format.
+    UnsupportedVersion(u32),
+    /// the pack is inconsistent, e.g. sizes that don't add up.
+    Invalid(&'static str),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FormatError::UnexpectedEnd => write!(f, "Unexpected end of pack data."),
+            FormatError::BadMagic => write!(f, "Not a pack of the expected kind."),
+            FormatError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported pack version {}.", version)
+            }
+            FormatError::Invalid(reason) => write!(f, "Invalid pack: {}.", reason),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FormatError {}
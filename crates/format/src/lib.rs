@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: MIT
+
+//! Math types and asset formats shared by the engine and offline tooling like bakers and
+//! converters. Only needs `alloc`, nothing here touches vulkan or a window.
+
+#![no_std]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+extern crate std;
+
+mod bytes;
+mod error;
+mod material;
+mod math;
+mod mesh;
+mod texture;
+mod vertex;
+
+pub use self::error::*;
+pub use self::material::*;
+pub use self::math::*;
+pub use self::mesh::*;
+pub use self::texture::*;
+pub use self::vertex::*;
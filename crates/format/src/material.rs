@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: MIT
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::bytes::{Reader, Writer};
+use crate::{FormatError, Vec3, Vec4};
+
+const MAGIC: &[u8; 4] = b"DMAT";
+const VERSION: u32 = 1;
+
+/// How a material is blended with what is behind it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MaterialBlend {
+    #[default]
+    Opaque,
+    Transparent,
+}
+
+/// A metallic roughness material, textures are referenced by asset name.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaterialDesc {
+    pub name: String,
+    pub base_color: Vec4,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub emissive: Vec3,
+    pub normal_scale: f32,
+    pub occlusion_strength: f32,
+    pub blend: MaterialBlend,
+    pub base_color_texture: Option<String>,
+    pub metallic_roughness_texture: Option<String>,
+    pub normal_texture: Option<String>,
+    pub occlusion_texture: Option<String>,
+    pub emissive_texture: Option<String>,
+}
+
+impl Default for MaterialDesc {
+    // the gltf defaults.
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            base_color: Vec4::splat(1.0),
+            metallic: 1.0,
+            roughness: 1.0,
+            emissive: Vec3::splat(0.0),
+            normal_scale: 1.0,
+            occlusion_strength: 1.0,
+            blend: MaterialBlend::Opaque,
+            base_color_texture: None,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            occlusion_texture: None,
+            emissive_texture: None,
+        }
+    }
+}
+
+impl MaterialDesc {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer::new(MAGIC, VERSION);
+        writer.string(&self.name);
+        writer.f32s(&<[f32; 4]>::from(self.base_color));
+        writer.f32(self.metallic);
+        writer.f32(self.roughness);
+        writer.f32s(&<[f32; 3]>::from(self.emissive));
+        writer.f32(self.normal_scale);
+        writer.f32(self.occlusion_strength);
+        writer.u8(match self.blend {
+            MaterialBlend::Opaque => 0,
+            MaterialBlend::Transparent => 1,
+        });
+        writer.optional_string(&self.base_color_texture);
+        writer.optional_string(&self.metallic_roughness_texture);
+        writer.optional_string(&self.normal_texture);
+        writer.optional_string(&self.occlusion_texture);
+        writer.optional_string(&self.emissive_texture);
+        writer.bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, FormatError> {
+        let (mut reader, version) = Reader::new(bytes, MAGIC)?;
+        if version > VERSION {
+            return Err(FormatError::UnsupportedVersion(version));
+        }
+
+        let desc = Self {
+            name: reader.string()?,
+            base_color: reader.f32s::<4>()?.into(),
+            metallic: reader.f32()?,
+            roughness: reader.f32()?,
+            emissive: reader.f32s::<3>()?.into(),
+            normal_scale: reader.f32()?,
+            occlusion_strength: reader.f32()?,
+            blend: match reader.u8()? {
+                0 => MaterialBlend::Opaque,
+                1 => MaterialBlend::Transparent,
+                _ => return Err(FormatError::Invalid("unknown blend mode")),
+            },
+            base_color_texture: reader.optional_string()?,
+            metallic_roughness_texture: reader.optional_string()?,
+            normal_texture: reader.optional_string()?,
+            occlusion_texture: reader.optional_string()?,
+            emissive_texture: reader.optional_string()?,
+        };
+        reader.finish()?;
+        Ok(desc)
+    }
+}
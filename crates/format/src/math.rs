@@ -0,0 +1,270 @@
+// SPDX-License-Identifier: MIT
+
+use core::ops::{Add, Mul, Neg, Sub};
+
+macro_rules! vector {
+    ($name:ident, $count:literal, $($field:ident),+) => {
+        #[repr(C)]
+        #[derive(Copy, Clone, Debug, Default, PartialEq)]
+        pub struct $name {
+            $(pub $field: f32),+
+        }
+
+        impl $name {
+            pub const fn new($($field: f32),+) -> Self {
+                Self { $($field),+ }
+            }
+
+            pub const fn splat(value: f32) -> Self {
+                Self { $($field: value),+ }
+            }
+
+            pub fn dot(self, other: Self) -> f32 {
+                0.0 $(+ self.$field * other.$field)+
+            }
+
+            pub fn length(self) -> f32 {
+                libm::sqrtf(self.dot(self))
+            }
+
+            /// the vector scaled to unit length, zero stays zero.
+            pub fn normalize(self) -> Self {
+                let length = self.length();
+                if length > 0.0 {
+                    self * (1.0 / length)
+                } else {
+                    self
+                }
+            }
+
+            pub fn lerp(self, other: Self, t: f32) -> Self {
+                self + (other - self) * t
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                Self { $($field: self.$field + other.$field),+ }
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                Self { $($field: self.$field - other.$field),+ }
+            }
+        }
+
+        impl Mul<f32> for $name {
+            type Output = Self;
+
+            fn mul(self, scale: f32) -> Self {
+                Self { $($field: self.$field * scale),+ }
+            }
+        }
+
+        impl Neg for $name {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self { $($field: -self.$field),+ }
+            }
+        }
+
+        impl From<[f32; $count]> for $name {
+            fn from(values: [f32; $count]) -> Self {
+                let [$($field),+] = values;
+                Self { $($field),+ }
+            }
+        }
+
+        impl From<$name> for [f32; $count] {
+            fn from(value: $name) -> Self {
+                [$(value.$field),+]
+            }
+        }
+    };
+}
+
+vector!(Vec2, 2, x, y);
+vector!(Vec3, 3, x, y, z);
+vector!(Vec4, 4, x, y, z, w);
+
+impl Vec3 {
+    pub fn cross(self, other: Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn extend(self, w: f32) -> Vec4 {
+        Vec4::new(self.x, self.y, self.z, w)
+    }
+}
+
+impl Vec4 {
+    pub fn truncate(self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+}
+
+/// A rotation as a unit quaternion, stored x, y, z, w like gltf.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Quat {
+    pub const IDENTITY: Quat = Quat::new(0.0, 0.0, 0.0, 1.0);
+
+    pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    /// a rotation of `angle` radians around a unit `axis`.
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+        let (sin, cos) = (libm::sinf(angle * 0.5), libm::cosf(angle * 0.5));
+        Self::new(axis.x * sin, axis.y * sin, axis.z * sin, cos)
+    }
+
+    pub fn normalize(self) -> Self {
+        let v = Vec4::from(self).normalize();
+        Self::new(v.x, v.y, v.z, v.w)
+    }
+
+    pub fn rotate(self, v: Vec3) -> Vec3 {
+        let u = Vec3::new(self.x, self.y, self.z);
+        let t = u.cross(v) * 2.0;
+        v + t * self.w + u.cross(t)
+    }
+}
+
+impl Default for Quat {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mul for Quat {
+    type Output = Self;
+
+    fn mul(self, o: Self) -> Self {
+        Self::new(
+            self.w * o.x + self.x * o.w + self.y * o.z - self.z * o.y,
+            self.w * o.y - self.x * o.z + self.y * o.w + self.z * o.x,
+            self.w * o.z + self.x * o.y - self.y * o.x + self.z * o.w,
+            self.w * o.w - self.x * o.x - self.y * o.y - self.z * o.z,
+        )
+    }
+}
+
+impl From<[f32; 4]> for Quat {
+    fn from([x, y, z, w]: [f32; 4]) -> Self {
+        Self::new(x, y, z, w)
+    }
+}
+
+impl From<Quat> for [f32; 4] {
+    fn from(q: Quat) -> Self {
+        [q.x, q.y, q.z, q.w]
+    }
+}
+
+impl From<Quat> for Vec4 {
+    fn from(q: Quat) -> Self {
+        Vec4::new(q.x, q.y, q.z, q.w)
+    }
+}
+
+/// A column major 4x4 matrix, laid out like the engine and glsl expect.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Mat4 {
+    pub columns: [Vec4; 4],
+}
+
+impl Mat4 {
+    pub const IDENTITY: Mat4 = Mat4 {
+        columns: [
+            Vec4::new(1.0, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, 1.0, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, 1.0, 0.0),
+            Vec4::new(0.0, 0.0, 0.0, 1.0),
+        ],
+    };
+
+    pub fn from_translation(t: Vec3) -> Self {
+        let mut m = Self::IDENTITY;
+        m.columns[3] = t.extend(1.0);
+        m
+    }
+
+    /// translation * rotation * scale, like a gltf node.
+    pub fn from_trs(translation: Vec3, rotation: Quat, scale: Vec3) -> Self {
+        let x = rotation.rotate(Vec3::new(1.0, 0.0, 0.0)) * scale.x;
+        let y = rotation.rotate(Vec3::new(0.0, 1.0, 0.0)) * scale.y;
+        let z = rotation.rotate(Vec3::new(0.0, 0.0, 1.0)) * scale.z;
+        Self {
+            columns: [
+                x.extend(0.0),
+                y.extend(0.0),
+                z.extend(0.0),
+                translation.extend(1.0),
+            ],
+        }
+    }
+
+    pub fn transform_point(&self, p: Vec3) -> Vec3 {
+        (self.columns[0] * p.x + self.columns[1] * p.y + self.columns[2] * p.z + self.columns[3])
+            .truncate()
+    }
+
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        (self.columns[0] * v.x + self.columns[1] * v.y + self.columns[2] * v.z).truncate()
+    }
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let column = |c: Vec4| {
+            self.columns[0] * c.x
+                + self.columns[1] * c.y
+                + self.columns[2] * c.z
+                + self.columns[3] * c.w
+        };
+        Self {
+            columns: other.columns.map(column),
+        }
+    }
+}
+
+impl From<[[f32; 4]; 4]> for Mat4 {
+    fn from(columns: [[f32; 4]; 4]) -> Self {
+        Self {
+            columns: columns.map(Vec4::from),
+        }
+    }
+}
+
+impl From<Mat4> for [[f32; 4]; 4] {
+    fn from(m: Mat4) -> Self {
+        m.columns.map(<[f32; 4]>::from)
+    }
+}
@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: MIT
+
+use alloc::vec::Vec;
+
+use crate::bytes::{Reader, Writer};
+use crate::{FormatError, PackVertex, VertexKind};
+
+const MAGIC: &[u8; 4] = b"DMSH";
+const VERSION: u32 = 1;
+
+/// An indexed mesh with interleaved vertices, as written by bakers and read by the engine.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshPack {
+    pub kind: VertexKind,
+    /// little endian vertex data, `vertex_count * stride` bytes.
+    pub vertices: Vec<u8>,
+    pub indices: Vec<u32>,
+}
+
+impl MeshPack {
+    pub fn from_vertices<T: PackVertex>(vertices: &[T], indices: &[u32]) -> Self {
+        let mut floats = Vec::new();
+        vertices.iter().for_each(|v| v.write(&mut floats));
+        Self {
+            kind: T::KIND,
+            vertices: floats.iter().flat_map(|f| f.to_le_bytes()).collect(),
+            indices: indices.to_vec(),
+        }
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len() / self.kind.layout().stride as usize
+    }
+
+    /// the vertices as `T`, fails when the pack holds a different kind.
+    pub fn vertices<T: PackVertex>(&self) -> Result<Vec<T>, FormatError> {
+        if T::KIND != self.kind {
+            return Err(FormatError::Invalid("vertex kind mismatch"));
+        }
+        let stride = self.kind.layout().stride as usize;
+        self.vertices.chunks_exact(stride).map(T::read).collect()
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer::new(MAGIC, VERSION);
+        writer.u8(self.kind.tag());
+        writer.blob(&self.vertices);
+        writer.u32(self.indices.len() as u32);
+        self.indices.iter().for_each(|i| writer.u32(*i));
+        writer.bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, FormatError> {
+        let (mut reader, version) = Reader::new(bytes, MAGIC)?;
+        if version > VERSION {
+            return Err(FormatError::UnsupportedVersion(version));
+        }
+
+        let kind = VertexKind::from_tag(reader.u8()?)?;
+        let vertices = reader.blob()?.to_vec();
+        if vertices.len() % kind.layout().stride as usize != 0 {
+            return Err(FormatError::Invalid(
+                "vertex data is not a multiple of the stride",
+            ));
+        }
+
+        let count = reader.u32()? as usize;
+        let mut indices = Vec::with_capacity(count.min(bytes.len() / 4));
+        for _ in 0..count {
+            indices.push(reader.u32()?);
+        }
+        reader.finish()?;
+
+        let pack = Self {
+            kind,
+            vertices,
+            indices,
+        };
+        let vertex_count = pack.vertex_count() as u32;
+        if pack.indices.iter().any(|i| *i >= vertex_count) {
+            return Err(FormatError::Invalid("index out of range"));
+        }
+        Ok(pack)
+    }
+}
@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: MIT
+
+use alloc::vec::Vec;
+
+use crate::bytes::{Reader, Writer};
+use crate::FormatError;
+
+const MAGIC: &[u8; 4] = b"DTEX";
+const VERSION: u32 = 1;
+
+/// The texel formats a texture pack can hold.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TextureFormat {
+    /// color data, sampled with srgb decoding.
+    Rgba8Srgb,
+    /// linear data like normal or metallic roughness maps.
+    Rgba8Unorm,
+    /// hdr data like environment maps.
+    Rgba16Float,
+}
+
+impl TextureFormat {
+    pub const fn bytes_per_texel(self) -> u32 {
+        match self {
+            TextureFormat::Rgba8Srgb | TextureFormat::Rgba8Unorm => 4,
+            TextureFormat::Rgba16Float => 8,
+        }
+    }
+
+    fn tag(self) -> u8 {
+        match self {
+            TextureFormat::Rgba8Srgb => 0,
+            TextureFormat::Rgba8Unorm => 1,
+            TextureFormat::Rgba16Float => 2,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Result<Self, FormatError> {
+        match tag {
+            0 => Ok(TextureFormat::Rgba8Srgb),
+            1 => Ok(TextureFormat::Rgba8Unorm),
+            2 => Ok(TextureFormat::Rgba16Float),
+            _ => Err(FormatError::Invalid("unknown texture format")),
+        }
+    }
+}
+
+/// A 2d texture with its mip chain, level 0 first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TexturePack {
+    pub width: u32,
+    pub height: u32,
+    pub format: TextureFormat,
+    pub levels: Vec<Vec<u8>>,
+}
+
+impl TexturePack {
+    /// a texture with only level 0.
+    pub fn new(width: u32, height: u32, format: TextureFormat, texels: Vec<u8>) -> Self {
+        Self {
+            width,
+            height,
+            format,
+            levels: alloc::vec![texels],
+        }
+    }
+
+    /// the size in bytes `level` must have, saturated when it doesn't fit a `usize`.
+    pub fn level_size(&self, level: u32) -> usize {
+        let width = self.width.checked_shr(level).unwrap_or(0).max(1) as usize;
+        let height = self.height.checked_shr(level).unwrap_or(0).max(1) as usize;
+        width
+            .checked_mul(height)
+            .and_then(|texels| texels.checked_mul(self.format.bytes_per_texel() as usize))
+            .unwrap_or(usize::MAX)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer::new(MAGIC, VERSION);
+        writer.u32(self.width);
+        writer.u32(self.height);
+        writer.u8(self.format.tag());
+        writer.u32(self.levels.len() as u32);
+        self.levels.iter().for_each(|l| writer.blob(l));
+        writer.bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, FormatError> {
+        let (mut reader, version) = Reader::new(bytes, MAGIC)?;
+        if version > VERSION {
+            return Err(FormatError::UnsupportedVersion(version));
+        }
+
+        let width = reader.u32()?;
+        let height = reader.u32()?;
+        let format = TextureFormat::from_tag(reader.u8()?)?;
+        if width == 0 || height == 0 {
+            return Err(FormatError::Invalid("texture has no texels"));
+        }
+
+        // a full chain of a 2^32 texture has 32 levels.
+        let count = reader.u32()?;
+        if count == 0 || count > 32 {
+            return Err(FormatError::Invalid("bad mip level count"));
+        }
+
+        let mut pack = Self {
+            width,
+            height,
+            format,
+            levels: Vec::with_capacity(count as usize),
+        };
+        for level in 0..count {
+            let texels = reader.blob()?;
+            if texels.len() != pack.level_size(level) {
+                return Err(FormatError::Invalid("mip level size doesn't match"));
+            }
+            pack.levels.push(texels.to_vec());
+        }
+        reader.finish()?;
+        Ok(pack)
+    }
+}
@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: MIT
+
+use alloc::vec::Vec;
+
+use crate::bytes::Reader;
+use crate::{FormatError, Vec2, Vec3, Vec4};
+
+/// The type of one vertex attribute.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum VertexFormat {
+    Float32x2,
+    Float32x3,
+    Float32x4,
+}
+
+impl VertexFormat {
+    pub const fn size(self) -> u32 {
+        match self {
+            VertexFormat::Float32x2 => 8,
+            VertexFormat::Float32x3 => 12,
+            VertexFormat::Float32x4 => 16,
+        }
+    }
+}
+
+/// Where an attribute sits in a vertex and which shader location reads it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VertexAttribute {
+    pub location: u32,
+    pub format: VertexFormat,
+    pub offset: u32,
+}
+
+/// The memory layout of a vertex, interleaved in a single buffer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VertexLayout {
+    pub stride: u32,
+    pub attributes: &'static [VertexAttribute],
+}
+
+const fn attribute(location: u32, format: VertexFormat, offset: u32) -> VertexAttribute {
+    VertexAttribute {
+        location,
+        format,
+        offset,
+    }
+}
+
+const BASIC_ATTRIBUTES: &[VertexAttribute] = &[
+    attribute(0, VertexFormat::Float32x3, 0),
+    attribute(1, VertexFormat::Float32x2, 12),
+    attribute(2, VertexFormat::Float32x3, 20),
+];
+
+const SURFACE_ATTRIBUTES: &[VertexAttribute] = &[
+    attribute(0, VertexFormat::Float32x3, 0),
+    attribute(1, VertexFormat::Float32x3, 12),
+    attribute(2, VertexFormat::Float32x4, 24),
+    attribute(3, VertexFormat::Float32x2, 40),
+];
+
+/// The vertex layouts of the engine, a mesh pack names the one its vertices use.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum VertexKind {
+    /// position, texel and color, for unlit meshes.
+    Basic,
+    /// position, normal, tangent and texel, for lit meshes.
+    Surface,
+}
+
+impl VertexKind {
+    pub const fn layout(self) -> VertexLayout {
+        match self {
+            VertexKind::Basic => VertexLayout {
+                stride: 32,
+                attributes: BASIC_ATTRIBUTES,
+            },
+            VertexKind::Surface => VertexLayout {
+                stride: 48,
+                attributes: SURFACE_ATTRIBUTES,
+            },
+        }
+    }
+
+    pub(crate) fn tag(self) -> u8 {
+        match self {
+            VertexKind::Basic => 0,
+            VertexKind::Surface => 1,
+        }
+    }
+
+    pub(crate) fn from_tag(tag: u8) -> Result<Self, FormatError> {
+        match tag {
+            0 => Ok(VertexKind::Basic),
+            1 => Ok(VertexKind::Surface),
+            _ => Err(FormatError::Invalid("unknown vertex kind")),
+        }
+    }
+}
+
+/// A vertex that can be stored in a mesh pack.
+pub trait PackVertex: Sized {
+    const KIND: VertexKind;
+
+    /// append the attributes in the order of the layout.
+    fn write(&self, out: &mut Vec<f32>);
+
+    /// read a vertex of exactly the stride of the layout.
+    fn read(bytes: &[u8]) -> Result<Self, FormatError>;
+}
+
+/// An unlit vertex, see `VertexKind::Basic`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct BasicVertex {
+    pub position: Vec3,
+    pub texel: Vec2,
+    pub color: Vec3,
+}
+
+impl PackVertex for BasicVertex {
+    const KIND: VertexKind = VertexKind::Basic;
+
+    fn write(&self, out: &mut Vec<f32>) {
+        out.extend_from_slice(&<[f32; 3]>::from(self.position));
+        out.extend_from_slice(&<[f32; 2]>::from(self.texel));
+        out.extend_from_slice(&<[f32; 3]>::from(self.color));
+    }
+
+    fn read(bytes: &[u8]) -> Result<Self, FormatError> {
+        let mut reader = Reader::raw(bytes);
+        Ok(Self {
+            position: reader.f32s::<3>()?.into(),
+            texel: reader.f32s::<2>()?.into(),
+            color: reader.f32s::<3>()?.into(),
+        })
+    }
+}
+
+/// A lit vertex, see `VertexKind::Surface`. the tangent w holds the handedness of the
+/// bitangent.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SurfaceVertex {
+    pub position: Vec3,
+    pub normal: Vec3,
+    pub tangent: Vec4,
+    pub texel: Vec2,
+}
+
+impl PackVertex for SurfaceVertex {
+    const KIND: VertexKind = VertexKind::Surface;
+
+    fn write(&self, out: &mut Vec<f32>) {
+        out.extend_from_slice(&<[f32; 3]>::from(self.position));
+        out.extend_from_slice(&<[f32; 3]>::from(self.normal));
+        out.extend_from_slice(&<[f32; 4]>::from(self.tangent));
+        out.extend_from_slice(&<[f32; 2]>::from(self.texel));
+    }
+
+    fn read(bytes: &[u8]) -> Result<Self, FormatError> {
+        let mut reader = Reader::raw(bytes);
+        Ok(Self {
+            position: reader.f32s::<3>()?.into(),
+            normal: reader.f32s::<3>()?.into(),
+            tangent: reader.f32s::<4>()?.into(),
+            texel: reader.f32s::<2>()?.into(),
+        })
+    }
+}
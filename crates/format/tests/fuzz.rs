@@ -0,0 +1,213 @@
+// SPDX-License-Identifier: MIT
+
+//! Generated and mutated packs for the decoders. whatever the bytes, decoding returns a
+//! `FormatError` instead of panicking, and what decodes survives another roundtrip.
+
+use deimos_format::*;
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+fn float() -> impl Strategy<Value = f32> {
+    prop_oneof![-1e3f32..1e3, any::<f32>()]
+}
+
+fn basic_vertex() -> impl Strategy<Value = BasicVertex> {
+    vec(float(), 8).prop_map(|f| BasicVertex {
+        position: [f[0], f[1], f[2]].into(),
+        texel: [f[3], f[4]].into(),
+        color: [f[5], f[6], f[7]].into(),
+    })
+}
+
+fn surface_vertex() -> impl Strategy<Value = SurfaceVertex> {
+    vec(float(), 12).prop_map(|f| SurfaceVertex {
+        position: [f[0], f[1], f[2]].into(),
+        normal: [f[3], f[4], f[5]].into(),
+        tangent: [f[6], f[7], f[8], f[9]].into(),
+        texel: [f[10], f[11]].into(),
+    })
+}
+
+// indices into `count` vertices
+fn indices(count: usize) -> impl Strategy<Value = Vec<u32>> {
+    if count == 0 {
+        Just(vec![]).boxed()
+    } else {
+        vec(0..count as u32, 0..48).boxed()
+    }
+}
+
+fn mesh_pack() -> impl Strategy<Value = MeshPack> {
+    prop_oneof![
+        vec(basic_vertex(), 0..16).prop_flat_map(|vertices| {
+            indices(vertices.len()).prop_map(move |i| MeshPack::from_vertices(&vertices, &i))
+        }),
+        vec(surface_vertex(), 0..16).prop_flat_map(|vertices| {
+            indices(vertices.len()).prop_map(move |i| MeshPack::from_vertices(&vertices, &i))
+        }),
+    ]
+}
+
+fn texture_format() -> impl Strategy<Value = TextureFormat> {
+    prop_oneof![
+        Just(TextureFormat::Rgba8Srgb),
+        Just(TextureFormat::Rgba8Unorm),
+        Just(TextureFormat::Rgba16Float),
+    ]
+}
+
+fn texture_pack() -> impl Strategy<Value = TexturePack> {
+    (1..=24u32, 1..=24u32, texture_format(), any::<u8>()).prop_flat_map(
+        |(width, height, format, fill)| {
+            let max = 32 - width.max(height).leading_zeros();
+            (1..=max).prop_map(move |count| {
+                let mut pack = TexturePack::new(width, height, format, vec![]);
+                pack.levels = (0..count)
+                    .map(|level| vec![fill.wrapping_add(level as u8); pack.level_size(level)])
+                    .collect();
+                pack
+            })
+        },
+    )
+}
+
+fn name() -> impl Strategy<Value = Option<String>> {
+    proptest::option::of("[a-z/._ ]{0,12}|\\PC{0,6}")
+}
+
+fn material_desc() -> impl Strategy<Value = MaterialDesc> {
+    (
+        "\\PC{0,16}",
+        vec(float(), 10),
+        any::<bool>(),
+        vec(name(), 5),
+    )
+        .prop_map(|(name, f, transparent, textures)| MaterialDesc {
+            name,
+            base_color: [f[0], f[1], f[2], f[3]].into(),
+            metallic: f[4],
+            roughness: f[5],
+            emissive: [f[6], f[7], f[8]].into(),
+            normal_scale: f[9],
+            occlusion_strength: f[9] * 0.5,
+            blend: if transparent {
+                MaterialBlend::Transparent
+            } else {
+                MaterialBlend::Opaque
+            },
+            base_color_texture: textures[0].clone(),
+            metallic_roughness_texture: textures[1].clone(),
+            normal_texture: textures[2].clone(),
+            occlusion_texture: textures[3].clone(),
+            emissive_texture: textures[4].clone(),
+        })
+}
+
+// flip, overwrite and cut bytes of a valid pack
+fn mutate(bytes: Vec<u8>) -> impl Strategy<Value = Vec<u8>> {
+    let len = bytes.len();
+    (
+        vec((0..len, any::<u8>(), any::<bool>()), 1..8),
+        proptest::option::of(0..=len),
+    )
+        .prop_map(move |(edits, cut)| {
+            let mut bytes = bytes.clone();
+            for (index, value, flip) in edits {
+                if flip {
+                    bytes[index] ^= 1 << (value % 8);
+                } else {
+                    bytes[index] = value;
+                }
+            }
+            bytes.truncate(cut.unwrap_or(len));
+            bytes
+        })
+}
+
+// the magic and version of a pack kind followed by noise, gets past the header checks
+fn noise_after(magic: &'static [u8; 4]) -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 0..256).prop_map(move |tail| {
+        let mut bytes = magic.to_vec();
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend(tail);
+        bytes
+    })
+}
+
+// decoding returns, and a decoded pack survives another roundtrip unchanged. the bytes are
+// compared so nan floats compare equal
+macro_rules! check_decode {
+    ($pack:ty, $bytes:expr) => {{
+        if let Ok(pack) = <$pack>::decode($bytes) {
+            let bytes = pack.encode();
+            prop_assert_eq!(<$pack>::decode(&bytes).map(|p| p.encode()), Ok(bytes));
+        }
+    }};
+}
+
+proptest! {
+    #[test]
+    fn mesh_packs_roundtrip(pack in mesh_pack()) {
+        let bytes = pack.encode();
+        let decoded = MeshPack::decode(&bytes).unwrap();
+        prop_assert_eq!(decoded.encode(), bytes);
+        prop_assert_eq!(decoded.vertex_count(), pack.vertex_count());
+    }
+
+    #[test]
+    fn texture_packs_roundtrip(pack in texture_pack()) {
+        let bytes = pack.encode();
+        prop_assert_eq!(TexturePack::decode(&bytes), Ok(pack));
+    }
+
+    #[test]
+    fn material_descs_roundtrip(desc in material_desc()) {
+        let bytes = desc.encode();
+        prop_assert_eq!(MaterialDesc::decode(&bytes).unwrap().encode(), bytes);
+    }
+
+    #[test]
+    fn mutated_mesh_packs_fail_cleanly(bytes in mesh_pack().prop_flat_map(|p| mutate(p.encode()))) {
+        check_decode!(MeshPack, &bytes);
+    }
+
+    #[test]
+    fn mutated_texture_packs_fail_cleanly(
+        bytes in texture_pack().prop_flat_map(|p| mutate(p.encode()))
+    ) {
+        check_decode!(TexturePack, &bytes);
+    }
+
+    #[test]
+    fn mutated_material_descs_fail_cleanly(
+        bytes in material_desc().prop_flat_map(|d| mutate(d.encode()))
+    ) {
+        check_decode!(MaterialDesc, &bytes);
+    }
+
+    #[test]
+    fn noise_fails_cleanly(
+        mesh in noise_after(b"DMSH"),
+        texture in noise_after(b"DTEX"),
+        material in noise_after(b"DMAT"),
+        any in vec(any::<u8>(), 0..64),
+    ) {
+        check_decode!(MeshPack, &mesh);
+        check_decode!(TexturePack, &texture);
+        check_decode!(MaterialDesc, &material);
+        check_decode!(MeshPack, &any);
+        check_decode!(TexturePack, &any);
+        check_decode!(MaterialDesc, &any);
+    }
+
+    #[test]
+    fn level_sizes_never_overflow(
+        width in any::<u32>(),
+        height in any::<u32>(),
+        format in texture_format(),
+        level in any::<u32>(),
+    ) {
+        let pack = TexturePack::new(width, height, format, vec![]);
+        prop_assert!(pack.level_size(level) >= format.bytes_per_texel() as usize);
+    }
+}
@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: MIT
+
+//! The smallest app: a spinning quad drawn by the mesh renderer, which runs the whole frame.
+
+use anyhow::Result;
+use cgmath::{vec2, vec3, vec4, Deg, Matrix4};
+use deimos::prelude::*;
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoop};
+use winit::window::WindowBuilder;
+
+fn main() -> Result<()> {
+    let event_loop = EventLoop::new();
+    let window = WindowBuilder::new().with_title("quad").build(&event_loop)?;
+
+    // the device, the renderer and a quad facing the camera
+    let mut device = Device::create(&window, "quad")?;
+    let mut renderer = Renderer::create(&device)?;
+    let white = vec3(1.0, 1.0, 1.0);
+    let vertices = [
+        Vertex::new(vec3(-0.5, -0.5, 0.0), vec2(0.0, 0.0), white),
+        Vertex::new(vec3(0.5, -0.5, 0.0), vec2(1.0, 0.0), white),
+        Vertex::new(vec3(0.5, 0.5, 0.0), vec2(1.0, 1.0), white),
+        Vertex::new(vec3(-0.5, 0.5, 0.0), vec2(0.0, 1.0), white),
+    ];
+    let quad = Mesh::create(&device, &vertices, &[0, 1, 2, 2, 3, 0])?.register(&device);
+    let material = Material::opaque(vec4(1.0, 0.5, 0.2, 1.0));
+    let camera = Camera::looking_at(
+        vec3(0.0, 0.0, 2.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+    );
+    let clock = std::time::Instant::now();
+
+    event_loop.run(move |event, _, control_flow| match event {
+        Event::MainEventsCleared if !device.is_minimized() => {
+            let angle = Deg(clock.elapsed().as_secs_f32() * 90.0);
+            renderer.begin_frame(camera);
+            renderer.draw(&quad, &material, Matrix4::from_angle_y(angle));
+            renderer.end_frame(&mut device, &window).unwrap();
+        }
+        Event::WindowEvent {
+            event: WindowEvent::Resized(size),
+            ..
+        } => {
+            device.resized(size.width, size.height);
+        }
+        Event::WindowEvent {
+            event: WindowEvent::CloseRequested,
+            ..
+        } => {
+            *control_flow = ControlFlow::Exit;
+        }
+        Event::LoopDestroyed => {
+            device.wait_idle().unwrap();
+            quad.destroy(&device).unwrap();
+            renderer.destroy(&device);
+            device.destroy();
+        }
+        _ => {}
+    });
+}
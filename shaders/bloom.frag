@@ -0,0 +1,19 @@
+#version 450
+
+layout(set = 1, binding = 0) uniform texture2D input_texture;
+layout(set = 1, binding = 2) uniform texture2D bloom_texture;
+layout(set = 1, binding = 3) uniform sampler input_sampler;
+
+layout(push_constant) uniform PushConstants {
+    float intensity;
+} pcs;
+
+layout(location = 0) in vec2 screen_texel;
+
+layout(location = 0) out vec4 output_color;
+
+void main() {
+    vec3 color = texture(sampler2D(input_texture, input_sampler), screen_texel).rgb;
+    vec3 bloom = texture(sampler2D(bloom_texture, input_sampler), screen_texel).rgb;
+    output_color = vec4(color + bloom * pcs.intensity, 1.0);
+}
@@ -0,0 +1,57 @@
+#version 450
+
+layout(set = 1, binding = 0) uniform texture2D input_texture;
+layout(set = 1, binding = 1) uniform sampler input_sampler;
+
+layout(push_constant) uniform PushConstants {
+    float threshold;
+    float knee;
+    uint prefilter;
+} pcs;
+
+layout(location = 0) in vec2 screen_texel;
+
+layout(location = 0) out vec4 output_color;
+
+vec3 fetch(vec2 texel) {
+    return texture(sampler2D(input_texture, input_sampler), texel).rgb;
+}
+
+// keep what is brighter than the threshold with a soft quadratic knee
+vec3 bright_pass(vec3 color) {
+    float brightness = max(color.r, max(color.g, color.b));
+    float soft = clamp(brightness - pcs.threshold + pcs.knee, 0.0, 2.0 * pcs.knee);
+    soft = soft * soft / (4.0 * pcs.knee + 0.0001);
+    float contribution = max(soft, brightness - pcs.threshold) / max(brightness, 0.0001);
+    return color * contribution;
+}
+
+void main() {
+    vec2 pixel = 1.0 / vec2(textureSize(input_texture, 0));
+
+    // 13 tap filter, a weighted sum of five overlapping boxes
+    vec3 a = fetch(screen_texel + pixel * vec2(-2.0, -2.0));
+    vec3 b = fetch(screen_texel + pixel * vec2(0.0, -2.0));
+    vec3 c = fetch(screen_texel + pixel * vec2(2.0, -2.0));
+    vec3 d = fetch(screen_texel + pixel * vec2(-2.0, 0.0));
+    vec3 e = fetch(screen_texel);
+    vec3 f = fetch(screen_texel + pixel * vec2(2.0, 0.0));
+    vec3 g = fetch(screen_texel + pixel * vec2(-2.0, 2.0));
+    vec3 h = fetch(screen_texel + pixel * vec2(0.0, 2.0));
+    vec3 i = fetch(screen_texel + pixel * vec2(2.0, 2.0));
+    vec3 j = fetch(screen_texel + pixel * vec2(-1.0, -1.0));
+    vec3 k = fetch(screen_texel + pixel * vec2(1.0, -1.0));
+    vec3 l = fetch(screen_texel + pixel * vec2(-1.0, 1.0));
+    vec3 m = fetch(screen_texel + pixel * vec2(1.0, 1.0));
+
+    vec3 color = e * 0.125;
+    color += (a + c + g + i) * 0.03125;
+    color += (b + d + f + h) * 0.0625;
+    color += (j + k + l + m) * 0.125;
+
+    if (pcs.prefilter != 0u) {
+        color = bright_pass(color);
+    }
+
+    output_color = vec4(color, 1.0);
+}
@@ -0,0 +1,29 @@
+#version 450
+
+layout(set = 1, binding = 0) uniform texture2D input_texture;
+layout(set = 1, binding = 1) uniform sampler input_sampler;
+
+layout(push_constant) uniform PushConstants {
+    float radius;
+} pcs;
+
+layout(location = 0) in vec2 screen_texel;
+
+layout(location = 0) out vec4 output_color;
+
+vec3 fetch(vec2 texel) {
+    return texture(sampler2D(input_texture, input_sampler), texel).rgb;
+}
+
+void main() {
+    vec2 pixel = pcs.radius / vec2(textureSize(input_texture, 0));
+
+    // 3x3 tent filter, added onto the larger level by blending
+    vec3 color = fetch(screen_texel) * 4.0;
+    color += (fetch(screen_texel + vec2(-pixel.x, 0.0)) + fetch(screen_texel + vec2(pixel.x, 0.0))) * 2.0;
+    color += (fetch(screen_texel + vec2(0.0, -pixel.y)) + fetch(screen_texel + vec2(0.0, pixel.y))) * 2.0;
+    color += fetch(screen_texel + vec2(-pixel.x, -pixel.y)) + fetch(screen_texel + vec2(pixel.x, -pixel.y));
+    color += fetch(screen_texel + vec2(-pixel.x, pixel.y)) + fetch(screen_texel + vec2(pixel.x, pixel.y));
+
+    output_color = vec4(color / 16.0, 1.0);
+}
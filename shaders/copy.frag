@@ -0,0 +1,12 @@
+#version 450
+
+layout(set = 1, binding = 0) uniform texture2D input_texture;
+layout(set = 1, binding = 3) uniform sampler input_sampler;
+
+layout(location = 0) in vec2 screen_texel;
+
+layout(location = 0) out vec4 output_color;
+
+void main() {
+    output_color = vec4(texture(sampler2D(input_texture, input_sampler), screen_texel).rgb, 1.0);
+}
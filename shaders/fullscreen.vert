@@ -0,0 +1,10 @@
+#version 450
+
+layout(location = 0) out vec2 screen_texel;
+
+void main() {
+    // a single triangle covering the screen, no vertex buffer needed
+    vec2 texel = vec2(float((gl_VertexIndex << 1) & 2), float(gl_VertexIndex & 2));
+    gl_Position = vec4(texel * 2.0 - 1.0, 0.0, 1.0);
+    screen_texel = texel;
+}
@@ -0,0 +1,133 @@
+#version 450
+
+layout(set = 1, binding = 0) uniform texture2D input_texture;
+layout(set = 1, binding = 3) uniform sampler input_sampler;
+
+layout(push_constant) uniform PushConstants {
+    float edge_threshold;
+    float edge_threshold_min;
+    float subpixel;
+} pcs;
+
+layout(location = 0) in vec2 screen_texel;
+
+layout(location = 0) out vec4 output_color;
+
+const int SEARCH_STEPS = 10;
+
+// perceptual luma of linear color
+float luma(vec3 color) {
+    return sqrt(dot(color, vec3(0.299, 0.587, 0.114)));
+}
+
+float sample_luma(vec2 texel) {
+    return luma(texture(sampler2D(input_texture, input_sampler), texel).rgb);
+}
+
+void main() {
+    vec2 pixel = 1.0 / vec2(textureSize(input_texture, 0));
+    vec3 center_color = texture(sampler2D(input_texture, input_sampler), screen_texel).rgb;
+
+    // local contrast
+    float center = luma(center_color);
+    float north = sample_luma(screen_texel + vec2(0.0, -pixel.y));
+    float south = sample_luma(screen_texel + vec2(0.0, pixel.y));
+    float east = sample_luma(screen_texel + vec2(pixel.x, 0.0));
+    float west = sample_luma(screen_texel + vec2(-pixel.x, 0.0));
+
+    float highest = max(max(max(north, south), max(east, west)), center);
+    float lowest = min(min(min(north, south), min(east, west)), center);
+    float contrast = highest - lowest;
+
+    // not an edge
+    if (contrast < max(pcs.edge_threshold_min, highest * pcs.edge_threshold)) {
+        output_color = vec4(center_color, 1.0);
+        return;
+    }
+
+    float north_west = sample_luma(screen_texel + vec2(-pixel.x, -pixel.y));
+    float north_east = sample_luma(screen_texel + vec2(pixel.x, -pixel.y));
+    float south_west = sample_luma(screen_texel + vec2(-pixel.x, pixel.y));
+    float south_east = sample_luma(screen_texel + vec2(pixel.x, pixel.y));
+
+    // sub-pixel blend factor from the neighbourhood average
+    float average = (2.0 * (north + south + east + west) + north_west + north_east + south_west + south_east) / 12.0;
+    float subpixel_blend = clamp(abs(average - center) / contrast, 0.0, 1.0);
+    subpixel_blend = smoothstep(0.0, 1.0, subpixel_blend);
+    subpixel_blend = subpixel_blend * subpixel_blend * pcs.subpixel;
+
+    // edge orientation
+    float horizontal = abs(north + south - 2.0 * center) * 2.0 + abs(north_east + south_east - 2.0 * east) + abs(north_west + south_west - 2.0 * west);
+    float vertical = abs(east + west - 2.0 * center) * 2.0 + abs(north_east + north_west - 2.0 * north) + abs(south_east + south_west - 2.0 * south);
+    bool is_horizontal = horizontal >= vertical;
+
+    // pick the side of the edge with the higher gradient
+    float positive = is_horizontal ? south : east;
+    float negative = is_horizontal ? north : west;
+    float positive_gradient = abs(positive - center);
+    float negative_gradient = abs(negative - center);
+
+    float step_length = is_horizontal ? pixel.y : pixel.x;
+    float opposite = positive;
+    float gradient = positive_gradient;
+    if (negative_gradient > positive_gradient) {
+        step_length = -step_length;
+        opposite = negative;
+        gradient = negative_gradient;
+    }
+
+    // walk along the edge in both directions
+    vec2 edge_texel = screen_texel;
+    vec2 edge_step = vec2(0.0);
+    if (is_horizontal) {
+        edge_texel.y += step_length * 0.5;
+        edge_step = vec2(pixel.x, 0.0);
+    } else {
+        edge_texel.x += step_length * 0.5;
+        edge_step = vec2(0.0, pixel.y);
+    }
+
+    float edge_luma = (center + opposite) * 0.5;
+    float threshold = gradient * 0.25;
+
+    vec2 positive_texel = edge_texel + edge_step;
+    vec2 negative_texel = edge_texel - edge_step;
+    float positive_delta = sample_luma(positive_texel) - edge_luma;
+    float negative_delta = sample_luma(negative_texel) - edge_luma;
+    bool positive_done = abs(positive_delta) >= threshold;
+    bool negative_done = abs(negative_delta) >= threshold;
+
+    for (int i = 0; i < SEARCH_STEPS; i++) {
+        if (positive_done && negative_done) {
+            break;
+        }
+        if (!positive_done) {
+            positive_texel += edge_step;
+            positive_delta = sample_luma(positive_texel) - edge_luma;
+            positive_done = abs(positive_delta) >= threshold;
+        }
+        if (!negative_done) {
+            negative_texel -= edge_step;
+            negative_delta = sample_luma(negative_texel) - edge_luma;
+            negative_done = abs(negative_delta) >= threshold;
+        }
+    }
+
+    // distance to both ends of the edge
+    float positive_distance = is_horizontal ? positive_texel.x - screen_texel.x : positive_texel.y - screen_texel.y;
+    float negative_distance = is_horizontal ? screen_texel.x - negative_texel.x : screen_texel.y - negative_texel.y;
+
+    float shortest = min(positive_distance, negative_distance);
+    float delta_sign = positive_distance <= negative_distance ? positive_delta : negative_delta;
+
+    // only blend when the end is on the far side of the center
+    float edge_blend = 0.0;
+    if ((delta_sign < 0.0) != (center - edge_luma < 0.0)) {
+        edge_blend = 0.5 - shortest / (positive_distance + negative_distance);
+    }
+
+    float blend = max(edge_blend, subpixel_blend);
+
+    vec2 offset = is_horizontal ? vec2(0.0, step_length * blend) : vec2(step_length * blend, 0.0);
+    output_color = vec4(texture(sampler2D(input_texture, input_sampler), screen_texel + offset).rgb, 1.0);
+}
@@ -0,0 +1,59 @@
+#version 450
+
+layout(push_constant) uniform PushConstants {
+    mat4 view_proj;
+    vec2 viewport;
+    float miter_limit;
+    uint style;
+    vec2 dash;
+    float dash_offset;
+} pcs;
+
+layout(location = 0) in vec4 line_color;
+layout(location = 1) noperspective in vec2 line_coord;
+layout(location = 2) flat in float line_length;
+layout(location = 3) noperspective in float line_half_width;
+layout(location = 4) in float line_distance;
+layout(location = 5) flat in uint line_flags;
+
+layout(location = 0) out vec4 output_color;
+
+const uint JOIN_ROUND = 1u;
+const uint CAP_ROUND = 2u;
+
+const uint HAS_PREV = 1u;
+const uint HAS_NEXT = 2u;
+
+void main() {
+    uint join = pcs.style & 3u;
+    uint cap = (pcs.style >> 2u) & 3u;
+
+    // distance to the segment center line in pixels
+    float reach = abs(line_coord.y);
+    bool rounded = false;
+    if (line_coord.x < 0.0) {
+        reach = length(line_coord);
+        rounded = (line_flags & HAS_PREV) != 0u ? join == JOIN_ROUND : cap == CAP_ROUND;
+    } else if (line_coord.x > line_length) {
+        reach = length(vec2(line_coord.x - line_length, line_coord.y));
+        rounded = (line_flags & HAS_NEXT) != 0u ? join == JOIN_ROUND : cap == CAP_ROUND;
+    }
+
+    // outside of a rounded cap or join
+    if (rounded && reach > line_half_width) {
+        discard;
+    }
+
+    // in a dash gap
+    float period = pcs.dash.x + pcs.dash.y;
+    if (pcs.dash.x > 0.0 && mod(line_distance + pcs.dash_offset, period) > pcs.dash.x) {
+        discard;
+    }
+
+    // smooth the edges over a pixel
+    float edge = rounded ? reach : abs(line_coord.y);
+    float coverage = clamp(line_half_width - edge + 0.5, 0.0, 1.0);
+
+    // premultiplied, so coverage scales all channels
+    output_color = line_color * coverage;
+}
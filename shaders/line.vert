@@ -0,0 +1,98 @@
+#version 450
+
+layout(push_constant) uniform PushConstants {
+    mat4 view_proj;
+    vec2 viewport;
+    float miter_limit;
+    uint style;
+    vec2 dash;
+    float dash_offset;
+} pcs;
+
+layout(location = 0) in vec3 prev;
+layout(location = 1) in vec3 start;
+layout(location = 2) in vec3 end;
+layout(location = 3) in vec3 next;
+layout(location = 4) in vec4 start_color;
+layout(location = 5) in vec4 end_color;
+layout(location = 6) in vec4 params;
+layout(location = 7) in uint flags;
+
+layout(location = 0) out vec4 line_color;
+layout(location = 1) noperspective out vec2 line_coord;
+layout(location = 2) flat out float line_length;
+layout(location = 3) noperspective out float line_half_width;
+layout(location = 4) out float line_distance;
+layout(location = 5) flat out uint line_flags;
+
+const uint JOIN_MITER = 0u;
+const uint JOIN_ROUND = 1u;
+const uint CAP_BUTT = 0u;
+
+const uint HAS_PREV = 1u;
+const uint HAS_NEXT = 2u;
+
+vec2 to_screen(vec4 clip) {
+    return (clip.xy / clip.w * 0.5 + 0.5) * pcs.viewport;
+}
+
+void main() {
+    // two triangles, x selects the segment end and y the side
+    vec2 corners[6] = vec2[6](
+        vec2(0.0, -1.0), vec2(0.0, 1.0), vec2(1.0, -1.0),
+        vec2(1.0, -1.0), vec2(0.0, 1.0), vec2(1.0, 1.0)
+    );
+    vec2 corner = corners[gl_VertexIndex];
+    bool at_end = corner.x > 0.5;
+
+    // project the segment into pixels
+    vec4 clip_start = pcs.view_proj * vec4(start, 1.0);
+    vec4 clip_end = pcs.view_proj * vec4(end, 1.0);
+    vec2 a = to_screen(clip_start);
+    vec2 b = to_screen(clip_end);
+
+    float len = length(b - a);
+    vec2 dir = len > 0.0 ? (b - a) / len : vec2(1.0, 0.0);
+    vec2 normal = vec2(-dir.y, dir.x);
+
+    uint join = pcs.style & 3u;
+    uint cap = (pcs.style >> 2u) & 3u;
+    float half_width = 0.5 * (at_end ? params.y : params.x);
+    bool joined = (flags & (at_end ? HAS_NEXT : HAS_PREV)) != 0u;
+
+    vec2 offset = normal * corner.y * half_width;
+    if (joined && join == JOIN_MITER) {
+        // bisect the corner with the neighbouring segment
+        vec2 other = at_end
+            ? to_screen(pcs.view_proj * vec4(next, 1.0))
+            : to_screen(pcs.view_proj * vec4(prev, 1.0));
+        vec2 other_dir = at_end ? other - b : a - other;
+        if (length(other_dir) > 0.0) {
+            vec2 tangent = normalize(dir + normalize(other_dir));
+            vec2 miter = vec2(-tangent.y, tangent.x);
+            float scale = 1.0 / max(dot(miter, normal), 0.0001);
+
+            // past the limit the join falls back to bevel
+            if (scale <= pcs.miter_limit) {
+                offset = miter * corner.y * half_width * scale;
+            }
+        }
+    } else if ((joined && join == JOIN_ROUND) || (!joined && cap != CAP_BUTT)) {
+        // extend past the end point to make room for the cap or join
+        offset += dir * half_width * (at_end ? 1.0 : -1.0);
+    }
+
+    vec2 position = (at_end ? b : a) + offset;
+    vec4 clip = at_end ? clip_end : clip_start;
+
+    // colors are given straight, blending expects them premultiplied
+    vec4 color = at_end ? end_color : start_color;
+    line_color = vec4(color.rgb * color.a, color.a);
+    line_coord = vec2((at_end ? len : 0.0) + dot(offset, dir), dot(offset, normal));
+    line_length = len;
+    line_half_width = half_width;
+    line_distance = at_end ? params.w : params.z;
+    line_flags = flags;
+
+    gl_Position = vec4((position / pcs.viewport * 2.0 - 1.0) * clip.w, clip.z, clip.w);
+}
@@ -0,0 +1,10 @@
+#version 450
+
+layout(location = 0) in vec4 surface_color;
+layout(location = 1) in vec2 surface_texel;
+
+layout(location = 0) out vec4 output_color;
+
+void main() {
+    output_color = surface_color;
+}
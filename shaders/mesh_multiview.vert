@@ -0,0 +1,24 @@
+#version 450
+#extension GL_EXT_multiview : require
+
+layout(set = 0, binding = 0) uniform Views {
+    mat4 view_proj[2];
+} views;
+
+layout(push_constant) uniform PushConstants {
+    mat4 model;
+    vec4 color;
+} pcs;
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec2 texel;
+layout(location = 2) in vec3 color;
+
+layout(location = 0) out vec4 surface_color;
+layout(location = 1) out vec2 surface_texel;
+
+void main() {
+    gl_Position = views.view_proj[gl_ViewIndex] * pcs.model * vec4(position, 1.0);
+    surface_color = vec4(color, 1.0) * pcs.color;
+    surface_texel = texel;
+}
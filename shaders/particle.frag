@@ -0,0 +1,54 @@
+#version 450
+
+layout(push_constant) uniform PushConstants {
+    mat4 view_proj;
+    vec4 right;
+    vec4 up;
+    vec2 depth;
+    float softness;
+    uint straight;
+} pcs;
+
+// the scene depth as drawn by the opaque pass, multisampled like the scene target
+#ifdef MULTISAMPLED
+layout(set = 1, binding = 0) uniform texture2DMS depth_texture;
+#else
+layout(set = 1, binding = 0) uniform texture2D depth_texture;
+#endif
+layout(set = 1, binding = 1) uniform sampler depth_sampler;
+
+layout(set = 2, binding = 0) uniform texture2D particle_texture;
+layout(set = 2, binding = 1) uniform sampler particle_sampler;
+
+layout(location = 0) in vec4 particle_color;
+layout(location = 1) in vec2 particle_texel;
+
+layout(location = 0) out vec4 output_color;
+
+// the distance to the camera of a depth buffer value
+float view_distance(float depth) {
+    return pcs.depth.y / (depth + pcs.depth.x);
+}
+
+void main() {
+    vec4 texel = texture(sampler2D(particle_texture, particle_sampler), particle_texel);
+
+    // straight textures are corrected after filtering, like sprites
+    if (pcs.straight != 0u) {
+        texel.rgb *= texel.a;
+    }
+
+    // fade out where the particle comes close to the geometry behind it
+    float fade = 1.0;
+    if (pcs.softness > 0.0) {
+#ifdef MULTISAMPLED
+        float scene = texelFetch(sampler2DMS(depth_texture, depth_sampler), ivec2(gl_FragCoord.xy), 0).r;
+#else
+        float scene = texelFetch(sampler2D(depth_texture, depth_sampler), ivec2(gl_FragCoord.xy), 0).r;
+#endif
+        float distance = view_distance(scene) - view_distance(gl_FragCoord.z);
+        fade = clamp(distance / pcs.softness, 0.0, 1.0);
+    }
+
+    output_color = texel * particle_color * fade;
+}
@@ -0,0 +1,40 @@
+#version 450
+
+layout(push_constant) uniform PushConstants {
+    mat4 view_proj;
+    vec4 right;
+    vec4 up;
+    vec2 depth;
+    float softness;
+    uint straight;
+} pcs;
+
+// position and rotation, size, tint
+layout(location = 0) in vec4 placement;
+layout(location = 1) in vec4 extent;
+layout(location = 2) in vec4 tint;
+
+layout(location = 0) out vec4 particle_color;
+layout(location = 1) out vec2 particle_texel;
+
+void main() {
+    // two triangles over the unit square
+    vec2 corners[6] = vec2[6](
+        vec2(0.0, 0.0), vec2(1.0, 0.0), vec2(0.0, 1.0),
+        vec2(0.0, 1.0), vec2(1.0, 0.0), vec2(1.0, 1.0)
+    );
+    vec2 corner = corners[gl_VertexIndex];
+
+    // scale around the center, then rotate in the view plane
+    vec2 local = (corner - vec2(0.5)) * extent.xy;
+    float s = sin(placement.w);
+    float c = cos(placement.w);
+    vec2 rotated = vec2(local.x * c - local.y * s, local.x * s + local.y * c);
+    vec3 position = placement.xyz + pcs.right.xyz * rotated.x + pcs.up.xyz * rotated.y;
+
+    // tints are given straight, blending expects them premultiplied
+    particle_color = vec4(tint.rgb * tint.a, tint.a);
+    particle_texel = vec2(corner.x, 1.0 - corner.y);
+
+    gl_Position = pcs.view_proj * vec4(position, 1.0);
+}
@@ -0,0 +1,12 @@
+#version 450
+
+layout(location = 0) in vec4 particle_color;
+layout(location = 1) in vec2 particle_corner;
+
+layout(location = 0) out vec4 output_color;
+
+void main() {
+    // a round spot fading out towards its edge
+    float falloff = clamp(1.0 - length(particle_corner), 0.0, 1.0);
+    output_color = particle_color * falloff * falloff;
+}
@@ -0,0 +1,53 @@
+#version 450
+
+layout(push_constant) uniform PushConstants {
+    mat4 view_proj;
+    vec4 right;
+    vec4 up;
+} pcs;
+
+layout(set = 3, binding = 0) uniform Emitter {
+    vec4 origin;
+    vec4 spread;
+    vec4 velocity_min;
+    vec4 velocity_max;
+    vec4 gravity;
+    vec4 color_start;
+    vec4 color_end;
+    vec4 size;
+    uvec4 counts;
+} emitter;
+
+struct Particle {
+    vec4 position;
+    vec4 velocity;
+};
+
+layout(set = 3, binding = 2) readonly buffer Particles {
+    Particle particles[];
+} system;
+
+layout(location = 0) out vec4 particle_color;
+layout(location = 1) out vec2 particle_corner;
+
+void main() {
+    // two triangles over the unit square
+    vec2 corners[6] = vec2[6](
+        vec2(0.0, 0.0), vec2(1.0, 0.0), vec2(0.0, 1.0),
+        vec2(0.0, 1.0), vec2(1.0, 0.0), vec2(1.0, 1.0)
+    );
+    vec2 corner = corners[gl_VertexIndex] * 2.0 - 1.0;
+
+    // size and color follow the age of the particle
+    Particle particle = system.particles[gl_InstanceIndex];
+    float t = clamp(particle.position.w / particle.velocity.w, 0.0, 1.0);
+    float size = mix(emitter.size.x, emitter.size.y, t);
+    vec4 color = mix(emitter.color_start, emitter.color_end, t);
+
+    // colors are given straight, blending expects them premultiplied
+    particle_color = vec4(color.rgb * color.a, color.a);
+    particle_corner = corner;
+
+    vec3 position = particle.position.xyz + (pcs.right.xyz * corner.x + pcs.up.xyz * corner.y) * size * 0.5;
+    gl_Position = pcs.view_proj * vec4(position, 1.0);
+}
@@ -0,0 +1,165 @@
+#version 450
+
+const uint MAX_LIGHTS = 8u;
+const float PI = 3.14159265;
+
+struct Light {
+    // xyz is the direction for directional lights, w is 0 for directional and 1 for point
+    // and spot lights
+    vec4 position;
+    // rgb color and intensity
+    vec4 color;
+    // x is the range of point and spot lights, 0 is unlimited, y and z the cosines of the
+    // outer and inner cone angle of spot lights
+    vec4 params;
+    // xyz is the way a spot light points, w is 1 for spot lights
+    vec4 direction;
+};
+
+layout(set = 0, binding = 0) uniform Frame {
+    mat4 view_proj;
+    vec4 camera_position;
+    vec4 ambient;
+    // x is the highest prefiltered environment level
+    vec4 environment;
+    uvec4 light_count;
+    Light lights[8];
+} frame;
+
+layout(set = 2, binding = 0) uniform texture2D base_color_texture;
+layout(set = 2, binding = 1) uniform texture2D metallic_roughness_texture;
+layout(set = 2, binding = 2) uniform texture2D normal_texture;
+layout(set = 2, binding = 3) uniform texture2D occlusion_texture;
+layout(set = 2, binding = 4) uniform texture2D emissive_texture;
+layout(set = 2, binding = 5) uniform sampler material_sampler;
+
+layout(set = 1, binding = 0) uniform textureCube irradiance_texture;
+layout(set = 1, binding = 1) uniform textureCube prefiltered_texture;
+layout(set = 1, binding = 2) uniform texture2D brdf_texture;
+layout(set = 1, binding = 3) uniform sampler environment_sampler;
+
+layout(push_constant) uniform PushConstants {
+    mat4 model;
+    vec4 base_color;
+    vec4 emissive;
+    // metallic, roughness, normal scale and occlusion strength
+    vec4 factors;
+} pcs;
+
+layout(location = 0) in vec3 surface_position;
+layout(location = 1) in vec3 surface_normal;
+layout(location = 2) in vec4 surface_tangent;
+layout(location = 3) in vec2 surface_texel;
+
+layout(location = 0) out vec4 output_color;
+
+vec4 sample_map(texture2D map) {
+    return texture(sampler2D(map, material_sampler), surface_texel);
+}
+
+float distribution_ggx(float n_dot_h, float alpha) {
+    float a2 = alpha * alpha;
+    float d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    return a2 / (PI * d * d);
+}
+
+float visibility_smith(float n_dot_v, float n_dot_l, float alpha) {
+    float a2 = alpha * alpha;
+    float gv = n_dot_l * sqrt(n_dot_v * n_dot_v * (1.0 - a2) + a2);
+    float gl = n_dot_v * sqrt(n_dot_l * n_dot_l * (1.0 - a2) + a2);
+    return 0.5 / max(gv + gl, 0.00001);
+}
+
+vec3 fresnel_schlick(float v_dot_h, vec3 f0) {
+    return f0 + (1.0 - f0) * pow(1.0 - v_dot_h, 5.0);
+}
+
+// the fresnel of a whole lobe, rough surfaces reflect less at grazing angles
+vec3 fresnel_schlick_roughness(float n_dot_v, vec3 f0, float roughness) {
+    return f0 + (max(vec3(1.0 - roughness), f0) - f0) * pow(1.0 - n_dot_v, 5.0);
+}
+
+void main() {
+    // material inputs, metallic is in blue and roughness in green as in gltf
+    vec4 base = sample_map(base_color_texture) * pcs.base_color;
+    vec4 packed = sample_map(metallic_roughness_texture);
+    float metallic = clamp(pcs.factors.x * packed.b, 0.0, 1.0);
+    float roughness = clamp(pcs.factors.y * packed.g, 0.04, 1.0);
+    float alpha = roughness * roughness;
+    float occlusion = 1.0 + pcs.factors.w * (sample_map(occlusion_texture).r - 1.0);
+    vec3 emission = sample_map(emissive_texture).rgb * pcs.emissive.rgb;
+
+    // perturb the interpolated normal, back faces of double sided surfaces face the viewer
+    vec3 n = normalize(surface_normal);
+    if (!gl_FrontFacing) {
+        n = -n;
+    }
+    vec3 t = normalize(surface_tangent.xyz - n * dot(n, surface_tangent.xyz));
+    vec3 b = cross(n, t) * surface_tangent.w;
+    vec3 mapped = sample_map(normal_texture).xyz * 2.0 - 1.0;
+    mapped.xy *= pcs.factors.z;
+    n = normalize(mat3(t, b, n) * mapped);
+
+    vec3 v = normalize(frame.camera_position.xyz - surface_position);
+    float n_dot_v = max(dot(n, v), 0.0001);
+
+    vec3 f0 = mix(vec3(0.04), base.rgb, metallic);
+    vec3 diffuse_color = base.rgb * (1.0 - metallic);
+
+    vec3 color = vec3(0.0);
+    for (uint i = 0u; i < min(frame.light_count.x, MAX_LIGHTS); i++) {
+        Light light = frame.lights[i];
+
+        // direction towards the light and its falloff
+        vec3 l = -normalize(light.position.xyz);
+        float attenuation = 1.0;
+        if (light.position.w > 0.5) {
+            vec3 to_light = light.position.xyz - surface_position;
+            float dist = length(to_light);
+            l = to_light / max(dist, 0.0001);
+            attenuation = 1.0 / max(dist * dist, 0.0001);
+
+            // fade out smoothly at the range
+            if (light.params.x > 0.0) {
+                float ratio = dist / light.params.x;
+                float window = clamp(1.0 - ratio * ratio * ratio * ratio, 0.0, 1.0);
+                attenuation *= window * window;
+            }
+        }
+
+        // fade from the inner to the outer cone of spot lights
+        if (light.direction.w > 0.5) {
+            attenuation *= smoothstep(light.params.y, light.params.z, dot(-l, light.direction.xyz));
+        }
+
+        float n_dot_l = dot(n, l);
+        if (n_dot_l <= 0.0) {
+            continue;
+        }
+
+        vec3 h = normalize(l + v);
+        float n_dot_h = max(dot(n, h), 0.0);
+        float v_dot_h = max(dot(v, h), 0.0);
+
+        vec3 fresnel = fresnel_schlick(v_dot_h, f0);
+        vec3 specular = fresnel * distribution_ggx(n_dot_h, alpha)
+            * visibility_smith(n_dot_v, n_dot_l, alpha);
+        vec3 diffuse = (1.0 - fresnel) * diffuse_color / PI;
+
+        vec3 radiance = light.color.rgb * light.color.w * attenuation;
+        color += (diffuse + specular) * radiance * n_dot_l;
+    }
+
+    // the environment with the split sum approximation, occluded by the baked occlusion
+    vec3 r = reflect(-v, n);
+    vec3 irradiance = texture(samplerCube(irradiance_texture, environment_sampler), n).rgb;
+    float level = roughness * frame.environment.x;
+    vec3 prefiltered =
+        textureLod(samplerCube(prefiltered_texture, environment_sampler), r, level).rgb;
+    vec2 brdf = texture(sampler2D(brdf_texture, environment_sampler), vec2(n_dot_v, roughness)).rg;
+    vec3 lobe = fresnel_schlick_roughness(n_dot_v, f0, roughness);
+    vec3 ambient = (1.0 - lobe) * diffuse_color * irradiance + prefiltered * (f0 * brdf.x + brdf.y);
+    color += ambient * frame.ambient.rgb * frame.ambient.w * occlusion;
+
+    output_color = vec4(color + emission, base.a);
+}
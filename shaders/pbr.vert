@@ -0,0 +1,49 @@
+#version 450
+
+struct Light {
+    vec4 position;
+    vec4 color;
+    vec4 params;
+    vec4 direction;
+};
+
+layout(set = 0, binding = 0) uniform Frame {
+    mat4 view_proj;
+    vec4 camera_position;
+    vec4 ambient;
+    vec4 environment;
+    uvec4 light_count;
+    Light lights[8];
+} frame;
+
+layout(push_constant) uniform PushConstants {
+    mat4 model;
+    vec4 base_color;
+    vec4 emissive;
+    vec4 factors;
+} pcs;
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec4 tangent;
+layout(location = 3) in vec2 texel;
+
+layout(location = 0) out vec3 surface_position;
+layout(location = 1) out vec3 surface_normal;
+layout(location = 2) out vec4 surface_tangent;
+layout(location = 3) out vec2 surface_texel;
+
+void main() {
+    vec4 world = pcs.model * vec4(position, 1.0);
+
+    // the cofactor matrix keeps normals perpendicular under non uniform scale
+    mat3 m = mat3(pcs.model);
+    mat3 cofactor = mat3(cross(m[1], m[2]), cross(m[2], m[0]), cross(m[0], m[1]));
+
+    surface_position = world.xyz;
+    surface_normal = cofactor * normal;
+    surface_tangent = vec4(m * tangent.xyz, tangent.w);
+    surface_texel = texel;
+
+    gl_Position = frame.view_proj * world;
+}
@@ -0,0 +1,26 @@
+#version 450
+
+layout(push_constant) uniform PushConstants {
+    mat4 view_proj;
+    uint straight;
+} pcs;
+
+layout(set = 2, binding = 0) uniform texture2D sprite_texture;
+layout(set = 2, binding = 1) uniform sampler sprite_sampler;
+
+layout(location = 0) in vec4 sprite_color;
+layout(location = 1) in vec2 sprite_texel;
+
+layout(location = 0) out vec4 output_color;
+
+void main() {
+    vec4 texel = texture(sampler2D(sprite_texture, sprite_sampler), sprite_texel);
+
+    // straight textures are corrected after filtering, this keeps blending right
+    // but filtered edges still pick up the color of transparent neighbours
+    if (pcs.straight != 0u) {
+        texel.rgb *= texel.a;
+    }
+
+    output_color = texel * sprite_color;
+}
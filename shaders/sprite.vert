@@ -0,0 +1,36 @@
+#version 450
+
+layout(push_constant) uniform PushConstants {
+    mat4 view_proj;
+    uint straight;
+} pcs;
+
+// position and rotation, size and pivot, texel rectangle, tint
+layout(location = 0) in vec4 placement;
+layout(location = 1) in vec4 extent;
+layout(location = 2) in vec4 texels;
+layout(location = 3) in vec4 tint;
+
+layout(location = 0) out vec4 sprite_color;
+layout(location = 1) out vec2 sprite_texel;
+
+void main() {
+    // two triangles over the unit square
+    vec2 corners[6] = vec2[6](
+        vec2(0.0, 0.0), vec2(1.0, 0.0), vec2(0.0, 1.0),
+        vec2(0.0, 1.0), vec2(1.0, 0.0), vec2(1.0, 1.0)
+    );
+    vec2 corner = corners[gl_VertexIndex];
+
+    // scale around the pivot, then rotate
+    vec2 local = (corner - extent.zw) * extent.xy;
+    float s = sin(placement.w);
+    float c = cos(placement.w);
+    vec2 rotated = vec2(local.x * c - local.y * s, local.x * s + local.y * c);
+
+    // tints are given straight, blending expects them premultiplied
+    sprite_color = vec4(tint.rgb * tint.a, tint.a);
+    sprite_texel = mix(texels.xy, texels.zw, vec2(corner.x, 1.0 - corner.y));
+
+    gl_Position = pcs.view_proj * vec4(placement.xy + rotated, placement.z, 1.0);
+}
@@ -0,0 +1,22 @@
+#version 450
+
+layout(set = 1, binding = 0) uniform texture2D input_texture;
+layout(set = 1, binding = 3) uniform sampler input_sampler;
+layout(set = 1, binding = 1) readonly buffer Exposure {
+    float luminance;
+    float exposure;
+} state;
+
+layout(location = 0) in vec2 screen_texel;
+
+layout(location = 0) out vec4 output_color;
+
+// narkowicz aces filmic fit
+vec3 aces(vec3 x) {
+    return clamp((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14), 0.0, 1.0);
+}
+
+void main() {
+    vec3 color = texture(sampler2D(input_texture, input_sampler), screen_texel).rgb;
+    output_color = vec4(aces(color * state.exposure), 1.0);
+}
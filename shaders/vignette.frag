@@ -0,0 +1,26 @@
+#version 450
+
+layout(set = 1, binding = 0) uniform texture2D input_texture;
+layout(set = 1, binding = 3) uniform sampler input_sampler;
+
+layout(push_constant) uniform PushConstants {
+    vec3 color;
+    float intensity;
+    float radius;
+    float smoothness;
+} pcs;
+
+layout(location = 0) in vec2 screen_texel;
+
+layout(location = 0) out vec4 output_color;
+
+void main() {
+    vec3 color = texture(sampler2D(input_texture, input_sampler), screen_texel).rgb;
+
+    // distance from the center, one at the corners
+    vec2 offset = (screen_texel - 0.5) * 2.0;
+    float reach = length(offset) / sqrt(2.0);
+
+    float amount = smoothstep(pcs.radius, pcs.radius + pcs.smoothness, reach) * pcs.intensity;
+    output_color = vec4(mix(color, pcs.color, clamp(amount, 0.0, 1.0)), 1.0);
+}
@@ -8,31 +8,456 @@
     clippy::unnecessary_wraps
 )]
 
-use crate::gfx;
 use anyhow::Result;
+use cgmath::{vec2, vec3, vec4, Deg, Quaternion, Rad, Rotation3};
+use deimos::gfx;
+use deimos::input::{GamepadButton, Input, Key};
+use deimos::math::{Mat4, Vec3};
+use deimos::rendering::{
+    AnimationChannel, AnimationClip, AnimationPlayer, AnimationValues, BloomPass, BloomSettings,
+    BodyPose, Camera, Clock, Curve, Environment, ExposureSettings, Follow, FxaaPass, Interpolation,
+    Light, LineCap, LineDash, LineJoin, LinePoint, LineRenderer, LineStyle, LookAt, Material, Mesh,
+    MeshId, Particle, ParticleBlend, ParticleEmitter, ParticleMaterial, ParticleRenderer,
+    ParticleSystem, PbrMaterial, PbrRenderer, PoseBatch, PostChain, Primitive, RenderTarget,
+    Renderer, SceneGraph, Shake, Sprite, SpriteRenderer, SpriteTexture, SyncHandle, TimeChannel,
+    ToneMapPass, Transform, TransformSync, VignettePass,
+};
+use deimos::window::{
+    CursorMode, DisplayController, DisplaySettings, WindowController, WindowMode,
+};
 use winit::window::Window;
 
 /// the app.
 pub struct App {
     pub graphics: gfx::Device,
+    pub lines: LineRenderer,
+    pub renderer: Renderer,
+    pub quad: MeshId,
+    pub pbr: PbrRenderer,
+    pub sphere: Mesh,
+    pub sky: Environment,
+    pub monitor: RenderTarget,
+    pub monitor_pbr: PbrRenderer,
+    pub screen: Mesh,
+    pub scene: SceneGraph,
+    pub animation: AnimationPlayer,
+    pub sprites: SpriteRenderer,
+    pub dot: SpriteTexture,
+    pub particles: ParticleRenderer,
+    pub sparks: ParticleSystem,
+    pub camera: CameraData,
+    pub physics: TransformSync,
+    pub ball: Ball,
+    pub post: PostChain,
     pub data: AppData,
+    pub clock: Clock,
+    pub display: DisplayController,
+    pub window: WindowController,
 }
 
 impl App {
     /// Creates the app.
     pub unsafe fn create(window: &Window) -> Result<Self> {
-        // create graphics
-        let graphics = gfx::Device::create(window, "D E I M O S")?;
+        // create graphics on the fastest gpu, keeping bug reports of validation errors and
+        // device loss
+        let adapter = gfx::AdapterSelection::Power(gfx::PowerPreference::HighPerformance);
+        let mut graphics = gfx::Device::create_with_adapter(window, "D E I M O S", adapter)?;
+        graphics.set_bug_reports(Some(gfx::BugReportSettings::default()))?;
+
+        // create line renderer
+        let lines = LineRenderer::create(&graphics)?;
+
+        // create mesh renderer and the demo mesh
+        let renderer = Renderer::create(&graphics)?;
+        let quad = create_quad(&graphics)?.register(&graphics);
+
+        // create the lit renderer and the demo sphere
+        let mut pbr = PbrRenderer::create(&graphics)?;
+        let sphere = Primitive::sphere(1.0, 48, 24).upload(&graphics)?;
+
+        // light the spheres with a simple sky
+        let sky = create_sky(&graphics, 256, 128)?;
+        pbr.set_environment(Some(&sky));
+        pbr.ambient = vec3(0.4, 0.4, 0.4);
+
+        // a screen showing the spheres from above, drawn by a renderer of its own
+        let monitor = RenderTarget::create(
+            &graphics,
+            "monitor",
+            256,
+            256,
+            Camera::looking_at(
+                vec3(0.0, 3.0, 0.01),
+                vec3(0.0, -0.6, 0.0),
+                vec3(0.0, 1.0, 0.0),
+            ),
+        )?;
+        let mut monitor_pbr = PbrRenderer::create(&graphics)?;
+        monitor_pbr.set_environment(Some(&sky));
+        monitor_pbr.ambient = pbr.ambient;
+        let screen = create_screen(&graphics)?;
+
+        // a moon circling above the spheres, alternating between two clips
+        let (mut scene, animation) = create_orbit();
+
+        // a ball bouncing on fixed physics ticks, synced into the scene
+        let (physics, ball) = create_ball(&mut scene);
+
+        // create sprite renderer and the demo sprite
+        let sprites = SpriteRenderer::create(&graphics)?;
+        let dot = create_dot(&graphics, 64)?;
+
+        // create the particle renderer, particles reuse the dot
+        let particles = ParticleRenderer::create(&graphics)?;
+
+        // a fountain of sparks simulated on the gpu
+        let sparks = ParticleSystem::create(&graphics, create_fountain(), 2048)?;
+
+        // create post processing, bloom works on hdr colors and so runs before tone mapping,
+        // the others expect display colors
+        let mut post = PostChain::create(&graphics)?;
+        post.add(
+            &graphics,
+            BloomPass::create(&graphics, BloomSettings::default())?,
+        )?;
+        post.add(
+            &graphics,
+            ToneMapPass::create(&graphics, ExposureSettings::default())?,
+        )?;
+        post.add(&graphics, FxaaPass::default())?;
+        post.add(&graphics, VignettePass::default())?;
+
+        // a camera drifting in front of the scene, shaken whenever the moon changes clips
+        let camera = CameraData::default();
 
         // init data
         let data = AppData::default();
 
         // init app instance
-        Ok(Self { graphics, data })
+        Ok(Self {
+            graphics,
+            lines,
+            renderer,
+            quad,
+            pbr,
+            sphere,
+            sky,
+            monitor,
+            monitor_pbr,
+            screen,
+            scene,
+            animation,
+            sprites,
+            dot,
+            particles,
+            sparks,
+            camera,
+            physics,
+            ball,
+            post,
+            data,
+            clock: Clock::new(),
+            display: DisplayController::new(),
+            window: WindowController::new(),
+        })
     }
 
     /// update s a frame for the app.
-    pub unsafe fn update(&mut self, window: &Window) -> Result<()> {
+    pub unsafe fn update(&mut self, window: &Window, input: &Input) -> Result<()> {
+        // the scene runs on game time, the camera and post effects on real time
+        self.clock.tick();
+        let time = self.clock.elapsed(TimeChannel::Game);
+        let delta = self.clock.delta(TimeChannel::Game);
+        let real = self.clock.time(TimeChannel::Ui);
+
+        // p pauses, t toggles slow motion
+        if input.key_pressed(Key::P) || input.any_gamepad_button_pressed(GamepadButton::Start) {
+            self.clock.toggle_pause();
+        }
+        if input.key_pressed(Key::T) {
+            self.clock.scale = if self.clock.scale < 1.0 { 1.0 } else { 0.25 };
+        }
+
+        // f11 toggles borderless fullscreen, alt enter exclusive fullscreen
+        if input.key_pressed(Key::F11) {
+            self.display.toggle_fullscreen(window, &mut self.graphics)?;
+        }
+        let alt = input.is_key_down(Key::LAlt) || input.is_key_down(Key::RAlt);
+        if alt && input.key_pressed(Key::Return) {
+            let settings = match self.display.settings().mode {
+                WindowMode::Exclusive => DisplaySettings::windowed(),
+                _ => DisplaySettings::exclusive(None),
+            };
+            self.display.apply(window, &mut self.graphics, settings)?;
+        }
+
+        // m locks the cursor for mouse look, released while another window has the focus
+        if input.key_pressed(Key::M) {
+            let mode = match self.window.cursor_mode() {
+                CursorMode::Locked => CursorMode::Normal,
+                _ => CursorMode::Locked,
+            };
+            self.window.set_cursor_mode(window, mode)?;
+        }
+        self.window.follow_focus(window, input.is_focused())?;
+
+        // left and right change the number of models
+        let less = input.key_pressed(Key::Left)
+            || input.any_gamepad_button_pressed(GamepadButton::DPadLeft);
+        let more = input.key_pressed(Key::Right)
+            || input.any_gamepad_button_pressed(GamepadButton::DPadRight);
+        if less && self.data.models > 1 {
+            self.data.models -= 1;
+        }
+        if more && self.data.models < 4 {
+            self.data.models += 1;
+        }
+
+        // space gives the camera a knock
+        if input.key_pressed(Key::Space) || input.any_gamepad_button_pressed(GamepadButton::South) {
+            self.camera.shake.add_trauma(0.4);
+        }
+
+        // one wave per model
+        for model in 0..self.data.models {
+            let offset = model as f32 * 0.5 - 0.75;
+            let wave = (0..64)
+                .map(|i| {
+                    let x = i as f32 / 63.0 * 4.0 - 2.0;
+                    LinePoint::new(
+                        vec3(x, offset + (x * 3.0 + time).sin() * 0.2, 0.0),
+                        2.0 + 6.0 * (i as f32 / 63.0),
+                        vec4(1.0, 0.6, 0.1, 1.0),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            self.lines.strip(
+                &wave,
+                &LineStyle {
+                    join: LineJoin::Round,
+                    cap: LineCap::Round,
+                    ..Default::default()
+                },
+            );
+        }
+
+        // dashed frame around the waves
+        let corners = [(-2.2, -1.2), (2.2, -1.2), (2.2, 1.2), (-2.2, 1.2)]
+            .iter()
+            .map(|(x, y)| LinePoint::new(vec3(*x, *y, 0.0), 3.0, vec4(0.3, 0.7, 1.0, 1.0)))
+            .collect::<Vec<_>>();
+
+        self.lines.ring(
+            &corners,
+            &LineStyle {
+                dash: Some(LineDash {
+                    length: 0.2,
+                    gap: 0.1,
+                    offset: time * 0.1,
+                }),
+                ..Default::default()
+            },
+        );
+
+        // a catmull-rom path through moving points
+        let path = Curve::catmull_rom(
+            (0..6)
+                .map(|i| {
+                    let x = i as f32 * 0.8 - 2.0;
+                    vec3(x, 0.8 + (time + i as f32).cos() * 0.2, 0.0)
+                })
+                .collect(),
+        );
+        self.lines.curve(
+            &path,
+            0.005,
+            4.0,
+            vec4(0.9, 0.9, 0.9, 1.0),
+            &LineStyle {
+                join: LineJoin::Round,
+                ..Default::default()
+            },
+        );
+
+        // an opaque backdrop with overlapping transparent panes in front
+        self.renderer.draw(
+            &self.quad,
+            &Material::opaque(vec4(0.2, 0.2, 0.25, 1.0)),
+            Mat4::from_translation(vec3(0.0, 0.0, -1.0)) * Mat4::from_scale(2.5),
+        );
+        for (i, color) in [
+            vec4(1.0, 0.2, 0.2, 1.0),
+            vec4(0.2, 1.0, 0.2, 1.0),
+            vec4(0.2, 0.2, 1.0, 1.0),
+        ]
+        .iter()
+        .enumerate()
+        {
+            let x = (time * 0.5 + i as f32 * 2.1).sin() * 0.6;
+            self.renderer.draw(
+                &self.quad,
+                &Material::transparent(*color, 0.5),
+                Mat4::from_translation(vec3(x, 0.0, i as f32 * 0.3 - 0.5)) * Mat4::from_scale(0.8),
+            );
+        }
+
+        // a row of spheres from rough dielectric to polished metal
+        self.pbr.lights = vec![
+            Light::Directional {
+                direction: vec3(-0.4, -1.0, -0.6),
+                color: vec3(1.0, 0.95, 0.9),
+                intensity: 2.0,
+            },
+            Light::Point {
+                position: vec3(time.cos() * 2.0, 0.5, 1.5),
+                color: vec3(0.4, 0.6, 1.0),
+                intensity: 4.0,
+                range: 6.0,
+            },
+            Light::Spot {
+                position: vec3(0.0, 1.5, 0.8),
+                direction: vec3(0.0, -1.0, -0.3),
+                color: vec3(1.0, 0.8, 0.5),
+                intensity: 6.0,
+                range: 5.0,
+                inner_angle: Deg(15.0).into(),
+                outer_angle: Deg(30.0).into(),
+            },
+        ];
+        self.monitor_pbr.lights = self.pbr.lights.clone();
+        for i in 0..5 {
+            let t = i as f32 / 4.0;
+            let material = PbrMaterial::new(vec4(0.9, 0.6, 0.3, 1.0), t, 1.0 - t * 0.8);
+            let transform =
+                Mat4::from_translation(vec3(t * 3.2 - 1.6, -0.6, 0.2)) * Mat4::from_scale(0.3);
+            self.pbr.draw(&self.sphere, &material, transform);
+            self.monitor_pbr.draw(&self.sphere, &material, transform);
+        }
+
+        // the screen shows the spheres as the monitor camera sees them
+        self.pbr.draw(
+            &self.screen,
+            &PbrMaterial {
+                emissive: vec3(1.0, 1.0, 1.0),
+                ..PbrMaterial::new(vec4(0.0, 0.0, 0.0, 1.0), 0.0, 0.3)
+            }
+            .with_render_target(&self.monitor),
+            Mat4::from_translation(vec3(1.7, 0.9, -0.5)) * Mat4::from_scale(0.7),
+        );
+
+        // the moon follows whichever clip is fading in
+        let clip = (time / 4.0) as usize % self.animation.clips.len();
+        if clip != self.data.clip {
+            self.data.clip = clip;
+            self.animation.crossfade(clip, 1.0);
+            self.camera.shake.add_trauma(0.5);
+        }
+        self.animation.tick(&self.clock);
+        self.animation.apply(&mut self.scene);
+
+        // the ball steps on fixed ticks and is drawn between the last two
+        let ball = &mut self.ball;
+        self.physics
+            .update(delta, |step, poses| ball.step(step, poses));
+        self.physics.write(&mut self.scene);
+
+        let worlds = self.scene.world_matrices();
+        if let Some(moon) = self.scene.find("moon") {
+            self.pbr.draw(
+                &self.sphere,
+                &PbrMaterial::new(vec4(0.8, 0.8, 0.85, 1.0), 0.0, 0.6),
+                worlds[moon],
+            );
+        }
+        self.pbr.draw(
+            &self.sphere,
+            &PbrMaterial::new(vec4(0.2, 0.5, 0.9, 1.0), 0.0, 0.3),
+            worlds[self.physics.node(self.ball.handle)],
+        );
+
+        // soft dots circling in front of the panes
+        for i in 0..8 {
+            let angle = real.elapsed * 0.4 + i as f32 * std::f32::consts::TAU / 8.0;
+            let sprite = Sprite::new(
+                vec3(angle.cos() * 1.6, angle.sin() * 0.9, 0.5),
+                vec2(0.4, 0.4),
+            )
+            .tinted(vec4(1.0, 0.9, 0.6, 0.8));
+            self.sprites.draw(&self.dot, &sprite);
+        }
+
+        // puffs drifting through the backdrop, fading where they cut into it
+        let puff = ParticleMaterial::new(&self.dot, 0.3);
+        for i in 0..6 {
+            let phase = time * 0.3 + i as f32 * 1.1;
+            let particle = Particle::new(
+                vec3(phase.sin() * 1.8, (phase * 0.7).cos() * 0.8, -1.0),
+                0.9,
+            )
+            .rotated(phase)
+            .tinted(vec4(0.7, 0.8, 1.0, 0.5));
+            self.particles.draw(&puff, &particle);
+        }
+
+        // camera looking at the origin
+        let extent = self.graphics.extent();
+        let camera = &mut self.camera;
+        camera.follow.target.translation = vec3((time * 0.3).sin() * 0.8, 0.3, 4.0);
+        let (view, proj) = camera
+            .base
+            .modified(
+                &mut [&mut camera.follow, &mut camera.look, &mut camera.shake],
+                real.delta,
+            )
+            .matrices(extent.width, extent.height);
+        let view_proj = proj * view;
+
+        // bug reports list the post passes that ran
+        self.graphics.set_report_passes(self.post.names());
+
+        // the sparks step as far as the game
+        self.sparks.tick(&self.graphics, &self.clock)?;
+
+        // render the frame, meshes first so lines and sprites draw over them
+        let lines = &mut self.lines;
+        let renderer = &mut self.renderer;
+        let pbr = &mut self.pbr;
+        let sprites = &mut self.sprites;
+        let particles = &mut self.particles;
+        let sparks = &self.sparks;
+        let monitor = &self.monitor;
+        let monitor_pbr = &mut self.monitor_pbr;
+        let post = &mut self.post;
+        self.graphics.update_with_prepare(
+            window,
+            |device, command_buffer, index| {
+                sparks.simulate(device, command_buffer, index)?;
+
+                // the monitor is drawn before the scene samples it
+                monitor.record(
+                    device,
+                    command_buffer,
+                    index,
+                    |device, command_buffer, index, view, proj| {
+                        monitor_pbr.record(device, command_buffer, index, view, proj)
+                    },
+                )
+            },
+            |device, command_buffer, index| {
+                renderer.record(device, command_buffer, index, view, proj)?;
+                pbr.record(device, command_buffer, index, view, proj)?;
+                lines.record(device, command_buffer, index, view_proj)?;
+                sprites.record(device, command_buffer, index, view_proj)?;
+                sparks.record(device, command_buffer, index, view, proj)?;
+
+                // particles sample the depth of everything before
+                device.begin_transparent_pass(command_buffer);
+                particles.record(device, command_buffer, index, view, proj)
+            },
+            |device, command_buffer, index| post.record(device, command_buffer, index, real.delta),
+        )?;
+
         // all went fine
         Ok(())
     }
@@ -40,6 +465,37 @@ impl App {
     /// Destroys the app.
     #[rustfmt::skip]
     pub unsafe fn destroy(&self) {  
+        // wait for pending frames before releasing anything
+        self.graphics.wait_idle().unwrap();
+
+        // destroy line renderer
+        self.lines.destroy(&self.graphics);
+
+        // destroy mesh renderer and demo mesh
+        self.quad.destroy(&self.graphics).unwrap();
+        self.renderer.destroy(&self.graphics);
+
+        // destroy lit renderer and demo sphere
+        self.sphere.destroy(&self.graphics);
+        self.pbr.destroy(&self.graphics);
+        self.sky.destroy(&self.graphics);
+
+        // destroy the monitor and its renderer
+        self.monitor.destroy(&self.graphics);
+        self.monitor_pbr.destroy(&self.graphics);
+        self.screen.destroy(&self.graphics);
+
+        // destroy sprite renderer and demo sprite
+        self.dot.destroy(&self.graphics);
+        self.sprites.destroy(&self.graphics);
+
+        // destroy particle renderer
+        self.particles.destroy(&self.graphics);
+        self.sparks.destroy(&self.graphics);
+
+        // destroy post processing
+        self.post.destroy(&self.graphics);
+
         // destroy graphics
         self.graphics.destroy();
     }
@@ -49,10 +505,275 @@ impl App {
 #[derive(Clone, Debug)]
 pub struct AppData {
     pub models: usize,
+    pub clip: usize,
 }
 
 impl Default for AppData {
     fn default() -> Self {
-        AppData { models: 1 }
+        AppData { models: 1, clip: 0 }
+    }
+}
+
+/// the demo camera and the modifiers applied to it each frame.
+pub struct CameraData {
+    pub base: Camera,
+    pub follow: Follow,
+    pub look: LookAt,
+    pub shake: Shake,
+}
+
+impl Default for CameraData {
+    fn default() -> Self {
+        CameraData {
+            base: Camera {
+                far: 10.0,
+                ..Default::default()
+            },
+            follow: Follow::new(Transform::default(), vec3(0.0, 0.0, 0.0), 0.5),
+            look: LookAt::new(vec3(0.0, 0.0, 0.0)),
+            shake: Shake::new(0.05, 0.02),
+        }
+    }
+}
+
+/// a ball thrown between two walls, standing in for a physics engine.
+pub struct Ball {
+    pub handle: SyncHandle,
+    pub velocity: Vec3,
+}
+
+impl Ball {
+    const GRAVITY: f32 = 9.81;
+    const RADIUS: f32 = 0.12;
+    const FLOOR: f32 = -1.0;
+    const WALL: f32 = 1.9;
+
+    /// one physics tick of `delta` seconds.
+    fn step(&mut self, delta: f32, poses: &mut PoseBatch) {
+        let pose = poses.get(self.handle);
+        self.velocity.y -= Self::GRAVITY * delta;
+        let mut position = pose.translation + self.velocity * delta;
+
+        // bounce off the floor and the walls, losing a little each time
+        if position.y < Self::FLOOR + Self::RADIUS {
+            position.y = Self::FLOOR + Self::RADIUS;
+            self.velocity.y = self.velocity.y.abs() * 0.8;
+
+            // throw it again once it has settled
+            if self.velocity.y < 0.5 {
+                self.velocity.y = 5.0;
+            }
+        }
+        if position.x.abs() > Self::WALL - Self::RADIUS {
+            position.x = (Self::WALL - Self::RADIUS) * position.x.signum();
+            self.velocity.x = -self.velocity.x;
+        }
+
+        // roll with the horizontal motion
+        let spin = Quaternion::from_angle_z(Rad(-self.velocity.x * delta / Self::RADIUS));
+        poses.set(self.handle, BodyPose::new(position, spin * pose.rotation));
+    }
+}
+
+/// bind the ball to a new node of the scene, ticking at 30 hz.
+fn create_ball(scene: &mut SceneGraph) -> (TransformSync, Ball) {
+    let node = scene.add(
+        "ball",
+        None,
+        Transform {
+            translation: vec3(-1.0, 0.5, 0.4),
+            scale: vec3(Ball::RADIUS, Ball::RADIUS, Ball::RADIUS),
+            ..Default::default()
+        },
+    );
+
+    let mut physics = TransformSync::new(1.0 / 30.0);
+    let handle = physics.bind(scene, node);
+    let ball = Ball {
+        handle,
+        velocity: vec3(1.3, 0.0, 0.0),
+    };
+
+    (physics, ball)
+}
+
+/// a unit quad in the xy plane facing the camera for lit materials, textures read upright.
+fn create_screen(device: &gfx::Device) -> Result<Mesh> {
+    let normal = vec3(0.0, 0.0, 1.0);
+    let tangent = vec4(1.0, 0.0, 0.0, 1.0);
+    let vertices = [
+        gfx::SurfaceVertex::new(vec3(-0.5, -0.5, 0.0), normal, tangent, vec2(0.0, 1.0)),
+        gfx::SurfaceVertex::new(vec3(0.5, -0.5, 0.0), normal, tangent, vec2(1.0, 1.0)),
+        gfx::SurfaceVertex::new(vec3(0.5, 0.5, 0.0), normal, tangent, vec2(1.0, 0.0)),
+        gfx::SurfaceVertex::new(vec3(-0.5, 0.5, 0.0), normal, tangent, vec2(0.0, 0.0)),
+    ];
+    let indices = [0, 1, 2, 2, 3, 0];
+
+    Mesh::create_surface(device, &vertices, &indices)
+}
+
+/// a unit quad in the xy plane facing the camera.
+fn create_quad(device: &gfx::Device) -> Result<Mesh> {
+    let white = vec3(1.0, 1.0, 1.0);
+    let vertices = [
+        gfx::Vertex::new(vec3(-0.5, -0.5, 0.0), vec2(0.0, 0.0), white),
+        gfx::Vertex::new(vec3(0.5, -0.5, 0.0), vec2(1.0, 0.0), white),
+        gfx::Vertex::new(vec3(0.5, 0.5, 0.0), vec2(1.0, 1.0), white),
+        gfx::Vertex::new(vec3(-0.5, 0.5, 0.0), vec2(0.0, 1.0), white),
+    ];
+    let indices = [0, 1, 2, 2, 3, 0];
+
+    Mesh::create(device, &vertices, &indices)
+}
+
+/// warm sparks thrown up from below the spheres, falling back down.
+fn create_fountain() -> ParticleEmitter {
+    ParticleEmitter {
+        position: vec3(0.0, -1.0, 0.5),
+        spread: vec3(0.1, 0.0, 0.1),
+        rate: 300.0,
+        lifetime_min: 1.0,
+        lifetime_max: 2.5,
+        velocity_min: vec3(-0.6, 2.0, -0.6),
+        velocity_max: vec3(0.6, 3.0, 0.6),
+        gravity: vec3(0.0, -3.0, 0.0),
+        drag: 0.2,
+        size_start: 0.08,
+        size_end: 0.02,
+        color_start: vec4(4.0, 2.0, 0.8, 1.0),
+        color_end: vec4(1.0, 0.2, 0.05, 0.0),
+        blend: ParticleBlend::Additive,
     }
 }
+
+/// a white dot fading out towards its edge, stored as straight alpha like an image file.
+fn create_dot(device: &gfx::Device, size: u32) -> Result<SpriteTexture> {
+    let pixels = (0..size * size)
+        .flat_map(|i| {
+            let x = (i % size) as f32 / (size - 1) as f32 * 2.0 - 1.0;
+            let y = (i / size) as f32 / (size - 1) as f32 * 2.0 - 1.0;
+            let alpha = (1.0 - (x * x + y * y).sqrt()).clamp(0.0, 1.0);
+            [255, 255, 255, (alpha * 255.0) as u8]
+        })
+        .collect();
+
+    SpriteTexture::create(device, size, size, pixels, gfx::AlphaMode::Premultiplied)
+}
+
+/// an equirectangular sky, blue above a bright horizon, a dark ground and a sun.
+fn create_sky(device: &gfx::Device, width: u32, height: u32) -> Result<Environment> {
+    let sun = vec3(0.4f32, 0.6, 0.7);
+    let sun = sun / (sun.x * sun.x + sun.y * sun.y + sun.z * sun.z).sqrt();
+
+    let pixels = (0..width * height)
+        .flat_map(|i| {
+            // the direction through the texel, y is up
+            let longitude = ((i % width) as f32 + 0.5) / width as f32 - 0.5;
+            let latitude = ((i / width) as f32 + 0.5) / height as f32;
+            let phi = longitude * std::f32::consts::TAU;
+            let theta = latitude * std::f32::consts::PI;
+            let direction = vec3(
+                theta.sin() * phi.cos(),
+                theta.cos(),
+                theta.sin() * phi.sin(),
+            );
+
+            // fade from horizon to zenith above and to the ground below
+            let up = direction.y;
+            let mut color = if up >= 0.0 {
+                vec3(0.85, 0.9, 1.0) * (1.0 - up) + vec3(0.25, 0.45, 0.85) * up
+            } else {
+                vec3(0.3, 0.27, 0.24)
+            };
+
+            // a small bright disk
+            let facing = direction.x * sun.x + direction.y * sun.y + direction.z * sun.z;
+            if facing > 0.995 {
+                color = vec3(1.0, 0.95, 0.85);
+            }
+
+            [
+                (color.x * 255.0) as u8,
+                (color.y * 255.0) as u8,
+                (color.z * 255.0) as u8,
+                255,
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    Environment::create(device, width, height, &pixels)
+}
+
+/// a pivot with a moon as its child, an orbiting and a bouncing clip drive them.
+fn create_orbit() -> (SceneGraph, AnimationPlayer) {
+    let mut scene = SceneGraph::new();
+    let pivot = scene.add(
+        "pivot",
+        None,
+        Transform {
+            translation: vec3(0.0, 0.5, 0.0),
+            ..Default::default()
+        },
+    );
+    let moon = scene.add(
+        "moon",
+        Some(pivot),
+        Transform {
+            translation: vec3(1.2, 0.0, 0.0),
+            scale: vec3(0.15, 0.15, 0.15),
+            ..Default::default()
+        },
+    );
+
+    // a full turn of the pivot around y
+    let turns = (0..5)
+        .map(|i| Quaternion::from_angle_y(Deg(i as f32 * 90.0)))
+        .collect();
+    let orbit = AnimationClip::new(
+        "orbit",
+        vec![AnimationChannel {
+            node: pivot,
+            interpolation: Interpolation::Linear,
+            times: vec![0.0, 1.0, 2.0, 3.0, 4.0],
+            values: AnimationValues::Rotation(turns),
+        }],
+    );
+
+    // the moon hops up and down with eased tangents, squashing on each landing
+    let flat = vec3(0.0, 0.0, 0.0);
+    let bounce = AnimationClip::new(
+        "bounce",
+        vec![
+            AnimationChannel {
+                node: moon,
+                interpolation: Interpolation::CubicSpline,
+                times: vec![0.0, 0.5, 1.0],
+                values: AnimationValues::Translation(vec![
+                    flat,
+                    vec3(1.2, 0.0, 0.0),
+                    vec3(0.0, 2.0, 0.0),
+                    flat,
+                    vec3(1.2, 0.5, 0.0),
+                    flat,
+                    vec3(0.0, -2.0, 0.0),
+                    vec3(1.2, 0.0, 0.0),
+                    flat,
+                ]),
+            },
+            AnimationChannel {
+                node: moon,
+                interpolation: Interpolation::Step,
+                times: vec![0.0, 0.1, 0.9],
+                values: AnimationValues::Scale(vec![
+                    vec3(0.18, 0.12, 0.18),
+                    vec3(0.15, 0.15, 0.15),
+                    vec3(0.18, 0.12, 0.18),
+                ]),
+            },
+        ],
+    );
+
+    let mut animation = AnimationPlayer::new(vec![orbit, bounce]);
+    animation.play(0);
+    (scene, animation)
+}
@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: MIT
+
+//! Frame captures through the RenderDoc in-application api, behind the `renderdoc` feature.
+//! RenderDoc has to be injected into the process, by launching from its ui or with
+//! `renderdoc cmd capture`, the calls do nothing otherwise and without the feature.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use tracing::{info, warn};
+
+use crate::gfx::validation_errors;
+
+// the captures `capture_on_validation_error` may still keep
+static AUTO_CAPTURES: AtomicU32 = AtomicU32::new(0);
+
+/// whether RenderDoc is loaded into the process and the captures work.
+pub fn is_available() -> bool {
+    api::with(|_| ()).is_some()
+}
+
+/// capture the next frame that is presented, the capture is written where RenderDoc keeps
+/// captures. does nothing without RenderDoc.
+pub fn trigger_capture() {
+    api::with(|api| api.trigger_capture());
+}
+
+/// start capturing everything submitted from now on, also work that is never presented such
+/// as `HeadlessDevice::execute`. does nothing while a capture is running.
+pub fn start_capture() {
+    api::with(|api| {
+        if !api.is_frame_capturing() {
+            api.start_frame_capture();
+        }
+    });
+}
+
+/// end the capture begun by `start_capture` and write it.
+pub fn end_capture() {
+    api::with(|api| {
+        if api.is_frame_capturing() {
+            api.end_frame_capture();
+        }
+    });
+}
+
+/// capture every frame and one time submission, keep those during which the validation
+/// layer reported an error and drop the others. at most `max_captures` are kept, 0 turns the
+/// automatic captures off.
+pub fn capture_on_validation_error(max_captures: u32) {
+    AUTO_CAPTURES.store(max_captures, Ordering::Relaxed);
+    if max_captures > 0 && !is_available() {
+        info!("RenderDoc is not loaded, frames with validation errors are not captured");
+    }
+}
+
+/// an automatic capture of what is submitted until it is dropped, see
+/// `capture_on_validation_error`.
+pub(crate) struct CheckedCapture {
+    // the validation errors when the capture started
+    started: Option<usize>,
+}
+
+impl CheckedCapture {
+    /// start a capture when automatic captures are on.
+    pub fn begin() -> Self {
+        if AUTO_CAPTURES.load(Ordering::Relaxed) == 0 {
+            return Self { started: None };
+        }
+        let started = api::with(|api| {
+            // a capture the application started is left alone
+            if api.is_frame_capturing() {
+                return None;
+            }
+            api.start_frame_capture();
+            Some(validation_errors())
+        });
+        Self {
+            started: started.flatten(),
+        }
+    }
+}
+
+// the capture is kept when validation errors occurred since it started
+impl Drop for CheckedCapture {
+    fn drop(&mut self) {
+        let Some(before) = self.started else {
+            return;
+        };
+        let errors = validation_errors() - before;
+        api::with(|api| {
+            let remaining = AUTO_CAPTURES.load(Ordering::Relaxed);
+            if errors == 0 || remaining == 0 {
+                api.discard_frame_capture();
+                return;
+            }
+            api.end_frame_capture();
+            AUTO_CAPTURES.store(remaining - 1, Ordering::Relaxed);
+            warn!(
+                "captured {} validation errors with RenderDoc, {} captures left",
+                errors,
+                remaining - 1
+            );
+        });
+    }
+}
+
+#[cfg(feature = "renderdoc")]
+mod api {
+    use std::ffi::c_void;
+    use std::ptr;
+    use std::sync::{Mutex, OnceLock};
+
+    use renderdoc::{RenderDoc, V141};
+
+    pub struct Api(RenderDoc<V141>);
+
+    // loaded on first use, it fails when RenderDoc did not inject itself
+    static API: OnceLock<Option<Mutex<Api>>> = OnceLock::new();
+
+    /// run `f` with the api, `None` without RenderDoc.
+    pub fn with<T>(f: impl FnOnce(&mut Api) -> T) -> Option<T> {
+        let api = API.get_or_init(|| RenderDoc::new().ok().map(|r| Mutex::new(Api(r))));
+        let mut api = api.as_ref()?.lock().unwrap_or_else(|e| e.into_inner());
+        Some(f(&mut api))
+    }
+
+    // null device and window pointers capture whatever device and window are active
+    impl Api {
+        pub fn trigger_capture(&mut self) {
+            self.0.trigger_capture();
+        }
+
+        pub fn is_frame_capturing(&mut self) -> bool {
+            self.0.is_frame_capturing()
+        }
+
+        pub fn start_frame_capture(&mut self) {
+            self.0
+                .start_frame_capture(ptr::null::<c_void>(), ptr::null());
+        }
+
+        pub fn end_frame_capture(&mut self) {
+            self.0.end_frame_capture(ptr::null::<c_void>(), ptr::null());
+        }
+
+        pub fn discard_frame_capture(&mut self) {
+            self.0
+                .discard_frame_capture(ptr::null::<c_void>(), ptr::null());
+        }
+    }
+}
+
+#[cfg(not(feature = "renderdoc"))]
+mod api {
+    // without the feature there is never an api to call
+    pub enum Api {}
+
+    pub fn with<T>(_: impl FnOnce(&mut Api) -> T) -> Option<T> {
+        None
+    }
+
+    impl Api {
+        pub fn trigger_capture(&mut self) {
+            match *self {}
+        }
+
+        pub fn is_frame_capturing(&mut self) -> bool {
+            match *self {}
+        }
+
+        pub fn start_frame_capture(&mut self) {
+            match *self {}
+        }
+
+        pub fn end_frame_capture(&mut self) {
+            match *self {}
+        }
+
+        pub fn discard_frame_capture(&mut self) {
+            match *self {}
+        }
+    }
+}
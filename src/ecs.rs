@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: MIT
+
+//! Components for drawing entities of an ecs world, for apps that keep their game state in
+//! an ecs instead of a `rendering::SceneGraph`. every frame `extract` walks the entities with
+//! a mesh, material and transform and queues the visible ones on a renderer. with the `hecs`
+//! feature `extract_world` does the walk for a `hecs::World`.
+
+use crate::math::Mat4;
+use crate::rendering::{Material, Mesh, MeshId, PbrMaterial, PbrRenderer, Renderer};
+
+pub use crate::rendering::Transform;
+
+/// The mesh an entity is drawn with, a `MeshId` for `Renderer` or a `Mesh` for `PbrRenderer`.
+/// the mesh is shared, it is destroyed by whoever created it and not with the entity.
+#[derive(Copy, Clone)]
+pub struct MeshHandle<T = Mesh>(pub T);
+
+/// The material an entity is drawn with, a `Material` for `Renderer` or a `PbrMaterial` for
+/// `PbrRenderer`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MaterialHandle<M = Material>(pub M);
+
+/// Whether an entity is drawn, entities without the component are.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Visibility {
+    #[default]
+    Visible,
+    Hidden,
+}
+
+impl Visibility {
+    pub fn is_visible(&self) -> bool {
+        *self == Visibility::Visible
+    }
+}
+
+/// A renderer that queues draws of materials `M` on meshes `T`, filled by `extract`.
+pub trait DrawList<M, T = Mesh> {
+    fn draw(&mut self, mesh: &T, material: &M, transform: Mat4);
+}
+
+impl DrawList<Material, MeshId> for Renderer {
+    fn draw(&mut self, mesh: &MeshId, material: &Material, transform: Mat4) {
+        Renderer::draw(self, mesh, material, transform);
+    }
+}
+
+impl DrawList<PbrMaterial> for PbrRenderer {
+    fn draw(&mut self, mesh: &Mesh, material: &PbrMaterial, transform: Mat4) {
+        PbrRenderer::draw(self, mesh, material, transform);
+    }
+}
+
+/// the draws in order, for inspecting what an extraction queues.
+impl<M: Copy, T: Copy> DrawList<M, T> for Vec<(T, M, Mat4)> {
+    fn draw(&mut self, mesh: &T, material: &M, transform: Mat4) {
+        self.push((*mesh, *material, transform));
+    }
+}
+
+/// queue the visible entities of `entities` on `list`, as the components a query of the
+/// world yields. returns the number of queued draws.
+pub fn extract<'a, M, T, L, I>(list: &mut L, entities: I) -> usize
+where
+    M: 'a,
+    T: 'a,
+    L: DrawList<M, T>,
+    I: IntoIterator<
+        Item = (
+            &'a MeshHandle<T>,
+            &'a MaterialHandle<M>,
+            &'a Transform,
+            Option<&'a Visibility>,
+        ),
+    >,
+{
+    let mut count = 0;
+    for (mesh, material, transform, visibility) in entities {
+        if visibility.is_some_and(|v| !v.is_visible()) {
+            continue;
+        }
+        list.draw(&mesh.0, &material.0, transform.matrix());
+        count += 1;
+    }
+    count
+}
+
+/// queue the visible entities of `world` with a mesh, material of `M` and transform on
+/// `list`. returns the number of queued draws.
+#[cfg(feature = "hecs")]
+pub fn extract_world<M, T, L>(world: &hecs::World, list: &mut L) -> usize
+where
+    M: Send + Sync + 'static,
+    T: Send + Sync + 'static,
+    L: DrawList<M, T>,
+{
+    let mut query = world.query::<(
+        &MeshHandle<T>,
+        &MaterialHandle<M>,
+        &Transform,
+        Option<&Visibility>,
+    )>();
+    extract(list, query.iter().map(|(_, components)| components))
+}
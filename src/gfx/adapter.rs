@@ -0,0 +1,154 @@
+#![allow(dead_code)]
+
+use std::collections::HashSet;
+
+use vulkanalia::prelude::v1_0::*;
+
+use super::Feature;
+
+/// What kind of gpu an adapter is.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AdapterType {
+    Discrete,
+    Integrated,
+    Virtual,
+    Cpu,
+    Other,
+}
+
+/// The limits of an adapter that decide what the renderer can do.
+#[derive(Copy, Clone, Debug)]
+pub struct AdapterLimits {
+    pub max_image_dimension_2d: u32,
+    pub max_push_constants_size: u32,
+    pub max_bound_descriptor_sets: u32,
+    pub max_uniform_buffer_range: u32,
+    pub max_sampler_anisotropy: f32,
+    pub max_color_samples: vk::SampleCountFlags,
+    /// the viewports a pass can set, 1 without the multiViewport feature.
+    pub max_viewports: u32,
+}
+
+/// A gpu that can drive the window, as returned by `enumerate_adapters`.
+#[derive(Clone, Debug)]
+pub struct AdapterInfo {
+    /// the position in the list of suitable adapters, pass it to `AdapterSelection::Index`.
+    pub index: usize,
+    pub name: String,
+    pub vendor: &'static str,
+    pub vendor_id: u32,
+    pub device_id: u32,
+    pub kind: AdapterType,
+    pub api_version: String,
+    pub driver_version: u32,
+    pub limits: AdapterLimits,
+    /// the optional features a device on this adapter can be created with.
+    pub features: HashSet<Feature>,
+}
+
+impl AdapterInfo {
+    pub(crate) unsafe fn get(
+        instance: &Instance,
+        physical: vk::PhysicalDevice,
+        index: usize,
+    ) -> Self {
+        let properties = instance.get_physical_device_properties(physical);
+        let features = instance.get_physical_device_features(physical);
+        let limits = properties.limits;
+        let kind = match properties.device_type {
+            vk::PhysicalDeviceType::DISCRETE_GPU => AdapterType::Discrete,
+            vk::PhysicalDeviceType::INTEGRATED_GPU => AdapterType::Integrated,
+            vk::PhysicalDeviceType::VIRTUAL_GPU => AdapterType::Virtual,
+            vk::PhysicalDeviceType::CPU => AdapterType::Cpu,
+            _ => AdapterType::Other,
+        };
+        let version = vulkanalia::Version::from(properties.api_version);
+        Self {
+            index,
+            name: properties.device_name.to_string(),
+            vendor: vendor_name(properties.vendor_id),
+            vendor_id: properties.vendor_id,
+            device_id: properties.device_id,
+            kind,
+            api_version: version.to_string(),
+            driver_version: properties.driver_version,
+            limits: AdapterLimits {
+                max_image_dimension_2d: limits.max_image_dimension_2d,
+                max_push_constants_size: limits.max_push_constants_size,
+                max_bound_descriptor_sets: limits.max_bound_descriptor_sets,
+                max_uniform_buffer_range: limits.max_uniform_buffer_range,
+                max_sampler_anisotropy: limits.max_sampler_anisotropy,
+                max_color_samples: limits.framebuffer_color_sample_counts,
+                max_viewports: if features.multi_viewport == vk::TRUE {
+                    limits.max_viewports
+                } else {
+                    1
+                },
+            },
+            features: Feature::available(&features, &limits),
+        }
+    }
+}
+
+/// Which kind of gpu to prefer when several can drive the window.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PowerPreference {
+    /// discrete before integrated gpus.
+    HighPerformance,
+    /// integrated before discrete gpus, e.g. to save battery.
+    LowPower,
+}
+
+/// How `Device::create_with_adapter` picks the gpu.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum AdapterSelection {
+    /// the first suitable adapter in the order of the driver.
+    #[default]
+    First,
+    /// the adapter with this `AdapterInfo::index`.
+    Index(usize),
+    /// the best adapter for the preference, ties go to the first.
+    Power(PowerPreference),
+}
+
+impl AdapterSelection {
+    /// the index of the chosen adapter in `adapters`.
+    pub fn choose(&self, adapters: &[AdapterInfo]) -> Option<usize> {
+        match *self {
+            AdapterSelection::First => (!adapters.is_empty()).then_some(0),
+            AdapterSelection::Index(index) => (index < adapters.len()).then_some(index),
+            AdapterSelection::Power(preference) => adapters
+                .iter()
+                .enumerate()
+                .min_by_key(|(index, adapter)| (rank(preference, adapter.kind), *index))
+                .map(|(index, _)| index),
+        }
+    }
+}
+
+// lower is better
+fn rank(preference: PowerPreference, kind: AdapterType) -> u32 {
+    match (preference, kind) {
+        (PowerPreference::HighPerformance, AdapterType::Discrete) => 0,
+        (PowerPreference::HighPerformance, AdapterType::Integrated) => 1,
+        (PowerPreference::LowPower, AdapterType::Integrated) => 0,
+        (PowerPreference::LowPower, AdapterType::Discrete) => 1,
+        (_, AdapterType::Virtual) => 2,
+        (_, AdapterType::Other) => 3,
+        (_, AdapterType::Cpu) => 4,
+    }
+}
+
+// the pci vendor ids of the common gpu vendors
+fn vendor_name(id: u32) -> &'static str {
+    match id {
+        0x1002 => "AMD",
+        0x106b => "Apple",
+        0x10de => "NVIDIA",
+        0x13b5 => "ARM",
+        0x5143 => "Qualcomm",
+        0x8086 => "Intel",
+        0x10005 => "Mesa",
+        _ => "Unknown",
+    }
+}
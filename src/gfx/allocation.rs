@@ -0,0 +1,184 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use anyhow::Result;
+use vulkanalia::prelude::v1_0::*;
+
+use super::names::raw_handle;
+
+/// What a device memory allocation backs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AllocationKind {
+    Buffer,
+    Texture,
+    /// the chunks `Device::allocate` hands out per frame data from.
+    Staging,
+}
+
+/// A device memory allocation of a buffer or texture, each has its own.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Allocation {
+    pub memory: vk::DeviceMemory,
+    /// the bytes allocated, the size of the memory requirements of the resource.
+    pub size: vk::DeviceSize,
+    /// the index of the memory type, see `vk::PhysicalDeviceMemoryProperties`.
+    pub memory_type: u32,
+    pub kind: AllocationKind,
+    /// the raw handle of the buffer or image the memory is bound to.
+    pub resource: u64,
+    /// the name given to the resource with `Device::set_name`, if any.
+    pub name: Option<String>,
+}
+
+/// The allocations alive on a device, see `Device::allocation_stats`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AllocationStats {
+    pub allocations: usize,
+    pub bytes: vk::DeviceSize,
+    /// the bytes allocated since the device was created, freed ones included and refused ones
+    /// not.
+    pub total_bytes: vk::DeviceSize,
+}
+
+/// Callbacks for every device memory allocation and free of a device, see
+/// `Device::set_allocation_hooks`, e.g. to enforce a memory budget or find leaks. they run
+/// on the thread that allocates, one call at a time, and must not create or destroy
+/// resources themselves.
+pub trait AllocationHooks: Send {
+    /// `allocation` was made, an error frees it again without calling `freed` and fails the
+    /// creation of its resource.
+    fn allocated(&mut self, allocation: &Allocation) -> Result<()> {
+        let _ = allocation;
+        Ok(())
+    }
+
+    /// `allocation` was freed. resources retired to a `DeletionQueue` are freed once the
+    /// queue destroys them, not when they are retired.
+    fn freed(&mut self, allocation: &Allocation) {
+        let _ = allocation;
+    }
+}
+
+#[derive(Default)]
+struct Tracker {
+    // shared with the calls in flight, so a hook never runs under the lock of the trackers
+    hooks: Option<Arc<Mutex<Box<dyn AllocationHooks>>>>,
+    live: HashMap<vk::DeviceMemory, Allocation>,
+    total_bytes: vk::DeviceSize,
+}
+
+// the trackers of the devices by their raw handle. resources are destroyed with the bare
+// `vulkanalia::Device`, so frees find their tracker by it
+static TRACKERS: Mutex<Option<HashMap<usize, Tracker>>> = Mutex::new(None);
+
+fn trackers() -> MutexGuard<'static, Option<HashMap<usize, Tracker>>> {
+    TRACKERS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn with<T>(device: vk::Device, f: impl FnOnce(&mut Tracker) -> T) -> Option<T> {
+    trackers()
+        .as_mut()
+        .and_then(|t| t.get_mut(&device.as_raw()))
+        .map(f)
+}
+
+/// track the allocations of `device` from now on.
+pub(crate) fn register(device: vk::Device) {
+    trackers()
+        .get_or_insert_with(HashMap::new)
+        .insert(device.as_raw(), Tracker::default());
+}
+
+/// stop tracking `device`, returns the allocations that were never freed.
+pub(crate) fn unregister(device: vk::Device) -> Vec<Allocation> {
+    let tracker = trackers().as_mut().and_then(|t| t.remove(&device.as_raw()));
+    tracker.map_or(vec![], |t| t.live.into_values().collect())
+}
+
+pub(crate) fn set_hooks(device: vk::Device, hooks: Option<Box<dyn AllocationHooks>>) {
+    with(device, |t| t.hooks = hooks.map(|h| Arc::new(Mutex::new(h))));
+}
+
+/// the allocations of `device` that are alive, largest first.
+pub(crate) fn live(device: vk::Device) -> Vec<Allocation> {
+    let mut live =
+        with(device, |t| t.live.values().cloned().collect::<Vec<_>>()).unwrap_or_default();
+    live.sort_by_key(|a| std::cmp::Reverse(a.size));
+    live
+}
+
+pub(crate) fn stats(device: vk::Device) -> AllocationStats {
+    with(device, |t| AllocationStats {
+        allocations: t.live.len(),
+        bytes: t.live.values().map(|a| a.size).sum(),
+        total_bytes: t.total_bytes,
+    })
+    .unwrap_or_default()
+}
+
+/// name the allocation bound to `handle`, when it is a buffer or image.
+pub(crate) fn set_name<H>(device: vk::Device, handle: H, name: &str)
+where
+    H: Handle,
+    u64: TryFrom<H::Repr>,
+{
+    if !matches!(H::TYPE, vk::ObjectType::BUFFER | vk::ObjectType::IMAGE) {
+        return;
+    }
+    let resource = raw_handle(handle);
+    with(device, |t| {
+        if let Some(allocation) = t.live.values_mut().find(|a| a.resource == resource) {
+            allocation.name = Some(name.to_string());
+        }
+    });
+}
+
+// call the hooks of `device` outside of the lock of the trackers. hooks replaced while
+// they run finish the call, the next one goes to the new hooks
+fn call<T>(device: vk::Device, f: impl FnOnce(&mut dyn AllocationHooks) -> T) -> Option<T> {
+    let hooks = with(device, |t| t.hooks.clone()).flatten()?;
+    let mut hooks = hooks.lock().unwrap_or_else(|e| e.into_inner());
+    Some(f(hooks.as_mut()))
+}
+
+/// allocate the memory of `info` for `resource` and report it to the hooks.
+pub(crate) unsafe fn allocate(
+    device: &vulkanalia::Device,
+    info: &vk::MemoryAllocateInfo,
+    kind: AllocationKind,
+    resource: u64,
+) -> Result<vk::DeviceMemory> {
+    let memory = device.allocate_memory(info, None)?;
+    let allocation = Allocation {
+        memory,
+        size: info.allocation_size,
+        memory_type: info.memory_type_index,
+        kind,
+        resource,
+        name: None,
+    };
+    if let Some(Err(error)) = call(device.handle(), |h| h.allocated(&allocation)) {
+        // refused allocations are undone without telling the hooks about a free
+        device.free_memory(memory, None);
+        return Err(error);
+    }
+
+    // only accepted allocations count
+    with(device.handle(), |t| {
+        t.total_bytes += allocation.size;
+        t.live.insert(memory, allocation);
+    });
+    Ok(memory)
+}
+
+/// free `memory` and report it to the hooks.
+pub(crate) unsafe fn free(device: &vulkanalia::Device, memory: vk::DeviceMemory) {
+    // forget the allocation before the driver can hand its handle out again
+    let allocation = with(device.handle(), |t| t.live.remove(&memory)).flatten();
+    device.free_memory(memory, None);
+    if let Some(allocation) = allocation {
+        call(device.handle(), |h| h.freed(&allocation));
+    }
+}
@@ -0,0 +1,385 @@
+#![allow(dead_code)]
+
+use std::mem::size_of;
+use std::ptr::copy_nonoverlapping as memcpy;
+
+use anyhow::{anyhow, Result};
+use tracing::debug;
+use vulkanalia::prelude::v1_0::*;
+
+use super::allocation;
+use super::{check_draw_indexed, pod_bytes, Pod};
+
+/// the memory properties a buffer of `usage` most likely wants, any combination of flags:
+/// - buffers read by the gpu and filled with copies, `TRANSFER_DST` with anything else but
+///   `TRANSFER_SRC`, and storage buffers written by shaders are device local.
+/// - everything else is written or read back by the host, staging, uniforms and downloads,
+///   and is host visible and coherent.
+///
+/// a storage buffer the host fills has to say so, see `create_buffer`.
+pub fn buffer_memory_properties(usage: vk::BufferUsageFlags) -> vk::MemoryPropertyFlags {
+    let transfer = vk::BufferUsageFlags::TRANSFER_SRC | vk::BufferUsageFlags::TRANSFER_DST;
+    let uploaded = usage.contains(vk::BufferUsageFlags::TRANSFER_DST)
+        && !usage.intersects(vk::BufferUsageFlags::TRANSFER_SRC)
+        && !(usage - transfer).is_empty();
+    if uploaded || usage.contains(vk::BufferUsageFlags::STORAGE_BUFFER) {
+        vk::MemoryPropertyFlags::DEVICE_LOCAL
+    } else {
+        vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE
+    }
+}
+
+/// A way commands use a buffer, barriers between two uses are built from their stages and
+/// accesses. storage buffers written by compute and read by a render pass are the common case.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BufferAccess {
+    ComputeRead,
+    ComputeWrite,
+    /// read by vertex shaders, a uniform or storage buffer.
+    VertexShaderRead,
+    /// read by fragment shaders, a uniform or storage buffer.
+    FragmentShaderRead,
+    /// read as vertex attributes.
+    Vertex,
+    Index,
+    /// the arguments of indirect draws and dispatches.
+    Indirect,
+    TransferRead,
+    TransferWrite,
+    HostRead,
+}
+
+impl BufferAccess {
+    pub fn stage(&self) -> vk::PipelineStageFlags {
+        match self {
+            BufferAccess::ComputeRead | BufferAccess::ComputeWrite => {
+                vk::PipelineStageFlags::COMPUTE_SHADER
+            }
+            BufferAccess::VertexShaderRead => vk::PipelineStageFlags::VERTEX_SHADER,
+            BufferAccess::FragmentShaderRead => vk::PipelineStageFlags::FRAGMENT_SHADER,
+            BufferAccess::Vertex | BufferAccess::Index => vk::PipelineStageFlags::VERTEX_INPUT,
+            BufferAccess::Indirect => vk::PipelineStageFlags::DRAW_INDIRECT,
+            BufferAccess::TransferRead | BufferAccess::TransferWrite => {
+                vk::PipelineStageFlags::TRANSFER
+            }
+            BufferAccess::HostRead => vk::PipelineStageFlags::HOST,
+        }
+    }
+
+    pub fn access(&self) -> vk::AccessFlags {
+        match self {
+            BufferAccess::ComputeRead
+            | BufferAccess::VertexShaderRead
+            | BufferAccess::FragmentShaderRead => vk::AccessFlags::SHADER_READ,
+            BufferAccess::ComputeWrite => vk::AccessFlags::SHADER_WRITE,
+            BufferAccess::Vertex => vk::AccessFlags::VERTEX_ATTRIBUTE_READ,
+            BufferAccess::Index => vk::AccessFlags::INDEX_READ,
+            BufferAccess::Indirect => vk::AccessFlags::INDIRECT_COMMAND_READ,
+            BufferAccess::TransferRead => vk::AccessFlags::TRANSFER_READ,
+            BufferAccess::TransferWrite => vk::AccessFlags::TRANSFER_WRITE,
+            BufferAccess::HostRead => vk::AccessFlags::HOST_READ,
+        }
+    }
+}
+
+/// make the `src` uses of whole buffers visible to their `dst` uses, in a single barrier.
+pub unsafe fn buffer_barrier(
+    device: &vulkanalia::Device,
+    command_buffer: vk::CommandBuffer,
+    buffers: &[vk::Buffer],
+    src: &[BufferAccess],
+    dst: &[BufferAccess],
+) {
+    let fold = |accesses: &[BufferAccess]| {
+        accesses.iter().fold(
+            (vk::PipelineStageFlags::empty(), vk::AccessFlags::empty()),
+            |(stage, access), a| (stage | a.stage(), access | a.access()),
+        )
+    };
+    let (src_stage, src_access) = fold(src);
+    let (dst_stage, dst_access) = fold(dst);
+
+    let barriers = buffers
+        .iter()
+        .map(|buffer| {
+            vk::BufferMemoryBarrier::builder()
+                .src_access_mask(src_access)
+                .dst_access_mask(dst_access)
+                .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .buffer(*buffer)
+                .offset(0)
+                .size(vk::WHOLE_SIZE as vk::DeviceSize)
+                .build()
+        })
+        .collect::<Vec<_>>();
+
+    device.cmd_pipeline_barrier(
+        command_buffer,
+        src_stage,
+        dst_stage,
+        vk::DependencyFlags::empty(),
+        &[] as &[vk::MemoryBarrier],
+        &barriers,
+        &[] as &[vk::ImageMemoryBarrier],
+    );
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Buffer {
+    pub buffer: vk::Buffer,
+    pub memory: vk::DeviceMemory,
+    pub size: vk::DeviceSize,
+}
+
+impl Buffer {
+    pub fn create(buffer: vk::Buffer, memory: vk::DeviceMemory, size: vk::DeviceSize) -> Self {
+        Self {
+            buffer,
+            memory,
+            size,
+        }
+    }
+
+    /// write `data` at `offset`, arrays and slices of any plain data alike.
+    pub unsafe fn write<T: Pod>(
+        &self,
+        device: &vulkanalia::Device,
+        offset: vk::DeviceSize,
+        data: &[T],
+    ) -> Result<()> {
+        self.write_bytes(device, offset, pod_bytes(data))
+    }
+
+    /// write bytes packed elsewhere at `offset`, e.g. `Uniform::bytes` or a mapped file. an
+    /// error when they don't fit the buffer.
+    pub unsafe fn write_bytes(
+        &self,
+        device: &vulkanalia::Device,
+        offset: vk::DeviceSize,
+        bytes: &[u8],
+    ) -> Result<()> {
+        let end = offset.checked_add(bytes.len() as vk::DeviceSize);
+        if end.is_none_or(|end| end > self.size) {
+            return Err(anyhow!(
+                "Writing {} bytes at {} overruns the buffer of {} bytes.",
+                bytes.len(),
+                offset,
+                self.size
+            ));
+        }
+
+        // nothing to do for empty writes
+        if bytes.is_empty() {
+            return Ok(());
+        }
+
+        // lock memory
+        let size = bytes.len() as vk::DeviceSize;
+        let memory = device.map_memory(self.memory, offset, size, vk::MemoryMapFlags::empty())?;
+
+        // copy data into
+        memcpy(bytes.as_ptr(), memory.cast(), bytes.len());
+
+        // unlock memory
+        device.unmap_memory(self.memory);
+
+        // all done
+        Ok(())
+    }
+
+    pub unsafe fn read<T: Pod>(
+        &self,
+        device: &vulkanalia::Device,
+        offset: vk::DeviceSize,
+        count: usize,
+    ) -> Result<Vec<T>> {
+        // size in bytes of the data
+        let size = self.check_read::<T>(offset, count)?;
+
+        // nothing to do for empty reads
+        if size == 0 {
+            return Ok(vec![]);
+        }
+
+        // lock memory
+        let memory = device.map_memory(self.memory, offset, size, vk::MemoryMapFlags::empty())?;
+
+        // copy data out
+        let mut data = Vec::with_capacity(count);
+        memcpy(memory.cast(), data.as_mut_ptr(), count);
+        data.set_len(count);
+
+        // unlock memory
+        device.unmap_memory(self.memory);
+
+        // all done
+        Ok(data)
+    }
+
+    // the bytes of `count` values at `offset`, an error when they don't all fit the buffer
+    pub(crate) fn check_read<T>(
+        &self,
+        offset: vk::DeviceSize,
+        count: usize,
+    ) -> Result<vk::DeviceSize> {
+        let size = (size_of::<T>() as vk::DeviceSize).checked_mul(count as vk::DeviceSize);
+        match size.and_then(|size| Some((size, offset.checked_add(size)?))) {
+            Some((size, end)) if end <= self.size => Ok(size),
+            _ => Err(anyhow!(
+                "Reading {} values of {} bytes at {} overruns the buffer of {} bytes.",
+                count,
+                size_of::<T>(),
+                offset,
+                self.size
+            )),
+        }
+    }
+
+    /// make the `src` uses of the buffer visible to its `dst` uses, see `buffer_barrier`.
+    pub unsafe fn barrier(
+        &self,
+        device: &vulkanalia::Device,
+        command_buffer: vk::CommandBuffer,
+        src: &[BufferAccess],
+        dst: &[BufferAccess],
+    ) {
+        buffer_barrier(device, command_buffer, &[self.buffer], src, dst);
+    }
+
+    pub unsafe fn destroy(&self, device: &Device) {
+        // destroy the buffer
+        device.destroy_buffer(self.buffer, None);
+
+        // free the memory
+        allocation::free(device, self.memory);
+    }
+}
+
+/// A host visible buffer that stays mapped until it is destroyed, for data written every
+/// frame like uniforms. writes are plain copies, without a map and unmap each, so they can
+/// happen right before the frame using them is submitted, see `Device::update_with_latch`.
+/// the memory must be host coherent, writes aren't flushed.
+#[derive(Debug)]
+pub struct MappedBuffer {
+    buffer: Buffer,
+    data: *mut u8,
+}
+
+// the mapping belongs to the buffer and is only written through it
+unsafe impl Send for MappedBuffer {}
+
+impl MappedBuffer {
+    /// map the whole memory of `buffer`, which must not be mapped already.
+    pub unsafe fn map(device: &vulkanalia::Device, buffer: Buffer) -> Result<Self> {
+        let data = device.map_memory(buffer.memory, 0, buffer.size, vk::MemoryMapFlags::empty())?;
+        Ok(Self {
+            buffer,
+            data: data.cast(),
+        })
+    }
+
+    /// the buffer to bind, its memory stays with the mapping.
+    pub fn buffer(&self) -> vk::Buffer {
+        self.buffer.buffer
+    }
+
+    /// the bytes of the buffer.
+    pub fn size(&self) -> vk::DeviceSize {
+        self.buffer.size
+    }
+
+    /// write `data` at `offset`, see `Buffer::write`.
+    pub fn write<T: Pod>(&self, offset: vk::DeviceSize, data: &[T]) -> Result<()> {
+        self.write_bytes(offset, pod_bytes(data))
+    }
+
+    /// write bytes at `offset`, an error when they don't fit the buffer.
+    pub fn write_bytes(&self, offset: vk::DeviceSize, bytes: &[u8]) -> Result<()> {
+        let end = offset.checked_add(bytes.len() as vk::DeviceSize);
+        if end.is_none_or(|end| end > self.buffer.size) {
+            return Err(anyhow!(
+                "Writing {} bytes at {} overruns the mapped buffer of {} bytes.",
+                bytes.len(),
+                offset,
+                self.buffer.size
+            ));
+        }
+
+        // the mapping covers the whole buffer and lives as long as it
+        unsafe { memcpy(bytes.as_ptr(), self.data.add(offset as usize), bytes.len()) };
+        Ok(())
+    }
+
+    /// unmap the buffer and hand it back, e.g. to read it.
+    pub unsafe fn unmap(self, device: &vulkanalia::Device) -> Buffer {
+        device.unmap_memory(self.buffer.memory);
+        self.buffer
+    }
+
+    /// unmap and destroy the buffer.
+    pub unsafe fn destroy(&self, device: &vulkanalia::Device) {
+        device.unmap_memory(self.buffer.memory);
+        self.buffer.destroy(device);
+    }
+}
+
+/// The indices of indexed draws, a buffer with the format and offset they are read at. draws
+/// are checked against the indices the buffer holds, vulkan would read past it otherwise.
+#[derive(Copy, Clone, Debug)]
+pub struct IndexBinding {
+    pub buffer: vk::Buffer,
+    /// the size of the whole buffer in bytes.
+    pub size: vk::DeviceSize,
+    pub offset: vk::DeviceSize,
+    pub index_type: vk::IndexType,
+}
+
+impl IndexBinding {
+    /// the indices of `buffer` from its start.
+    pub fn new(buffer: &Buffer, index_type: vk::IndexType) -> Self {
+        Self {
+            buffer: buffer.buffer,
+            size: buffer.size,
+            offset: 0,
+            index_type,
+        }
+    }
+
+    pub fn with_offset(mut self, offset: vk::DeviceSize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub unsafe fn bind(&self, device: &vulkanalia::Device, command_buffer: vk::CommandBuffer) {
+        debug!(buffer = ?self.buffer, index_type = ?self.index_type, "bind index buffer");
+        device.cmd_bind_index_buffer(command_buffer, self.buffer, self.offset, self.index_type);
+    }
+
+    /// draw `index_count` indices from `first_index` of the bound indices, fails without
+    /// recording when they are not all in the buffer.
+    pub unsafe fn draw(
+        &self,
+        device: &vulkanalia::Device,
+        command_buffer: vk::CommandBuffer,
+        first_index: u32,
+        index_count: u32,
+        instance_count: u32,
+    ) -> Result<()> {
+        check_draw_indexed(self, first_index, index_count)?;
+        debug!(
+            indices = index_count,
+            instances = instance_count,
+            "draw indexed"
+        );
+        device.cmd_draw_indexed(
+            command_buffer,
+            index_count,
+            instance_count,
+            first_index,
+            0,
+            0,
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,262 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::error;
+use std::ffi::{c_char, c_void, CStr};
+use std::mem::transmute;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use vulkanalia::loader::Loader;
+use vulkanalia::prelude::v1_0::*;
+
+use super::recorded::recorded;
+use super::trace::items;
+use super::{FrameTrace, TraceBufferBarrier, TraceCommand, TraceImageBarrier, TraceSubmission};
+
+/// Loads vulkan through `inner` and hands out capturing versions of the recorded commands,
+/// queue submissions and command buffer begins. they pass straight through until a capture
+/// is started, see `Device::start_capture`.
+pub(crate) struct CaptureLoader<L> {
+    inner: L,
+}
+
+// what a capture records, shared by all devices since the entry points are plain functions
+#[derive(Default)]
+struct CaptureState {
+    // the commands of the command buffers begun during the capture
+    recording: HashMap<usize, Vec<TraceCommand>>,
+    submissions: Vec<TraceSubmission>,
+}
+
+static CAPTURING: AtomicBool = AtomicBool::new(false);
+static STATE: Mutex<Option<CaptureState>> = Mutex::new(None);
+
+// the functions of the inner loader
+static GET_INSTANCE: AtomicUsize = AtomicUsize::new(0);
+static GET_DEVICE: AtomicUsize = AtomicUsize::new(0);
+static BEGIN: AtomicUsize = AtomicUsize::new(0);
+static SUBMIT: AtomicUsize = AtomicUsize::new(0);
+// the latest instance, device commands are taken from it so they work on every device
+static INSTANCE: AtomicUsize = AtomicUsize::new(0);
+
+impl<L: Loader> CaptureLoader<L> {
+    pub fn new(inner: L) -> Self {
+        Self { inner }
+    }
+}
+
+impl<L: Loader> Loader for CaptureLoader<L> {
+    unsafe fn load(
+        &self,
+        name: &[u8],
+    ) -> Result<extern "system" fn(), Box<dyn error::Error + Send + Sync + 'static>> {
+        let function = self.inner.load(name)?;
+        let capturing = match name {
+            b"vkGetInstanceProcAddr" => {
+                GET_INSTANCE.store(function as usize, Ordering::Release);
+                transmute::<vk::PFN_vkGetInstanceProcAddr, extern "system" fn()>(
+                    get_instance_proc_addr,
+                )
+            }
+            b"vkGetDeviceProcAddr" => {
+                GET_DEVICE.store(function as usize, Ordering::Release);
+                transmute::<vk::PFN_vkGetDeviceProcAddr, extern "system" fn()>(get_device_proc_addr)
+            }
+            _ => function,
+        };
+        Ok(capturing)
+    }
+}
+
+/// start recording the submissions of all devices, a running capture starts over.
+pub(crate) fn start() {
+    *state() = Some(CaptureState::default());
+    CAPTURING.store(true, Ordering::Release);
+}
+
+/// stop the capture and return what it recorded, empty without a running capture.
+pub(crate) fn stop() -> FrameTrace {
+    CAPTURING.store(false, Ordering::Release);
+    let submissions = state().take().map(|s| s.submissions).unwrap_or_default();
+    FrameTrace { submissions }
+}
+
+fn state() -> MutexGuard<'static, Option<CaptureState>> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn record(command_buffer: vk::CommandBuffer, command: TraceCommand) {
+    if let Some(state) = state().as_mut() {
+        if let Some(commands) = state.recording.get_mut(&command_buffer.as_raw()) {
+            commands.push(command);
+        }
+    }
+}
+
+unsafe fn real_instance_proc_addr(
+    instance: vk::Instance,
+    name: *const c_char,
+) -> vk::PFN_vkVoidFunction {
+    let real =
+        transmute::<usize, vk::PFN_vkGetInstanceProcAddr>(GET_INSTANCE.load(Ordering::Acquire));
+    real(instance, name)
+}
+
+// a capturing version in place of `real`, which it stores to forward to
+unsafe fn intercept(name: &[u8], real: vk::PFN_vkVoidFunction) -> vk::PFN_vkVoidFunction {
+    let real = real?;
+    let (slot, capturing) = match name {
+        b"vkBeginCommandBuffer" => (
+            &BEGIN,
+            transmute::<vk::PFN_vkBeginCommandBuffer, unsafe extern "system" fn()>(
+                begin_command_buffer,
+            ),
+        ),
+        b"vkQueueSubmit" => (
+            &SUBMIT,
+            transmute::<vk::PFN_vkQueueSubmit, unsafe extern "system" fn()>(queue_submit),
+        ),
+        _ => match capturing_command(name) {
+            Some((index, capturing)) => (&REAL[index], capturing),
+            None => return Some(real),
+        },
+    };
+    slot.store(real as usize, Ordering::Release);
+    Some(capturing)
+}
+
+unsafe extern "system" fn get_instance_proc_addr(
+    instance: vk::Instance,
+    name: *const c_char,
+) -> vk::PFN_vkVoidFunction {
+    if !instance.is_null() {
+        INSTANCE.store(instance.as_raw(), Ordering::Release);
+    }
+    let real = real_instance_proc_addr(instance, name);
+    intercept(CStr::from_ptr(name).to_bytes(), real)
+}
+
+unsafe extern "system" fn get_device_proc_addr(
+    device: vk::Device,
+    name: *const c_char,
+) -> vk::PFN_vkVoidFunction {
+    let get_device =
+        transmute::<usize, vk::PFN_vkGetDeviceProcAddr>(GET_DEVICE.load(Ordering::Acquire));
+    let real = get_device(device, name);
+    let bytes = CStr::from_ptr(name).to_bytes();
+    if real.is_none()
+        || (capturing_command(bytes).is_none()
+            && !matches!(bytes, b"vkBeginCommandBuffer" | b"vkQueueSubmit"))
+    {
+        return real;
+    }
+
+    // the functions of a device only work on it, those of the instance dispatch to any
+    let instance = vk::Instance::from_raw(INSTANCE.load(Ordering::Acquire));
+    let dispatching = if instance.is_null() {
+        None
+    } else {
+        real_instance_proc_addr(instance, name)
+    };
+    intercept(bytes, dispatching.or(real))
+}
+
+unsafe extern "system" fn begin_command_buffer(
+    command_buffer: vk::CommandBuffer,
+    info: *const vk::CommandBufferBeginInfo,
+) -> vk::Result {
+    let real = transmute::<usize, vk::PFN_vkBeginCommandBuffer>(BEGIN.load(Ordering::Acquire));
+    let result = real(command_buffer, info);
+    if CAPTURING.load(Ordering::Acquire) && result == vk::Result::SUCCESS {
+        if let Some(state) = state().as_mut() {
+            state.recording.insert(command_buffer.as_raw(), vec![]);
+        }
+    }
+    result
+}
+
+unsafe extern "system" fn queue_submit(
+    queue: vk::Queue,
+    count: u32,
+    submits: *const vk::SubmitInfo,
+    fence: vk::Fence,
+) -> vk::Result {
+    let real = transmute::<usize, vk::PFN_vkQueueSubmit>(SUBMIT.load(Ordering::Acquire));
+    let result = real(queue, count, submits, fence);
+    if !CAPTURING.load(Ordering::Acquire) || result != vk::Result::SUCCESS {
+        return result;
+    }
+
+    if let Some(state) = state().as_mut() {
+        for submit in items(submits, count) {
+            let command_buffers = items(submit.command_buffers, submit.command_buffer_count);
+            // command buffers begun before the capture add no commands
+            let commands = command_buffers
+                .iter()
+                .filter_map(|c| state.recording.get(&c.as_raw()))
+                .flatten()
+                .cloned()
+                .collect();
+            state.submissions.push(TraceSubmission {
+                command_buffers: command_buffers.to_vec(),
+                commands,
+                wait_semaphores: items(submit.wait_semaphores, submit.wait_semaphore_count)
+                    .to_vec(),
+                signal_semaphores: items(submit.signal_semaphores, submit.signal_semaphore_count)
+                    .to_vec(),
+                fence,
+            });
+        }
+    }
+    result
+}
+
+// the recorded commands forward to the real ones, one slot of `REAL` each
+macro_rules! capture_commands {
+    ($($vk:literal $name:ident: $pfn:ident($command_buffer:ident $(, $arg:ident: $ty:ty)*) => $command:expr;)*) => {
+        const NAMES: &[&str] = &[$($vk),*];
+        static REAL: [AtomicUsize; NAMES.len()] = [const { AtomicUsize::new(0) }; NAMES.len()];
+
+        const fn index(name: &str) -> usize {
+            let mut i = 0;
+            while !eq(NAMES[i].as_bytes(), name.as_bytes()) {
+                i += 1;
+            }
+            i
+        }
+
+        $(unsafe extern "system" fn $name($command_buffer: vk::CommandBuffer $(, $arg: $ty)*) {
+            const INDEX: usize = index($vk);
+            let real = transmute::<usize, vk::$pfn>(REAL[INDEX].load(Ordering::Acquire));
+            real($command_buffer $(, $arg)*);
+            if CAPTURING.load(Ordering::Acquire) {
+                record($command_buffer, $command);
+            }
+        })*
+
+        fn capturing_command(name: &[u8]) -> Option<(usize, unsafe extern "system" fn())> {
+            $(if name == $vk.as_bytes() {
+                let function = unsafe { transmute::<vk::$pfn, unsafe extern "system" fn()>($name) };
+                return Some((index($vk), function));
+            })*
+            None
+        }
+    };
+}
+
+const fn eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+recorded!(capture_commands);
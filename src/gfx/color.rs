@@ -0,0 +1,229 @@
+#![allow(dead_code)]
+
+use anyhow::{anyhow, Result};
+use vulkanalia::prelude::v1_0::*;
+
+use crate::math::Vec4;
+
+/// A straight alpha rgba color with components in 0..1. colors are kept as given, hex colors
+/// and the palette are srgb encoded like colors picked in an editor. `to_linear` decodes them
+/// for shading and clears of srgb targets, which expect linear values.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub const TRANSPARENT: Color = Color::rgba(0.0, 0.0, 0.0, 0.0);
+    pub const BLACK: Color = Color::rgb(0.0, 0.0, 0.0);
+    pub const WHITE: Color = Color::rgb(1.0, 1.0, 1.0);
+    pub const GRAY: Color = Color::rgb(0.5, 0.5, 0.5);
+    pub const DARK_GRAY: Color = Color::rgb(0.25, 0.25, 0.25);
+    pub const LIGHT_GRAY: Color = Color::rgb(0.75, 0.75, 0.75);
+    pub const RED: Color = Color::rgb(1.0, 0.0, 0.0);
+    pub const GREEN: Color = Color::rgb(0.0, 1.0, 0.0);
+    pub const BLUE: Color = Color::rgb(0.0, 0.0, 1.0);
+    pub const YELLOW: Color = Color::rgb(1.0, 1.0, 0.0);
+    pub const CYAN: Color = Color::rgb(0.0, 1.0, 1.0);
+    pub const MAGENTA: Color = Color::rgb(1.0, 0.0, 1.0);
+    pub const ORANGE: Color = Color::rgb(1.0, 0.647, 0.0);
+    pub const PURPLE: Color = Color::rgb(0.502, 0.0, 0.502);
+    pub const PINK: Color = Color::rgb(1.0, 0.753, 0.796);
+    pub const BROWN: Color = Color::rgb(0.647, 0.165, 0.165);
+    pub const NAVY: Color = Color::rgb(0.0, 0.0, 0.502);
+    pub const TEAL: Color = Color::rgb(0.0, 0.502, 0.502);
+    pub const OLIVE: Color = Color::rgb(0.502, 0.502, 0.0);
+    pub const MAROON: Color = Color::rgb(0.502, 0.0, 0.0);
+    pub const GOLD: Color = Color::rgb(1.0, 0.843, 0.0);
+    pub const SKY_BLUE: Color = Color::rgb(0.529, 0.808, 0.922);
+    pub const CORNFLOWER_BLUE: Color = Color::rgb(0.392, 0.584, 0.929);
+
+    pub const fn rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// an opaque color.
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self::rgba(r, g, b, 1.0)
+    }
+
+    /// parse `#rgb`, `#rrggbb` or `#rrggbbaa`, the `#` is optional.
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        let invalid = || anyhow!("Invalid hex color {}.", hex);
+        if !digits.is_ascii() {
+            return Err(invalid());
+        }
+
+        // short colors repeat each digit
+        let channels = match digits.len() {
+            3 => digits
+                .chars()
+                .map(|c| u8::from_str_radix(&c.to_string().repeat(2), 16))
+                .collect::<Result<Vec<_>, _>>(),
+            6 | 8 => (0..digits.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&digits[i..i + 2], 16))
+                .collect::<Result<Vec<_>, _>>(),
+            _ => return Err(invalid()),
+        }
+        .map_err(|_| invalid())?;
+
+        let channel = |i: usize| channels.get(i).map_or(1.0, |c| *c as f32 / 255.0);
+        Ok(Self::rgba(channel(0), channel(1), channel(2), channel(3)))
+    }
+
+    /// the color of `hue` in degrees, `saturation` and `value`, opaque.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let chroma = value * saturation;
+        Self::from_hue(hue, chroma, value - chroma)
+    }
+
+    /// the hue in degrees, saturation and value of the color.
+    pub fn to_hsv(&self) -> (f32, f32, f32) {
+        let (hue, max, chroma) = self.hue();
+        let saturation = if max > 0.0 { chroma / max } else { 0.0 };
+        (hue, saturation, max)
+    }
+
+    /// the color of `hue` in degrees, `saturation` and `lightness`, opaque.
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        Self::from_hue(hue, chroma, lightness - chroma / 2.0)
+    }
+
+    /// the hue in degrees, saturation and lightness of the color.
+    pub fn to_hsl(&self) -> (f32, f32, f32) {
+        let (hue, max, chroma) = self.hue();
+        let lightness = max - chroma / 2.0;
+        let saturation = if lightness > 0.0 && lightness < 1.0 {
+            chroma / (1.0 - (2.0 * lightness - 1.0).abs())
+        } else {
+            0.0
+        };
+        (hue, saturation, lightness)
+    }
+
+    /// decode srgb color to linear, alpha is linear already.
+    pub fn to_linear(&self) -> Self {
+        Self::rgba(
+            srgb_to_linear(self.r),
+            srgb_to_linear(self.g),
+            srgb_to_linear(self.b),
+            self.a,
+        )
+    }
+
+    /// encode linear color as srgb, alpha stays linear.
+    pub fn to_srgb(&self) -> Self {
+        Self::rgba(
+            linear_to_srgb(self.r),
+            linear_to_srgb(self.g),
+            linear_to_srgb(self.b),
+            self.a,
+        )
+    }
+
+    /// blend towards `other` by `t`, component wise in the color space both are in.
+    pub fn lerp(&self, other: Color, t: f32) -> Self {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        Self::rgba(
+            mix(self.r, other.r),
+            mix(self.g, other.g),
+            mix(self.b, other.b),
+            mix(self.a, other.a),
+        )
+    }
+
+    pub fn with_alpha(&self, a: f32) -> Self {
+        Self { a, ..*self }
+    }
+
+    // the rgb of a hue with `chroma`, lifted by `min`
+    fn from_hue(hue: f32, chroma: f32, min: f32) -> Self {
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (r, g, b) = match sector as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        Self::rgb(r + min, g + min, b + min)
+    }
+
+    // the hue in degrees, the largest component and the chroma
+    fn hue(&self) -> (f32, f32, f32) {
+        let max = self.r.max(self.g).max(self.b);
+        let chroma = max - self.r.min(self.g).min(self.b);
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == self.r {
+            60.0 * ((self.g - self.b) / chroma).rem_euclid(6.0)
+        } else if max == self.g {
+            60.0 * ((self.b - self.r) / chroma + 2.0)
+        } else {
+            60.0 * ((self.r - self.g) / chroma + 4.0)
+        };
+        (hue, max, chroma)
+    }
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Self::BLACK
+    }
+}
+
+impl From<[f32; 4]> for Color {
+    fn from(c: [f32; 4]) -> Self {
+        Self::rgba(c[0], c[1], c[2], c[3])
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(c: Color) -> Self {
+        [c.r, c.g, c.b, c.a]
+    }
+}
+
+impl From<Color> for Vec4 {
+    fn from(c: Color) -> Self {
+        Vec4::new(c.r, c.g, c.b, c.a)
+    }
+}
+
+impl From<Color> for vk::ClearColorValue {
+    fn from(c: Color) -> Self {
+        vk::ClearColorValue { float32: c.into() }
+    }
+}
+
+impl From<Color> for vk::ClearValue {
+    fn from(c: Color) -> Self {
+        vk::ClearValue { color: c.into() }
+    }
+}
+
+/// decode an srgb encoded component to linear.
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// encode a linear component as srgb.
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
@@ -0,0 +1,211 @@
+#![allow(dead_code)]
+
+use anyhow::Result;
+use vulkanalia::prelude::v1_0::*;
+
+pub struct CommandPool {
+    pub pool: vk::CommandPool,
+}
+
+impl CommandPool {
+    pub unsafe fn create(
+        device: &vulkanalia::Device,
+        family: u32,
+        flags: vk::CommandPoolCreateFlags,
+    ) -> Result<CommandPool> {
+        // info to use for creating the pool
+        let info = vk::CommandPoolCreateInfo::builder()
+            .flags(flags)
+            .queue_family_index(family);
+
+        // all done
+        Ok(CommandPool {
+            pool: device.create_command_pool(&info, None)?,
+        })
+    }
+
+    pub unsafe fn allocate(
+        &self,
+        device: &vulkanalia::Device,
+        level: vk::CommandBufferLevel,
+        count: u32,
+    ) -> Result<Vec<CommandBuffer>> {
+        // info to use for allocating the buffers
+        let info = vk::CommandBufferAllocateInfo::builder()
+            .command_pool(self.pool)
+            .level(level)
+            .command_buffer_count(count);
+
+        // allocate and wrap the buffers
+        Ok(device
+            .allocate_command_buffers(&info)?
+            .into_iter()
+            .map(CommandBuffer::create)
+            .collect())
+    }
+
+    pub unsafe fn reset(&self, device: &vulkanalia::Device) -> Result<()> {
+        // reset all buffers allocated from the pool
+        device.reset_command_pool(self.pool, vk::CommandPoolResetFlags::empty())?;
+
+        // all done
+        Ok(())
+    }
+
+    pub unsafe fn begin_single(&self, device: &vulkanalia::Device) -> Result<CommandBuffer> {
+        // allocate a single primary buffer
+        let buffer = self.allocate(device, vk::CommandBufferLevel::PRIMARY, 1)?[0];
+
+        // the buffer is only submitted once
+        let info = vk::CommandBufferBeginInfo::builder()
+            .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+
+        // begin the buffer
+        device.begin_command_buffer(buffer.buffer, &info)?;
+
+        // all done
+        Ok(buffer)
+    }
+
+    pub unsafe fn end_single(
+        &self,
+        device: &vulkanalia::Device,
+        queue: vk::Queue,
+        buffer: CommandBuffer,
+    ) -> Result<()> {
+        // end the buffer
+        device.end_command_buffer(buffer.buffer)?;
+
+        // submit and wait for the queue to drain
+        let buffers = &[buffer.buffer];
+        let info = vk::SubmitInfo::builder().command_buffers(buffers);
+        device.queue_submit(queue, &[info], vk::Fence::null())?;
+        device.queue_wait_idle(queue)?;
+
+        // cleanup
+        device.free_command_buffers(self.pool, buffers);
+
+        // all done
+        Ok(())
+    }
+
+    /// free a buffer from `begin_single` without submitting it, e.g. when recording failed.
+    /// it can still be recording, buffers are freed in any state but pending.
+    pub unsafe fn discard_single(&self, device: &vulkanalia::Device, buffer: CommandBuffer) {
+        device.free_command_buffers(self.pool, &[buffer.buffer]);
+    }
+
+    pub unsafe fn destroy(&self, device: &Device) {
+        // destroy the pool, this frees all its buffers as well
+        device.destroy_command_pool(self.pool, None);
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CommandBuffer {
+    pub buffer: vk::CommandBuffer,
+}
+
+impl CommandBuffer {
+    pub fn create(buffer: vk::CommandBuffer) -> Self {
+        Self { buffer }
+    }
+}
+
+/// The subpass secondary command buffers draw in, what they inherit from the primary command
+/// buffer that executes them. the framebuffer may be null when it isn't known yet.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PassInheritance {
+    pub render_pass: vk::RenderPass,
+    pub subpass: u32,
+    pub framebuffer: vk::Framebuffer,
+}
+
+impl PassInheritance {
+    /// the same pass one subpass further, after `next_subpass`.
+    pub fn next_subpass(self) -> Self {
+        Self {
+            subpass: self.subpass + 1,
+            ..self
+        }
+    }
+}
+
+/// Secondary command buffers recording draws of a subpass on one thread. command pools can't
+/// be used from two threads at once, so every recording thread has a pool of its own, the
+/// buffers are executed by the primary command buffer with `cmd_execute_commands` once they
+/// ended. `reset` makes all of them available again once the frame that executed them is
+/// complete.
+pub struct SecondaryPool {
+    pool: CommandPool,
+    buffers: Vec<vk::CommandBuffer>,
+    // the buffers handed out since the last reset
+    used: usize,
+}
+
+impl SecondaryPool {
+    pub unsafe fn create(device: &vulkanalia::Device, family: u32) -> Result<Self> {
+        // the buffers are only ever reset all at once
+        Ok(Self {
+            pool: CommandPool::create(device, family, vk::CommandPoolCreateFlags::TRANSIENT)?,
+            buffers: vec![],
+            used: 0,
+        })
+    }
+
+    /// begin a secondary command buffer continuing the subpass of `inheritance`, draws
+    /// recorded into it until `end` are executed in that subpass.
+    pub unsafe fn begin(
+        &mut self,
+        device: &vulkanalia::Device,
+        inheritance: &PassInheritance,
+    ) -> Result<vk::CommandBuffer> {
+        // allocate another buffer once all are in use
+        if self.used == self.buffers.len() {
+            let level = vk::CommandBufferLevel::SECONDARY;
+            let buffer = self.pool.allocate(device, level, 1)?[0];
+            self.buffers.push(buffer.buffer);
+        }
+        let buffer = self.buffers[self.used];
+
+        // the buffer continues the render pass of the primary buffer
+        let inheritance_info = vk::CommandBufferInheritanceInfo::builder()
+            .render_pass(inheritance.render_pass)
+            .subpass(inheritance.subpass)
+            .framebuffer(inheritance.framebuffer);
+        let info = vk::CommandBufferBeginInfo::builder()
+            .flags(
+                vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT
+                    | vk::CommandBufferUsageFlags::RENDER_PASS_CONTINUE,
+            )
+            .inheritance_info(&inheritance_info);
+        device.begin_command_buffer(buffer, &info)?;
+        self.used += 1;
+
+        // all done
+        Ok(buffer)
+    }
+
+    /// end a buffer from `begin`, it can be executed from here on.
+    pub unsafe fn end(&self, device: &vulkanalia::Device, buffer: vk::CommandBuffer) -> Result<()> {
+        device.end_command_buffer(buffer)?;
+        Ok(())
+    }
+
+    /// the buffers begun since the last reset.
+    pub fn buffers(&self) -> &[vk::CommandBuffer] {
+        &self.buffers[..self.used]
+    }
+
+    /// make all buffers available for recording again, the submissions executing them must
+    /// be complete.
+    pub unsafe fn reset(&mut self, device: &vulkanalia::Device) -> Result<()> {
+        self.pool.reset(device)?;
+        self.used = 0;
+        Ok(())
+    }
+
+    pub unsafe fn destroy(&self, device: &vulkanalia::Device) {
+        self.pool.destroy(device);
+    }
+}
@@ -0,0 +1,154 @@
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use anyhow::Result;
+use vulkanalia::prelude::v1_0::*;
+
+use super::{Buffer, Shader, Texture, TextureView, Timeline};
+
+/// A resource that is no longer used by new commands but may still be read by submitted ones.
+pub enum Retired {
+    Buffer(Buffer),
+    Texture(Texture),
+    TextureView(TextureView),
+    Shader(Shader),
+}
+
+impl Retired {
+    pub(super) unsafe fn destroy(&self, device: &vulkanalia::Device) {
+        match self {
+            Retired::Buffer(buffer) => buffer.destroy(device),
+            Retired::Texture(texture) => texture.destroy(device),
+            Retired::TextureView(view) => view.destroy(device),
+            Retired::Shader(shader) => shader.destroy(device),
+        }
+    }
+}
+
+impl From<Buffer> for Retired {
+    fn from(buffer: Buffer) -> Self {
+        Retired::Buffer(buffer)
+    }
+}
+
+impl From<Texture> for Retired {
+    fn from(texture: Texture) -> Self {
+        Retired::Texture(texture)
+    }
+}
+
+impl From<TextureView> for Retired {
+    fn from(view: TextureView) -> Self {
+        Retired::TextureView(view)
+    }
+}
+
+impl From<Shader> for Retired {
+    fn from(shader: Shader) -> Self {
+        Retired::Shader(shader)
+    }
+}
+
+/// Resources waiting for the submissions that may use them, each is destroyed once its
+/// timeline value completed. dropping a resource while a frame in flight still reads it is
+/// undefined, retiring it here is the safe way to let go of it mid run.
+#[derive(Default)]
+pub struct DeletionQueue {
+    // in the order they were retired, the values never decrease
+    retired: RefCell<Vec<(u64, Retired)>>,
+}
+
+impl DeletionQueue {
+    /// destroy `resource` once the submission with timeline value `value` completed.
+    pub fn retire(&self, value: u64, resource: impl Into<Retired>) {
+        let mut retired = self.retired.borrow_mut();
+        let value = retired.last().map_or(value, |(last, _)| value.max(*last));
+        retired.push((value, resource.into()));
+    }
+
+    /// whether `buffer` waits here for its destruction.
+    pub fn is_retired(&self, buffer: vk::Buffer) -> bool {
+        let retired = self.retired.borrow();
+        retired
+            .iter()
+            .any(|(_, r)| matches!(r, Retired::Buffer(b) if b.buffer == buffer))
+    }
+
+    /// the number of resources still waiting.
+    pub fn len(&self) -> usize {
+        self.retired.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// destroy the resources whose submissions on `timeline` completed, returns how many.
+    pub unsafe fn collect(
+        &self,
+        device: &vulkanalia::Device,
+        timeline: &Timeline,
+    ) -> Result<usize> {
+        let completed = timeline.completed(device)?;
+        let mut retired = self.retired.borrow_mut();
+        let count = retired.iter().take_while(|(v, _)| *v <= completed).count();
+        retired.drain(..count).for_each(|(_, r)| r.destroy(device));
+        Ok(count)
+    }
+
+    /// destroy every resource right away, the device must be idle.
+    pub unsafe fn destroy(&self, device: &vulkanalia::Device) {
+        let mut retired = self.retired.borrow_mut();
+        retired.drain(..).for_each(|(_, r)| r.destroy(device));
+    }
+}
+
+/// The last submission each buffer was recorded into, by timeline value. encoders fill it,
+/// see `Encoder::with_usage`, so buffers can be let go of while frames still read them.
+#[derive(Default)]
+pub struct ResourceUsage {
+    last_use: RefCell<HashMap<vk::Buffer, u64>>,
+}
+
+impl ResourceUsage {
+    /// `buffer` is read by the submission with timeline value `value`.
+    pub fn record(&self, buffer: vk::Buffer, value: u64) {
+        let mut last_use = self.last_use.borrow_mut();
+        let last = last_use.entry(buffer).or_insert(value);
+        *last = value.max(*last);
+    }
+
+    /// the value of the last submission using `buffer`, if any was recorded.
+    pub fn last_use(&self, buffer: vk::Buffer) -> Option<u64> {
+        self.last_use.borrow().get(&buffer).copied()
+    }
+
+    /// forget the uses up to `completed`, they can no longer hold anything back.
+    pub fn forget_completed(&self, completed: u64) {
+        self.last_use.borrow_mut().retain(|_, v| *v > completed);
+    }
+
+    /// destroy `buffer` right away when no submission after `completed` uses it, otherwise
+    /// retire it to `deletion` until its last one completed. returns whether it was deferred.
+    pub unsafe fn destroy(
+        &self,
+        device: &vulkanalia::Device,
+        deletion: &DeletionQueue,
+        completed: u64,
+        buffer: Buffer,
+    ) -> bool {
+        let last_use = self.last_use.borrow_mut().remove(&buffer.buffer);
+        match last_use {
+            Some(value) if value > completed => {
+                deletion.retire(value, buffer);
+                true
+            }
+            _ => {
+                buffer.destroy(device);
+                false
+            }
+        }
+    }
+}
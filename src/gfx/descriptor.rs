@@ -0,0 +1,353 @@
+#![allow(dead_code)]
+
+use anyhow::{anyhow, Result};
+use tracing::debug;
+use vulkanalia::prelude::v1_0::*;
+
+use super::{check_bindings, check_writes, Buffer};
+
+/// The set index of constants shared by all draws of a frame, camera and lights.
+pub const FRAME_SET: u32 = 0;
+/// The set index of the inputs of the current pass, previous targets or the environment.
+pub const PASS_SET: u32 = 1;
+/// The set index of textures and factors shared by all draws of a material.
+pub const MATERIAL_SET: u32 = 2;
+/// The set index of data owned by a single draw.
+pub const OBJECT_SET: u32 = 3;
+
+/// The descriptor set convention shared by the engine and user shaders, every set layout
+/// belongs to one slot and shaders declare its bindings at `layout(set = slot index)`.
+/// Pipeline layouts built with `Device::create_pipeline_layout` put each layout at its
+/// slot and refuse two layouts for the same slot.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DescriptorSlot {
+    Frame,
+    Pass,
+    Material,
+    Object,
+}
+
+impl DescriptorSlot {
+    /// all slots in set index order.
+    pub const ALL: [DescriptorSlot; 4] = [
+        DescriptorSlot::Frame,
+        DescriptorSlot::Pass,
+        DescriptorSlot::Material,
+        DescriptorSlot::Object,
+    ];
+
+    /// the set index shaders use for the slot.
+    pub fn index(&self) -> u32 {
+        match self {
+            DescriptorSlot::Frame => FRAME_SET,
+            DescriptorSlot::Pass => PASS_SET,
+            DescriptorSlot::Material => MATERIAL_SET,
+            DescriptorSlot::Object => OBJECT_SET,
+        }
+    }
+}
+
+/// order set layouts by their slot, slots below the highest used one get `empty`.
+/// fails when two layouts claim the same slot.
+pub fn slot_set_layouts(
+    sets: &[&DescriptorSets],
+    empty: vk::DescriptorSetLayout,
+) -> Result<Vec<vk::DescriptorSetLayout>> {
+    let mut layouts = vec![None; DescriptorSlot::ALL.len()];
+    for set in sets {
+        let index = set.slot.index() as usize;
+        if layouts[index].is_some() {
+            return Err(anyhow!(
+                "Two descriptor set layouts use the {:?} slot (set {}).",
+                set.slot,
+                index
+            ));
+        }
+        layouts[index] = Some(set.layout);
+    }
+
+    // trailing unused slots are left out of the pipeline layout
+    let count = layouts
+        .iter()
+        .rposition(|l| l.is_some())
+        .map_or(0, |i| i + 1);
+    Ok(layouts[..count]
+        .iter()
+        .map(|l| l.unwrap_or(empty))
+        .collect())
+}
+
+/// A resource written to a binding of a descriptor set, the binding's descriptor type in the
+/// layout decides how it is read.
+#[derive(Copy, Clone, Debug)]
+pub enum BindingResource {
+    /// a range of a uniform or storage buffer.
+    Buffer {
+        buffer: vk::Buffer,
+        offset: vk::DeviceSize,
+        range: vk::DeviceSize,
+    },
+    Sampler(vk::Sampler),
+    /// a view sampled in the shader read only layout, or an input attachment.
+    Texture(vk::ImageView),
+    /// a view with the sampler it is read with, a combined image sampler.
+    SampledTexture(vk::ImageView, vk::Sampler),
+    /// a view written by shaders in the general layout.
+    StorageTexture(vk::ImageView),
+}
+
+impl BindingResource {
+    /// the whole of `buffer`.
+    pub fn buffer(buffer: &Buffer) -> Self {
+        BindingResource::Buffer {
+            buffer: buffer.buffer,
+            offset: 0,
+            range: vk::WHOLE_SIZE as vk::DeviceSize,
+        }
+    }
+
+    /// whether the resource can be written to a binding of `type_`.
+    pub fn accepts(&self, type_: vk::DescriptorType) -> bool {
+        match self {
+            BindingResource::Buffer { .. } => matches!(
+                type_,
+                vk::DescriptorType::UNIFORM_BUFFER
+                    | vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC
+                    | vk::DescriptorType::STORAGE_BUFFER
+                    | vk::DescriptorType::STORAGE_BUFFER_DYNAMIC
+            ),
+            BindingResource::Sampler(_) => type_ == vk::DescriptorType::SAMPLER,
+            BindingResource::Texture(_) => matches!(
+                type_,
+                vk::DescriptorType::SAMPLED_IMAGE | vk::DescriptorType::INPUT_ATTACHMENT
+            ),
+            BindingResource::SampledTexture(..) => {
+                type_ == vk::DescriptorType::COMBINED_IMAGE_SAMPLER
+            }
+            BindingResource::StorageTexture(_) => type_ == vk::DescriptorType::STORAGE_IMAGE,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BindingResource::Buffer { .. } => "buffer",
+            BindingResource::Sampler(_) => "sampler",
+            BindingResource::Texture(_) => "texture",
+            BindingResource::SampledTexture(..) => "sampled texture",
+            BindingResource::StorageTexture(_) => "storage texture",
+        }
+    }
+}
+
+/// Descriptor sets of a single layout, one per swapchain image, allocated on first use.
+/// Sets are never freed, so a set can be rewritten once its image is acquired again.
+pub struct DescriptorSets {
+    pub slot: DescriptorSlot,
+    pub layout: vk::DescriptorSetLayout,
+    // the descriptor type of each binding, writes are checked against them
+    bindings: Vec<(u32, vk::DescriptorType)>,
+    sizes: Vec<vk::DescriptorPoolSize>,
+    pools: Vec<vk::DescriptorPool>,
+    sets: Vec<vk::DescriptorSet>,
+}
+
+impl DescriptorSets {
+    pub unsafe fn create(
+        device: &vulkanalia::Device,
+        slot: DescriptorSlot,
+        bindings: &[vk::DescriptorSetLayoutBinding],
+    ) -> Result<Self> {
+        // create the layout
+        check_bindings(bindings)?;
+        let info = vk::DescriptorSetLayoutCreateInfo::builder().bindings(bindings);
+        let layout = device.create_descriptor_set_layout(&info, None)?;
+
+        // the pool sizes needed by a single set
+        let sizes = bindings
+            .iter()
+            .map(|b| {
+                vk::DescriptorPoolSize::builder()
+                    .type_(b.descriptor_type)
+                    .descriptor_count(b.descriptor_count)
+                    .build()
+            })
+            .collect();
+
+        Ok(Self {
+            slot,
+            layout,
+            bindings: bindings
+                .iter()
+                .map(|b| (b.binding, b.descriptor_type))
+                .collect(),
+            sizes,
+            pools: vec![],
+            sets: vec![],
+        })
+    }
+
+    /// the set of a swapchain image, missing sets are allocated from a new pool.
+    pub unsafe fn get(
+        &mut self,
+        device: &vulkanalia::Device,
+        index: usize,
+    ) -> Result<vk::DescriptorSet> {
+        if index >= self.sets.len() {
+            let count = (index + 1 - self.sets.len()) as u32;
+
+            // grow the pool sizes to hold all missing sets
+            let sizes = self
+                .sizes
+                .iter()
+                .map(|s| {
+                    vk::DescriptorPoolSize::builder()
+                        .type_(s.type_)
+                        .descriptor_count(s.descriptor_count * count)
+                        .build()
+                })
+                .collect::<Vec<_>>();
+
+            let info = vk::DescriptorPoolCreateInfo::builder()
+                .pool_sizes(&sizes)
+                .max_sets(count);
+            let pool = device.create_descriptor_pool(&info, None)?;
+            self.pools.push(pool);
+
+            // allocate the sets
+            let layouts = vec![self.layout; count as usize];
+            let info = vk::DescriptorSetAllocateInfo::builder()
+                .descriptor_pool(pool)
+                .set_layouts(&layouts);
+            self.sets.extend(device.allocate_descriptor_sets(&info)?);
+        }
+
+        Ok(self.sets[index])
+    }
+
+    /// the set of a swapchain image if `get` allocated it already.
+    pub fn allocated(&self, index: usize) -> Option<vk::DescriptorSet> {
+        self.sets.get(index).copied()
+    }
+
+    /// write resources to the bindings of `set`, each goes to the first element of its
+    /// binding. fails without writing anything when a binding is not in the layout or its
+    /// descriptor type doesn't take the resource.
+    pub unsafe fn write(
+        &self,
+        device: &vulkanalia::Device,
+        set: vk::DescriptorSet,
+        writes: &[(u32, BindingResource)],
+    ) -> Result<()> {
+        check_writes(&self.bindings, writes)?;
+
+        // the infos have to outlive the writes pointing at them
+        let mut buffers = Vec::with_capacity(writes.len());
+        let mut images = Vec::with_capacity(writes.len());
+        for (_, resource) in writes {
+            match *resource {
+                BindingResource::Buffer {
+                    buffer,
+                    offset,
+                    range,
+                } => buffers.push(
+                    vk::DescriptorBufferInfo::builder()
+                        .buffer(buffer)
+                        .offset(offset)
+                        .range(range)
+                        .build(),
+                ),
+                BindingResource::Sampler(sampler) => {
+                    images.push(vk::DescriptorImageInfo::builder().sampler(sampler).build())
+                }
+                BindingResource::Texture(view) => images.push(image_info(
+                    view,
+                    vk::Sampler::null(),
+                    vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                )),
+                BindingResource::SampledTexture(view, sampler) => images.push(image_info(
+                    view,
+                    sampler,
+                    vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                )),
+                BindingResource::StorageTexture(view) => images.push(image_info(
+                    view,
+                    vk::Sampler::null(),
+                    vk::ImageLayout::GENERAL,
+                )),
+            }
+        }
+
+        let (mut buffer, mut image) = (0, 0);
+        let writes = writes
+            .iter()
+            .map(|(binding, resource)| {
+                let write = vk::WriteDescriptorSet::builder()
+                    .dst_set(set)
+                    .dst_binding(*binding)
+                    .descriptor_type(self.binding_type(*binding));
+                if let BindingResource::Buffer { .. } = resource {
+                    buffer += 1;
+                    write.buffer_info(&buffers[buffer - 1..buffer]).build()
+                } else {
+                    image += 1;
+                    write.image_info(&images[image - 1..image]).build()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        debug!(?set, count = writes.len(), "write descriptor set");
+        device.update_descriptor_sets(&writes, &[] as &[vk::CopyDescriptorSet]);
+        Ok(())
+    }
+
+    /// bind a set at the set index of the slot.
+    pub unsafe fn bind(
+        &self,
+        device: &vulkanalia::Device,
+        command_buffer: vk::CommandBuffer,
+        bind_point: vk::PipelineBindPoint,
+        layout: vk::PipelineLayout,
+        set: vk::DescriptorSet,
+    ) {
+        debug!(first = self.slot.index(), ?set, "bind descriptor set");
+        device.cmd_bind_descriptor_sets(
+            command_buffer,
+            bind_point,
+            layout,
+            self.slot.index(),
+            &[set],
+            &[],
+        );
+    }
+
+    // the descriptor type of a binding that `check_writes` found in the layout
+    fn binding_type(&self, binding: u32) -> vk::DescriptorType {
+        self.bindings
+            .iter()
+            .find(|(b, _)| *b == binding)
+            .map(|(_, t)| *t)
+            .expect("the binding is in the layout")
+    }
+
+    pub unsafe fn destroy(&self, device: &vulkanalia::Device) {
+        // destroying the pools frees the sets
+        self.pools
+            .iter()
+            .for_each(|p| device.destroy_descriptor_pool(*p, None));
+
+        // destroy the layout
+        device.destroy_descriptor_set_layout(self.layout, None);
+    }
+}
+
+fn image_info(
+    view: vk::ImageView,
+    sampler: vk::Sampler,
+    layout: vk::ImageLayout,
+) -> vk::DescriptorImageInfo {
+    vk::DescriptorImageInfo::builder()
+        .image_view(view)
+        .sampler(sampler)
+        .image_layout(layout)
+        .build()
+}
@@ -6,229 +6,2896 @@
     clippy::unnecessary_wraps
 )]
 
-use std::collections::HashSet;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::ffi::CStr;
 use std::os::raw::c_void;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Result};
-use log::*;
+use tracing::{debug, debug_span, error, info, info_span, trace, warn};
 use vulkanalia::loader::{LibloadingLoader, LIBRARY};
 use vulkanalia::prelude::v1_0::*;
-use vulkanalia::window as vk_window;
 use vulkanalia::Version;
-use winit::window::Window;
+
+use super::{DeviceWindow, Frame, HeadlessWindow, NullBackend};
 
 use vulkanalia::vk::ExtDebugUtilsExtension;
+use vulkanalia::vk::GoogleDisplayTimingExtension;
 use vulkanalia::vk::KhrSurfaceExtension;
 use vulkanalia::vk::KhrSwapchainExtension;
 
-use super::{
-    FrameBuffer, QueueFamilyIndices, SuitabilityError, SwapChainSupport, Texture, TextureView,
-};
+use crate::debug::CheckedCapture;
+
+use super::allocation::{self, Allocation, AllocationHooks, AllocationKind, AllocationStats};
+use super::capture::{self, CaptureLoader};
+use super::names::DebugNames;
+use super::{
+    buffer_memory_properties, check_buffer, check_feature, check_format_feature,
+    check_pipeline_layout, check_pixels, check_texture, clip_rects, device_features,
+    logical_extent, max_mip_levels, present_modes, read_png, record_texture_readback,
+    record_validation_message, render_extent, scale_rect, scissored_clears, set_viewports,
+    shared_queue_families, sharing_mode, shrink_pixels, slot_set_layouts, surface_extent,
+    validation_errors, write_bug_report, AdapterInfo, AdapterSelection, Buffer, BugReportSettings,
+    CommandBuffer, CommandPool, DeletionQueue, DescriptorSets, DeviceEvent, DeviceEventKind,
+    DeviceEvents, Encoder, ExportedFrame, Feature, FrameBuffer, FrameClock, FrameExportSettings,
+    FrameSink, FrameTrace, GpuFuture, GraphicsSettings, LatencyStats, LatencyTracker, ObserverId,
+    PassInheritance, PassLoad, Pod, PresentMode, QueryKind, QuerySet, Queue, QueueFamilyIndices,
+    QueueKind, Registered, RenderPassCache, RenderPassDescriptor, ReportFrame, ResizeTracker,
+    ResourceCounts, ResourceRegistry, ResourceUsage, Retired, SamplerCache, SamplerDescriptor,
+    SceneAttachments, StagingBelt, SubmitGraph, SuitabilityError, SurfaceFormat, SwapChainSupport,
+    SwapchainRebuild, SwapchainShape, Texture, TextureImport, TexturePixels, TextureView, Timeline,
+    TimelineWait, ValidationSettings, WorkDone,
+};
+
+// The colors of the labeled regions of a frame.
+const PREPARE_LABEL: [f32; 4] = [0.9, 0.6, 0.2, 1.0];
+const SCENE_LABEL: [f32; 4] = [0.2, 0.6, 0.9, 1.0];
+const POST_LABEL: [f32; 4] = [0.6, 0.3, 0.9, 1.0];
+
+// The name of the validation layers.
+pub(super) const VALIDATION_LAYER: vk::ExtensionName =
+    vk::ExtensionName::from_bytes(b"VK_LAYER_KHRONOS_validation");
+
+// The required device extensions.
+const DEVICE_EXTENSIONS: &[vk::ExtensionName] = &[vk::KHR_SWAPCHAIN_EXTENSION.name];
+/// The Vulkan SDK version that started requiring the portability subset extension for macOS.
+const PORTABILITY_MACOS_VERSION: Version = Version::new(1, 3, 216);
+
+// The maximum number of frames that can be processed concurrently.
+const MAX_FRAMES_IN_FLIGHT: usize = 2;
+// the frames latency stats average over
+const LATENCY_HISTORY: usize = 120;
+
+// The bytes of per frame data each frame in flight starts with, see `Device::allocate`.
+const STAGING_SIZE: vk::DeviceSize = 4 << 20;
+
+/// The format of the hdr scene color target.
+pub const SCENE_FORMAT: vk::Format = vk::Format::R16G16B16A16_SFLOAT;
+
+struct DeviceCommandData {
+    pool: CommandPool,
+    // one time pools of the dedicated compute and transfer queues
+    queue_pools: Vec<(QueueKind, CommandPool)>,
+    pools: Vec<CommandPool>,
+    primary_command_buffers: Vec<CommandBuffer>,
+}
+
+struct DeviceSyncData {
+    textures_available_semaphores: Vec<vk::Semaphore>,
+    render_finished_semaphores: Vec<vk::Semaphore>,
+    // every frame submission signals the next value
+    timeline: Timeline,
+    // the value of the frame last submitted from each frame slot and to each image
+    in_flight_frames: Vec<u64>,
+    in_flight_textures: Vec<u64>,
+    // timeline values of other queues the next frame waits for
+    dependencies: Vec<TimelineWait>,
+}
+
+struct DeviceTargetData {
+    // the swapchain extent scaled by the render scale
+    extent: vk::Extent2D,
+    albedo_texture: Texture,
+    albedo_texture_view: TextureView,
+    depth_texture: Texture,
+    depth_texture_view: TextureView,
+    scene_texture: Texture,
+    scene_texture_view: TextureView,
+}
+
+// the render passes depend on formats only, they live as long as the device and pipelines
+// created with them survive resizes
+struct RenderPassData {
+    scene_render_pass: vk::RenderPass,
+    scene_partial_render_pass: vk::RenderPass,
+    // loads everything, the damage is cleared rectangle by rectangle
+    scene_partial_load_render_pass: vk::RenderPass,
+    scene_transparent_render_pass: vk::RenderPass,
+    present_render_pass: vk::RenderPass,
+    // the swapchain format the present pass writes
+    present_format: vk::Format,
+}
+
+// everything sized by the swapchain extent or image count, rebuilt on resizes
+struct SwapchainData {
+    handle: vk::SwapchainKHR,
+    extent: vk::Extent2D,
+    format: vk::Format,
+    present_framebuffers: Vec<FrameBuffer>,
+    scene_framebuffer: FrameBuffer,
+    textures: Vec<Texture>,
+    views: Vec<TextureView>,
+    target: DeviceTargetData,
+}
+
+struct DamageData {
+    enabled: bool,
+    // rectangles reported since the last frame
+    rects: Vec<vk::Rect2D>,
+    // the area recorded by the current frame
+    area: vk::Rect2D,
+    // whether the scene target holds a complete previous frame
+    valid: bool,
+    // clear and draw the damaged rectangles one by one instead of their bounds
+    scissored: bool,
+    // the damaged rectangles in scene pixels, cleared one by one this frame when not empty
+    scissors: Vec<vk::Rect2D>,
+}
+
+struct FrameExportData {
+    clock: FrameClock,
+    sink: Box<dyn FrameSink + Send>,
+    started: Instant,
+    // one copy per frame in flight, read once the slot comes around so capturing never stalls
+    readbacks: Vec<Buffer>,
+    // the index, timestamp and extent of the capture each slot copies
+    pending: Vec<Option<(u64, Duration, vk::Extent2D)>>,
+}
+
+struct LatencyData {
+    tracker: LatencyTracker,
+    started: Instant,
+    // the id the next presented frame gets
+    next_id: u32,
+    // the id of the frame each slot presented, until seen complete without display timing
+    slots: [Option<u32>; MAX_FRAMES_IN_FLIGHT],
+}
+
+struct BugReportData {
+    settings: BugReportSettings,
+    // per swapchain image copies of the presented color, empty when not readable
+    readbacks: Vec<Buffer>,
+    // the image submitted with each frame in flight
+    submitted: Vec<Option<usize>>,
+    // the image of the last frame known to be complete
+    completed: Option<usize>,
+    // the image of the last submitted frame
+    last: Option<usize>,
+    // the validation error count when the last report was written
+    reported_errors: usize,
+    // the passes recorded each frame, as described by the renderer
+    passes: Vec<String>,
+}
+
+// the offscreen attachments the scene draws go to instead of the scene target
+#[derive(Copy, Clone)]
+struct ActiveTarget {
+    framebuffer: vk::Framebuffer,
+    extent: vk::Extent2D,
+    depth_view: vk::ImageView,
+}
+
+struct QueueData {
+    graphics_family: u32,
+    graphics: vk::Queue,
+    present_family: u32,
+    present: vk::Queue,
+    // the first queue of the dedicated families
+    compute: Option<(u32, vk::Queue)>,
+    transfer: Option<(u32, vk::Queue)>,
+    // the families the buffers and textures of the device are concurrent across
+    shared: Vec<u32>,
+}
+
+/// The vulkan device of a window with its swapchain. a device is `Send`, it can move to a
+/// render thread that acquires, submits and presents while the event loop thread keeps the
+/// window and forwards its resizes. it isn't `Sync`, one thread uses it at a time, command
+/// buffers are recorded on other threads with a `SecondaryPool` each.
+pub struct Device {
+    entry: Entry,
+    instance: vulkanalia::Instance,
+    surface: vk::SurfaceKHR,
+    physical: vk::PhysicalDevice,
+    adapter: AdapterInfo,
+    // the optional features enabled at creation
+    features: HashSet<Feature>,
+    device: vulkanalia::Device,
+    samples: vk::SampleCountFlags,
+    messenger: Option<vk::DebugUtilsMessengerEXT>,
+    names: DebugNames,
+    render_passes: RenderPassData,
+    swapchain: SwapchainData,
+    queue: QueueData,
+    commands: DeviceCommandData,
+    sync: DeviceSyncData,
+    staging: StagingBelt,
+    deletion: DeletionQueue,
+    // the resources code holds by id, destroyed with the device
+    registry: RefCell<ResourceRegistry>,
+    usage: ResourceUsage,
+    samplers: SamplerCache,
+    settings: GraphicsSettings,
+    passes: RenderPassCache,
+    damage: DamageData,
+    // what the scene pass does with the scene target
+    scene_load: PassLoad,
+    // whether scene draws are recorded inline or in secondary command buffers
+    scene_contents: vk::SubpassContents,
+    // scene render passes with other load ops than the default, by their ops
+    load_render_passes: RefCell<HashMap<[vk::AttachmentLoadOp; 2], vk::RenderPass>>,
+    incremental_present: bool,
+    display_timing: bool,
+    timeline_semaphores: bool,
+    host_query_reset: bool,
+    report: Option<BugReportData>,
+    latency: Option<LatencyData>,
+    export: Option<FrameExportData>,
+    // set between begin_target_pass and end_target_pass
+    active_target: Cell<Option<ActiveTarget>>,
+    // set while the scene closure records, `extent` is the scene extent then
+    recording_scene: Cell<bool>,
+    // the scene target size relative to the swapchain and how post processing samples it
+    render_scale: f32,
+    upscale_filter: vk::Filter,
+    // the scene target is rebuilt at the next frame for a changed render scale
+    target_stale: bool,
+    // textures whose copies the next frame records
+    uploads: RefCell<Vec<StagedTexture>>,
+    // fills descriptor slots a pipeline doesn't use
+    empty_set_layout: vk::DescriptorSetLayout,
+    frame: usize,
+    resize: ResizeTracker,
+    // called on resizes, swapchain changes, frames and device loss
+    events: DeviceEvents,
+}
+
+impl Device {
+    pub fn create(window: &dyn DeviceWindow, title: &str) -> Result<Self> {
+        Self::create_with_adapter(window, title, AdapterSelection::First)
+    }
+
+    /// create the device on the adapter `selection` picks from `enumerate_adapters`.
+    pub fn create_with_adapter(
+        window: &dyn DeviceWindow,
+        title: &str,
+        selection: AdapterSelection,
+    ) -> Result<Self> {
+        Self::create_with_features(window, title, selection, &[])
+    }
+
+    /// create the device with the optional `features` enabled, fails with
+    /// `DescriptionError::FeatureUnavailable` when the adapter lacks one of them. validation
+    /// follows `DEIMOS_VALIDATION`, see `ValidationSettings::from_env`.
+    pub fn create_with_features(
+        window: &dyn DeviceWindow,
+        title: &str,
+        selection: AdapterSelection,
+        features: &[Feature],
+    ) -> Result<Self> {
+        let validation = ValidationSettings::from_env();
+        Self::create_with_validation(window, title, selection, features, validation)
+    }
+
+    /// create the device validated as `validation` says, whatever the build or environment.
+    /// fails when validation is enabled and the layer isn't installed.
+    pub fn create_with_validation(
+        window: &dyn DeviceWindow,
+        title: &str,
+        selection: AdapterSelection,
+        features: &[Feature],
+        validation: ValidationSettings,
+    ) -> Result<Self> {
+        unsafe {
+            // every device loads through the capture loader so it can capture at any time
+            let loader = CaptureLoader::new(LibloadingLoader::new(LIBRARY)?);
+            let entry = Entry::new(loader).map_err(|b| anyhow!("{}", b))?;
+            Self::create_on(entry, window, title, selection, features, validation)
+        }
+    }
+
+    /// create the device on the null driver `backend`, presenting to `window`. like
+    /// `HeadlessDevice::create_null` it needs no gpu, its frames run and nothing is shown.
+    pub fn create_null(backend: &NullBackend, window: &HeadlessWindow) -> Result<Self> {
+        unsafe {
+            // the driver checks the calls itself, there is no layer to load
+            let entry = Entry::new(backend.clone()).map_err(|e| anyhow!("{}", e))?;
+            let validation = ValidationSettings::disabled();
+            backend.enter(|| {
+                Self::create_on(
+                    entry,
+                    window,
+                    "null",
+                    AdapterSelection::First,
+                    &[],
+                    validation,
+                )
+            })
+        }
+    }
+
+    unsafe fn create_on(
+        entry: Entry,
+        window: &dyn DeviceWindow,
+        title: &str,
+        selection: AdapterSelection,
+        features: &[Feature],
+        validation: ValidationSettings,
+    ) -> Result<Self> {
+        let (instance, messenger) = create_instance(&entry, window, title, validation)?;
+        let names = DebugNames::new(validation.enabled || debug_utils_available(&entry)?);
+        let surface = window.create_surface(&instance)?;
+        let (physical, adapter) = pick_physical_device(&instance, &surface, selection)?;
+        let samples = get_max_msaa_samples(&instance, &physical);
+
+        // create the logical device
+        let (
+            device,
+            queue,
+            incremental_present,
+            display_timing,
+            timeline_semaphores,
+            multiview,
+            host_query_reset,
+        ) = create_logical_device(
+            &entry,
+            &instance,
+            &surface,
+            &physical,
+            features,
+            &adapter.features,
+            validation.enabled,
+        )?;
+        allocation::register(device.handle());
+        let features = features.iter().copied().collect();
+
+        // create the render passes for the format of the surface, then the swapchain
+        let support = SwapChainSupport::get(&instance, &surface, physical)?;
+        let format = get_surface_format(&support.formats).format;
+        let render_passes =
+            create_render_pass_objects(&instance, &physical, &device, &samples, format)?;
+        let swapchain = construct_swapchain(
+            window,
+            &instance,
+            &surface,
+            &physical,
+            &device,
+            &samples,
+            &render_passes,
+            1.0,
+            vk::SwapchainKHR::null(),
+        )?;
+
+        // the first frame is always drawn completely
+        let extent = swapchain.target.extent;
+
+        // create command objects
+        let commands = create_command_objects(&instance, &surface, &physical, &device, &swapchain)?;
+
+        // create sync objects
+        let sync = create_sync_objects(&device, &swapchain, timeline_semaphores)?;
+        let staging = StagingBelt::create(
+            &instance,
+            physical,
+            &device,
+            MAX_FRAMES_IN_FLIGHT,
+            STAGING_SIZE,
+        )?;
+        let samplers = SamplerCache::new(adapter.limits.max_sampler_anisotropy);
+
+        // create the layout of unused descriptor slots
+        let info = vk::DescriptorSetLayoutCreateInfo::builder();
+        let empty_set_layout = device.create_descriptor_set_layout(&info, None)?;
+
+        // init app instance
+        let device = Self {
+            entry,
+            instance,
+            surface,
+            physical,
+            adapter,
+            features,
+            device,
+            samples,
+            messenger,
+            names,
+            render_passes,
+            swapchain,
+            queue,
+            commands,
+            sync,
+            staging,
+            deletion: DeletionQueue::default(),
+            registry: RefCell::new(ResourceRegistry::new()),
+            usage: ResourceUsage::default(),
+            samplers,
+            settings: GraphicsSettings::default(),
+            passes: RenderPassCache::new(multiview),
+            damage: DamageData {
+                enabled: false,
+                rects: vec![],
+                area: vk::Rect2D {
+                    offset: vk::Offset2D::default(),
+                    extent,
+                },
+                valid: false,
+                scissored: false,
+                scissors: vec![],
+            },
+            scene_load: PassLoad::default(),
+            scene_contents: vk::SubpassContents::INLINE,
+            load_render_passes: RefCell::new(HashMap::new()),
+            incremental_present,
+            display_timing,
+            timeline_semaphores,
+            host_query_reset,
+            report: None,
+            latency: None,
+            export: None,
+            active_target: Cell::new(None),
+            recording_scene: Cell::new(false),
+            render_scale: 1.0,
+            upscale_filter: vk::Filter::LINEAR,
+            target_stale: false,
+            uploads: RefCell::new(vec![]),
+            empty_set_layout,
+            frame: 0,
+            resize: ResizeTracker::default().with_scale_factor(window.scale_factor()),
+            events: DeviceEvents::new(),
+        };
+        device.name_frame_objects();
+        Ok(device)
+    }
+
+    /// the adapter the device was created on.
+    pub fn adapter(&self) -> &AdapterInfo {
+        &self.adapter
+    }
+
+    /// the optional features the device was created with.
+    pub fn features(&self) -> &HashSet<Feature> {
+        &self.features
+    }
+
+    /// fail with `DescriptionError::FeatureNotEnabled` unless `feature` was enabled.
+    pub fn require_feature(&self, feature: Feature) -> Result<()> {
+        Ok(check_feature(feature, &self.features)?)
+    }
+
+    /// whether timelines are backed by timeline semaphores, otherwise they use fences.
+    pub fn supports_timeline_semaphores(&self) -> bool {
+        self.timeline_semaphores
+    }
+
+    /// whether the validation layer checks the calls, see `create_with_validation`.
+    pub fn is_validated(&self) -> bool {
+        self.messenger.is_some()
+    }
+
+    /// whether presents hint the changed regions to the compositor, see
+    /// `PostFrame::with_damage`. without `VK_KHR_incremental_present` the whole image is
+    /// presented as changed.
+    pub fn supports_incremental_present(&self) -> bool {
+        self.incremental_present
+    }
+
+    /// whether latency tracking sees when frames were displayed, otherwise it estimates it
+    /// from when they completed.
+    pub fn supports_display_timing(&self) -> bool {
+        self.display_timing
+    }
+
+    /// whether render passes can draw several views at once, see
+    /// `RenderPassDescriptor::multiview`.
+    pub fn supports_multiview(&self) -> bool {
+        self.passes.multiview()
+    }
+
+    /// create a timeline for work submitted outside the frames, e.g. on a transfer queue.
+    pub fn create_timeline(&self) -> Result<Timeline> {
+        unsafe { Timeline::create(&self.device, self.timeline_semaphores) }
+    }
+
+    /// whether query sets can be reset on the host with `reset_queries`, otherwise they are
+    /// reset with `Encoder::reset_query_set`.
+    pub fn supports_host_query_reset(&self) -> bool {
+        self.host_query_reset
+    }
+
+    /// create a set of `count` queries, timestamps need `Feature::TimestampQuery`.
+    pub fn create_query_set(&self, kind: QueryKind, count: u32) -> Result<QuerySet> {
+        create_query_set(&self.device, &self.features, kind, count)
+    }
+
+    /// reset `queries` of `set` on the host, none of them may be in use by the device.
+    pub fn reset_queries(&self, set: &QuerySet, queries: std::ops::Range<u32>) -> Result<()> {
+        if !self.host_query_reset {
+            return Err(anyhow!("The device can't reset queries on the host."));
+        }
+        unsafe { set.reset(&self.device, queries) }
+    }
+
+    /// the timeline every frame submission advances by one.
+    pub fn frame_timeline(&self) -> &Timeline {
+        &self.sync.timeline
+    }
+
+    /// the timeline value of the latest frame. resources the frame uses can be released
+    /// once `completed_frame` reached the value or `wait_for_frame` returned for it.
+    pub fn frame_value(&self) -> u64 {
+        self.sync.timeline.submitted()
+    }
+
+    /// the value of the latest frame known to be complete.
+    pub fn completed_frame(&self) -> Result<u64> {
+        unsafe { self.sync.timeline.completed(&self.device) }
+    }
+
+    /// block until the frame with `value` is complete.
+    pub fn wait_for_frame(&self, value: u64) -> Result<()> {
+        unsafe { self.sync.timeline.wait(&self.device, value) }
+    }
+
+    /// look at the frame timeline without blocking, like `wgpu::Device::poll`. the futures of
+    /// completed work wake and its callbacks run, every frame does it as well. returns the
+    /// completed value.
+    pub fn poll(&self) -> Result<u64> {
+        self.completed_frame()
+    }
+
+    /// the work of the frames submitted so far, done once the latest frame is complete.
+    /// `then` callbacks run in a later `update` without blocking it.
+    pub fn on_submitted_work_done(&self) -> WorkDone<'_> {
+        self.sync
+            .timeline
+            .work_done(&self.device, self.frame_value())
+    }
+
+    /// make the next frame wait on the gpu until `timeline` reached `value`, e.g. for an
+    /// upload on another queue. without timeline semaphores this blocks right away instead.
+    pub fn wait_for_timeline(&mut self, timeline: &Timeline, value: u64) -> Result<()> {
+        let stage = vk::PipelineStageFlags::ALL_COMMANDS;
+        if let Some(wait) = unsafe { timeline.gpu_wait(&self.device, value, stage)? } {
+            self.sync.dependencies.push(wait);
+        }
+        Ok(())
+    }
+
+    /// submit `graph` now and make the next frame wait for the submits at its ends, e.g. a
+    /// shadow pass on another queue and the passes after it. returns the timeline value of
+    /// every submit by index.
+    pub fn submit_before_frame(&mut self, graph: SubmitGraph) -> Result<Vec<u64>> {
+        let leaves = graph
+            .leaves()
+            .into_iter()
+            .map(|id| (id, graph.timeline(id)))
+            .collect::<Vec<_>>();
+        let values = unsafe { graph.submit(&self.device)? };
+        for (id, timeline) in leaves {
+            self.wait_for_timeline(timeline, values[id.index()])?;
+        }
+        Ok(values)
+    }
+
+    /// the window was resized, the swapchain follows once the size settled. a zero size
+    /// means the window was minimized, frames are skipped until it has an area again and
+    /// the first frame after the restore recreates the swapchain.
+    pub fn resized(&mut self, width: u32, height: u32) {
+        self.resize.resized(width, height);
+        self.events.emit(DeviceEvent::Resized { width, height });
+    }
+
+    /// the window went fullscreen or back, see `window::DisplayController`. the swapchain is
+    /// recreated for the new size before the next frame.
+    pub fn display_mode_changed(&mut self, width: u32, height: u32) {
+        self.resize.mode_changed(width, height);
+        self.events.emit(DeviceEvent::Resized { width, height });
+    }
+
+    /// the window moved to a display with another scale factor, `width` and `height` are
+    /// the new size in physical pixels winit asks for. the swapchain is recreated at that
+    /// size before the next frame.
+    pub fn scale_factor_changed(&mut self, scale_factor: f64, width: u32, height: u32) {
+        self.resize.scale_changed(scale_factor, width, height);
+        self.events.emit(DeviceEvent::Resized { width, height });
+    }
+
+    /// physical pixels per logical pixel of the display the window is on.
+    pub fn scale_factor(&self) -> f64 {
+        self.resize.scale_factor()
+    }
+
+    /// the size of `extent` in logical pixels, the unit of window sizes and of ui like line
+    /// widths. `extent` is in physical pixels, those the swapchain and viewports have.
+    pub fn logical_extent(&self) -> (f32, f32) {
+        logical_extent(self.extent(), self.scale_factor())
+    }
+
+    /// whether frames are skipped because the window has no area.
+    pub fn is_minimized(&self) -> bool {
+        self.resize.is_minimized()
+    }
+
+    /// call `observer` when the window changed its size, mode or scale factor. observers
+    /// of an event are called in the order they were registered.
+    pub fn on_resize<F>(&mut self, observer: F) -> ObserverId
+    where
+        F: FnMut(&DeviceEvent) + Send + 'static,
+    {
+        self.events.observe(DeviceEventKind::Resized, observer)
+    }
+
+    /// call `observer` once a new swapchain replaced the old one, e.g. to rebuild what
+    /// depends on the surface format.
+    pub fn on_swapchain_recreated<F>(&mut self, observer: F) -> ObserverId
+    where
+        F: FnMut(&DeviceEvent) + Send + 'static,
+    {
+        self.events
+            .observe(DeviceEventKind::SwapchainRecreated, observer)
+    }
+
+    /// call `observer` when a frame acquired its image, before anything is recorded.
+    pub fn on_frame_begin<F>(&mut self, observer: F) -> ObserverId
+    where
+        F: FnMut(&DeviceEvent) + Send + 'static,
+    {
+        self.events.observe(DeviceEventKind::FrameBegin, observer)
+    }
+
+    /// call `observer` when a frame was submitted and presented.
+    pub fn on_frame_end<F>(&mut self, observer: F) -> ObserverId
+    where
+        F: FnMut(&DeviceEvent) + Send + 'static,
+    {
+        self.events.observe(DeviceEventKind::FrameEnd, observer)
+    }
+
+    /// call `observer` when the device was lost, after the bug report was written.
+    pub fn on_device_lost<F>(&mut self, observer: F) -> ObserverId
+    where
+        F: FnMut(&DeviceEvent) + Send + 'static,
+    {
+        self.events.observe(DeviceEventKind::DeviceLost, observer)
+    }
+
+    /// stop calling the observer of `id`, false when it was removed already.
+    pub fn remove_observer(&mut self, id: ObserverId) -> bool {
+        self.events.remove(id)
+    }
+
+    /// how long the window size must stay the same before the swapchain is recreated.
+    pub fn set_resize_debounce(&mut self, debounce: Duration) {
+        self.resize.debounce = debounce;
+    }
+
+    /// enable redrawing only the damaged parts of the scene, for mostly static tool uis.
+    /// the scene target keeps the previous frame, `scene` draws are scissored to the
+    /// damage and post processing and presentation still cover the whole image.
+    pub fn set_damage_mode(&mut self, enabled: bool) {
+        self.damage.enabled = enabled;
+        self.damage.rects.clear();
+    }
+
+    /// in damage mode, load the scene target and clear only the damaged rectangles instead
+    /// of their bounds when that touches fewer pixels. scene draws must then be recorded once
+    /// per rectangle of `scissors`, as sprites and lines are, draws scissored to `scissor`
+    /// alone draw over the loaded previous frame between the rectangles. scenes recorded in
+    /// secondary command buffers always clear the bounds.
+    pub fn set_scissored_clears(&mut self, enabled: bool) {
+        self.damage.scissored = enabled;
+    }
+
+    /// set what the scene pass does with the scene target before drawing, a clear color or
+    /// `LoadOp::DontCare` when the scene covers every pixel. loading keeps the previous frame,
+    /// a new scene target is cleared as by default first. damage mode clears the damage.
+    pub fn set_scene_load(&mut self, load: PassLoad) {
+        self.scene_load = load;
+    }
+
+    pub fn scene_load(&self) -> PassLoad {
+        self.scene_load
+    }
+
+    /// record the scene pass, its transparent part and target passes in secondary command
+    /// buffers with `SECONDARY_COMMAND_BUFFERS`. the scene closure then only executes them,
+    /// they are recorded with `scene_inheritance`, on other threads with a `SecondaryPool`
+    /// each. renderers drawing inline don't work in such a scene.
+    pub fn set_scene_contents(&mut self, contents: vk::SubpassContents) {
+        self.scene_contents = contents;
+    }
+
+    pub fn scene_contents(&self) -> vk::SubpassContents {
+        self.scene_contents
+    }
+
+    /// what secondary command buffers drawing in the scene pass inherit. the framebuffer is
+    /// that of the running target pass, otherwise of the scene target.
+    pub fn scene_inheritance(&self) -> PassInheritance {
+        PassInheritance {
+            render_pass: self.render_passes.scene_render_pass,
+            subpass: 0,
+            framebuffer: match self.active_target.get() {
+                Some(target) => target.framebuffer,
+                None => self.swapchain.scene_framebuffer.buffer,
+            },
+        }
+    }
+
+    /// the queue family of the graphics queue, for command pools recording on other threads.
+    pub fn queue_family(&self) -> u32 {
+        self.queue.graphics_family
+    }
+
+    // the opaque scene render pass with the load ops of `load`, compatible with `render_pass`
+    unsafe fn load_render_pass(&self, load: &PassLoad) -> Result<vk::RenderPass> {
+        let ops = load.ops();
+        if ops == PassLoad::default().ops() {
+            return Ok(self.render_passes.scene_render_pass);
+        }
+        if let Some(render_pass) = self.load_render_passes.borrow().get(&ops) {
+            return Ok(*render_pass);
+        }
+
+        debug!(?ops, "create scene render pass");
+        let render_pass = create_render_pass(
+            &self.instance,
+            &self.physical,
+            &self.device,
+            &self.samples,
+            SCENE_FORMAT,
+            false,
+            load,
+        )?;
+        self.load_render_passes
+            .borrow_mut()
+            .insert(ops, render_pass);
+        Ok(render_pass)
+    }
+
+    /// report a rectangle in pixels that changed since the last frame, unused outside damage mode.
+    pub fn add_damage(&mut self, rect: vk::Rect2D) {
+        if self.damage.enabled {
+            self.damage.rects.push(rect);
+        }
+    }
+
+    /// the scissor scene draws must use, the whole target unless in damage mode.
+    pub fn scissor(&self) -> vk::Rect2D {
+        match self.active_target.get() {
+            Some(target) => vk::Rect2D {
+                offset: vk::Offset2D::default(),
+                extent: target.extent,
+            },
+            None => self.damage.area,
+        }
+    }
+
+    /// the scissors scene draws are recorded with one after another, the damaged rectangles
+    /// with scissored clears and `scissor` otherwise.
+    pub fn scissors(&self) -> Vec<vk::Rect2D> {
+        if self.active_target.get().is_some() || self.damage.scissors.is_empty() {
+            vec![self.scissor()]
+        } else {
+            self.damage.scissors.clone()
+        }
+    }
+
+    /// measure how long frames take from their start until they are displayed and count
+    /// the refreshes they missed, see `latency_stats`. uses `VK_GOOGLE_display_timing` where
+    /// available and estimates from frame completion otherwise.
+    pub fn set_latency_tracking(&mut self, enabled: bool) {
+        self.latency = enabled.then(|| {
+            let mut tracker = LatencyTracker::new(LATENCY_HISTORY);
+            tracker.set_refresh(self.display_refresh());
+            LatencyData {
+                tracker,
+                started: Instant::now(),
+                next_id: 0,
+                slots: [None; MAX_FRAMES_IN_FLIGHT],
+            }
+        });
+    }
+
+    /// the latencies of the recent frames, none unless tracking.
+    pub fn latency_stats(&self) -> Option<LatencyStats> {
+        self.latency.as_ref().map(|l| l.tracker.stats())
+    }
+
+    // the refresh cycle the presentation engine reports for the swapchain
+    fn display_refresh(&self) -> Option<Duration> {
+        if !self.display_timing {
+            return None;
+        }
+        let refresh = unsafe {
+            self.device
+                .get_refresh_cycle_duration_google(self.swapchain.handle)
+        };
+        refresh
+            .ok()
+            .map(|r| Duration::from_nanos(r.refresh_duration))
+    }
+
+    /// write bug reports when validation errors pile up or the device is lost, `None` disables them.
+    /// while enabled every presented image is copied back so the report can include the last
+    /// completed frame.
+    pub fn set_bug_reports(&mut self, settings: Option<BugReportSettings>) -> Result<()> {
+        unsafe {
+            // release the previous copies
+            if let Some(report) = self.report.take() {
+                self.device.device_wait_idle()?;
+                report
+                    .readbacks
+                    .iter()
+                    .for_each(|b| b.destroy(&self.device));
+            }
+
+            if let Some(settings) = settings {
+                self.report = Some(BugReportData {
+                    settings,
+                    readbacks: self.create_readbacks()?,
+                    submitted: vec![None; MAX_FRAMES_IN_FLIGHT],
+                    completed: None,
+                    last: None,
+                    reported_errors: validation_errors(),
+                    passes: vec![],
+                });
+            }
+
+            Ok(())
+        }
+    }
+
+    /// copy presented frames back and hand them to `sink` at the framerate of `settings`,
+    /// until `stop_frame_export`. a running export is stopped first. fails when swapchain
+    /// images can't be copied.
+    pub fn start_frame_export(
+        &mut self,
+        settings: FrameExportSettings,
+        sink: impl FrameSink + Send + 'static,
+    ) -> Result<()> {
+        self.stop_frame_export()?;
+        unsafe {
+            let readbacks = self.create_export_readbacks()?;
+            self.export = Some(FrameExportData {
+                clock: FrameClock::new(settings.framerate),
+                sink: Box::new(sink),
+                started: Instant::now(),
+                readbacks,
+                pending: vec![None; MAX_FRAMES_IN_FLIGHT],
+            });
+        }
+        Ok(())
+    }
+
+    /// whether presented frames are exported.
+    pub fn is_exporting(&self) -> bool {
+        self.export.is_some()
+    }
+
+    /// stop exporting, the frames still in flight are waited for and written before the
+    /// sink finishes.
+    pub fn stop_frame_export(&mut self) -> Result<()> {
+        if self.export.is_none() {
+            return Ok(());
+        }
+        unsafe {
+            self.device.device_wait_idle()?;
+            let flushed = self.flush_frame_export();
+            let Some(mut export) = self.export.take() else {
+                return flushed;
+            };
+            export
+                .readbacks
+                .iter()
+                .for_each(|b| b.destroy(&self.device));
+            flushed.and(export.sink.finish())
+        }
+    }
+
+    // one export copy per frame in flight at the extent of the swapchain
+    unsafe fn create_export_readbacks(&self) -> Result<Vec<Buffer>> {
+        let support = SwapChainSupport::get(&self.instance, &self.surface, self.physical)?;
+        if !support
+            .capabilities
+            .supported_usage_flags
+            .contains(vk::ImageUsageFlags::TRANSFER_SRC)
+        {
+            return Err(anyhow!(
+                "Swapchain images can't be copied, frames can't be exported."
+            ));
+        }
+
+        let extent = self.swapchain.extent;
+        let size = extent.width as vk::DeviceSize * extent.height as vk::DeviceSize * 4;
+        (0..MAX_FRAMES_IN_FLIGHT)
+            .map(|_| {
+                self.create_buffer(
+                    size,
+                    vk::BufferUsageFlags::TRANSFER_DST,
+                    vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+                )
+            })
+            .collect()
+    }
+
+    // hand the capture of a completed frame slot to the sink
+    unsafe fn export_frame(&mut self, slot: usize) -> Result<()> {
+        let format = self.swapchain.format;
+        let Some(export) = &mut self.export else {
+            return Ok(());
+        };
+        let Some((index, timestamp, extent)) = export.pending[slot].take() else {
+            return Ok(());
+        };
+
+        let (width, height) = (extent.width, extent.height);
+        let count = width as usize * height as usize * 4;
+        let data = export.readbacks[slot].read::<u8>(&self.device, 0, count)?;
+        let mut pixels = TexturePixels {
+            width,
+            height,
+            format,
+            data,
+        }
+        .to_rgba8()?;
+
+        // the window is opaque, the stored alpha is meaningless
+        pixels.chunks_exact_mut(4).for_each(|p| p[3] = 255);
+
+        export.sink.write(&ExportedFrame {
+            index,
+            timestamp,
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    // hand every pending capture to the sink in order, the device must be idle
+    unsafe fn flush_frame_export(&mut self) -> Result<()> {
+        let Some(export) = &self.export else {
+            return Ok(());
+        };
+        let mut slots = (0..MAX_FRAMES_IN_FLIGHT)
+            .filter_map(|slot| export.pending[slot].map(|(index, ..)| (index, slot)))
+            .collect::<Vec<_>>();
+        slots.sort();
+        for (_, slot) in slots {
+            self.export_frame(slot)?;
+        }
+        Ok(())
+    }
+
+    /// describe the passes recorded by `post`, only used in bug reports.
+    pub fn set_report_passes(&mut self, passes: Vec<String>) {
+        if let Some(report) = &mut self.report {
+            report.passes = passes;
+        }
+    }
+
+    /// the logical device.
+    pub fn device(&self) -> &vulkanalia::Device {
+        &self.device
+    }
+
+    /// the render pass all scene draws are recorded in, it renders into the hdr scene target.
+    /// the transparent pass is compatible, pipelines created with this pass work in both. it
+    /// lives as long as the device, resizes keep it.
+    pub fn render_pass(&self) -> vk::RenderPass {
+        self.render_passes.scene_render_pass
+    }
+
+    /// the depth of the scene, readable by shaders once `begin_transparent_pass` was recorded.
+    /// the view has the sample count of the render pass and is in the depth read only layout.
+    pub fn depth_view(&self) -> vk::ImageView {
+        match self.active_target.get() {
+            Some(target) => target.depth_view,
+            None => self.swapchain.target.depth_texture_view.view,
+        }
+    }
+
+    /// the render pass writing the swapchain image, single sampled without depth.
+    pub fn present_render_pass(&self) -> vk::RenderPass {
+        self.render_passes.present_render_pass
+    }
+
+    /// the resolved hdr scene color, readable by shaders once the scene pass has ended.
+    pub fn scene_view(&self) -> vk::ImageView {
+        self.swapchain.target.scene_texture_view.view
+    }
+
+    /// the sample count of the render pass color and depth attachments.
+    pub fn samples(&self) -> vk::SampleCountFlags {
+        self.samples
+    }
+
+    /// the format of depth attachments, the first of the depth formats the adapter can draw
+    /// to and sample.
+    pub fn depth_format(&self) -> Result<vk::Format> {
+        unsafe { get_depth_format(&self.instance, &self.physical) }
+    }
+
+    /// the current extent of the swapchain, or of the offscreen target while one is recorded.
+    /// scene draws see the scene target, which is smaller at a render scale below 1.
+    pub fn extent(&self) -> vk::Extent2D {
+        match self.active_target.get() {
+            Some(target) => target.extent,
+            None if self.recording_scene.get() => self.swapchain.target.extent,
+            None => self.swapchain.extent,
+        }
+    }
+
+    /// the extent of the swapchain images, also while the scene or an offscreen target is
+    /// recorded.
+    pub fn swapchain_extent(&self) -> vk::Extent2D {
+        self.swapchain.extent
+    }
+
+    /// the extent of the hdr scene target, the swapchain extent scaled by the render scale.
+    pub fn scene_extent(&self) -> vk::Extent2D {
+        self.swapchain.target.extent
+    }
+
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    /// the filter post processing samples the scene with, see `set_render_scale`.
+    pub fn upscale_filter(&self) -> vk::Filter {
+        self.upscale_filter
+    }
+
+    /// render the scene at `scale` of the swapchain extent, e.g. 0.75 to save fill rate on
+    /// slow gpus, and scale it to the swapchain with `filter` in post processing. above 1 the
+    /// scene is supersampled. the scene target is rebuilt at the next frame, the swapchain is
+    /// kept.
+    pub fn set_render_scale(&mut self, scale: f32, filter: vk::Filter) -> Result<()> {
+        if !(scale > 0.0 && scale <= 2.0) {
+            return Err(anyhow!(
+                "Render scale must be in 0..=2 and above 0, not {}.",
+                scale
+            ));
+        }
+        if scale != self.render_scale {
+            self.target_stale = true;
+        }
+        self.render_scale = scale;
+        self.upscale_filter = filter;
+        Ok(())
+    }
+
+    /// the number of swapchain images, per-image resources should use this count.
+    pub fn image_count(&self) -> usize {
+        self.swapchain.textures.len()
+    }
+
+    /// wait until the device has finished all submitted work.
+    pub fn wait_idle(&self) -> Result<()> {
+        unsafe { self.device.device_wait_idle()? };
+        Ok(())
+    }
+
+    /// create a single sampled device local texture with optimal tiling.
+    pub fn create_texture(
+        &self,
+        width: u32,
+        height: u32,
+        mip_levels: u32,
+        format: vk::Format,
+        usage: vk::ImageUsageFlags,
+    ) -> Result<Texture> {
+        check_format_feature(format, &self.features)?;
+        unsafe {
+            create_texture(
+                &self.instance,
+                &self.physical,
+                &self.device,
+                width,
+                height,
+                mip_levels,
+                vk::SampleCountFlags::_1,
+                format,
+                vk::ImageTiling::OPTIMAL,
+                usage,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+                &self.queue.shared,
+            )
+        }
+    }
+
+    /// create offscreen attachments like the scene target, the resolved color starts out
+    /// cleared to black so it can be sampled before the first pass. it can be copied to and
+    /// from outside of passes.
+    pub fn create_scene_attachments(&self, width: u32, height: u32) -> Result<SceneAttachments> {
+        unsafe {
+            let (instance, physical, device) = (&self.instance, &self.physical, &self.device);
+            let (color_texture, color_texture_view) = create_swapchain_albedo_objects(
+                instance,
+                physical,
+                device,
+                &self.samples,
+                width,
+                height,
+                SCENE_FORMAT,
+            )?;
+            let (depth_texture, depth_texture_view) = create_swapchain_depth_objects(
+                instance,
+                physical,
+                device,
+                &self.samples,
+                width,
+                height,
+            )?;
+            let (resolve_texture, resolve_texture_view) = create_swapchain_scene_objects(
+                instance,
+                physical,
+                device,
+                width,
+                height,
+                vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::TRANSFER_SRC,
+            )?;
+            let framebuffer = FrameBuffer::create(
+                device,
+                &self.render_passes.scene_render_pass,
+                &[color_texture_view, depth_texture_view, resolve_texture_view],
+                width,
+                height,
+            )?;
+
+            // a target sampled before it was drawn shows black
+            let image = resolve_texture.image;
+            self.execute(|device, command_buffer| {
+                let range = vk::ImageSubresourceRange::builder()
+                    .aspect_mask(vk::ImageAspectFlags::COLOR)
+                    .base_mip_level(0)
+                    .level_count(1)
+                    .base_array_layer(0)
+                    .layer_count(1)
+                    .build();
+                let barrier = |old, new, src, dst| {
+                    vk::ImageMemoryBarrier::builder()
+                        .old_layout(old)
+                        .new_layout(new)
+                        .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                        .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                        .image(image)
+                        .subresource_range(range)
+                        .src_access_mask(src)
+                        .dst_access_mask(dst)
+                        .build()
+                };
+
+                let clear = barrier(
+                    vk::ImageLayout::UNDEFINED,
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    vk::AccessFlags::empty(),
+                    vk::AccessFlags::TRANSFER_WRITE,
+                );
+                device.device.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::TOP_OF_PIPE,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::DependencyFlags::empty(),
+                    &[] as &[vk::MemoryBarrier],
+                    &[] as &[vk::BufferMemoryBarrier],
+                    &[clear],
+                );
+
+                let color = vk::ClearColorValue {
+                    float32: [0.0, 0.0, 0.0, 1.0],
+                };
+                device.device.cmd_clear_color_image(
+                    command_buffer,
+                    image,
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    &color,
+                    &[range],
+                );
+
+                let read = barrier(
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                    vk::AccessFlags::TRANSFER_WRITE,
+                    vk::AccessFlags::SHADER_READ,
+                );
+                device.device.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::PipelineStageFlags::FRAGMENT_SHADER,
+                    vk::DependencyFlags::empty(),
+                    &[] as &[vk::MemoryBarrier],
+                    &[] as &[vk::BufferMemoryBarrier],
+                    &[read],
+                );
+
+                Ok(())
+            })?;
+
+            self.set_name(color_texture.image, "target color");
+            self.set_name(depth_texture.image, "target depth");
+            self.set_name(resolve_texture.image, "target");
+            self.set_name(framebuffer.buffer, "target");
+
+            Ok(SceneAttachments {
+                extent: vk::Extent2D { width, height },
+                color_texture,
+                color_texture_view,
+                depth_texture,
+                depth_texture_view,
+                resolve_texture,
+                resolve_texture_view,
+                framebuffer,
+            })
+        }
+    }
+
+    /// create a device local texture with `layers` layers of a single level, e.g. the layered
+    /// attachments of a multiview pass.
+    pub fn create_array_texture(
+        &self,
+        width: u32,
+        height: u32,
+        layers: u32,
+        format: vk::Format,
+        usage: vk::ImageUsageFlags,
+    ) -> Result<Texture> {
+        check_format_feature(format, &self.features)?;
+        unsafe {
+            create_layered_texture(
+                &self.instance,
+                &self.physical,
+                &self.device,
+                width,
+                height,
+                1,
+                layers,
+                vk::ImageCreateFlags::empty(),
+                vk::SampleCountFlags::_1,
+                format,
+                vk::ImageTiling::OPTIMAL,
+                usage,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+                &self.queue.shared,
+            )
+        }
+    }
+
+    /// create a device local cube texture with six square layers in the vulkan face order.
+    pub fn create_cube_texture(
+        &self,
+        size: u32,
+        mip_levels: u32,
+        format: vk::Format,
+        usage: vk::ImageUsageFlags,
+    ) -> Result<Texture> {
+        check_format_feature(format, &self.features)?;
+        unsafe {
+            create_layered_texture(
+                &self.instance,
+                &self.physical,
+                &self.device,
+                size,
+                size,
+                mip_levels,
+                6,
+                vk::ImageCreateFlags::CUBE_COMPATIBLE,
+                vk::SampleCountFlags::_1,
+                format,
+                vk::ImageTiling::OPTIMAL,
+                usage,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+                &self.queue.shared,
+            )
+        }
+    }
+
+    /// record commands into a one time command buffer, submit it and wait for it to finish.
+    /// the buffer runs on the graphics queue, which supports compute on all common drivers.
+    pub fn execute<F>(&self, record: F) -> Result<()>
+    where
+        F: FnOnce(&Device, vk::CommandBuffer) -> Result<()>,
+    {
+        let _span = debug_span!("command buffer", kind = "one time").entered();
+        let _capture = CheckedCapture::begin();
+        unsafe {
+            let command_buffer = self.commands.pool.begin_single(&self.device)?;
+            self.set_name(command_buffer.buffer, "execute");
+            if let Err(error) = record(self, command_buffer.buffer) {
+                self.commands
+                    .pool
+                    .discard_single(&self.device, command_buffer);
+                return Err(error);
+            }
+            self.commands
+                .pool
+                .end_single(&self.device, self.queue.graphics, command_buffer)
+        }
+    }
+
+    /// record commands like `execute` and submit them to the queue of `kind` instead, e.g. to
+    /// upload on the transfer queue. fails when the device has no such queue, the present
+    /// queue doesn't take commands. buffers and textures from `create_buffer` and the
+    /// `create_*texture` methods are concurrent across the queues and need no ownership
+    /// transfers, other resources belong to the graphics family.
+    pub fn execute_on<F>(&self, kind: QueueKind, record: F) -> Result<()>
+    where
+        F: FnOnce(&Device, vk::CommandBuffer) -> Result<()>,
+    {
+        let pool = match kind {
+            QueueKind::Graphics => &self.commands.pool,
+            QueueKind::Present => return Err(anyhow!("The present queue doesn't take commands.")),
+            _ => match self.commands.queue_pools.iter().find(|(k, _)| *k == kind) {
+                Some((_, pool)) => pool,
+                None => return Err(anyhow!("The device has no {:?} queue.", kind)),
+            },
+        };
+        let Some(queue) = self.queue(kind) else {
+            return Err(anyhow!("The device has no {:?} queue.", kind));
+        };
+
+        let _span = debug_span!("command buffer", kind = "one time", queue = ?kind).entered();
+        let _capture = CheckedCapture::begin();
+        unsafe {
+            let command_buffer = pool.begin_single(&self.device)?;
+            self.set_name(command_buffer.buffer, "execute");
+            if let Err(error) = record(self, command_buffer.buffer) {
+                pool.discard_single(&self.device, command_buffer);
+                return Err(error);
+            }
+            pool.end_single(&self.device, queue.queue, command_buffer)
+        }
+    }
+
+    /// the queue of `kind`, none for compute and transfer when the adapter has no family
+    /// dedicated to them. graphics does their work then. submissions of your own, like
+    /// `Timeline::submit` or a `SubmitGraph`, go to `Queue::queue`.
+    pub fn queue(&self, kind: QueueKind) -> Option<Queue> {
+        let (family, queue) = match kind {
+            QueueKind::Graphics => (self.queue.graphics_family, self.queue.graphics),
+            QueueKind::Present => (self.queue.present_family, self.queue.present),
+            QueueKind::Compute => self.queue.compute?,
+            QueueKind::Transfer => self.queue.transfer?,
+        };
+        Some(Queue {
+            kind,
+            family,
+            queue,
+        })
+    }
+
+    /// the queues of the device, graphics and present first.
+    pub fn queues(&self) -> Vec<Queue> {
+        [
+            QueueKind::Graphics,
+            QueueKind::Present,
+            QueueKind::Compute,
+            QueueKind::Transfer,
+        ]
+        .into_iter()
+        .filter_map(|k| self.queue(k))
+        .collect()
+    }
+
+    /// the formats and color spaces swapchain images of the window surface can have, for
+    /// settings menus. the swapchain prefers `B8G8R8A8_SRGB` in `SRGB_NONLINEAR`.
+    pub fn supported_surface_formats(&self) -> Result<Vec<SurfaceFormat>> {
+        let support =
+            unsafe { SwapChainSupport::get(&self.instance, &self.surface, self.physical)? };
+        Ok(support
+            .formats
+            .into_iter()
+            .map(SurfaceFormat::from)
+            .collect())
+    }
+
+    /// the present modes of the window surface, fifo is always among them. the swapchain
+    /// prefers mailbox.
+    pub fn supported_present_modes(&self) -> Result<Vec<PresentMode>> {
+        let support =
+            unsafe { SwapChainSupport::get(&self.instance, &self.surface, self.physical)? };
+        Ok(present_modes(&support.present_modes))
+    }
+
+    /// name `handle` for RenderDoc and the validation messages, does nothing without
+    /// `VK_EXT_debug_utils`.
+    pub fn set_name<H>(&self, handle: H, name: &str)
+    where
+        H: vk::Handle,
+        u64: TryFrom<H::Repr>,
+    {
+        unsafe {
+            self.names
+                .set_name(&self.instance, self.device.handle(), handle, name)
+        }
+        allocation::set_name(self.device.handle(), handle, name);
+    }
+
+    /// begin a labeled region of `command_buffer` that RenderDoc groups the commands by,
+    /// regions nest and end with `end_label`.
+    pub fn begin_label(&self, command_buffer: vk::CommandBuffer, name: &str, color: [f32; 4]) {
+        unsafe {
+            self.names
+                .begin_label(&self.instance, command_buffer, name, color)
+        }
+    }
+
+    /// end the innermost region begun by `begin_label`.
+    pub fn end_label(&self, command_buffer: vk::CommandBuffer) {
+        unsafe { self.names.end_label(&self.instance, command_buffer) }
+    }
+
+    /// start capturing the command buffers submitted from now on into a `FrameTrace`. the
+    /// capture is process wide, it records the submissions of every device.
+    pub fn start_capture(&self) {
+        capture::start();
+    }
+
+    /// stop the capture and return the submissions it recorded.
+    pub fn stop_capture(&self) -> FrameTrace {
+        capture::stop()
+    }
+
+    /// run the commands of `trace` on the graphics queue, see `FrameTrace::replay`. the
+    /// objects it names have to be alive, so it replays on the device it was captured on.
+    pub fn replay(&self, trace: &FrameTrace) -> Result<()> {
+        unsafe { trace.replay(&self.device, self.queue.graphics, &self.commands.pool) }
+    }
+
+    /// create a pipeline layout with every set at the index of its slot, see `DescriptorSlot`.
+    /// fails when the sets or push constants exceed the limits of the adapter.
+    pub fn create_pipeline_layout(
+        &self,
+        sets: &[&DescriptorSets],
+        push_constant_ranges: &[vk::PushConstantRange],
+    ) -> Result<vk::PipelineLayout> {
+        let set_layouts = slot_set_layouts(sets, self.empty_set_layout)?;
+        let limits = &self.adapter.limits;
+        check_pipeline_layout(
+            set_layouts.len() as u32,
+            push_constant_ranges,
+            limits.max_bound_descriptor_sets,
+            limits.max_push_constants_size,
+        )?;
+        let info = vk::PipelineLayoutCreateInfo::builder()
+            .set_layouts(&set_layouts)
+            .push_constant_ranges(push_constant_ranges);
+
+        unsafe { Ok(self.device.create_pipeline_layout(&info, None)?) }
+    }
+
+    /// create a buffer with the memory properties `buffer_memory_properties` picks for usage.
+    pub fn create_buffer_for(
+        &self,
+        size: vk::DeviceSize,
+        usage: vk::BufferUsageFlags,
+    ) -> Result<Buffer> {
+        self.create_buffer(size, usage, buffer_memory_properties(usage))
+    }
+
+    /// create a buffer backed by its own memory allocation.
+    pub fn create_buffer(
+        &self,
+        size: vk::DeviceSize,
+        usage: vk::BufferUsageFlags,
+        properties: vk::MemoryPropertyFlags,
+    ) -> Result<Buffer> {
+        unsafe {
+            create_buffer(
+                &self.instance,
+                &self.physical,
+                &self.device,
+                size,
+                usage,
+                properties,
+                &self.queue.shared,
+            )
+        }
+    }
+
+    /// hand out `size` bytes aligned to `align` that live until the frame is submitted, e.g. for
+    /// uniforms or vertices written every frame. returns the buffer, the offset to bind it at
+    /// and the mapped bytes to write. the memory is reused once the frame completed.
+    #[allow(clippy::mut_from_ref)]
+    pub fn allocate(
+        &self,
+        size: vk::DeviceSize,
+        align: vk::DeviceSize,
+    ) -> Result<(vk::Buffer, vk::DeviceSize, &mut [u8])> {
+        unsafe {
+            self.staging
+                .allocate(&self.device, &self.sync.timeline, self.frame, size, align)
+        }
+    }
+
+    /// the shared sampler of `descriptor`, it lives as long as the device and must not be
+    /// destroyed.
+    pub fn sampler(&self, descriptor: &SamplerDescriptor) -> Result<vk::Sampler> {
+        unsafe { self.samplers.get(&self.device, descriptor) }
+    }
+
+    /// the shared sampler of `descriptor` for material textures, with the anisotropy and lod
+    /// bias of the graphics settings. look it up every frame to follow changed settings.
+    pub fn texture_sampler(&self, descriptor: &SamplerDescriptor) -> Result<vk::Sampler> {
+        let max_anisotropy = self.samplers.max_anisotropy();
+        self.sampler(&self.settings.texture_sampler(descriptor, max_anisotropy))
+    }
+
+    pub fn graphics_settings(&self) -> GraphicsSettings {
+        self.settings
+    }
+
+    /// change how textures are sampled and loaded, see `GraphicsSettings`. samplers of the
+    /// previous settings stay valid for the frames in flight.
+    pub fn set_graphics_settings(&mut self, settings: GraphicsSettings) -> Result<()> {
+        settings.check()?;
+        debug!(?settings, "set graphics settings");
+        self.settings = settings;
+        Ok(())
+    }
+
+    /// set viewports and their scissors from index `first` on, for split screen. indices past
+    /// 0 are refused unless the adapter has several, see `AdapterLimits::max_viewports`.
+    pub fn set_viewports(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        first: u32,
+        viewports: &[(vk::Viewport, vk::Rect2D)],
+    ) -> Result<()> {
+        let limit = self.adapter.limits.max_viewports;
+        unsafe { set_viewports(&self.device, command_buffer, first, viewports, limit) }
+    }
+
+    /// the shared render pass of the formats and ops of `descriptor`, for pipelines drawing
+    /// into its attachments. it lives as long as the device.
+    pub fn render_pass_for(&self, descriptor: &RenderPassDescriptor) -> Result<vk::RenderPass> {
+        unsafe { self.passes.render_pass(&self.device, descriptor) }
+    }
+
+    /// begin a pass drawing into the views of `descriptor` instead of the scene target, for
+    /// rendering to textures. must be recorded outside of any render pass, the attachments
+    /// are sampled in later passes once it ended.
+    pub fn begin_render_pass(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        descriptor: &RenderPassDescriptor,
+    ) -> Result<vk::RenderPass> {
+        let contents = vk::SubpassContents::INLINE;
+        unsafe {
+            self.passes
+                .begin(&self.device, command_buffer, descriptor, contents)
+        }
+    }
+
+    /// begin the pass of `descriptor` like `begin_render_pass`, but its first subpass only
+    /// executes secondary command buffers. they are recorded with the returned inheritance,
+    /// on other threads with a `SecondaryPool` each.
+    pub fn begin_secondary_render_pass(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        descriptor: &RenderPassDescriptor,
+    ) -> Result<PassInheritance> {
+        let contents = vk::SubpassContents::SECONDARY_COMMAND_BUFFERS;
+        unsafe {
+            let render_pass =
+                self.passes
+                    .begin(&self.device, command_buffer, descriptor, contents)?;
+            Ok(PassInheritance {
+                render_pass,
+                subpass: 0,
+                framebuffer: self.passes.framebuffer(&self.device, descriptor)?,
+            })
+        }
+    }
+
+    /// destroy the framebuffers drawing into `view` before it is destroyed, frames that used
+    /// them must be complete.
+    pub fn release_view(&self, view: vk::ImageView) {
+        unsafe { self.passes.release_view(&self.device, view) }
+    }
+
+    /// destroy `resource` once the frames that may still use it are complete, including the
+    /// frame being recorded.
+    pub fn retire(&self, resource: impl Into<Retired>) {
+        self.deletion.retire(self.frame_value() + 1, resource);
+    }
+
+    /// keep `resource` with the device, returns the id it is reached by. the device destroys
+    /// it with `destroy_resource` or when it is destroyed itself.
+    pub fn register<T: Registered>(&self, resource: T) -> super::Handle<T> {
+        self.registry.borrow_mut().insert(resource)
+    }
+
+    /// call `f` with the resource of `id`, an error once it was destroyed. the registry is
+    /// borrowed while `f` runs, so `f` must not register or destroy resources itself.
+    pub fn with_resource<T: Registered, R>(
+        &self,
+        id: super::Handle<T>,
+        f: impl FnOnce(&T) -> R,
+    ) -> Result<R> {
+        Ok(f(self.registry.borrow().get(id)?))
+    }
+
+    /// call `f` with the registry, e.g. to record with an `Encoder` resolving buffer ids
+    /// against it. like `with_resource`, `f` must not register or destroy resources.
+    pub fn with_registry<R>(&self, f: impl FnOnce(&ResourceRegistry) -> R) -> R {
+        f(&self.registry.borrow())
+    }
+
+    /// create a buffer like `create_buffer_for` and keep it with the device.
+    pub fn create_registered_buffer(
+        &self,
+        size: vk::DeviceSize,
+        usage: vk::BufferUsageFlags,
+    ) -> Result<super::BufferId> {
+        Ok(self.register(self.create_buffer_for(size, usage)?))
+    }
+
+    /// create a texture like `create_texture` and keep it with the device.
+    pub fn create_registered_texture(
+        &self,
+        width: u32,
+        height: u32,
+        mip_levels: u32,
+        format: vk::Format,
+        usage: vk::ImageUsageFlags,
+    ) -> Result<super::TextureId> {
+        Ok(self.register(self.create_texture(width, height, mip_levels, format, usage)?))
+    }
+
+    /// create a view of the registered `texture` and keep it with the device, destroy it
+    /// before the texture.
+    pub fn create_registered_view(
+        &self,
+        texture: super::TextureId,
+        aspects: vk::ImageAspectFlags,
+        mip_levels: u32,
+    ) -> Result<super::TextureViewId> {
+        let view = self.with_resource(texture, |t| unsafe {
+            t.create_view(&self.device, t.format, aspects, mip_levels)
+        })??;
+        Ok(self.register(view))
+    }
+
+    /// destroy the resource of `id` once the frames that may still use it are complete, the
+    /// id is invalid right away. buffers go like `destroy_buffer`, the other kinds aren't
+    /// tracked by encoders and wait for the frame being recorded.
+    pub fn destroy_resource<T: Registered>(&self, id: super::Handle<T>) -> Result<()> {
+        let resource = self.registry.borrow_mut().remove(id)?;
+        match resource.into() {
+            Retired::Buffer(buffer) => self.destroy_buffer(buffer),
+            resource => {
+                self.retire(resource);
+                Ok(())
+            }
+        }
+    }
+
+    /// the number of registered resources of each kind.
+    pub fn resource_counts(&self) -> ResourceCounts {
+        self.registry.borrow().counts()
+    }
+
+    /// call `hooks` with every device memory allocation and free from now on, none removes
+    /// them. buffers and textures each have their own allocation.
+    pub fn set_allocation_hooks(&self, hooks: Option<Box<dyn AllocationHooks>>) {
+        allocation::set_hooks(self.device.handle(), hooks);
+    }
+
+    /// the device memory allocated and not freed yet.
+    pub fn allocation_stats(&self) -> AllocationStats {
+        allocation::stats(self.device.handle())
+    }
+
+    /// the allocations alive, largest first, e.g. to find what holds on to memory.
+    pub fn live_allocations(&self) -> Vec<Allocation> {
+        allocation::live(self.device.handle())
+    }
+
+    /// a checked encoder recording into `command_buffer` of the current frame, it refuses
+    /// retired buffers and marks the bound ones used until the frame completed. buffer ids
+    /// resolve against the registered resources, which can't be registered or destroyed
+    /// while the encoder lives.
+    pub fn encoder(&self, command_buffer: vk::CommandBuffer) -> Encoder<'_> {
+        Encoder::new(&self.device, command_buffer)
+            .with_retired(&self.deletion)
+            .with_usage(&self.usage, self.frame_value() + 1)
+            .with_borrowed_registry(self.registry.borrow())
+    }
+
+    /// destroy `buffer` once the frames that used it through `encoder` completed, right away
+    /// when none is in flight.
+    pub fn destroy_buffer(&self, buffer: Buffer) -> Result<()> {
+        let completed = self.completed_frame()?;
+        unsafe {
+            self.usage
+                .destroy(&self.device, &self.deletion, completed, buffer);
+        }
+        Ok(())
+    }
+
+    /// load a png into a sampled and mipmapped texture, `import` decides the format. the
+    /// texture is shrunk by the texture scale of the graphics settings.
+    pub fn load_texture(
+        &self,
+        path: &str,
+        import: TextureImport,
+    ) -> Result<(Texture, TextureView)> {
+        let (width, height, pixels) = read_png(path)?;
+        let (width, height, pixels) = self.scale_texture(width, height, pixels);
+        let (texture, view) = self.upload_texture(width, height, &pixels, import)?;
+        self.set_name(texture.image, path);
+        self.set_name(view.view, path);
+        Ok((texture, view))
+    }
+
+    /// shrink the rgba8 pixels of a texture file by the texture scale of the graphics
+    /// settings, returns the new size and pixels.
+    pub fn scale_texture(&self, width: u32, height: u32, pixels: Vec<u8>) -> (u32, u32, Vec<u8>) {
+        let (new_width, new_height) = self.settings.texture_extent(width, height);
+        if (new_width, new_height) == (width, height) {
+            return (width, height, pixels);
+        }
+        let pixels = shrink_pixels(width, height, &pixels, new_width, new_height);
+        (new_width, new_height, pixels)
+    }
+
+    /// upload rgba8 pixels into a sampled and mipmapped texture, `import` decides the format.
+    pub fn upload_texture(
+        &self,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        import: TextureImport,
+    ) -> Result<(Texture, TextureView)> {
+        unsafe {
+            upload_texture(
+                &self.instance,
+                &self.physical,
+                &self.device,
+                &self.commands.pool,
+                self.queue.graphics,
+                width,
+                height,
+                pixels,
+                import,
+            )
+        }
+    }
+
+    /// upload rgba8 pixels like `upload_texture` without waiting for the device, the next
+    /// frame records the copy before `prepare`. the texture and its view are ready once the
+    /// frame completed, shaders of that frame sample it already. the future owns the texture
+    /// until then, dropping it retires the texture until the frame completed.
+    pub fn upload_texture_async(
+        &self,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        import: TextureImport,
+    ) -> Result<GpuFuture<'_, (Texture, TextureView)>> {
+        unsafe {
+            let (staged, texture) = stage_texture(
+                &self.instance,
+                &self.physical,
+                &self.device,
+                width,
+                height,
+                pixels,
+                import,
+            )?;
+            let mip_levels = staged.mip_levels;
+            self.uploads.borrow_mut().push(staged);
+            let value = self.frame_value() + 1;
+            let work = self.sync.timeline.work_done(&self.device, value);
+
+            // handed out by the future or retired when it is dropped
+            let finished = Rc::new(Cell::new(Some(texture)));
+            let dropped = finished.clone();
+            let future = GpuFuture::new(work, move || {
+                let texture = finished.take().unwrap();
+                let aspects = vk::ImageAspectFlags::COLOR;
+                let view =
+                    texture.create_view(&self.device, texture.format, aspects, mip_levels)?;
+                Ok((texture, view))
+            });
+            Ok(future.on_drop(move || {
+                if let Some(texture) = dropped.take() {
+                    self.deletion.retire(value, texture);
+                }
+            }))
+        }
+    }
+
+    /// read `count` values at `offset` of a host visible buffer once the frames submitted
+    /// so far completed, e.g. results a compute pass of the latest frame wrote. an error
+    /// right away when the values don't all fit the buffer.
+    pub fn map_async<T: Pod>(
+        &self,
+        buffer: &Buffer,
+        offset: vk::DeviceSize,
+        count: usize,
+    ) -> Result<GpuFuture<'_, Vec<T>>> {
+        buffer.check_read::<T>(offset, count)?;
+        let buffer = *buffer;
+        let work = self.on_submitted_work_done();
+        Ok(GpuFuture::new(work, move || unsafe {
+            buffer.read(&self.device, offset, count)
+        }))
+    }
+
+    /// copy the first level of a color texture in `layout` back to the cpu, waiting for the
+    /// device. the texture needs `TRANSFER_SRC` usage and is left in `layout`.
+    pub fn read_pixels(
+        &self,
+        texture: &Texture,
+        width: u32,
+        height: u32,
+        layout: vk::ImageLayout,
+    ) -> Result<TexturePixels> {
+        unsafe {
+            read_texture(
+                &self.instance,
+                &self.physical,
+                &self.device,
+                &self.commands.pool,
+                self.queue.graphics,
+                texture,
+                width,
+                height,
+                layout,
+            )
+        }
+    }
+
+    /// update the app, `scene` is invoked while the scene render pass is active.
+    /// `post` is invoked after the scene pass outside of any render pass, it must write
+    /// the swapchain image between `begin_present_pass` and `end_present_pass`.
+    pub fn update<S, P>(&mut self, window: &dyn DeviceWindow, scene: S, post: P) -> Result<()>
+    where
+        S: FnOnce(&Device, vk::CommandBuffer, usize) -> Result<()>,
+        P: FnOnce(&Device, vk::CommandBuffer, usize) -> Result<()>,
+    {
+        self.update_with_prepare(window, |_, _, _| Ok(()), scene, post)
+    }
+
+    /// update the app like `update`, `prepare` is invoked first outside of any render pass.
+    /// it records the work the scene depends on, like compute dispatches and copies.
+    pub fn update_with_prepare<R, S, P>(
+        &mut self,
+        window: &dyn DeviceWindow,
+        prepare: R,
+        scene: S,
+        post: P,
+    ) -> Result<()>
+    where
+        R: FnOnce(&Device, vk::CommandBuffer, usize) -> Result<()>,
+        S: FnOnce(&Device, vk::CommandBuffer, usize) -> Result<()>,
+        P: FnOnce(&Device, vk::CommandBuffer, usize) -> Result<()>,
+    {
+        self.update_with_latch(window, prepare, scene, post, |_, _| Ok(()))
+    }
+
+    /// update the app like `update_with_prepare`, `latch` is invoked once the frame is
+    /// recorded, right before it is submitted. it writes what the recorded commands read
+    /// from persistently mapped memory and should be as fresh as possible, like the camera
+    /// following the latest input, see `MappedBuffer`. it gets the image index.
+    pub fn update_with_latch<R, S, P, L>(
+        &mut self,
+        window: &dyn DeviceWindow,
+        prepare: R,
+        scene: S,
+        post: P,
+        latch: L,
+    ) -> Result<()>
+    where
+        R: FnOnce(&Device, vk::CommandBuffer, usize) -> Result<()>,
+        S: FnOnce(&Device, vk::CommandBuffer, usize) -> Result<()>,
+        P: FnOnce(&Device, vk::CommandBuffer, usize) -> Result<()>,
+        L: FnOnce(&Device, usize) -> Result<()>,
+    {
+        let span = info_span!("frame", slot = self.frame);
+        let capture = CheckedCapture::begin();
+        let result = span.in_scope(|| self.render(window, prepare, scene, post, latch));
+        drop(capture);
+        self.frame_ended(result)
+    }
+
+    /// begin the next frame, `None` while the window has no area or the swapchain was just
+    /// replaced. the frame records the scene and then presents, see `Frame`. `update` is the
+    /// same with closures.
+    pub fn begin_frame<'a>(
+        &'a mut self,
+        window: &'a dyn DeviceWindow,
+    ) -> Result<Option<Frame<'a>>> {
+        let capture = CheckedCapture::begin();
+        let acquired = unsafe { self.acquire_frame(window) };
+        let Some((index, regions)) = self.check_frame(acquired)? else {
+            return Ok(None);
+        };
+        let command_buffer = unsafe { self.begin_commands(index) };
+        let command_buffer = self.check_frame(command_buffer)?;
+        self.begin_label(command_buffer, "prepare", PREPARE_LABEL);
+        Ok(Some(Frame {
+            device: self,
+            window,
+            index,
+            regions,
+            command_buffer,
+            _capture: capture,
+        }))
+    }
+
+    /// report a device lost while recording or presenting a frame, `result` is passed on.
+    pub(super) fn check_frame<T>(&mut self, result: Result<T>) -> Result<T> {
+        // a lost device can't be inspected anymore, report what is left
+        if let Err(e) = &result {
+            if e.downcast_ref::<vk::ErrorCode>() == Some(&vk::ErrorCode::DEVICE_LOST) {
+                self.report_bug("device lost", true);
+                self.events.emit(DeviceEvent::DeviceLost);
+            }
+        }
+        result
+    }
+
+    /// report what went wrong in a frame once it was presented, `result` is passed on.
+    pub(super) fn frame_ended(&mut self, result: Result<()>) -> Result<()> {
+        self.check_frame(result)?;
+
+        // report once enough validation errors occurred since the last report
+        let errors = validation_errors();
+        if let Some(report) = &self.report {
+            if errors - report.reported_errors >= report.settings.error_threshold {
+                self.report_bug(
+                    &format!("{} validation errors", errors - report.reported_errors),
+                    false,
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// acquire, record, latch, submit and present a frame.
+    fn render<R, S, P, L>(
+        &mut self,
+        window: &dyn DeviceWindow,
+        prepare: R,
+        scene: S,
+        post: P,
+        latch: L,
+    ) -> Result<()>
+    where
+        R: FnOnce(&Device, vk::CommandBuffer, usize) -> Result<()>,
+        S: FnOnce(&Device, vk::CommandBuffer, usize) -> Result<()>,
+        P: FnOnce(&Device, vk::CommandBuffer, usize) -> Result<()>,
+        L: FnOnce(&Device, usize) -> Result<()>,
+    {
+        unsafe {
+            let Some((index, regions)) = self.acquire_frame(window)? else {
+                return Ok(());
+            };
+
+            // record the work the scene depends on, the scene and post processing
+            let command_buffer = self.begin_commands(index)?;
+            self.begin_label(command_buffer, "prepare", PREPARE_LABEL);
+            debug_span!("prepare").in_scope(|| prepare(self, command_buffer, index))?;
+            self.record_scene(command_buffer, index, scene)?;
+            self.finish_commands(command_buffer, index, post)?;
+            self.latch_frame(index, latch)?;
+
+            let hint = self.damage_hint(regions);
+            self.present_frame(window, index, hint.as_deref())
+        }
+    }
+
+    /// wait for the frame slot and acquire the next image, none when there is nothing to
+    /// render to. returns the image index and the damaged regions.
+    pub(super) unsafe fn acquire_frame(
+        &mut self,
+        window: &dyn DeviceWindow,
+    ) -> Result<Option<(usize, Vec<vk::Rect2D>)>> {
+        let begun = Instant::now();
+
+        // nothing is rendered while the window has no area
+        if self.resize.is_minimized() {
+            if let Some(latency) = &mut self.latency {
+                latency.tracker.pause();
+            }
+            return Ok(None);
+        }
+
+        // follow the window once its size settled
+        if self.resize.should_recreate() {
+            self.recreate_swapchain(window)?;
+            if self.resize.is_minimized() {
+                return Ok(None);
+            }
+        }
+
+        // follow a changed render scale
+        if self.target_stale {
+            self.recreate_scene_target()?;
+        }
+
+        // wait for the frame last submitted from this slot
+        self.sync
+            .timeline
+            .wait(&self.device, self.sync.in_flight_frames[self.frame])?;
+        if let Some(latency) = &mut self.latency {
+            // every frame seen complete by now, oldest first
+            let completed = self.sync.timeline.completed(&self.device)?;
+            let at = latency.started.elapsed();
+            let mut done = (0..MAX_FRAMES_IN_FLIGHT)
+                .filter(|s| self.sync.in_flight_frames[*s] <= completed)
+                .filter_map(|s| latency.slots[s].take())
+                .collect::<Vec<_>>();
+            done.sort_unstable();
+            done.into_iter().for_each(|id| {
+                latency.tracker.completed(id, at);
+            });
+        }
+
+        // release what completed frames retired
+        self.deletion.collect(&self.device, &self.sync.timeline)?;
+        self.usage
+            .forget_completed(self.sync.timeline.completed(&self.device)?);
+
+        // the frame submitted from this slot is complete
+        if let Some(report) = &mut self.report {
+            if let Some(index) = report.submitted[self.frame] {
+                report.completed = Some(index);
+            }
+        }
+        self.export_frame(self.frame)?;
+
+        // get next image
+        let result = self.device.acquire_next_image_khr(
+            self.swapchain.handle,
+            u64::MAX,
+            self.sync.textures_available_semaphores[self.frame],
+            vk::Fence::null(),
+        );
+
+        // get the image or rebuild if not found
+        let index = match result {
+            Ok((index, _)) => index as usize,
+            Err(vk::ErrorCode::OUT_OF_DATE_KHR) => {
+                self.resize.out_of_date();
+                self.recreate_swapchain(window)?;
+                return Ok(None);
+            }
+            Err(e) => return Err(anyhow!(e)),
+        };
+
+        // wait for the frame that last rendered to the image
+        self.sync
+            .timeline
+            .wait(&self.device, self.sync.in_flight_textures[index])?;
+
+        // capture the frame when the export clock reached the next interval
+        let extent = self.swapchain.extent;
+        if let Some(export) = &mut self.export {
+            let capture = export.clock.tick(export.started.elapsed());
+            export.pending[self.frame] = capture.map(|(i, t)| (i, t, extent));
+        }
+
+        // decide which part of the scene is redrawn
+        let regions = self.damage_regions();
+        self.damage.area = self.damage_area(&regions);
+        self.damage.scissors = self.damage_scissors(&regions);
+
+        // the frame counts from when it was asked for, the refresh from the monitor unless
+        // the presentation engine tells
+        if let Some(latency) = &mut self.latency {
+            if latency.tracker.refresh().is_none() {
+                let millihertz = window.refresh_rate_millihertz();
+                let refresh = millihertz.map(|mhz| Duration::from_secs_f64(1000.0 / mhz as f64));
+                latency.tracker.set_refresh(refresh);
+            }
+            let at = begun.duration_since(latency.started);
+            latency.tracker.begin(latency.next_id, at);
+        }
+
+        let slot = self.frame;
+        self.events.emit(DeviceEvent::FrameBegin { slot, index });
+        Ok(Some((index, regions)))
+    }
+
+    /// write the late data of the recorded frame with `latch`, the last step before submit.
+    pub(super) fn latch_frame<L>(&self, index: usize, latch: L) -> Result<()>
+    where
+        L: FnOnce(&Device, usize) -> Result<()>,
+    {
+        debug_span!("latch").in_scope(|| latch(self, index))
+    }
+
+    /// the regions presents hint as changed in damage mode, none while everything is.
+    pub(super) fn damage_hint(&self, regions: Vec<vk::Rect2D>) -> Option<Vec<vk::Rect2D>> {
+        (self.damage.enabled && self.damage.valid).then_some(regions)
+    }
+
+    /// submit the recorded frame and present image `index`, `hint` are the regions that
+    /// changed since the image was presented before, all of them when none.
+    pub(super) unsafe fn present_frame(
+        &mut self,
+        window: &dyn DeviceWindow,
+        index: usize,
+        hint: Option<&[vk::Rect2D]>,
+    ) -> Result<()> {
+        // submit after the image is available and the dependencies are done
+        let waits = &[(
+            self.sync.textures_available_semaphores[self.frame],
+            vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+        )];
+        let command_buffers = &[self.commands.primary_command_buffers[index].buffer];
+        let signal_semaphores = &[self.sync.render_finished_semaphores[self.frame]];
+        let value = self.submit_frame(waits, command_buffers, signal_semaphores)?;
+        self.sync.in_flight_frames[self.frame] = value;
+        self.sync.in_flight_textures[index] = value;
+        self.staging.finish(self.frame, value);
+
+        // remember which copy the frame writes
+        if let Some(report) = &mut self.report {
+            report.submitted[self.frame] = Some(index);
+            report.last = Some(index);
+        }
 
-// Whether the validation layers should be enabled.
-const VALIDATION_ENABLED: bool = cfg!(debug_assertions);
-// The name of the validation layers.
-const VALIDATION_LAYER: vk::ExtensionName =
-    vk::ExtensionName::from_bytes(b"VK_LAYER_KHRONOS_validation");
+        // get the swapchain
+        let swapchains = &[self.swapchain.handle];
+
+        // image index to present
+        let indices = &[index as u32];
+
+        // get the present infoe
+        let mut present_info = vk::PresentInfoKHR::builder()
+            .wait_semaphores(signal_semaphores)
+            .swapchains(swapchains)
+            .image_indices(indices);
+
+        // hint the changed regions to the presentation engine
+        let rectangles = hint
+            .unwrap_or_default()
+            .iter()
+            .map(|r| {
+                vk::RectLayerKHR::builder()
+                    .offset(r.offset)
+                    .extent(r.extent)
+                    .layer(0)
+                    .build()
+            })
+            .collect::<Vec<_>>();
+        let present_regions = &[vk::PresentRegionKHR::builder().rectangles(&rectangles)];
+        let mut present_regions_info = vk::PresentRegionsKHR::builder().regions(present_regions);
+        if self.incremental_present && hint.is_some() {
+            present_info = present_info.push_next(&mut present_regions_info);
+        }
 
-// The required device extensions.
-const DEVICE_EXTENSIONS: &[vk::ExtensionName] = &[vk::KHR_SWAPCHAIN_EXTENSION.name];
-/// The Vulkan SDK version that started requiring the portability subset extension for macOS.
-const PORTABILITY_MACOS_VERSION: Version = Version::new(1, 3, 216);
+        // tag the present so display timing reports when the frame was shown
+        let present_id = self.latency.as_ref().map(|l| l.next_id);
+        let times = &[vk::PresentTimeGOOGLE {
+            present_id: present_id.unwrap_or_default(),
+            desired_present_time: 0,
+        }];
+        let mut present_times_info = vk::PresentTimesInfoGOOGLE::builder().times(times);
+        if self.display_timing && present_id.is_some() {
+            present_info = present_info.push_next(&mut present_times_info);
+        }
 
-// The maximum number of frames that can be processed concurrently.
-const MAX_FRAMES_IN_FLIGHT: usize = 2;
+        // get the current presentation info
+        let result = self
+            .device
+            .queue_present_khr(self.queue.present, &present_info);
+
+        // the scene target is complete from now on
+        self.damage.valid = true;
+        self.damage.rects.clear();
+
+        // match the frames displayed so far
+        if let Some(latency) = &mut self.latency {
+            let id = latency.next_id;
+            latency.tracker.presented(id, latency.started.elapsed());
+            latency.next_id = id.wrapping_add(1);
+            if self.display_timing {
+                let timings = self
+                    .device
+                    .get_past_presentation_timing_google(self.swapchain.handle);
+                for timing in timings.unwrap_or_default() {
+                    latency.tracker.displayed(
+                        timing.present_id,
+                        timing.actual_present_time,
+                        timing.earliest_present_time,
+                    );
+                }
+            } else {
+                latency.slots[self.frame] = Some(id);
+            }
+        }
 
-struct DeviceSyncData {
-    textures_available_semaphores: Vec<vk::Semaphore>,
-    render_finished_semaphores: Vec<vk::Semaphore>,
-    in_flight_fences: Vec<vk::Fence>,
-    in_flight_textures: Vec<vk::Fence>,
-}
+        // a swapchain that no longer matches is replaced once the size settled,
+        // one that can't be presented anymore right away
+        match result {
+            Ok(vk::SuccessCode::SUBOPTIMAL_KHR) => self.resize.suboptimal(),
+            Err(vk::ErrorCode::OUT_OF_DATE_KHR) => self.resize.out_of_date(),
+            Err(e) => return Err(anyhow!(e)),
+            Ok(_) => {}
+        }
+        let slot = self.frame;
+        self.events.emit(DeviceEvent::FrameEnd { slot, index });
+        if self.resize.should_recreate() {
+            self.recreate_swapchain(window)?;
+        }
 
-struct DeviceTargetData {
-    albedo_texture: Texture,
-    albedo_texture_view: TextureView,
-    depth_texture: Texture,
-    depth_texture_view: TextureView,
-}
+        // update frame counter
+        self.frame = (self.frame + 1) % MAX_FRAMES_IN_FLIGHT;
 
-struct SwapchainData {
-    handle: vk::SwapchainKHR,
-    extent: vk::Extent2D,
-    format: vk::Format,
-    framebuffers: Vec<FrameBuffer>,
-    render_pass: vk::RenderPass,
-    textures: Vec<Texture>,
-    views: Vec<TextureView>,
-    target: DeviceTargetData,
-}
+        Ok(())
+    }
 
-struct QueueData {
-    graphics: vk::Queue,
-    present: vk::Queue,
-}
+    // submit the frame on the timeline after the dependencies of other queues
+    unsafe fn submit_frame(
+        &mut self,
+        waits: &[(vk::Semaphore, vk::PipelineStageFlags)],
+        command_buffers: &[vk::CommandBuffer],
+        signals: &[vk::Semaphore],
+    ) -> Result<u64> {
+        let dependencies = std::mem::take(&mut self.sync.dependencies);
+        self.sync.timeline.submit(
+            &self.device,
+            self.queue.graphics,
+            command_buffers,
+            waits,
+            &dependencies,
+            signals,
+        )
+    }
 
-pub struct Device {
-    entry: Entry,
-    instance: vulkanalia::Instance,
-    surface: vk::SurfaceKHR,
-    physical: vk::PhysicalDevice,
-    device: vulkanalia::Device,
-    samples: vk::SampleCountFlags,
-    messenger: Option<vk::DebugUtilsMessengerEXT>,
-    swapchain: SwapchainData,
-    queue: QueueData,
-    sync: DeviceSyncData,
-    frame: usize,
-}
+    /// the reported damage clipped to the swapchain, empty rectangles are dropped.
+    fn damage_regions(&self) -> Vec<vk::Rect2D> {
+        clip_rects(&self.damage.rects, self.swapchain.extent)
+    }
 
-impl Device {
-    pub fn create(window: &Window, title: &str) -> Result<Self> {
-        unsafe {
-            let loader = LibloadingLoader::new(LIBRARY)?;
-            let entry = Entry::new(loader).map_err(|b| anyhow!("{}", b))?;
-            let (instance, messenger) = create_instance(&entry, window, title)?;
-            let surface = vk_window::create_surface(&instance, &window, &window)?;
-            let physical = pick_physical_device(&instance, &surface)?;
-            let samples = get_max_msaa_samples(&instance, &physical);
+    /// the regions in pixels of the scene target when they are cleared one by one this frame,
+    /// empty when their bounds are.
+    fn damage_scissors(&self, regions: &[vk::Rect2D]) -> Vec<vk::Rect2D> {
+        let inline = self.scene_contents == vk::SubpassContents::INLINE;
+        if !self.damage.enabled || !self.damage.valid || !self.damage.scissored || !inline {
+            return vec![];
+        }
 
-            // create the logical device
-            let (device, graphics_queue, present_queue) =
-                create_logical_device(&entry, &instance, &surface, &physical)?;
+        let (from, to) = (self.swapchain.extent, self.swapchain.target.extent);
+        let scissors = regions
+            .iter()
+            .map(|r| scale_rect(*r, from, to))
+            .collect::<Vec<_>>();
+        if scissored_clears(&scissors, self.damage.area) {
+            scissors
+        } else {
+            vec![]
+        }
+    }
 
-            // create the swapchain
-            let swapchain =
-                construct_swapchain(window, &instance, &surface, &physical, &device, &samples)?;
+    /// the area of the scene recorded this frame, the bounds of all regions in damage mode.
+    /// regions are in swapchain pixels, the area in pixels of the scene target.
+    fn damage_area(&self, regions: &[vk::Rect2D]) -> vk::Rect2D {
+        let full = vk::Rect2D {
+            offset: vk::Offset2D::default(),
+            extent: self.swapchain.target.extent,
+        };
+
+        // without a complete previous frame everything is redrawn
+        if !self.damage.enabled || !self.damage.valid {
+            return full;
+        }
 
-            // create sync objects
-            let sync = create_sync_objects(&device, &swapchain)?;
+        // render areas can't be empty, a single pixel is redrawn when nothing changed
+        let Some(first) = regions.first() else {
+            return vk::Rect2D {
+                offset: vk::Offset2D::default(),
+                extent: vk::Extent2D {
+                    width: 1,
+                    height: 1,
+                },
+            };
+        };
+
+        let (x0, y0, x1, y1) = regions.iter().fold(
+            (
+                first.offset.x,
+                first.offset.y,
+                first.offset.x + first.extent.width as i32,
+                first.offset.y + first.extent.height as i32,
+            ),
+            |(x0, y0, x1, y1), r| {
+                (
+                    x0.min(r.offset.x),
+                    y0.min(r.offset.y),
+                    x1.max(r.offset.x + r.extent.width as i32),
+                    y1.max(r.offset.y + r.extent.height as i32),
+                )
+            },
+        );
 
-            // init app instance
-            Ok(Self {
-                entry,
-                instance,
-                surface,
-                physical,
-                device,
-                samples,
-                messenger,
-                swapchain,
-                queue: QueueData {
-                    graphics: graphics_queue,
-                    present: present_queue,
+        let area = vk::Rect2D {
+            offset: vk::Offset2D { x: x0, y: y0 },
+            extent: vk::Extent2D {
+                width: (x1 - x0) as u32,
+                height: (y1 - y0) as u32,
+            },
+        };
+        scale_rect(area, self.swapchain.extent, full.extent)
+    }
+
+    /// name the swapchain images, the scene target and the frame command buffers.
+    fn name_frame_objects(&self) {
+        let target = &self.swapchain.target;
+        self.set_name(target.albedo_texture.image, "scene color");
+        self.set_name(target.depth_texture.image, "scene depth");
+        self.set_name(target.scene_texture.image, "scene");
+        self.set_name(self.swapchain.scene_framebuffer.buffer, "scene");
+        for (i, texture) in self.swapchain.textures.iter().enumerate() {
+            self.set_name(texture.image, &format!("swapchain image {}", i));
+        }
+        let buffers = &self.commands.primary_command_buffers;
+        for (i, command_buffer) in buffers.iter().enumerate() {
+            self.set_name(command_buffer.buffer, &format!("frame {}", i));
+        }
+    }
+
+    /// reset and begin the primary command buffer of a swapchain image, with the copies of
+    /// the textures uploaded since the last frame.
+    pub(super) unsafe fn begin_commands(&self, index: usize) -> Result<vk::CommandBuffer> {
+        // reset command pool
+        self.commands.pools[index].reset(&self.device)?;
+
+        // get the command buffer associated
+        let command_buffer = self.commands.primary_command_buffers[index].buffer;
+
+        // prepare command info
+        let info = vk::CommandBufferBeginInfo::builder()
+            .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+
+        // begin the command
+        self.device.begin_command_buffer(command_buffer, &info)?;
+
+        // copy the textures uploaded since the last frame, their staging goes with the frame
+        for upload in self.uploads.take() {
+            upload.record(&self.device, command_buffer);
+            self.deletion.retire(self.frame_value() + 1, upload.staging);
+        }
+
+        Ok(command_buffer)
+    }
+
+    /// end the prepare label and record the scene pass with `scene`.
+    pub(super) unsafe fn record_scene<S>(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        scene: S,
+    ) -> Result<()>
+    where
+        S: FnOnce(&Device, vk::CommandBuffer, usize) -> Result<()>,
+    {
+        self.end_label(command_buffer);
+
+        // define render area, only the damage is cleared and resolved in damage mode
+        let render_area = self.damage.area;
+
+        // keep the previous scene outside the render area once there is one
+        // there is nothing to load before the scene target was drawn once
+        let load = if self.damage.valid || !self.scene_load.loads() {
+            self.scene_load
+        } else {
+            PassLoad::default()
+        };
+        let scissored = !self.damage.scissors.is_empty();
+        let render_pass = if scissored {
+            self.render_passes.scene_partial_load_render_pass
+        } else if self.damage.enabled && self.damage.valid {
+            self.render_passes.scene_partial_render_pass
+        } else {
+            self.load_render_pass(&load)?
+        };
+
+        let clear_values = &load.clear_values();
+        let info = vk::RenderPassBeginInfo::builder()
+            .render_pass(render_pass)
+            .framebuffer(self.swapchain.scene_framebuffer.buffer)
+            .render_area(render_area)
+            .clear_values(clear_values);
+
+        // begin the scene render pass
+        self.begin_label(command_buffer, "scene", SCENE_LABEL);
+        self.device
+            .cmd_begin_render_pass(command_buffer, &info, self.scene_contents);
+
+        // the pass loaded everything, only the damaged rectangles start over
+        if scissored {
+            let attachments = [
+                vk::ClearAttachment {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    color_attachment: 0,
+                    clear_value: clear_values[0],
                 },
-                sync,
-                frame: 0,
-            })
+                vk::ClearAttachment {
+                    aspect_mask: vk::ImageAspectFlags::DEPTH,
+                    color_attachment: 0,
+                    clear_value: clear_values[1],
+                },
+            ];
+            let rects = self
+                .damage
+                .scissors
+                .iter()
+                .map(|r| vk::ClearRect {
+                    rect: *r,
+                    base_array_layer: 0,
+                    layer_count: 1,
+                })
+                .collect::<Vec<_>>();
+            self.device
+                .cmd_clear_attachments(command_buffer, &attachments, &rects);
+        }
+
+        // record the scene draws, they see the extent of the scene target
+        self.recording_scene.set(true);
+        let result =
+            debug_span!("pass", name = "scene").in_scope(|| scene(self, command_buffer, index));
+        self.recording_scene.set(false);
+        result?;
+
+        // end the scene render pass
+        self.device.cmd_end_render_pass(command_buffer);
+        self.end_label(command_buffer);
+
+        Ok(())
+    }
+
+    /// record post processing with `post` and the readbacks, then end the command buffer.
+    pub(super) unsafe fn finish_commands<P>(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        post: P,
+    ) -> Result<()>
+    where
+        P: FnOnce(&Device, vk::CommandBuffer, usize) -> Result<()>,
+    {
+        // record post processing and the present pass
+        self.begin_label(command_buffer, "post", POST_LABEL);
+        debug_span!("post").in_scope(|| post(self, command_buffer, index))?;
+        self.end_label(command_buffer);
+
+        // keep a copy of the presented image for bug reports
+        if let Some(readback) = self.report.as_ref().and_then(|r| r.readbacks.get(index)) {
+            self.record_readback(command_buffer, index, readback);
+        }
+
+        // and one for the export when the frame is captured
+        if let Some(export) = &self.export {
+            if export.pending[self.frame].is_some() {
+                self.record_readback(command_buffer, index, &export.readbacks[self.frame]);
+            }
         }
+
+        // end the command buffer
+        self.device.end_command_buffer(command_buffer)?;
+
+        Ok(())
     }
 
-    /// update the app.
-    pub fn update(&mut self, window: &Window, count: usize) -> Result<()> {
+    /// end the opaque part of the scene pass and continue the scene with depth read only.
+    /// must be recorded in the scene closure, pipelines of `render_pass` stay usable and
+    /// the depth drawn so far can be sampled through `depth_view` until the scene ends.
+    pub fn begin_transparent_pass(&self, command_buffer: vk::CommandBuffer) {
         unsafe {
-            // create an in flight fence to wait for
-            let in_flight_fence = self.sync.in_flight_fences[self.frame];
+            // end the opaque part
+            self.device.cmd_end_render_pass(command_buffer);
+
+            // everything is loaded, so there is nothing to clear
+            let (framebuffer, render_area) = match self.active_target.get() {
+                Some(target) => (target.framebuffer, self.scissor()),
+                None => (self.swapchain.scene_framebuffer.buffer, self.damage.area),
+            };
+            let info = vk::RenderPassBeginInfo::builder()
+                .render_pass(self.render_passes.scene_transparent_render_pass)
+                .framebuffer(framebuffer)
+                .render_area(render_area);
 
-            // wait for the fence
             self.device
-                .wait_for_fences(&[in_flight_fence], true, u64::max_value())?;
-
-            // get next image
-            let result = self.device.acquire_next_image_khr(
-                self.swapchain.handle,
-                u64::max_value(),
-                self.sync.textures_available_semaphores[self.frame],
-                vk::Fence::null(),
-            );
+                .cmd_begin_render_pass(command_buffer, &info, self.scene_contents);
+        }
+    }
 
-            // get the image or rebuild if not found
-            let index = match result {
-                Ok((index, _)) => index as usize,
-                Err(vk::ErrorCode::OUT_OF_DATE_KHR) => {
-                    return recontruct_swapchain(
-                        window, instance, surface, physical, device, samples, swapchain,
-                    )
-                }
-                Err(e) => return Err(anyhow!(e)),
-            };
+    /// begin the scene render pass on offscreen attachments, must be recorded outside of any
+    /// render pass, e.g. in `prepare`. until `end_target_pass` scene draws go to the target,
+    /// `extent`, `scissor` and `depth_view` describe it and `begin_transparent_pass` works.
+    /// `load` applies to the target, loading needs attachments a pass drew into before.
+    pub fn begin_target_pass(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        target: &SceneAttachments,
+        load: &PassLoad,
+    ) -> Result<()> {
+        let clear_values = &load.clear_values();
+        let info = vk::RenderPassBeginInfo::builder()
+            .render_pass(unsafe { self.load_render_pass(load)? })
+            .framebuffer(target.framebuffer.buffer)
+            .render_area(vk::Rect2D {
+                offset: vk::Offset2D::default(),
+                extent: target.extent,
+            })
+            .clear_values(clear_values);
+
+        debug!(extent = ?target.extent, "begin target pass");
+        self.begin_label(command_buffer, "target", SCENE_LABEL);
+        unsafe {
+            self.device
+                .cmd_begin_render_pass(command_buffer, &info, self.scene_contents)
+        };
+
+        self.active_target.set(Some(ActiveTarget {
+            framebuffer: target.framebuffer.buffer,
+            extent: target.extent,
+            depth_view: target.depth_texture_view.view,
+        }));
+        Ok(())
+    }
 
-            // get the current image to use
-            let texture_in_flight = self.sync.in_flight_textures[index];
+    /// end the pass begun by `begin_target_pass`, the resolved color is readable by shaders
+    /// from here on.
+    pub fn end_target_pass(&self, command_buffer: vk::CommandBuffer) {
+        debug!("end target pass");
+        unsafe { self.device.cmd_end_render_pass(command_buffer) };
+        self.end_label(command_buffer);
+        self.active_target.set(None);
+    }
 
-            // check if valid
-            if !texture_in_flight.is_null() {
-                // wait for it until it is valid
-                self.device
-                    .wait_for_fences(&[texture_in_flight], true, u64::max_value())?;
-            }
+    /// begin the render pass writing the swapchain image, the previous contents are discarded.
+    pub fn begin_present_pass(&self, command_buffer: vk::CommandBuffer, index: usize) {
+        // define render area
+        let render_area = vk::Rect2D::builder()
+            .offset(vk::Offset2D::default())
+            .extent(self.swapchain.extent);
 
-            // set next image to use
-            self.sync.in_flight_textures[index] = in_flight_fence;
+        let info = vk::RenderPassBeginInfo::builder()
+            .render_pass(self.render_passes.present_render_pass)
+            .framebuffer(self.swapchain.present_framebuffers[index].buffer)
+            .render_area(render_area);
+
+        debug!(index, "begin present pass");
+        self.begin_label(command_buffer, "present", POST_LABEL);
+        unsafe {
+            self.device
+                .cmd_begin_render_pass(command_buffer, &info, vk::SubpassContents::INLINE);
+        }
+    }
 
-            // update command buffer
-            // self.update_command_buffer(index, count)?;
+    /// end the render pass writing the swapchain image.
+    pub fn end_present_pass(&self, command_buffer: vk::CommandBuffer) {
+        debug!("end present pass");
+        unsafe { self.device.cmd_end_render_pass(command_buffer) };
+        self.end_label(command_buffer);
+    }
 
-            // update uniform buffer
-            // self.update_uniform_buffer(index)?;
+    /// recreates the swapchain and everything depending on it.
+    unsafe fn recreate_swapchain(&mut self, window: &dyn DeviceWindow) -> Result<()> {
+        // a surface without area can't have a swapchain, the old one is kept until it has
+        let support = SwapChainSupport::get(&self.instance, &self.surface, self.physical)?;
+        let (width, height) = window.inner_size();
+        if surface_extent(width, height, &support.capabilities).is_none() {
+            self.resize.surface_lost_area();
+            return Ok(());
+        }
 
-            let wait_semaphores = &[self.sync.textures_available_semaphores[self.frame]];
-            let wait_stages = &[vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
-            let command_buffers = &[self.sync.primary_command_buffers[index].buffer];
-            let signal_semaphores = &[self.sync.render_finished_semaphores[self.frame]];
-            let submit_info = vk::SubmitInfo::builder()
-                .wait_semaphores(wait_semaphores)
-                .wait_dst_stage_mask(wait_stages)
-                .command_buffers(command_buffers)
-                .signal_semaphores(signal_semaphores);
+        // wait until device is idle
+        self.device.device_wait_idle()?;
 
-            // reset all fences
-            self.device.reset_fences(&[in_flight_fence])?;
+        // exported frames in flight were copied at the old extent
+        self.flush_frame_export()?;
 
-            // submit buffers to queue
+        // the render passes are kept, only the present pass follows a new surface format
+        let format = get_surface_format(&support.formats).format;
+        if format != self.render_passes.present_format {
+            debug!(?format, "recreate present render pass");
             self.device
-                .queue_submit(self.queue.graphics, &[submit_info], in_flight_fence)?;
+                .destroy_render_pass(self.render_passes.present_render_pass, None);
+            self.render_passes.present_render_pass =
+                create_present_render_pass(&self.device, format)?;
+            self.render_passes.present_format = format;
+        }
 
-            // get the swapchain
-            let swapchains = &[self.swapchain.handle];
+        // rebuild the views and framebuffers, the old swapchain hands its images over
+        let rebuild = recontruct_swapchain(
+            window,
+            &self.instance,
+            &self.surface,
+            &self.physical,
+            &self.device,
+            &self.samples,
+            &self.render_passes,
+            self.render_scale,
+            &mut self.swapchain,
+        )?;
+        self.target_stale = false;
+        debug!(?rebuild, "recreate swapchain");
+
+        // the per-image command objects only follow the image count
+        if rebuild.commands {
+            let indices = QueueFamilyIndices::get(&self.instance, &self.surface, self.physical)?;
+            resize_command_objects(
+                &self.device,
+                indices.graphics,
+                &mut self.commands,
+                self.swapchain.textures.len(),
+            )?;
+        }
 
-            // image index to present
-            let indices = &[index as u32];
+        self.name_frame_objects();
 
-            // get the present infoe
-            let present_info = vk::PresentInfoKHR::builder()
-                .wait_semaphores(signal_semaphores)
-                .swapchains(swapchains)
-                .image_indices(indices);
+        // image count might have changed
+        self.sync
+            .in_flight_textures
+            .resize(self.swapchain.textures.len(), 0);
 
-            // get the current presentation info
-            let result = self
-                .device
-                .queue_present_khr(self.queue.present, &present_info);
+        // the copies follow the new image count and extent
+        if rebuild.readbacks {
+            self.recreate_readbacks()?;
+        }
+        if let Some(report) = &mut self.report {
+            report.submitted = vec![None; MAX_FRAMES_IN_FLIGHT];
+            report.completed = None;
+            report.last = None;
+        }
 
-            // check if changed or resized
-            let changed = result == Ok(vk::SuccessCode::SUBOPTIMAL_KHR)
-                || result == Err(vk::ErrorCode::OUT_OF_DATE_KHR);
+        // a new scene target starts out empty, a kept one still holds the last frame
+        if rebuild.scene_target {
+            self.damage.valid = false;
+            self.damage.area = vk::Rect2D {
+                offset: vk::Offset2D::default(),
+                extent: self.swapchain.target.extent,
+            };
+        }
 
-            // check if resizing occurred and if resize the swapchain
-            if self.resized || changed {
-                // reset resized status
-                self.resized = false;
+        // the swapchain matches the window again
+        self.resize.recreated();
 
-                // recreate the swapchain
-                self.recreate_swapchain(window)?;
-            } else if let Err(e) = result {
-                // handle error
-                return Err(anyhow!(e));
+        // frames of the old swapchain are never reported, the new one may refresh differently
+        let refresh = self.display_refresh();
+        if let Some(latency) = &mut self.latency {
+            latency.tracker.pause();
+            latency.slots = [None; MAX_FRAMES_IN_FLIGHT];
+            if refresh.is_some() {
+                latency.tracker.set_refresh(refresh);
             }
+        }
+        self.events.emit(DeviceEvent::SwapchainRecreated {
+            format: self.swapchain.format,
+            extent: self.swapchain.extent,
+        });
 
-            // update frame counter
-            self.frame = (self.frame + 1) % MAX_FRAMES_IN_FLIGHT;
+        Ok(())
+    }
 
-            // all went fine
-            Ok(())
+    /// replace the report and export copies with ones for the current swapchain images.
+    unsafe fn recreate_readbacks(&mut self) -> Result<()> {
+        if let Some(report) = &self.report {
+            report
+                .readbacks
+                .iter()
+                .for_each(|b| b.destroy(&self.device));
+        }
+        let readbacks = match self.report {
+            Some(_) => self.create_readbacks()?,
+            None => vec![],
+        };
+        if let Some(report) = &mut self.report {
+            report.readbacks = readbacks;
+        }
+        if let Some(export) = &self.export {
+            export
+                .readbacks
+                .iter()
+                .for_each(|b| b.destroy(&self.device));
+        }
+        let readbacks = match self.export {
+            Some(_) => self.create_export_readbacks()?,
+            None => vec![],
+        };
+        if let Some(export) = &mut self.export {
+            export.readbacks = readbacks;
+        }
+        Ok(())
+    }
+
+    /// rebuild the scene target at the render scale, the swapchain is kept.
+    unsafe fn recreate_scene_target(&mut self) -> Result<()> {
+        // the target may still be drawn or read by pending frames
+        self.device.device_wait_idle()?;
+
+        let extent = render_extent(self.swapchain.extent, self.render_scale);
+        debug!(?extent, scale = self.render_scale, "recreate scene target");
+        let (target, framebuffer) = create_scene_target(
+            &self.instance,
+            &self.physical,
+            &self.device,
+            &self.samples,
+            &self.render_passes,
+            extent,
+        )?;
+        destroy_scene_target(
+            &self.device,
+            &self.swapchain.target,
+            &self.swapchain.scene_framebuffer,
+        );
+        self.swapchain.target = target;
+        self.swapchain.scene_framebuffer = framebuffer;
+        self.target_stale = false;
+        self.name_frame_objects();
+
+        // the new scene target starts out empty
+        self.damage.valid = false;
+        self.damage.area = vk::Rect2D {
+            offset: vk::Offset2D::default(),
+            extent,
+        };
+
+        Ok(())
+    }
+
+    /// create one host visible copy per swapchain image, none when images can't be copied.
+    unsafe fn create_readbacks(&self) -> Result<Vec<Buffer>> {
+        // copying needs transfer support on the swapchain images
+        let support = SwapChainSupport::get(&self.instance, &self.surface, self.physical)?;
+        if !support
+            .capabilities
+            .supported_usage_flags
+            .contains(vk::ImageUsageFlags::TRANSFER_SRC)
+        {
+            warn!("Swapchain images can't be copied, bug reports won't include frames.");
+            return Ok(vec![]);
+        }
+
+        let extent = self.swapchain.extent;
+        let size = extent.width as vk::DeviceSize * extent.height as vk::DeviceSize * 4;
+        (0..self.image_count())
+            .map(|_| {
+                self.create_buffer(
+                    size,
+                    vk::BufferUsageFlags::TRANSFER_DST,
+                    vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+                )
+            })
+            .collect()
+    }
+
+    /// copy the presentable swapchain image into its readback buffer.
+    unsafe fn record_readback(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        readback: &Buffer,
+    ) {
+        // the image goes back to presentation after the copy
+        record_texture_readback(
+            &self.device,
+            command_buffer,
+            self.swapchain.textures[index].image,
+            self.swapchain.extent.width,
+            self.swapchain.extent.height,
+            vk::ImageLayout::PRESENT_SRC_KHR,
+            readback.buffer,
+        );
+    }
+
+    /// write a bug report with the last completed frame, failures are only logged.
+    fn report_bug(&mut self, reason: &str, lost: bool) {
+        let Some(report) = &self.report else {
+            return;
+        };
+
+        // a working device finishes everything so the newest copy can be used
+        let index = if !lost && unsafe { self.device.device_wait_idle() }.is_ok() {
+            report.last
+        } else {
+            report.completed
+        };
+
+        // read back the copy of that frame
+        let extent = self.swapchain.extent;
+        let frame = index.and_then(|i| report.readbacks.get(i)).and_then(|b| {
+            let count = (extent.width * extent.height * 4) as usize;
+            match unsafe { b.read::<u8>(&self.device, 0, count) } {
+                Ok(pixels) => Some(ReportFrame::from_swapchain(
+                    extent.width,
+                    extent.height,
+                    self.swapchain.format,
+                    pixels,
+                )),
+                Err(e) => {
+                    warn!("Failed to read back the last frame ({}).", e);
+                    None
+                }
+            }
+        });
+
+        let state = self.describe_state(&report.passes);
+        match write_bug_report(&report.settings.folder, reason, &state, frame.as_ref()) {
+            Ok(path) => warn!("Bug report written to {} ({}).", path.display(), reason),
+            Err(e) => error!("Failed to write bug report ({}).", e),
+        }
+
+        // the next report needs as many new errors
+        if let Some(report) = &mut self.report {
+            report.reported_errors = validation_errors();
+        }
+    }
+
+    /// describe the adapter and device configuration for bug reports.
+    fn describe_state(&self, passes: &[String]) -> String {
+        let properties = unsafe { self.instance.get_physical_device_properties(self.physical) };
+
+        // the device extensions enabled at creation
+        let mut extensions = DEVICE_EXTENSIONS
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        if self.incremental_present {
+            extensions.push(vk::KHR_INCREMENTAL_PRESENT_EXTENSION.name.to_string());
         }
+        if self.timeline_semaphores {
+            extensions.push(vk::KHR_TIMELINE_SEMAPHORE_EXTENSION.name.to_string());
+        }
+
+        // the device always records the scene and hands the rest to `post`
+        let mut recorded = vec!["scene".to_string()];
+        recorded.extend(passes.iter().cloned());
+        recorded.push("present".to_string());
+
+        let extent = self.swapchain.extent;
+        let mut state = String::new();
+        state += &format!("adapter: {}\n", properties.device_name);
+        state += &format!("adapter type: {:?}\n", properties.device_type);
+        state += &format!(
+            "vendor id: {:#06x}, device id: {:#06x}\n",
+            properties.vendor_id, properties.device_id
+        );
+        state += &format!("api version: {}\n", Version::from(properties.api_version));
+        state += &format!("driver version: {:#x}\n", properties.driver_version);
+        state += &format!("validation: {}\n", self.is_validated());
+        state += &format!("extensions: {}\n", extensions.join(", "));
+        state += "features: sampler_anisotropy, sample_rate_shading\n";
+        state += &format!("samples: {:?}\n", self.samples);
+        state += &format!(
+            "swapchain: {}x{} {:?}, {} images\n",
+            extent.width,
+            extent.height,
+            self.swapchain.format,
+            self.image_count()
+        );
+        state += &format!("scene format: {:?}\n", SCENE_FORMAT);
+        state += &format!(
+            "scene: {}x{} at render scale {}, {:?} upscale\n",
+            self.swapchain.target.extent.width,
+            self.swapchain.target.extent.height,
+            self.render_scale,
+            self.upscale_filter
+        );
+        state += &format!("damage mode: {}\n", self.damage.enabled);
+        state += &format!("passes: {}\n", recorded.join(", "));
+        state
     }
 
     pub fn destroy(&self) {
@@ -236,11 +2903,25 @@ impl Device {
             // wait until device is idle
             self.device.device_wait_idle().unwrap();
 
-            // destroy fences
-            self.sync
-                .in_flight_fences
+            // destroy sync objects
+            self.sync.timeline.destroy(&self.device);
+            self.staging.destroy(&self.device);
+            self.deletion.destroy(&self.device);
+            self.registry
+                .borrow_mut()
+                .drain()
                 .iter()
-                .for_each(|f| self.device.destroy_fence(*f, None));
+                .for_each(|r| r.destroy(&self.device));
+            self.uploads
+                .take()
+                .iter()
+                .for_each(|u| u.staging.destroy(&self.device));
+            self.samplers.destroy(&self.device);
+            self.passes.destroy(&self.device);
+            self.load_render_passes
+                .borrow_mut()
+                .drain()
+                .for_each(|(_, p)| self.device.destroy_render_pass(p, None));
             self.sync
                 .render_finished_semaphores
                 .iter()
@@ -250,8 +2931,37 @@ impl Device {
                 .iter()
                 .for_each(|s| self.device.destroy_semaphore(*s, None));
 
-            // deconstruct swapchain
+            // destroy bug report and export copies
+            if let Some(report) = &self.report {
+                report
+                    .readbacks
+                    .iter()
+                    .for_each(|b| b.destroy(&self.device));
+            }
+            if let Some(export) = &self.export {
+                export
+                    .readbacks
+                    .iter()
+                    .for_each(|b| b.destroy(&self.device));
+            }
+
+            // destroy the layout of unused slots
+            self.device
+                .destroy_descriptor_set_layout(self.empty_set_layout, None);
+
+            // destroy command pools
+            destroy_command_objects(&self.device, &self.commands);
+
+            // deconstruct swapchain and its render passes
             destroy_swapchain(&self.device, &self.swapchain);
+            self.device
+                .destroy_swapchain_khr(self.swapchain.handle, None);
+            destroy_render_pass_objects(&self.device, &self.render_passes);
+
+            // what is left was never destroyed
+            for leak in allocation::unregister(self.device.handle()) {
+                warn!(?leak, "device memory was never freed");
+            }
 
             // destroy device
             self.device.destroy_device(None);
@@ -260,9 +2970,9 @@ impl Device {
             self.instance.destroy_surface_khr(self.surface, None);
 
             // check when validation is enabled
-            if VALIDATION_ENABLED && self.messenger.is_some() {
+            if let Some(messenger) = self.messenger {
                 self.instance
-                    .destroy_debug_utils_messenger_ext(self.messenger.unwrap(), None);
+                    .destroy_debug_utils_messenger_ext(messenger, None);
             }
 
             // destroy instance
@@ -271,7 +2981,7 @@ impl Device {
     }
 }
 
-extern "system" fn debug_callback(
+pub(super) extern "system" fn debug_callback(
     severity: vk::DebugUtilsMessageSeverityFlagsEXT,
     type_: vk::DebugUtilsMessageTypeFlagsEXT,
     data: *const vk::DebugUtilsMessengerCallbackDataEXT,
@@ -280,6 +2990,14 @@ extern "system" fn debug_callback(
     let data = unsafe { *data };
     let message = unsafe { CStr::from_ptr(data.message) }.to_string_lossy();
 
+    // keep warnings and errors around for bug reports
+    if severity >= vk::DebugUtilsMessageSeverityFlagsEXT::WARNING {
+        record_validation_message(
+            &format!("({:?}) {}", type_, message),
+            severity >= vk::DebugUtilsMessageSeverityFlagsEXT::ERROR,
+        );
+    }
+
     if severity >= vk::DebugUtilsMessageSeverityFlagsEXT::ERROR {
         error!("({:?}) {}", type_, message);
     } else if severity >= vk::DebugUtilsMessageSeverityFlagsEXT::WARNING {
@@ -293,19 +3011,106 @@ extern "system" fn debug_callback(
     vk::FALSE
 }
 
+unsafe fn create_command_objects(
+    instance: &vulkanalia::Instance,
+    surface: &vk::SurfaceKHR,
+    physical: &vk::PhysicalDevice,
+    device: &vulkanalia::Device,
+    swapchain: &SwapchainData,
+) -> Result<DeviceCommandData> {
+    let indices = QueueFamilyIndices::get(instance, surface, *physical)?;
+
+    // global pool used for one time submits
+    let pool = CommandPool::create(
+        device,
+        indices.graphics,
+        vk::CommandPoolCreateFlags::TRANSIENT,
+    )?;
+
+    // one time pools of the dedicated queues
+    let queue_pools = [
+        (QueueKind::Compute, indices.compute),
+        (QueueKind::Transfer, indices.transfer),
+    ]
+    .into_iter()
+    .filter_map(|(kind, family)| family.map(|f| (kind, f)))
+    .map(|(kind, family)| {
+        let flags = vk::CommandPoolCreateFlags::TRANSIENT;
+        Ok((kind, CommandPool::create(device, family, flags)?))
+    })
+    .collect::<Result<Vec<_>>>()?;
+
+    // per swapchain image pools
+    let mut commands = DeviceCommandData {
+        pool,
+        queue_pools,
+        pools: vec![],
+        primary_command_buffers: vec![],
+    };
+    resize_command_objects(
+        device,
+        indices.graphics,
+        &mut commands,
+        swapchain.textures.len(),
+    )?;
+
+    Ok(commands)
+}
+
+/// match the per image pools to `count` swapchain images, the pools of images that remain
+/// are kept with their command buffers.
+unsafe fn resize_command_objects(
+    device: &vulkanalia::Device,
+    family: u32,
+    commands: &mut DeviceCommandData,
+    count: usize,
+) -> Result<()> {
+    // drop the pools of images that are gone
+    while commands.pools.len() > count {
+        if let Some(pool) = commands.pools.pop() {
+            pool.destroy(device);
+        }
+        commands.primary_command_buffers.pop();
+    }
+
+    // one pool with a primary command buffer per new image
+    while commands.pools.len() < count {
+        let pool = CommandPool::create(device, family, vk::CommandPoolCreateFlags::TRANSIENT)?;
+        let command_buffer = pool.allocate(device, vk::CommandBufferLevel::PRIMARY, 1)?[0];
+        commands.pools.push(pool);
+        commands.primary_command_buffers.push(command_buffer);
+    }
+
+    Ok(())
+}
+
+unsafe fn destroy_command_objects(device: &vulkanalia::Device, commands: &DeviceCommandData) {
+    // destroy per image pools
+    commands.pools.iter().for_each(|p| p.destroy(device));
+
+    // destroy global pools
+    commands.pool.destroy(device);
+    commands
+        .queue_pools
+        .iter()
+        .for_each(|(_, p)| p.destroy(device));
+}
+
 unsafe fn create_sync_objects(
     device: &vulkanalia::Device,
     swapchain: &SwapchainData,
+    timeline_semaphores: bool,
 ) -> Result<DeviceSyncData> {
     let semaphore_info = vk::SemaphoreCreateInfo::builder();
-    let fence_info = vk::FenceCreateInfo::builder().flags(vk::FenceCreateFlags::SIGNALED);
 
-    // create sync object
+    // create sync object, value 0 is complete from the start
     let mut data = DeviceSyncData {
         textures_available_semaphores: vec![],
         render_finished_semaphores: vec![],
-        in_flight_fences: vec![],
-        in_flight_textures: vec![],
+        timeline: Timeline::create(device, timeline_semaphores)?,
+        in_flight_frames: vec![0; MAX_FRAMES_IN_FLIGHT],
+        in_flight_textures: vec![0; swapchain.textures.len()],
+        dependencies: vec![],
     };
 
     for _ in 0..MAX_FRAMES_IN_FLIGHT {
@@ -313,25 +3118,16 @@ unsafe fn create_sync_objects(
             .push(device.create_semaphore(&semaphore_info, None)?);
         data.render_finished_semaphores
             .push(device.create_semaphore(&semaphore_info, None)?);
-
-        data.in_flight_fences
-            .push(device.create_fence(&fence_info, None)?);
     }
 
-    // get the the inflight texture fences
-    data.in_flight_textures = swapchain
-        .textures
-        .iter()
-        .map(|_| vk::Fence::null())
-        .collect();
-
     Ok(data)
 }
 
 unsafe fn create_instance(
     entry: &Entry,
-    window: &Window,
+    window: &dyn DeviceWindow,
     title: &str,
+    validation: ValidationSettings,
 ) -> Result<(Instance, Option<vk::DebugUtilsMessengerEXT>)> {
     // Application Info
 
@@ -350,37 +3146,43 @@ unsafe fn create_instance(
         .map(|l| l.layer_name)
         .collect::<HashSet<_>>();
 
-    if VALIDATION_ENABLED && !available_layers.contains(&VALIDATION_LAYER) {
+    if validation.enabled && !available_layers.contains(&VALIDATION_LAYER) {
         return Err(anyhow!("Validation layer requested but not supported."));
     }
 
-    let layers = if VALIDATION_ENABLED {
+    let layers = if validation.enabled {
         vec![VALIDATION_LAYER.as_ptr()]
     } else {
         Vec::new()
     };
 
     // Extensions
-    let mut extensions = vk_window::get_required_instance_extensions(window)
+    let mut extensions = window
+        .required_extensions()
         .iter()
         .map(|e| e.as_ptr())
         .collect::<Vec<_>>();
 
     // Required by Vulkan SDK on macOS since 1.3.216.
-    let flags = if cfg!(target_os = "macos") && entry.version()? >= PORTABILITY_MACOS_VERSION {
+    let portability = cfg!(target_os = "macos") && entry.version()? >= PORTABILITY_MACOS_VERSION;
+    let flags = if portability {
         info!("Enabling extensions for macOS portability.");
-        extensions.push(
-            vk::KHR_GET_PHYSICAL_DEVICE_PROPERTIES2_EXTENSION
-                .name
-                .as_ptr(),
-        );
         extensions.push(vk::KHR_PORTABILITY_ENUMERATION_EXTENSION.name.as_ptr());
         vk::InstanceCreateFlags::ENUMERATE_PORTABILITY_KHR
     } else {
         vk::InstanceCreateFlags::empty()
     };
 
-    if VALIDATION_ENABLED {
+    // needed by portability and by timeline semaphores on a vulkan 1.0 instance
+    if portability || properties2_available(entry)? {
+        extensions.push(
+            vk::KHR_GET_PHYSICAL_DEVICE_PROPERTIES2_EXTENSION
+                .name
+                .as_ptr(),
+        );
+    }
+
+    if validation.enabled || debug_utils_available(entry)? {
         extensions.push(vk::EXT_DEBUG_UTILS_EXTENSION.name.as_ptr());
     }
 
@@ -392,11 +3194,11 @@ unsafe fn create_instance(
         .flags(flags);
 
     let mut debug_info = vk::DebugUtilsMessengerCreateInfoEXT::builder()
-        .message_severity(vk::DebugUtilsMessageSeverityFlagsEXT::all())
+        .message_severity(validation.severity.flags())
         .message_type(vk::DebugUtilsMessageTypeFlagsEXT::all())
         .user_callback(Some(debug_callback));
 
-    if VALIDATION_ENABLED {
+    if validation.enabled {
         info = info.push_next(&mut debug_info);
     }
 
@@ -404,16 +3206,42 @@ unsafe fn create_instance(
 
     let mut messenger = None;
     // Messenger
-    if VALIDATION_ENABLED {
+    if validation.enabled {
         messenger = Some(instance.create_debug_utils_messenger_ext(&debug_info, None)?);
     }
     Ok((instance, messenger))
 }
 
-unsafe fn pick_physical_device(
+/// The adapters that can drive `window`, in the order of the driver.
+pub fn enumerate_adapters(window: &dyn DeviceWindow) -> Result<Vec<AdapterInfo>> {
+    unsafe {
+        // a throwaway instance and surface, the device creates its own
+        let loader = LibloadingLoader::new(LIBRARY)?;
+        let entry = Entry::new(loader).map_err(|b| anyhow!("{}", b))?;
+        let validation = ValidationSettings::from_env();
+        let (instance, messenger) = create_instance(&entry, window, "adapters", validation)?;
+        let surface = window.create_surface(&instance);
+        let adapters = surface
+            .and_then(|surface| {
+                let adapters = suitable_adapters(&instance, &surface);
+                instance.destroy_surface_khr(surface, None);
+                adapters
+            })
+            .map(|adapters| adapters.into_iter().map(|(_, info)| info).collect());
+
+        if let Some(messenger) = messenger {
+            instance.destroy_debug_utils_messenger_ext(messenger, None);
+        }
+        instance.destroy_instance(None);
+        adapters
+    }
+}
+
+unsafe fn suitable_adapters(
     instance: &Instance,
     surface: &vk::SurfaceKHR,
-) -> Result<vk::PhysicalDevice> {
+) -> Result<Vec<(vk::PhysicalDevice, AdapterInfo)>> {
+    let mut adapters = vec![];
     for physical_device in instance.enumerate_physical_devices()? {
         let properties = instance.get_physical_device_properties(physical_device);
 
@@ -423,12 +3251,33 @@ unsafe fn pick_physical_device(
                 properties.device_name, error
             );
         } else {
-            info!("Selected physical device (`{}`).", properties.device_name);
-            return Ok(physical_device);
+            let info = AdapterInfo::get(instance, physical_device, adapters.len());
+            adapters.push((physical_device, info));
         }
     }
+    Ok(adapters)
+}
+
+unsafe fn pick_physical_device(
+    instance: &Instance,
+    surface: &vk::SurfaceKHR,
+    selection: AdapterSelection,
+) -> Result<(vk::PhysicalDevice, AdapterInfo)> {
+    let mut adapters = suitable_adapters(instance, surface)?;
+    if adapters.is_empty() {
+        return Err(anyhow!("Failed to find suitable physical device."));
+    }
 
-    Err(anyhow!("Failed to find suitable physical device."))
+    let infos = adapters
+        .iter()
+        .map(|(_, info)| info.clone())
+        .collect::<Vec<_>>();
+    let index = selection
+        .choose(&infos)
+        .ok_or_else(|| anyhow!("No suitable physical device for {:?}.", selection))?;
+    let (physical_device, info) = adapters.swap_remove(index);
+    info!("Selected physical device (`{}`).", info.name);
+    Ok((physical_device, info))
 }
 
 unsafe fn check_physical_device(
@@ -496,7 +3345,10 @@ unsafe fn create_logical_device(
     instance: &Instance,
     surface: &vk::SurfaceKHR,
     physical: &vk::PhysicalDevice,
-) -> Result<(vulkanalia::Device, vk::Queue, vk::Queue)> {
+    requested: &[Feature],
+    available_features: &HashSet<Feature>,
+    validated: bool,
+) -> Result<(vulkanalia::Device, QueueData, bool, bool, bool, bool, bool)> {
     // Queue Create Infos
 
     let indices = QueueFamilyIndices::get(instance, surface, *physical)?;
@@ -504,6 +3356,8 @@ unsafe fn create_logical_device(
     let mut unique_indices = HashSet::new();
     unique_indices.insert(indices.graphics);
     unique_indices.insert(indices.present);
+    unique_indices.extend(indices.compute);
+    unique_indices.extend(indices.transfer);
 
     let queue_priorities = &[1.0];
     let queue_infos = unique_indices
@@ -517,7 +3371,7 @@ unsafe fn create_logical_device(
 
     // Layers
 
-    let layers = if VALIDATION_ENABLED {
+    let layers = if validated {
         vec![VALIDATION_LAYER.as_ptr()]
     } else {
         vec![]
@@ -534,42 +3388,453 @@ unsafe fn create_logical_device(
         extensions.push(vk::KHR_PORTABILITY_SUBSET_EXTENSION.name.as_ptr());
     }
 
-    // Features
-    let features = vk::PhysicalDeviceFeatures::builder()
+    // present regions are only a hint, enable them where available
+    let available = instance
+        .enumerate_device_extension_properties(*physical, None)?
+        .iter()
+        .map(|e| e.extension_name)
+        .collect::<HashSet<_>>();
+    let incremental_present = available.contains(&vk::KHR_INCREMENTAL_PRESENT_EXTENSION.name);
+    if incremental_present {
+        extensions.push(vk::KHR_INCREMENTAL_PRESENT_EXTENSION.name.as_ptr());
+    }
+
+    // latency tracking reads display times where the presentation engine reports them
+    let display_timing = available.contains(&vk::GOOGLE_DISPLAY_TIMING_EXTENSION.name);
+    if display_timing {
+        extensions.push(vk::GOOGLE_DISPLAY_TIMING_EXTENSION.name.as_ptr());
+    }
+
+    // frames fall back to fences without timeline semaphores
+    let timeline_semaphores = timeline_semaphores_available(instance, &available);
+    if timeline_semaphores {
+        extensions.push(vk::KHR_TIMELINE_SEMAPHORE_EXTENSION.name.as_ptr());
+    }
+
+    // stereo targets are drawn in one pass where views are available
+    let multiview = multiview_available(instance, &available);
+    if multiview {
+        extensions.push(vk::KHR_MULTIVIEW_EXTENSION.name.as_ptr());
+    }
+
+    // query sets are reset with commands where the host can't
+    let host_query_reset = host_query_reset_available(instance, &available);
+    if host_query_reset {
+        extensions.push(vk::EXT_HOST_QUERY_RESET_EXTENSION.name.as_ptr());
+    }
+
+    // Features, split screen draws to several viewports where available
+    let multi_viewport = instance
+        .get_physical_device_features(*physical)
+        .multi_viewport;
+    let base = vk::PhysicalDeviceFeatures::builder()
         .sampler_anisotropy(true)
-        .sample_rate_shading(true);
+        .sample_rate_shading(true)
+        .multi_viewport(multi_viewport == vk::TRUE)
+        .build();
+    let features = device_features(base, requested, available_features)?;
+    let mut timeline_features =
+        vk::PhysicalDeviceTimelineSemaphoreFeatures::builder().timeline_semaphore(true);
+    let mut multiview_features = vk::PhysicalDeviceMultiviewFeatures::builder().multiview(true);
+    let mut host_query_reset_features =
+        vk::PhysicalDeviceHostQueryResetFeatures::builder().host_query_reset(true);
 
     // Create
-    let info = vk::DeviceCreateInfo::builder()
+    let mut info = vk::DeviceCreateInfo::builder()
         .queue_create_infos(&queue_infos)
         .enabled_layer_names(&layers)
         .enabled_extension_names(&extensions)
         .enabled_features(&features);
+    if timeline_semaphores {
+        info = info.push_next(&mut timeline_features);
+    }
+    if multiview {
+        info = info.push_next(&mut multiview_features);
+    }
+    if host_query_reset {
+        info = info.push_next(&mut host_query_reset_features);
+    }
 
     let device = instance.create_device(*physical, &info, None)?;
 
     // Queues
-    let graphics_queue = device.get_device_queue(indices.graphics, 0);
-    let present_queue = device.get_device_queue(indices.present, 0);
+    let queue = QueueData {
+        graphics_family: indices.graphics,
+        graphics: device.get_device_queue(indices.graphics, 0),
+        present_family: indices.present,
+        present: device.get_device_queue(indices.present, 0),
+        compute: indices.compute.map(|f| (f, device.get_device_queue(f, 0))),
+        transfer: indices.transfer.map(|f| (f, device.get_device_queue(f, 0))),
+        shared: shared_queue_families(indices.graphics, indices.compute, indices.transfer),
+    };
+
+    Ok((
+        device,
+        queue,
+        incremental_present,
+        display_timing,
+        timeline_semaphores,
+        multiview,
+        host_query_reset,
+    ))
+}
+
+// `VK_EXT_debug_utils` names objects for tools like RenderDoc, which provide it without the
+// validation layer
+pub(super) unsafe fn debug_utils_available(entry: &Entry) -> Result<bool> {
+    Ok(entry
+        .enumerate_instance_extension_properties(None)?
+        .iter()
+        .any(|e| e.extension_name == vk::EXT_DEBUG_UTILS_EXTENSION.name))
+}
+
+pub(super) unsafe fn properties2_available(entry: &Entry) -> Result<bool> {
+    Ok(entry
+        .enumerate_instance_extension_properties(None)?
+        .iter()
+        .any(|e| e.extension_name == vk::KHR_GET_PHYSICAL_DEVICE_PROPERTIES2_EXTENSION.name))
+}
+
+// timeline semaphores need the instance to have `VK_KHR_get_physical_device_properties2`,
+// the instances enable it where they can
+pub(super) fn timeline_semaphores_available(
+    instance: &Instance,
+    available: &HashSet<vk::ExtensionName>,
+) -> bool {
+    instance
+        .extensions()
+        .contains(&vk::KHR_GET_PHYSICAL_DEVICE_PROPERTIES2_EXTENSION.name)
+        && available.contains(&vk::KHR_TIMELINE_SEMAPHORE_EXTENSION.name)
+}
+
+// multiview needs `VK_KHR_get_physical_device_properties2` on the instance as well
+pub(super) fn multiview_available(
+    instance: &Instance,
+    available: &HashSet<vk::ExtensionName>,
+) -> bool {
+    instance
+        .extensions()
+        .contains(&vk::KHR_GET_PHYSICAL_DEVICE_PROPERTIES2_EXTENSION.name)
+        && available.contains(&vk::KHR_MULTIVIEW_EXTENSION.name)
+}
+
+// host query resets need `VK_KHR_get_physical_device_properties2` on the instance for their
+// feature
+pub(super) fn host_query_reset_available(
+    instance: &Instance,
+    available: &HashSet<vk::ExtensionName>,
+) -> bool {
+    instance
+        .extensions()
+        .contains(&vk::KHR_GET_PHYSICAL_DEVICE_PROPERTIES2_EXTENSION.name)
+        && available.contains(&vk::EXT_HOST_QUERY_RESET_EXTENSION.name)
+}
+
+/// create a query set, checking timestamps were enabled.
+pub(super) fn create_query_set(
+    device: &vulkanalia::Device,
+    features: &HashSet<Feature>,
+    kind: QueryKind,
+    count: u32,
+) -> Result<QuerySet> {
+    if kind == QueryKind::Timestamp {
+        check_feature(Feature::TimestampQuery, features)?;
+    }
+    unsafe { QuerySet::create(device, kind, count) }
+}
+
+/// create a buffer backed by its own memory allocation.
+pub(super) unsafe fn create_buffer(
+    instance: &vulkanalia::Instance,
+    physical: &vk::PhysicalDevice,
+    device: &vulkanalia::Device,
+    size: vk::DeviceSize,
+    usage: vk::BufferUsageFlags,
+    properties: vk::MemoryPropertyFlags,
+    families: &[u32],
+) -> Result<Buffer> {
+    let limits = instance.get_physical_device_properties(*physical).limits;
+    check_buffer(size, usage, &limits)?;
+
+    // create buffer info
+    let info = vk::BufferCreateInfo::builder()
+        .size(size)
+        .usage(usage)
+        .sharing_mode(sharing_mode(families))
+        .queue_family_indices(families);
+
+    // create native buffer
+    let buffer = device.create_buffer(&info, None)?;
+
+    // get memory requirements
+    let requirements = device.get_buffer_memory_requirements(buffer);
+
+    // get memory info
+    let info = vk::MemoryAllocateInfo::builder()
+        .allocation_size(requirements.size)
+        .memory_type_index(get_memory_type_index(
+            instance,
+            physical,
+            properties,
+            requirements,
+        )?);
+
+    // allocate and bind the memory
+    let memory = allocation::allocate(device, &info, AllocationKind::Buffer, buffer.as_raw())
+        .inspect_err(|_| device.destroy_buffer(buffer, None))?;
+    device.bind_buffer_memory(buffer, memory, 0)?;
+
+    // all done
+    Ok(Buffer::create(buffer, memory, size))
+}
+
+/// upload rgba8 pixels into a sampled and mipmapped texture, the copy runs on `queue`.
+pub(super) unsafe fn upload_texture(
+    instance: &vulkanalia::Instance,
+    physical: &vk::PhysicalDevice,
+    device: &vulkanalia::Device,
+    pool: &CommandPool,
+    queue: vk::Queue,
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+    import: TextureImport,
+) -> Result<(Texture, TextureView)> {
+    let (staged, texture) =
+        stage_texture(instance, physical, device, width, height, pixels, import)?;
+
+    // copy into the first level and fill the others
+    let command_buffer = pool.begin_single(device)?;
+    staged.record(device, command_buffer.buffer);
+    pool.end_single(device, queue, command_buffer)?;
+
+    // cleanup
+    staged.staging.destroy(device);
+
+    // create the view over all levels
+    let view = texture.create_view(
+        device,
+        texture.format,
+        vk::ImageAspectFlags::COLOR,
+        staged.mip_levels,
+    )?;
+
+    Ok((texture, view))
+}
+
+// the pixels of a texture waiting in a staging buffer for their copy
+pub(super) struct StagedTexture {
+    staging: Buffer,
+    image: vk::Image,
+    width: u32,
+    height: u32,
+    mip_levels: u32,
+}
+
+impl StagedTexture {
+    // copy into the first level, fill the others and leave the texture to shaders
+    unsafe fn record(&self, device: &vulkanalia::Device, command_buffer: vk::CommandBuffer) {
+        record_texture_upload(
+            device,
+            command_buffer,
+            self.staging.buffer,
+            self.image,
+            self.width,
+            self.height,
+            self.mip_levels,
+        );
+    }
+}
+
+// create a sampled and mipmapped texture and stage rgba8 pixels for it
+unsafe fn stage_texture(
+    instance: &vulkanalia::Instance,
+    physical: &vk::PhysicalDevice,
+    device: &vulkanalia::Device,
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+    import: TextureImport,
+) -> Result<(StagedTexture, Texture)> {
+    let format = import.format();
+    check_pixels(width, height, pixels)?;
+    let size = pixels.len() as vk::DeviceSize;
+
+    // mipmaps are generated with linear blits, skip them when the format can't
+    let linear = instance
+        .get_physical_device_format_properties(*physical, format)
+        .optimal_tiling_features
+        .contains(vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR);
+    let mip_levels = if linear {
+        max_mip_levels(width, height)
+    } else {
+        1
+    };
+
+    // refuse the extent before anything is staged
+    let limits = instance.get_physical_device_properties(*physical).limits;
+    check_texture(
+        width,
+        height,
+        mip_levels,
+        1,
+        vk::ImageCreateFlags::empty(),
+        vk::SampleCountFlags::_1,
+        vk::ImageUsageFlags::SAMPLED,
+        &limits,
+    )?;
+
+    // stage the pixels
+    let staging = create_buffer(
+        instance,
+        physical,
+        device,
+        size,
+        vk::BufferUsageFlags::TRANSFER_SRC,
+        vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+        &[],
+    )?;
+    staging.write(device, 0, pixels)?;
+
+    // create the texture
+    let texture = create_texture(
+        instance,
+        physical,
+        device,
+        width,
+        height,
+        mip_levels,
+        vk::SampleCountFlags::_1,
+        format,
+        vk::ImageTiling::OPTIMAL,
+        vk::ImageUsageFlags::SAMPLED
+            | vk::ImageUsageFlags::TRANSFER_DST
+            | vk::ImageUsageFlags::TRANSFER_SRC,
+        vk::MemoryPropertyFlags::DEVICE_LOCAL,
+        &[],
+    )?;
+
+    let staged = StagedTexture {
+        staging,
+        image: texture.image,
+        width,
+        height,
+        mip_levels,
+    };
+    Ok((staged, texture))
+}
+
+/// copy the first level of a color texture back to the cpu and wait for it, `layout` is
+/// the layout the texture is in and is left in.
+pub(super) unsafe fn read_texture(
+    instance: &vulkanalia::Instance,
+    physical: &vk::PhysicalDevice,
+    device: &vulkanalia::Device,
+    pool: &CommandPool,
+    queue: vk::Queue,
+    texture: &Texture,
+    width: u32,
+    height: u32,
+    layout: vk::ImageLayout,
+) -> Result<TexturePixels> {
+    let format = texture.format;
+    let texel_size = TexturePixels::texel_size(format)
+        .ok_or_else(|| anyhow!("Can't read back {:?} textures.", format))?;
+    let size = width as usize * height as usize * texel_size;
+
+    // the texels land in host visible memory
+    let readback = create_buffer(
+        instance,
+        physical,
+        device,
+        size as vk::DeviceSize,
+        vk::BufferUsageFlags::TRANSFER_DST,
+        vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+        &[],
+    )?;
+
+    // copy and wait for the copy to finish
+    let copied = pool.begin_single(device).and_then(|command_buffer| {
+        record_texture_readback(
+            device,
+            command_buffer.buffer,
+            texture.image,
+            width,
+            height,
+            layout,
+            readback.buffer,
+        );
+        pool.end_single(device, queue, command_buffer)
+    });
+    let data = copied.and_then(|_| readback.read::<u8>(device, 0, size));
+
+    // cleanup
+    readback.destroy(device);
+
+    Ok(TexturePixels {
+        width,
+        height,
+        format,
+        data: data?,
+    })
+}
 
-    Ok((device, graphics_queue, present_queue))
+pub(super) unsafe fn create_texture(
+    instance: &vulkanalia::Instance,
+    physical: &vk::PhysicalDevice,
+    device: &vulkanalia::Device,
+    width: u32,
+    height: u32,
+    mip_levels: u32,
+    samples: vk::SampleCountFlags,
+    format: vk::Format,
+    tiling: vk::ImageTiling,
+    usage: vk::ImageUsageFlags,
+    properties: vk::MemoryPropertyFlags,
+    families: &[u32],
+) -> Result<Texture> {
+    create_layered_texture(
+        instance,
+        physical,
+        device,
+        width,
+        height,
+        mip_levels,
+        1,
+        vk::ImageCreateFlags::empty(),
+        samples,
+        format,
+        tiling,
+        usage,
+        properties,
+        families,
+    )
 }
 
-unsafe fn create_texture(
+pub(super) unsafe fn create_layered_texture(
     instance: &vulkanalia::Instance,
     physical: &vk::PhysicalDevice,
     device: &vulkanalia::Device,
     width: u32,
     height: u32,
     mip_levels: u32,
+    layers: u32,
+    flags: vk::ImageCreateFlags,
     samples: vk::SampleCountFlags,
     format: vk::Format,
     tiling: vk::ImageTiling,
     usage: vk::ImageUsageFlags,
     properties: vk::MemoryPropertyFlags,
+    families: &[u32],
 ) -> Result<Texture> {
+    let limits = instance.get_physical_device_properties(*physical).limits;
+    check_texture(
+        width, height, mip_levels, layers, flags, samples, usage, &limits,
+    )?;
+
     // create the image info using specified data
     let info = vk::ImageCreateInfo::builder()
+        .flags(flags)
         .image_type(vk::ImageType::_2D)
         .extent(vk::Extent3D {
             width,
@@ -577,12 +3842,13 @@ unsafe fn create_texture(
             depth: 1,
         })
         .mip_levels(mip_levels)
-        .array_layers(1)
+        .array_layers(layers)
         .format(format)
         .tiling(tiling)
         .initial_layout(vk::ImageLayout::UNDEFINED)
         .usage(usage)
-        .sharing_mode(vk::SharingMode::EXCLUSIVE)
+        .sharing_mode(sharing_mode(families))
+        .queue_family_indices(families)
         .samples(samples);
 
     // create the actual image
@@ -602,13 +3868,200 @@ unsafe fn create_texture(
         )?);
 
     // allocate the memory for the image
-    let memory = device.allocate_memory(&info, None)?;
+    let memory = allocation::allocate(device, &info, AllocationKind::Texture, image.as_raw())
+        .inspect_err(|_| device.destroy_image(image, None))?;
 
     // bind the memory to the image
     device.bind_image_memory(image, memory, 0)?;
 
     // all done create the texture
-    Ok(Texture::create(image, memory))
+    Ok(Texture::create(image, memory, format))
+}
+
+unsafe fn record_texture_upload(
+    device: &vulkanalia::Device,
+    command_buffer: vk::CommandBuffer,
+    staging: vk::Buffer,
+    image: vk::Image,
+    width: u32,
+    height: u32,
+    mip_levels: u32,
+) {
+    let barrier = |level: u32,
+                   old_layout: vk::ImageLayout,
+                   new_layout: vk::ImageLayout,
+                   src_access: vk::AccessFlags,
+                   dst_access: vk::AccessFlags| {
+        let subresource = vk::ImageSubresourceRange::builder()
+            .aspect_mask(vk::ImageAspectFlags::COLOR)
+            .base_mip_level(level)
+            .level_count(1)
+            .base_array_layer(0)
+            .layer_count(1);
+
+        vk::ImageMemoryBarrier::builder()
+            .old_layout(old_layout)
+            .new_layout(new_layout)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .image(image)
+            .subresource_range(subresource)
+            .src_access_mask(src_access)
+            .dst_access_mask(dst_access)
+            .build()
+    };
+
+    // all levels become transfer destinations
+    let subresource = vk::ImageSubresourceRange::builder()
+        .aspect_mask(vk::ImageAspectFlags::COLOR)
+        .base_mip_level(0)
+        .level_count(mip_levels)
+        .base_array_layer(0)
+        .layer_count(1);
+    let to_transfer = vk::ImageMemoryBarrier::builder()
+        .old_layout(vk::ImageLayout::UNDEFINED)
+        .new_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+        .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .image(image)
+        .subresource_range(subresource)
+        .src_access_mask(vk::AccessFlags::empty())
+        .dst_access_mask(vk::AccessFlags::TRANSFER_WRITE);
+    device.cmd_pipeline_barrier(
+        command_buffer,
+        vk::PipelineStageFlags::TOP_OF_PIPE,
+        vk::PipelineStageFlags::TRANSFER,
+        vk::DependencyFlags::empty(),
+        &[] as &[vk::MemoryBarrier],
+        &[] as &[vk::BufferMemoryBarrier],
+        &[to_transfer],
+    );
+
+    // copy the pixels into the first level
+    let layers = vk::ImageSubresourceLayers::builder()
+        .aspect_mask(vk::ImageAspectFlags::COLOR)
+        .mip_level(0)
+        .base_array_layer(0)
+        .layer_count(1);
+    let region = vk::BufferImageCopy::builder()
+        .buffer_offset(0)
+        .buffer_row_length(0)
+        .buffer_image_height(0)
+        .image_subresource(layers)
+        .image_offset(vk::Offset3D { x: 0, y: 0, z: 0 })
+        .image_extent(vk::Extent3D {
+            width,
+            height,
+            depth: 1,
+        });
+    device.cmd_copy_buffer_to_image(
+        command_buffer,
+        staging,
+        image,
+        vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+        &[region],
+    );
+
+    // blit every level into the next, each level is done once it was read
+    let mut level_width = width as i32;
+    let mut level_height = height as i32;
+    for level in 1..mip_levels {
+        let src = barrier(
+            level - 1,
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            vk::AccessFlags::TRANSFER_WRITE,
+            vk::AccessFlags::TRANSFER_READ,
+        );
+        device.cmd_pipeline_barrier(
+            command_buffer,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::DependencyFlags::empty(),
+            &[] as &[vk::MemoryBarrier],
+            &[] as &[vk::BufferMemoryBarrier],
+            &[src],
+        );
+
+        let next_width = (level_width / 2).max(1);
+        let next_height = (level_height / 2).max(1);
+        let src_subresource = vk::ImageSubresourceLayers::builder()
+            .aspect_mask(vk::ImageAspectFlags::COLOR)
+            .mip_level(level - 1)
+            .base_array_layer(0)
+            .layer_count(1);
+        let dst_subresource = vk::ImageSubresourceLayers::builder()
+            .aspect_mask(vk::ImageAspectFlags::COLOR)
+            .mip_level(level)
+            .base_array_layer(0)
+            .layer_count(1);
+        let blit = vk::ImageBlit::builder()
+            .src_offsets([
+                vk::Offset3D { x: 0, y: 0, z: 0 },
+                vk::Offset3D {
+                    x: level_width,
+                    y: level_height,
+                    z: 1,
+                },
+            ])
+            .src_subresource(src_subresource)
+            .dst_offsets([
+                vk::Offset3D { x: 0, y: 0, z: 0 },
+                vk::Offset3D {
+                    x: next_width,
+                    y: next_height,
+                    z: 1,
+                },
+            ])
+            .dst_subresource(dst_subresource);
+        device.cmd_blit_image(
+            command_buffer,
+            image,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            image,
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            &[blit],
+            vk::Filter::LINEAR,
+        );
+
+        let done = barrier(
+            level - 1,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            vk::AccessFlags::TRANSFER_READ,
+            vk::AccessFlags::SHADER_READ,
+        );
+        device.cmd_pipeline_barrier(
+            command_buffer,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::PipelineStageFlags::FRAGMENT_SHADER,
+            vk::DependencyFlags::empty(),
+            &[] as &[vk::MemoryBarrier],
+            &[] as &[vk::BufferMemoryBarrier],
+            &[done],
+        );
+
+        level_width = next_width;
+        level_height = next_height;
+    }
+
+    // the last level was only written
+    let last = barrier(
+        mip_levels - 1,
+        vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+        vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        vk::AccessFlags::TRANSFER_WRITE,
+        vk::AccessFlags::SHADER_READ,
+    );
+    device.cmd_pipeline_barrier(
+        command_buffer,
+        vk::PipelineStageFlags::TRANSFER,
+        vk::PipelineStageFlags::FRAGMENT_SHADER,
+        vk::DependencyFlags::empty(),
+        &[] as &[vk::MemoryBarrier],
+        &[] as &[vk::BufferMemoryBarrier],
+        &[last],
+    );
 }
 
 unsafe fn create_swapchain_albedo_objects(
@@ -633,6 +4086,7 @@ unsafe fn create_swapchain_albedo_objects(
         vk::ImageTiling::OPTIMAL,
         vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSIENT_ATTACHMENT,
         vk::MemoryPropertyFlags::DEVICE_LOCAL,
+        &[],
     )?;
 
     // texture view
@@ -664,8 +4118,9 @@ unsafe fn create_swapchain_depth_objects(
         *samples,
         format,
         vk::ImageTiling::OPTIMAL,
-        vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+        vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
         vk::MemoryPropertyFlags::DEVICE_LOCAL,
+        &[],
     )?;
 
     // depth texture view
@@ -675,25 +4130,154 @@ unsafe fn create_swapchain_depth_objects(
     Ok((texture, view))
 }
 
-unsafe fn construct_swapchain(
-    window: &Window,
-    instance: &vulkanalia::Instance,
-    surface: &vk::SurfaceKHR,
-    physical: &vk::PhysicalDevice,
+unsafe fn create_swapchain_scene_objects(
+    instance: &vulkanalia::Instance,
+    physical: &vk::PhysicalDevice,
+    device: &vulkanalia::Device,
+    width: u32,
+    height: u32,
+    usage: vk::ImageUsageFlags,
+) -> Result<(Texture, TextureView)> {
+    // resolved scene texture, read by post processing
+    let texture = create_texture(
+        instance,
+        physical,
+        device,
+        width,
+        height,
+        1,
+        vk::SampleCountFlags::_1,
+        SCENE_FORMAT,
+        vk::ImageTiling::OPTIMAL,
+        vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED | usage,
+        vk::MemoryPropertyFlags::DEVICE_LOCAL,
+        &[],
+    )?;
+
+    // scene texture view
+    let view = texture.create_view(device, SCENE_FORMAT, vk::ImageAspectFlags::COLOR, 1)?;
+
+    // all went fine
+    Ok((texture, view))
+}
+
+/// create the render passes of the scene and of presenting to a swapchain of `format`.
+unsafe fn create_render_pass_objects(
+    instance: &vulkanalia::Instance,
+    physical: &vk::PhysicalDevice,
+    device: &vulkanalia::Device,
+    samples: &vk::SampleCountFlags,
+    format: vk::Format,
+) -> Result<RenderPassData> {
+    let scene_render_pass = create_render_pass(
+        instance,
+        physical,
+        device,
+        samples,
+        SCENE_FORMAT,
+        false,
+        &PassLoad::default(),
+    )?;
+    let scene_partial_render_pass = create_render_pass(
+        instance,
+        physical,
+        device,
+        samples,
+        SCENE_FORMAT,
+        true,
+        &PassLoad::default(),
+    )?;
+    let scene_partial_load_render_pass = create_render_pass(
+        instance,
+        physical,
+        device,
+        samples,
+        SCENE_FORMAT,
+        true,
+        &PassLoad::new(None, None),
+    )?;
+    let scene_transparent_render_pass =
+        create_transparent_render_pass(instance, physical, device, samples, SCENE_FORMAT)?;
+    let present_render_pass = create_present_render_pass(device, format)?;
+
+    Ok(RenderPassData {
+        scene_render_pass,
+        scene_partial_render_pass,
+        scene_partial_load_render_pass,
+        scene_transparent_render_pass,
+        present_render_pass,
+        present_format: format,
+    })
+}
+
+unsafe fn destroy_render_pass_objects(device: &vulkanalia::Device, render_passes: &RenderPassData) {
+    device.destroy_render_pass(render_passes.present_render_pass, None);
+    device.destroy_render_pass(render_passes.scene_render_pass, None);
+    device.destroy_render_pass(render_passes.scene_partial_render_pass, None);
+    device.destroy_render_pass(render_passes.scene_partial_load_render_pass, None);
+    device.destroy_render_pass(render_passes.scene_transparent_render_pass, None);
+}
+
+/// create the swapchain with its framebuffers for `render_passes` and the scene target at
+/// `render_scale` of its extent, `old` is the swapchain it replaces or null.
+unsafe fn construct_swapchain(
+    window: &dyn DeviceWindow,
+    instance: &vulkanalia::Instance,
+    surface: &vk::SurfaceKHR,
+    physical: &vk::PhysicalDevice,
+    device: &vulkanalia::Device,
+    samples: &vk::SampleCountFlags,
+    render_passes: &RenderPassData,
+    render_scale: f32,
+    old: vk::SwapchainKHR,
+) -> Result<SwapchainData> {
+    // create swapchain
+    let (swapchain, format, extent) =
+        create_swapchain(window, instance, surface, physical, device, old)?;
+
+    // create the views and framebuffers of its images
+    let (textures, views, present_framebuffers) =
+        create_present_objects(device, render_passes, swapchain, format, extent)?;
+
+    // create the scene target
+    let scene_extent = render_extent(extent, render_scale);
+    let (target, scene_framebuffer) = create_scene_target(
+        instance,
+        physical,
+        device,
+        samples,
+        render_passes,
+        scene_extent,
+    )?;
+
+    // all done
+    Ok(SwapchainData {
+        extent,
+        handle: swapchain,
+        format,
+        present_framebuffers,
+        scene_framebuffer,
+        target,
+        textures,
+        views,
+    })
+}
+
+/// wrap the images of `swapchain` into textures with views and present framebuffers.
+unsafe fn create_present_objects(
     device: &vulkanalia::Device,
-    samples: &vk::SampleCountFlags,
-) -> Result<SwapchainData> {
-    // create swapchain
-    let (swapchain, format, extent) =
-        create_swapchain(window, instance, surface, physical, device)?;
-
+    render_passes: &RenderPassData,
+    swapchain: vk::SwapchainKHR,
+    format: vk::Format,
+    extent: vk::Extent2D,
+) -> Result<(Vec<Texture>, Vec<TextureView>, Vec<FrameBuffer>)> {
     // get swap chain images
     let images = device.get_swapchain_images_khr(swapchain)?;
 
     // map into textures
     let textures = images
         .iter()
-        .map(|i| Texture::create(*i, vk::DeviceMemory::null()))
+        .map(|i| Texture::create(*i, vk::DeviceMemory::null(), format))
         .collect::<Vec<_>>();
 
     // map into views
@@ -702,126 +4286,218 @@ unsafe fn construct_swapchain(
         .map(|i| i.create_view(device, format, vk::ImageAspectFlags::COLOR, 1))
         .collect::<Result<Vec<_>, _>>()?;
 
-    // create render pass
-    let render_pass = create_render_pass(instance, physical, device, samples, format)?;
+    // create present framebuffers
+    let present_framebuffers = views
+        .iter()
+        .map(|i| {
+            FrameBuffer::create(
+                device,
+                &render_passes.present_render_pass,
+                &[*i],
+                extent.width,
+                extent.height,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((textures, views, present_framebuffers))
+}
 
+/// create the attachments the scene pass draws into and their framebuffer.
+unsafe fn create_scene_target(
+    instance: &vulkanalia::Instance,
+    physical: &vk::PhysicalDevice,
+    device: &vulkanalia::Device,
+    samples: &vk::SampleCountFlags,
+    render_passes: &RenderPassData,
+    extent: vk::Extent2D,
+) -> Result<(DeviceTargetData, FrameBuffer)> {
     // create albedo info
     let (albedo_texture, albedo_texture_view) = create_swapchain_albedo_objects(
-        &instance,
-        &physical,
-        &device,
-        &samples,
+        instance,
+        physical,
+        device,
+        samples,
         extent.width,
         extent.height,
-        format,
+        SCENE_FORMAT,
+    )?;
+
+    // create resolved scene info
+    let (scene_texture, scene_texture_view) = create_swapchain_scene_objects(
+        instance,
+        physical,
+        device,
+        extent.width,
+        extent.height,
+        vk::ImageUsageFlags::empty(),
     )?;
 
     // create depth info
     let (depth_texture, depth_texture_view) = create_swapchain_depth_objects(
-        &instance,
-        &physical,
-        &device,
-        &samples,
+        instance,
+        physical,
+        device,
+        samples,
         extent.width,
         extent.height,
     )?;
 
-    // create framebuffers
-    let framebuffers: Vec<_> = views
-        .iter()
-        .map(|i| {
-            FrameBuffer::create(
-                device,
-                &render_pass,
-                &[albedo_texture_view, depth_texture_view, *i],
-                extent.width,
-                extent.height,
-            )
-            .expect("Failed to create framebuffer.")
-        })
-        .collect();
+    // create the scene framebuffer
+    let scene_framebuffer = FrameBuffer::create(
+        device,
+        &render_passes.scene_render_pass,
+        &[albedo_texture_view, depth_texture_view, scene_texture_view],
+        extent.width,
+        extent.height,
+    )?;
 
     // create target
     let target = DeviceTargetData {
+        extent,
         albedo_texture,
         albedo_texture_view,
         depth_texture,
         depth_texture_view,
+        scene_texture,
+        scene_texture_view,
     };
 
-    // all done
-    Ok(SwapchainData {
-        extent,
-        handle: swapchain,
-        format,
-        framebuffers,
-        render_pass,
-        target,
-        textures,
-        views,
-    })
+    Ok((target, scene_framebuffer))
 }
 
+/// replace the swapchain of `swapchain` in place, passing it as the old swapchain so its
+/// images are handed over. the views and framebuffers of the images are rebuilt, the scene
+/// target only when the render extent changed.
 unsafe fn recontruct_swapchain(
-    window: &Window,
+    window: &dyn DeviceWindow,
     instance: &vulkanalia::Instance,
     surface: &vk::SurfaceKHR,
     physical: &vk::PhysicalDevice,
     device: &vulkanalia::Device,
     samples: &vk::SampleCountFlags,
-    swapchain: &SwapchainData,
-) -> Result<SwapchainData> {
-    // destrpy current swap chain
-    destroy_swapchain(device, swapchain);
+    render_passes: &RenderPassData,
+    render_scale: f32,
+    swapchain: &mut SwapchainData,
+) -> Result<SwapchainRebuild> {
+    let old = SwapchainShape {
+        format: swapchain.format,
+        extent: swapchain.extent,
+        image_count: swapchain.textures.len(),
+    };
+
+    // the views and framebuffers of the old images go, the handle is retired by the new one
+    destroy_present_objects(device, swapchain);
+    swapchain.present_framebuffers.clear();
+    swapchain.views.clear();
+    swapchain.textures.clear();
 
-    // create new swap chain
-    let swapchain = construct_swapchain(window, &instance, &surface, &physical, &device, &samples)?;
+    // create new swap chain, the old one goes either way
+    let created = create_swapchain(
+        window,
+        instance,
+        surface,
+        physical,
+        device,
+        swapchain.handle,
+    );
+    device.destroy_swapchain_khr(swapchain.handle, None);
+    swapchain.handle = vk::SwapchainKHR::null();
+    let (handle, format, extent) = created?;
+    swapchain.handle = handle;
+
+    // create the views and framebuffers of the new images
+    let (textures, views, present_framebuffers) =
+        create_present_objects(device, render_passes, handle, format, extent)?;
+    let new = SwapchainShape {
+        format,
+        extent,
+        image_count: textures.len(),
+    };
+    swapchain.format = format;
+    swapchain.extent = extent;
+    swapchain.textures = textures;
+    swapchain.views = views;
+    swapchain.present_framebuffers = present_framebuffers;
+
+    // the scene target only follows a new render extent
+    let rebuild = SwapchainRebuild::between(old, new, swapchain.target.extent, render_scale);
+    if rebuild.scene_target {
+        let (target, framebuffer) = create_scene_target(
+            instance,
+            physical,
+            device,
+            samples,
+            render_passes,
+            render_extent(extent, render_scale),
+        )?;
+        destroy_scene_target(device, &swapchain.target, &swapchain.scene_framebuffer);
+        swapchain.target = target;
+        swapchain.scene_framebuffer = framebuffer;
+    }
 
     // all done
-    Ok(swapchain)
+    Ok(rebuild)
 }
 
-unsafe fn destroy_swapchain(device: &vulkanalia::Device, swapchain: &SwapchainData) {
-    // destroy framebuffers
-    swapchain
-        .framebuffers
-        .iter()
-        .for_each(|f| f.destroy(&device));
-
-    // destroy render pass
-    device.destroy_render_pass(swapchain.render_pass, None);
+unsafe fn destroy_scene_target(
+    device: &vulkanalia::Device,
+    target: &DeviceTargetData,
+    framebuffer: &FrameBuffer,
+) {
+    // destroy scene framebuffer
+    framebuffer.destroy(device);
 
     // destroy albedo texture & view
-    swapchain.target.albedo_texture.destroy(&device);
-    swapchain.target.albedo_texture_view.destroy(&device);
+    target.albedo_texture.destroy(device);
+    target.albedo_texture_view.destroy(device);
 
     // destroy depth texture & view
-    swapchain.target.depth_texture.destroy(&device);
-    swapchain.target.depth_texture_view.destroy(&device);
+    target.depth_texture.destroy(device);
+    target.depth_texture_view.destroy(device);
 
-    // destroy swapchain views, textures not needed
-    swapchain.views.iter().for_each(|v| v.destroy(&device));
+    // destroy scene texture & view
+    target.scene_texture.destroy(device);
+    target.scene_texture_view.destroy(device);
+}
 
-    // destroy swapchain
-    device.destroy_swapchain_khr(swapchain.handle, None);
+// destroys everything but the handle, which the next swapchain retires
+unsafe fn destroy_swapchain(device: &vulkanalia::Device, swapchain: &SwapchainData) {
+    destroy_present_objects(device, swapchain);
+
+    // destroy the scene target
+    destroy_scene_target(device, &swapchain.target, &swapchain.scene_framebuffer);
+}
+
+unsafe fn destroy_present_objects(device: &vulkanalia::Device, swapchain: &SwapchainData) {
+    // destroy framebuffers
+    swapchain
+        .present_framebuffers
+        .iter()
+        .for_each(|f| f.destroy(device));
+
+    // destroy swapchain views, textures not needed
+    swapchain.views.iter().for_each(|v| v.destroy(device));
 }
 
 unsafe fn create_swapchain(
-    window: &Window,
+    window: &dyn DeviceWindow,
     instance: &vulkanalia::Instance,
     surface: &vk::SurfaceKHR,
     physical: &vk::PhysicalDevice,
     device: &vulkanalia::Device,
+    old: vk::SwapchainKHR,
 ) -> Result<(vk::SwapchainKHR, vk::Format, vk::Extent2D)> {
     let indices = QueueFamilyIndices::get(instance, surface, *physical)?;
     let support = SwapChainSupport::get(instance, surface, *physical)?;
 
     let surface_format = get_surface_format(&support.formats);
     let present_mode = get_present_mode(&support.present_modes);
-    let extent = get_extent(window, support.capabilities);
+    let (width, height) = window.inner_size();
+    let extent = surface_extent(width, height, &support.capabilities)
+        .ok_or_else(|| anyhow!("Can't create a swapchain for a surface without area."))?;
 
     let format = surface_format.format;
-    let extent = extent;
 
     let mut image_count = support.capabilities.min_image_count + 1;
     if support.capabilities.max_image_count != 0
@@ -839,6 +4515,16 @@ unsafe fn create_swapchain(
         vk::SharingMode::EXCLUSIVE
     };
 
+    // allow copying the images where supported, used by bug reports
+    let mut image_usage = vk::ImageUsageFlags::COLOR_ATTACHMENT;
+    if support
+        .capabilities
+        .supported_usage_flags
+        .contains(vk::ImageUsageFlags::TRANSFER_SRC)
+    {
+        image_usage |= vk::ImageUsageFlags::TRANSFER_SRC;
+    }
+
     // build info
     let info = vk::SwapchainCreateInfoKHR::builder()
         .surface(*surface)
@@ -847,14 +4533,14 @@ unsafe fn create_swapchain(
         .image_color_space(surface_format.color_space)
         .image_extent(extent)
         .image_array_layers(1)
-        .image_usage(vk::ImageUsageFlags::COLOR_ATTACHMENT)
+        .image_usage(image_usage)
         .image_sharing_mode(image_sharing_mode)
         .queue_family_indices(&queue_family_indices)
         .pre_transform(support.capabilities.current_transform)
         .composite_alpha(vk::CompositeAlphaFlagsKHR::OPAQUE)
         .present_mode(present_mode)
         .clipped(true)
-        .old_swapchain(vk::SwapchainKHR::null());
+        .old_swapchain(old);
 
     // create swap chain
     let swapchain = device.create_swapchain_khr(&info, None)?;
@@ -863,33 +4549,61 @@ unsafe fn create_swapchain(
     Ok((swapchain, format, extent))
 }
 
+/// create the opaque scene render pass, `preserve` keeps the resolved scene outside the render
+/// area and `load` sets what happens to the color and depth attachments. depth is stored so
+/// the transparent pass can continue the scene.
 unsafe fn create_render_pass(
     instance: &vulkanalia::Instance,
     physical: &vk::PhysicalDevice,
     device: &vulkanalia::Device,
     samples: &vk::SampleCountFlags,
     format: vk::Format,
+    preserve: bool,
+    load: &PassLoad,
 ) -> Result<vk::RenderPass> {
+    // an undefined initial layout allows discarding the previous scene
+    let resolve_layout = if preserve {
+        vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL
+    } else {
+        vk::ImageLayout::UNDEFINED
+    };
+
+    // loaded attachments are still in the layout the last pass left them in
+    let initial_layout = |loaded: bool, layout: vk::ImageLayout| {
+        if loaded {
+            layout
+        } else {
+            vk::ImageLayout::UNDEFINED
+        }
+    };
+    let [color_load, depth_load] = load.ops();
+
     // Attachments
     let color_attachment = vk::AttachmentDescription::builder()
         .format(format)
         .samples(*samples)
-        .load_op(vk::AttachmentLoadOp::CLEAR)
+        .load_op(color_load)
         .store_op(vk::AttachmentStoreOp::STORE)
         .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
         .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
-        .initial_layout(vk::ImageLayout::UNDEFINED)
+        .initial_layout(initial_layout(
+            color_load == vk::AttachmentLoadOp::LOAD,
+            vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+        ))
         .final_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
 
     let depth_stencil_attachment = vk::AttachmentDescription::builder()
         .format(get_depth_format(instance, physical)?)
         .samples(*samples)
-        .load_op(vk::AttachmentLoadOp::CLEAR)
-        .store_op(vk::AttachmentStoreOp::DONT_CARE)
-        .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+        .load_op(depth_load)
+        .store_op(vk::AttachmentStoreOp::STORE)
+        .stencil_load_op(depth_load)
         .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
-        .initial_layout(vk::ImageLayout::UNDEFINED)
-        .final_layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL);
+        .initial_layout(initial_layout(
+            depth_load == vk::AttachmentLoadOp::LOAD,
+            vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL,
+        ))
+        .final_layout(vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL);
 
     let color_resolve_attachment = vk::AttachmentDescription::builder()
         .format(format)
@@ -898,8 +4612,8 @@ unsafe fn create_render_pass(
         .store_op(vk::AttachmentStoreOp::STORE)
         .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
         .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
-        .initial_layout(vk::ImageLayout::UNDEFINED)
-        .final_layout(vk::ImageLayout::PRESENT_SRC_KHR);
+        .initial_layout(resolve_layout)
+        .final_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
 
     // Subpasses
 
@@ -925,12 +4639,15 @@ unsafe fn create_render_pass(
 
     // Dependencies
 
+    // previous reads of the scene target must finish before it is written again
     let dependency = vk::SubpassDependency::builder()
         .src_subpass(vk::SUBPASS_EXTERNAL)
         .dst_subpass(0)
         .src_stage_mask(
             vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
-                | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS,
+                | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS
+                | vk::PipelineStageFlags::FRAGMENT_SHADER
+                | vk::PipelineStageFlags::COMPUTE_SHADER,
         )
         .src_access_mask(vk::AccessFlags::empty())
         .dst_stage_mask(
@@ -942,6 +4659,137 @@ unsafe fn create_render_pass(
                 | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
         );
 
+    // the resolved scene is read by post processing afterwards
+    let post_dependency = vk::SubpassDependency::builder()
+        .src_subpass(0)
+        .dst_subpass(vk::SUBPASS_EXTERNAL)
+        .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+        .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+        .dst_stage_mask(
+            vk::PipelineStageFlags::FRAGMENT_SHADER | vk::PipelineStageFlags::COMPUTE_SHADER,
+        )
+        .dst_access_mask(vk::AccessFlags::SHADER_READ);
+
+    // Create
+
+    let attachments = &[
+        color_attachment,
+        depth_stencil_attachment,
+        color_resolve_attachment,
+    ];
+    let subpasses = &[subpass];
+    let dependencies = &[dependency, post_dependency];
+    let info = vk::RenderPassCreateInfo::builder()
+        .attachments(attachments)
+        .subpasses(subpasses)
+        .dependencies(dependencies);
+
+    let render_pass = device.create_render_pass(&info, None)?;
+
+    Ok(render_pass)
+}
+
+/// create the transparent scene render pass, it continues the opaque pass with depth read only.
+/// the attachments match so both passes are compatible with the same pipelines and framebuffer.
+unsafe fn create_transparent_render_pass(
+    instance: &vulkanalia::Instance,
+    physical: &vk::PhysicalDevice,
+    device: &vulkanalia::Device,
+    samples: &vk::SampleCountFlags,
+    format: vk::Format,
+) -> Result<vk::RenderPass> {
+    // Attachments
+    let color_attachment = vk::AttachmentDescription::builder()
+        .format(format)
+        .samples(*samples)
+        .load_op(vk::AttachmentLoadOp::LOAD)
+        .store_op(vk::AttachmentStoreOp::STORE)
+        .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+        .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+        .initial_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+        .final_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+
+    let depth_stencil_attachment = vk::AttachmentDescription::builder()
+        .format(get_depth_format(instance, physical)?)
+        .samples(*samples)
+        .load_op(vk::AttachmentLoadOp::LOAD)
+        .store_op(vk::AttachmentStoreOp::DONT_CARE)
+        .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+        .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+        .initial_layout(vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL)
+        .final_layout(vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL);
+
+    // the opaque pass resolved already, outside the render area that is kept
+    let color_resolve_attachment = vk::AttachmentDescription::builder()
+        .format(format)
+        .samples(vk::SampleCountFlags::_1)
+        .load_op(vk::AttachmentLoadOp::DONT_CARE)
+        .store_op(vk::AttachmentStoreOp::STORE)
+        .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+        .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+        .initial_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+        .final_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+
+    // Subpasses
+
+    let color_attachment_ref = vk::AttachmentReference::builder()
+        .attachment(0)
+        .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+
+    // tested but not written, which allows sampling it at the same time
+    let depth_stencil_attachment_ref = vk::AttachmentReference::builder()
+        .attachment(1)
+        .layout(vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL);
+
+    let color_resolve_attachment_ref = vk::AttachmentReference::builder()
+        .attachment(2)
+        .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+
+    let color_attachments = &[color_attachment_ref];
+    let resolve_attachments = &[color_resolve_attachment_ref];
+    let subpass = vk::SubpassDescription::builder()
+        .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+        .color_attachments(color_attachments)
+        .depth_stencil_attachment(&depth_stencil_attachment_ref)
+        .resolve_attachments(resolve_attachments);
+
+    // Dependencies
+
+    // the opaque color and depth must be written before they are blended onto and sampled
+    let dependency = vk::SubpassDependency::builder()
+        .src_subpass(vk::SUBPASS_EXTERNAL)
+        .dst_subpass(0)
+        .src_stage_mask(
+            vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+                | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+        )
+        .src_access_mask(
+            vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+                | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+        )
+        .dst_stage_mask(
+            vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+                | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS
+                | vk::PipelineStageFlags::FRAGMENT_SHADER,
+        )
+        .dst_access_mask(
+            vk::AccessFlags::COLOR_ATTACHMENT_READ
+                | vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+                | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ
+                | vk::AccessFlags::SHADER_READ,
+        );
+
+    // the resolved scene is read by post processing afterwards
+    let post_dependency = vk::SubpassDependency::builder()
+        .src_subpass(0)
+        .dst_subpass(vk::SUBPASS_EXTERNAL)
+        .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+        .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+        .dst_stage_mask(
+            vk::PipelineStageFlags::FRAGMENT_SHADER | vk::PipelineStageFlags::COMPUTE_SHADER,
+        )
+        .dst_access_mask(vk::AccessFlags::SHADER_READ);
+
     // Create
 
     let attachments = &[
@@ -950,6 +4798,57 @@ unsafe fn create_render_pass(
         color_resolve_attachment,
     ];
     let subpasses = &[subpass];
+    let dependencies = &[dependency, post_dependency];
+    let info = vk::RenderPassCreateInfo::builder()
+        .attachments(attachments)
+        .subpasses(subpasses)
+        .dependencies(dependencies);
+
+    let render_pass = device.create_render_pass(&info, None)?;
+
+    Ok(render_pass)
+}
+
+unsafe fn create_present_render_pass(
+    device: &vulkanalia::Device,
+    format: vk::Format,
+) -> Result<vk::RenderPass> {
+    // Attachments, every pixel is written so nothing is loaded
+    let color_attachment = vk::AttachmentDescription::builder()
+        .format(format)
+        .samples(vk::SampleCountFlags::_1)
+        .load_op(vk::AttachmentLoadOp::DONT_CARE)
+        .store_op(vk::AttachmentStoreOp::STORE)
+        .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+        .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+        .initial_layout(vk::ImageLayout::UNDEFINED)
+        .final_layout(vk::ImageLayout::PRESENT_SRC_KHR);
+
+    // Subpasses
+
+    let color_attachment_ref = vk::AttachmentReference::builder()
+        .attachment(0)
+        .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+
+    let color_attachments = &[color_attachment_ref];
+    let subpass = vk::SubpassDescription::builder()
+        .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+        .color_attachments(color_attachments);
+
+    // Dependencies
+
+    let dependency = vk::SubpassDependency::builder()
+        .src_subpass(vk::SUBPASS_EXTERNAL)
+        .dst_subpass(0)
+        .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+        .src_access_mask(vk::AccessFlags::empty())
+        .dst_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+        .dst_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE);
+
+    // Create
+
+    let attachments = &[color_attachment];
+    let subpasses = &[subpass];
     let dependencies = &[dependency];
     let info = vk::RenderPassCreateInfo::builder()
         .attachments(attachments)
@@ -980,28 +4879,7 @@ fn get_present_mode(present_modes: &[vk::PresentModeKHR]) -> vk::PresentModeKHR
         .unwrap_or(vk::PresentModeKHR::FIFO)
 }
 
-fn get_extent(window: &Window, capabilities: vk::SurfaceCapabilitiesKHR) -> vk::Extent2D {
-    if capabilities.current_extent.width != u32::max_value() {
-        capabilities.current_extent
-    } else {
-        let size = window.inner_size();
-        let clamp = |min: u32, max: u32, v: u32| min.max(max.min(v));
-        vk::Extent2D::builder()
-            .width(clamp(
-                capabilities.min_image_extent.width,
-                capabilities.max_image_extent.width,
-                size.width,
-            ))
-            .height(clamp(
-                capabilities.min_image_extent.height,
-                capabilities.max_image_extent.height,
-                size.height,
-            ))
-            .build()
-    }
-}
-
-unsafe fn get_memory_type_index(
+pub(super) unsafe fn get_memory_type_index(
     instance: &vulkanalia::Instance,
     physical: &vk::PhysicalDevice,
     properties: vk::MemoryPropertyFlags,
@@ -1032,7 +4910,7 @@ unsafe fn get_depth_format(
         physical,
         candidates,
         vk::ImageTiling::OPTIMAL,
-        vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT,
+        vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT | vk::FormatFeatureFlags::SAMPLED_IMAGE,
     )
 }
 
@@ -0,0 +1,643 @@
+#![allow(dead_code)]
+
+use std::cell::Ref;
+use std::collections::HashMap;
+use std::ops::{Deref, Range};
+
+use thiserror::Error;
+use tracing::debug;
+use vulkanalia::prelude::v1_0::*;
+
+use super::{
+    Buffer, BufferId, DeletionQueue, IndexBinding, PassInheritance, QueryKind, QueryResolve,
+    QuerySet, ResourceRegistry, ResourceUsage,
+};
+
+/// A command an `Encoder` refused, with the call and the resource it was about. nothing
+/// was recorded for it.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[error("{call}: {problem}")]
+pub struct CommandError {
+    pub call: &'static str,
+    pub problem: String,
+}
+
+fn refuse<T>(call: &'static str, problem: impl Into<String>) -> Result<T, CommandError> {
+    Err(CommandError {
+        call,
+        problem: problem.into(),
+    })
+}
+
+/// Records into a command buffer and checks every command against what was recorded before
+/// it, so misuse comes back as a `CommandError` instead of reaching vulkan. it knows about
+/// the commands that went through it only, raw commands in between are not seen.
+pub struct Encoder<'a> {
+    device: &'a vulkanalia::Device,
+    command_buffer: vk::CommandBuffer,
+    // buffers retired here are no longer safe to use in new commands
+    retired: Option<&'a DeletionQueue>,
+    // where bound buffers are marked used by the submission with the value
+    usage: Option<(&'a ResourceUsage, u64)>,
+    // what buffer ids are resolved against
+    registry: Option<Registry<'a>>,
+    in_render_pass: bool,
+    // recording a secondary command buffer inside the render pass of its primary
+    continues: bool,
+    // the index of the running subpass
+    subpass: u32,
+    // whether the running subpass is recorded inline or executes secondary command buffers
+    inline: bool,
+    pipelines: HashMap<vk::PipelineBindPoint, vk::Pipeline>,
+    vertex_buffers: HashMap<u32, vk::Buffer>,
+    index_buffer: Option<IndexBinding>,
+}
+
+// the registry buffer ids are resolved against, a device lends its own for as long as the
+// encoder lives
+enum Registry<'a> {
+    Plain(&'a ResourceRegistry),
+    Borrowed(Ref<'a, ResourceRegistry>),
+}
+
+impl Deref for Registry<'_> {
+    type Target = ResourceRegistry;
+
+    fn deref(&self) -> &ResourceRegistry {
+        match self {
+            Self::Plain(registry) => registry,
+            Self::Borrowed(registry) => registry,
+        }
+    }
+}
+
+impl<'a> Encoder<'a> {
+    /// record into `command_buffer`, which must be recording already.
+    pub fn new(device: &'a vulkanalia::Device, command_buffer: vk::CommandBuffer) -> Self {
+        Self {
+            device,
+            command_buffer,
+            retired: None,
+            usage: None,
+            registry: None,
+            in_render_pass: false,
+            continues: false,
+            subpass: 0,
+            inline: true,
+            pipelines: HashMap::new(),
+            vertex_buffers: HashMap::new(),
+            index_buffer: None,
+        }
+    }
+
+    /// record into a secondary command buffer begun to continue the subpass of
+    /// `inheritance`, e.g. by `SecondaryPool::begin`. it draws without beginning a pass.
+    pub fn continuing(
+        device: &'a vulkanalia::Device,
+        command_buffer: vk::CommandBuffer,
+        inheritance: &PassInheritance,
+    ) -> Self {
+        Self {
+            in_render_pass: true,
+            continues: true,
+            subpass: inheritance.subpass,
+            ..Self::new(device, command_buffer)
+        }
+    }
+
+    /// refuse buffers waiting in `retired` for their destruction.
+    pub fn with_retired(mut self, retired: &'a DeletionQueue) -> Self {
+        self.retired = Some(retired);
+        self
+    }
+
+    /// mark the buffers bound as used by the submission with timeline value `value`.
+    pub fn with_usage(mut self, usage: &'a ResourceUsage, value: u64) -> Self {
+        self.usage = Some((usage, value));
+        self
+    }
+
+    /// resolve buffer ids against `registry`, ids of destroyed buffers are refused.
+    pub fn with_registry(mut self, registry: &'a ResourceRegistry) -> Self {
+        self.registry = Some(Registry::Plain(registry));
+        self
+    }
+
+    /// resolve buffer ids against the registry of a device, borrowed until the encoder is
+    /// dropped.
+    pub(crate) fn with_borrowed_registry(mut self, registry: Ref<'a, ResourceRegistry>) -> Self {
+        self.registry = Some(Registry::Borrowed(registry));
+        self
+    }
+
+    fn resolve(&self, call: &'static str, id: BufferId) -> Result<Buffer, CommandError> {
+        let Some(registry) = &self.registry else {
+            return refuse(call, format!("no registry resolves buffer {:?}", id));
+        };
+        match registry.get(id) {
+            Ok(buffer) => Ok(*buffer),
+            Err(_) => refuse(call, format!("buffer {:?} was destroyed", id)),
+        }
+    }
+
+    fn used(&self, buffer: vk::Buffer) {
+        if let Some((usage, value)) = self.usage {
+            usage.record(buffer, value);
+        }
+    }
+
+    pub fn command_buffer(&self) -> vk::CommandBuffer {
+        self.command_buffer
+    }
+
+    pub fn in_render_pass(&self) -> bool {
+        self.in_render_pass
+    }
+
+    /// the subpass of the running render pass draws go to.
+    pub fn subpass(&self) -> u32 {
+        self.subpass
+    }
+
+    fn check_buffer(&self, call: &'static str, buffer: vk::Buffer) -> Result<(), CommandError> {
+        if buffer.is_null() {
+            return refuse(call, "the buffer is null");
+        }
+        if self.retired.is_some_and(|r| r.is_retired(buffer)) {
+            return refuse(call, format!("buffer {:?} was retired", buffer));
+        }
+        Ok(())
+    }
+
+    pub unsafe fn begin_render_pass(
+        &mut self,
+        info: &vk::RenderPassBeginInfo,
+    ) -> Result<(), CommandError> {
+        self.begin_render_pass_with(info, vk::SubpassContents::INLINE)
+    }
+
+    /// begin a render pass whose first subpass is recorded as `contents` says, with
+    /// `SECONDARY_COMMAND_BUFFERS` it only executes secondary command buffers.
+    pub unsafe fn begin_render_pass_with(
+        &mut self,
+        info: &vk::RenderPassBeginInfo,
+        contents: vk::SubpassContents,
+    ) -> Result<(), CommandError> {
+        let call = "begin_render_pass";
+        if self.in_render_pass {
+            return refuse(call, "a render pass is already running");
+        }
+        if info.render_pass.is_null() || info.framebuffer.is_null() {
+            return refuse(call, "the render pass or framebuffer is null");
+        }
+        self.device
+            .cmd_begin_render_pass(self.command_buffer, info, contents);
+        self.in_render_pass = true;
+        self.subpass = 0;
+        self.inline = contents == vk::SubpassContents::INLINE;
+        Ok(())
+    }
+
+    /// continue with the next subpass of the running render pass. pipelines are made for a
+    /// subpass, so one has to be bound again before drawing.
+    pub unsafe fn next_subpass(&mut self) -> Result<(), CommandError> {
+        self.next_subpass_with(vk::SubpassContents::INLINE)
+    }
+
+    /// continue with the next subpass, recorded as `contents` says.
+    pub unsafe fn next_subpass_with(
+        &mut self,
+        contents: vk::SubpassContents,
+    ) -> Result<(), CommandError> {
+        let call = "next_subpass";
+        if self.continues {
+            return refuse(
+                call,
+                "the render pass belongs to the primary command buffer",
+            );
+        }
+        if !self.in_render_pass {
+            return refuse(call, "there is no render pass to advance");
+        }
+        self.device.cmd_next_subpass(self.command_buffer, contents);
+        self.subpass += 1;
+        self.inline = contents == vk::SubpassContents::INLINE;
+        self.pipelines.remove(&vk::PipelineBindPoint::GRAPHICS);
+        Ok(())
+    }
+
+    pub unsafe fn end_render_pass(&mut self) -> Result<(), CommandError> {
+        let call = "end_render_pass";
+        if self.continues {
+            return refuse(
+                call,
+                "the render pass belongs to the primary command buffer",
+            );
+        }
+        if !self.in_render_pass {
+            return refuse(call, "there is no render pass to end");
+        }
+        self.device.cmd_end_render_pass(self.command_buffer);
+        self.in_render_pass = false;
+        Ok(())
+    }
+
+    pub unsafe fn bind_pipeline(
+        &mut self,
+        bind_point: vk::PipelineBindPoint,
+        pipeline: vk::Pipeline,
+    ) -> Result<(), CommandError> {
+        if pipeline.is_null() {
+            return refuse("bind_pipeline", "the pipeline is null");
+        }
+        debug!(?pipeline, "bind pipeline");
+        self.device
+            .cmd_bind_pipeline(self.command_buffer, bind_point, pipeline);
+        self.pipelines.insert(bind_point, pipeline);
+        Ok(())
+    }
+
+    /// bind `buffers` at consecutive bindings from `first_binding`, with the offsets of each.
+    pub unsafe fn bind_vertex_buffers(
+        &mut self,
+        first_binding: u32,
+        buffers: &[(&Buffer, vk::DeviceSize)],
+    ) -> Result<(), CommandError> {
+        let call = "bind_vertex_buffers";
+        for (buffer, offset) in buffers {
+            self.check_buffer(call, buffer.buffer)?;
+            if *offset >= buffer.size {
+                return refuse(
+                    call,
+                    format!(
+                        "the offset {} is past the {} bytes of buffer {:?}",
+                        offset, buffer.size, buffer.buffer
+                    ),
+                );
+            }
+        }
+
+        let handles = buffers.iter().map(|(b, _)| b.buffer).collect::<Vec<_>>();
+        let offsets = buffers.iter().map(|(_, o)| *o).collect::<Vec<_>>();
+        self.device
+            .cmd_bind_vertex_buffers(self.command_buffer, first_binding, &handles, &offsets);
+        for (i, buffer) in handles.into_iter().enumerate() {
+            self.used(buffer);
+            self.vertex_buffers.insert(first_binding + i as u32, buffer);
+        }
+        Ok(())
+    }
+
+    /// `bind_vertex_buffers` with the registered buffers of `ids`.
+    pub unsafe fn bind_vertex_buffer_ids(
+        &mut self,
+        first_binding: u32,
+        ids: &[(BufferId, vk::DeviceSize)],
+    ) -> Result<(), CommandError> {
+        let buffers = ids
+            .iter()
+            .map(|(id, offset)| Ok((self.resolve("bind_vertex_buffers", *id)?, *offset)))
+            .collect::<Result<Vec<_>, CommandError>>()?;
+        let buffers = buffers.iter().map(|(b, o)| (b, *o)).collect::<Vec<_>>();
+        self.bind_vertex_buffers(first_binding, &buffers)
+    }
+
+    pub unsafe fn bind_index_buffer(&mut self, indices: IndexBinding) -> Result<(), CommandError> {
+        self.check_buffer("bind_index_buffer", indices.buffer)?;
+        indices.bind(self.device, self.command_buffer);
+        self.used(indices.buffer);
+        self.index_buffer = Some(indices);
+        Ok(())
+    }
+
+    /// bind the indices of the registered buffer of `id` from its start.
+    pub unsafe fn bind_index_buffer_id(
+        &mut self,
+        id: BufferId,
+        index_type: vk::IndexType,
+    ) -> Result<(), CommandError> {
+        let buffer = self.resolve("bind_index_buffer", id)?;
+        self.bind_index_buffer(IndexBinding::new(&buffer, index_type))
+    }
+
+    // draws need a graphics pipeline inside a render pass
+    fn check_draw(&self, call: &'static str) -> Result<(), CommandError> {
+        if !self.in_render_pass {
+            return refuse(call, "draws outside of a render pass");
+        }
+        if !self.inline {
+            return refuse(call, "the subpass executes secondary command buffers");
+        }
+        if !self
+            .pipelines
+            .contains_key(&vk::PipelineBindPoint::GRAPHICS)
+        {
+            return refuse(call, "draws without a graphics pipeline");
+        }
+        for buffer in self.vertex_buffers.values() {
+            self.check_buffer(call, *buffer)?;
+        }
+        Ok(())
+    }
+
+    pub unsafe fn draw(
+        &mut self,
+        vertex_count: u32,
+        instance_count: u32,
+        first_vertex: u32,
+        first_instance: u32,
+    ) -> Result<(), CommandError> {
+        self.check_draw("draw")?;
+        debug!(vertices = vertex_count, instances = instance_count, "draw");
+        self.device.cmd_draw(
+            self.command_buffer,
+            vertex_count,
+            instance_count,
+            first_vertex,
+            first_instance,
+        );
+        Ok(())
+    }
+
+    /// draw indices of the bound index buffer, they must all be in it.
+    pub unsafe fn draw_indexed(
+        &mut self,
+        first_index: u32,
+        index_count: u32,
+        instance_count: u32,
+    ) -> Result<(), CommandError> {
+        let call = "draw_indexed";
+        self.check_draw(call)?;
+        let Some(indices) = self.index_buffer else {
+            return refuse(call, "draws indexed without an index buffer");
+        };
+        self.check_buffer(call, indices.buffer)?;
+        indices
+            .draw(
+                self.device,
+                self.command_buffer,
+                first_index,
+                index_count,
+                instance_count,
+            )
+            .map_err(|error| CommandError {
+                call,
+                problem: format!("{} (buffer {:?})", error, indices.buffer),
+            })
+    }
+
+    /// run secondary command buffers that ended, in a subpass begun for them or outside of
+    /// render passes.
+    pub unsafe fn execute_commands(
+        &mut self,
+        command_buffers: &[vk::CommandBuffer],
+    ) -> Result<(), CommandError> {
+        let call = "execute_commands";
+        if self.in_render_pass && self.inline {
+            return refuse(call, "the subpass is recorded inline");
+        }
+        if command_buffers.iter().any(|b| b.is_null()) {
+            return refuse(call, "a command buffer is null");
+        }
+        debug!(count = command_buffers.len(), "execute commands");
+        self.device
+            .cmd_execute_commands(self.command_buffer, command_buffers);
+        Ok(())
+    }
+
+    pub unsafe fn dispatch(&mut self, x: u32, y: u32, z: u32) -> Result<(), CommandError> {
+        let call = "dispatch";
+        if self.in_render_pass {
+            return refuse(call, "dispatches inside a render pass");
+        }
+        if !self.pipelines.contains_key(&vk::PipelineBindPoint::COMPUTE) {
+            return refuse(call, "dispatches without a compute pipeline");
+        }
+        debug!(x, y, z, "dispatch");
+        self.device.cmd_dispatch(self.command_buffer, x, y, z);
+        Ok(())
+    }
+
+    /// zero `size` bytes of `buffer` from `offset`, `None` clears to the end of the buffer.
+    /// offset and size must be multiples of 4 and inside the buffer, which needs
+    /// `TRANSFER_DST` usage. fills can't be recorded in a render pass, so a buffer is cleared
+    /// before the compute or render passes using it, with a barrier in between.
+    pub unsafe fn clear_buffer(
+        &mut self,
+        buffer: &Buffer,
+        offset: vk::DeviceSize,
+        size: Option<vk::DeviceSize>,
+    ) -> Result<(), CommandError> {
+        let call = "clear_buffer";
+        if self.in_render_pass {
+            return refuse(call, "clears a buffer inside a render pass");
+        }
+        self.check_buffer(call, buffer.buffer)?;
+        if offset % 4 != 0 {
+            return refuse(
+                call,
+                format!("the offset {} is not a multiple of 4", offset),
+            );
+        }
+        if offset >= buffer.size {
+            return refuse(
+                call,
+                format!(
+                    "the offset {} is past the {} bytes of buffer {:?}",
+                    offset, buffer.size, buffer.buffer
+                ),
+            );
+        }
+        if let Some(size) = size {
+            if size == 0 || size % 4 != 0 {
+                return refuse(
+                    call,
+                    format!("the size {} is not a positive multiple of 4", size),
+                );
+            }
+            if size > buffer.size - offset {
+                return refuse(
+                    call,
+                    format!(
+                        "{} bytes from {} are past the {} bytes of buffer {:?}",
+                        size, offset, buffer.size, buffer.buffer
+                    ),
+                );
+            }
+        }
+
+        // the whole size stops at the last multiple of 4
+        let size = size.unwrap_or(vk::WHOLE_SIZE as vk::DeviceSize);
+        debug!(buffer = ?buffer.buffer, offset, size, "clear buffer");
+        self.device
+            .cmd_fill_buffer(self.command_buffer, buffer.buffer, offset, size, 0);
+        self.used(buffer.buffer);
+        Ok(())
+    }
+
+    /// `clear_buffer` with the registered buffer of `id`.
+    pub unsafe fn clear_buffer_id(
+        &mut self,
+        id: BufferId,
+        offset: vk::DeviceSize,
+        size: Option<vk::DeviceSize>,
+    ) -> Result<(), CommandError> {
+        let buffer = self.resolve("clear_buffer", id)?;
+        self.clear_buffer(&buffer, offset, size)
+    }
+
+    /// reset `queries` of `set` before they are written again, outside of a render pass.
+    pub unsafe fn reset_query_set(
+        &mut self,
+        set: &QuerySet,
+        queries: Range<u32>,
+    ) -> Result<(), CommandError> {
+        let call = "reset_query_set";
+        if self.in_render_pass {
+            return refuse(call, "resets queries inside a render pass");
+        }
+        check_queries(call, set, &queries)?;
+        debug!(pool = ?set.pool, ?queries, "reset queries");
+        self.device.cmd_reset_query_pool(
+            self.command_buffer,
+            set.pool,
+            queries.start,
+            queries.end - queries.start,
+        );
+        Ok(())
+    }
+
+    /// write the time the commands before reach `stage` into `query` of a timestamp set.
+    pub unsafe fn write_timestamp(
+        &mut self,
+        set: &QuerySet,
+        query: u32,
+        stage: vk::PipelineStageFlags,
+    ) -> Result<(), CommandError> {
+        let call = "write_timestamp";
+        if set.kind != QueryKind::Timestamp {
+            return refuse(
+                call,
+                format!("query set {:?} holds no timestamps", set.pool),
+            );
+        }
+        check_queries(call, set, &(query..query + 1))?;
+        debug!(pool = ?set.pool, query, ?stage, "write timestamp");
+        self.device
+            .cmd_write_timestamp(self.command_buffer, stage, set.pool, query);
+        Ok(())
+    }
+
+    /// count the samples of the following draws into `query` of an occlusion set until
+    /// `end_query`.
+    pub unsafe fn begin_query(&mut self, set: &QuerySet, query: u32) -> Result<(), CommandError> {
+        let call = "begin_query";
+        if set.kind != QueryKind::Occlusion {
+            return refuse(call, format!("query set {:?} counts no samples", set.pool));
+        }
+        check_queries(call, set, &(query..query + 1))?;
+        debug!(pool = ?set.pool, query, "begin query");
+        self.device.cmd_begin_query(
+            self.command_buffer,
+            set.pool,
+            query,
+            vk::QueryControlFlags::empty(),
+        );
+        Ok(())
+    }
+
+    pub unsafe fn end_query(&mut self, set: &QuerySet, query: u32) -> Result<(), CommandError> {
+        let call = "end_query";
+        check_queries(call, set, &(query..query + 1))?;
+        debug!(pool = ?set.pool, query, "end query");
+        self.device
+            .cmd_end_query(self.command_buffer, set.pool, query);
+        Ok(())
+    }
+
+    /// copy the results of `queries` of `set` into `destination` from `offset`, 64 bit
+    /// each and laid out as `mode` says, see `QueryResolve::results` to read them back.
+    /// the offset must be a multiple of 8 and the buffer needs `TRANSFER_DST` usage. copies
+    /// can't be recorded in a render pass, and the queries are reset before they are
+    /// written again.
+    pub unsafe fn resolve_query_set(
+        &mut self,
+        set: &QuerySet,
+        queries: Range<u32>,
+        destination: &Buffer,
+        offset: vk::DeviceSize,
+        mode: QueryResolve,
+    ) -> Result<(), CommandError> {
+        let call = "resolve_query_set";
+        if self.in_render_pass {
+            return refuse(call, "resolves queries inside a render pass");
+        }
+        check_queries(call, set, &queries)?;
+        self.check_buffer(call, destination.buffer)?;
+        if mode == QueryResolve::Partial && set.kind == QueryKind::Timestamp {
+            return refuse(call, "timestamps can't be resolved partially");
+        }
+        if offset % 8 != 0 {
+            return refuse(
+                call,
+                format!("the offset {} is not a multiple of 8", offset),
+            );
+        }
+        let count = queries.end - queries.start;
+        let size = count as vk::DeviceSize * mode.stride();
+        if offset
+            .checked_add(size)
+            .is_none_or(|end| end > destination.size)
+        {
+            return refuse(
+                call,
+                format!(
+                    "{} bytes from {} are past the {} bytes of buffer {:?}",
+                    size, offset, destination.size, destination.buffer
+                ),
+            );
+        }
+
+        debug!(pool = ?set.pool, ?queries, buffer = ?destination.buffer, offset, ?mode, "resolve queries");
+        self.device.cmd_copy_query_pool_results(
+            self.command_buffer,
+            set.pool,
+            queries.start,
+            count,
+            destination.buffer,
+            offset,
+            mode.stride(),
+            mode.flags(),
+        );
+        self.used(destination.buffer);
+        Ok(())
+    }
+
+    /// check the recording can end, a render pass must not be left running.
+    pub fn finish(self) -> Result<(), CommandError> {
+        if self.in_render_pass && !self.continues {
+            return refuse("finish", "the render pass was not ended");
+        }
+        Ok(())
+    }
+}
+
+fn check_queries(
+    call: &'static str,
+    set: &QuerySet,
+    queries: &Range<u32>,
+) -> Result<(), CommandError> {
+    if set.pool.is_null() {
+        return refuse(call, "the query set is null");
+    }
+    if !set.contains(queries) {
+        return refuse(
+            call,
+            format!(
+                "queries {:?} are not in the {} of query set {:?}",
+                queries, set.count, set.pool
+            ),
+        );
+    }
+    Ok(())
+}
@@ -9,6 +9,7 @@
 )]
 
 use anyhow::{anyhow, Result};
+use deimos_format::{VertexFormat, VertexKind, VertexLayout};
 use std::hash::{Hash, Hasher};
 use std::mem::size_of;
 use thiserror::Error;
@@ -16,9 +17,8 @@ use thiserror::Error;
 use vulkanalia::prelude::v1_0::*;
 use vulkanalia::vk::KhrSurfaceExtension;
 
-type Vec2 = cgmath::Vector2<f32>;
-type Vec3 = cgmath::Vector3<f32>;
-type Mat4 = cgmath::Matrix4<f32>;
+use super::{dedicated_queue_families, VertexBufferLayout};
+use crate::math::{Mat4, Vec2, Vec3, Vec4};
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug)]
@@ -27,6 +27,11 @@ pub struct UniformBufferObject {
     pub proj: Mat4,
 }
 
+crate::pod!(UniformBufferObject {
+    view: Mat4,
+    proj: Mat4
+});
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug)]
 pub struct Vertex {
@@ -35,6 +40,12 @@ pub struct Vertex {
     pub color: Vec3,
 }
 
+crate::pod!(Vertex {
+    position: Vec3,
+    texel: Vec2,
+    color: Vec3
+});
+
 impl Vertex {
     pub fn new(position: Vec3, texel: Vec2, color: Vec3) -> Self {
         Self {
@@ -45,33 +56,21 @@ impl Vertex {
     }
 
     pub fn binding_description() -> vk::VertexInputBindingDescription {
-        vk::VertexInputBindingDescription::builder()
-            .binding(0)
-            .stride(size_of::<Vertex>() as u32)
-            .input_rate(vk::VertexInputRate::VERTEX)
-            .build()
+        binding_description(VertexKind::Basic.layout())
     }
 
     pub fn attribute_descriptions() -> [vk::VertexInputAttributeDescription; 3] {
-        let pos = vk::VertexInputAttributeDescription::builder()
-            .binding(0)
-            .location(0)
-            .format(vk::Format::R32G32B32_SFLOAT)
-            .offset(0)
-            .build();
-        let color = vk::VertexInputAttributeDescription::builder()
-            .binding(0)
-            .location(1)
-            .format(vk::Format::R32G32B32_SFLOAT)
-            .offset(size_of::<Vec3>() as u32)
-            .build();
-        let tex_coord = vk::VertexInputAttributeDescription::builder()
-            .binding(0)
-            .location(2)
-            .format(vk::Format::R32G32_SFLOAT)
-            .offset((size_of::<Vec3>() + size_of::<Vec3>()) as u32)
-            .build();
-        [pos, color, tex_coord]
+        attribute_descriptions(VertexKind::Basic.layout())
+    }
+
+    /// the layout of a vertex buffer of these vertices at binding 0.
+    pub fn layout() -> VertexBufferLayout {
+        VertexBufferLayout {
+            binding: 0,
+            stride: size_of::<Self>() as u32,
+            input_rate: vk::VertexInputRate::VERTEX,
+            attributes: Self::attribute_descriptions().to_vec(),
+        }
     }
 }
 
@@ -96,14 +95,95 @@ impl Hash for Vertex {
     }
 }
 
+/// A lit surface vertex, the tangent w holds the handedness of the bitangent.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SurfaceVertex {
+    pub position: Vec3,
+    pub normal: Vec3,
+    pub tangent: Vec4,
+    pub texel: Vec2,
+}
+
+crate::pod!(SurfaceVertex {
+    position: Vec3,
+    normal: Vec3,
+    tangent: Vec4,
+    texel: Vec2
+});
+
+impl SurfaceVertex {
+    pub fn new(position: Vec3, normal: Vec3, tangent: Vec4, texel: Vec2) -> Self {
+        Self {
+            position,
+            normal,
+            tangent,
+            texel,
+        }
+    }
+
+    pub fn binding_description() -> vk::VertexInputBindingDescription {
+        binding_description(VertexKind::Surface.layout())
+    }
+
+    pub fn attribute_descriptions() -> [vk::VertexInputAttributeDescription; 4] {
+        attribute_descriptions(VertexKind::Surface.layout())
+    }
+
+    /// the layout of a vertex buffer of these vertices at binding 0.
+    pub fn layout() -> VertexBufferLayout {
+        VertexBufferLayout {
+            binding: 0,
+            stride: size_of::<Self>() as u32,
+            input_rate: vk::VertexInputRate::VERTEX,
+            attributes: Self::attribute_descriptions().to_vec(),
+        }
+    }
+}
+
+// the layouts are shared with the asset tooling, the engine vertices must match them.
+const _: () = assert!(size_of::<Vertex>() == VertexKind::Basic.layout().stride as usize);
+const _: () = assert!(size_of::<SurfaceVertex>() == VertexKind::Surface.layout().stride as usize);
+
+fn binding_description(layout: VertexLayout) -> vk::VertexInputBindingDescription {
+    vk::VertexInputBindingDescription::builder()
+        .binding(0)
+        .stride(layout.stride)
+        .input_rate(vk::VertexInputRate::VERTEX)
+        .build()
+}
+
+fn attribute_descriptions<const N: usize>(
+    layout: VertexLayout,
+) -> [vk::VertexInputAttributeDescription; N] {
+    assert_eq!(layout.attributes.len(), N);
+    std::array::from_fn(|i| {
+        let attribute = layout.attributes[i];
+        let format = match attribute.format {
+            VertexFormat::Float32x2 => vk::Format::R32G32_SFLOAT,
+            VertexFormat::Float32x3 => vk::Format::R32G32B32_SFLOAT,
+            VertexFormat::Float32x4 => vk::Format::R32G32B32A32_SFLOAT,
+        };
+        vk::VertexInputAttributeDescription::builder()
+            .binding(0)
+            .location(attribute.location)
+            .format(format)
+            .offset(attribute.offset)
+            .build()
+    })
+}
+
 #[derive(Debug, Error)]
 #[error("{0}")]
-pub struct SuitabilityError(pub &'static str);
+pub(crate) struct SuitabilityError(pub &'static str);
 
 #[derive(Copy, Clone, Debug)]
-pub struct QueueFamilyIndices {
+pub(crate) struct QueueFamilyIndices {
     pub graphics: u32,
     pub present: u32,
+    // dedicated families, none when the graphics family does the work
+    pub compute: Option<u32>,
+    pub transfer: Option<u32>,
 }
 
 impl QueueFamilyIndices {
@@ -131,8 +211,14 @@ impl QueueFamilyIndices {
             }
         }
 
+        let (compute, transfer) = dedicated_queue_families(&properties);
         if let (Some(graphics), Some(present)) = (graphics, present) {
-            Ok(Self { graphics, present })
+            Ok(Self {
+                graphics,
+                present,
+                compute,
+                transfer,
+            })
         } else {
             Err(anyhow!(SuitabilityError(
                 "Missing required queue families."
@@ -0,0 +1,101 @@
+#![allow(dead_code)]
+
+use vulkanalia::prelude::v1_0::*;
+
+/// What happened to a `Device`, passed to the observers registered with `Device::on_resize`
+/// and the like.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DeviceEvent {
+    /// the window changed its size, mode or scale factor. `width` and `height` are physical
+    /// pixels, zero while minimized. the swapchain follows later.
+    Resized { width: u32, height: u32 },
+    /// the swapchain was replaced, views, framebuffers and pipelines of the old format or
+    /// extent must be rebuilt.
+    SwapchainRecreated {
+        format: vk::Format,
+        extent: vk::Extent2D,
+    },
+    /// an image was acquired, `slot` is the frame in flight and `index` the swapchain image.
+    FrameBegin { slot: usize, index: usize },
+    /// the frame was submitted and presented.
+    FrameEnd { slot: usize, index: usize },
+    /// the gpu stopped responding, the device must be recreated.
+    DeviceLost,
+}
+
+impl DeviceEvent {
+    pub fn kind(&self) -> DeviceEventKind {
+        match self {
+            DeviceEvent::Resized { .. } => DeviceEventKind::Resized,
+            DeviceEvent::SwapchainRecreated { .. } => DeviceEventKind::SwapchainRecreated,
+            DeviceEvent::FrameBegin { .. } => DeviceEventKind::FrameBegin,
+            DeviceEvent::FrameEnd { .. } => DeviceEventKind::FrameEnd,
+            DeviceEvent::DeviceLost => DeviceEventKind::DeviceLost,
+        }
+    }
+}
+
+/// The kinds of `DeviceEvent` an observer is registered for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DeviceEventKind {
+    Resized,
+    SwapchainRecreated,
+    FrameBegin,
+    FrameEnd,
+    DeviceLost,
+}
+
+/// Identifies a registered observer, see `Device::remove_observer`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ObserverId(u64);
+
+type Observer = Box<dyn FnMut(&DeviceEvent) + Send>;
+
+/// The observers of device events, called in the order they were registered.
+#[derive(Default)]
+pub struct DeviceEvents {
+    observers: Vec<(ObserverId, DeviceEventKind, Observer)>,
+    next: u64,
+}
+
+impl DeviceEvents {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// call `observer` with every event of `kind` from now on, on the thread the device is
+    /// used on.
+    pub fn observe<F>(&mut self, kind: DeviceEventKind, observer: F) -> ObserverId
+    where
+        F: FnMut(&DeviceEvent) + Send + 'static,
+    {
+        let id = ObserverId(self.next);
+        self.next += 1;
+        self.observers.push((id, kind, Box::new(observer)));
+        id
+    }
+
+    /// stop calling the observer of `id`, false when it was removed already.
+    pub fn remove(&mut self, id: ObserverId) -> bool {
+        let count = self.observers.len();
+        self.observers.retain(|(i, _, _)| *i != id);
+        self.observers.len() != count
+    }
+
+    /// the number of registered observers.
+    pub fn len(&self) -> usize {
+        self.observers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.observers.is_empty()
+    }
+
+    /// call the observers of the kind of `event`, oldest first.
+    pub fn emit(&mut self, event: DeviceEvent) {
+        let kind = event.kind();
+        for (_, _, observer) in self.observers.iter_mut().filter(|(_, k, _)| *k == kind) {
+            observer(&event);
+        }
+    }
+}
@@ -0,0 +1,220 @@
+#![allow(dead_code)]
+
+#[cfg(feature = "ffmpeg")]
+use std::io::Write;
+#[cfg(feature = "ffmpeg")]
+use std::path::PathBuf;
+#[cfg(feature = "ffmpeg")]
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use anyhow::Result;
+#[cfg(feature = "ffmpeg")]
+use anyhow::{anyhow, Context};
+
+/// A presented frame copied back for export, rgba8 pixels in the color space of the
+/// swapchain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportedFrame {
+    /// the position of the frame in the capture, gaps are frames the renderer was too slow
+    /// for.
+    pub index: u64,
+    /// the time of the frame in the capture, `index` frame intervals.
+    pub timestamp: Duration,
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// Where exported frames go, e.g. files, a network stream or `FfmpegSink`. closures taking
+/// an `&ExportedFrame` are sinks too.
+pub trait FrameSink {
+    /// take the next frame, frames arrive in the order of their index.
+    fn write(&mut self, frame: &ExportedFrame) -> Result<()>;
+
+    /// called once when the export stops, after the last frame.
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<F> FrameSink for F
+where
+    F: FnMut(&ExportedFrame) -> Result<()>,
+{
+    fn write(&mut self, frame: &ExportedFrame) -> Result<()> {
+        self(frame)
+    }
+}
+
+/// How often frames are captured while exporting.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FrameExportSettings {
+    /// the frames per second of the capture, independent of the rate the app renders at.
+    pub framerate: f64,
+}
+
+impl FrameExportSettings {
+    pub fn new(framerate: f64) -> Self {
+        Self {
+            framerate: framerate.max(1.0),
+        }
+    }
+}
+
+impl Default for FrameExportSettings {
+    fn default() -> Self {
+        Self::new(30.0)
+    }
+}
+
+/// Picks the rendered frames that are captured to reach a framerate. a frame is captured
+/// once the time since the start crossed into a new frame interval, the capture gets the
+/// timestamp of that interval.
+#[derive(Copy, Clone, Debug)]
+pub struct FrameClock {
+    interval: Duration,
+    // the index of the next interval to capture
+    next: u64,
+}
+
+impl FrameClock {
+    pub fn new(framerate: f64) -> Self {
+        Self {
+            interval: Duration::from_secs_f64(1.0 / framerate.max(1.0)),
+            next: 0,
+        }
+    }
+
+    /// the time between captured frames.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// the index and timestamp to capture a frame rendered `elapsed` after the start with,
+    /// none when the interval it falls in was captured already.
+    pub fn tick(&mut self, elapsed: Duration) -> Option<(u64, Duration)> {
+        let index = (elapsed.as_nanos() / self.interval.as_nanos()) as u64;
+        if index < self.next {
+            return None;
+        }
+        self.next = index + 1;
+        let timestamp = self.interval.as_nanos() * index as u128;
+        Some((index, Duration::from_nanos(timestamp as u64)))
+    }
+}
+
+/// Encodes exported frames into a video file by piping them to the `ffmpeg` executable,
+/// which must be on the path. frames the renderer skipped are filled in with the frame
+/// before them so the video keeps its framerate.
+#[cfg(feature = "ffmpeg")]
+pub struct FfmpegSink {
+    path: PathBuf,
+    framerate: f64,
+    // extra arguments for the output, h264 in yuv420p by default
+    codec: Vec<String>,
+    encoder: Option<Child>,
+    // the size the stream was started with
+    size: Option<(u32, u32)>,
+    // the index and pixels of the frame written last
+    last: Option<(u64, Vec<u8>)>,
+}
+
+#[cfg(feature = "ffmpeg")]
+impl FfmpegSink {
+    /// encode into `path` at `framerate`, which should match `FrameExportSettings::framerate`.
+    pub fn new(path: impl Into<PathBuf>, framerate: f64) -> Self {
+        Self {
+            path: path.into(),
+            framerate,
+            codec: ["-c:v", "libx264", "-pix_fmt", "yuv420p"]
+                .map(String::from)
+                .to_vec(),
+            encoder: None,
+            size: None,
+            last: None,
+        }
+    }
+
+    /// encode with these output arguments instead of h264, e.g. `["-c:v", "ffv1"]`.
+    pub fn with_codec(mut self, arguments: &[&str]) -> Self {
+        self.codec = arguments.iter().map(|a| a.to_string()).collect();
+        self
+    }
+
+    fn start(&mut self, width: u32, height: u32) -> Result<()> {
+        let encoder = Command::new("ffmpeg")
+            .args([
+                "-y",
+                "-loglevel",
+                "error",
+                "-f",
+                "rawvideo",
+                "-pixel_format",
+                "rgba",
+            ])
+            .args(["-video_size", &format!("{}x{}", width, height)])
+            .args(["-framerate", &self.framerate.to_string()])
+            .args(["-i", "-"])
+            .args(&self.codec)
+            .arg(&self.path)
+            .stdin(Stdio::piped())
+            .spawn()
+            .context("Failed to start ffmpeg.")?;
+        self.encoder = Some(encoder);
+        self.size = Some((width, height));
+        Ok(())
+    }
+}
+
+#[cfg(feature = "ffmpeg")]
+impl FrameSink for FfmpegSink {
+    fn write(&mut self, frame: &ExportedFrame) -> Result<()> {
+        match self.size {
+            None => self.start(frame.width, frame.height)?,
+            Some(size) if size != (frame.width, frame.height) => {
+                return Err(anyhow!(
+                    "A video started at {}x{} can't continue at {}x{}.",
+                    size.0,
+                    size.1,
+                    frame.width,
+                    frame.height
+                ))
+            }
+            Some(_) => {}
+        }
+        let stdin = self
+            .encoder
+            .as_mut()
+            .and_then(|e| e.stdin.as_mut())
+            .ok_or_else(|| anyhow!("The ffmpeg encoder has finished."))?;
+
+        // repeat the last frame for the intervals that were skipped
+        if let Some((index, pixels)) = &self.last {
+            for _ in index + 1..frame.index {
+                stdin.write_all(pixels)?;
+            }
+        }
+        stdin.write_all(&frame.pixels)?;
+        self.last = Some((frame.index, frame.pixels.clone()));
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let Some(mut encoder) = self.encoder.take() else {
+            return Ok(());
+        };
+
+        // closing the input ends the video
+        drop(encoder.stdin.take());
+        let status = encoder.wait()?;
+        if !status.success() {
+            return Err(anyhow!(
+                "ffmpeg failed to encode {:?} ({}).",
+                self.path,
+                status
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+#![allow(dead_code)]
+
+use std::collections::HashSet;
+
+use vulkanalia::prelude::v1_0::*;
+
+use super::DescriptionError;
+
+/// An optional capability of an adapter. `AdapterInfo::features` lists the ones an adapter
+/// has, devices enable those asked for when they are created and refuse anything that needs
+/// a feature they didn't enable.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Feature {
+    /// depth is clamped to the viewport range instead of clipped, `depthClamp`.
+    DepthClipControl,
+    /// timestamps can be written on the graphics and compute queues.
+    TimestampQuery,
+    /// indirect draws can start past the first instance, `drawIndirectFirstInstance`.
+    IndirectFirstInstance,
+    /// several indirect draws in a single command, `multiDrawIndirect`.
+    MultiDrawIndirect,
+    /// BC1 to BC7 compressed textures, `textureCompressionBC`.
+    TextureCompressionBc,
+    /// ETC2 and EAC compressed textures, `textureCompressionETC2`.
+    TextureCompressionEtc2,
+    /// ASTC LDR compressed textures, `textureCompressionASTC_LDR`.
+    TextureCompressionAstc,
+    /// wireframe and point polygon modes, `fillModeNonSolid`.
+    FillModeNonSolid,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 8] = [
+        Feature::DepthClipControl,
+        Feature::TimestampQuery,
+        Feature::IndirectFirstInstance,
+        Feature::MultiDrawIndirect,
+        Feature::TextureCompressionBc,
+        Feature::TextureCompressionEtc2,
+        Feature::TextureCompressionAstc,
+        Feature::FillModeNonSolid,
+    ];
+
+    /// the features of an adapter with these vulkan features and limits.
+    pub fn available(
+        features: &vk::PhysicalDeviceFeatures,
+        limits: &vk::PhysicalDeviceLimits,
+    ) -> HashSet<Feature> {
+        Self::ALL
+            .into_iter()
+            .filter(|feature| match feature {
+                Feature::DepthClipControl => features.depth_clamp == vk::TRUE,
+                Feature::TimestampQuery => limits.timestamp_compute_and_graphics == vk::TRUE,
+                Feature::IndirectFirstInstance => features.draw_indirect_first_instance == vk::TRUE,
+                Feature::MultiDrawIndirect => features.multi_draw_indirect == vk::TRUE,
+                Feature::TextureCompressionBc => features.texture_compression_bc == vk::TRUE,
+                Feature::TextureCompressionEtc2 => features.texture_compression_etc2 == vk::TRUE,
+                Feature::TextureCompressionAstc => {
+                    features.texture_compression_astc_ldr == vk::TRUE
+                }
+                Feature::FillModeNonSolid => features.fill_mode_non_solid == vk::TRUE,
+            })
+            .collect()
+    }
+
+    /// turn the vulkan feature behind `self` on in `features`, timestamps need none.
+    pub fn enable(&self, features: &mut vk::PhysicalDeviceFeatures) {
+        match self {
+            Feature::DepthClipControl => features.depth_clamp = vk::TRUE,
+            Feature::TimestampQuery => {}
+            Feature::IndirectFirstInstance => features.draw_indirect_first_instance = vk::TRUE,
+            Feature::MultiDrawIndirect => features.multi_draw_indirect = vk::TRUE,
+            Feature::TextureCompressionBc => features.texture_compression_bc = vk::TRUE,
+            Feature::TextureCompressionEtc2 => features.texture_compression_etc2 = vk::TRUE,
+            Feature::TextureCompressionAstc => features.texture_compression_astc_ldr = vk::TRUE,
+            Feature::FillModeNonSolid => features.fill_mode_non_solid = vk::TRUE,
+        }
+    }
+
+    /// the feature textures of `format` need, if any.
+    pub fn of_format(format: vk::Format) -> Option<Feature> {
+        let raw = format.as_raw();
+        let within =
+            |first: vk::Format, last: vk::Format| (first.as_raw()..=last.as_raw()).contains(&raw);
+        if within(vk::Format::BC1_RGB_UNORM_BLOCK, vk::Format::BC7_SRGB_BLOCK) {
+            Some(Feature::TextureCompressionBc)
+        } else if within(
+            vk::Format::ETC2_R8G8B8_UNORM_BLOCK,
+            vk::Format::EAC_R11G11_SNORM_BLOCK,
+        ) {
+            Some(Feature::TextureCompressionEtc2)
+        } else if within(
+            vk::Format::ASTC_4X4_UNORM_BLOCK,
+            vk::Format::ASTC_12X12_SRGB_BLOCK,
+        ) {
+            Some(Feature::TextureCompressionAstc)
+        } else {
+            None
+        }
+    }
+}
+
+/// the vulkan features to create a device with, the ones every device enables and those of
+/// `requested`. fails when the adapter lacks one of them.
+pub fn device_features(
+    base: vk::PhysicalDeviceFeatures,
+    requested: &[Feature],
+    available: &HashSet<Feature>,
+) -> Result<vk::PhysicalDeviceFeatures, DescriptionError> {
+    let mut features = base;
+    for feature in requested {
+        if !available.contains(feature) {
+            return Err(DescriptionError::FeatureUnavailable(*feature));
+        }
+        feature.enable(&mut features);
+    }
+    Ok(features)
+}
+
+/// check `feature` was enabled on the device.
+pub fn check_feature(feature: Feature, enabled: &HashSet<Feature>) -> Result<(), DescriptionError> {
+    if !enabled.contains(&feature) {
+        return Err(DescriptionError::FeatureNotEnabled(feature));
+    }
+    Ok(())
+}
+
+/// check textures of `format` can be created with the `enabled` features.
+pub fn check_format_feature(
+    format: vk::Format,
+    enabled: &HashSet<Feature>,
+) -> Result<(), DescriptionError> {
+    match Feature::of_format(format) {
+        Some(feature) => check_feature(feature, enabled),
+        None => Ok(()),
+    }
+}
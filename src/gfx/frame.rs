@@ -1,9 +1,12 @@
 #![allow(dead_code, unused_variables)]
 
-use super::TextureView;
+use super::{clip_rects, DeviceWindow, TextureView};
 use anyhow::Result;
+use tracing::debug_span;
 use vulkanalia::prelude::v1_0::*;
 
+use crate::debug::CheckedCapture;
+
 pub struct FrameBuffer {
     pub buffer: vk::Framebuffer,
 }
@@ -40,3 +43,118 @@ impl FrameBuffer {
         device.destroy_framebuffer(self.buffer, None);
     }
 }
+
+/// A swapchain image acquired by `Device::begin_frame`, recording the work the scene depends
+/// on. `scene` records the scene pass and `PostFrame::present` post processing, then the
+/// frame is submitted and presented. each step takes the frame, so they can't be skipped or
+/// run out of order. a frame must be presented, the image stays acquired otherwise.
+#[must_use = "the acquired image is only released by presenting the frame"]
+pub struct Frame<'a> {
+    pub(super) device: &'a mut super::Device,
+    pub(super) window: &'a dyn DeviceWindow,
+    pub(super) index: usize,
+    pub(super) regions: Vec<vk::Rect2D>,
+    pub(super) command_buffer: vk::CommandBuffer,
+    pub(super) _capture: CheckedCapture,
+}
+
+impl<'a> Frame<'a> {
+    /// the device the frame records on.
+    pub fn device(&self) -> &super::Device {
+        self.device
+    }
+
+    /// the primary command buffer of the frame, in its prepare region until `scene`.
+    pub fn command_buffer(&self) -> vk::CommandBuffer {
+        self.command_buffer
+    }
+
+    /// the index of the acquired swapchain image.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// end the prepare region and record the scene pass, `scene` records the draws.
+    pub fn scene<S>(self, scene: S) -> Result<PostFrame<'a>>
+    where
+        S: FnOnce(&super::Device, vk::CommandBuffer, usize) -> Result<()>,
+    {
+        let result = unsafe {
+            self.device
+                .record_scene(self.command_buffer, self.index, scene)
+        };
+        self.device.check_frame(result)?;
+        Ok(PostFrame {
+            frame: self,
+            hint: None,
+        })
+    }
+}
+
+/// A frame whose scene is recorded, `present` records post processing and presents it.
+#[must_use = "the acquired image is only released by presenting the frame"]
+pub struct PostFrame<'a> {
+    frame: Frame<'a>,
+    // the regions the present hints as changed, set by `with_damage`
+    hint: Option<Vec<vk::Rect2D>>,
+}
+
+impl<'a> PostFrame<'a> {
+    /// the device the frame records on.
+    pub fn device(&self) -> &super::Device {
+        self.frame.device
+    }
+
+    /// the primary command buffer of the frame.
+    pub fn command_buffer(&self) -> vk::CommandBuffer {
+        self.frame.command_buffer
+    }
+
+    /// the index of the acquired swapchain image.
+    pub fn index(&self) -> usize {
+        self.frame.index
+    }
+
+    /// hint to the compositor that only `damage` changed since the image was presented
+    /// before, in swapchain pixels. unchanged parts of mostly static frames then aren't
+    /// composited again. the whole image is still recorded, the hint needs
+    /// `Device::supports_incremental_present` and overrides the damage of damage mode.
+    pub fn with_damage(mut self, damage: &[vk::Rect2D]) -> Self {
+        let extent = self.frame.device.extent();
+        self.hint = Some(clip_rects(damage, extent));
+        self
+    }
+
+    /// record post processing with `post`, then submit and present the frame.
+    pub fn present<P>(self, post: P) -> Result<()>
+    where
+        P: FnOnce(&super::Device, vk::CommandBuffer, usize) -> Result<()>,
+    {
+        self.present_with_latch(post, |_, _| Ok(()))
+    }
+
+    /// record post processing with `post`, then invoke `latch` right before the frame is
+    /// submitted and presented, see `Device::update_with_latch`.
+    pub fn present_with_latch<P, L>(self, post: P, latch: L) -> Result<()>
+    where
+        P: FnOnce(&super::Device, vk::CommandBuffer, usize) -> Result<()>,
+        L: FnOnce(&super::Device, usize) -> Result<()>,
+    {
+        let Frame {
+            device,
+            window,
+            index,
+            regions,
+            command_buffer,
+            _capture,
+        } = self.frame;
+        let hint = self.hint.or_else(|| device.damage_hint(regions));
+        let result = debug_span!("present").in_scope(|| unsafe {
+            device.finish_commands(command_buffer, index, post)?;
+            device.latch_frame(index, latch)?;
+            device.present_frame(window, index, hint.as_deref())
+        });
+        drop(_capture);
+        device.frame_ended(result)
+    }
+}
@@ -0,0 +1,187 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use anyhow::{anyhow, Result};
+
+/// A typed reference to a resource of `T` in a `HandlePool`. handles are plain ids, copying
+/// one doesn't copy the resource, and a handle of one type can't be passed where another is
+/// expected. the generation tells a handle of a destroyed resource apart from the one that
+/// reuses its slot, using it afterwards is an error instead of touching the wrong resource,
+/// from the pool and from an `Encoder` recording with the ids of a `ResourceRegistry`.
+pub struct Handle<T> {
+    index: u32,
+    generation: u32,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    /// the slot of the resource in its pool.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// how often the slot was reused before this resource.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+// derives would require `T` to implement the traits too
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Handle({}v{})", self.index, self.generation)
+    }
+}
+
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+/// Resources of `T` behind handles. removing a resource invalidates its handle, its slot is
+/// reused by the next insert with the generation bumped.
+pub struct HandlePool<T> {
+    slots: Vec<Slot<T>>,
+    // slots without a resource, reused last freed first
+    free: Vec<u32>,
+}
+
+impl<T> Default for HandlePool<T> {
+    fn default() -> Self {
+        Self {
+            slots: vec![],
+            free: vec![],
+        }
+    }
+}
+
+impl<T> HandlePool<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// store `value`, returns the handle it is reached by.
+    pub fn insert(&mut self, value: T) -> Handle<T> {
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index as usize].value = Some(value);
+                index
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    value: Some(value),
+                });
+                (self.slots.len() - 1) as u32
+            }
+        };
+        Handle {
+            index,
+            generation: self.slots[index as usize].generation,
+            marker: PhantomData,
+        }
+    }
+
+    /// whether `handle` still refers to a resource.
+    pub fn contains(&self, handle: Handle<T>) -> bool {
+        self.slot(handle).is_some()
+    }
+
+    /// the resource of `handle`, an error when it was removed.
+    pub fn get(&self, handle: Handle<T>) -> Result<&T> {
+        self.slot(handle)
+            .and_then(|s| s.value.as_ref())
+            .ok_or_else(|| stale(handle))
+    }
+
+    pub fn get_mut(&mut self, handle: Handle<T>) -> Result<&mut T> {
+        match self.slots.get_mut(handle.index as usize) {
+            Some(slot) if slot.generation == handle.generation => {
+                slot.value.as_mut().ok_or_else(|| stale(handle))
+            }
+            _ => Err(stale(handle)),
+        }
+    }
+
+    /// take the resource of `handle` out, the handle and its copies are invalid afterwards.
+    pub fn remove(&mut self, handle: Handle<T>) -> Result<T> {
+        let slot = match self.slots.get_mut(handle.index as usize) {
+            Some(slot) if slot.generation == handle.generation => slot,
+            _ => return Err(stale(handle)),
+        };
+        let value = slot.value.take().ok_or_else(|| stale(handle))?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(handle.index);
+        Ok(value)
+    }
+
+    /// the number of stored resources.
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// the stored resources with their handles, in slot order.
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let handle = Handle {
+                index: index as u32,
+                generation: slot.generation,
+                marker: PhantomData,
+            };
+            slot.value.as_ref().map(|value| (handle, value))
+        })
+    }
+
+    /// take every resource out, e.g. to destroy them with the device. all handles are
+    /// invalid afterwards.
+    pub fn drain(&mut self) -> Vec<T> {
+        let mut values = vec![];
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if let Some(value) = slot.value.take() {
+                slot.generation = slot.generation.wrapping_add(1);
+                self.free.push(index as u32);
+                values.push(value);
+            }
+        }
+        values
+    }
+
+    fn slot(&self, handle: Handle<T>) -> Option<&Slot<T>> {
+        self.slots
+            .get(handle.index as usize)
+            .filter(|s| s.generation == handle.generation && s.value.is_some())
+    }
+}
+
+fn stale<T>(handle: Handle<T>) -> anyhow::Error {
+    anyhow!("Handle {handle:?} doesn't refer to a resource, it was destroyed or never created.")
+}
@@ -0,0 +1,701 @@
+#![allow(dead_code)]
+
+use std::cell::Cell;
+use std::collections::HashSet;
+
+use anyhow::{anyhow, Result};
+use tracing::{debug_span, info, warn};
+use vulkanalia::loader::{LibloadingLoader, LIBRARY};
+use vulkanalia::prelude::v1_0::*;
+use vulkanalia::vk::ExtDebugUtilsExtension;
+
+use crate::debug::CheckedCapture;
+
+use super::allocation::{self, Allocation, AllocationHooks, AllocationStats};
+use super::capture::{self, CaptureLoader};
+use super::device::{
+    create_buffer, create_layered_texture, create_query_set, create_texture, debug_callback,
+    debug_utils_available, host_query_reset_available, multiview_available, properties2_available,
+    read_texture, timeline_semaphores_available, upload_texture, VALIDATION_LAYER,
+};
+use super::names::DebugNames;
+use super::{
+    buffer_memory_properties, check_feature, check_format_feature, device_features, set_viewports,
+    validation_errors, AdapterInfo, AdapterSelection, Buffer, CommandPool, Feature, FrameTrace,
+    NullBackend, PassInheritance, QueryKind, QuerySet, RenderPassCache, RenderPassDescriptor,
+    SamplerCache, SamplerDescriptor, Texture, TextureImport, TexturePixels, TextureView, Timeline,
+    ValidationSettings,
+};
+
+/// A device without a window or swapchain, for tests, offline tools and compute work. the
+/// single queue supports graphics and compute, every submission waits for completion.
+pub struct HeadlessDevice {
+    entry: Entry,
+    instance: Instance,
+    messenger: Option<vk::DebugUtilsMessengerEXT>,
+    names: DebugNames,
+    physical: vk::PhysicalDevice,
+    adapter: AdapterInfo,
+    // the optional features enabled at creation
+    features: HashSet<Feature>,
+    device: vulkanalia::Device,
+    family: u32,
+    queue: vk::Queue,
+    pool: CommandPool,
+    samplers: SamplerCache,
+    passes: RenderPassCache,
+    timeline_semaphores: bool,
+    host_query_reset: bool,
+    // the validation error count when the device was created
+    errors: usize,
+    null: Option<NullBackend>,
+    // the submissions of the null backend when the capture started
+    capture: Cell<Option<usize>>,
+}
+
+impl HeadlessDevice {
+    /// create the device on the adapter `selection` picks. none when there is no vulkan
+    /// loader or no adapter, so tests can skip on machines without a driver.
+    pub fn create(selection: AdapterSelection) -> Result<Option<Self>> {
+        Self::create_with_features(selection, &[])
+    }
+
+    /// create the device with the optional `features` enabled, see
+    /// `Device::create_with_features`.
+    pub fn create_with_features(
+        selection: AdapterSelection,
+        features: &[Feature],
+    ) -> Result<Option<Self>> {
+        Self::create_with_validation(selection, features, ValidationSettings::from_env())
+    }
+
+    /// create the device validated as `validation` says. unlike windowed devices it is
+    /// created without validation when the layer isn't installed.
+    pub fn create_with_validation(
+        selection: AdapterSelection,
+        features: &[Feature],
+        validation: ValidationSettings,
+    ) -> Result<Option<Self>> {
+        unsafe {
+            let Ok(loader) = LibloadingLoader::new(LIBRARY) else {
+                return Ok(None);
+            };
+            let loader = CaptureLoader::new(loader);
+            let Ok(entry) = Entry::new(loader) else {
+                return Ok(None);
+            };
+            Self::create_on(entry, selection, features, validation, None)
+        }
+    }
+
+    /// create the device on the null driver `backend`, it needs no gpu and records what the
+    /// device does for tests to check.
+    pub fn create_null(backend: &NullBackend) -> Result<Self> {
+        Self::create_null_with_features(backend, &[])
+    }
+
+    /// create the device on the null driver with the optional `features` enabled.
+    pub fn create_null_with_features(backend: &NullBackend, features: &[Feature]) -> Result<Self> {
+        unsafe {
+            let entry = Entry::new(backend.clone()).map_err(|e| anyhow!("{}", e))?;
+            backend
+                .enter(|| {
+                    let null = Some(backend.clone());
+                    let validation = ValidationSettings::from_env();
+                    Self::create_on(entry, AdapterSelection::First, features, validation, null)
+                })?
+                .ok_or_else(|| anyhow!("The null driver has no adapter."))
+        }
+    }
+
+    unsafe fn create_on(
+        entry: Entry,
+        selection: AdapterSelection,
+        features: &[Feature],
+        validation: ValidationSettings,
+        null: Option<NullBackend>,
+    ) -> Result<Option<Self>> {
+        // a loader without any driver reports an incompatible driver
+        let (instance, messenger, names) = match create_instance(&entry, validation) {
+            Err(error) if error.downcast_ref() == Some(&vk::ErrorCode::INCOMPATIBLE_DRIVER) => {
+                return Ok(None)
+            }
+            result => result?,
+        };
+
+        // adapters with a queue that can draw and dispatch
+        let mut adapters = vec![];
+        for physical in instance.enumerate_physical_devices()? {
+            if let Some(family) = queue_family(&instance, physical) {
+                let info = AdapterInfo::get(&instance, physical, adapters.len());
+                adapters.push((physical, family, info));
+            }
+        }
+
+        let infos = adapters
+            .iter()
+            .map(|(_, _, info)| info.clone())
+            .collect::<Vec<_>>();
+        let Some(index) = selection.choose(&infos) else {
+            if let Some(messenger) = messenger {
+                instance.destroy_debug_utils_messenger_ext(messenger, None);
+            }
+            instance.destroy_instance(None);
+            return Ok(None);
+        };
+        let (physical, family, adapter) = adapters.swap_remove(index);
+        info!("Selected headless physical device (`{}`).", adapter.name);
+
+        // refuse features the adapter lacks before anything else is created
+        let base = vk::PhysicalDeviceFeatures::builder()
+            .multi_viewport(adapter.limits.max_viewports > 1)
+            .build();
+        let enabled_features = match device_features(base, features, &adapter.features) {
+            Ok(enabled) => enabled,
+            Err(error) => {
+                if let Some(messenger) = messenger {
+                    instance.destroy_debug_utils_messenger_ext(messenger, None);
+                }
+                instance.destroy_instance(None);
+                return Err(error.into());
+            }
+        };
+
+        // create the logical device with its single queue
+        let layers = if messenger.is_some() {
+            vec![VALIDATION_LAYER.as_ptr()]
+        } else {
+            vec![]
+        };
+        let priorities = &[1.0];
+        let queue_info = vk::DeviceQueueCreateInfo::builder()
+            .queue_family_index(family)
+            .queue_priorities(priorities);
+        let queue_infos = &[queue_info];
+
+        // timelines fall back to fences without timeline semaphores
+        let available = instance
+            .enumerate_device_extension_properties(physical, None)?
+            .iter()
+            .map(|e| e.extension_name)
+            .collect::<HashSet<_>>();
+        let timeline_semaphores = timeline_semaphores_available(&instance, &available);
+        let multiview = multiview_available(&instance, &available);
+        let host_query_reset = host_query_reset_available(&instance, &available);
+        let mut extensions = vec![];
+        if timeline_semaphores {
+            extensions.push(vk::KHR_TIMELINE_SEMAPHORE_EXTENSION.name.as_ptr());
+        }
+        if multiview {
+            extensions.push(vk::KHR_MULTIVIEW_EXTENSION.name.as_ptr());
+        }
+        if host_query_reset {
+            extensions.push(vk::EXT_HOST_QUERY_RESET_EXTENSION.name.as_ptr());
+        }
+        let mut timeline_features =
+            vk::PhysicalDeviceTimelineSemaphoreFeatures::builder().timeline_semaphore(true);
+        let mut multiview_features = vk::PhysicalDeviceMultiviewFeatures::builder().multiview(true);
+        let mut host_query_reset_features =
+            vk::PhysicalDeviceHostQueryResetFeatures::builder().host_query_reset(true);
+
+        let mut info = vk::DeviceCreateInfo::builder()
+            .queue_create_infos(queue_infos)
+            .enabled_layer_names(&layers)
+            .enabled_extension_names(&extensions)
+            .enabled_features(&enabled_features);
+        if timeline_semaphores {
+            info = info.push_next(&mut timeline_features);
+        }
+        if multiview {
+            info = info.push_next(&mut multiview_features);
+        }
+        if host_query_reset {
+            info = info.push_next(&mut host_query_reset_features);
+        }
+        let device = instance.create_device(physical, &info, None)?;
+        allocation::register(device.handle());
+        let queue = device.get_device_queue(family, 0);
+
+        // command buffers are one time only
+        let pool = CommandPool::create(&device, family, vk::CommandPoolCreateFlags::TRANSIENT)?;
+
+        Ok(Some(Self {
+            entry,
+            instance,
+            messenger,
+            names,
+            physical,
+            adapter,
+            features: features.iter().copied().collect(),
+            device,
+            family,
+            queue,
+            pool,
+            // anisotropic filtering isn't enabled
+            samplers: SamplerCache::new(1.0),
+            passes: RenderPassCache::new(multiview),
+            timeline_semaphores,
+            host_query_reset,
+            errors: validation_errors(),
+            null,
+            capture: Cell::new(None),
+        }))
+    }
+
+    pub fn device(&self) -> &vulkanalia::Device {
+        &self.device
+    }
+
+    pub fn instance(&self) -> &Instance {
+        &self.instance
+    }
+
+    pub fn physical(&self) -> vk::PhysicalDevice {
+        self.physical
+    }
+
+    pub fn adapter(&self) -> &AdapterInfo {
+        &self.adapter
+    }
+
+    /// the optional features the device was created with.
+    pub fn features(&self) -> &HashSet<Feature> {
+        &self.features
+    }
+
+    /// fail with `DescriptionError::FeatureNotEnabled` unless `feature` was enabled.
+    pub fn require_feature(&self, feature: Feature) -> Result<()> {
+        Ok(check_feature(feature, &self.features)?)
+    }
+
+    /// the queue family of the queue everything is submitted to.
+    pub fn queue_family(&self) -> u32 {
+        self.family
+    }
+
+    /// the queue `execute` submits to, for submissions of your own like `Timeline::submit`.
+    pub fn queue(&self) -> vk::Queue {
+        self.queue
+    }
+
+    /// whether timelines are backed by timeline semaphores, otherwise they use fences.
+    pub fn supports_timeline_semaphores(&self) -> bool {
+        self.timeline_semaphores
+    }
+
+    pub fn supports_multiview(&self) -> bool {
+        self.passes.multiview()
+    }
+
+    /// create a timeline, backed by a semaphore where the device supports it.
+    pub fn create_timeline(&self) -> Result<Timeline> {
+        unsafe { Timeline::create(&self.device, self.timeline_semaphores) }
+    }
+
+    /// whether query sets can be reset on the host with `reset_queries`.
+    pub fn supports_host_query_reset(&self) -> bool {
+        self.host_query_reset
+    }
+
+    /// create a set of `count` queries, timestamps need `Feature::TimestampQuery`.
+    pub fn create_query_set(&self, kind: QueryKind, count: u32) -> Result<QuerySet> {
+        create_query_set(&self.device, &self.features, kind, count)
+    }
+
+    /// reset `queries` of `set` on the host, none of them may be in use by the device.
+    pub fn reset_queries(&self, set: &QuerySet, queries: std::ops::Range<u32>) -> Result<()> {
+        if !self.host_query_reset {
+            return Err(anyhow!("The device can't reset queries on the host."));
+        }
+        unsafe { set.reset(&self.device, queries) }
+    }
+
+    /// whether the validation layer checks the calls, see `create_with_validation`.
+    pub fn is_validated(&self) -> bool {
+        self.messenger.is_some() || self.null.is_some()
+    }
+
+    /// the number of validation errors reported since the device was created.
+    pub fn validation_errors(&self) -> usize {
+        let null = self.null.as_ref().map_or(0, |n| n.errors().len());
+        validation_errors() - self.errors + null
+    }
+
+    /// the shared sampler of `descriptor`, see `Device::sampler`. anisotropic filtering is
+    /// refused, the device is created without it.
+    pub fn sampler(&self, descriptor: &SamplerDescriptor) -> Result<vk::Sampler> {
+        unsafe { self.samplers.get(&self.device, descriptor) }
+    }
+
+    /// set viewports and their scissors from index `first` on, see `Device::set_viewports`.
+    pub fn set_viewports(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        first: u32,
+        viewports: &[(vk::Viewport, vk::Rect2D)],
+    ) -> Result<()> {
+        let limit = self.adapter.limits.max_viewports;
+        unsafe { set_viewports(&self.device, command_buffer, first, viewports, limit) }
+    }
+
+    /// the shared render pass of `descriptor`, see `Device::render_pass_for`.
+    pub fn render_pass_for(&self, descriptor: &RenderPassDescriptor) -> Result<vk::RenderPass> {
+        unsafe { self.passes.render_pass(&self.device, descriptor) }
+    }
+
+    /// begin a pass drawing into the views of `descriptor`, see `Device::begin_render_pass`.
+    pub fn begin_render_pass(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        descriptor: &RenderPassDescriptor,
+    ) -> Result<vk::RenderPass> {
+        let contents = vk::SubpassContents::INLINE;
+        unsafe {
+            self.passes
+                .begin(&self.device, command_buffer, descriptor, contents)
+        }
+    }
+
+    /// begin a pass whose first subpass executes secondary command buffers, see
+    /// `Device::begin_secondary_render_pass`.
+    pub fn begin_secondary_render_pass(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        descriptor: &RenderPassDescriptor,
+    ) -> Result<PassInheritance> {
+        let contents = vk::SubpassContents::SECONDARY_COMMAND_BUFFERS;
+        unsafe {
+            let render_pass =
+                self.passes
+                    .begin(&self.device, command_buffer, descriptor, contents)?;
+            Ok(PassInheritance {
+                render_pass,
+                subpass: 0,
+                framebuffer: self.passes.framebuffer(&self.device, descriptor)?,
+            })
+        }
+    }
+
+    /// the render passes and framebuffers created for descriptors.
+    pub fn render_pass_counts(&self) -> (usize, usize) {
+        self.passes.counts()
+    }
+
+    pub fn release_view(&self, view: vk::ImageView) {
+        unsafe { self.passes.release_view(&self.device, view) }
+    }
+
+    /// create a buffer with the memory properties `buffer_memory_properties` picks for usage.
+    pub fn create_buffer_for(
+        &self,
+        size: vk::DeviceSize,
+        usage: vk::BufferUsageFlags,
+    ) -> Result<Buffer> {
+        self.create_buffer(size, usage, buffer_memory_properties(usage))
+    }
+
+    /// create a buffer backed by its own memory allocation.
+    pub fn create_buffer(
+        &self,
+        size: vk::DeviceSize,
+        usage: vk::BufferUsageFlags,
+        properties: vk::MemoryPropertyFlags,
+    ) -> Result<Buffer> {
+        unsafe {
+            create_buffer(
+                &self.instance,
+                &self.physical,
+                &self.device,
+                size,
+                usage,
+                properties,
+                &[],
+            )
+        }
+    }
+
+    /// create a single sampled device local texture with optimal tiling.
+    pub fn create_texture(
+        &self,
+        width: u32,
+        height: u32,
+        mip_levels: u32,
+        format: vk::Format,
+        usage: vk::ImageUsageFlags,
+    ) -> Result<Texture> {
+        check_format_feature(format, &self.features)?;
+        unsafe {
+            create_texture(
+                &self.instance,
+                &self.physical,
+                &self.device,
+                width,
+                height,
+                mip_levels,
+                vk::SampleCountFlags::_1,
+                format,
+                vk::ImageTiling::OPTIMAL,
+                usage,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+                &[],
+            )
+        }
+    }
+
+    /// create a texture with `layers` layers, see `Device::create_array_texture`.
+    pub fn create_array_texture(
+        &self,
+        width: u32,
+        height: u32,
+        layers: u32,
+        format: vk::Format,
+        usage: vk::ImageUsageFlags,
+    ) -> Result<Texture> {
+        check_format_feature(format, &self.features)?;
+        unsafe {
+            create_layered_texture(
+                &self.instance,
+                &self.physical,
+                &self.device,
+                width,
+                height,
+                1,
+                layers,
+                vk::ImageCreateFlags::empty(),
+                vk::SampleCountFlags::_1,
+                format,
+                vk::ImageTiling::OPTIMAL,
+                usage,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+                &[],
+            )
+        }
+    }
+
+    /// upload rgba8 pixels into a sampled and mipmapped texture, `import` decides the format.
+    pub fn upload_texture(
+        &self,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        import: TextureImport,
+    ) -> Result<(Texture, TextureView)> {
+        unsafe {
+            upload_texture(
+                &self.instance,
+                &self.physical,
+                &self.device,
+                &self.pool,
+                self.queue,
+                width,
+                height,
+                pixels,
+                import,
+            )
+        }
+    }
+
+    /// copy a texture back to the cpu, see `Device::read_pixels`.
+    pub fn read_pixels(
+        &self,
+        texture: &Texture,
+        width: u32,
+        height: u32,
+        layout: vk::ImageLayout,
+    ) -> Result<TexturePixels> {
+        unsafe {
+            read_texture(
+                &self.instance,
+                &self.physical,
+                &self.device,
+                &self.pool,
+                self.queue,
+                texture,
+                width,
+                height,
+                layout,
+            )
+        }
+    }
+
+    /// record commands into a one time command buffer, submit it and wait for it to finish.
+    pub fn execute<F>(&self, record: F) -> Result<()>
+    where
+        F: FnOnce(&vulkanalia::Device, vk::CommandBuffer) -> Result<()>,
+    {
+        let _span = debug_span!("command buffer", kind = "one time").entered();
+        let _capture = CheckedCapture::begin();
+        unsafe {
+            let command_buffer = self.pool.begin_single(&self.device)?;
+            if let Err(error) = record(&self.device, command_buffer.buffer) {
+                self.pool.discard_single(&self.device, command_buffer);
+                return Err(error);
+            }
+            self.pool
+                .end_single(&self.device, self.queue, command_buffer)
+        }
+    }
+
+    /// name `handle` for RenderDoc and the validation messages, see `Device::set_name`.
+    pub fn set_name<H>(&self, handle: H, name: &str)
+    where
+        H: vk::Handle,
+        u64: TryFrom<H::Repr>,
+    {
+        unsafe {
+            self.names
+                .set_name(&self.instance, self.device.handle(), handle, name)
+        }
+        allocation::set_name(self.device.handle(), handle, name);
+    }
+
+    /// call `hooks` with every allocation and free, see `Device::set_allocation_hooks`.
+    pub fn set_allocation_hooks(&self, hooks: Option<Box<dyn AllocationHooks>>) {
+        allocation::set_hooks(self.device.handle(), hooks);
+    }
+
+    /// the device memory allocated and not freed yet.
+    pub fn allocation_stats(&self) -> AllocationStats {
+        allocation::stats(self.device.handle())
+    }
+
+    /// the allocations alive, largest first.
+    pub fn live_allocations(&self) -> Vec<Allocation> {
+        allocation::live(self.device.handle())
+    }
+
+    /// begin a labeled region of `command_buffer`, see `Device::begin_label`.
+    pub fn begin_label(&self, command_buffer: vk::CommandBuffer, name: &str, color: [f32; 4]) {
+        unsafe {
+            self.names
+                .begin_label(&self.instance, command_buffer, name, color)
+        }
+    }
+
+    /// end the innermost region begun by `begin_label`.
+    pub fn end_label(&self, command_buffer: vk::CommandBuffer) {
+        unsafe { self.names.end_label(&self.instance, command_buffer) }
+    }
+
+    /// start capturing the command buffers submitted from now on into a `FrameTrace`. on a
+    /// null device the trace is what its backend records, otherwise the capture is process
+    /// wide like `Device::start_capture`.
+    pub fn start_capture(&self) {
+        match &self.null {
+            Some(backend) => self.capture.set(Some(backend.submissions().len())),
+            None => capture::start(),
+        }
+    }
+
+    /// stop the capture and return the submissions it recorded.
+    pub fn stop_capture(&self) -> FrameTrace {
+        match &self.null {
+            Some(backend) => {
+                let start = self.capture.take().unwrap_or(usize::MAX);
+                let submissions = backend.submissions().into_iter().skip(start).collect();
+                FrameTrace { submissions }
+            }
+            None => capture::stop(),
+        }
+    }
+
+    /// run the commands of `trace` on the queue, see `FrameTrace::replay`.
+    pub fn replay(&self, trace: &FrameTrace) -> Result<()> {
+        unsafe { trace.replay(&self.device, self.queue, &self.pool) }
+    }
+
+    pub fn wait_idle(&self) -> Result<()> {
+        unsafe { self.device.device_wait_idle()? };
+        Ok(())
+    }
+
+    pub fn destroy(&self) {
+        unsafe {
+            self.device.device_wait_idle().ok();
+            self.pool.destroy(&self.device);
+            self.samplers.destroy(&self.device);
+            self.passes.destroy(&self.device);
+            for leak in allocation::unregister(self.device.handle()) {
+                warn!(?leak, "device memory was never freed");
+            }
+            self.device.destroy_device(None);
+            if let Some(messenger) = self.messenger {
+                self.instance
+                    .destroy_debug_utils_messenger_ext(messenger, None);
+            }
+            self.instance.destroy_instance(None);
+        }
+    }
+}
+
+// an instance without surface extensions, validated when asked for and the layer exists
+unsafe fn create_instance(
+    entry: &Entry,
+    validation: ValidationSettings,
+) -> Result<(Instance, Option<vk::DebugUtilsMessengerEXT>, DebugNames)> {
+    let application_info = vk::ApplicationInfo::builder()
+        .application_name(b"deimos headless\0")
+        .application_version(vk::make_version(1, 0, 0))
+        .engine_name(b"No Engine\0")
+        .engine_version(vk::make_version(1, 0, 0))
+        .api_version(vk::make_version(1, 0, 0));
+
+    let available_layers = entry
+        .enumerate_instance_layer_properties()?
+        .iter()
+        .map(|l| l.layer_name)
+        .collect::<HashSet<_>>();
+    let validated = validation.enabled && available_layers.contains(&VALIDATION_LAYER);
+
+    let layers = if validated {
+        vec![VALIDATION_LAYER.as_ptr()]
+    } else {
+        vec![]
+    };
+    let debug_utils = validated || debug_utils_available(entry)?;
+    let mut extensions = if debug_utils {
+        vec![vk::EXT_DEBUG_UTILS_EXTENSION.name.as_ptr()]
+    } else {
+        vec![]
+    };
+
+    // timeline semaphores need it on a vulkan 1.0 instance
+    if properties2_available(entry)? {
+        extensions.push(
+            vk::KHR_GET_PHYSICAL_DEVICE_PROPERTIES2_EXTENSION
+                .name
+                .as_ptr(),
+        );
+    }
+
+    let mut debug_info = vk::DebugUtilsMessengerCreateInfoEXT::builder()
+        .message_severity(validation.severity.flags())
+        .message_type(vk::DebugUtilsMessageTypeFlagsEXT::all())
+        .user_callback(Some(debug_callback));
+
+    let mut info = vk::InstanceCreateInfo::builder()
+        .application_info(&application_info)
+        .enabled_layer_names(&layers)
+        .enabled_extension_names(&extensions);
+    if validated {
+        info = info.push_next(&mut debug_info);
+    }
+
+    let instance = entry.create_instance(&info, None)?;
+    let messenger = if validated {
+        Some(instance.create_debug_utils_messenger_ext(&debug_info, None)?)
+    } else {
+        None
+    };
+    Ok((instance, messenger, DebugNames::new(debug_utils)))
+}
+
+// the first queue family that supports graphics and compute
+unsafe fn queue_family(instance: &Instance, physical: vk::PhysicalDevice) -> Option<u32> {
+    let required = vk::QueueFlags::GRAPHICS | vk::QueueFlags::COMPUTE;
+    instance
+        .get_physical_device_queue_family_properties(physical)
+        .iter()
+        .position(|p| p.queue_flags.contains(required))
+        .map(|i| i as u32)
+}
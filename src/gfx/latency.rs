@@ -0,0 +1,183 @@
+#![allow(dead_code)]
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// How the display time of a frame was found.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LatencySource {
+    /// reported by the presentation engine through `VK_GOOGLE_display_timing`.
+    Display,
+    /// the time the device saw the frame complete, without display timing. frames are shown
+    /// at the next refresh after that at the earliest.
+    Estimated,
+}
+
+/// The time from the start of a frame until it was displayed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FrameLatency {
+    /// the id the frame was presented with, counting up from 0.
+    pub id: u32,
+    pub latency: Duration,
+    /// the refreshes between this frame and the previous one that showed no new frame.
+    pub missed_vsyncs: u32,
+    pub source: LatencySource,
+}
+
+/// The latencies of the recent frames, see `Device::latency_stats`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LatencyStats {
+    /// the frames displayed since tracking started.
+    pub frames: u64,
+    pub last: Option<FrameLatency>,
+    /// the average and the maximum of the recent frames.
+    pub average: Duration,
+    pub max: Duration,
+    /// the refreshes without a new frame since tracking started.
+    pub missed_vsyncs: u64,
+    /// the time between refreshes of the display, when known.
+    pub refresh: Option<Duration>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct PendingFrame {
+    id: u32,
+    begun: Duration,
+    presented: Option<Duration>,
+}
+
+/// Matches the frames a device begins and presents with the times they were displayed.
+/// times are durations since a start the caller picks, display timing reports times of the
+/// presentation engine's clock, which is aligned to the caller's by the earliest time a
+/// frame could have been displayed, never before it was presented.
+#[derive(Clone, Debug)]
+pub struct LatencyTracker {
+    history: usize,
+    refresh: Option<Duration>,
+    // begun and not displayed yet, oldest first
+    pending: VecDeque<PendingFrame>,
+    // the latest displayed frames, oldest first
+    recent: VecDeque<FrameLatency>,
+    frames: u64,
+    missed_vsyncs: u64,
+    // when the previous frame was displayed, none after a pause
+    last_display: Option<Duration>,
+    // the display clock minus the caller's clock in nanoseconds, the smallest seen
+    offset: Option<i128>,
+}
+
+impl LatencyTracker {
+    /// keep the latencies of the last `history` frames for the stats.
+    pub fn new(history: usize) -> Self {
+        Self {
+            history: history.max(1),
+            refresh: None,
+            pending: VecDeque::new(),
+            recent: VecDeque::new(),
+            frames: 0,
+            missed_vsyncs: 0,
+            last_display: None,
+            offset: None,
+        }
+    }
+
+    /// the display refreshes every `refresh`, needed to count missed vsyncs.
+    pub fn set_refresh(&mut self, refresh: Option<Duration>) {
+        self.refresh = refresh.filter(|r| !r.is_zero());
+    }
+
+    pub fn refresh(&self) -> Option<Duration> {
+        self.refresh
+    }
+
+    /// frame `id` started at `at`.
+    pub fn begin(&mut self, id: u32, at: Duration) {
+        // a frame that never got presented is begun again under its id
+        self.pending.retain(|f| f.id != id);
+        self.pending.push_back(PendingFrame {
+            id,
+            begun: at,
+            presented: None,
+        });
+
+        // frames the engine never reports about don't pile up
+        while self.pending.len() > 4 * self.history {
+            self.pending.pop_front();
+        }
+    }
+
+    /// frame `id` was handed to the presentation engine at `at`.
+    pub fn presented(&mut self, id: u32, at: Duration) {
+        if let Some(frame) = self.pending.iter_mut().find(|f| f.id == id) {
+            frame.presented = Some(at);
+        }
+    }
+
+    /// the device saw frame `id` complete at `at`, the estimate without display timing.
+    pub fn completed(&mut self, id: u32, at: Duration) -> Option<FrameLatency> {
+        self.record(id, at, LatencySource::Estimated)
+    }
+
+    /// the presentation engine displayed frame `id` at `actual` and could have at
+    /// `earliest` at the soonest, both in nanoseconds of its clock.
+    pub fn displayed(&mut self, id: u32, actual: u64, earliest: u64) -> Option<FrameLatency> {
+        let presented = self.pending.iter().find(|f| f.id == id)?.presented?;
+        let offset = earliest as i128 - presented.as_nanos() as i128;
+        let offset = self.offset.map_or(offset, |o| o.min(offset));
+        self.offset = Some(offset);
+        let at = (actual as i128 - offset).max(0) as u64;
+        self.record(id, Duration::from_nanos(at), LatencySource::Display)
+    }
+
+    /// no frames are displayed for a while, e.g. while the window is minimized. the gap
+    /// doesn't count as missed vsyncs.
+    pub fn pause(&mut self) {
+        self.last_display = None;
+        self.pending.clear();
+    }
+
+    pub fn stats(&self) -> LatencyStats {
+        let latencies = self.recent.iter().map(|f| f.latency);
+        LatencyStats {
+            frames: self.frames,
+            last: self.recent.back().copied(),
+            average: match self.recent.len() {
+                0 => Duration::ZERO,
+                n => latencies.clone().sum::<Duration>() / n as u32,
+            },
+            max: latencies.max().unwrap_or_default(),
+            missed_vsyncs: self.missed_vsyncs,
+            refresh: self.refresh,
+        }
+    }
+
+    fn record(&mut self, id: u32, at: Duration, source: LatencySource) -> Option<FrameLatency> {
+        // older frames weren't reported and won't be anymore
+        let position = self.pending.iter().position(|f| f.id == id)?;
+        let frame = self.pending.drain(..=position).next_back()?;
+
+        let missed_vsyncs = match (self.last_display, self.refresh) {
+            (Some(last), Some(refresh)) => {
+                let cycles =
+                    (at.saturating_sub(last).as_secs_f64() / refresh.as_secs_f64()).round() as u32;
+                cycles.saturating_sub(1)
+            }
+            _ => 0,
+        };
+        self.last_display = Some(at);
+
+        let latency = FrameLatency {
+            id,
+            latency: at.saturating_sub(frame.begun),
+            missed_vsyncs,
+            source,
+        };
+        self.frames += 1;
+        self.missed_vsyncs += missed_vsyncs as u64;
+        self.recent.push_back(latency);
+        if self.recent.len() > self.history {
+            self.recent.pop_front();
+        }
+        Some(latency)
+    }
+}
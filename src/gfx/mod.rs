@@ -1,11 +1,134 @@
+mod adapter;
+mod allocation;
+mod buffer;
+mod capture;
+mod color;
+mod command;
+mod deletion;
+mod descriptor;
 mod device;
+mod encoder;
 mod entities;
+mod events;
+mod export;
+mod features;
 mod frame;
+mod handle;
+mod headless;
+mod latency;
+mod names;
+mod null;
+mod null_driver;
+mod pass;
+mod pipeline;
+mod pod;
+mod query;
+mod queues;
+mod readback;
+mod recorded;
+mod registry;
+mod report;
+mod resize;
+mod sampler;
+mod settings;
+mod shader;
+mod staging;
+mod submit;
+mod surface;
 mod swapchain;
+mod target;
+mod task;
 mod texture;
+mod timeline;
+mod trace;
+mod uniform;
+mod validate;
+mod vertex;
 
-pub use self::device::*;
-pub use self::entities::*;
-pub use self::frame::*;
-pub use self::swapchain::*;
-pub use self::texture::*;
\ No newline at end of file
+pub use self::adapter::{
+    AdapterInfo, AdapterLimits, AdapterSelection, AdapterType, PowerPreference,
+};
+pub use self::allocation::{Allocation, AllocationHooks, AllocationKind, AllocationStats};
+pub use self::buffer::{
+    buffer_barrier, buffer_memory_properties, Buffer, BufferAccess, IndexBinding, MappedBuffer,
+};
+pub use self::color::Color;
+pub use self::command::{CommandBuffer, CommandPool, PassInheritance, SecondaryPool};
+pub use self::deletion::{DeletionQueue, ResourceUsage, Retired};
+pub use self::descriptor::{slot_set_layouts, BindingResource, DescriptorSets, DescriptorSlot};
+pub use self::device::{enumerate_adapters, Device};
+pub use self::encoder::{CommandError, Encoder};
+pub use self::entities::{SurfaceVertex, Vertex};
+pub use self::events::{DeviceEvent, DeviceEventKind, DeviceEvents, ObserverId};
+#[cfg(feature = "ffmpeg")]
+pub use self::export::FfmpegSink;
+pub use self::export::{ExportedFrame, FrameClock, FrameExportSettings, FrameSink};
+pub use self::features::Feature;
+pub use self::frame::{Frame, FrameBuffer, PostFrame};
+pub use self::handle::{Handle, HandlePool};
+pub use self::headless::HeadlessDevice;
+pub use self::latency::{FrameLatency, LatencySource, LatencyStats, LatencyTracker};
+pub use self::pass::{
+    set_viewports, ColorAttachment, DepthAttachment, DepthStencilClear, LoadOp, PassLoad,
+    RenderPassDescriptor, Subpass,
+};
+pub use self::pipeline::{Blend, PipelineTarget, RenderPipelineBuilder};
+pub use self::pod::{pod_bytes, Pod};
+pub use self::query::{QueryKind, QueryResolve, QuerySet};
+pub use self::queues::{dedicated_queue_families, shared_queue_families, Queue, QueueKind};
+pub use self::readback::TexturePixels;
+pub use self::registry::{
+    BufferId, Registered, ResourceCounts, ResourceRegistry, ShaderId, TextureId, TextureViewId,
+};
+pub use self::report::BugReportSettings;
+pub use self::resize::{
+    clip_rects, logical_extent, render_extent, scale_rect, scissored_clears, SwapchainRebuild,
+    SwapchainShape,
+};
+pub use self::sampler::SamplerDescriptor;
+pub use self::settings::{
+    shrink_pixels, GraphicsSettings, ValidationSettings, ValidationSeverity, VALIDATION_VARIABLE,
+};
+pub use self::shader::Shader;
+pub use self::staging::StagingBelt;
+pub use self::submit::{SubmitGraph, SubmitId};
+pub use self::surface::{DeviceWindow, HeadlessWindow};
+pub use self::swapchain::{present_modes, PresentMode, SurfaceFormat};
+pub use self::target::SceneAttachments;
+pub use self::task::BackgroundTask;
+pub use self::texture::{AlphaMode, ColorSpace, Texture, TextureImport, TextureView};
+pub use self::timeline::{GpuFuture, Timeline, TimelineWait, WorkDone};
+pub use self::uniform::{member, BlockLayout, Uniform};
+pub use self::validate::{
+    check_bindings, check_buffer, check_pipeline_layout, check_pixels, check_render_pass,
+    check_texture, max_mip_levels, DescriptionError,
+};
+pub use self::vertex::{vertex_input_descriptions, VertexAttribute, VertexBufferLayout};
+
+// the null driver and command captures, for tests of the crate and its users
+#[doc(hidden)]
+pub use self::null::NullBackend;
+#[doc(hidden)]
+pub use self::trace::{
+    FrameTrace, TraceBufferBarrier, TraceCommand, TraceImageBarrier, TraceSubmission,
+};
+
+pub(crate) use self::color::{linear_to_srgb, srgb_to_linear};
+pub(crate) use self::descriptor::{FRAME_SET, PASS_SET};
+pub(crate) use self::device::SCENE_FORMAT;
+pub(crate) use self::entities::{QueueFamilyIndices, SuitabilityError};
+pub(crate) use self::features::{check_feature, check_format_feature, device_features};
+pub(crate) use self::pass::RenderPassCache;
+pub(crate) use self::queues::sharing_mode;
+pub(crate) use self::readback::record_texture_readback;
+pub(crate) use self::report::{
+    record_validation_message, validation_errors, write_bug_report, ReportFrame,
+};
+pub(crate) use self::resize::{surface_extent, ResizeTracker};
+pub(crate) use self::sampler::SamplerCache;
+pub(crate) use self::swapchain::SwapChainSupport;
+pub(crate) use self::texture::{premultiply, read_png};
+pub(crate) use self::validate::{
+    check_draw_indexed, check_render_pipeline, check_sampler, check_vertex_layouts,
+    check_viewports, check_writes,
+};
@@ -0,0 +1,78 @@
+#![allow(dead_code)]
+
+use std::ffi::CString;
+
+use vulkanalia::prelude::v1_0::*;
+use vulkanalia::vk::ExtDebugUtilsExtension;
+
+/// Object names and labeled command buffer regions of `VK_EXT_debug_utils`, RenderDoc and
+/// the validation messages show them in place of bare handles. every call does nothing
+/// when the instance was created without the extension.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct DebugNames {
+    enabled: bool,
+}
+
+impl DebugNames {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub unsafe fn set_name<H>(&self, instance: &Instance, device: vk::Device, handle: H, name: &str)
+    where
+        H: Handle,
+        u64: TryFrom<H::Repr>,
+    {
+        if !self.enabled || handle.is_null() {
+            return;
+        }
+        let name = c_string(name);
+        let info = vk::DebugUtilsObjectNameInfoEXT::builder()
+            .object_type(H::TYPE)
+            .object_handle(raw_handle(handle))
+            .object_name(name.as_bytes_with_nul());
+        // a name is only a hint for tools, failing to set it is not worth an error
+        instance.set_debug_utils_object_name_ext(device, &info).ok();
+    }
+
+    pub unsafe fn begin_label(
+        &self,
+        instance: &Instance,
+        command_buffer: vk::CommandBuffer,
+        name: &str,
+        color: [f32; 4],
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let name = c_string(name);
+        let label = vk::DebugUtilsLabelEXT::builder()
+            .label_name(name.as_bytes_with_nul())
+            .color(color);
+        instance.cmd_begin_debug_utils_label_ext(command_buffer, &label);
+    }
+
+    pub unsafe fn end_label(&self, instance: &Instance, command_buffer: vk::CommandBuffer) {
+        if self.enabled {
+            instance.cmd_end_debug_utils_label_ext(command_buffer);
+        }
+    }
+}
+
+/// the value of `handle` the way debug utils pass handles of every type.
+pub(crate) fn raw_handle<H>(handle: H) -> u64
+where
+    H: Handle,
+    u64: TryFrom<H::Repr>,
+{
+    u64::try_from(handle.as_raw()).unwrap_or_default()
+}
+
+// names are free text, a nul would cut them short
+fn c_string(name: &str) -> CString {
+    CString::new(name.replace('\0', " ")).unwrap_or_default()
+}
@@ -0,0 +1,175 @@
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::error;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use vulkanalia::loader::Loader;
+use vulkanalia::prelude::v1_0::*;
+
+use super::names::raw_handle;
+use super::null_driver::{self, NullState};
+use super::{TraceCommand, TraceSubmission};
+
+/// A vulkan driver without a gpu, for running renderer code on ci. it validates the calls
+/// it gets, records command buffers into `TraceCommand`s and completes every submission right
+/// away. buffer copies and fills run on submit so buffer readbacks work, images have no
+/// contents. pass it to `HeadlessDevice::create_null` or `Device::create_null`, clones share
+/// the recordings.
+#[derive(Clone)]
+pub struct NullBackend {
+    state: Arc<Mutex<NullState>>,
+}
+
+impl NullBackend {
+    /// a null driver with `VK_KHR_timeline_semaphore`.
+    pub fn new() -> Self {
+        Self::with_state(NullState::new(true))
+    }
+
+    /// a null driver without timeline semaphores, timelines fall back to fences on it.
+    pub fn without_timeline_semaphores() -> Self {
+        Self::with_state(NullState::new(false))
+    }
+
+    fn with_state(state: NullState) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// everything submitted so far in submission order.
+    pub fn submissions(&self) -> Vec<TraceSubmission> {
+        self.lock().submissions.clone()
+    }
+
+    /// the commands of all submissions so far in the order they ran.
+    pub fn commands(&self) -> Vec<TraceCommand> {
+        self.lock()
+            .submissions
+            .iter()
+            .flat_map(|s| s.commands.iter().cloned())
+            .collect()
+    }
+
+    /// the calls the driver refused or found invalid, in the order they were made.
+    pub fn errors(&self) -> Vec<String> {
+        self.lock().errors.clone()
+    }
+
+    /// forget the submissions and errors so far, e.g. between the setup and the frame a
+    /// test checks.
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.submissions.clear();
+        state.errors.clear();
+    }
+
+    /// the number of objects of `kind` that were created and not destroyed yet.
+    pub fn live_objects(&self, kind: vk::ObjectType) -> usize {
+        self.lock().live_objects(kind)
+    }
+
+    /// the bytes the buffer range written to the first element of `binding` of `set` holds
+    /// now, e.g. the uniforms a submitted frame read. none when no buffer was written there or
+    /// the set is gone.
+    pub fn descriptor_bytes(&self, set: vk::DescriptorSet, binding: u32) -> Option<Vec<u8>> {
+        self.lock().descriptor_bytes(set, binding)
+    }
+
+    /// the name `vkSetDebugUtilsObjectNameEXT` gave the live object `handle`.
+    pub fn object_name<H>(&self, handle: H) -> Option<String>
+    where
+        H: Handle,
+        u64: TryFrom<H::Repr>,
+    {
+        self.lock().object_name(raw_handle(handle))
+    }
+
+    /// run `f` with vkCreateInstance creating instances of this driver on the thread, the
+    /// entry points are plain functions and have no other way to tell the drivers apart.
+    pub(super) fn enter<T>(&self, f: impl FnOnce() -> T) -> T {
+        CREATING.with(|c| *c.borrow_mut() = Some(self.state.clone()));
+        let result = f();
+        CREATING.with(|c| *c.borrow_mut() = None);
+        result
+    }
+
+    fn lock(&self) -> MutexGuard<'_, NullState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for NullBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Loader for NullBackend {
+    unsafe fn load(
+        &self,
+        name: &[u8],
+    ) -> Result<extern "system" fn(), Box<dyn error::Error + Send + Sync + 'static>> {
+        // every other command is looked up through these two
+        match null_driver::proc_addr(name) {
+            Some(function) => Ok(std::mem::transmute::<
+                unsafe extern "system" fn(),
+                extern "system" fn(),
+            >(function)),
+            None => Err(format!(
+                "The null driver has no `{}`.",
+                String::from_utf8_lossy(name)
+            )
+            .into()),
+        }
+    }
+}
+
+thread_local! {
+    // the driver vkCreateInstance creates instances of, see `NullBackend::enter`
+    static CREATING: RefCell<Option<Arc<Mutex<NullState>>>> = const { RefCell::new(None) };
+}
+
+// The drivers of the dispatchable handles, instances, physical devices, devices, queues and
+// command buffers, every call carries one of them.
+static DRIVERS: Mutex<BTreeMap<usize, Arc<Mutex<NullState>>>> = Mutex::new(BTreeMap::new());
+
+pub(super) fn creating() -> Option<Arc<Mutex<NullState>>> {
+    CREATING.with(|c| c.borrow().clone())
+}
+
+pub(super) fn register(handle: usize, state: &Arc<Mutex<NullState>>) {
+    drivers().insert(handle, state.clone());
+}
+
+pub(super) fn unregister(handles: &[usize]) {
+    let mut drivers = drivers();
+    handles.iter().for_each(|h| {
+        drivers.remove(h);
+    });
+}
+
+pub(super) fn driver(handle: usize) -> Arc<Mutex<NullState>> {
+    match drivers().get(&handle) {
+        Some(state) => state.clone(),
+        // a real driver would crash here
+        None => panic!("The null driver never created handle {:#x}.", handle),
+    }
+}
+
+fn drivers() -> MutexGuard<'static, BTreeMap<usize, Arc<Mutex<NullState>>>> {
+    DRIVERS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// group leaked objects by kind for the messages
+pub(super) fn count_kinds(
+    kinds: impl Iterator<Item = vk::ObjectType>,
+) -> Vec<(vk::ObjectType, usize)> {
+    let mut counts = HashMap::new();
+    kinds.for_each(|k| *counts.entry(k).or_insert(0) += 1);
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    counts.sort_by_key(|(k, _)| k.as_raw());
+    counts
+}
@@ -0,0 +1,3617 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::ffi::{c_char, c_void, CStr};
+use std::mem::size_of;
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tracing::error;
+use vulkanalia::prelude::v1_0::*;
+
+use super::null::{count_kinds, creating, driver, register, unregister};
+use super::recorded::recorded;
+use super::trace::items;
+use super::{max_mip_levels, TraceBufferBarrier, TraceCommand, TraceImageBarrier, TraceSubmission};
+
+// The handles of all null drivers count up from here, a handle is never reused so a stale
+// one is always caught.
+static HANDLES: AtomicU64 = AtomicU64::new(0x1000);
+
+// The largest allocation the null driver backs with host memory.
+const MAX_ALLOCATION: u64 = 1 << 30;
+// The alignment and size granularity of all memory requirements.
+const ALIGNMENT: u64 = 256;
+const MAX_PUSH_CONSTANTS: u32 = 128;
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+const WHOLE_SIZE: u64 = vk::WHOLE_SIZE as u64;
+
+/// The objects and recordings of one null driver.
+pub(crate) struct NullState {
+    timeline_semaphores: bool,
+    objects: HashMap<u64, Object>,
+    // the clock timestamps are written from, a tick per timestamp
+    ticks: u64,
+    pub(super) submissions: Vec<TraceSubmission>,
+    pub(super) errors: Vec<String>,
+}
+
+struct Object {
+    ty: vk::ObjectType,
+    // the device or instance the object was created from
+    owner: u64,
+    kind: Kind,
+    // set by vkSetDebugUtilsObjectNameEXT
+    name: Option<String>,
+}
+
+enum Kind {
+    Plain,
+    Device {
+        timeline_semaphores: bool,
+        // viewports past the first need the multiViewport feature
+        multi_viewport: bool,
+        // render passes with view masks need VK_KHR_multiview
+        multiview: bool,
+        // query pools reset on the host need VK_EXT_host_query_reset
+        host_query_reset: bool,
+        queue: u64,
+    },
+    Buffer {
+        size: u64,
+        usage: vk::BufferUsageFlags,
+        // the memory and the offset into it
+        memory: Option<(u64, u64)>,
+    },
+    Image {
+        extent: vk::Extent3D,
+        format: vk::Format,
+        mip_levels: u32,
+        layers: u32,
+        usage: vk::ImageUsageFlags,
+        bound: bool,
+    },
+    ImageView {
+        image: u64,
+    },
+    Memory {
+        data: Box<[u8]>,
+        host_visible: bool,
+        mapped: bool,
+    },
+    CommandPool {
+        resettable: bool,
+    },
+    CommandBuffer(Box<Recorder>),
+    Fence {
+        signaled: bool,
+    },
+    // binary semaphores are signaled at a value of 1
+    Semaphore {
+        timeline: bool,
+        value: u64,
+    },
+    DescriptorPool {
+        max_sets: u32,
+        allocated: u32,
+        free: bool,
+    },
+    DescriptorSet {
+        pool: u64,
+        // the buffer, offset and range written to the first element of buffer bindings
+        buffers: HashMap<u32, (vk::Buffer, u64, u64)>,
+    },
+    PipelineLayout {
+        sets: u32,
+        push_constants: Vec<vk::PushConstantRange>,
+    },
+    RenderPass {
+        attachments: u32,
+        // the number of clear values a begin needs
+        clears: u32,
+        subpasses: u32,
+    },
+    Pipeline {
+        bind_point: vk::PipelineBindPoint,
+        // created with `ALLOW_DERIVATIVES`
+        derivatives: bool,
+    },
+    QueryPool {
+        ty: vk::QueryType,
+        queries: Vec<QueryState>,
+    },
+    // the images are owned by the swapchain and handed out in turn
+    Swapchain {
+        images: Vec<u64>,
+        next: usize,
+    },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum QueryState {
+    // created and never reset, queries can't be used before a reset
+    Undefined,
+    Reset,
+    Available(u64),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum RecordState {
+    Initial,
+    Recording,
+    Executable,
+    // a one time submit command buffer after its submission
+    Invalid,
+}
+
+struct Recorder {
+    pool: u64,
+    state: RecordState,
+    one_time: bool,
+    commands: Vec<TraceCommand>,
+    secondary: bool,
+    // a secondary command buffer begun to continue the render pass of its primary
+    continues: bool,
+    in_render_pass: bool,
+    // whether the running subpass records its draws inline or executes secondaries
+    inline: bool,
+    // the subpasses of the running render pass after the current one
+    subpasses_left: u32,
+    // the depth of the open debug label regions
+    labels: u32,
+    graphics: bool,
+    compute: bool,
+    // the number of indices past the offset of the bound index buffer
+    index_buffer: Option<u64>,
+}
+
+impl NullState {
+    pub(super) fn new(timeline_semaphores: bool) -> Self {
+        Self {
+            timeline_semaphores,
+            objects: HashMap::new(),
+            ticks: 0,
+            submissions: vec![],
+            errors: vec![],
+        }
+    }
+
+    pub(super) fn object_name(&self, handle: u64) -> Option<String> {
+        self.objects.get(&handle).and_then(|o| o.name.clone())
+    }
+
+    pub(super) fn live_objects(&self, ty: vk::ObjectType) -> usize {
+        self.objects.values().filter(|o| o.ty == ty).count()
+    }
+
+    fn error(&mut self, message: String) {
+        error!("(null driver) {}", message);
+        self.errors.push(message);
+    }
+
+    fn create(&mut self, owner: u64, ty: vk::ObjectType, kind: Kind) -> u64 {
+        let handle = HANDLES.fetch_add(1, Ordering::Relaxed);
+        self.objects.insert(
+            handle,
+            Object {
+                ty,
+                owner,
+                kind,
+                name: None,
+            },
+        );
+        handle
+    }
+
+    // whether `handle` is a live object of type `ty`, an error is recorded otherwise
+    fn check(&mut self, call: &str, handle: u64, ty: vk::ObjectType) -> bool {
+        let message = match self.objects.get(&handle) {
+            Some(o) if o.ty == ty => return true,
+            Some(o) => format!(
+                "{}: {:#x} is a {:?} and not a {:?}.",
+                call, handle, o.ty, ty
+            ),
+            None if handle == 0 => format!("{}: the {:?} is null.", call, ty),
+            None => format!(
+                "{}: {:?} {:#x} was destroyed or never created.",
+                call, ty, handle
+            ),
+        };
+        self.error(message);
+        false
+    }
+
+    fn get(&mut self, call: &str, handle: u64, ty: vk::ObjectType) -> Option<&mut Kind> {
+        if self.check(call, handle, ty) {
+            self.objects.get_mut(&handle).map(|o| &mut o.kind)
+        } else {
+            None
+        }
+    }
+
+    fn destroy(&mut self, call: &str, handle: u64, ty: vk::ObjectType) -> Option<Object> {
+        // destroying a null handle does nothing
+        if handle != 0 && self.check(call, handle, ty) {
+            self.objects.remove(&handle)
+        } else {
+            None
+        }
+    }
+
+    // remove the objects `owned` selects, returns their handles
+    fn remove(&mut self, owned: impl Fn(&Object) -> bool) -> Vec<u64> {
+        let handles = self
+            .objects
+            .iter()
+            .filter(|(_, o)| owned(o))
+            .map(|(h, _)| *h)
+            .collect::<Vec<_>>();
+        handles.iter().for_each(|h| {
+            self.objects.remove(h);
+        });
+        handles
+    }
+
+    // the size of a live buffer with memory bound that has `usage`
+    fn buffer(
+        &mut self,
+        call: &str,
+        buffer: vk::Buffer,
+        usage: vk::BufferUsageFlags,
+    ) -> Option<u64> {
+        let Some(Kind::Buffer {
+            size,
+            usage: has,
+            memory,
+        }) = self.get(call, buffer.as_raw(), vk::ObjectType::BUFFER)
+        else {
+            return None;
+        };
+        let (size, has, bound) = (*size, *has, memory.is_some());
+        if !has.contains(usage) {
+            let message = format!(
+                "{}: buffer {:#x} lacks the {:?} usage.",
+                call,
+                buffer.as_raw(),
+                usage
+            );
+            self.error(message);
+        }
+        if !bound {
+            let message = format!(
+                "{}: buffer {:#x} has no memory bound.",
+                call,
+                buffer.as_raw()
+            );
+            self.error(message);
+        }
+        Some(size)
+    }
+
+    // the mip levels of a live image with memory bound that has `usage`
+    fn image(&mut self, call: &str, image: vk::Image, usage: vk::ImageUsageFlags) -> Option<u32> {
+        let Some(Kind::Image {
+            mip_levels,
+            usage: has,
+            bound,
+            ..
+        }) = self.get(call, image.as_raw(), vk::ObjectType::IMAGE)
+        else {
+            return None;
+        };
+        let (mip_levels, has, bound) = (*mip_levels, *has, *bound);
+        if !has.contains(usage) {
+            let message = format!(
+                "{}: image {:#x} lacks the {:?} usage.",
+                call,
+                image.as_raw(),
+                usage
+            );
+            self.error(message);
+        }
+        if !bound {
+            let message = format!("{}: image {:#x} has no memory bound.", call, image.as_raw());
+            self.error(message);
+        }
+        Some(mip_levels)
+    }
+
+    fn bounds(&mut self, call: &str, what: &str, start: u64, len: u64, size: u64) {
+        if start.checked_add(len).is_none_or(|end| end > size) {
+            self.error(format!(
+                "{}: {} at {} of {} bytes is out of the {} bytes.",
+                call, what, start, len, size
+            ));
+        }
+    }
+
+    // the type of a live query pool that has queries `first..first + count`
+    fn queries(
+        &mut self,
+        call: &str,
+        pool: vk::QueryPool,
+        first: u32,
+        count: u32,
+    ) -> Option<vk::QueryType> {
+        let Some(Kind::QueryPool { ty, queries }) =
+            self.get(call, pool.as_raw(), vk::ObjectType::QUERY_POOL)
+        else {
+            return None;
+        };
+        let (ty, len) = (*ty, queries.len() as u64);
+        if count == 0 || first as u64 + count as u64 > len {
+            self.error(format!(
+                "{}: queries {}..{} are not in the {} of the pool.",
+                call,
+                first,
+                first as u64 + count as u64,
+                len
+            ));
+        }
+        Some(ty)
+    }
+
+    fn layout(&mut self, call: &str, layout: vk::ImageLayout, expected: vk::ImageLayout) {
+        if layout != expected && layout != vk::ImageLayout::GENERAL {
+            self.error(format!(
+                "{}: the image is in {:?} and not in {:?}.",
+                call, layout, expected
+            ));
+        }
+    }
+
+    fn level(&mut self, call: &str, level: u32, levels: Option<u32>) {
+        if levels.is_some_and(|levels| level >= levels) {
+            self.error(format!(
+                "{}: mip level {} is past the last of the image.",
+                call, level
+            ));
+        }
+    }
+
+    // the checks of the objects a command uses
+    fn check_command(&mut self, call: &str, command: &TraceCommand) {
+        match command {
+            TraceCommand::BeginRenderPass {
+                render_pass,
+                framebuffer,
+                clear_values,
+                ..
+            } => {
+                self.check(call, framebuffer.as_raw(), vk::ObjectType::FRAMEBUFFER);
+                if let Some(Kind::RenderPass { clears, .. }) =
+                    self.get(call, render_pass.as_raw(), vk::ObjectType::RENDER_PASS)
+                {
+                    let clears = *clears;
+                    if (clear_values.len() as u32) < clears {
+                        self.error(format!(
+                            "{}: {} clear values for a render pass that clears {} attachments.",
+                            call,
+                            clear_values.len(),
+                            clears
+                        ));
+                    }
+                }
+            }
+            TraceCommand::BindPipeline {
+                bind_point,
+                pipeline,
+            } => {
+                if let Some(Kind::Pipeline {
+                    bind_point: has, ..
+                }) = self.get(call, pipeline.as_raw(), vk::ObjectType::PIPELINE)
+                {
+                    if has != bind_point {
+                        let message =
+                            format!("{}: a {:?} pipeline bound to {:?}.", call, has, bind_point);
+                        self.error(message);
+                    }
+                }
+            }
+            TraceCommand::BindDescriptorSets {
+                layout,
+                first_set,
+                sets,
+                ..
+            } => {
+                for set in sets {
+                    self.check(call, set.as_raw(), vk::ObjectType::DESCRIPTOR_SET);
+                }
+                if let Some(Kind::PipelineLayout { sets: count, .. }) =
+                    self.get(call, layout.as_raw(), vk::ObjectType::PIPELINE_LAYOUT)
+                {
+                    let count = *count;
+                    if first_set + sets.len() as u32 > count {
+                        self.error(format!(
+                            "{}: sets {}..{} bound to a layout of {} sets.",
+                            call,
+                            first_set,
+                            first_set + sets.len() as u32,
+                            count
+                        ));
+                    }
+                }
+            }
+            TraceCommand::BindVertexBuffers {
+                buffers, offsets, ..
+            } => {
+                for (buffer, offset) in buffers.iter().zip(offsets) {
+                    if let Some(size) =
+                        self.buffer(call, *buffer, vk::BufferUsageFlags::VERTEX_BUFFER)
+                    {
+                        self.bounds(call, "the vertices", *offset, 1, size);
+                    }
+                }
+            }
+            TraceCommand::BindIndexBuffer {
+                buffer,
+                offset,
+                index_type,
+            } => {
+                if let Some(size) = self.buffer(call, *buffer, vk::BufferUsageFlags::INDEX_BUFFER) {
+                    self.bounds(call, "the indices", *offset, 1, size);
+                }
+                match index_stride(*index_type) {
+                    Some(stride) if offset % stride != 0 => self.error(format!(
+                        "{}: the offset {} is not a multiple of the {:?} size.",
+                        call, offset, index_type
+                    )),
+                    None => self.error(format!("{}: {:?} indices.", call, index_type)),
+                    _ => {}
+                }
+            }
+            TraceCommand::PushConstants {
+                layout,
+                stages,
+                offset,
+                data,
+            } => {
+                let size = data.len() as u32;
+                if offset % 4 != 0 || size % 4 != 0 {
+                    self.error(format!("{}: offset and size must be multiples of 4.", call));
+                }
+                if let Some(Kind::PipelineLayout { push_constants, .. }) =
+                    self.get(call, layout.as_raw(), vk::ObjectType::PIPELINE_LAYOUT)
+                {
+                    // the updated bytes lie in ranges of all the stages
+                    let covered = push_constants.iter().any(|r| {
+                        r.stage_flags.contains(*stages)
+                            && r.offset <= *offset
+                            && offset + size <= r.offset + r.size
+                    });
+                    if !covered {
+                        self.error(format!(
+                            "{}: no push constant range of the layout holds {} bytes at {} for {:?}.",
+                            call, size, offset, stages
+                        ));
+                    }
+                }
+            }
+            TraceCommand::DrawIndirect {
+                buffer,
+                offset,
+                draw_count,
+                stride,
+            } => {
+                if let Some(size) =
+                    self.buffer(call, *buffer, vk::BufferUsageFlags::INDIRECT_BUFFER)
+                {
+                    let len = draw_count.saturating_sub(1) as u64 * *stride as u64 + 16;
+                    self.bounds(call, "the draws", *offset, len, size);
+                }
+            }
+            TraceCommand::PipelineBarrier {
+                buffers, images, ..
+            } => {
+                for barrier in buffers {
+                    self.check(call, barrier.buffer.as_raw(), vk::ObjectType::BUFFER);
+                }
+                for barrier in images {
+                    self.check(call, barrier.image.as_raw(), vk::ObjectType::IMAGE);
+                }
+            }
+            TraceCommand::CopyBuffer { src, dst, regions } => {
+                let src_size = self.buffer(call, *src, vk::BufferUsageFlags::TRANSFER_SRC);
+                let dst_size = self.buffer(call, *dst, vk::BufferUsageFlags::TRANSFER_DST);
+                for region in regions {
+                    if let Some(size) = src_size {
+                        self.bounds(call, "the source", region.src_offset, region.size, size);
+                    }
+                    if let Some(size) = dst_size {
+                        self.bounds(
+                            call,
+                            "the destination",
+                            region.dst_offset,
+                            region.size,
+                            size,
+                        );
+                    }
+                }
+            }
+            TraceCommand::CopyBufferToImage {
+                buffer,
+                image,
+                layout,
+                regions,
+            } => {
+                self.buffer(call, *buffer, vk::BufferUsageFlags::TRANSFER_SRC);
+                let levels = self.image(call, *image, vk::ImageUsageFlags::TRANSFER_DST);
+                self.layout(call, *layout, vk::ImageLayout::TRANSFER_DST_OPTIMAL);
+                for region in regions {
+                    self.level(call, region.image_subresource.mip_level, levels);
+                }
+            }
+            TraceCommand::CopyImageToBuffer {
+                image,
+                layout,
+                buffer,
+                regions,
+            } => {
+                let levels = self.image(call, *image, vk::ImageUsageFlags::TRANSFER_SRC);
+                self.layout(call, *layout, vk::ImageLayout::TRANSFER_SRC_OPTIMAL);
+                self.buffer(call, *buffer, vk::BufferUsageFlags::TRANSFER_DST);
+                for region in regions {
+                    self.level(call, region.image_subresource.mip_level, levels);
+                }
+            }
+            TraceCommand::BlitImage {
+                src,
+                src_layout,
+                dst,
+                dst_layout,
+                regions,
+                ..
+            } => {
+                let src_levels = self.image(call, *src, vk::ImageUsageFlags::TRANSFER_SRC);
+                let dst_levels = self.image(call, *dst, vk::ImageUsageFlags::TRANSFER_DST);
+                self.layout(call, *src_layout, vk::ImageLayout::TRANSFER_SRC_OPTIMAL);
+                self.layout(call, *dst_layout, vk::ImageLayout::TRANSFER_DST_OPTIMAL);
+                for region in regions {
+                    self.level(call, region.src_subresource.mip_level, src_levels);
+                    self.level(call, region.dst_subresource.mip_level, dst_levels);
+                }
+            }
+            TraceCommand::ClearColorImage { image, layout, .. } => {
+                self.image(call, *image, vk::ImageUsageFlags::TRANSFER_DST);
+                self.layout(call, *layout, vk::ImageLayout::TRANSFER_DST_OPTIMAL);
+            }
+            TraceCommand::FillBuffer {
+                buffer,
+                offset,
+                size,
+                ..
+            } => {
+                if offset % 4 != 0 || (*size != WHOLE_SIZE && size % 4 != 0) {
+                    self.error(format!("{}: offset and size must be multiples of 4.", call));
+                }
+                if let Some(buffer_size) =
+                    self.buffer(call, *buffer, vk::BufferUsageFlags::TRANSFER_DST)
+                {
+                    let size = if *size == WHOLE_SIZE {
+                        buffer_size.saturating_sub(*offset)
+                    } else {
+                        *size
+                    };
+                    self.bounds(call, "the fill", *offset, size, buffer_size);
+                }
+            }
+            TraceCommand::ResetQueryPool {
+                pool,
+                first_query,
+                query_count,
+            } => {
+                self.queries(call, *pool, *first_query, *query_count);
+            }
+            TraceCommand::WriteTimestamp { pool, query, .. } => {
+                let ty = self.queries(call, *pool, *query, 1);
+                if let Some(ty) = ty.filter(|ty| *ty != vk::QueryType::TIMESTAMP) {
+                    self.error(format!("{}: writes a timestamp to a {:?} pool.", call, ty));
+                }
+            }
+            TraceCommand::BeginQuery { pool, query, .. }
+            | TraceCommand::EndQuery { pool, query } => {
+                let ty = self.queries(call, *pool, *query, 1);
+                if ty == Some(vk::QueryType::TIMESTAMP) {
+                    self.error(format!(
+                        "{}: timestamp queries are written, not begun.",
+                        call
+                    ));
+                }
+            }
+            TraceCommand::CopyQueryPoolResults {
+                pool,
+                first_query,
+                query_count,
+                buffer,
+                offset,
+                stride,
+                flags,
+            } => {
+                let ty = self.queries(call, *pool, *first_query, *query_count);
+                if ty == Some(vk::QueryType::TIMESTAMP)
+                    && flags.contains(vk::QueryResultFlags::PARTIAL)
+                {
+                    self.error(format!("{}: timestamps can't be partial.", call));
+                }
+                let word = query_word(*flags);
+                if offset % word != 0 || stride % word != 0 {
+                    self.error(format!(
+                        "{}: offset and stride must be multiples of {}.",
+                        call, word
+                    ));
+                }
+                let words = if flags.contains(vk::QueryResultFlags::WITH_AVAILABILITY) {
+                    2
+                } else {
+                    1
+                };
+                if *stride < words * word {
+                    self.error(format!(
+                        "{}: a stride of {} overlaps the results.",
+                        call, stride
+                    ));
+                }
+                if let Some(size) = self.buffer(call, *buffer, vk::BufferUsageFlags::TRANSFER_DST) {
+                    let len = query_count.saturating_sub(1) as u64 * stride + words * word;
+                    self.bounds(call, "the results", *offset, len, size);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // append a command to a recording command buffer, checking it fits the recorded state
+    fn record(&mut self, call: &str, command_buffer: vk::CommandBuffer, command: TraceCommand) {
+        self.check_command(call, &command);
+        let indices = match &command {
+            TraceCommand::BindIndexBuffer {
+                buffer,
+                offset,
+                index_type,
+            } => match self.objects.get(&buffer.as_raw()) {
+                Some(Object {
+                    kind: Kind::Buffer { size, .. },
+                    ..
+                }) => Some(size.saturating_sub(*offset) / index_stride(*index_type).unwrap_or(4)),
+                _ => Some(0),
+            },
+            _ => None,
+        };
+        let subpasses = match &command {
+            TraceCommand::BeginRenderPass { render_pass, .. } => {
+                match self.objects.get(&render_pass.as_raw()) {
+                    Some(Object {
+                        kind: Kind::RenderPass { subpasses, .. },
+                        ..
+                    }) => *subpasses,
+                    _ => 1,
+                }
+            }
+            _ => 1,
+        };
+        let viewports = match &command {
+            TraceCommand::SetViewport {
+                first_viewport: first,
+                viewports,
+            } => Some((*first, viewports.len() as u32)),
+            TraceCommand::SetScissor {
+                first_scissor: first,
+                scissors,
+            } => Some((*first, scissors.len() as u32)),
+            _ => None,
+        };
+        // the problems of the secondary command buffers a primary executes
+        let mut executed = vec![];
+        if let TraceCommand::ExecuteCommands { command_buffers } = &command {
+            for buffer in command_buffers {
+                executed.push(match self.objects.get(&(buffer.as_raw() as u64)) {
+                    Some(Object {
+                        kind: Kind::CommandBuffer(secondary),
+                        ..
+                    }) => match secondary.state {
+                        _ if !secondary.secondary => Some("executes a primary command buffer"),
+                        RecordState::Executable => None,
+                        _ => Some("executes a command buffer that was not ended"),
+                    },
+                    _ => Some("executes an unknown command buffer"),
+                });
+            }
+        }
+        let continuing = match &command {
+            TraceCommand::ExecuteCommands { command_buffers } => command_buffers.iter().any(|b| {
+                matches!(
+                    self.objects.get(&(b.as_raw() as u64)),
+                    Some(Object {
+                        kind: Kind::CommandBuffer(secondary),
+                        ..
+                    }) if secondary.continues
+                )
+            }),
+            _ => false,
+        };
+        let viewport_limit = match viewports {
+            Some(_) if self.multi_viewport(command_buffer) => properties().limits.max_viewports,
+            Some(_) => 1,
+            None => 0,
+        };
+        let Some(Kind::CommandBuffer(recorder)) = self.get(
+            call,
+            command_buffer.as_raw() as u64,
+            vk::ObjectType::COMMAND_BUFFER,
+        ) else {
+            return;
+        };
+
+        let mut problems = vec![];
+        let mut range = None;
+        if recorder.state != RecordState::Recording {
+            problems.push("the command buffer is not recording");
+        }
+        match &command {
+            TraceCommand::BeginRenderPass { contents, .. } => {
+                if recorder.in_render_pass {
+                    problems.push("begins a render pass inside another");
+                }
+                recorder.in_render_pass = true;
+                recorder.inline = *contents == vk::SubpassContents::INLINE;
+                recorder.subpasses_left = subpasses.saturating_sub(1);
+            }
+            TraceCommand::EndRenderPass => {
+                if recorder.continues {
+                    problems.push("ends the render pass its primary command buffer began");
+                } else if !recorder.in_render_pass {
+                    problems.push("there is no render pass to end");
+                } else if recorder.subpasses_left > 0 {
+                    problems.push("ends the render pass before its last subpass");
+                }
+                recorder.in_render_pass = false;
+            }
+            TraceCommand::NextSubpass { contents } => {
+                recorder.inline = *contents == vk::SubpassContents::INLINE;
+                if recorder.continues {
+                    problems.push("advances the render pass its primary command buffer began");
+                } else if !recorder.in_render_pass {
+                    problems.push("there is no render pass to advance");
+                } else if recorder.subpasses_left == 0 {
+                    problems.push("advances past the last subpass");
+                }
+                recorder.subpasses_left = recorder.subpasses_left.saturating_sub(1);
+            }
+            TraceCommand::BindPipeline { bind_point, .. } => match *bind_point {
+                vk::PipelineBindPoint::GRAPHICS => recorder.graphics = true,
+                vk::PipelineBindPoint::COMPUTE => recorder.compute = true,
+                _ => {}
+            },
+            TraceCommand::BindIndexBuffer { .. } => recorder.index_buffer = indices,
+            TraceCommand::BeginLabel { .. } => recorder.labels += 1,
+            TraceCommand::EndLabel => match recorder.labels.checked_sub(1) {
+                Some(labels) => recorder.labels = labels,
+                None => problems.push("there is no label region to end"),
+            },
+            TraceCommand::Draw { .. }
+            | TraceCommand::DrawIndexed { .. }
+            | TraceCommand::DrawIndirect { .. } => {
+                if !recorder.in_render_pass {
+                    problems.push("draws outside of a render pass");
+                } else if !recorder.inline {
+                    problems.push("draws inline in a subpass of secondary command buffers");
+                }
+                if !recorder.graphics {
+                    problems.push("draws without a graphics pipeline");
+                }
+                if let TraceCommand::DrawIndexed {
+                    index_count,
+                    first_index,
+                    ..
+                } = command
+                {
+                    match recorder.index_buffer {
+                        None => problems.push("draws indexed without an index buffer"),
+                        Some(available) if first_index as u64 + index_count as u64 > available => {
+                            range = Some(format!(
+                                "{}: draws indices {}..{} of a buffer with {}.",
+                                call,
+                                first_index,
+                                first_index as u64 + index_count as u64,
+                                available
+                            ));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            TraceCommand::ExecuteCommands { .. } => {
+                if recorder.secondary {
+                    problems.push("executes commands in a secondary command buffer");
+                }
+                if recorder.in_render_pass && recorder.inline {
+                    problems.push("executes secondary command buffers in an inline subpass");
+                }
+                if continuing && !recorder.in_render_pass {
+                    problems.push("executes render pass commands outside of a render pass");
+                }
+                problems.extend(executed.into_iter().flatten());
+            }
+            TraceCommand::Dispatch { .. } => {
+                if recorder.in_render_pass {
+                    problems.push("dispatches inside a render pass");
+                }
+                if !recorder.compute {
+                    problems.push("dispatches without a compute pipeline");
+                }
+            }
+            TraceCommand::CopyBuffer { .. }
+            | TraceCommand::CopyBufferToImage { .. }
+            | TraceCommand::CopyImageToBuffer { .. }
+            | TraceCommand::BlitImage { .. }
+            | TraceCommand::ClearColorImage { .. }
+            | TraceCommand::FillBuffer { .. }
+            | TraceCommand::CopyQueryPoolResults { .. }
+                if recorder.in_render_pass =>
+            {
+                problems.push("transfers inside a render pass");
+            }
+            TraceCommand::ResetQueryPool { .. } if recorder.in_render_pass => {
+                problems.push("resets queries inside a render pass");
+            }
+            _ => {}
+        }
+        if let Some((first, count)) = viewports.filter(|(f, c)| f + c > viewport_limit) {
+            range = Some(format!(
+                "{}: sets viewports {}..{} of the {} the device has.",
+                call,
+                first,
+                first + count,
+                viewport_limit
+            ));
+        }
+        recorder.commands.push(command);
+
+        for problem in problems {
+            self.error(format!("{}: {}.", call, problem));
+        }
+        if let Some(range) = range {
+            self.error(range);
+        }
+    }
+
+    // whether the device recording `command_buffer` enabled multiple viewports
+    fn multi_viewport(&self, command_buffer: vk::CommandBuffer) -> bool {
+        let device = match self.objects.get(&(command_buffer.as_raw() as u64)) {
+            Some(Object {
+                kind: Kind::CommandBuffer(recorder),
+                ..
+            }) => self.objects.get(&recorder.pool).map(|pool| pool.owner),
+            _ => None,
+        };
+        matches!(
+            device.and_then(|d| self.objects.get(&d)),
+            Some(Object {
+                kind: Kind::Device {
+                    multi_viewport: true,
+                    ..
+                },
+                ..
+            })
+        )
+    }
+
+    // the memory and the offset into it of `len` bytes of `buffer` at `offset`, none where
+    // validation already reported the range
+    fn span(&self, buffer: vk::Buffer, offset: u64, len: u64) -> Option<(u64, usize, usize)> {
+        let Some(Object {
+            kind:
+                Kind::Buffer {
+                    size,
+                    memory: Some((memory, base)),
+                    ..
+                },
+            ..
+        }) = self.objects.get(&buffer.as_raw())
+        else {
+            return None;
+        };
+        let end = offset.checked_add(len).filter(|end| end <= size)?;
+        let Some(Object {
+            kind: Kind::Memory { data, .. },
+            ..
+        }) = self.objects.get(memory)
+        else {
+            return None;
+        };
+        let (start, end) = (base + offset, base + end);
+        (end <= data.len() as u64).then_some((*memory, start as usize, end as usize))
+    }
+
+    fn read(&self, buffer: vk::Buffer, offset: u64, len: u64) -> Option<Vec<u8>> {
+        let (memory, start, end) = self.span(buffer, offset, len)?;
+        match &self.objects[&memory].kind {
+            Kind::Memory { data, .. } => Some(data[start..end].to_vec()),
+            _ => None,
+        }
+    }
+
+    // concurrent sharing needs two distinct families, the device has only family 0
+    fn sharing(&mut self, call: &str, mode: vk::SharingMode, families: &[u32]) {
+        if mode != vk::SharingMode::CONCURRENT {
+            return;
+        }
+        if let Some(family) = families.iter().find(|f| **f != 0) {
+            self.error(format!("{}: there is no queue family {}.", call, family));
+        }
+        let mut distinct = families.to_vec();
+        distinct.sort_unstable();
+        distinct.dedup();
+        if distinct.len() < 2 {
+            self.error(format!(
+                "{}: concurrent sharing across {:?} needs two distinct families.",
+                call, families
+            ));
+        }
+    }
+
+    // remember the buffer range written to `binding` of `set`
+    fn write_descriptor(
+        &mut self,
+        set: vk::DescriptorSet,
+        binding: u32,
+        buffer: vk::Buffer,
+        offset: u64,
+        range: u64,
+    ) {
+        if let Some(Object {
+            kind: Kind::DescriptorSet { buffers, .. },
+            ..
+        }) = self.objects.get_mut(&set.as_raw())
+        {
+            buffers.insert(binding, (buffer, offset, range));
+        }
+    }
+
+    pub(super) fn descriptor_bytes(&self, set: vk::DescriptorSet, binding: u32) -> Option<Vec<u8>> {
+        let Some(Object {
+            kind: Kind::DescriptorSet { buffers, .. },
+            ..
+        }) = self.objects.get(&set.as_raw())
+        else {
+            return None;
+        };
+        let (buffer, offset, range) = *buffers.get(&binding)?;
+        self.read(buffer, offset, range)
+    }
+
+    fn write(&mut self, buffer: vk::Buffer, offset: u64, bytes: &[u8]) {
+        if let Some((memory, start, end)) = self.span(buffer, offset, bytes.len() as u64) {
+            if let Some(Object {
+                kind: Kind::Memory { data, .. },
+                ..
+            }) = self.objects.get_mut(&memory)
+            {
+                data[start..end].copy_from_slice(bytes);
+            }
+        }
+    }
+
+    // the commands with effects the host can see
+    fn run(&mut self, command: &TraceCommand) {
+        match command {
+            TraceCommand::CopyBuffer { src, dst, regions } => {
+                for region in regions {
+                    if let Some(bytes) = self.read(*src, region.src_offset, region.size) {
+                        self.write(*dst, region.dst_offset, &bytes);
+                    }
+                }
+            }
+            TraceCommand::FillBuffer {
+                buffer,
+                offset,
+                size,
+                data,
+            } => {
+                let size = match self.objects.get(&buffer.as_raw()) {
+                    Some(Object {
+                        kind: Kind::Buffer { size: whole, .. },
+                        ..
+                    }) if *size == WHOLE_SIZE => whole.saturating_sub(*offset) / 4 * 4,
+                    _ => *size,
+                };
+                let bytes = data.to_le_bytes().repeat((size / 4) as usize);
+                self.write(*buffer, *offset, &bytes);
+            }
+            TraceCommand::ResetQueryPool {
+                pool,
+                first_query,
+                query_count,
+            } => {
+                self.set_queries(*pool, *first_query, *query_count, QueryState::Reset);
+            }
+            TraceCommand::WriteTimestamp { pool, query, .. } => {
+                self.ticks += 1;
+                let state = QueryState::Available(self.ticks);
+                self.write_query("vkCmdWriteTimestamp", *pool, *query, state);
+            }
+            TraceCommand::BeginQuery { pool, query, .. } => {
+                // the query counts from its reset, nothing is drawn so it stays at 0
+                self.write_query("vkCmdBeginQuery", *pool, *query, QueryState::Reset);
+            }
+            TraceCommand::EndQuery { pool, query } => {
+                self.set_queries(*pool, *query, 1, QueryState::Available(0));
+            }
+            TraceCommand::CopyQueryPoolResults {
+                pool,
+                first_query,
+                query_count,
+                buffer,
+                offset,
+                stride,
+                flags,
+            } => {
+                let call = "vkCmdCopyQueryPoolResults";
+                let Some(Object {
+                    kind: Kind::QueryPool { queries, .. },
+                    ..
+                }) = self.objects.get(&pool.as_raw())
+                else {
+                    return;
+                };
+                let first = *first_query as usize;
+                let Some(states) = queries.get(first..first + *query_count as usize) else {
+                    return;
+                };
+                let word = query_word(*flags);
+                let bytes = |value: u64| match word {
+                    8 => value.to_ne_bytes().to_vec(),
+                    _ => (value as u32).to_ne_bytes().to_vec(),
+                };
+                let mut writes = vec![];
+                let mut problems = vec![];
+                for (i, state) in states.iter().enumerate() {
+                    let at = offset + i as u64 * stride;
+                    let value = match state {
+                        QueryState::Available(value) => Some(*value),
+                        QueryState::Undefined => {
+                            problems.push(format!(
+                                "{}: query {} was never reset.",
+                                call,
+                                first + i
+                            ));
+                            continue;
+                        }
+                        // the device would wait for it forever
+                        QueryState::Reset if flags.contains(vk::QueryResultFlags::WAIT) => {
+                            problems.push(format!(
+                                "{}: waits for query {} that nothing wrote.",
+                                call,
+                                first + i
+                            ));
+                            continue;
+                        }
+                        QueryState::Reset if flags.contains(vk::QueryResultFlags::PARTIAL) => {
+                            Some(0)
+                        }
+                        QueryState::Reset => None,
+                    };
+                    if let Some(value) = value {
+                        writes.push((at, bytes(value)));
+                    }
+                    if flags.contains(vk::QueryResultFlags::WITH_AVAILABILITY) {
+                        let available = matches!(state, QueryState::Available(_));
+                        writes.push((at + word, bytes(available as u64)));
+                    }
+                }
+                for (at, bytes) in writes {
+                    self.write(*buffer, at, &bytes);
+                }
+                for problem in problems {
+                    self.error(problem);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // set queries `first..first + count` of `pool` to `state`, where they exist
+    fn set_queries(&mut self, pool: vk::QueryPool, first: u32, count: u32, state: QueryState) {
+        if let Some(Object {
+            kind: Kind::QueryPool { queries, .. },
+            ..
+        }) = self.objects.get_mut(&pool.as_raw())
+        {
+            let first = first as usize;
+            let end = (first + count as usize).min(queries.len());
+            queries
+                .iter_mut()
+                .take(end)
+                .skip(first)
+                .for_each(|q| *q = state);
+        }
+    }
+
+    // write `query` of `pool`, which has to be reset since it was written last
+    fn write_query(&mut self, call: &str, pool: vk::QueryPool, query: u32, state: QueryState) {
+        let previous = match self.objects.get(&pool.as_raw()) {
+            Some(Object {
+                kind: Kind::QueryPool { queries, .. },
+                ..
+            }) => queries.get(query as usize).copied(),
+            _ => None,
+        };
+        match previous {
+            Some(QueryState::Reset) => self.set_queries(pool, query, 1, state),
+            Some(_) => self.error(format!(
+                "{}: query {} was not reset since it was written.",
+                call, query
+            )),
+            None => {}
+        }
+    }
+}
+
+fn with<T>(handle: usize, f: impl FnOnce(&mut NullState) -> T) -> T {
+    let state = driver(handle);
+    let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+    f(&mut state)
+}
+
+// the two call enumeration of vulkan, the count first and then the items
+unsafe fn fill<T: Copy>(items: &[T], count: *mut u32, out: *mut T) -> vk::Result {
+    if out.is_null() {
+        *count = items.len() as u32;
+        return vk::Result::SUCCESS;
+    }
+    let written = (*count as usize).min(items.len());
+    ptr::copy_nonoverlapping(items.as_ptr(), out, written);
+    *count = written as u32;
+    if written < items.len() {
+        vk::Result::INCOMPLETE
+    } else {
+        vk::Result::SUCCESS
+    }
+}
+
+unsafe fn names(pointer: *const *const c_char, count: u32) -> Vec<vk::ExtensionName> {
+    items(pointer, count)
+        .iter()
+        .map(|n| vk::ExtensionName::from_ptr(*n))
+        .collect()
+}
+
+// the structure of `s_type` in a `next` chain
+unsafe fn find<'a, T>(next: *const c_void, s_type: vk::StructureType) -> Option<&'a T> {
+    let mut next = next as *const vk::BaseInStructure;
+    while !next.is_null() {
+        if (*next).s_type == s_type {
+            return Some(&*(next as *const T));
+        }
+        next = (*next).next;
+    }
+    None
+}
+
+fn align(size: u64) -> u64 {
+    size.div_ceil(ALIGNMENT) * ALIGNMENT
+}
+
+// the bytes per texel of `format`, the usual formats are enough to size memory
+fn texel_size(format: vk::Format) -> u64 {
+    match format {
+        vk::Format::R8_UNORM | vk::Format::R8_UINT => 1,
+        vk::Format::R16G16B16A16_SFLOAT
+        | vk::Format::R32G32_SFLOAT
+        | vk::Format::D32_SFLOAT_S8_UINT => 8,
+        vk::Format::R32G32B32A32_SFLOAT => 16,
+        _ => 4,
+    }
+}
+
+fn instance_extensions() -> Vec<vk::ExtensionProperties> {
+    vec![
+        vk::ExtensionProperties {
+            extension_name: vk::KHR_GET_PHYSICAL_DEVICE_PROPERTIES2_EXTENSION.name,
+            spec_version: 1,
+        },
+        vk::ExtensionProperties {
+            extension_name: vk::EXT_DEBUG_UTILS_EXTENSION.name,
+            spec_version: 2,
+        },
+        vk::ExtensionProperties {
+            extension_name: vk::KHR_SURFACE_EXTENSION.name,
+            spec_version: 25,
+        },
+        vk::ExtensionProperties {
+            extension_name: vk::EXT_HEADLESS_SURFACE_EXTENSION.name,
+            spec_version: 1,
+        },
+    ]
+}
+
+fn device_extensions(timeline_semaphores: bool) -> Vec<vk::ExtensionProperties> {
+    let mut extensions = vec![
+        vk::ExtensionProperties {
+            extension_name: vk::KHR_MULTIVIEW_EXTENSION.name,
+            spec_version: 1,
+        },
+        vk::ExtensionProperties {
+            extension_name: vk::EXT_HOST_QUERY_RESET_EXTENSION.name,
+            spec_version: 1,
+        },
+        vk::ExtensionProperties {
+            extension_name: vk::KHR_SWAPCHAIN_EXTENSION.name,
+            spec_version: 70,
+        },
+    ];
+    if timeline_semaphores {
+        extensions.push(vk::ExtensionProperties {
+            extension_name: vk::KHR_TIMELINE_SEMAPHORE_EXTENSION.name,
+            spec_version: 2,
+        });
+    }
+    extensions
+}
+
+fn properties() -> vk::PhysicalDeviceProperties {
+    let limits = vk::PhysicalDeviceLimits {
+        max_image_dimension_1d: 16384,
+        max_image_dimension_2d: 16384,
+        max_image_dimension_3d: 2048,
+        max_image_dimension_cube: 16384,
+        max_image_array_layers: 2048,
+        max_uniform_buffer_range: 65536,
+        max_storage_buffer_range: 1 << 27,
+        max_push_constants_size: MAX_PUSH_CONSTANTS,
+        max_memory_allocation_count: 4096,
+        max_sampler_allocation_count: 4000,
+        max_bound_descriptor_sets: 8,
+        max_per_stage_descriptor_samplers: 16,
+        max_per_stage_descriptor_uniform_buffers: 15,
+        max_per_stage_descriptor_storage_buffers: 16,
+        max_per_stage_descriptor_sampled_images: 128,
+        max_per_stage_descriptor_storage_images: 8,
+        max_per_stage_resources: 128,
+        max_vertex_input_attributes: 16,
+        max_vertex_input_bindings: 16,
+        max_color_attachments: 8,
+        max_compute_work_group_count: [65535; 3],
+        max_compute_work_group_invocations: 1024,
+        max_compute_work_group_size: [1024, 1024, 64],
+        max_sampler_anisotropy: 16.0,
+        max_viewports: 16,
+        max_viewport_dimensions: [16384; 2],
+        max_framebuffer_width: 16384,
+        max_framebuffer_height: 16384,
+        max_framebuffer_layers: 2048,
+        framebuffer_color_sample_counts: vk::SampleCountFlags::_1 | vk::SampleCountFlags::_4,
+        framebuffer_depth_sample_counts: vk::SampleCountFlags::_1 | vk::SampleCountFlags::_4,
+        sampled_image_color_sample_counts: vk::SampleCountFlags::_1 | vk::SampleCountFlags::_4,
+        min_uniform_buffer_offset_alignment: ALIGNMENT,
+        min_storage_buffer_offset_alignment: ALIGNMENT,
+        non_coherent_atom_size: ALIGNMENT,
+        timestamp_compute_and_graphics: vk::TRUE,
+        timestamp_period: 1.0,
+        ..Default::default()
+    };
+    vk::PhysicalDeviceProperties {
+        api_version: vk::make_version(1, 0, 0),
+        driver_version: vk::make_version(0, 1, 0),
+        device_type: vk::PhysicalDeviceType::CPU,
+        device_name: vk::StringArray::from_bytes(b"deimos null device"),
+        limits,
+        ..Default::default()
+    }
+}
+
+// entry commands
+
+unsafe extern "system" fn get_instance_proc_addr(
+    _instance: vk::Instance,
+    name: *const c_char,
+) -> vk::PFN_vkVoidFunction {
+    proc_addr(CStr::from_ptr(name).to_bytes())
+}
+
+unsafe extern "system" fn get_device_proc_addr(
+    _device: vk::Device,
+    name: *const c_char,
+) -> vk::PFN_vkVoidFunction {
+    proc_addr(CStr::from_ptr(name).to_bytes())
+}
+
+unsafe extern "system" fn enumerate_instance_extension_properties(
+    layer: *const c_char,
+    count: *mut u32,
+    properties: *mut vk::ExtensionProperties,
+) -> vk::Result {
+    if !layer.is_null() {
+        return vk::Result::ERROR_LAYER_NOT_PRESENT;
+    }
+    fill(&instance_extensions(), count, properties)
+}
+
+unsafe extern "system" fn enumerate_instance_layer_properties(
+    count: *mut u32,
+    properties: *mut vk::LayerProperties,
+) -> vk::Result {
+    fill(&[], count, properties)
+}
+
+unsafe extern "system" fn create_instance(
+    info: *const vk::InstanceCreateInfo,
+    _: *const vk::AllocationCallbacks,
+    instance: *mut vk::Instance,
+) -> vk::Result {
+    let Some(state) = creating() else {
+        return vk::Result::ERROR_INITIALIZATION_FAILED;
+    };
+    let info = &*info;
+    if info.enabled_layer_count > 0 {
+        return vk::Result::ERROR_LAYER_NOT_PRESENT;
+    }
+    let available = instance_extensions();
+    let requested = names(info.enabled_extension_names, info.enabled_extension_count);
+    if !requested
+        .iter()
+        .all(|n| available.iter().any(|e| e.extension_name == *n))
+    {
+        return vk::Result::ERROR_EXTENSION_NOT_PRESENT;
+    }
+
+    // every instance has the one physical device
+    let mut locked = state.lock().unwrap_or_else(|e| e.into_inner());
+    let handle = locked.create(0, vk::ObjectType::INSTANCE, Kind::Plain);
+    let physical = locked.create(handle, vk::ObjectType::PHYSICAL_DEVICE, Kind::Plain);
+    drop(locked);
+    register(handle as usize, &state);
+    register(physical as usize, &state);
+
+    *instance = vk::Instance::from_raw(handle as usize);
+    vk::Result::SUCCESS
+}
+
+// instance commands
+
+unsafe extern "system" fn destroy_instance(
+    instance: vk::Instance,
+    _: *const vk::AllocationCallbacks,
+) {
+    if instance.is_null() {
+        return;
+    }
+    let handle = instance.as_raw() as u64;
+    let removed = with(instance.as_raw(), |s| {
+        let devices = s
+            .objects
+            .values()
+            .filter(|o| o.owner == handle && o.ty == vk::ObjectType::DEVICE)
+            .count();
+        if devices > 0 {
+            s.error(format!(
+                "vkDestroyInstance: {} devices were not destroyed.",
+                devices
+            ));
+        }
+        let surfaces = s.remove(|o| o.owner == handle && o.ty == vk::ObjectType::SURFACE_KHR);
+        if !surfaces.is_empty() {
+            s.error(format!(
+                "vkDestroyInstance: {} surfaces were not destroyed.",
+                surfaces.len()
+            ));
+        }
+        s.objects.remove(&handle);
+        s.remove(|o| o.owner == handle && o.ty == vk::ObjectType::PHYSICAL_DEVICE)
+    });
+    unregister(&[instance.as_raw()]);
+    unregister(&removed.iter().map(|h| *h as usize).collect::<Vec<_>>());
+}
+
+unsafe extern "system" fn enumerate_physical_devices(
+    instance: vk::Instance,
+    count: *mut u32,
+    devices: *mut vk::PhysicalDevice,
+) -> vk::Result {
+    let handle = instance.as_raw() as u64;
+    let physical = with(instance.as_raw(), |s| {
+        s.objects
+            .iter()
+            .filter(|(_, o)| o.owner == handle && o.ty == vk::ObjectType::PHYSICAL_DEVICE)
+            .map(|(h, _)| vk::PhysicalDevice::from_raw(*h as usize))
+            .collect::<Vec<_>>()
+    });
+    fill(&physical, count, devices)
+}
+
+unsafe extern "system" fn get_physical_device_properties(
+    _physical: vk::PhysicalDevice,
+    properties: *mut vk::PhysicalDeviceProperties,
+) {
+    *properties = self::properties();
+}
+
+unsafe extern "system" fn get_physical_device_features(
+    _physical: vk::PhysicalDevice,
+    features: *mut vk::PhysicalDeviceFeatures,
+) {
+    *features = self::features();
+}
+
+// bc textures but neither etc2 nor astc, like most desktop gpus
+fn features() -> vk::PhysicalDeviceFeatures {
+    vk::PhysicalDeviceFeatures {
+        independent_blend: vk::TRUE,
+        sample_rate_shading: vk::TRUE,
+        fill_mode_non_solid: vk::TRUE,
+        wide_lines: vk::TRUE,
+        depth_clamp: vk::TRUE,
+        multi_draw_indirect: vk::TRUE,
+        draw_indirect_first_instance: vk::TRUE,
+        sampler_anisotropy: vk::TRUE,
+        multi_viewport: vk::TRUE,
+        image_cube_array: vk::TRUE,
+        fragment_stores_and_atomics: vk::TRUE,
+        texture_compression_bc: vk::TRUE,
+        ..Default::default()
+    }
+}
+
+// whether every feature in `enabled` is one the device has
+fn features_present(enabled: &vk::PhysicalDeviceFeatures) -> bool {
+    // the struct is nothing but `VkBool32`s
+    let count = size_of::<vk::PhysicalDeviceFeatures>() / size_of::<vk::Bool32>();
+    let bools = |f: &vk::PhysicalDeviceFeatures| unsafe {
+        slice::from_raw_parts(f as *const _ as *const vk::Bool32, count).to_vec()
+    };
+    let present = self::features();
+    bools(enabled)
+        .into_iter()
+        .zip(bools(&present))
+        .all(|(enabled, present)| enabled == vk::FALSE || present == vk::TRUE)
+}
+
+unsafe extern "system" fn get_physical_device_memory_properties(
+    _physical: vk::PhysicalDevice,
+    properties: *mut vk::PhysicalDeviceMemoryProperties,
+) {
+    // a device local type and a host visible one, each on its own heap
+    let mut memory = vk::PhysicalDeviceMemoryProperties {
+        memory_type_count: 2,
+        memory_heap_count: 2,
+        ..Default::default()
+    };
+    memory.memory_types[0] = vk::MemoryType {
+        property_flags: vk::MemoryPropertyFlags::DEVICE_LOCAL,
+        heap_index: 0,
+    };
+    memory.memory_types[1] = vk::MemoryType {
+        property_flags: vk::MemoryPropertyFlags::HOST_VISIBLE
+            | vk::MemoryPropertyFlags::HOST_COHERENT
+            | vk::MemoryPropertyFlags::HOST_CACHED,
+        heap_index: 1,
+    };
+    memory.memory_heaps[0] = vk::MemoryHeap {
+        size: 1 << 32,
+        flags: vk::MemoryHeapFlags::DEVICE_LOCAL,
+    };
+    memory.memory_heaps[1] = vk::MemoryHeap {
+        size: 1 << 32,
+        flags: vk::MemoryHeapFlags::empty(),
+    };
+    *properties = memory;
+}
+
+unsafe extern "system" fn get_physical_device_format_properties(
+    _physical: vk::PhysicalDevice,
+    _format: vk::Format,
+    properties: *mut vk::FormatProperties,
+) {
+    *properties = vk::FormatProperties {
+        linear_tiling_features: vk::FormatFeatureFlags::all(),
+        optimal_tiling_features: vk::FormatFeatureFlags::all(),
+        buffer_features: vk::FormatFeatureFlags::all(),
+    };
+}
+
+unsafe extern "system" fn get_physical_device_queue_family_properties(
+    _physical: vk::PhysicalDevice,
+    count: *mut u32,
+    properties: *mut vk::QueueFamilyProperties,
+) {
+    let family = vk::QueueFamilyProperties {
+        queue_flags: vk::QueueFlags::GRAPHICS | vk::QueueFlags::COMPUTE | vk::QueueFlags::TRANSFER,
+        queue_count: 1,
+        timestamp_valid_bits: 64,
+        min_image_transfer_granularity: vk::Extent3D {
+            width: 1,
+            height: 1,
+            depth: 1,
+        },
+    };
+    fill(&[family], count, properties);
+}
+
+unsafe extern "system" fn enumerate_device_extension_properties(
+    physical: vk::PhysicalDevice,
+    layer: *const c_char,
+    count: *mut u32,
+    properties: *mut vk::ExtensionProperties,
+) -> vk::Result {
+    if !layer.is_null() {
+        return vk::Result::ERROR_LAYER_NOT_PRESENT;
+    }
+    let timeline_semaphores = with(physical.as_raw(), |s| s.timeline_semaphores);
+    fill(&device_extensions(timeline_semaphores), count, properties)
+}
+
+unsafe extern "system" fn create_device(
+    physical: vk::PhysicalDevice,
+    info: *const vk::DeviceCreateInfo,
+    _: *const vk::AllocationCallbacks,
+    device: *mut vk::Device,
+) -> vk::Result {
+    let info = &*info;
+    let state = driver(physical.as_raw());
+    let mut s = state.lock().unwrap_or_else(|e| e.into_inner());
+
+    let available = device_extensions(s.timeline_semaphores);
+    let requested = names(info.enabled_extension_names, info.enabled_extension_count);
+    if !requested
+        .iter()
+        .all(|n| available.iter().any(|e| e.extension_name == *n))
+    {
+        return vk::Result::ERROR_EXTENSION_NOT_PRESENT;
+    }
+    if !info.enabled_features.is_null() && !features_present(&*info.enabled_features) {
+        return vk::Result::ERROR_FEATURE_NOT_PRESENT;
+    }
+    for queue in items(info.queue_create_infos, info.queue_create_info_count) {
+        if queue.queue_family_index != 0 || queue.queue_count != 1 {
+            s.error(format!(
+                "vkCreateDevice: there is one queue in family 0, not {} in family {}.",
+                queue.queue_count, queue.queue_family_index
+            ));
+        }
+    }
+
+    let timeline_semaphores = requested.contains(&vk::KHR_TIMELINE_SEMAPHORE_EXTENSION.name);
+    let multiview = requested.contains(&vk::KHR_MULTIVIEW_EXTENSION.name);
+    let host_query_reset = requested.contains(&vk::EXT_HOST_QUERY_RESET_EXTENSION.name)
+        && find::<vk::PhysicalDeviceHostQueryResetFeatures>(
+            info.next,
+            vk::StructureType::PHYSICAL_DEVICE_HOST_QUERY_RESET_FEATURES,
+        )
+        .is_some_and(|f| f.host_query_reset == vk::TRUE);
+    let multi_viewport =
+        !info.enabled_features.is_null() && (*info.enabled_features).multi_viewport == vk::TRUE;
+    let instance = s.objects[&(physical.as_raw() as u64)].owner;
+    let handle = s.create(
+        instance,
+        vk::ObjectType::DEVICE,
+        Kind::Device {
+            timeline_semaphores,
+            multi_viewport,
+            multiview,
+            host_query_reset,
+            queue: 0,
+        },
+    );
+    let queue = s.create(handle, vk::ObjectType::QUEUE, Kind::Plain);
+    if let Some(Object {
+        kind: Kind::Device { queue: q, .. },
+        ..
+    }) = s.objects.get_mut(&handle)
+    {
+        *q = queue;
+    }
+    drop(s);
+    register(handle as usize, &state);
+    register(queue as usize, &state);
+
+    *device = vk::Device::from_raw(handle as usize);
+    vk::Result::SUCCESS
+}
+
+// device commands
+
+unsafe extern "system" fn destroy_device(device: vk::Device, _: *const vk::AllocationCallbacks) {
+    if device.is_null() {
+        return;
+    }
+    let handle = device.as_raw() as u64;
+    let removed = with(device.as_raw(), |s| {
+        // everything the device created has to go first, the command buffers and descriptor
+        // sets go with their pools
+        let leaked = count_kinds(
+            s.objects
+                .values()
+                .filter(|o| o.owner == handle)
+                .map(|o| o.ty)
+                .filter(|t| {
+                    !matches!(
+                        *t,
+                        vk::ObjectType::QUEUE
+                            | vk::ObjectType::COMMAND_BUFFER
+                            | vk::ObjectType::DESCRIPTOR_SET
+                    )
+                }),
+        );
+        for (ty, count) in leaked {
+            s.error(format!(
+                "vkDestroyDevice: {} {:?} objects were not destroyed.",
+                count, ty
+            ));
+        }
+        s.objects.remove(&handle);
+        s.remove(|o| o.owner == handle)
+    });
+    unregister(&[device.as_raw()]);
+    unregister(&removed.iter().map(|h| *h as usize).collect::<Vec<_>>());
+}
+
+unsafe extern "system" fn get_device_queue(
+    device: vk::Device,
+    family: u32,
+    index: u32,
+    queue: *mut vk::Queue,
+) {
+    with(device.as_raw(), |s| {
+        if family != 0 || index != 0 {
+            s.error(format!(
+                "vkGetDeviceQueue: there is no queue {} in family {}.",
+                index, family
+            ));
+        }
+        if let Some(Kind::Device { queue: handle, .. }) = s.get(
+            "vkGetDeviceQueue",
+            device.as_raw() as u64,
+            vk::ObjectType::DEVICE,
+        ) {
+            *queue = vk::Queue::from_raw(*handle as usize);
+        }
+    });
+}
+
+unsafe extern "system" fn device_wait_idle(_device: vk::Device) -> vk::Result {
+    // every submission completed when it was submitted
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn queue_wait_idle(_queue: vk::Queue) -> vk::Result {
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn create_buffer(
+    device: vk::Device,
+    info: *const vk::BufferCreateInfo,
+    _: *const vk::AllocationCallbacks,
+    buffer: *mut vk::Buffer,
+) -> vk::Result {
+    let info = &*info;
+    with(device.as_raw(), |s| {
+        if info.size == 0 {
+            s.error("vkCreateBuffer: the size is 0.".into());
+        }
+        if info.usage.is_empty() {
+            s.error("vkCreateBuffer: the buffer has no usage.".into());
+        }
+        let families = items(info.queue_family_indices, info.queue_family_index_count);
+        s.sharing("vkCreateBuffer", info.sharing_mode, families);
+        let kind = Kind::Buffer {
+            size: info.size,
+            usage: info.usage,
+            memory: None,
+        };
+        *buffer =
+            vk::Buffer::from_raw(s.create(device.as_raw() as u64, vk::ObjectType::BUFFER, kind));
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn create_image(
+    device: vk::Device,
+    info: *const vk::ImageCreateInfo,
+    _: *const vk::AllocationCallbacks,
+    image: *mut vk::Image,
+) -> vk::Result {
+    let info = &*info;
+    with(device.as_raw(), |s| {
+        let extent = info.extent;
+        if extent.width == 0 || extent.height == 0 || extent.depth == 0 {
+            s.error(format!("vkCreateImage: the extent {:?} is empty.", extent));
+        }
+        let levels = max_mip_levels(extent.width, extent.height);
+        if info.mip_levels == 0 || info.mip_levels > levels {
+            s.error(format!(
+                "vkCreateImage: {} mip levels for a chain of {}.",
+                info.mip_levels, levels
+            ));
+        }
+        if info.array_layers == 0 {
+            s.error("vkCreateImage: the image has no layers.".into());
+        }
+        if info.usage.is_empty() {
+            s.error("vkCreateImage: the image has no usage.".into());
+        }
+        let families = items(info.queue_family_indices, info.queue_family_index_count);
+        s.sharing("vkCreateImage", info.sharing_mode, families);
+        let kind = Kind::Image {
+            extent,
+            format: info.format,
+            mip_levels: info.mip_levels,
+            layers: info.array_layers,
+            usage: info.usage,
+            bound: false,
+        };
+        *image = vk::Image::from_raw(s.create(device.as_raw() as u64, vk::ObjectType::IMAGE, kind));
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn get_buffer_memory_requirements(
+    device: vk::Device,
+    buffer: vk::Buffer,
+    requirements: *mut vk::MemoryRequirements,
+) {
+    with(device.as_raw(), |s| {
+        if let Some(Kind::Buffer { size, .. }) = s.get(
+            "vkGetBufferMemoryRequirements",
+            buffer.as_raw(),
+            vk::ObjectType::BUFFER,
+        ) {
+            *requirements = vk::MemoryRequirements {
+                size: align(*size),
+                alignment: ALIGNMENT,
+                memory_type_bits: 0b11,
+            };
+        }
+    });
+}
+
+// the size of the whole mip chain of all layers
+fn image_size(extent: vk::Extent3D, format: vk::Format, mip_levels: u32, layers: u32) -> u64 {
+    let texels = (0..mip_levels)
+        .map(|l| {
+            let width = (extent.width >> l).max(1) as u64;
+            let height = (extent.height >> l).max(1) as u64;
+            width * height * extent.depth.max(1) as u64
+        })
+        .sum::<u64>();
+    align(texels * layers as u64 * texel_size(format))
+}
+
+unsafe extern "system" fn get_image_memory_requirements(
+    device: vk::Device,
+    image: vk::Image,
+    requirements: *mut vk::MemoryRequirements,
+) {
+    with(device.as_raw(), |s| {
+        if let Some(Kind::Image {
+            extent,
+            format,
+            mip_levels,
+            layers,
+            ..
+        }) = s.get(
+            "vkGetImageMemoryRequirements",
+            image.as_raw(),
+            vk::ObjectType::IMAGE,
+        ) {
+            *requirements = vk::MemoryRequirements {
+                size: image_size(*extent, *format, *mip_levels, *layers),
+                alignment: ALIGNMENT,
+                memory_type_bits: 0b11,
+            };
+        }
+    });
+}
+
+unsafe extern "system" fn allocate_memory(
+    device: vk::Device,
+    info: *const vk::MemoryAllocateInfo,
+    _: *const vk::AllocationCallbacks,
+    memory: *mut vk::DeviceMemory,
+) -> vk::Result {
+    let info = &*info;
+    with(device.as_raw(), |s| {
+        if info.memory_type_index > 1 {
+            s.error(format!(
+                "vkAllocateMemory: there is no memory type {}.",
+                info.memory_type_index
+            ));
+        }
+        if info.allocation_size == 0 {
+            s.error("vkAllocateMemory: the size is 0.".into());
+        }
+        if info.allocation_size > MAX_ALLOCATION {
+            return vk::Result::ERROR_OUT_OF_DEVICE_MEMORY;
+        }
+        let kind = Kind::Memory {
+            data: vec![0; info.allocation_size as usize].into_boxed_slice(),
+            host_visible: info.memory_type_index == 1,
+            mapped: false,
+        };
+        let handle = s.create(device.as_raw() as u64, vk::ObjectType::DEVICE_MEMORY, kind);
+        *memory = vk::DeviceMemory::from_raw(handle);
+        vk::Result::SUCCESS
+    })
+}
+
+// the size of live memory, a bind of `size` bytes at `offset` has to fit into it
+fn memory_fits(s: &mut NullState, call: &str, memory: vk::DeviceMemory, offset: u64, size: u64) {
+    if let Some(Kind::Memory { data, .. }) =
+        s.get(call, memory.as_raw(), vk::ObjectType::DEVICE_MEMORY)
+    {
+        let len = data.len() as u64;
+        s.bounds(call, "the binding", offset, size, len);
+    }
+    if offset % ALIGNMENT != 0 {
+        s.error(format!(
+            "{}: the offset {} is not aligned to {}.",
+            call, offset, ALIGNMENT
+        ));
+    }
+}
+
+unsafe extern "system" fn bind_buffer_memory(
+    device: vk::Device,
+    buffer: vk::Buffer,
+    memory: vk::DeviceMemory,
+    offset: vk::DeviceSize,
+) -> vk::Result {
+    let call = "vkBindBufferMemory";
+    with(device.as_raw(), |s| {
+        let Some(Kind::Buffer {
+            size,
+            memory: bound,
+            ..
+        }) = s.get(call, buffer.as_raw(), vk::ObjectType::BUFFER)
+        else {
+            return;
+        };
+        let size = *size;
+        let was_bound = bound.replace((memory.as_raw(), offset)).is_some();
+        if was_bound {
+            s.error(format!(
+                "{}: buffer {:#x} is bound already.",
+                call,
+                buffer.as_raw()
+            ));
+        }
+        memory_fits(s, call, memory, offset, align(size));
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn bind_image_memory(
+    device: vk::Device,
+    image: vk::Image,
+    memory: vk::DeviceMemory,
+    offset: vk::DeviceSize,
+) -> vk::Result {
+    let call = "vkBindImageMemory";
+    with(device.as_raw(), |s| {
+        let Some(Kind::Image {
+            extent,
+            format,
+            mip_levels,
+            layers,
+            bound,
+            ..
+        }) = s.get(call, image.as_raw(), vk::ObjectType::IMAGE)
+        else {
+            return;
+        };
+        let size = image_size(*extent, *format, *mip_levels, *layers);
+        let was_bound = std::mem::replace(bound, true);
+        if was_bound {
+            s.error(format!(
+                "{}: image {:#x} is bound already.",
+                call,
+                image.as_raw()
+            ));
+        }
+        memory_fits(s, call, memory, offset, size);
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn map_memory(
+    device: vk::Device,
+    memory: vk::DeviceMemory,
+    offset: vk::DeviceSize,
+    size: vk::DeviceSize,
+    _flags: vk::MemoryMapFlags,
+    data: *mut *mut c_void,
+) -> vk::Result {
+    let call = "vkMapMemory";
+    with(device.as_raw(), |s| {
+        let Some(Kind::Memory {
+            data: bytes,
+            host_visible,
+            mapped,
+        }) = s.get(call, memory.as_raw(), vk::ObjectType::DEVICE_MEMORY)
+        else {
+            return vk::Result::ERROR_MEMORY_MAP_FAILED;
+        };
+        let len = bytes.len() as u64;
+        let pointer = bytes.as_mut_ptr();
+        let (host_visible, was_mapped) = (*host_visible, std::mem::replace(mapped, true));
+        if !host_visible {
+            s.error(format!("{}: the memory is not host visible.", call));
+            return vk::Result::ERROR_MEMORY_MAP_FAILED;
+        }
+        if was_mapped {
+            s.error(format!("{}: the memory is mapped already.", call));
+        }
+        let size = if size == WHOLE_SIZE {
+            len.saturating_sub(offset)
+        } else {
+            size
+        };
+        s.bounds(call, "the mapping", offset, size, len);
+        *data = pointer.add(offset.min(len) as usize).cast();
+        vk::Result::SUCCESS
+    })
+}
+
+unsafe extern "system" fn unmap_memory(device: vk::Device, memory: vk::DeviceMemory) {
+    let call = "vkUnmapMemory";
+    with(device.as_raw(), |s| {
+        if let Some(Kind::Memory { mapped, .. }) =
+            s.get(call, memory.as_raw(), vk::ObjectType::DEVICE_MEMORY)
+        {
+            if !std::mem::replace(mapped, false) {
+                s.error(format!("{}: the memory is not mapped.", call));
+            }
+        }
+    });
+}
+
+unsafe extern "system" fn create_image_view(
+    device: vk::Device,
+    info: *const vk::ImageViewCreateInfo,
+    _: *const vk::AllocationCallbacks,
+    view: *mut vk::ImageView,
+) -> vk::Result {
+    let info = &*info;
+    with(device.as_raw(), |s| {
+        s.check(
+            "vkCreateImageView",
+            info.image.as_raw(),
+            vk::ObjectType::IMAGE,
+        );
+        let kind = Kind::ImageView {
+            image: info.image.as_raw(),
+        };
+        *view = vk::ImageView::from_raw(s.create(
+            device.as_raw() as u64,
+            vk::ObjectType::IMAGE_VIEW,
+            kind,
+        ));
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn create_sampler(
+    device: vk::Device,
+    _info: *const vk::SamplerCreateInfo,
+    _: *const vk::AllocationCallbacks,
+    sampler: *mut vk::Sampler,
+) -> vk::Result {
+    with(device.as_raw(), |s| {
+        *sampler = vk::Sampler::from_raw(s.create(
+            device.as_raw() as u64,
+            vk::ObjectType::SAMPLER,
+            Kind::Plain,
+        ));
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn create_shader_module(
+    device: vk::Device,
+    info: *const vk::ShaderModuleCreateInfo,
+    _: *const vk::AllocationCallbacks,
+    module: *mut vk::ShaderModule,
+) -> vk::Result {
+    let info = &*info;
+    with(device.as_raw(), |s| {
+        // the code is read as words, it has to be spir-v
+        if info.code_size == 0 || info.code_size % 4 != 0 {
+            s.error(format!(
+                "vkCreateShaderModule: {} bytes are not spir-v words.",
+                info.code_size
+            ));
+        } else if *info.code != SPIRV_MAGIC {
+            s.error("vkCreateShaderModule: the code is not spir-v.".into());
+        }
+        let handle = s.create(
+            device.as_raw() as u64,
+            vk::ObjectType::SHADER_MODULE,
+            Kind::Plain,
+        );
+        *module = vk::ShaderModule::from_raw(handle);
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn create_descriptor_set_layout(
+    device: vk::Device,
+    _info: *const vk::DescriptorSetLayoutCreateInfo,
+    _: *const vk::AllocationCallbacks,
+    layout: *mut vk::DescriptorSetLayout,
+) -> vk::Result {
+    with(device.as_raw(), |s| {
+        let handle = s.create(
+            device.as_raw() as u64,
+            vk::ObjectType::DESCRIPTOR_SET_LAYOUT,
+            Kind::Plain,
+        );
+        *layout = vk::DescriptorSetLayout::from_raw(handle);
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn create_pipeline_layout(
+    device: vk::Device,
+    info: *const vk::PipelineLayoutCreateInfo,
+    _: *const vk::AllocationCallbacks,
+    layout: *mut vk::PipelineLayout,
+) -> vk::Result {
+    let call = "vkCreatePipelineLayout";
+    let info = &*info;
+    with(device.as_raw(), |s| {
+        for set in items(info.set_layouts, info.set_layout_count) {
+            s.check(call, set.as_raw(), vk::ObjectType::DESCRIPTOR_SET_LAYOUT);
+        }
+        let ranges = items(info.push_constant_ranges, info.push_constant_range_count);
+        for range in ranges {
+            if range.size == 0 || range.offset % 4 != 0 || range.size % 4 != 0 {
+                s.error(format!(
+                    "{}: the push constant range of {} bytes at {} is not in words.",
+                    call, range.size, range.offset
+                ));
+            }
+            if range.offset + range.size > MAX_PUSH_CONSTANTS {
+                s.error(format!(
+                    "{}: push constants end past the limit of {} bytes.",
+                    call, MAX_PUSH_CONSTANTS
+                ));
+            }
+        }
+        let kind = Kind::PipelineLayout {
+            sets: info.set_layout_count,
+            push_constants: ranges.to_vec(),
+        };
+        let handle = s.create(
+            device.as_raw() as u64,
+            vk::ObjectType::PIPELINE_LAYOUT,
+            kind,
+        );
+        *layout = vk::PipelineLayout::from_raw(handle);
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn create_render_pass(
+    device: vk::Device,
+    info: *const vk::RenderPassCreateInfo,
+    _: *const vk::AllocationCallbacks,
+    render_pass: *mut vk::RenderPass,
+) -> vk::Result {
+    let info = &*info;
+    with(device.as_raw(), |s| {
+        let attachments = items(info.attachments, info.attachment_count);
+        let clears = attachments
+            .iter()
+            .rposition(|a| {
+                a.load_op == vk::AttachmentLoadOp::CLEAR
+                    || a.stencil_load_op == vk::AttachmentLoadOp::CLEAR
+            })
+            .map_or(0, |i| i as u32 + 1);
+        if info.subpass_count == 0 {
+            s.error("vkCreateRenderPass: the render pass has no subpasses.".into());
+        }
+        let views = find::<vk::RenderPassMultiviewCreateInfo>(
+            info.next,
+            vk::StructureType::RENDER_PASS_MULTIVIEW_CREATE_INFO,
+        );
+        if let Some(views) = views {
+            if !matches!(
+                s.objects.get(&(device.as_raw() as u64)),
+                Some(Object {
+                    kind: Kind::Device {
+                        multiview: true,
+                        ..
+                    },
+                    ..
+                })
+            ) {
+                s.error("vkCreateRenderPass: view masks need VK_KHR_multiview.".into());
+            } else if views.subpass_count != info.subpass_count {
+                s.error(format!(
+                    "vkCreateRenderPass: {} view masks for {} subpasses.",
+                    views.subpass_count, info.subpass_count
+                ));
+            }
+        }
+        let kind = Kind::RenderPass {
+            attachments: info.attachment_count,
+            clears,
+            subpasses: info.subpass_count,
+        };
+        let handle = s.create(device.as_raw() as u64, vk::ObjectType::RENDER_PASS, kind);
+        *render_pass = vk::RenderPass::from_raw(handle);
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn create_framebuffer(
+    device: vk::Device,
+    info: *const vk::FramebufferCreateInfo,
+    _: *const vk::AllocationCallbacks,
+    framebuffer: *mut vk::Framebuffer,
+) -> vk::Result {
+    let call = "vkCreateFramebuffer";
+    let info = &*info;
+    with(device.as_raw(), |s| {
+        for view in items(info.attachments, info.attachment_count) {
+            s.check(call, view.as_raw(), vk::ObjectType::IMAGE_VIEW);
+        }
+        if let Some(Kind::RenderPass { attachments, .. }) =
+            s.get(call, info.render_pass.as_raw(), vk::ObjectType::RENDER_PASS)
+        {
+            let attachments = *attachments;
+            if attachments != info.attachment_count {
+                s.error(format!(
+                    "{}: {} attachments for a render pass of {}.",
+                    call, info.attachment_count, attachments
+                ));
+            }
+        }
+        let handle = s.create(
+            device.as_raw() as u64,
+            vk::ObjectType::FRAMEBUFFER,
+            Kind::Plain,
+        );
+        *framebuffer = vk::Framebuffer::from_raw(handle);
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn create_graphics_pipelines(
+    device: vk::Device,
+    _cache: vk::PipelineCache,
+    count: u32,
+    infos: *const vk::GraphicsPipelineCreateInfo,
+    _: *const vk::AllocationCallbacks,
+    pipelines: *mut vk::Pipeline,
+) -> vk::Result {
+    let call = "vkCreateGraphicsPipelines";
+    with(device.as_raw(), |s| {
+        for (i, info) in items(infos, count).iter().enumerate() {
+            s.check(call, info.layout.as_raw(), vk::ObjectType::PIPELINE_LAYOUT);
+            s.check(call, info.render_pass.as_raw(), vk::ObjectType::RENDER_PASS);
+            for stage in items(info.stages, info.stage_count) {
+                s.check(call, stage.module.as_raw(), vk::ObjectType::SHADER_MODULE);
+            }
+            if info.flags.contains(vk::PipelineCreateFlags::DERIVATIVE) {
+                let base = info.base_pipeline_handle.as_raw();
+                if let Some(Kind::Pipeline { derivatives, .. }) =
+                    s.get(call, base, vk::ObjectType::PIPELINE)
+                {
+                    if !*derivatives {
+                        let message =
+                            format!("{}: base pipeline {:#x} forbids derivatives.", call, base);
+                        s.error(message);
+                    }
+                }
+            }
+            let kind = Kind::Pipeline {
+                bind_point: vk::PipelineBindPoint::GRAPHICS,
+                derivatives: info
+                    .flags
+                    .contains(vk::PipelineCreateFlags::ALLOW_DERIVATIVES),
+            };
+            let handle = s.create(device.as_raw() as u64, vk::ObjectType::PIPELINE, kind);
+            *pipelines.add(i) = vk::Pipeline::from_raw(handle);
+        }
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn create_compute_pipelines(
+    device: vk::Device,
+    _cache: vk::PipelineCache,
+    count: u32,
+    infos: *const vk::ComputePipelineCreateInfo,
+    _: *const vk::AllocationCallbacks,
+    pipelines: *mut vk::Pipeline,
+) -> vk::Result {
+    let call = "vkCreateComputePipelines";
+    with(device.as_raw(), |s| {
+        for (i, info) in items(infos, count).iter().enumerate() {
+            s.check(call, info.layout.as_raw(), vk::ObjectType::PIPELINE_LAYOUT);
+            s.check(
+                call,
+                info.stage.module.as_raw(),
+                vk::ObjectType::SHADER_MODULE,
+            );
+            let kind = Kind::Pipeline {
+                bind_point: vk::PipelineBindPoint::COMPUTE,
+                derivatives: info
+                    .flags
+                    .contains(vk::PipelineCreateFlags::ALLOW_DERIVATIVES),
+            };
+            let handle = s.create(device.as_raw() as u64, vk::ObjectType::PIPELINE, kind);
+            *pipelines.add(i) = vk::Pipeline::from_raw(handle);
+        }
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn create_descriptor_pool(
+    device: vk::Device,
+    info: *const vk::DescriptorPoolCreateInfo,
+    _: *const vk::AllocationCallbacks,
+    pool: *mut vk::DescriptorPool,
+) -> vk::Result {
+    let info = &*info;
+    with(device.as_raw(), |s| {
+        let kind = Kind::DescriptorPool {
+            max_sets: info.max_sets,
+            allocated: 0,
+            free: info
+                .flags
+                .contains(vk::DescriptorPoolCreateFlags::FREE_DESCRIPTOR_SET),
+        };
+        let handle = s.create(
+            device.as_raw() as u64,
+            vk::ObjectType::DESCRIPTOR_POOL,
+            kind,
+        );
+        *pool = vk::DescriptorPool::from_raw(handle);
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn destroy_descriptor_pool(
+    device: vk::Device,
+    pool: vk::DescriptorPool,
+    _: *const vk::AllocationCallbacks,
+) {
+    with(device.as_raw(), |s| {
+        let object = s.destroy(
+            "vkDestroyDescriptorPool",
+            pool.as_raw(),
+            vk::ObjectType::DESCRIPTOR_POOL,
+        );
+        if object.is_some() {
+            s.remove(
+                |o| matches!(o.kind, Kind::DescriptorSet { pool: p, .. } if p == pool.as_raw()),
+            );
+        }
+    });
+}
+
+unsafe extern "system" fn reset_descriptor_pool(
+    device: vk::Device,
+    pool: vk::DescriptorPool,
+    _flags: vk::DescriptorPoolResetFlags,
+) -> vk::Result {
+    with(device.as_raw(), |s| {
+        if let Some(Kind::DescriptorPool { allocated, .. }) = s.get(
+            "vkResetDescriptorPool",
+            pool.as_raw(),
+            vk::ObjectType::DESCRIPTOR_POOL,
+        ) {
+            *allocated = 0;
+            s.remove(
+                |o| matches!(o.kind, Kind::DescriptorSet { pool: p, .. } if p == pool.as_raw()),
+            );
+        }
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn allocate_descriptor_sets(
+    device: vk::Device,
+    info: *const vk::DescriptorSetAllocateInfo,
+    sets: *mut vk::DescriptorSet,
+) -> vk::Result {
+    let call = "vkAllocateDescriptorSets";
+    let info = &*info;
+    with(device.as_raw(), |s| {
+        let layouts = items(info.set_layouts, info.descriptor_set_count);
+        for layout in layouts {
+            s.check(call, layout.as_raw(), vk::ObjectType::DESCRIPTOR_SET_LAYOUT);
+        }
+        let pool = info.descriptor_pool.as_raw();
+        let Some(Kind::DescriptorPool {
+            max_sets,
+            allocated,
+            ..
+        }) = s.get(call, pool, vk::ObjectType::DESCRIPTOR_POOL)
+        else {
+            return vk::Result::ERROR_OUT_OF_POOL_MEMORY;
+        };
+        // running out is a result and not a misuse
+        if *allocated + layouts.len() as u32 > *max_sets {
+            return vk::Result::ERROR_OUT_OF_POOL_MEMORY;
+        }
+        *allocated += layouts.len() as u32;
+        for i in 0..layouts.len() {
+            let handle = s.create(
+                device.as_raw() as u64,
+                vk::ObjectType::DESCRIPTOR_SET,
+                Kind::DescriptorSet {
+                    pool,
+                    buffers: HashMap::new(),
+                },
+            );
+            *sets.add(i) = vk::DescriptorSet::from_raw(handle);
+        }
+        vk::Result::SUCCESS
+    })
+}
+
+unsafe extern "system" fn free_descriptor_sets(
+    device: vk::Device,
+    pool: vk::DescriptorPool,
+    count: u32,
+    sets: *const vk::DescriptorSet,
+) -> vk::Result {
+    let call = "vkFreeDescriptorSets";
+    with(device.as_raw(), |s| {
+        let Some(Kind::DescriptorPool {
+            allocated, free, ..
+        }) = s.get(call, pool.as_raw(), vk::ObjectType::DESCRIPTOR_POOL)
+        else {
+            return;
+        };
+        let sets = items(sets, count);
+        *allocated = allocated.saturating_sub(sets.len() as u32);
+        if !*free {
+            s.error(format!(
+                "{}: the pool was created without FREE_DESCRIPTOR_SET.",
+                call
+            ));
+        }
+        for set in sets {
+            s.destroy(call, set.as_raw(), vk::ObjectType::DESCRIPTOR_SET);
+        }
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn update_descriptor_sets(
+    device: vk::Device,
+    write_count: u32,
+    writes: *const vk::WriteDescriptorSet,
+    copy_count: u32,
+    copies: *const vk::CopyDescriptorSet,
+) {
+    let call = "vkUpdateDescriptorSets";
+    with(device.as_raw(), |s| {
+        for write in items(writes, write_count) {
+            s.check(call, write.dst_set.as_raw(), vk::ObjectType::DESCRIPTOR_SET);
+            let usage = match write.descriptor_type {
+                vk::DescriptorType::UNIFORM_BUFFER | vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC => {
+                    Some(vk::BufferUsageFlags::UNIFORM_BUFFER)
+                }
+                vk::DescriptorType::STORAGE_BUFFER | vk::DescriptorType::STORAGE_BUFFER_DYNAMIC => {
+                    Some(vk::BufferUsageFlags::STORAGE_BUFFER)
+                }
+                _ => None,
+            };
+            if let Some(usage) = usage {
+                for (i, info) in items(write.buffer_info, write.descriptor_count)
+                    .iter()
+                    .enumerate()
+                {
+                    if let Some(size) = s.buffer(call, info.buffer, usage) {
+                        let range = if info.range == WHOLE_SIZE {
+                            size.saturating_sub(info.offset)
+                        } else {
+                            info.range
+                        };
+                        s.bounds(call, "the descriptor range", info.offset, range, size);
+                        if write.dst_array_element == 0 && i == 0 {
+                            s.write_descriptor(
+                                write.dst_set,
+                                write.dst_binding,
+                                info.buffer,
+                                info.offset,
+                                range,
+                            );
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let (view, sampler) = match write.descriptor_type {
+                vk::DescriptorType::SAMPLER => (false, true),
+                vk::DescriptorType::COMBINED_IMAGE_SAMPLER => (true, true),
+                vk::DescriptorType::SAMPLED_IMAGE
+                | vk::DescriptorType::STORAGE_IMAGE
+                | vk::DescriptorType::INPUT_ATTACHMENT => (true, false),
+                _ => (false, false),
+            };
+            for info in items(write.image_info, write.descriptor_count) {
+                if view {
+                    s.check(call, info.image_view.as_raw(), vk::ObjectType::IMAGE_VIEW);
+                }
+                // immutable samplers leave it null
+                if sampler && !info.sampler.is_null() {
+                    s.check(call, info.sampler.as_raw(), vk::ObjectType::SAMPLER);
+                }
+            }
+        }
+        for copy in items(copies, copy_count) {
+            s.check(call, copy.src_set.as_raw(), vk::ObjectType::DESCRIPTOR_SET);
+            s.check(call, copy.dst_set.as_raw(), vk::ObjectType::DESCRIPTOR_SET);
+        }
+    });
+}
+
+unsafe extern "system" fn create_command_pool(
+    device: vk::Device,
+    info: *const vk::CommandPoolCreateInfo,
+    _: *const vk::AllocationCallbacks,
+    pool: *mut vk::CommandPool,
+) -> vk::Result {
+    let info = &*info;
+    with(device.as_raw(), |s| {
+        if info.queue_family_index != 0 {
+            s.error(format!(
+                "vkCreateCommandPool: there is no queue family {}.",
+                info.queue_family_index
+            ));
+        }
+        let kind = Kind::CommandPool {
+            resettable: info
+                .flags
+                .contains(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER),
+        };
+        let handle = s.create(device.as_raw() as u64, vk::ObjectType::COMMAND_POOL, kind);
+        *pool = vk::CommandPool::from_raw(handle);
+    });
+    vk::Result::SUCCESS
+}
+
+// the command buffers allocated from `pool`
+fn pool_buffers(s: &NullState, pool: u64) -> Vec<u64> {
+    s.objects
+        .iter()
+        .filter(|(_, o)| matches!(&o.kind, Kind::CommandBuffer(r) if r.pool == pool))
+        .map(|(h, _)| *h)
+        .collect()
+}
+
+unsafe extern "system" fn destroy_command_pool(
+    device: vk::Device,
+    pool: vk::CommandPool,
+    _: *const vk::AllocationCallbacks,
+) {
+    let freed = with(device.as_raw(), |s| {
+        match s.destroy(
+            "vkDestroyCommandPool",
+            pool.as_raw(),
+            vk::ObjectType::COMMAND_POOL,
+        ) {
+            Some(_) => {
+                let buffers = pool_buffers(s, pool.as_raw());
+                buffers.iter().for_each(|b| {
+                    s.objects.remove(b);
+                });
+                buffers
+            }
+            None => vec![],
+        }
+    });
+    unregister(&freed.iter().map(|b| *b as usize).collect::<Vec<_>>());
+}
+
+unsafe extern "system" fn reset_command_pool(
+    device: vk::Device,
+    pool: vk::CommandPool,
+    _flags: vk::CommandPoolResetFlags,
+) -> vk::Result {
+    with(device.as_raw(), |s| {
+        if s.check(
+            "vkResetCommandPool",
+            pool.as_raw(),
+            vk::ObjectType::COMMAND_POOL,
+        ) {
+            for buffer in pool_buffers(s, pool.as_raw()) {
+                if let Some(Object {
+                    kind: Kind::CommandBuffer(recorder),
+                    ..
+                }) = s.objects.get_mut(&buffer)
+                {
+                    recorder.state = RecordState::Initial;
+                    recorder.commands.clear();
+                }
+            }
+        }
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn allocate_command_buffers(
+    device: vk::Device,
+    info: *const vk::CommandBufferAllocateInfo,
+    buffers: *mut vk::CommandBuffer,
+) -> vk::Result {
+    let info = &*info;
+    let state = driver(device.as_raw());
+    let mut s = state.lock().unwrap_or_else(|e| e.into_inner());
+    let pool = info.command_pool.as_raw();
+    if !s.check(
+        "vkAllocateCommandBuffers",
+        pool,
+        vk::ObjectType::COMMAND_POOL,
+    ) {
+        return vk::Result::ERROR_INITIALIZATION_FAILED;
+    }
+    let mut handles = vec![];
+    for i in 0..info.command_buffer_count as usize {
+        let recorder = Recorder {
+            pool,
+            state: RecordState::Initial,
+            one_time: false,
+            commands: vec![],
+            secondary: info.level == vk::CommandBufferLevel::SECONDARY,
+            continues: false,
+            in_render_pass: false,
+            inline: true,
+            subpasses_left: 0,
+            labels: 0,
+            graphics: false,
+            compute: false,
+            index_buffer: None,
+        };
+        let handle = s.create(
+            device.as_raw() as u64,
+            vk::ObjectType::COMMAND_BUFFER,
+            Kind::CommandBuffer(Box::new(recorder)),
+        );
+        *buffers.add(i) = vk::CommandBuffer::from_raw(handle as usize);
+        handles.push(handle);
+    }
+    drop(s);
+    handles.iter().for_each(|h| register(*h as usize, &state));
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn free_command_buffers(
+    device: vk::Device,
+    pool: vk::CommandPool,
+    count: u32,
+    buffers: *const vk::CommandBuffer,
+) {
+    let call = "vkFreeCommandBuffers";
+    let buffers = items(buffers, count);
+    with(device.as_raw(), |s| {
+        s.check(call, pool.as_raw(), vk::ObjectType::COMMAND_POOL);
+        for buffer in buffers.iter().filter(|b| !b.is_null()) {
+            if let Some(Object {
+                kind: Kind::CommandBuffer(recorder),
+                ..
+            }) = s.destroy(call, buffer.as_raw() as u64, vk::ObjectType::COMMAND_BUFFER)
+            {
+                if recorder.pool != pool.as_raw() {
+                    s.error(format!(
+                        "{}: command buffer {:#x} is from another pool.",
+                        call,
+                        buffer.as_raw()
+                    ));
+                }
+            }
+        }
+    });
+    unregister(&buffers.iter().map(|b| b.as_raw()).collect::<Vec<_>>());
+}
+
+unsafe extern "system" fn begin_command_buffer(
+    command_buffer: vk::CommandBuffer,
+    info: *const vk::CommandBufferBeginInfo,
+) -> vk::Result {
+    let call = "vkBeginCommandBuffer";
+    let info = &*info;
+    with(command_buffer.as_raw(), |s| {
+        let handle = command_buffer.as_raw() as u64;
+        let Some(Kind::CommandBuffer(recorder)) =
+            s.get(call, handle, vk::ObjectType::COMMAND_BUFFER)
+        else {
+            return;
+        };
+        let (state, pool, secondary) = (recorder.state, recorder.pool, recorder.secondary);
+        let inheritance = (!info.inheritance_info.is_null()).then(|| &*info.inheritance_info);
+        let continues = secondary
+            && info
+                .flags
+                .contains(vk::CommandBufferUsageFlags::RENDER_PASS_CONTINUE);
+        **recorder = Recorder {
+            pool,
+            state: RecordState::Recording,
+            one_time: info
+                .flags
+                .contains(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT),
+            commands: vec![],
+            secondary,
+            continues,
+            in_render_pass: continues,
+            inline: true,
+            subpasses_left: 0,
+            labels: 0,
+            graphics: false,
+            compute: false,
+            index_buffer: None,
+        };
+
+        // beginning again resets the buffer, which its pool has to allow
+        let resettable = matches!(
+            s.objects.get(&pool),
+            Some(Object {
+                kind: Kind::CommandPool { resettable: true },
+                ..
+            })
+        );
+        // a secondary buffer continuing a render pass draws in its subpass
+        match inheritance {
+            None if secondary => s.error(format!(
+                "{}: the secondary command buffer has no inheritance info.",
+                call
+            )),
+            Some(inheritance) if continues => {
+                s.check(
+                    call,
+                    inheritance.render_pass.as_raw(),
+                    vk::ObjectType::RENDER_PASS,
+                );
+            }
+            _ => {}
+        }
+
+        if state == RecordState::Recording {
+            s.error(format!(
+                "{}: the command buffer is recording already.",
+                call
+            ));
+        } else if state != RecordState::Initial && !resettable {
+            s.error(format!(
+                "{}: the command buffer can't be reset, its pool lacks RESET_COMMAND_BUFFER.",
+                call
+            ));
+        }
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn end_command_buffer(command_buffer: vk::CommandBuffer) -> vk::Result {
+    let call = "vkEndCommandBuffer";
+    with(command_buffer.as_raw(), |s| {
+        let handle = command_buffer.as_raw() as u64;
+        let Some(Kind::CommandBuffer(recorder)) =
+            s.get(call, handle, vk::ObjectType::COMMAND_BUFFER)
+        else {
+            return;
+        };
+        let (state, in_render_pass, labels) =
+            (recorder.state, recorder.in_render_pass, recorder.labels);
+        let continues = recorder.continues;
+        recorder.state = RecordState::Executable;
+        if state != RecordState::Recording {
+            s.error(format!("{}: the command buffer is not recording.", call));
+        }
+        if in_render_pass && !continues {
+            s.error(format!("{}: the render pass was not ended.", call));
+        }
+        if labels > 0 {
+            s.error(format!(
+                "{}: {} label regions were not ended.",
+                call, labels
+            ));
+        }
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn set_debug_utils_object_name(
+    device: vk::Device,
+    info: *const vk::DebugUtilsObjectNameInfoEXT,
+) -> vk::Result {
+    let call = "vkSetDebugUtilsObjectNameEXT";
+    let info = &*info;
+    with(device.as_raw(), |s| {
+        if s.check(call, info.object_handle, info.object_type) {
+            let name = (!info.object_name.is_null()).then(|| {
+                CStr::from_ptr(info.object_name)
+                    .to_string_lossy()
+                    .into_owned()
+            });
+            s.objects.get_mut(&info.object_handle).unwrap().name = name;
+        }
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn queue_submit(
+    queue: vk::Queue,
+    count: u32,
+    submits: *const vk::SubmitInfo,
+    fence: vk::Fence,
+) -> vk::Result {
+    let call = "vkQueueSubmit";
+    with(queue.as_raw(), |s| {
+        if let Some(Kind::Fence { signaled }) = (!fence.is_null())
+            .then(|| s.get(call, fence.as_raw(), vk::ObjectType::FENCE))
+            .flatten()
+        {
+            if *signaled {
+                s.error(format!(
+                    "{}: fence {:#x} is signaled already.",
+                    call,
+                    fence.as_raw()
+                ));
+            }
+        }
+
+        for submit in items(submits, count) {
+            let values = find::<vk::TimelineSemaphoreSubmitInfo>(
+                submit.next,
+                vk::StructureType::TIMELINE_SEMAPHORE_SUBMIT_INFO,
+            );
+            let wait_values = values.map_or(&[][..], |v| {
+                items(v.wait_semaphore_values, v.wait_semaphore_value_count)
+            });
+            let signal_values = values.map_or(&[][..], |v| {
+                items(v.signal_semaphore_values, v.signal_semaphore_value_count)
+            });
+
+            // everything before completed, waits have to be satisfied already
+            let waits = items(submit.wait_semaphores, submit.wait_semaphore_count);
+            for (i, semaphore) in waits.iter().enumerate() {
+                let Some(Kind::Semaphore { timeline, value }) =
+                    s.get(call, semaphore.as_raw(), vk::ObjectType::SEMAPHORE)
+                else {
+                    continue;
+                };
+                let message = if *timeline {
+                    match wait_values.get(i) {
+                        Some(wait) if *wait <= *value => None,
+                        Some(wait) => Some(format!(
+                            "waits for value {} of a timeline at {} that nothing signals",
+                            wait, value
+                        )),
+                        None => Some("waits on a timeline without a value".to_string()),
+                    }
+                } else if *value == 1 {
+                    *value = 0;
+                    None
+                } else {
+                    Some("waits on a semaphore nothing signals".to_string())
+                };
+                if let Some(message) = message {
+                    s.error(format!(
+                        "{}: {} ({:#x}).",
+                        call,
+                        message,
+                        semaphore.as_raw()
+                    ));
+                }
+            }
+
+            // run the command buffers
+            let command_buffers = items(submit.command_buffers, submit.command_buffer_count);
+            let mut commands = vec![];
+            for command_buffer in command_buffers {
+                let handle = command_buffer.as_raw() as u64;
+                let Some(Kind::CommandBuffer(recorder)) =
+                    s.get(call, handle, vk::ObjectType::COMMAND_BUFFER)
+                else {
+                    continue;
+                };
+                let (state, secondary) = (recorder.state, recorder.secondary);
+                commands.extend(recorder.commands.iter().cloned());
+                if recorder.one_time && state == RecordState::Executable {
+                    recorder.state = RecordState::Invalid;
+                }
+                let problem = match state {
+                    _ if secondary => "is a secondary command buffer, it is executed instead",
+                    RecordState::Executable => continue,
+                    RecordState::Invalid => "was submitted once already",
+                    _ => "was not ended",
+                };
+                s.error(format!(
+                    "{}: command buffer {:#x} {}.",
+                    call, handle, problem
+                ));
+            }
+            for command in &commands {
+                s.run(command);
+            }
+
+            let signals = items(submit.signal_semaphores, submit.signal_semaphore_count);
+            for (i, semaphore) in signals.iter().enumerate() {
+                let Some(Kind::Semaphore { timeline, value }) =
+                    s.get(call, semaphore.as_raw(), vk::ObjectType::SEMAPHORE)
+                else {
+                    continue;
+                };
+                let message = if *timeline {
+                    match signal_values.get(i) {
+                        Some(signal) if *signal > *value => {
+                            *value = *signal;
+                            None
+                        }
+                        Some(signal) => Some(format!(
+                            "signals value {} of a timeline at {} already",
+                            signal, value
+                        )),
+                        None => Some("signals a timeline without a value".to_string()),
+                    }
+                } else if *value == 0 {
+                    *value = 1;
+                    None
+                } else {
+                    Some("signals a semaphore that is signaled already".to_string())
+                };
+                if let Some(message) = message {
+                    s.error(format!(
+                        "{}: {} ({:#x}).",
+                        call,
+                        message,
+                        semaphore.as_raw()
+                    ));
+                }
+            }
+
+            s.submissions.push(TraceSubmission {
+                command_buffers: command_buffers.to_vec(),
+                commands,
+                wait_semaphores: waits.to_vec(),
+                signal_semaphores: signals.to_vec(),
+                fence,
+            });
+        }
+
+        if let Some(Object {
+            kind: Kind::Fence { signaled },
+            ..
+        }) = s.objects.get_mut(&fence.as_raw())
+        {
+            *signaled = true;
+        }
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn create_fence(
+    device: vk::Device,
+    info: *const vk::FenceCreateInfo,
+    _: *const vk::AllocationCallbacks,
+    fence: *mut vk::Fence,
+) -> vk::Result {
+    let info = &*info;
+    with(device.as_raw(), |s| {
+        let kind = Kind::Fence {
+            signaled: info.flags.contains(vk::FenceCreateFlags::SIGNALED),
+        };
+        *fence = vk::Fence::from_raw(s.create(device.as_raw() as u64, vk::ObjectType::FENCE, kind));
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn wait_for_fences(
+    device: vk::Device,
+    count: u32,
+    fences: *const vk::Fence,
+    wait_all: vk::Bool32,
+    timeout: u64,
+) -> vk::Result {
+    let call = "vkWaitForFences";
+    with(device.as_raw(), |s| {
+        let mut signaled = vec![];
+        for fence in items(fences, count) {
+            if let Some(Kind::Fence { signaled: done }) =
+                s.get(call, fence.as_raw(), vk::ObjectType::FENCE)
+            {
+                signaled.push(*done);
+            }
+        }
+        let done = if wait_all == vk::TRUE {
+            signaled.iter().all(|d| *d)
+        } else {
+            signaled.iter().any(|d| *d)
+        };
+        // submissions complete right away, an unsignaled fence was never submitted
+        if !done && timeout > 0 {
+            s.error(format!(
+                "{}: waits for fences that were never submitted.",
+                call
+            ));
+        }
+        if done {
+            vk::Result::SUCCESS
+        } else {
+            vk::Result::TIMEOUT
+        }
+    })
+}
+
+unsafe extern "system" fn reset_fences(
+    device: vk::Device,
+    count: u32,
+    fences: *const vk::Fence,
+) -> vk::Result {
+    with(device.as_raw(), |s| {
+        for fence in items(fences, count) {
+            if let Some(Kind::Fence { signaled }) =
+                s.get("vkResetFences", fence.as_raw(), vk::ObjectType::FENCE)
+            {
+                *signaled = false;
+            }
+        }
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn get_fence_status(device: vk::Device, fence: vk::Fence) -> vk::Result {
+    with(device.as_raw(), |s| {
+        match s.get("vkGetFenceStatus", fence.as_raw(), vk::ObjectType::FENCE) {
+            Some(Kind::Fence { signaled: true }) => vk::Result::SUCCESS,
+            _ => vk::Result::NOT_READY,
+        }
+    })
+}
+
+unsafe extern "system" fn create_semaphore(
+    device: vk::Device,
+    info: *const vk::SemaphoreCreateInfo,
+    _: *const vk::AllocationCallbacks,
+    semaphore: *mut vk::Semaphore,
+) -> vk::Result {
+    let call = "vkCreateSemaphore";
+    let info = &*info;
+    let kind = find::<vk::SemaphoreTypeCreateInfo>(
+        info.next,
+        vk::StructureType::SEMAPHORE_TYPE_CREATE_INFO,
+    )
+    .filter(|t| t.semaphore_type == vk::SemaphoreType::TIMELINE)
+    .map_or(
+        Kind::Semaphore {
+            timeline: false,
+            value: 0,
+        },
+        |t| Kind::Semaphore {
+            timeline: true,
+            value: t.initial_value,
+        },
+    );
+    with(device.as_raw(), |s| {
+        let timeline = matches!(kind, Kind::Semaphore { timeline: true, .. });
+        if timeline
+            && !matches!(
+                s.objects.get(&(device.as_raw() as u64)),
+                Some(Object {
+                    kind: Kind::Device {
+                        timeline_semaphores: true,
+                        ..
+                    },
+                    ..
+                })
+            )
+        {
+            s.error(format!(
+                "{}: timeline semaphores need VK_KHR_timeline_semaphore.",
+                call
+            ));
+        }
+        let handle = s.create(device.as_raw() as u64, vk::ObjectType::SEMAPHORE, kind);
+        *semaphore = vk::Semaphore::from_raw(handle);
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn get_semaphore_counter_value(
+    device: vk::Device,
+    semaphore: vk::Semaphore,
+    value: *mut u64,
+) -> vk::Result {
+    let call = "vkGetSemaphoreCounterValue";
+    with(device.as_raw(), |s| {
+        match s.get(call, semaphore.as_raw(), vk::ObjectType::SEMAPHORE) {
+            Some(Kind::Semaphore {
+                timeline: true,
+                value: current,
+            }) => *value = *current,
+            Some(_) => s.error(format!("{}: the semaphore is not a timeline.", call)),
+            None => {}
+        }
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn wait_semaphores(
+    device: vk::Device,
+    info: *const vk::SemaphoreWaitInfo,
+    timeout: u64,
+) -> vk::Result {
+    let call = "vkWaitSemaphores";
+    let info = &*info;
+    with(device.as_raw(), |s| {
+        let semaphores = items(info.semaphores, info.semaphore_count);
+        let values = items(info.values, info.semaphore_count);
+        let mut reached = vec![];
+        for (semaphore, wait) in semaphores.iter().zip(values) {
+            match s.get(call, semaphore.as_raw(), vk::ObjectType::SEMAPHORE) {
+                Some(Kind::Semaphore {
+                    timeline: true,
+                    value,
+                }) => reached.push(*value >= *wait),
+                Some(_) => s.error(format!("{}: the semaphore is not a timeline.", call)),
+                None => {}
+            }
+        }
+        let done = if info.flags.contains(vk::SemaphoreWaitFlags::ANY) {
+            reached.iter().any(|r| *r)
+        } else {
+            reached.iter().all(|r| *r)
+        };
+        if !done && timeout > 0 {
+            s.error(format!("{}: waits for values nothing signals.", call));
+        }
+        if done {
+            vk::Result::SUCCESS
+        } else {
+            vk::Result::TIMEOUT
+        }
+    })
+}
+
+unsafe extern "system" fn create_query_pool(
+    device: vk::Device,
+    info: *const vk::QueryPoolCreateInfo,
+    _: *const vk::AllocationCallbacks,
+    pool: *mut vk::QueryPool,
+) -> vk::Result {
+    let call = "vkCreateQueryPool";
+    let info = &*info;
+    with(device.as_raw(), |s| {
+        if info.query_count == 0 {
+            s.error(format!("{}: the pool has no queries.", call));
+        }
+        // pipeline statistics need a feature the driver lacks
+        if !matches!(
+            info.query_type,
+            vk::QueryType::TIMESTAMP | vk::QueryType::OCCLUSION
+        ) {
+            s.error(format!("{}: {:?} queries.", call, info.query_type));
+        }
+        let kind = Kind::QueryPool {
+            ty: info.query_type,
+            queries: vec![QueryState::Undefined; info.query_count as usize],
+        };
+        let handle = s.create(device.as_raw() as u64, vk::ObjectType::QUERY_POOL, kind);
+        *pool = vk::QueryPool::from_raw(handle);
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn reset_query_pool(
+    device: vk::Device,
+    pool: vk::QueryPool,
+    first: u32,
+    count: u32,
+) {
+    let call = "vkResetQueryPool";
+    with(device.as_raw(), |s| {
+        if !matches!(
+            s.objects.get(&(device.as_raw() as u64)),
+            Some(Object {
+                kind: Kind::Device {
+                    host_query_reset: true,
+                    ..
+                },
+                ..
+            })
+        ) {
+            s.error(format!(
+                "{}: host resets need VK_EXT_host_query_reset and its feature.",
+                call
+            ));
+        }
+        if s.queries(call, pool, first, count).is_some() {
+            s.set_queries(pool, first, count, QueryState::Reset);
+        }
+    });
+}
+
+// the destroy commands without anything else to do
+// surface and swapchain commands, surfaces are headless and every queue presents
+
+unsafe extern "system" fn create_headless_surface(
+    instance: vk::Instance,
+    _info: *const vk::HeadlessSurfaceCreateInfoEXT,
+    _: *const vk::AllocationCallbacks,
+    surface: *mut vk::SurfaceKHR,
+) -> vk::Result {
+    let handle = with(instance.as_raw(), |s| {
+        s.create(
+            instance.as_raw() as u64,
+            vk::ObjectType::SURFACE_KHR,
+            Kind::Plain,
+        )
+    });
+    *surface = vk::SurfaceKHR::from_raw(handle);
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn destroy_surface(
+    instance: vk::Instance,
+    surface: vk::SurfaceKHR,
+    _: *const vk::AllocationCallbacks,
+) {
+    with(instance.as_raw(), |s| {
+        s.destroy(
+            "vkDestroySurfaceKHR",
+            surface.as_raw(),
+            vk::ObjectType::SURFACE_KHR,
+        );
+    });
+}
+
+unsafe extern "system" fn get_physical_device_surface_support(
+    physical: vk::PhysicalDevice,
+    family: u32,
+    surface: vk::SurfaceKHR,
+    supported: *mut vk::Bool32,
+) -> vk::Result {
+    let call = "vkGetPhysicalDeviceSurfaceSupportKHR";
+    with(physical.as_raw(), |s| {
+        s.check(call, surface.as_raw(), vk::ObjectType::SURFACE_KHR)
+    });
+    *supported = if family == 0 { vk::TRUE } else { vk::FALSE };
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn get_physical_device_surface_capabilities(
+    physical: vk::PhysicalDevice,
+    surface: vk::SurfaceKHR,
+    capabilities: *mut vk::SurfaceCapabilitiesKHR,
+) -> vk::Result {
+    let call = "vkGetPhysicalDeviceSurfaceCapabilitiesKHR";
+    with(physical.as_raw(), |s| {
+        s.check(call, surface.as_raw(), vk::ObjectType::SURFACE_KHR)
+    });
+    // like headless surfaces elsewhere the swapchain decides the extent
+    *capabilities = vk::SurfaceCapabilitiesKHR {
+        min_image_count: 2,
+        max_image_count: 8,
+        current_extent: vk::Extent2D {
+            width: u32::MAX,
+            height: u32::MAX,
+        },
+        min_image_extent: vk::Extent2D {
+            width: 1,
+            height: 1,
+        },
+        max_image_extent: vk::Extent2D {
+            width: 16384,
+            height: 16384,
+        },
+        max_image_array_layers: 1,
+        supported_transforms: vk::SurfaceTransformFlagsKHR::IDENTITY,
+        current_transform: vk::SurfaceTransformFlagsKHR::IDENTITY,
+        supported_composite_alpha: vk::CompositeAlphaFlagsKHR::OPAQUE,
+        supported_usage_flags: vk::ImageUsageFlags::COLOR_ATTACHMENT
+            | vk::ImageUsageFlags::TRANSFER_SRC
+            | vk::ImageUsageFlags::TRANSFER_DST,
+    };
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn get_physical_device_surface_formats(
+    physical: vk::PhysicalDevice,
+    surface: vk::SurfaceKHR,
+    count: *mut u32,
+    formats: *mut vk::SurfaceFormatKHR,
+) -> vk::Result {
+    let call = "vkGetPhysicalDeviceSurfaceFormatsKHR";
+    with(physical.as_raw(), |s| {
+        s.check(call, surface.as_raw(), vk::ObjectType::SURFACE_KHR)
+    });
+    let available = [vk::Format::B8G8R8A8_SRGB, vk::Format::B8G8R8A8_UNORM].map(|format| {
+        vk::SurfaceFormatKHR {
+            format,
+            color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR,
+        }
+    });
+    fill(&available, count, formats)
+}
+
+unsafe extern "system" fn get_physical_device_surface_present_modes(
+    physical: vk::PhysicalDevice,
+    surface: vk::SurfaceKHR,
+    count: *mut u32,
+    modes: *mut vk::PresentModeKHR,
+) -> vk::Result {
+    let call = "vkGetPhysicalDeviceSurfacePresentModesKHR";
+    with(physical.as_raw(), |s| {
+        s.check(call, surface.as_raw(), vk::ObjectType::SURFACE_KHR)
+    });
+    let available = [vk::PresentModeKHR::FIFO, vk::PresentModeKHR::IMMEDIATE];
+    fill(&available, count, modes)
+}
+
+unsafe extern "system" fn create_swapchain(
+    device: vk::Device,
+    info: *const vk::SwapchainCreateInfoKHR,
+    _: *const vk::AllocationCallbacks,
+    swapchain: *mut vk::SwapchainKHR,
+) -> vk::Result {
+    let call = "vkCreateSwapchainKHR";
+    let info = &*info;
+    let handle = with(device.as_raw(), |s| {
+        s.check(call, info.surface.as_raw(), vk::ObjectType::SURFACE_KHR);
+        if !info.old_swapchain.is_null() {
+            s.check(
+                call,
+                info.old_swapchain.as_raw(),
+                vk::ObjectType::SWAPCHAIN_KHR,
+            );
+        }
+        let extent = info.image_extent;
+        if extent.width == 0 || extent.height == 0 {
+            s.error(format!("{}: the extent {:?} is empty.", call, extent));
+        }
+        if !(2..=8).contains(&info.min_image_count) {
+            s.error(format!(
+                "{}: {} images are out of the 2 to 8 the surface has.",
+                call, info.min_image_count
+            ));
+        }
+
+        let handle = s.create(
+            device.as_raw() as u64,
+            vk::ObjectType::SWAPCHAIN_KHR,
+            Kind::Swapchain {
+                images: vec![],
+                next: 0,
+            },
+        );
+        let images = (0..info.min_image_count.clamp(2, 8))
+            .map(|_| {
+                let kind = Kind::Image {
+                    extent: vk::Extent3D {
+                        width: extent.width,
+                        height: extent.height,
+                        depth: 1,
+                    },
+                    format: info.image_format,
+                    mip_levels: 1,
+                    layers: 1,
+                    usage: info.image_usage,
+                    bound: true,
+                };
+                s.create(handle, vk::ObjectType::IMAGE, kind)
+            })
+            .collect();
+        if let Some(Kind::Swapchain { images: i, .. }) =
+            s.get(call, handle, vk::ObjectType::SWAPCHAIN_KHR)
+        {
+            *i = images;
+        }
+        handle
+    });
+    *swapchain = vk::SwapchainKHR::from_raw(handle);
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn destroy_swapchain(
+    device: vk::Device,
+    swapchain: vk::SwapchainKHR,
+    _: *const vk::AllocationCallbacks,
+) {
+    with(device.as_raw(), |s| {
+        let handle = swapchain.as_raw();
+        if s.destroy(
+            "vkDestroySwapchainKHR",
+            handle,
+            vk::ObjectType::SWAPCHAIN_KHR,
+        )
+        .is_some()
+        {
+            s.remove(|o| o.owner == handle);
+        }
+    });
+}
+
+unsafe extern "system" fn get_swapchain_images(
+    device: vk::Device,
+    swapchain: vk::SwapchainKHR,
+    count: *mut u32,
+    images: *mut vk::Image,
+) -> vk::Result {
+    let call = "vkGetSwapchainImagesKHR";
+    let handles = with(device.as_raw(), |s| {
+        match s.get(call, swapchain.as_raw(), vk::ObjectType::SWAPCHAIN_KHR) {
+            Some(Kind::Swapchain { images, .. }) => images.clone(),
+            _ => vec![],
+        }
+    });
+    let handles = handles
+        .into_iter()
+        .map(vk::Image::from_raw)
+        .collect::<Vec<_>>();
+    fill(&handles, count, images)
+}
+
+unsafe extern "system" fn acquire_next_image(
+    device: vk::Device,
+    swapchain: vk::SwapchainKHR,
+    _timeout: u64,
+    semaphore: vk::Semaphore,
+    fence: vk::Fence,
+    index: *mut u32,
+) -> vk::Result {
+    let call = "vkAcquireNextImageKHR";
+    with(device.as_raw(), |s| {
+        if semaphore.is_null() && fence.is_null() {
+            s.error(format!(
+                "{}: neither a semaphore nor a fence is given.",
+                call
+            ));
+        }
+        if let Some(Kind::Swapchain { images, next }) =
+            s.get(call, swapchain.as_raw(), vk::ObjectType::SWAPCHAIN_KHR)
+        {
+            *index = *next as u32;
+            *next = (*next + 1) % images.len().max(1);
+        }
+        // the image is available right away
+        if !semaphore.is_null() {
+            match s.get(call, semaphore.as_raw(), vk::ObjectType::SEMAPHORE) {
+                Some(Kind::Semaphore {
+                    timeline: false,
+                    value,
+                }) if *value == 0 => *value = 1,
+                Some(_) => s.error(format!(
+                    "{}: semaphore {:#x} is a timeline or signaled already.",
+                    call,
+                    semaphore.as_raw()
+                )),
+                None => {}
+            }
+        }
+        if let Some(Kind::Fence { signaled }) = (!fence.is_null())
+            .then(|| s.get(call, fence.as_raw(), vk::ObjectType::FENCE))
+            .flatten()
+        {
+            *signaled = true;
+        }
+    });
+    vk::Result::SUCCESS
+}
+
+unsafe extern "system" fn queue_present(
+    queue: vk::Queue,
+    info: *const vk::PresentInfoKHR,
+) -> vk::Result {
+    let call = "vkQueuePresentKHR";
+    let info = &*info;
+    with(queue.as_raw(), |s| {
+        for semaphore in items(info.wait_semaphores, info.wait_semaphore_count) {
+            match s.get(call, semaphore.as_raw(), vk::ObjectType::SEMAPHORE) {
+                Some(Kind::Semaphore {
+                    timeline: false,
+                    value,
+                }) if *value == 1 => *value = 0,
+                Some(_) => s.error(format!(
+                    "{}: waits on a semaphore nothing signals ({:#x}).",
+                    call,
+                    semaphore.as_raw()
+                )),
+                None => {}
+            }
+        }
+        let swapchains = items(info.swapchains, info.swapchain_count);
+        let indices = items(info.image_indices, info.swapchain_count);
+        for (swapchain, index) in swapchains.iter().zip(indices) {
+            if let Some(Kind::Swapchain { images, .. }) =
+                s.get(call, swapchain.as_raw(), vk::ObjectType::SWAPCHAIN_KHR)
+            {
+                if *index as usize >= images.len() {
+                    let count = images.len();
+                    s.error(format!(
+                        "{}: image {} of a swapchain with {}.",
+                        call, index, count
+                    ));
+                }
+            }
+        }
+    });
+    vk::Result::SUCCESS
+}
+
+macro_rules! destroy {
+    ($($name:ident: $ty:ident => $call:literal;)*) => {$(
+        unsafe extern "system" fn $name(
+            device: vk::Device,
+            handle: vk::$ty,
+            _: *const vk::AllocationCallbacks,
+        ) {
+            with(device.as_raw(), |s| {
+                s.destroy($call, handle.as_raw(), vk::$ty::TYPE);
+            });
+        }
+    )*};
+}
+
+destroy! {
+    destroy_buffer: Buffer => "vkDestroyBuffer";
+    destroy_image: Image => "vkDestroyImage";
+    destroy_image_view: ImageView => "vkDestroyImageView";
+    free_memory: DeviceMemory => "vkFreeMemory";
+    destroy_sampler: Sampler => "vkDestroySampler";
+    destroy_shader_module: ShaderModule => "vkDestroyShaderModule";
+    destroy_descriptor_set_layout: DescriptorSetLayout => "vkDestroyDescriptorSetLayout";
+    destroy_pipeline_layout: PipelineLayout => "vkDestroyPipelineLayout";
+    destroy_render_pass: RenderPass => "vkDestroyRenderPass";
+    destroy_framebuffer: Framebuffer => "vkDestroyFramebuffer";
+    destroy_pipeline: Pipeline => "vkDestroyPipeline";
+    destroy_fence: Fence => "vkDestroyFence";
+    destroy_semaphore: Semaphore => "vkDestroySemaphore";
+    destroy_query_pool: QueryPool => "vkDestroyQueryPool";
+}
+
+// recorded commands
+
+fn record(call: &str, command_buffer: vk::CommandBuffer, command: TraceCommand) {
+    with(command_buffer.as_raw(), |s| {
+        s.record(call, command_buffer, command)
+    });
+}
+
+// the null driver records the commands
+macro_rules! null_commands {
+    ($($vk:literal $name:ident: $pfn:ident($command_buffer:ident $(, $arg:ident: $ty:ty)*) => $command:expr;)*) => {
+        $(unsafe extern "system" fn $name($command_buffer: vk::CommandBuffer $(, $arg: $ty)*) {
+            record($vk, $command_buffer, $command);
+        })*
+
+        fn recorded_proc_addr(name: &[u8]) -> vk::PFN_vkVoidFunction {
+            $(if name == $vk.as_bytes() {
+                return Some(unsafe { std::mem::transmute::<vk::$pfn, unsafe extern "system" fn()>($name) });
+            })*
+            None
+        }
+    };
+}
+
+recorded!(null_commands);
+
+// the entry points by name, typed so a wrong signature fails to build. commands that are
+// not here panic when called, e.g. the window surface ones
+macro_rules! commands {
+    ($name:expr; $($vk:literal => $f:ident: $ty:ident,)*) => {
+        match $name {
+            $($vk => Some(std::mem::transmute::<vk::$ty, unsafe extern "system" fn()>($f)),)*
+            _ => recorded_proc_addr($name),
+        }
+    };
+}
+
+pub(super) fn proc_addr(name: &[u8]) -> vk::PFN_vkVoidFunction {
+    unsafe {
+        commands! { name;
+            b"vkGetInstanceProcAddr" => get_instance_proc_addr: PFN_vkGetInstanceProcAddr,
+            b"vkGetDeviceProcAddr" => get_device_proc_addr: PFN_vkGetDeviceProcAddr,
+            b"vkEnumerateInstanceExtensionProperties" => enumerate_instance_extension_properties: PFN_vkEnumerateInstanceExtensionProperties,
+            b"vkEnumerateInstanceLayerProperties" => enumerate_instance_layer_properties: PFN_vkEnumerateInstanceLayerProperties,
+            b"vkCreateInstance" => create_instance: PFN_vkCreateInstance,
+            b"vkDestroyInstance" => destroy_instance: PFN_vkDestroyInstance,
+            b"vkEnumeratePhysicalDevices" => enumerate_physical_devices: PFN_vkEnumeratePhysicalDevices,
+            b"vkGetPhysicalDeviceProperties" => get_physical_device_properties: PFN_vkGetPhysicalDeviceProperties,
+            b"vkGetPhysicalDeviceFeatures" => get_physical_device_features: PFN_vkGetPhysicalDeviceFeatures,
+            b"vkGetPhysicalDeviceMemoryProperties" => get_physical_device_memory_properties: PFN_vkGetPhysicalDeviceMemoryProperties,
+            b"vkGetPhysicalDeviceFormatProperties" => get_physical_device_format_properties: PFN_vkGetPhysicalDeviceFormatProperties,
+            b"vkGetPhysicalDeviceQueueFamilyProperties" => get_physical_device_queue_family_properties: PFN_vkGetPhysicalDeviceQueueFamilyProperties,
+            b"vkEnumerateDeviceExtensionProperties" => enumerate_device_extension_properties: PFN_vkEnumerateDeviceExtensionProperties,
+            b"vkCreateDevice" => create_device: PFN_vkCreateDevice,
+            b"vkDestroyDevice" => destroy_device: PFN_vkDestroyDevice,
+            b"vkGetDeviceQueue" => get_device_queue: PFN_vkGetDeviceQueue,
+            b"vkDeviceWaitIdle" => device_wait_idle: PFN_vkDeviceWaitIdle,
+            b"vkQueueWaitIdle" => queue_wait_idle: PFN_vkQueueWaitIdle,
+            b"vkQueueSubmit" => queue_submit: PFN_vkQueueSubmit,
+            b"vkSetDebugUtilsObjectNameEXT" => set_debug_utils_object_name: PFN_vkSetDebugUtilsObjectNameEXT,
+            b"vkCreateBuffer" => create_buffer: PFN_vkCreateBuffer,
+            b"vkDestroyBuffer" => destroy_buffer: PFN_vkDestroyBuffer,
+            b"vkCreateImage" => create_image: PFN_vkCreateImage,
+            b"vkDestroyImage" => destroy_image: PFN_vkDestroyImage,
+            b"vkGetBufferMemoryRequirements" => get_buffer_memory_requirements: PFN_vkGetBufferMemoryRequirements,
+            b"vkGetImageMemoryRequirements" => get_image_memory_requirements: PFN_vkGetImageMemoryRequirements,
+            b"vkAllocateMemory" => allocate_memory: PFN_vkAllocateMemory,
+            b"vkFreeMemory" => free_memory: PFN_vkFreeMemory,
+            b"vkBindBufferMemory" => bind_buffer_memory: PFN_vkBindBufferMemory,
+            b"vkBindImageMemory" => bind_image_memory: PFN_vkBindImageMemory,
+            b"vkMapMemory" => map_memory: PFN_vkMapMemory,
+            b"vkUnmapMemory" => unmap_memory: PFN_vkUnmapMemory,
+            b"vkCreateImageView" => create_image_view: PFN_vkCreateImageView,
+            b"vkDestroyImageView" => destroy_image_view: PFN_vkDestroyImageView,
+            b"vkCreateSampler" => create_sampler: PFN_vkCreateSampler,
+            b"vkDestroySampler" => destroy_sampler: PFN_vkDestroySampler,
+            b"vkCreateShaderModule" => create_shader_module: PFN_vkCreateShaderModule,
+            b"vkDestroyShaderModule" => destroy_shader_module: PFN_vkDestroyShaderModule,
+            b"vkCreateDescriptorSetLayout" => create_descriptor_set_layout: PFN_vkCreateDescriptorSetLayout,
+            b"vkDestroyDescriptorSetLayout" => destroy_descriptor_set_layout: PFN_vkDestroyDescriptorSetLayout,
+            b"vkCreatePipelineLayout" => create_pipeline_layout: PFN_vkCreatePipelineLayout,
+            b"vkDestroyPipelineLayout" => destroy_pipeline_layout: PFN_vkDestroyPipelineLayout,
+            b"vkCreateRenderPass" => create_render_pass: PFN_vkCreateRenderPass,
+            b"vkDestroyRenderPass" => destroy_render_pass: PFN_vkDestroyRenderPass,
+            b"vkCreateFramebuffer" => create_framebuffer: PFN_vkCreateFramebuffer,
+            b"vkDestroyFramebuffer" => destroy_framebuffer: PFN_vkDestroyFramebuffer,
+            b"vkCreateGraphicsPipelines" => create_graphics_pipelines: PFN_vkCreateGraphicsPipelines,
+            b"vkCreateComputePipelines" => create_compute_pipelines: PFN_vkCreateComputePipelines,
+            b"vkDestroyPipeline" => destroy_pipeline: PFN_vkDestroyPipeline,
+            b"vkCreateDescriptorPool" => create_descriptor_pool: PFN_vkCreateDescriptorPool,
+            b"vkDestroyDescriptorPool" => destroy_descriptor_pool: PFN_vkDestroyDescriptorPool,
+            b"vkResetDescriptorPool" => reset_descriptor_pool: PFN_vkResetDescriptorPool,
+            b"vkAllocateDescriptorSets" => allocate_descriptor_sets: PFN_vkAllocateDescriptorSets,
+            b"vkFreeDescriptorSets" => free_descriptor_sets: PFN_vkFreeDescriptorSets,
+            b"vkUpdateDescriptorSets" => update_descriptor_sets: PFN_vkUpdateDescriptorSets,
+            b"vkCreateCommandPool" => create_command_pool: PFN_vkCreateCommandPool,
+            b"vkDestroyCommandPool" => destroy_command_pool: PFN_vkDestroyCommandPool,
+            b"vkResetCommandPool" => reset_command_pool: PFN_vkResetCommandPool,
+            b"vkAllocateCommandBuffers" => allocate_command_buffers: PFN_vkAllocateCommandBuffers,
+            b"vkFreeCommandBuffers" => free_command_buffers: PFN_vkFreeCommandBuffers,
+            b"vkBeginCommandBuffer" => begin_command_buffer: PFN_vkBeginCommandBuffer,
+            b"vkEndCommandBuffer" => end_command_buffer: PFN_vkEndCommandBuffer,
+            b"vkCreateFence" => create_fence: PFN_vkCreateFence,
+            b"vkDestroyFence" => destroy_fence: PFN_vkDestroyFence,
+            b"vkWaitForFences" => wait_for_fences: PFN_vkWaitForFences,
+            b"vkResetFences" => reset_fences: PFN_vkResetFences,
+            b"vkGetFenceStatus" => get_fence_status: PFN_vkGetFenceStatus,
+            b"vkCreateSemaphore" => create_semaphore: PFN_vkCreateSemaphore,
+            b"vkDestroySemaphore" => destroy_semaphore: PFN_vkDestroySemaphore,
+            b"vkGetSemaphoreCounterValue" => get_semaphore_counter_value: PFN_vkGetSemaphoreCounterValue,
+            b"vkGetSemaphoreCounterValueKHR" => get_semaphore_counter_value: PFN_vkGetSemaphoreCounterValue,
+            b"vkWaitSemaphores" => wait_semaphores: PFN_vkWaitSemaphores,
+            b"vkWaitSemaphoresKHR" => wait_semaphores: PFN_vkWaitSemaphores,
+            b"vkCreateQueryPool" => create_query_pool: PFN_vkCreateQueryPool,
+            b"vkDestroyQueryPool" => destroy_query_pool: PFN_vkDestroyQueryPool,
+            b"vkResetQueryPool" => reset_query_pool: PFN_vkResetQueryPool,
+            b"vkResetQueryPoolEXT" => reset_query_pool: PFN_vkResetQueryPool,
+            b"vkCreateHeadlessSurfaceEXT" => create_headless_surface: PFN_vkCreateHeadlessSurfaceEXT,
+            b"vkDestroySurfaceKHR" => destroy_surface: PFN_vkDestroySurfaceKHR,
+            b"vkGetPhysicalDeviceSurfaceSupportKHR" => get_physical_device_surface_support: PFN_vkGetPhysicalDeviceSurfaceSupportKHR,
+            b"vkGetPhysicalDeviceSurfaceCapabilitiesKHR" => get_physical_device_surface_capabilities: PFN_vkGetPhysicalDeviceSurfaceCapabilitiesKHR,
+            b"vkGetPhysicalDeviceSurfaceFormatsKHR" => get_physical_device_surface_formats: PFN_vkGetPhysicalDeviceSurfaceFormatsKHR,
+            b"vkGetPhysicalDeviceSurfacePresentModesKHR" => get_physical_device_surface_present_modes: PFN_vkGetPhysicalDeviceSurfacePresentModesKHR,
+            b"vkCreateSwapchainKHR" => create_swapchain: PFN_vkCreateSwapchainKHR,
+            b"vkDestroySwapchainKHR" => destroy_swapchain: PFN_vkDestroySwapchainKHR,
+            b"vkGetSwapchainImagesKHR" => get_swapchain_images: PFN_vkGetSwapchainImagesKHR,
+            b"vkAcquireNextImageKHR" => acquire_next_image: PFN_vkAcquireNextImageKHR,
+            b"vkQueuePresentKHR" => queue_present: PFN_vkQueuePresentKHR,
+        }
+    }
+}
+
+// the bytes of a query result or availability
+fn query_word(flags: vk::QueryResultFlags) -> u64 {
+    if flags.contains(vk::QueryResultFlags::_64) {
+        8
+    } else {
+        4
+    }
+}
+
+// the bytes of an index, none for types the driver doesn't support
+fn index_stride(index_type: vk::IndexType) -> Option<u64> {
+    match index_type {
+        vk::IndexType::UINT16 => Some(2),
+        vk::IndexType::UINT32 => Some(4),
+        _ => None,
+    }
+}
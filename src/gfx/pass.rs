@@ -0,0 +1,815 @@
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use anyhow::Result;
+use tracing::debug;
+use vulkanalia::prelude::v1_0::*;
+
+use super::{check_render_pass, check_viewports, TextureView};
+
+/// What a pass does with the previous contents of an attachment, clearing it to the value,
+/// keeping them or leaving them undefined when every pixel is drawn anyway.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LoadOp<T> {
+    Clear(T),
+    Load,
+    DontCare,
+}
+
+impl<T> LoadOp<T> {
+    pub fn vk(&self) -> vk::AttachmentLoadOp {
+        match self {
+            LoadOp::Clear(_) => vk::AttachmentLoadOp::CLEAR,
+            LoadOp::Load => vk::AttachmentLoadOp::LOAD,
+            LoadOp::DontCare => vk::AttachmentLoadOp::DONT_CARE,
+        }
+    }
+}
+
+/// The values a depth stencil attachment is cleared to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DepthStencilClear {
+    pub depth: f32,
+    pub stencil: u32,
+}
+
+impl Default for DepthStencilClear {
+    fn default() -> Self {
+        Self {
+            depth: 1.0,
+            stencil: 0,
+        }
+    }
+}
+
+/// The load ops of the color and depth stencil attachments of a scene pass, by default both
+/// are cleared, color to opaque black and depth to the far plane.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PassLoad {
+    pub color: LoadOp<[f32; 4]>,
+    pub depth_stencil: LoadOp<DepthStencilClear>,
+}
+
+impl PassLoad {
+    /// clear color to `color` and depth to `depth`, an attachment without a value keeps its
+    /// previous contents. the stencil is cleared to zero with the depth.
+    pub fn new(color: Option<[f32; 4]>, depth: Option<f32>) -> Self {
+        Self {
+            color: color.map_or(LoadOp::Load, LoadOp::Clear),
+            depth_stencil: depth.map_or(LoadOp::Load, |depth| {
+                LoadOp::Clear(DepthStencilClear { depth, stencil: 0 })
+            }),
+        }
+    }
+
+    /// clear color to `color` and depth as by default.
+    pub fn clear(color: [f32; 4]) -> Self {
+        Self {
+            color: LoadOp::Clear(color),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_depth_stencil(mut self, depth_stencil: LoadOp<DepthStencilClear>) -> Self {
+        self.depth_stencil = depth_stencil;
+        self
+    }
+
+    /// the load ops of the color and depth stencil attachment, passes differing only in the
+    /// values share a render pass.
+    pub fn ops(&self) -> [vk::AttachmentLoadOp; 2] {
+        [self.color.vk(), self.depth_stencil.vk()]
+    }
+
+    /// whether an attachment keeps its previous contents.
+    pub fn loads(&self) -> bool {
+        self.color == LoadOp::Load || self.depth_stencil == LoadOp::Load
+    }
+
+    /// the clear values of the color and depth stencil attachment, zero when not cleared.
+    pub fn clear_values(&self) -> [vk::ClearValue; 2] {
+        let color = match self.color {
+            LoadOp::Clear(color) => color,
+            _ => [0.0; 4],
+        };
+        let depth_stencil = match self.depth_stencil {
+            LoadOp::Clear(clear) => clear,
+            _ => DepthStencilClear {
+                depth: 0.0,
+                stencil: 0,
+            },
+        };
+        [
+            vk::ClearValue {
+                color: vk::ClearColorValue { float32: color },
+            },
+            vk::ClearValue {
+                depth_stencil: vk::ClearDepthStencilValue {
+                    depth: depth_stencil.depth,
+                    stencil: depth_stencil.stencil,
+                },
+            },
+        ]
+    }
+}
+
+impl Default for PassLoad {
+    fn default() -> Self {
+        Self {
+            color: LoadOp::Clear([0.0, 0.0, 0.0, 1.0]),
+            depth_stencil: LoadOp::Clear(DepthStencilClear::default()),
+        }
+    }
+}
+
+/// A color attachment of a pass, the view drawn into and what happens to its contents. it
+/// ends in the shader read only layout so a later pass can sample it, or its resolve target
+/// when multisampled.
+#[derive(Copy, Clone, Debug)]
+pub struct ColorAttachment {
+    pub view: vk::ImageView,
+    pub format: vk::Format,
+    pub samples: vk::SampleCountFlags,
+    pub load: LoadOp<[f32; 4]>,
+    pub store: vk::AttachmentStoreOp,
+    /// the single sampled view the attachment is resolved into at the end of the pass.
+    pub resolve: Option<vk::ImageView>,
+}
+
+impl ColorAttachment {
+    /// a single sampled attachment cleared to opaque black and stored.
+    pub fn new(view: &TextureView, format: vk::Format) -> Self {
+        Self {
+            view: view.view,
+            format,
+            samples: vk::SampleCountFlags::_1,
+            load: LoadOp::Clear([0.0, 0.0, 0.0, 1.0]),
+            store: vk::AttachmentStoreOp::STORE,
+            resolve: None,
+        }
+    }
+
+    /// draw with `samples` per pixel and resolve into `resolve`, of the same format.
+    pub fn multisampled(mut self, samples: vk::SampleCountFlags, resolve: &TextureView) -> Self {
+        self.samples = samples;
+        self.resolve = Some(resolve.view);
+        self
+    }
+
+    pub fn with_load(mut self, load: LoadOp<[f32; 4]>) -> Self {
+        self.load = load;
+        self
+    }
+
+    /// leave the contents undefined after the pass, for attachments only the resolve of is read.
+    pub fn discard(mut self) -> Self {
+        self.store = vk::AttachmentStoreOp::DONT_CARE;
+        self
+    }
+
+    // the multisampled contents are only kept for the next pass when resolved
+    fn final_layout(&self) -> vk::ImageLayout {
+        match self.resolve {
+            Some(_) => vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+            None => vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        }
+    }
+}
+
+/// The depth stencil attachment of a pass, it ends in the depth read only layout.
+#[derive(Copy, Clone, Debug)]
+pub struct DepthAttachment {
+    pub view: vk::ImageView,
+    pub format: vk::Format,
+    pub samples: vk::SampleCountFlags,
+    pub load: LoadOp<DepthStencilClear>,
+    pub store: vk::AttachmentStoreOp,
+}
+
+impl DepthAttachment {
+    /// a single sampled attachment cleared to the far plane and stored.
+    pub fn new(view: &TextureView, format: vk::Format) -> Self {
+        Self {
+            view: view.view,
+            format,
+            samples: vk::SampleCountFlags::_1,
+            load: LoadOp::Clear(DepthStencilClear::default()),
+            store: vk::AttachmentStoreOp::STORE,
+        }
+    }
+
+    pub fn with_samples(mut self, samples: vk::SampleCountFlags) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    pub fn with_load(mut self, load: LoadOp<DepthStencilClear>) -> Self {
+        self.load = load;
+        self
+    }
+
+    pub fn discard(mut self) -> Self {
+        self.store = vk::AttachmentStoreOp::DONT_CARE;
+        self
+    }
+}
+
+/// A subpass of a pass, the color attachments it draws into and those it reads as input
+/// attachments, by their index in the descriptor. shaders read the inputs in order with
+/// `input_attachment_index`, the depth stencil attachment comes after the colors.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Subpass {
+    pub color: Vec<u32>,
+    pub input: Vec<u32>,
+    /// whether draws test the depth stencil attachment, and write it unless it is an input.
+    pub depth_stencil: bool,
+    pub depth_input: bool,
+}
+
+impl Subpass {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn color(mut self, attachment: u32) -> Self {
+        self.color.push(attachment);
+        self
+    }
+
+    /// read the color attachment an earlier subpass drew, at the pixel being shaded.
+    pub fn input(mut self, attachment: u32) -> Self {
+        self.input.push(attachment);
+        self
+    }
+
+    pub fn depth_stencil(mut self) -> Self {
+        self.depth_stencil = true;
+        self
+    }
+
+    /// read the depth an earlier subpass drew, testing against it stays possible.
+    pub fn depth_input(mut self) -> Self {
+        self.depth_input = true;
+        self
+    }
+
+    // the attachment indices the subpass uses, the depth stencil one is `colors`
+    fn uses(&self, colors: u32) -> impl Iterator<Item = u32> + '_ {
+        let depth = (self.depth_stencil || self.depth_input).then_some(colors);
+        self.color
+            .iter()
+            .chain(self.input.iter())
+            .copied()
+            .chain(depth)
+    }
+}
+
+/// The attachments a pass draws into, any views rather than the scene target. passes with
+/// the same formats, samples and ops share a render pass, see `RenderPassCache`.
+#[derive(Clone, Debug)]
+pub struct RenderPassDescriptor {
+    pub extent: vk::Extent2D,
+    pub color: Vec<ColorAttachment>,
+    pub depth_stencil: Option<DepthAttachment>,
+    /// the layers every draw goes to at once, 0 draws to the first layer only.
+    pub view_mask: u32,
+    /// the subpasses in the order they run, none is a single one using every attachment.
+    pub subpasses: Vec<Subpass>,
+}
+
+impl RenderPassDescriptor {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            extent: vk::Extent2D { width, height },
+            color: vec![],
+            depth_stencil: None,
+            view_mask: 0,
+            subpasses: vec![],
+        }
+    }
+
+    /// run `subpass` after the ones added before, e.g. filling a g-buffer and then lighting
+    /// it. `Encoder::next_subpass` moves on, pipelines are made for one subpass.
+    pub fn subpass(mut self, subpass: Subpass) -> Self {
+        self.subpasses.push(subpass);
+        self
+    }
+
+    /// the subpasses the render pass has, the single default one when none were added.
+    pub fn all_subpasses(&self) -> Vec<Subpass> {
+        if !self.subpasses.is_empty() {
+            return self.subpasses.clone();
+        }
+        vec![Subpass {
+            color: (0..self.color.len() as u32).collect(),
+            depth_stencil: self.depth_stencil.is_some(),
+            ..Default::default()
+        }]
+    }
+
+    /// draw to the attachment layers in `view_mask` at once, shaders tell them apart by
+    /// `gl_ViewIndex`. the views must be array views with a layer for each view.
+    pub fn multiview(mut self, view_mask: u32) -> Self {
+        self.view_mask = view_mask;
+        self
+    }
+
+    pub fn color(mut self, attachment: ColorAttachment) -> Self {
+        self.color.push(attachment);
+        self
+    }
+
+    pub fn depth_stencil(mut self, attachment: DepthAttachment) -> Self {
+        self.depth_stencil = Some(attachment);
+        self
+    }
+
+    // what the render pass depends on, the views only matter to the framebuffer
+    fn key(&self) -> RenderPassKey {
+        RenderPassKey {
+            color: self
+                .color
+                .iter()
+                .map(|a| {
+                    (
+                        a.format,
+                        a.samples,
+                        a.load.vk(),
+                        a.store,
+                        a.resolve.is_some(),
+                    )
+                })
+                .collect(),
+            depth_stencil: self
+                .depth_stencil
+                .map(|a| (a.format, a.samples, a.load.vk(), a.store)),
+            view_mask: self.view_mask,
+            subpasses: self.all_subpasses(),
+        }
+    }
+
+    // colors, then depth stencil, then the resolves
+    fn views(&self) -> Vec<vk::ImageView> {
+        self.color
+            .iter()
+            .map(|a| a.view)
+            .chain(self.depth_stencil.iter().map(|a| a.view))
+            .chain(self.color.iter().filter_map(|a| a.resolve))
+            .collect()
+    }
+
+    /// the clear values by attachment, zero for attachments that are not cleared.
+    pub fn clear_values(&self) -> Vec<vk::ClearValue> {
+        let color = self.color.iter().map(|a| {
+            let color = match a.load {
+                LoadOp::Clear(color) => color,
+                _ => [0.0; 4],
+            };
+            vk::ClearValue {
+                color: vk::ClearColorValue { float32: color },
+            }
+        });
+        let depth_stencil = self.depth_stencil.iter().map(|a| {
+            let clear = match a.load {
+                LoadOp::Clear(clear) => clear,
+                _ => DepthStencilClear {
+                    depth: 0.0,
+                    stencil: 0,
+                },
+            };
+            vk::ClearValue {
+                depth_stencil: vk::ClearDepthStencilValue {
+                    depth: clear.depth,
+                    stencil: clear.stencil,
+                },
+            }
+        });
+        color.chain(depth_stencil).collect()
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct RenderPassKey {
+    color: Vec<(
+        vk::Format,
+        vk::SampleCountFlags,
+        vk::AttachmentLoadOp,
+        vk::AttachmentStoreOp,
+        bool,
+    )>,
+    depth_stencil: Option<(
+        vk::Format,
+        vk::SampleCountFlags,
+        vk::AttachmentLoadOp,
+        vk::AttachmentStoreOp,
+    )>,
+    view_mask: u32,
+    subpasses: Vec<Subpass>,
+}
+
+// the views and size of a framebuffer of a render pass
+type FramebufferKey = (vk::RenderPass, Vec<vk::ImageView>, (u32, u32));
+
+/// The render passes and framebuffers of the passes drawn through `RenderPassDescriptor`s.
+/// render passes live as long as the cache, framebuffers until a view of theirs is released
+/// with `release_view`, which must happen before the view is destroyed.
+pub struct RenderPassCache {
+    // whether the device enabled `VK_KHR_multiview`
+    multiview: bool,
+    render_passes: RefCell<HashMap<RenderPassKey, vk::RenderPass>>,
+    framebuffers: RefCell<HashMap<FramebufferKey, vk::Framebuffer>>,
+}
+
+impl RenderPassCache {
+    pub fn new(multiview: bool) -> Self {
+        Self {
+            multiview,
+            render_passes: RefCell::new(HashMap::new()),
+            framebuffers: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// whether descriptors with view masks can be drawn.
+    pub fn multiview(&self) -> bool {
+        self.multiview
+    }
+
+    /// the render pass of `descriptor`, created when its formats and ops are first seen.
+    /// pipelines created with it can draw in any pass with the same formats and samples.
+    pub unsafe fn render_pass(
+        &self,
+        device: &vulkanalia::Device,
+        descriptor: &RenderPassDescriptor,
+    ) -> Result<vk::RenderPass> {
+        check_render_pass(descriptor, self.multiview)?;
+        let key = descriptor.key();
+        if let Some(render_pass) = self.render_passes.borrow().get(&key) {
+            return Ok(*render_pass);
+        }
+
+        debug!(colors = descriptor.color.len(), "create render pass");
+        let render_pass = create_render_pass(device, descriptor)?;
+        self.render_passes.borrow_mut().insert(key, render_pass);
+        Ok(render_pass)
+    }
+
+    /// the framebuffer of the views of `descriptor`.
+    pub unsafe fn framebuffer(
+        &self,
+        device: &vulkanalia::Device,
+        descriptor: &RenderPassDescriptor,
+    ) -> Result<vk::Framebuffer> {
+        let render_pass = self.render_pass(device, descriptor)?;
+        let extent = descriptor.extent;
+        let key = (
+            render_pass,
+            descriptor.views(),
+            (extent.width, extent.height),
+        );
+        if let Some(framebuffer) = self.framebuffers.borrow().get(&key) {
+            return Ok(*framebuffer);
+        }
+
+        let info = vk::FramebufferCreateInfo::builder()
+            .render_pass(render_pass)
+            .attachments(&key.1)
+            .width(extent.width)
+            .height(extent.height)
+            .layers(1);
+        let framebuffer = device.create_framebuffer(&info, None)?;
+        self.framebuffers.borrow_mut().insert(key, framebuffer);
+        Ok(framebuffer)
+    }
+
+    /// begin the pass of `descriptor` over all of its extent, its first subpass is recorded
+    /// as `contents` says.
+    pub unsafe fn begin(
+        &self,
+        device: &vulkanalia::Device,
+        command_buffer: vk::CommandBuffer,
+        descriptor: &RenderPassDescriptor,
+        contents: vk::SubpassContents,
+    ) -> Result<vk::RenderPass> {
+        let render_pass = self.render_pass(device, descriptor)?;
+        let framebuffer = self.framebuffer(device, descriptor)?;
+        let clear_values = descriptor.clear_values();
+        let info = vk::RenderPassBeginInfo::builder()
+            .render_pass(render_pass)
+            .framebuffer(framebuffer)
+            .render_area(vk::Rect2D {
+                offset: vk::Offset2D::default(),
+                extent: descriptor.extent,
+            })
+            .clear_values(&clear_values);
+
+        debug!(extent = ?descriptor.extent, "begin render pass");
+        device.cmd_begin_render_pass(command_buffer, &info, contents);
+        Ok(render_pass)
+    }
+
+    /// destroy the framebuffers drawing into `view`, frames using them must be complete.
+    pub unsafe fn release_view(&self, device: &vulkanalia::Device, view: vk::ImageView) {
+        self.framebuffers
+            .borrow_mut()
+            .retain(|(_, views, _), framebuffer| {
+                let keep = !views.contains(&view);
+                if !keep {
+                    device.destroy_framebuffer(*framebuffer, None);
+                }
+                keep
+            });
+    }
+
+    /// the number of distinct render passes and framebuffers created.
+    pub fn counts(&self) -> (usize, usize) {
+        (
+            self.render_passes.borrow().len(),
+            self.framebuffers.borrow().len(),
+        )
+    }
+
+    pub unsafe fn destroy(&self, device: &vulkanalia::Device) {
+        self.framebuffers
+            .borrow_mut()
+            .drain()
+            .for_each(|(_, f)| device.destroy_framebuffer(f, None));
+        self.render_passes
+            .borrow_mut()
+            .drain()
+            .for_each(|(_, p)| device.destroy_render_pass(p, None));
+    }
+}
+
+/// set the viewports and scissors from index `first` on, one scissor per viewport. indices
+/// past 0 need the multiViewport feature, `max_viewports` is `AdapterLimits::max_viewports`.
+pub unsafe fn set_viewports(
+    device: &vulkanalia::Device,
+    command_buffer: vk::CommandBuffer,
+    first: u32,
+    viewports: &[(vk::Viewport, vk::Rect2D)],
+    max_viewports: u32,
+) -> Result<()> {
+    check_viewports(first, viewports.len() as u32, max_viewports)?;
+    let (viewports, scissors): (Vec<_>, Vec<_>) = viewports.iter().copied().unzip();
+    device.cmd_set_viewport(command_buffer, first, &viewports);
+    device.cmd_set_scissor(command_buffer, first, &scissors);
+    Ok(())
+}
+
+// loaded attachments are in the layout the previous pass left them in
+fn initial_layout<T>(load: &LoadOp<T>, layout: vk::ImageLayout) -> vk::ImageLayout {
+    match load {
+        LoadOp::Load => layout,
+        _ => vk::ImageLayout::UNDEFINED,
+    }
+}
+
+unsafe fn create_render_pass(
+    device: &vulkanalia::Device,
+    descriptor: &RenderPassDescriptor,
+) -> Result<vk::RenderPass> {
+    let colors = descriptor.color.len() as u32;
+    let depth = descriptor.depth_stencil.iter().count() as u32;
+
+    // Attachments
+
+    let mut attachments = descriptor
+        .color
+        .iter()
+        .map(|a| {
+            vk::AttachmentDescription::builder()
+                .format(a.format)
+                .samples(a.samples)
+                .load_op(a.load.vk())
+                .store_op(a.store)
+                .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+                .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+                .initial_layout(initial_layout(&a.load, a.final_layout()))
+                .final_layout(a.final_layout())
+                .build()
+        })
+        .collect::<Vec<_>>();
+    if let Some(a) = &descriptor.depth_stencil {
+        let layout = vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL;
+        attachments.push(
+            vk::AttachmentDescription::builder()
+                .format(a.format)
+                .samples(a.samples)
+                .load_op(a.load.vk())
+                .store_op(a.store)
+                .stencil_load_op(a.load.vk())
+                .stencil_store_op(a.store)
+                .initial_layout(initial_layout(&a.load, layout))
+                .final_layout(layout)
+                .build(),
+        );
+    }
+    for a in descriptor.color.iter().filter(|a| a.resolve.is_some()) {
+        attachments.push(
+            vk::AttachmentDescription::builder()
+                .format(a.format)
+                .samples(vk::SampleCountFlags::_1)
+                .load_op(vk::AttachmentLoadOp::DONT_CARE)
+                .store_op(vk::AttachmentStoreOp::STORE)
+                .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+                .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+                .initial_layout(vk::ImageLayout::UNDEFINED)
+                .final_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                .build(),
+        );
+    }
+
+    // Subpasses
+
+    let subpasses = descriptor.all_subpasses();
+    let last = subpasses.len() - 1;
+
+    // resolves follow the depth stencil attachment in the order of their colors
+    let mut next_resolve = colors + depth;
+    let resolves = descriptor
+        .color
+        .iter()
+        .map(|a| {
+            a.resolve.map(|_| {
+                next_resolve += 1;
+                next_resolve - 1
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let reference = |attachment, layout| {
+        vk::AttachmentReference::builder()
+            .attachment(attachment)
+            .layout(layout)
+            .build()
+    };
+    let references = subpasses
+        .iter()
+        .enumerate()
+        .map(|(i, subpass)| {
+            let color = subpass
+                .color
+                .iter()
+                .map(|c| reference(*c, vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL))
+                .collect::<Vec<_>>();
+
+            // multisampled colors are resolved by the last subpass drawing them
+            let resolve = subpass
+                .color
+                .iter()
+                .map(|c| {
+                    let later = subpasses[i + 1..].iter().any(|s| s.color.contains(c));
+                    let attachment = match resolves[*c as usize] {
+                        Some(resolve) if !later => resolve,
+                        _ => vk::ATTACHMENT_UNUSED,
+                    };
+                    reference(attachment, vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+                })
+                .collect::<Vec<_>>();
+
+            // depth read as an input can't be written at the same time
+            let depth_layout = if subpass.depth_input {
+                vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL
+            } else {
+                vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL
+            };
+            let mut input = subpass
+                .input
+                .iter()
+                .map(|c| reference(*c, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL))
+                .collect::<Vec<_>>();
+            if subpass.depth_input {
+                input.push(reference(colors, depth_layout));
+            }
+
+            // attachments drawn before and used after must survive the subpasses between
+            let preserve = (0..colors + depth)
+                .filter(|a| {
+                    let used = |s: &Subpass| s.uses(colors).any(|u| u == *a);
+                    !used(subpass)
+                        && subpasses[..i].iter().any(used)
+                        && subpasses[i + 1..].iter().any(used)
+                })
+                .collect::<Vec<_>>();
+
+            (
+                color,
+                resolve,
+                input,
+                reference(colors, depth_layout),
+                preserve,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let descriptions = subpasses
+        .iter()
+        .zip(&references)
+        .map(
+            |(subpass, (color, resolve, input, depth_stencil, preserve))| {
+                let mut description = vk::SubpassDescription::builder()
+                    .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+                    .color_attachments(color)
+                    .input_attachments(input)
+                    .preserve_attachments(preserve);
+                if resolve
+                    .iter()
+                    .any(|r| r.attachment != vk::ATTACHMENT_UNUSED)
+                {
+                    description = description.resolve_attachments(resolve);
+                }
+                if subpass.depth_stencil {
+                    description = description.depth_stencil_attachment(depth_stencil);
+                }
+                description.build()
+            },
+        )
+        .collect::<Vec<_>>();
+
+    // Dependencies
+
+    // earlier reads of the attachments must finish before they are written again
+    let dependency = vk::SubpassDependency::builder()
+        .src_subpass(vk::SUBPASS_EXTERNAL)
+        .dst_subpass(0)
+        .src_stage_mask(
+            vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+                | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS
+                | vk::PipelineStageFlags::FRAGMENT_SHADER
+                | vk::PipelineStageFlags::COMPUTE_SHADER,
+        )
+        .src_access_mask(vk::AccessFlags::empty())
+        .dst_stage_mask(
+            vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+                | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS,
+        )
+        .dst_access_mask(
+            vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+                | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+        )
+        .build();
+
+    // each subpass sees what the one before drew, at the same pixel
+    let drawn = vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+        | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS;
+    let writes =
+        vk::AccessFlags::COLOR_ATTACHMENT_WRITE | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE;
+    let between = (1..subpasses.len() as u32).map(|i| {
+        vk::SubpassDependency::builder()
+            .src_subpass(i - 1)
+            .dst_subpass(i)
+            .src_stage_mask(drawn)
+            .src_access_mask(writes)
+            .dst_stage_mask(
+                vk::PipelineStageFlags::FRAGMENT_SHADER
+                    | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS
+                    | vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+            )
+            .dst_access_mask(
+                writes
+                    | vk::AccessFlags::INPUT_ATTACHMENT_READ
+                    | vk::AccessFlags::COLOR_ATTACHMENT_READ
+                    | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ,
+            )
+            .dependency_flags(vk::DependencyFlags::BY_REGION)
+            .build()
+    });
+
+    // the attachments are sampled by later passes
+    let read_dependency = vk::SubpassDependency::builder()
+        .src_subpass(last as u32)
+        .dst_subpass(vk::SUBPASS_EXTERNAL)
+        .src_stage_mask(drawn)
+        .src_access_mask(writes)
+        .dst_stage_mask(
+            vk::PipelineStageFlags::FRAGMENT_SHADER | vk::PipelineStageFlags::COMPUTE_SHADER,
+        )
+        .dst_access_mask(vk::AccessFlags::SHADER_READ)
+        .build();
+
+    // Views, all of them are correlated as they see the same scene
+
+    let view_masks = vec![descriptor.view_mask; subpasses.len()];
+    let correlation_masks = &[descriptor.view_mask];
+    let mut multiview = vk::RenderPassMultiviewCreateInfo::builder()
+        .view_masks(&view_masks)
+        .correlation_masks(correlation_masks);
+
+    // Create
+
+    let dependencies = std::iter::once(dependency)
+        .chain(between)
+        .chain(std::iter::once(read_dependency))
+        .collect::<Vec<_>>();
+    let mut info = vk::RenderPassCreateInfo::builder()
+        .attachments(&attachments)
+        .subpasses(&descriptions)
+        .dependencies(&dependencies);
+    if descriptor.view_mask != 0 {
+        info = info.push_next(&mut multiview);
+    }
+
+    Ok(device.create_render_pass(&info, None)?)
+}
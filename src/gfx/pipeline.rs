@@ -0,0 +1,404 @@
+#![allow(dead_code)]
+
+use anyhow::Result;
+use vulkanalia::prelude::v1_0::*;
+
+use super::{
+    check_render_pipeline, vertex_input_descriptions, BackgroundTask, DescriptionError, Device,
+    RenderPassDescriptor, Shader, VertexBufferLayout,
+};
+
+/// How the colors a fragment shader writes are combined with the attachment.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Blend {
+    /// the written color replaces the attachment.
+    Replace,
+    /// straight alpha, the color is weighted by its alpha.
+    Alpha,
+    /// the color was multiplied by its alpha in the shader.
+    Premultiplied,
+    /// the color is added to the attachment, for glows and sparks.
+    Additive,
+}
+
+impl Blend {
+    /// the blend state of a color attachment.
+    pub fn attachment(&self) -> vk::PipelineColorBlendAttachmentState {
+        let (src, dst) = match self {
+            Blend::Replace => (vk::BlendFactor::ONE, vk::BlendFactor::ZERO),
+            Blend::Alpha => (
+                vk::BlendFactor::SRC_ALPHA,
+                vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+            ),
+            Blend::Premultiplied => (vk::BlendFactor::ONE, vk::BlendFactor::ONE_MINUS_SRC_ALPHA),
+            Blend::Additive => (vk::BlendFactor::ONE, vk::BlendFactor::ONE),
+        };
+        let dst_alpha = match self {
+            Blend::Additive => vk::BlendFactor::ONE,
+            _ => dst,
+        };
+
+        vk::PipelineColorBlendAttachmentState::builder()
+            .color_write_mask(vk::ColorComponentFlags::all())
+            .blend_enable(*self != Blend::Replace)
+            .src_color_blend_factor(src)
+            .dst_color_blend_factor(dst)
+            .color_blend_op(vk::BlendOp::ADD)
+            .src_alpha_blend_factor(vk::BlendFactor::ONE)
+            .dst_alpha_blend_factor(dst_alpha)
+            .alpha_blend_op(vk::BlendOp::ADD)
+            .build()
+    }
+}
+
+/// The render pass and subpass a pipeline draws in and what it knows of the attachments of
+/// the subpass, the build checks the pipeline state against them.
+#[derive(Copy, Clone, Debug)]
+pub struct PipelineTarget {
+    pub render_pass: vk::RenderPass,
+    pub subpass: u32,
+    pub samples: vk::SampleCountFlags,
+    pub color_attachments: u32,
+    pub depth: bool,
+    /// whether the subpass reads depth as an input, so it can't be written.
+    pub depth_read_only: bool,
+}
+
+impl PipelineTarget {
+    /// the scene pass of the device, one color attachment and depth.
+    pub fn scene(device: &Device) -> Self {
+        Self {
+            render_pass: device.render_pass(),
+            subpass: 0,
+            samples: device.samples(),
+            color_attachments: 1,
+            depth: true,
+            depth_read_only: false,
+        }
+    }
+
+    /// the first subpass of a pass from `render_pass_for(descriptor)`.
+    pub fn new(render_pass: vk::RenderPass, descriptor: &RenderPassDescriptor) -> Self {
+        Self::for_subpass(render_pass, descriptor, 0).expect("passes have a first subpass")
+    }
+
+    /// subpass `subpass` of a pass from `render_pass_for(descriptor)`.
+    pub fn for_subpass(
+        render_pass: vk::RenderPass,
+        descriptor: &RenderPassDescriptor,
+        subpass: u32,
+    ) -> Result<Self, DescriptionError> {
+        let subpasses = descriptor.all_subpasses();
+        let Some(s) = subpasses.get(subpass as usize) else {
+            return Err(DescriptionError::UnknownSubpass(subpass));
+        };
+        let samples = descriptor
+            .color
+            .iter()
+            .map(|a| a.samples)
+            .chain(descriptor.depth_stencil.iter().map(|a| a.samples))
+            .next()
+            .unwrap_or(vk::SampleCountFlags::_1);
+
+        Ok(Self {
+            render_pass,
+            subpass,
+            samples,
+            color_attachments: s.color.len() as u32,
+            depth: s.depth_stencil,
+            depth_read_only: s.depth_input,
+        })
+    }
+}
+
+/// A graphics pipeline described by what differs from the defaults: triangle lists without
+/// vertex buffers, back faces culled with counter clockwise fronts, no depth, colors replaced,
+/// and a dynamic viewport and scissor.
+/// `RenderPipelineBuilder::new(layout).vertex_shader(vert).fragment_shader(frag).depth(true)
+/// .blend(Blend::Alpha).build(&device)` draws in the scene pass.
+#[derive(Clone, Debug)]
+pub struct RenderPipelineBuilder<'a> {
+    pub layout: vk::PipelineLayout,
+    pub vertex: Option<&'a [u8]>,
+    pub fragment: Option<&'a [u8]>,
+    pub buffers: Vec<VertexBufferLayout>,
+    pub topology: vk::PrimitiveTopology,
+    pub cull_mode: vk::CullModeFlags,
+    pub front_face: vk::FrontFace,
+    pub depth_test: bool,
+    pub depth_write: bool,
+    pub depth_compare: vk::CompareOp,
+    pub blend: Blend,
+    /// the fraction of samples shaded on their own, none when 0.
+    pub sample_shading: f32,
+    /// other pipelines can be derived from this one.
+    pub allow_derivatives: bool,
+    /// the pipeline this one is derived from, it must allow derivatives. drivers may create
+    /// derivatives faster and switch between them and their base cheaper.
+    pub base: Option<vk::Pipeline>,
+}
+
+impl<'a> RenderPipelineBuilder<'a> {
+    pub fn new(layout: vk::PipelineLayout) -> Self {
+        Self {
+            layout,
+            vertex: None,
+            fragment: None,
+            buffers: vec![],
+            topology: vk::PrimitiveTopology::TRIANGLE_LIST,
+            cull_mode: vk::CullModeFlags::BACK,
+            front_face: vk::FrontFace::COUNTER_CLOCKWISE,
+            depth_test: false,
+            depth_write: false,
+            depth_compare: vk::CompareOp::LESS,
+            blend: Blend::Replace,
+            sample_shading: 0.0,
+            allow_derivatives: false,
+            base: None,
+        }
+    }
+
+    /// the spir-v of the vertex shader, its entry point is `main`.
+    pub fn vertex_shader(mut self, code: &'a [u8]) -> Self {
+        self.vertex = Some(code);
+        self
+    }
+
+    /// the spir-v of the fragment shader, its entry point is `main`.
+    pub fn fragment_shader(mut self, code: &'a [u8]) -> Self {
+        self.fragment = Some(code);
+        self
+    }
+
+    /// read a vertex buffer with `layout`, per vertex or per instance.
+    pub fn vertex_buffer(mut self, layout: VertexBufferLayout) -> Self {
+        self.buffers.push(layout);
+        self
+    }
+
+    pub fn topology(mut self, topology: vk::PrimitiveTopology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn cull(mut self, cull_mode: vk::CullModeFlags) -> Self {
+        self.cull_mode = cull_mode;
+        self
+    }
+
+    pub fn front_face(mut self, front_face: vk::FrontFace) -> Self {
+        self.front_face = front_face;
+        self
+    }
+
+    /// test against depth and write it, nearer fragments pass.
+    pub fn depth(mut self, enabled: bool) -> Self {
+        self.depth_test = enabled;
+        self.depth_write = enabled;
+        self
+    }
+
+    /// keep testing depth but leave it unchanged, for transparent surfaces.
+    pub fn depth_read_only(mut self) -> Self {
+        self.depth_test = true;
+        self.depth_write = false;
+        self
+    }
+
+    pub fn depth_compare(mut self, compare: vk::CompareOp) -> Self {
+        self.depth_compare = compare;
+        self
+    }
+
+    pub fn blend(mut self, blend: Blend) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    pub fn sample_shading(mut self, fraction: f32) -> Self {
+        self.sample_shading = fraction;
+        self
+    }
+
+    pub fn allow_derivatives(mut self) -> Self {
+        self.allow_derivatives = true;
+        self
+    }
+
+    /// create the pipeline as a derivative of `base`, see `base`.
+    pub fn derive_from(mut self, base: vk::Pipeline) -> Self {
+        self.base = Some(base);
+        self
+    }
+
+    /// create the pipeline in the scene pass of `device`.
+    pub fn build(&self, device: &Device) -> Result<vk::Pipeline> {
+        self.build_for(device.device(), &PipelineTarget::scene(device))
+    }
+
+    /// create the pipeline like `build_for` on a thread of its own, drivers compile the
+    /// shaders while it is created. the check runs right away.
+    pub fn build_async(
+        &self,
+        device: &vulkanalia::Device,
+        target: PipelineTarget,
+    ) -> Result<BackgroundTask<vk::Pipeline>> {
+        check_render_pipeline(self, &target)?;
+        // the thread owns the shader code and the rest of the state
+        let RenderPipelineBuilder {
+            layout,
+            vertex,
+            fragment,
+            buffers,
+            topology,
+            cull_mode,
+            front_face,
+            depth_test,
+            depth_write,
+            depth_compare,
+            blend,
+            sample_shading,
+            allow_derivatives,
+            base,
+        } = self.clone();
+        let (vertex, fragment) = (vertex.map(<[u8]>::to_vec), fragment.map(<[u8]>::to_vec));
+        let device = device.clone();
+        BackgroundTask::spawn("pipeline", move || {
+            let builder = RenderPipelineBuilder {
+                layout,
+                vertex: vertex.as_deref(),
+                fragment: fragment.as_deref(),
+                buffers,
+                topology,
+                cull_mode,
+                front_face,
+                depth_test,
+                depth_write,
+                depth_compare,
+                blend,
+                sample_shading,
+                allow_derivatives,
+                base,
+            };
+            builder.build_for(&device, &target)
+        })
+    }
+
+    /// create the pipeline in the pass of `target`, the combination is checked first and
+    /// refused with a `DescriptionError`.
+    pub fn build_for(
+        &self,
+        device: &vulkanalia::Device,
+        target: &PipelineTarget,
+    ) -> Result<vk::Pipeline> {
+        check_render_pipeline(self, target)?;
+        let (bindings, attributes) = vertex_input_descriptions(&self.buffers)?;
+
+        unsafe {
+            // Stages, the check made sure there is a vertex shader
+
+            let mut shaders: Vec<(vk::ShaderStageFlags, Shader)> = vec![];
+            for (stage, code) in [
+                (vk::ShaderStageFlags::VERTEX, self.vertex),
+                (vk::ShaderStageFlags::FRAGMENT, self.fragment),
+            ] {
+                let Some(code) = code else {
+                    continue;
+                };
+                match Shader::create(device, code) {
+                    Ok(shader) => shaders.push((stage, shader)),
+                    Err(e) => {
+                        shaders.iter().for_each(|(_, s)| s.destroy(device));
+                        return Err(e);
+                    }
+                }
+            }
+            let stages = shaders
+                .iter()
+                .map(|(stage, shader)| {
+                    vk::PipelineShaderStageCreateInfo::builder()
+                        .stage(*stage)
+                        .module(shader.module)
+                        .name(b"main\0")
+                        .build()
+                })
+                .collect::<Vec<_>>();
+
+            // Fixed Function State
+
+            let vertex_input_state = vk::PipelineVertexInputStateCreateInfo::builder()
+                .vertex_binding_descriptions(&bindings)
+                .vertex_attribute_descriptions(&attributes);
+
+            let input_assembly_state = vk::PipelineInputAssemblyStateCreateInfo::builder()
+                .topology(self.topology)
+                .primitive_restart_enable(false);
+
+            let viewport_state = vk::PipelineViewportStateCreateInfo::builder()
+                .viewport_count(1)
+                .scissor_count(1);
+
+            let dynamic_states = &[vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+            let dynamic_state =
+                vk::PipelineDynamicStateCreateInfo::builder().dynamic_states(dynamic_states);
+
+            let rasterization_state = vk::PipelineRasterizationStateCreateInfo::builder()
+                .polygon_mode(vk::PolygonMode::FILL)
+                .line_width(1.0)
+                .cull_mode(self.cull_mode)
+                .front_face(self.front_face);
+
+            let multisample_state = vk::PipelineMultisampleStateCreateInfo::builder()
+                .sample_shading_enable(self.sample_shading > 0.0)
+                .min_sample_shading(self.sample_shading)
+                .rasterization_samples(target.samples);
+
+            let depth_stencil_state = vk::PipelineDepthStencilStateCreateInfo::builder()
+                .depth_test_enable(self.depth_test)
+                .depth_write_enable(self.depth_write)
+                .depth_compare_op(self.depth_compare);
+
+            // every color attachment blends the same way
+            let attachments = vec![self.blend.attachment(); target.color_attachments as usize];
+            let color_blend_state = vk::PipelineColorBlendStateCreateInfo::builder()
+                .logic_op(vk::LogicOp::COPY)
+                .attachments(&attachments);
+
+            // Create
+
+            let mut flags = vk::PipelineCreateFlags::empty();
+            if self.allow_derivatives {
+                flags |= vk::PipelineCreateFlags::ALLOW_DERIVATIVES;
+            }
+            if self.base.is_some() {
+                flags |= vk::PipelineCreateFlags::DERIVATIVE;
+            }
+
+            let info = vk::GraphicsPipelineCreateInfo::builder()
+                .flags(flags)
+                .stages(&stages)
+                .vertex_input_state(&vertex_input_state)
+                .input_assembly_state(&input_assembly_state)
+                .viewport_state(&viewport_state)
+                .rasterization_state(&rasterization_state)
+                .multisample_state(&multisample_state)
+                .depth_stencil_state(&depth_stencil_state)
+                .color_blend_state(&color_blend_state)
+                .dynamic_state(&dynamic_state)
+                .layout(self.layout)
+                .render_pass(target.render_pass)
+                .subpass(target.subpass)
+                .base_pipeline_handle(self.base.unwrap_or_default())
+                .base_pipeline_index(-1);
+
+            let result = device.create_graphics_pipelines(vk::PipelineCache::null(), &[info], None);
+
+            // clean up
+            for (_, shader) in &shaders {
+                shader.destroy(device);
+            }
+
+            Ok(result?.0[0])
+        }
+    }
+}
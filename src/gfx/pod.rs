@@ -0,0 +1,72 @@
+#![allow(dead_code)]
+
+use std::slice;
+
+use cgmath::{Matrix2, Matrix3, Matrix4, Quaternion, Vector2, Vector3, Vector4};
+
+/// Plain old data, types buffers are written and read as. every byte of a value is part of a
+/// field and every bit pattern is a valid value, so values can be viewed as bytes and read
+/// back from bytes the gpu wrote. `Copy` alone allows padding, whose bytes are undefined.
+///
+/// # Safety
+///
+/// the type must have no padding and no fields with invalid bit patterns, like `bool`,
+/// references or enums. `pod!` implements it for structs and checks the padding.
+pub unsafe trait Pod: Copy + 'static {}
+
+macro_rules! pods {
+    ($($type:ty,)*) => {
+        $(
+            unsafe impl Pod for $type {}
+        )*
+    };
+}
+
+pods! {
+    u8, i8, u16, i16, u32, i32, u64, i64, f32, f64,
+}
+
+// arrays and the cgmath types are their elements without gaps
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+unsafe impl<T: Pod> Pod for Vector2<T> {}
+unsafe impl<T: Pod> Pod for Vector3<T> {}
+unsafe impl<T: Pod> Pod for Vector4<T> {}
+unsafe impl<T: Pod> Pod for Matrix2<T> {}
+unsafe impl<T: Pod> Pod for Matrix3<T> {}
+unsafe impl<T: Pod> Pod for Matrix4<T> {}
+unsafe impl<T: Pod> Pod for Quaternion<T> {}
+
+/// view `data` as its bytes.
+pub fn pod_bytes<T: Pod>(data: &[T]) -> &[u8] {
+    unsafe { slice::from_raw_parts(data.as_ptr().cast::<u8>(), std::mem::size_of_val(data)) }
+}
+
+/// Implements `Pod` for a struct from all of its fields and their types,
+/// `pod!(Instance { placement: Vec4, color: Vec4 })`. a missing field, a wrong type, a field
+/// that isn't `Pod` or padding between the fields fails to compile. `#[repr(C)]` keeps the
+/// fields in the order shaders expect them.
+#[macro_export]
+macro_rules! pod {
+    ($name:ident { $($field:ident: $type:ty),* $(,)? }) => {
+        const _: () = {
+            fn pod<T: $crate::gfx::Pod>() {}
+
+            // the fields are exactly these, with these types
+            #[allow(unused_variables)]
+            fn fields(value: &$name) {
+                let $name { $($field),* } = value;
+                $(
+                    let _: &$type = $field;
+                    pod::<$type>();
+                )*
+            }
+
+            assert!(
+                ::std::mem::size_of::<$name>() == 0 $(+ ::std::mem::size_of::<$type>())*,
+                concat!(stringify!($name), " has padding between its fields.")
+            );
+        };
+
+        unsafe impl $crate::gfx::Pod for $name {}
+    };
+}
@@ -0,0 +1,125 @@
+#![allow(dead_code)]
+
+use std::ops::Range;
+
+use anyhow::{anyhow, Result};
+use vulkanalia::prelude::v1_0::*;
+use vulkanalia::vk::ExtHostQueryResetExtension;
+
+/// What the queries of a `QuerySet` measure.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum QueryKind {
+    /// the time a command reached a pipeline stage in ticks of `timestampPeriod`
+    /// nanoseconds, needs `Feature::TimestampQuery`.
+    Timestamp,
+    /// the samples that passed the depth and stencil tests between begin and end.
+    Occlusion,
+}
+
+impl QueryKind {
+    pub fn query_type(&self) -> vk::QueryType {
+        match self {
+            QueryKind::Timestamp => vk::QueryType::TIMESTAMP,
+            QueryKind::Occlusion => vk::QueryType::OCCLUSION,
+        }
+    }
+}
+
+/// How `Encoder::resolve_query_set` copies query results into a buffer. results are always
+/// 64 bit, the modes without waiting put the availability of each query after its result.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum QueryResolve {
+    /// the copy waits until every query of the range is available, the results are final.
+    /// a query nothing writes is waited on forever.
+    Wait,
+    /// copy right away, unavailable queries leave their result as it was and write an
+    /// availability of 0.
+    Available,
+    /// like `Available`, but unavailable occlusion queries write what they counted so far,
+    /// between 0 and the final result. timestamps can't be partial.
+    Partial,
+}
+
+impl QueryResolve {
+    pub fn flags(&self) -> vk::QueryResultFlags {
+        match self {
+            QueryResolve::Wait => vk::QueryResultFlags::_64 | vk::QueryResultFlags::WAIT,
+            QueryResolve::Available => {
+                vk::QueryResultFlags::_64 | vk::QueryResultFlags::WITH_AVAILABILITY
+            }
+            QueryResolve::Partial => {
+                vk::QueryResultFlags::_64
+                    | vk::QueryResultFlags::WITH_AVAILABILITY
+                    | vk::QueryResultFlags::PARTIAL
+            }
+        }
+    }
+
+    /// the bytes each query takes in the buffer.
+    pub fn stride(&self) -> vk::DeviceSize {
+        match self {
+            QueryResolve::Wait => 8,
+            QueryResolve::Available | QueryResolve::Partial => 16,
+        }
+    }
+
+    /// the results of the queries resolved into `bytes`, none for those that weren't
+    /// available. partial results count as available.
+    pub fn results(&self, bytes: &[u8]) -> Vec<Option<u64>> {
+        let word = |b: &[u8]| u64::from_ne_bytes(b.try_into().unwrap());
+        bytes
+            .chunks_exact(self.stride() as usize)
+            .map(|query| match self {
+                QueryResolve::Wait => Some(word(&query[..8])),
+                QueryResolve::Available => (word(&query[8..]) != 0).then(|| word(&query[..8])),
+                QueryResolve::Partial => Some(word(&query[..8])),
+            })
+            .collect()
+    }
+}
+
+/// A vulkan query pool of `count` queries of one kind. queries are reset before they are
+/// written, with `Encoder::reset_query_set` or on the host with `reset`, and their results
+/// are copied into buffers with `Encoder::resolve_query_set`.
+#[derive(Copy, Clone, Debug)]
+pub struct QuerySet {
+    pub pool: vk::QueryPool,
+    pub kind: QueryKind,
+    pub count: u32,
+}
+
+impl QuerySet {
+    pub unsafe fn create(device: &vulkanalia::Device, kind: QueryKind, count: u32) -> Result<Self> {
+        if count == 0 {
+            return Err(anyhow!("Query sets need at least one query."));
+        }
+        let info = vk::QueryPoolCreateInfo::builder()
+            .query_type(kind.query_type())
+            .query_count(count);
+        let pool = device.create_query_pool(&info, None)?;
+        Ok(Self { pool, kind, count })
+    }
+
+    /// whether `queries` is a range of queries of the set that isn't empty.
+    pub fn contains(&self, queries: &Range<u32>) -> bool {
+        queries.start < queries.end && queries.end <= self.count
+    }
+
+    /// reset `queries` on the host, they can't be in use by pending commands. needs the
+    /// device to support host query resets, see `HeadlessDevice::supports_host_query_reset`.
+    pub unsafe fn reset(&self, device: &vulkanalia::Device, queries: Range<u32>) -> Result<()> {
+        if !self.contains(&queries) {
+            return Err(anyhow!(
+                "Queries {:?} are not in the {} of the query set.",
+                queries,
+                self.count
+            ));
+        }
+        device.reset_query_pool_ext(self.pool, queries.start, queries.end - queries.start);
+        Ok(())
+    }
+
+    pub unsafe fn destroy(&self, device: &vulkanalia::Device) {
+        device.destroy_query_pool(self.pool, None);
+    }
+}
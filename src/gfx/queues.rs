@@ -0,0 +1,88 @@
+#![allow(dead_code)]
+
+use vulkanalia::prelude::v1_0::*;
+
+/// The queues of a `Device`, see `Device::queue`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum QueueKind {
+    /// draws, dispatches and copies, frames and `Device::execute` are submitted to it.
+    Graphics,
+    /// presents swapchain images, often the graphics queue itself.
+    Present,
+    /// dispatches and copies on a family without graphics, runs next to the frames on gpus
+    /// with async compute.
+    Compute,
+    /// copies on a family without graphics or compute, usually a dma engine that uploads
+    /// while the gpu draws.
+    Transfer,
+}
+
+/// A queue of a device with its family. buffers and textures of `Device::create_buffer` and
+/// the `create_*texture` methods are concurrent across the queues, those of exclusive sharing
+/// move between families with ownership transfer barriers. command buffers are recorded from
+/// pools of the family.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Queue {
+    pub kind: QueueKind,
+    pub family: u32,
+    pub queue: vk::Queue,
+}
+
+/// the first family with `flags` and none of `without`, e.g. compute without graphics for a
+/// dedicated compute queue.
+pub fn dedicated_queue_family(
+    families: &[vk::QueueFamilyProperties],
+    flags: vk::QueueFlags,
+    without: vk::QueueFlags,
+) -> Option<u32> {
+    families
+        .iter()
+        .position(|f| {
+            f.queue_count > 0 && f.queue_flags.contains(flags) && !f.queue_flags.intersects(without)
+        })
+        .map(|i| i as u32)
+}
+
+/// the families of the dedicated compute and transfer queues, when the adapter has them.
+pub fn dedicated_queue_families(
+    families: &[vk::QueueFamilyProperties],
+) -> (Option<u32>, Option<u32>) {
+    let compute =
+        dedicated_queue_family(families, vk::QueueFlags::COMPUTE, vk::QueueFlags::GRAPHICS);
+    let transfer = dedicated_queue_family(
+        families,
+        vk::QueueFlags::TRANSFER,
+        vk::QueueFlags::GRAPHICS | vk::QueueFlags::COMPUTE,
+    );
+    (compute, transfer)
+}
+
+/// the families resources used on the graphics and the dedicated queues are concurrent
+/// across, none when graphics is the only family and resources are exclusive to it.
+pub fn shared_queue_families(
+    graphics: u32,
+    compute: Option<u32>,
+    transfer: Option<u32>,
+) -> Vec<u32> {
+    let mut families = vec![graphics];
+    for family in [compute, transfer].into_iter().flatten() {
+        if !families.contains(&family) {
+            families.push(family);
+        }
+    }
+
+    // a single family owns everything
+    if families.len() < 2 {
+        families.clear();
+    }
+    families
+}
+
+/// concurrent sharing across `families`, exclusive without them.
+pub(crate) fn sharing_mode(families: &[u32]) -> vk::SharingMode {
+    if families.is_empty() {
+        vk::SharingMode::EXCLUSIVE
+    } else {
+        vk::SharingMode::CONCURRENT
+    }
+}
@@ -0,0 +1,176 @@
+#![allow(dead_code)]
+
+use anyhow::{anyhow, Result};
+use vulkanalia::prelude::v1_0::*;
+
+/// The texels of a texture copied back to the cpu, rows tightly packed in the layout of
+/// `format`. `to_rgba8` converts them for comparisons and image files.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TexturePixels {
+    pub width: u32,
+    pub height: u32,
+    pub format: vk::Format,
+    pub data: Vec<u8>,
+}
+
+impl TexturePixels {
+    /// the bytes per texel of the formats pixels can be read back in, none for the others.
+    pub fn texel_size(format: vk::Format) -> Option<usize> {
+        match format {
+            vk::Format::R8_UNORM | vk::Format::R8_SRGB => Some(1),
+            vk::Format::R8G8B8A8_UNORM
+            | vk::Format::R8G8B8A8_SRGB
+            | vk::Format::B8G8R8A8_UNORM
+            | vk::Format::B8G8R8A8_SRGB
+            | vk::Format::R32_SFLOAT => Some(4),
+            vk::Format::R16G16B16A16_SFLOAT => Some(8),
+            vk::Format::R32G32B32A32_SFLOAT => Some(16),
+            _ => None,
+        }
+    }
+
+    /// the texels as rgba8 in the order of the rows. float channels are clamped to 0..1,
+    /// missing channels are 0 and a missing alpha is opaque. srgb values stay encoded.
+    pub fn to_rgba8(&self) -> Result<Vec<u8>> {
+        let unorm = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let data = &self.data;
+        let pixels = match self.format {
+            vk::Format::R8G8B8A8_UNORM | vk::Format::R8G8B8A8_SRGB => data.clone(),
+            vk::Format::B8G8R8A8_UNORM | vk::Format::B8G8R8A8_SRGB => data
+                .chunks_exact(4)
+                .flat_map(|p| [p[2], p[1], p[0], p[3]])
+                .collect(),
+            vk::Format::R8_UNORM | vk::Format::R8_SRGB => {
+                data.iter().flat_map(|r| [*r, 0, 0, 255]).collect()
+            }
+            vk::Format::R32_SFLOAT => data
+                .chunks_exact(4)
+                .flat_map(|r| {
+                    [
+                        unorm(f32::from_ne_bytes([r[0], r[1], r[2], r[3]])),
+                        0,
+                        0,
+                        255,
+                    ]
+                })
+                .collect(),
+            vk::Format::R16G16B16A16_SFLOAT => data
+                .chunks_exact(2)
+                .map(|c| unorm(half_to_f32(u16::from_ne_bytes([c[0], c[1]]))))
+                .collect(),
+            vk::Format::R32G32B32A32_SFLOAT => data
+                .chunks_exact(4)
+                .map(|c| unorm(f32::from_ne_bytes([c[0], c[1], c[2], c[3]])))
+                .collect(),
+            format => return Err(anyhow!("Can't convert {:?} pixels to rgba8.", format)),
+        };
+        Ok(pixels)
+    }
+}
+
+/// widen an ieee half float.
+pub fn half_to_f32(half: u16) -> f32 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((half >> 10) & 0x1f) as i32;
+    let mantissa = (half & 0x3ff) as f32;
+    match exponent {
+        // subnormals
+        0 => sign * mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => sign * f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+/// record a copy of the first level and layer of `image` into `buffer`, rows tightly
+/// packed. the image is in `layout` before and after, all earlier writes to it are waited
+/// for.
+pub unsafe fn record_texture_readback(
+    device: &vulkanalia::Device,
+    command_buffer: vk::CommandBuffer,
+    image: vk::Image,
+    width: u32,
+    height: u32,
+    layout: vk::ImageLayout,
+    buffer: vk::Buffer,
+) {
+    let subresource = vk::ImageSubresourceRange::builder()
+        .aspect_mask(vk::ImageAspectFlags::COLOR)
+        .base_mip_level(0)
+        .level_count(1)
+        .base_array_layer(0)
+        .layer_count(1);
+    let barrier = |old, new, src, dst| {
+        vk::ImageMemoryBarrier::builder()
+            .image(image)
+            .old_layout(old)
+            .new_layout(new)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .subresource_range(subresource)
+            .src_access_mask(src)
+            .dst_access_mask(dst)
+            .build()
+    };
+
+    // wait for whatever wrote the image and make it copyable
+    let to_transfer = barrier(
+        layout,
+        vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        vk::AccessFlags::MEMORY_WRITE,
+        vk::AccessFlags::TRANSFER_READ,
+    );
+    device.cmd_pipeline_barrier(
+        command_buffer,
+        vk::PipelineStageFlags::ALL_COMMANDS,
+        vk::PipelineStageFlags::TRANSFER,
+        vk::DependencyFlags::empty(),
+        &[] as &[vk::MemoryBarrier],
+        &[] as &[vk::BufferMemoryBarrier],
+        &[to_transfer],
+    );
+
+    // a row length of 0 packs the rows without padding
+    let region = vk::BufferImageCopy::builder()
+        .buffer_offset(0)
+        .buffer_row_length(0)
+        .buffer_image_height(0)
+        .image_subresource(vk::ImageSubresourceLayers {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            mip_level: 0,
+            base_array_layer: 0,
+            layer_count: 1,
+        })
+        .image_offset(vk::Offset3D::default())
+        .image_extent(vk::Extent3D {
+            width,
+            height,
+            depth: 1,
+        });
+    device.cmd_copy_image_to_buffer(
+        command_buffer,
+        image,
+        vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        buffer,
+        &[region],
+    );
+
+    // hand the image back in the layout it had, an undefined one can't be returned to
+    if layout != vk::ImageLayout::UNDEFINED {
+        let back = barrier(
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            layout,
+            vk::AccessFlags::TRANSFER_READ,
+            vk::AccessFlags::MEMORY_READ | vk::AccessFlags::MEMORY_WRITE,
+        );
+        device.cmd_pipeline_barrier(
+            command_buffer,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::PipelineStageFlags::ALL_COMMANDS,
+            vk::DependencyFlags::empty(),
+            &[] as &[vk::MemoryBarrier],
+            &[] as &[vk::BufferMemoryBarrier],
+            &[back],
+        );
+    }
+}
@@ -0,0 +1,321 @@
+// The commands the null driver and captures record, each with the `TraceCommand` it becomes.
+// `recorded!(m)` invokes `m!` with the table, which generates the entry points of its own
+// from it. `items`, `TraceCommand` and the buffer barrier conversions have to be in scope
+// where `m!` expands.
+macro_rules! recorded {
+    ($m:ident) => {
+        $m! {
+            "vkCmdBeginRenderPass" cmd_begin_render_pass: PFN_vkCmdBeginRenderPass(
+                command_buffer,
+                info: *const vk::RenderPassBeginInfo,
+                contents: vk::SubpassContents
+            ) => TraceCommand::BeginRenderPass {
+                render_pass: (*info).render_pass,
+                framebuffer: (*info).framebuffer,
+                area: (*info).render_area,
+                clear_values: items((*info).clear_values, (*info).clear_value_count).to_vec(),
+                contents,
+            };
+            "vkCmdEndRenderPass" cmd_end_render_pass: PFN_vkCmdEndRenderPass(
+                command_buffer
+            ) => TraceCommand::EndRenderPass;
+            "vkCmdNextSubpass" cmd_next_subpass: PFN_vkCmdNextSubpass(
+                command_buffer,
+                contents: vk::SubpassContents
+            ) => TraceCommand::NextSubpass { contents };
+            "vkCmdBindPipeline" cmd_bind_pipeline: PFN_vkCmdBindPipeline(
+                command_buffer,
+                bind_point: vk::PipelineBindPoint,
+                pipeline: vk::Pipeline
+            ) => TraceCommand::BindPipeline {
+                bind_point,
+                pipeline,
+            };
+            "vkCmdBindDescriptorSets" cmd_bind_descriptor_sets: PFN_vkCmdBindDescriptorSets(
+                command_buffer,
+                bind_point: vk::PipelineBindPoint,
+                layout: vk::PipelineLayout,
+                first_set: u32,
+                set_count: u32,
+                sets: *const vk::DescriptorSet,
+                dynamic_offset_count: u32,
+                dynamic_offsets: *const u32
+            ) => TraceCommand::BindDescriptorSets {
+                bind_point,
+                layout,
+                first_set,
+                sets: items(sets, set_count).to_vec(),
+                dynamic_offsets: items(dynamic_offsets, dynamic_offset_count).to_vec(),
+            };
+            "vkCmdBindVertexBuffers" cmd_bind_vertex_buffers: PFN_vkCmdBindVertexBuffers(
+                command_buffer,
+                first_binding: u32,
+                count: u32,
+                buffers: *const vk::Buffer,
+                offsets: *const vk::DeviceSize
+            ) => TraceCommand::BindVertexBuffers {
+                first_binding,
+                buffers: items(buffers, count).to_vec(),
+                offsets: items(offsets, count).to_vec(),
+            };
+            "vkCmdBindIndexBuffer" cmd_bind_index_buffer: PFN_vkCmdBindIndexBuffer(
+                command_buffer,
+                buffer: vk::Buffer,
+                offset: vk::DeviceSize,
+                index_type: vk::IndexType
+            ) => TraceCommand::BindIndexBuffer {
+                buffer,
+                offset,
+                index_type,
+            };
+            "vkCmdPushConstants" cmd_push_constants: PFN_vkCmdPushConstants(
+                command_buffer,
+                layout: vk::PipelineLayout,
+                stages: vk::ShaderStageFlags,
+                offset: u32,
+                size: u32,
+                values: *const c_void
+            ) => TraceCommand::PushConstants {
+                layout,
+                stages,
+                offset,
+                data: items(values.cast::<u8>(), size).to_vec(),
+            };
+            "vkCmdSetViewport" cmd_set_viewport: PFN_vkCmdSetViewport(
+                command_buffer,
+                first_viewport: u32,
+                count: u32,
+                viewports: *const vk::Viewport
+            ) => TraceCommand::SetViewport {
+                first_viewport,
+                viewports: items(viewports, count).to_vec(),
+            };
+            "vkCmdSetScissor" cmd_set_scissor: PFN_vkCmdSetScissor(
+                command_buffer,
+                first_scissor: u32,
+                count: u32,
+                scissors: *const vk::Rect2D
+            ) => TraceCommand::SetScissor {
+                first_scissor,
+                scissors: items(scissors, count).to_vec(),
+            };
+            "vkCmdDraw" cmd_draw: PFN_vkCmdDraw(
+                command_buffer,
+                vertex_count: u32,
+                instance_count: u32,
+                first_vertex: u32,
+                first_instance: u32
+            ) => TraceCommand::Draw {
+                vertex_count,
+                instance_count,
+                first_vertex,
+                first_instance,
+            };
+            "vkCmdDrawIndexed" cmd_draw_indexed: PFN_vkCmdDrawIndexed(
+                command_buffer,
+                index_count: u32,
+                instance_count: u32,
+                first_index: u32,
+                vertex_offset: i32,
+                first_instance: u32
+            ) => TraceCommand::DrawIndexed {
+                index_count,
+                instance_count,
+                first_index,
+                vertex_offset,
+                first_instance,
+            };
+            "vkCmdDrawIndirect" cmd_draw_indirect: PFN_vkCmdDrawIndirect(
+                command_buffer,
+                buffer: vk::Buffer,
+                offset: vk::DeviceSize,
+                draw_count: u32,
+                stride: u32
+            ) => TraceCommand::DrawIndirect {
+                buffer,
+                offset,
+                draw_count,
+                stride,
+            };
+            "vkCmdDispatch" cmd_dispatch: PFN_vkCmdDispatch(
+                command_buffer,
+                x: u32,
+                y: u32,
+                z: u32
+            ) => TraceCommand::Dispatch { x, y, z };
+            "vkCmdPipelineBarrier" cmd_pipeline_barrier: PFN_vkCmdPipelineBarrier(
+                command_buffer,
+                src_stage: vk::PipelineStageFlags,
+                dst_stage: vk::PipelineStageFlags,
+                dependency: vk::DependencyFlags,
+                memory_barrier_count: u32,
+                memory_barriers: *const vk::MemoryBarrier,
+                buffer_barrier_count: u32,
+                buffer_barriers: *const vk::BufferMemoryBarrier,
+                image_barrier_count: u32,
+                image_barriers: *const vk::ImageMemoryBarrier
+            ) => TraceCommand::PipelineBarrier {
+                src_stage,
+                dst_stage,
+                dependency,
+                memory_barriers: items(memory_barriers, memory_barrier_count)
+                    .iter()
+                    .map(|b| (b.src_access_mask, b.dst_access_mask))
+                    .collect(),
+                buffers: items(buffer_barriers, buffer_barrier_count)
+                    .iter()
+                    .map(TraceBufferBarrier::from)
+                    .collect(),
+                images: items(image_barriers, image_barrier_count)
+                    .iter()
+                    .map(TraceImageBarrier::from)
+                    .collect(),
+            };
+            "vkCmdCopyBuffer" cmd_copy_buffer: PFN_vkCmdCopyBuffer(
+                command_buffer,
+                src: vk::Buffer,
+                dst: vk::Buffer,
+                count: u32,
+                regions: *const vk::BufferCopy
+            ) => TraceCommand::CopyBuffer {
+                src,
+                dst,
+                regions: items(regions, count).to_vec(),
+            };
+            "vkCmdCopyBufferToImage" cmd_copy_buffer_to_image: PFN_vkCmdCopyBufferToImage(
+                command_buffer,
+                buffer: vk::Buffer,
+                image: vk::Image,
+                layout: vk::ImageLayout,
+                count: u32,
+                regions: *const vk::BufferImageCopy
+            ) => TraceCommand::CopyBufferToImage {
+                buffer,
+                image,
+                layout,
+                regions: items(regions, count).to_vec(),
+            };
+            "vkCmdCopyImageToBuffer" cmd_copy_image_to_buffer: PFN_vkCmdCopyImageToBuffer(
+                command_buffer,
+                image: vk::Image,
+                layout: vk::ImageLayout,
+                buffer: vk::Buffer,
+                count: u32,
+                regions: *const vk::BufferImageCopy
+            ) => TraceCommand::CopyImageToBuffer {
+                image,
+                layout,
+                buffer,
+                regions: items(regions, count).to_vec(),
+            };
+            "vkCmdBlitImage" cmd_blit_image: PFN_vkCmdBlitImage(
+                command_buffer,
+                src: vk::Image,
+                src_layout: vk::ImageLayout,
+                dst: vk::Image,
+                dst_layout: vk::ImageLayout,
+                count: u32,
+                regions: *const vk::ImageBlit,
+                filter: vk::Filter
+            ) => TraceCommand::BlitImage {
+                src,
+                src_layout,
+                dst,
+                dst_layout,
+                regions: items(regions, count).to_vec(),
+                filter,
+            };
+            "vkCmdClearColorImage" cmd_clear_color_image: PFN_vkCmdClearColorImage(
+                command_buffer,
+                image: vk::Image,
+                layout: vk::ImageLayout,
+                color: *const vk::ClearColorValue,
+                range_count: u32,
+                ranges: *const vk::ImageSubresourceRange
+            ) => TraceCommand::ClearColorImage {
+                image,
+                layout,
+                color: vk::ClearValue { color: *color },
+                ranges: items(ranges, range_count).to_vec(),
+            };
+            "vkCmdFillBuffer" cmd_fill_buffer: PFN_vkCmdFillBuffer(
+                command_buffer,
+                buffer: vk::Buffer,
+                offset: vk::DeviceSize,
+                size: vk::DeviceSize,
+                data: u32
+            ) => TraceCommand::FillBuffer {
+                buffer,
+                offset,
+                size,
+                data,
+            };
+            "vkCmdBeginDebugUtilsLabelEXT" cmd_begin_debug_utils_label_ext: PFN_vkCmdBeginDebugUtilsLabelEXT(
+                command_buffer,
+                label: *const vk::DebugUtilsLabelEXT
+            ) => TraceCommand::BeginLabel {
+                name: std::ffi::CStr::from_ptr((*label).label_name)
+                    .to_string_lossy()
+                    .into_owned(),
+                color: (*label).color,
+            };
+            "vkCmdEndDebugUtilsLabelEXT" cmd_end_debug_utils_label_ext: PFN_vkCmdEndDebugUtilsLabelEXT(
+                command_buffer
+            ) => TraceCommand::EndLabel;
+            "vkCmdExecuteCommands" cmd_execute_commands: PFN_vkCmdExecuteCommands(
+                command_buffer,
+                count: u32,
+                command_buffers: *const vk::CommandBuffer
+            ) => TraceCommand::ExecuteCommands {
+                command_buffers: items(command_buffers, count).to_vec(),
+            };
+            "vkCmdResetQueryPool" cmd_reset_query_pool: PFN_vkCmdResetQueryPool(
+                command_buffer,
+                pool: vk::QueryPool,
+                first_query: u32,
+                query_count: u32
+            ) => TraceCommand::ResetQueryPool {
+                pool,
+                first_query,
+                query_count,
+            };
+            "vkCmdWriteTimestamp" cmd_write_timestamp: PFN_vkCmdWriteTimestamp(
+                command_buffer,
+                stage: vk::PipelineStageFlags,
+                pool: vk::QueryPool,
+                query: u32
+            ) => TraceCommand::WriteTimestamp { stage, pool, query };
+            "vkCmdBeginQuery" cmd_begin_query: PFN_vkCmdBeginQuery(
+                command_buffer,
+                pool: vk::QueryPool,
+                query: u32,
+                flags: vk::QueryControlFlags
+            ) => TraceCommand::BeginQuery { pool, query, flags };
+            "vkCmdEndQuery" cmd_end_query: PFN_vkCmdEndQuery(
+                command_buffer,
+                pool: vk::QueryPool,
+                query: u32
+            ) => TraceCommand::EndQuery { pool, query };
+            "vkCmdCopyQueryPoolResults" cmd_copy_query_pool_results: PFN_vkCmdCopyQueryPoolResults(
+                command_buffer,
+                pool: vk::QueryPool,
+                first_query: u32,
+                query_count: u32,
+                buffer: vk::Buffer,
+                offset: vk::DeviceSize,
+                stride: vk::DeviceSize,
+                flags: vk::QueryResultFlags
+            ) => TraceCommand::CopyQueryPoolResults {
+                pool,
+                first_query,
+                query_count,
+                buffer,
+                offset,
+                stride,
+                flags,
+            };
+        }
+    };
+}
+
+pub(super) use recorded;
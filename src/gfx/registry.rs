@@ -0,0 +1,105 @@
+#![allow(dead_code)]
+
+use anyhow::Result;
+
+use super::{Buffer, Handle, HandlePool, Retired, Shader, Texture, TextureView};
+
+pub type BufferId = Handle<Buffer>;
+pub type TextureId = Handle<Texture>;
+pub type TextureViewId = Handle<TextureView>;
+pub type ShaderId = Handle<Shader>;
+
+/// A resource kind the `ResourceRegistry` keeps, the ones the deletion queue can retire.
+pub trait Registered: Into<Retired> + Sized {
+    fn pool(registry: &ResourceRegistry) -> &HandlePool<Self>;
+    fn pool_mut(registry: &mut ResourceRegistry) -> &mut HandlePool<Self>;
+}
+
+macro_rules! registered {
+    ($ty:ty, $field:ident) => {
+        impl Registered for $ty {
+            fn pool(registry: &ResourceRegistry) -> &HandlePool<Self> {
+                &registry.$field
+            }
+
+            fn pool_mut(registry: &mut ResourceRegistry) -> &mut HandlePool<Self> {
+                &mut registry.$field
+            }
+        }
+    };
+}
+
+registered!(Buffer, buffers);
+registered!(Texture, textures);
+registered!(TextureView, views);
+registered!(Shader, shaders);
+
+/// The number of resources of each kind in a `ResourceRegistry`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResourceCounts {
+    pub buffers: usize,
+    pub textures: usize,
+    pub views: usize,
+    pub shaders: usize,
+}
+
+impl ResourceCounts {
+    pub fn total(&self) -> usize {
+        self.buffers + self.textures + self.views + self.shaders
+    }
+}
+
+/// The vulkan objects of a device keyed by typed ids, see `Device::register`. code holding
+/// on to resources keeps the ids, which are copied freely and sent to other threads, while
+/// the objects stay with the device that destroys them.
+#[derive(Default)]
+pub struct ResourceRegistry {
+    buffers: HandlePool<Buffer>,
+    textures: HandlePool<Texture>,
+    views: HandlePool<TextureView>,
+    shaders: HandlePool<Shader>,
+}
+
+impl ResourceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// keep `resource`, returns its id.
+    pub fn insert<T: Registered>(&mut self, resource: T) -> Handle<T> {
+        T::pool_mut(self).insert(resource)
+    }
+
+    /// the resource of `id`, an error once it was removed.
+    pub fn get<T: Registered>(&self, id: Handle<T>) -> Result<&T> {
+        T::pool(self).get(id)
+    }
+
+    pub fn contains<T: Registered>(&self, id: Handle<T>) -> bool {
+        T::pool(self).contains(id)
+    }
+
+    /// take the resource of `id` out to destroy it, the id is invalid afterwards.
+    pub fn remove<T: Registered>(&mut self, id: Handle<T>) -> Result<T> {
+        T::pool_mut(self).remove(id)
+    }
+
+    pub fn counts(&self) -> ResourceCounts {
+        ResourceCounts {
+            buffers: self.buffers.len(),
+            textures: self.textures.len(),
+            views: self.views.len(),
+            shaders: self.shaders.len(),
+        }
+    }
+
+    /// take every resource out, views first so they go before their textures.
+    pub fn drain(&mut self) -> Vec<Retired> {
+        let mut resources = vec![];
+        resources.extend(self.views.drain().into_iter().map(Retired::from));
+        resources.extend(self.textures.drain().into_iter().map(Retired::from));
+        resources.extend(self.buffers.drain().into_iter().map(Retired::from));
+        resources.extend(self.shaders.drain().into_iter().map(Retired::from));
+        resources
+    }
+}
@@ -0,0 +1,163 @@
+#![allow(dead_code)]
+
+use std::collections::VecDeque;
+use std::fs::{create_dir_all, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use vulkanalia::prelude::v1_0::*;
+
+// The number of validation messages kept for bug reports.
+const MAX_VALIDATION_MESSAGES: usize = 32;
+
+// The number of validation errors reported so far.
+static VALIDATION_ERRORS: AtomicUsize = AtomicUsize::new(0);
+// The most recent validation warnings and errors.
+static VALIDATION_MESSAGES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// When and where bug reports are written.
+#[derive(Clone, Debug)]
+pub struct BugReportSettings {
+    /// the folder each report gets its own subfolder in.
+    pub folder: PathBuf,
+    /// the number of validation errors since the last report that triggers a new one.
+    pub error_threshold: usize,
+}
+
+impl BugReportSettings {
+    pub fn new(folder: impl Into<PathBuf>, error_threshold: usize) -> Self {
+        Self {
+            folder: folder.into(),
+            error_threshold: error_threshold.max(1),
+        }
+    }
+}
+
+impl Default for BugReportSettings {
+    fn default() -> Self {
+        Self::new("bug-reports", 10)
+    }
+}
+
+/// A captured frame, rgba8 pixels in the color space of the swapchain.
+pub(crate) struct ReportFrame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl ReportFrame {
+    /// convert pixels read back from a swapchain image of `format`.
+    pub fn from_swapchain(
+        width: u32,
+        height: u32,
+        format: vk::Format,
+        mut pixels: Vec<u8>,
+    ) -> Self {
+        // most swapchains are bgra, png wants rgba
+        if matches!(
+            format,
+            vk::Format::B8G8R8A8_SRGB | vk::Format::B8G8R8A8_UNORM
+        ) {
+            pixels.chunks_exact_mut(4).for_each(|p| p.swap(0, 2));
+        }
+
+        // the window is opaque, the stored alpha is meaningless
+        pixels.chunks_exact_mut(4).for_each(|p| p[3] = 255);
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+/// record a validation message, errors count towards the report threshold.
+pub(crate) fn record_validation_message(message: &str, error: bool) {
+    if error {
+        VALIDATION_ERRORS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    let mut messages = VALIDATION_MESSAGES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if messages.len() == MAX_VALIDATION_MESSAGES {
+        messages.pop_front();
+    }
+    messages.push_back(message.to_string());
+}
+
+/// the number of validation errors reported since startup.
+pub(crate) fn validation_errors() -> usize {
+    VALIDATION_ERRORS.load(Ordering::Relaxed)
+}
+
+/// write a report into a new subfolder of `folder`, returns the subfolder.
+/// `state` is written as is, followed by the recent validation messages.
+pub(crate) fn write_bug_report(
+    folder: &Path,
+    reason: &str,
+    state: &str,
+    frame: Option<&ReportFrame>,
+) -> Result<PathBuf> {
+    // one folder per report, named after the time it was written
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let path = folder.join(format!(
+        "report-{}-{:03}",
+        time.as_secs(),
+        time.subsec_millis()
+    ));
+    create_dir_all(&path)?;
+
+    // describe what happened
+    let mut file = BufWriter::new(File::create(path.join("report.txt"))?);
+    writeln!(file, "reason: {}", reason)?;
+    writeln!(file, "time: {}", time.as_secs())?;
+    writeln!(file, "validation errors: {}", validation_errors())?;
+    writeln!(file)?;
+    write!(file, "{}", state)?;
+    writeln!(file)?;
+    writeln!(file, "recent validation messages:")?;
+    let messages = VALIDATION_MESSAGES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    for message in messages.iter() {
+        writeln!(file, "  {}", message)?;
+    }
+    file.flush()?;
+
+    // store the last completed frame
+    if let Some(frame) = frame {
+        write_frame(&path, frame)?;
+    }
+
+    Ok(path)
+}
+
+#[cfg(feature = "image-loading")]
+fn write_frame(path: &Path, frame: &ReportFrame) -> Result<()> {
+    let file = BufWriter::new(File::create(path.join("frame.png"))?);
+    let mut encoder = png::Encoder::new(file, frame.width, frame.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&frame.pixels)?;
+    Ok(())
+}
+
+// without png support the pixels are stored raw, the size is in the name.
+#[cfg(not(feature = "image-loading"))]
+fn write_frame(path: &Path, frame: &ReportFrame) -> Result<()> {
+    let name = format!("frame-{}x{}.rgba", frame.width, frame.height);
+    let mut file = BufWriter::new(File::create(path.join(name))?);
+    file.write_all(&frame.pixels)?;
+    file.flush()?;
+    Ok(())
+}
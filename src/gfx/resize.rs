@@ -0,0 +1,282 @@
+#![allow(dead_code)]
+
+use std::time::{Duration, Instant};
+
+use vulkanalia::prelude::v1_0::*;
+
+/// The extent a swapchain for a window of `width` by `height` pixels gets, clamped to what the
+/// surface supports. none while the window or the surface has no area, e.g. when minimized.
+pub(crate) fn surface_extent(
+    width: u32,
+    height: u32,
+    capabilities: &vk::SurfaceCapabilitiesKHR,
+) -> Option<vk::Extent2D> {
+    // the surface decides unless it leaves the extent to the swapchain
+    let extent = if capabilities.current_extent.width != u32::MAX {
+        capabilities.current_extent
+    } else {
+        let min = capabilities.min_image_extent;
+        let max = capabilities.max_image_extent;
+        vk::Extent2D {
+            width: width.clamp(min.width, max.width.max(min.width)),
+            height: height.clamp(min.height, max.height.max(min.height)),
+        }
+    };
+
+    // some platforms report a zero maximum instead of a zero current extent
+    let empty = extent.width == 0
+        || extent.height == 0
+        || capabilities.max_image_extent.width == 0
+        || capabilities.max_image_extent.height == 0;
+    if empty || width == 0 || height == 0 {
+        None
+    } else {
+        Some(extent)
+    }
+}
+
+/// the size in logical pixels of `extent` physical pixels on a display of `scale_factor`,
+/// the size windows, input and ui are laid out in. a scale below or at 0 is taken as 1.
+pub fn logical_extent(extent: vk::Extent2D, scale_factor: f64) -> (f32, f32) {
+    let scale = if scale_factor > 0.0 {
+        scale_factor
+    } else {
+        1.0
+    };
+    (
+        (extent.width as f64 / scale) as f32,
+        (extent.height as f64 / scale) as f32,
+    )
+}
+
+/// the extent the scene is rendered at for a swapchain of `extent` at `render_scale`, at
+/// least a pixel in each direction.
+pub fn render_extent(extent: vk::Extent2D, render_scale: f32) -> vk::Extent2D {
+    let scale = |size: u32| ((size as f32 * render_scale).round() as u32).max(1);
+    vk::Extent2D {
+        width: scale(extent.width),
+        height: scale(extent.height),
+    }
+}
+
+/// `rect` of an image of `from` pixels on an image of `to` pixels, grown to whole pixels so
+/// it covers at least the same area and clamped to the image.
+pub fn scale_rect(rect: vk::Rect2D, from: vk::Extent2D, to: vk::Extent2D) -> vk::Rect2D {
+    if from == to {
+        return rect;
+    }
+
+    let sx = to.width as f64 / from.width.max(1) as f64;
+    let sy = to.height as f64 / from.height.max(1) as f64;
+    let x0 = (rect.offset.x as f64 * sx).floor().max(0.0) as i32;
+    let y0 = (rect.offset.y as f64 * sy).floor().max(0.0) as i32;
+    let x1 = ((rect.offset.x as f64 + rect.extent.width as f64) * sx).ceil() as i32;
+    let y1 = ((rect.offset.y as f64 + rect.extent.height as f64) * sy).ceil() as i32;
+    let x1 = x1.min(to.width as i32).max(x0 + 1);
+    let y1 = y1.min(to.height as i32).max(y0 + 1);
+    vk::Rect2D {
+        offset: vk::Offset2D { x: x0, y: y0 },
+        extent: vk::Extent2D {
+            width: (x1 - x0) as u32,
+            height: (y1 - y0) as u32,
+        },
+    }
+}
+
+/// `rects` clipped to an image of `extent`, rectangles left without area are dropped.
+pub fn clip_rects(rects: &[vk::Rect2D], extent: vk::Extent2D) -> Vec<vk::Rect2D> {
+    rects
+        .iter()
+        .filter_map(|r| {
+            let x0 = r.offset.x.max(0);
+            let y0 = r.offset.y.max(0);
+            let x1 = (r.offset.x + r.extent.width as i32).min(extent.width as i32);
+            let y1 = (r.offset.y + r.extent.height as i32).min(extent.height as i32);
+            if x1 <= x0 || y1 <= y0 {
+                return None;
+            }
+
+            Some(vk::Rect2D {
+                offset: vk::Offset2D { x: x0, y: y0 },
+                extent: vk::Extent2D {
+                    width: (x1 - x0) as u32,
+                    height: (y1 - y0) as u32,
+                },
+            })
+        })
+        .collect()
+}
+
+/// whether clearing and drawing `scissors` one by one touches fewer pixels than clearing
+/// their bounding `area`, e.g. for damage in opposite corners of a window.
+pub fn scissored_clears(scissors: &[vk::Rect2D], area: vk::Rect2D) -> bool {
+    let pixels = |r: &vk::Rect2D| r.extent.width as u64 * r.extent.height as u64;
+    scissors.len() > 1 && scissors.iter().map(pixels).sum::<u64>() < pixels(&area)
+}
+
+/// The format, extent and image count of a swapchain.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SwapchainShape {
+    pub format: vk::Format,
+    pub extent: vk::Extent2D,
+    pub image_count: usize,
+}
+
+/// What a swapchain recreation rebuilds besides the views and framebuffers of the new
+/// images, which are always new. render passes and pipelines survive as long as the format
+/// does.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SwapchainRebuild {
+    /// the scene target is reallocated at the new render extent.
+    pub scene_target: bool,
+    /// the per-image copies follow a new extent or image count.
+    pub readbacks: bool,
+    /// the per-image command objects follow a new image count.
+    pub commands: bool,
+}
+
+impl SwapchainRebuild {
+    /// what replacing a swapchain of `old` with one of `new` rebuilds, `target` is the extent
+    /// the scene target currently has and `render_scale` the scale it is rendered at.
+    pub fn between(
+        old: SwapchainShape,
+        new: SwapchainShape,
+        target: vk::Extent2D,
+        render_scale: f32,
+    ) -> Self {
+        Self {
+            scene_target: render_extent(new.extent, render_scale) != target,
+            readbacks: old.extent != new.extent || old.image_count != new.image_count,
+            commands: old.image_count != new.image_count,
+        }
+    }
+}
+
+/// Decides when the swapchain is recreated. Resizes are debounced so dragging a window edge
+/// recreates once it settles, a swapchain that went out of date is recreated right away, and
+/// nothing is rendered while the window has no area.
+#[derive(Clone, Debug)]
+pub(crate) struct ResizeTracker {
+    /// how long the size must stay the same before the swapchain follows it.
+    pub debounce: Duration,
+    // physical pixels per logical pixel of the display the window is on
+    scale_factor: f64,
+    minimized: bool,
+    // when the size last changed, while a recreation is pending
+    pending: Option<Instant>,
+    // the swapchain can't be presented anymore
+    forced: bool,
+}
+
+impl ResizeTracker {
+    pub fn new(debounce: Duration) -> Self {
+        Self {
+            debounce,
+            scale_factor: 1.0,
+            minimized: false,
+            pending: None,
+            forced: false,
+        }
+    }
+
+    /// start on a display of `scale_factor`.
+    pub fn with_scale_factor(mut self, scale_factor: f64) -> Self {
+        self.scale_factor = scale_factor;
+        self
+    }
+
+    /// the window was resized, a zero size means it was minimized. a restored window has its
+    /// final size, rendering resumes with a new swapchain right away instead of after the
+    /// debounce.
+    pub fn resized(&mut self, width: u32, height: u32) {
+        let restored = self.minimized;
+        self.minimized = width == 0 || height == 0;
+        self.pending = Some(Instant::now());
+        if restored && !self.minimized {
+            self.forced = true;
+        }
+    }
+
+    /// the window changed between windowed and fullscreen or moved to a display of another
+    /// scale, the new size is final so the swapchain follows right away instead of after the
+    /// debounce.
+    pub fn mode_changed(&mut self, width: u32, height: u32) {
+        self.minimized = width == 0 || height == 0;
+        self.pending = None;
+        self.forced = true;
+    }
+
+    /// the window moved to a display of another scale, `width` and `height` are its new size
+    /// in physical pixels.
+    pub fn scale_changed(&mut self, scale_factor: f64, width: u32, height: u32) {
+        self.scale_factor = scale_factor;
+        self.mode_changed(width, height);
+    }
+
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// the swapchain still works but no longer matches the surface.
+    pub fn suboptimal(&mut self) {
+        self.pending.get_or_insert_with(Instant::now);
+    }
+
+    /// the swapchain can't be used anymore and must be recreated before the next frame.
+    pub fn out_of_date(&mut self) {
+        self.forced = true;
+    }
+
+    /// the surface has no area, rendering stops until the next resize.
+    pub fn surface_lost_area(&mut self) {
+        self.minimized = true;
+    }
+
+    pub fn is_minimized(&self) -> bool {
+        self.minimized
+    }
+
+    /// whether the swapchain should be recreated before the next frame.
+    pub fn should_recreate(&self) -> bool {
+        if self.minimized {
+            return false;
+        }
+
+        self.forced || self.pending.is_some_and(|at| at.elapsed() >= self.debounce)
+    }
+
+    /// the swapchain matches the surface again.
+    pub fn recreated(&mut self) {
+        self.pending = None;
+        self.forced = false;
+    }
+}
+
+impl Default for ResizeTracker {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(50))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restoring_at_the_same_size_recreates_once() {
+        // long enough that only the restore can ask for a recreation
+        let mut tracker = ResizeTracker::new(Duration::from_secs(3600));
+        tracker.resized(0, 0);
+        assert!(tracker.is_minimized());
+        assert!(!tracker.should_recreate());
+
+        tracker.resized(800, 600);
+        assert!(!tracker.is_minimized());
+        assert!(tracker.should_recreate());
+
+        tracker.recreated();
+        assert!(!tracker.should_recreate());
+        tracker.resized(800, 600);
+        assert!(!tracker.should_recreate());
+    }
+}
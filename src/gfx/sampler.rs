@@ -0,0 +1,192 @@
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use anyhow::Result;
+use vulkanalia::prelude::v1_0::*;
+
+use super::check_sampler;
+
+/// How a sampler filters and addresses a texture. samplers are shared, see `SamplerCache`,
+/// so equal descriptors end up with the same `vk::Sampler`.
+#[derive(Copy, Clone, Debug)]
+pub struct SamplerDescriptor {
+    pub mag_filter: vk::Filter,
+    pub min_filter: vk::Filter,
+    pub mipmap_mode: vk::SamplerMipmapMode,
+    pub address_mode_u: vk::SamplerAddressMode,
+    pub address_mode_v: vk::SamplerAddressMode,
+    pub address_mode_w: vk::SamplerAddressMode,
+    /// 1 disables anisotropic filtering, up to `max_sampler_anisotropy` of the adapter.
+    pub max_anisotropy: f32,
+    /// the comparison of depth samplers, reads return how many texels passed it.
+    pub compare: Option<vk::CompareOp>,
+    pub min_lod: f32,
+    pub max_lod: f32,
+    /// added to the level of detail the sampler picks, positive values blur.
+    pub mip_lod_bias: f32,
+}
+
+impl SamplerDescriptor {
+    /// linear filtering over all levels, `address` applies to all axes.
+    pub const fn linear(address: vk::SamplerAddressMode) -> Self {
+        Self {
+            mag_filter: vk::Filter::LINEAR,
+            min_filter: vk::Filter::LINEAR,
+            mipmap_mode: vk::SamplerMipmapMode::LINEAR,
+            address_mode_u: address,
+            address_mode_v: address,
+            address_mode_w: address,
+            max_anisotropy: 1.0,
+            compare: None,
+            min_lod: 0.0,
+            max_lod: vk::LOD_CLAMP_NONE,
+            mip_lod_bias: 0.0,
+        }
+    }
+
+    /// the nearest texel of the nearest level, `address` applies to all axes.
+    pub const fn nearest(address: vk::SamplerAddressMode) -> Self {
+        Self {
+            mag_filter: vk::Filter::NEAREST,
+            min_filter: vk::Filter::NEAREST,
+            mipmap_mode: vk::SamplerMipmapMode::NEAREST,
+            ..Self::linear(address)
+        }
+    }
+
+    pub fn with_anisotropy(mut self, max_anisotropy: f32) -> Self {
+        self.max_anisotropy = max_anisotropy;
+        self
+    }
+
+    pub fn with_compare(mut self, compare: vk::CompareOp) -> Self {
+        self.compare = Some(compare);
+        self
+    }
+
+    pub fn with_lod(mut self, min_lod: f32, max_lod: f32) -> Self {
+        self.min_lod = min_lod;
+        self.max_lod = max_lod;
+        self
+    }
+
+    pub fn with_lod_bias(mut self, mip_lod_bias: f32) -> Self {
+        self.mip_lod_bias = mip_lod_bias;
+        self
+    }
+
+    /// the create info of the sampler.
+    pub fn info(&self) -> vk::SamplerCreateInfo {
+        vk::SamplerCreateInfo::builder()
+            .mag_filter(self.mag_filter)
+            .min_filter(self.min_filter)
+            .mipmap_mode(self.mipmap_mode)
+            .mip_lod_bias(self.mip_lod_bias)
+            .address_mode_u(self.address_mode_u)
+            .address_mode_v(self.address_mode_v)
+            .address_mode_w(self.address_mode_w)
+            .anisotropy_enable(self.max_anisotropy > 1.0)
+            .max_anisotropy(self.max_anisotropy)
+            .compare_enable(self.compare.is_some())
+            .compare_op(self.compare.unwrap_or(vk::CompareOp::ALWAYS))
+            .min_lod(self.min_lod)
+            .max_lod(self.max_lod)
+            .build()
+    }
+
+    // the fields with the floats as bits, so descriptors can be hashed
+    fn key(&self) -> impl Hash + Eq {
+        (
+            (self.mag_filter, self.min_filter, self.mipmap_mode),
+            (
+                self.address_mode_u,
+                self.address_mode_v,
+                self.address_mode_w,
+            ),
+            self.max_anisotropy.to_bits(),
+            self.compare,
+            (
+                self.min_lod.to_bits(),
+                self.max_lod.to_bits(),
+                self.mip_lod_bias.to_bits(),
+            ),
+        )
+    }
+}
+
+impl Default for SamplerDescriptor {
+    fn default() -> Self {
+        Self::linear(vk::SamplerAddressMode::REPEAT)
+    }
+}
+
+impl PartialEq for SamplerDescriptor {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for SamplerDescriptor {}
+
+impl Hash for SamplerDescriptor {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+/// The samplers of a device by descriptor, each is created on first use and lives as long
+/// as the cache. users must not destroy them.
+pub struct SamplerCache {
+    // 1 when the device was created without anisotropic filtering
+    max_anisotropy: f32,
+    samplers: RefCell<HashMap<SamplerDescriptor, vk::Sampler>>,
+}
+
+impl SamplerCache {
+    pub fn new(max_anisotropy: f32) -> Self {
+        Self {
+            max_anisotropy,
+            samplers: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// the sampler of `descriptor`, created when it is asked for the first time.
+    pub unsafe fn get(
+        &self,
+        device: &vulkanalia::Device,
+        descriptor: &SamplerDescriptor,
+    ) -> Result<vk::Sampler> {
+        if let Some(sampler) = self.samplers.borrow().get(descriptor) {
+            return Ok(*sampler);
+        }
+
+        check_sampler(descriptor, self.max_anisotropy)?;
+        let sampler = device.create_sampler(&descriptor.info(), None)?;
+        self.samplers.borrow_mut().insert(*descriptor, sampler);
+        Ok(sampler)
+    }
+
+    /// the highest anisotropy descriptors may ask for.
+    pub fn max_anisotropy(&self) -> f32 {
+        self.max_anisotropy
+    }
+
+    /// the number of distinct samplers created.
+    pub fn len(&self) -> usize {
+        self.samplers.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub unsafe fn destroy(&self, device: &vulkanalia::Device) {
+        let mut samplers = self.samplers.borrow_mut();
+        samplers
+            .drain()
+            .for_each(|(_, s)| device.destroy_sampler(s, None));
+    }
+}
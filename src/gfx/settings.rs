@@ -0,0 +1,209 @@
+#![allow(dead_code)]
+
+use std::env;
+
+use anyhow::{anyhow, Result};
+use tracing::warn;
+use vulkanalia::prelude::v1_0::*;
+
+use super::SamplerDescriptor;
+
+/// How textures are sampled and loaded, changed at runtime with
+/// `Device::set_graphics_settings`. texture samplers pick the changes up in the next frame,
+/// textures loaded from files keep their size until they are loaded again, see
+/// `AssetCache::apply_graphics_settings`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GraphicsSettings {
+    /// the anisotropic filtering of texture samplers, 1 disables it. clamped to the adapter.
+    pub anisotropy: f32,
+    /// added to the level of detail texture samplers pick, positive values blur.
+    pub mip_lod_bias: f32,
+    /// the size of textures loaded from files relative to the files, in 0..=1.
+    pub texture_scale: f32,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            anisotropy: 16.0,
+            mip_lod_bias: 0.0,
+            texture_scale: 1.0,
+        }
+    }
+}
+
+impl GraphicsSettings {
+    /// refuse settings no device can use.
+    pub fn check(&self) -> Result<()> {
+        if self.anisotropy.is_nan() || self.anisotropy < 1.0 {
+            return Err(anyhow!(
+                "Anisotropy must be at least 1, not {}.",
+                self.anisotropy
+            ));
+        }
+        if !self.mip_lod_bias.is_finite() {
+            return Err(anyhow!("Mip lod bias must be finite."));
+        }
+        if !(self.texture_scale > 0.0 && self.texture_scale <= 1.0) {
+            return Err(anyhow!(
+                "Texture scale must be in 0..=1 and above 0, not {}.",
+                self.texture_scale
+            ));
+        }
+        Ok(())
+    }
+
+    /// `descriptor` with the anisotropy, clamped to `max_anisotropy`, and the lod bias of the
+    /// settings. nearest filtering keeps its single texel.
+    pub fn texture_sampler(
+        &self,
+        descriptor: &SamplerDescriptor,
+        max_anisotropy: f32,
+    ) -> SamplerDescriptor {
+        let mut descriptor = *descriptor;
+        if descriptor.min_filter == vk::Filter::LINEAR {
+            descriptor.max_anisotropy = self.anisotropy.min(max_anisotropy).max(1.0);
+        }
+        descriptor.mip_lod_bias = self.mip_lod_bias;
+        descriptor
+    }
+
+    /// the size a texture of `width` by `height` texels is loaded at, at least a texel.
+    pub fn texture_extent(&self, width: u32, height: u32) -> (u32, u32) {
+        let scale = |size: u32| ((size as f32 * self.texture_scale).round() as u32).max(1);
+        (scale(width), scale(height))
+    }
+}
+
+/// The environment variable that overrides the default `ValidationSettings`, see
+/// `ValidationSettings::parse` for its values.
+pub const VALIDATION_VARIABLE: &str = "DEIMOS_VALIDATION";
+
+/// The lowest severity of the validation messages passed on to the log.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ValidationSeverity {
+    Verbose,
+    Info,
+    Warning,
+    Error,
+}
+
+impl ValidationSeverity {
+    /// the severities of `VK_EXT_debug_utils` from this one up.
+    pub fn flags(&self) -> vk::DebugUtilsMessageSeverityFlagsEXT {
+        use vk::DebugUtilsMessageSeverityFlagsEXT as F;
+        match self {
+            ValidationSeverity::Verbose => F::VERBOSE | F::INFO | F::WARNING | F::ERROR,
+            ValidationSeverity::Info => F::INFO | F::WARNING | F::ERROR,
+            ValidationSeverity::Warning => F::WARNING | F::ERROR,
+            ValidationSeverity::Error => F::ERROR,
+        }
+    }
+}
+
+/// Whether the validation layer checks a device and which of its messages are logged, given
+/// at creation with `Device::create_with_validation`. the other constructors take
+/// `from_env`, so release builds can be validated in the field by setting
+/// `DEIMOS_VALIDATION`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ValidationSettings {
+    /// load the validation layer, windowed devices fail to create when it is not installed.
+    pub enabled: bool,
+    pub severity: ValidationSeverity,
+}
+
+impl Default for ValidationSettings {
+    /// validated in debug builds, with every message.
+    fn default() -> Self {
+        Self {
+            enabled: cfg!(debug_assertions),
+            severity: ValidationSeverity::Verbose,
+        }
+    }
+}
+
+impl ValidationSettings {
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Default::default()
+        }
+    }
+
+    /// validated, logging the messages from `severity` up.
+    pub fn enabled(severity: ValidationSeverity) -> Self {
+        Self {
+            enabled: true,
+            severity,
+        }
+    }
+
+    /// the default settings changed by a value of `DEIMOS_VALIDATION`. `0`, `off` and
+    /// `false` disable validation, `1`, `on` and `true` enable it, and `verbose`, `info`,
+    /// `warning` or `error` enable it logging the messages from that severity up.
+    pub fn parse(value: &str) -> Result<Self> {
+        let severity = |severity| Ok(Self::enabled(severity));
+        match value.trim().to_ascii_lowercase().as_str() {
+            "0" | "off" | "false" => Ok(Self::disabled()),
+            "1" | "on" | "true" => severity(Self::default().severity),
+            "verbose" => severity(ValidationSeverity::Verbose),
+            "info" => severity(ValidationSeverity::Info),
+            "warning" => severity(ValidationSeverity::Warning),
+            "error" => severity(ValidationSeverity::Error),
+            _ => Err(anyhow!(
+                "Unknown validation setting `{}`, expected on, off or a severity.",
+                value
+            )),
+        }
+    }
+
+    /// the settings `DEIMOS_VALIDATION` asks for, the default ones when it isn't set. a value
+    /// that can't be parsed is ignored with a warning rather than failing the device.
+    pub fn from_env() -> Self {
+        let Ok(value) = env::var(VALIDATION_VARIABLE) else {
+            return Self::default();
+        };
+        Self::parse(&value).unwrap_or_else(|error| {
+            warn!("Ignoring {}: {}", VALIDATION_VARIABLE, error);
+            Self::default()
+        })
+    }
+}
+
+/// average rgba8 `pixels` of `width` by `height` down to `new_width` by `new_height`, each
+/// new pixel is the mean of the pixels it covers.
+pub fn shrink_pixels(
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+    new_width: u32,
+    new_height: u32,
+) -> Vec<u8> {
+    // the source rows or columns a new pixel covers, at least one
+    let span = |i: u32, size: u32, new_size: u32| {
+        let start = (i as u64 * size as u64 / new_size as u64) as u32;
+        let end = ((i as u64 + 1) * size as u64).div_ceil(new_size as u64) as u32;
+        start..end.max(start + 1).min(size)
+    };
+
+    let mut shrunk = Vec::with_capacity((new_width * new_height * 4) as usize);
+    for y in 0..new_height {
+        let rows = span(y, height, new_height);
+        for x in 0..new_width {
+            let columns = span(x, width, new_width);
+            let mut sum = [0u32; 4];
+            let mut count = 0;
+            for row in rows.clone() {
+                for column in columns.clone() {
+                    let i = ((row * width + column) * 4) as usize;
+                    for (s, p) in sum.iter_mut().zip(&pixels[i..i + 4]) {
+                        *s += *p as u32;
+                    }
+                    count += 1;
+                }
+            }
+            shrunk.extend(sum.iter().map(|s| ((s + count / 2) / count) as u8));
+        }
+    }
+    shrunk
+}
@@ -0,0 +1,31 @@
+#![allow(dead_code)]
+
+use anyhow::Result;
+use vulkanalia::bytecode::Bytecode;
+use vulkanalia::prelude::v1_0::*;
+
+pub struct Shader {
+    pub module: vk::ShaderModule,
+}
+
+impl Shader {
+    pub unsafe fn create(device: &vulkanalia::Device, code: &[u8]) -> Result<Shader> {
+        // get aligned bytes
+        let bytes = Bytecode::new(code)?;
+
+        // create info
+        let info = vk::ShaderModuleCreateInfo::builder()
+            .code_size(bytes.code_size())
+            .code(bytes.code());
+
+        // all done
+        Ok(Shader {
+            module: device.create_shader_module(&info, None)?,
+        })
+    }
+
+    pub unsafe fn destroy(&self, device: &Device) {
+        // destroy the shader
+        device.destroy_shader_module(self.module, None);
+    }
+}
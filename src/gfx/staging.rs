@@ -0,0 +1,185 @@
+#![allow(dead_code)]
+
+use std::cell::{Cell, RefCell};
+use std::slice;
+
+use anyhow::{anyhow, Result};
+use vulkanalia::prelude::v1_0::*;
+
+use super::allocation::{self, AllocationKind};
+use super::device::get_memory_type_index;
+use super::{Buffer, Timeline};
+
+/// what the belt memory can be used for, per frame data of every kind.
+pub const STAGING_USAGE: vk::BufferUsageFlags = vk::BufferUsageFlags::from_bits_truncate(
+    vk::BufferUsageFlags::VERTEX_BUFFER.bits()
+        | vk::BufferUsageFlags::INDEX_BUFFER.bits()
+        | vk::BufferUsageFlags::UNIFORM_BUFFER.bits()
+        | vk::BufferUsageFlags::STORAGE_BUFFER.bits()
+        | vk::BufferUsageFlags::TRANSFER_SRC.bits(),
+);
+
+const PROPERTIES: vk::MemoryPropertyFlags = vk::MemoryPropertyFlags::from_bits_truncate(
+    vk::MemoryPropertyFlags::HOST_VISIBLE.bits() | vk::MemoryPropertyFlags::HOST_COHERENT.bits(),
+);
+
+/// Host visible memory for data that lives one frame, e.g. uniforms, debug lines and ui
+/// vertices. every frame in flight has its own ring of persistently mapped buffers and a bump
+/// allocator over it. a frame's ring starts over once the submission that used it completed,
+/// a ring that runs out gets another buffer of twice the size and keeps it.
+pub struct StagingBelt {
+    memory_type: u32,
+    frames: Vec<StagingFrame>,
+}
+
+struct StagingFrame {
+    chunks: RefCell<Vec<Chunk>>,
+    // the timeline value of the submission that last used the ring
+    pending: Cell<Option<u64>>,
+}
+
+struct Chunk {
+    buffer: Buffer,
+    data: *mut u8,
+    used: Cell<vk::DeviceSize>,
+}
+
+// the mapping belongs to the chunk and is only written through the belt that owns it
+unsafe impl Send for Chunk {}
+
+impl StagingBelt {
+    /// create a belt of `frames` rings, each starting with `size` bytes.
+    pub unsafe fn create(
+        instance: &vulkanalia::Instance,
+        physical: vk::PhysicalDevice,
+        device: &vulkanalia::Device,
+        frames: usize,
+        size: vk::DeviceSize,
+    ) -> Result<Self> {
+        let mut memory_type = 0;
+        let mut rings = vec![];
+        for _ in 0..frames {
+            let chunk = create_chunk(device, size, |requirements| {
+                memory_type = get_memory_type_index(instance, &physical, PROPERTIES, requirements)?;
+                Ok(memory_type)
+            })?;
+            rings.push(StagingFrame {
+                chunks: RefCell::new(vec![chunk]),
+                pending: Cell::new(None),
+            });
+        }
+        Ok(Self {
+            memory_type,
+            frames: rings,
+        })
+    }
+
+    /// the number of frames in flight the belt has rings for.
+    pub fn frames(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// the bytes the ring of `frame` holds over all its buffers.
+    pub fn capacity(&self, frame: usize) -> vk::DeviceSize {
+        let chunks = self.frames[frame].chunks.borrow();
+        chunks.iter().map(|c| c.buffer.size).sum()
+    }
+
+    /// hand out `size` bytes aligned to `align` from the ring of `frame`, returns the buffer,
+    /// the offset into it and the mapped bytes. when the ring was submitted before, this
+    /// waits on `timeline` for the submission and starts the ring over first.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn allocate(
+        &self,
+        device: &vulkanalia::Device,
+        timeline: &Timeline,
+        frame: usize,
+        size: vk::DeviceSize,
+        align: vk::DeviceSize,
+    ) -> Result<(vk::Buffer, vk::DeviceSize, &mut [u8])> {
+        if size == 0 {
+            return Err(anyhow!("Staging allocations need a size."));
+        }
+        if !align.is_power_of_two() {
+            return Err(anyhow!(
+                "Staging alignment {} is not a power of two.",
+                align
+            ));
+        }
+
+        // the ring is only reused once the gpu is done with it. no bytes of it are borrowed
+        // anymore, `finish` takes the belt mutably
+        let ring = &self.frames[frame];
+        if let Some(value) = ring.pending.get() {
+            timeline.wait(device, value)?;
+            ring.chunks.borrow().iter().for_each(|c| c.used.set(0));
+            ring.pending.set(None);
+        }
+
+        let mut chunks = ring.chunks.borrow_mut();
+        let fits = |c: &Chunk| aligned(c.used.get(), align) + size <= c.buffer.size;
+        if !chunks.iter().any(fits) {
+            let largest = chunks.iter().map(|c| c.buffer.size).max().unwrap_or(0);
+            let chunk_size = (largest * 2).max(size.next_power_of_two());
+            chunks.push(create_chunk(device, chunk_size, |_| Ok(self.memory_type))?);
+        }
+
+        // each range is handed out once until the ring starts over, the slices never overlap
+        let chunk = chunks.iter().find(|c| fits(c)).unwrap();
+        let offset = aligned(chunk.used.get(), align);
+        chunk.used.set(offset + size);
+        let bytes = slice::from_raw_parts_mut(chunk.data.add(offset as usize), size as usize);
+        Ok((chunk.buffer.buffer, offset, bytes))
+    }
+
+    /// the ring of `frame` was submitted with timeline value `value`, its next allocation
+    /// waits for it.
+    pub fn finish(&mut self, frame: usize, value: u64) {
+        let ring = &self.frames[frame];
+        let used = ring.chunks.borrow().iter().any(|c| c.used.get() > 0);
+        if used {
+            ring.pending.set(Some(value));
+        }
+    }
+
+    pub unsafe fn destroy(&self, device: &vulkanalia::Device) {
+        for ring in &self.frames {
+            for chunk in ring.chunks.borrow().iter() {
+                device.unmap_memory(chunk.buffer.memory);
+                chunk.buffer.destroy(device);
+            }
+        }
+    }
+}
+
+fn aligned(offset: vk::DeviceSize, align: vk::DeviceSize) -> vk::DeviceSize {
+    (offset + align - 1) & !(align - 1)
+}
+
+// a persistently mapped buffer, `memory_type` picks the memory for its requirements
+unsafe fn create_chunk(
+    device: &vulkanalia::Device,
+    size: vk::DeviceSize,
+    memory_type: impl FnOnce(vk::MemoryRequirements) -> Result<u32>,
+) -> Result<Chunk> {
+    let info = vk::BufferCreateInfo::builder()
+        .size(size)
+        .usage(STAGING_USAGE)
+        .sharing_mode(vk::SharingMode::EXCLUSIVE);
+    let buffer = device.create_buffer(&info, None)?;
+
+    let requirements = device.get_buffer_memory_requirements(buffer);
+    let info = vk::MemoryAllocateInfo::builder()
+        .allocation_size(requirements.size)
+        .memory_type_index(memory_type(requirements)?);
+    let memory = allocation::allocate(device, &info, AllocationKind::Staging, buffer.as_raw())
+        .inspect_err(|_| device.destroy_buffer(buffer, None))?;
+    device.bind_buffer_memory(buffer, memory, 0)?;
+
+    let data = device.map_memory(memory, 0, size, vk::MemoryMapFlags::empty())?;
+    Ok(Chunk {
+        buffer: Buffer::create(buffer, memory, size),
+        data: data.cast(),
+        used: Cell::new(0),
+    })
+}
@@ -0,0 +1,130 @@
+use anyhow::{anyhow, Result};
+use vulkanalia::prelude::v1_0::*;
+
+use super::{Timeline, TimelineWait};
+
+/// A submit of a `SubmitGraph`, to name it as a dependency of later submits.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SubmitId(usize);
+
+impl SubmitId {
+    /// the position of the submit in the graph, and of its value in `SubmitGraph::submit`.
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+// command buffers on a queue, signaling the next value of a timeline
+struct Node<'a> {
+    timeline: &'a Timeline,
+    queue: vk::Queue,
+    command_buffers: Vec<vk::CommandBuffer>,
+    after: Vec<(SubmitId, vk::PipelineStageFlags)>,
+    waits: Vec<TimelineWait>,
+}
+
+/// The submits of a frame and the order between them, e.g. a shadow pass on a compute queue
+/// the main pass waits for. each submit signals the next value of its timeline and waits on
+/// the gpu for the values of the submits it comes after, submits on different queues overlap
+/// otherwise. submits are made in the order they are added, so a submit can only come after
+/// earlier ones and the graph has no cycles.
+#[derive(Default)]
+pub struct SubmitGraph<'a> {
+    nodes: Vec<Node<'a>>,
+}
+
+impl<'a> SubmitGraph<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// add a submit of `command_buffers` to `queue`, signaling `timeline`.
+    pub fn add(
+        &mut self,
+        timeline: &'a Timeline,
+        queue: vk::Queue,
+        command_buffers: &[vk::CommandBuffer],
+    ) -> SubmitId {
+        self.nodes.push(Node {
+            timeline,
+            queue,
+            command_buffers: command_buffers.to_vec(),
+            after: vec![],
+            waits: vec![],
+        });
+        SubmitId(self.nodes.len() - 1)
+    }
+
+    /// make `submit` wait at `stage` until `dependency` completed.
+    pub fn after(
+        &mut self,
+        submit: SubmitId,
+        dependency: SubmitId,
+        stage: vk::PipelineStageFlags,
+    ) -> Result<()> {
+        if dependency.0 >= submit.0 || submit.0 >= self.nodes.len() {
+            return Err(anyhow!(
+                "Submit {} can't come after submit {}, only after submits added before it.",
+                submit.0,
+                dependency.0
+            ));
+        }
+        self.nodes[submit.0].after.push((dependency, stage));
+        Ok(())
+    }
+
+    /// make `submit` wait for work outside the graph, see `Timeline::gpu_wait`.
+    pub fn wait(&mut self, submit: SubmitId, wait: TimelineWait) {
+        self.nodes[submit.0].waits.push(wait);
+    }
+
+    /// the submits no other submit comes after, the ends of the graph.
+    pub fn leaves(&self) -> Vec<SubmitId> {
+        (0..self.nodes.len())
+            .filter(|i| {
+                !self
+                    .nodes
+                    .iter()
+                    .any(|n| n.after.iter().any(|(d, _)| d.0 == *i))
+            })
+            .map(SubmitId)
+            .collect()
+    }
+
+    /// the timeline `submit` signals.
+    pub fn timeline(&self, submit: SubmitId) -> &'a Timeline {
+        self.nodes[submit.0].timeline
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// submit everything in order, returns the timeline value of every submit by index.
+    /// dependencies on fence backed timelines block until they completed instead.
+    pub unsafe fn submit(self, device: &vulkanalia::Device) -> Result<Vec<u64>> {
+        let mut values: Vec<u64> = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let mut waits = node.waits.clone();
+            for (dependency, stage) in &node.after {
+                let timeline = self.nodes[dependency.0].timeline;
+                if let Some(wait) = timeline.gpu_wait(device, values[dependency.0], *stage)? {
+                    waits.push(wait);
+                }
+            }
+            values.push(node.timeline.submit(
+                device,
+                node.queue,
+                &node.command_buffers,
+                &[],
+                &waits,
+                &[],
+            )?);
+        }
+        Ok(values)
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::Result;
+use vulkanalia::prelude::v1_0::*;
+use vulkanalia::vk::ExtHeadlessSurfaceExtension;
+#[cfg(feature = "window")]
+use vulkanalia::window as vk_window;
+#[cfg(feature = "window")]
+use winit::window::Window;
+
+/// What a `Device` presents to, a winit window or a `HeadlessWindow`. the device asks it for
+/// its size when the swapchain follows a resize.
+pub trait DeviceWindow {
+    /// the size of the area drawn to in physical pixels.
+    fn inner_size(&self) -> (u32, u32);
+
+    /// physical pixels per logical pixel of the display.
+    fn scale_factor(&self) -> f64;
+
+    /// the refresh rate of the display in millihertz, none when unknown.
+    fn refresh_rate_millihertz(&self) -> Option<u32>;
+
+    /// the instance extensions surfaces of the window need.
+    fn required_extensions(&self) -> Vec<&'static vk::ExtensionName>;
+
+    /// create a surface of the window on `instance`, which has the required extensions.
+    unsafe fn create_surface(&self, instance: &Instance) -> Result<vk::SurfaceKHR>;
+}
+
+#[cfg(feature = "window")]
+impl DeviceWindow for Window {
+    fn inner_size(&self) -> (u32, u32) {
+        let size = Window::inner_size(self);
+        (size.width, size.height)
+    }
+
+    fn scale_factor(&self) -> f64 {
+        Window::scale_factor(self)
+    }
+
+    fn refresh_rate_millihertz(&self) -> Option<u32> {
+        self.current_monitor()
+            .and_then(|m| m.refresh_rate_millihertz())
+    }
+
+    fn required_extensions(&self) -> Vec<&'static vk::ExtensionName> {
+        vk_window::get_required_instance_extensions(self).to_vec()
+    }
+
+    unsafe fn create_surface(&self, instance: &Instance) -> Result<vk::SurfaceKHR> {
+        Ok(vk_window::create_surface(instance, self, self)?)
+    }
+}
+
+/// A window without a display, the device presents to a `VK_EXT_headless_surface` of it and
+/// nothing is shown. for running the frames of a `Device` in tests and tools, e.g. on the
+/// null driver with `Device::create_null`. the swapchain follows `width` and `height` at the
+/// next resize the device is told about.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HeadlessWindow {
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f64,
+}
+
+impl HeadlessWindow {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            scale_factor: 1.0,
+        }
+    }
+}
+
+impl DeviceWindow for HeadlessWindow {
+    fn inner_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    fn refresh_rate_millihertz(&self) -> Option<u32> {
+        None
+    }
+
+    fn required_extensions(&self) -> Vec<&'static vk::ExtensionName> {
+        vec![
+            &vk::KHR_SURFACE_EXTENSION.name,
+            &vk::EXT_HEADLESS_SURFACE_EXTENSION.name,
+        ]
+    }
+
+    unsafe fn create_surface(&self, instance: &Instance) -> Result<vk::SurfaceKHR> {
+        let info = vk::HeadlessSurfaceCreateInfoEXT::builder();
+        Ok(instance.create_headless_surface_ext(&info, None)?)
+    }
+}
@@ -11,9 +11,10 @@ use anyhow::Result;
 use vulkanalia::prelude::v1_0::*;
 use vulkanalia::vk::KhrSurfaceExtension;
 
+use super::ColorSpace;
 
 #[derive(Clone, Debug)]
-pub struct SwapChainSupport {
+pub(crate) struct SwapChainSupport {
     pub capabilities: vk::SurfaceCapabilitiesKHR,
     pub formats: Vec<vk::SurfaceFormatKHR>,
     pub present_modes: Vec<vk::PresentModeKHR>,
@@ -33,4 +34,74 @@ impl SwapChainSupport {
                 .get_physical_device_surface_present_modes_khr(physical_device, *surface)?,
         })
     }
-}
\ No newline at end of file
+}
+
+/// How presented images reach the screen, see `Device::supported_present_modes`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PresentMode {
+    /// waits for vertical blank, no tearing. every surface supports it.
+    Fifo,
+    /// waits for vertical blank unless the image is late, late images tear.
+    FifoRelaxed,
+    /// replaces the waiting image with the newest one, no tearing and low latency.
+    Mailbox,
+    /// presents right away, tears.
+    Immediate,
+}
+
+impl PresentMode {
+    /// the mode of a vulkan present mode, none for modes of extensions.
+    pub fn from_vk(mode: vk::PresentModeKHR) -> Option<Self> {
+        match mode {
+            vk::PresentModeKHR::FIFO => Some(PresentMode::Fifo),
+            vk::PresentModeKHR::FIFO_RELAXED => Some(PresentMode::FifoRelaxed),
+            vk::PresentModeKHR::MAILBOX => Some(PresentMode::Mailbox),
+            vk::PresentModeKHR::IMMEDIATE => Some(PresentMode::Immediate),
+            _ => None,
+        }
+    }
+
+    pub fn to_vk(self) -> vk::PresentModeKHR {
+        match self {
+            PresentMode::Fifo => vk::PresentModeKHR::FIFO,
+            PresentMode::FifoRelaxed => vk::PresentModeKHR::FIFO_RELAXED,
+            PresentMode::Mailbox => vk::PresentModeKHR::MAILBOX,
+            PresentMode::Immediate => vk::PresentModeKHR::IMMEDIATE,
+        }
+    }
+}
+
+/// A format swapchain images can have with the color space the display interprets them in,
+/// see `Device::supported_surface_formats`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SurfaceFormat {
+    pub format: vk::Format,
+    pub color_space: vk::ColorSpaceKHR,
+}
+
+impl SurfaceFormat {
+    /// how the texels of the format are encoded, srgb formats encode gamma on write.
+    pub fn encoding(&self) -> ColorSpace {
+        ColorSpace::of(self.format)
+    }
+}
+
+impl From<vk::SurfaceFormatKHR> for SurfaceFormat {
+    fn from(format: vk::SurfaceFormatKHR) -> Self {
+        Self {
+            format: format.format,
+            color_space: format.color_space,
+        }
+    }
+}
+
+/// the present modes of `modes` that map to a `PresentMode`, without duplicates.
+pub fn present_modes(modes: &[vk::PresentModeKHR]) -> Vec<PresentMode> {
+    let mut supported = Vec::with_capacity(modes.len());
+    for mode in modes.iter().filter_map(|m| PresentMode::from_vk(*m)) {
+        if !supported.contains(&mode) {
+            supported.push(mode);
+        }
+    }
+    supported
+}
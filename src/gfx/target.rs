@@ -0,0 +1,32 @@
+#![allow(dead_code, unused_variables)]
+
+use vulkanalia::prelude::v1_0::*;
+
+use super::{FrameBuffer, Texture, TextureView};
+
+/// Offscreen attachments laid out like the scene target, so pipelines created with
+/// `Device::render_pass` can draw into them, see `Device::begin_target_pass`.
+pub struct SceneAttachments {
+    pub extent: vk::Extent2D,
+    /// multisampled color, resolved at the end of each pass.
+    pub color_texture: Texture,
+    pub color_texture_view: TextureView,
+    pub depth_texture: Texture,
+    pub depth_texture_view: TextureView,
+    /// the resolved hdr color, in the shader read only layout outside of a pass.
+    pub resolve_texture: Texture,
+    pub resolve_texture_view: TextureView,
+    pub framebuffer: FrameBuffer,
+}
+
+impl SceneAttachments {
+    pub unsafe fn destroy(&self, device: &Device) {
+        self.framebuffer.destroy(device);
+        self.color_texture_view.destroy(device);
+        self.color_texture.destroy(device);
+        self.depth_texture_view.destroy(device);
+        self.depth_texture.destroy(device);
+        self.resolve_texture_view.destroy(device);
+        self.resolve_texture.destroy(device);
+    }
+}
@@ -0,0 +1,91 @@
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::thread::{self, JoinHandle};
+
+use anyhow::{anyhow, Result};
+
+// the result of the thread and the waker of the last poll
+struct Shared<T> {
+    result: Option<Result<T>>,
+    waker: Option<Waker>,
+}
+
+/// Work running on a thread of its own, e.g. building pipelines, which can take long on the
+/// first run of a driver. the thread wakes the task when it is done, so it can be awaited on
+/// any executor or sent to another thread.
+pub struct BackgroundTask<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<T: Send + 'static> BackgroundTask<T> {
+    /// run `work` on a new thread named `name`.
+    pub fn spawn(name: &str, work: impl FnOnce() -> Result<T> + Send + 'static) -> Result<Self> {
+        let shared = Arc::new(Mutex::new(Shared {
+            result: None,
+            waker: None,
+        }));
+        let done = shared.clone();
+        let thread = thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || {
+                let result = panic::catch_unwind(AssertUnwindSafe(work))
+                    .unwrap_or_else(|_| Err(anyhow!("The background task panicked.")));
+                let mut done = done.lock().unwrap_or_else(|e| e.into_inner());
+                done.result = Some(result);
+                if let Some(waker) = done.waker.take() {
+                    waker.wake();
+                }
+            })?;
+        Ok(Self {
+            shared,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl<T> BackgroundTask<T> {
+    /// whether the work is done, `wait` returns right away once it is.
+    pub fn is_done(&self) -> bool {
+        self.lock().result.is_some()
+    }
+
+    /// block until the work is done.
+    pub fn wait(mut self) -> Result<T> {
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        self.take()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Shared<T>> {
+        self.shared.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // the thread always leaves a result, it was taken when there is none
+    fn take(&self) -> Result<T> {
+        self.lock()
+            .result
+            .take()
+            .unwrap_or_else(|| Err(anyhow!("The background task was completed already.")))
+    }
+}
+
+impl<T> Future for BackgroundTask<T> {
+    type Output = Result<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // the thread sets the result and takes the waker under the same lock
+        let mut shared = self.lock();
+        match shared.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
@@ -1,16 +1,104 @@
 #![allow(dead_code)]
 
-use anyhow::Result;
+#[cfg(feature = "image-loading")]
+use std::fs::File;
+
+use anyhow::{anyhow, Result};
+use tracing::warn;
 use vulkanalia::prelude::v1_0::*;
 
+use super::allocation;
+use super::{linear_to_srgb, srgb_to_linear};
+
+/// How the texel values of a texture are encoded.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    /// gamma encoded color, decoded to linear by the sampler.
+    Srgb,
+    /// values are used as stored.
+    Linear,
+}
+
+impl ColorSpace {
+    /// the color space implied by a vulkan format.
+    pub fn of(format: vk::Format) -> Self {
+        match format {
+            vk::Format::R8_SRGB
+            | vk::Format::R8G8_SRGB
+            | vk::Format::R8G8B8_SRGB
+            | vk::Format::B8G8R8_SRGB
+            | vk::Format::R8G8B8A8_SRGB
+            | vk::Format::B8G8R8A8_SRGB
+            | vk::Format::A8B8G8R8_SRGB_PACK32 => ColorSpace::Srgb,
+            _ => ColorSpace::Linear,
+        }
+    }
+}
+
+/// The import policy of an image file, decides the format it is uploaded with.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TextureImport {
+    /// color authored in srgb, e.g. albedo or emissive maps.
+    Color,
+    /// non color data like roughness, metallic or occlusion maps.
+    Data,
+    /// tangent space normals encoded into 0..1.
+    Normal,
+}
+
+impl TextureImport {
+    /// the format texels are uploaded with.
+    pub fn format(&self) -> vk::Format {
+        match self {
+            TextureImport::Color => vk::Format::R8G8B8A8_SRGB,
+            TextureImport::Data | TextureImport::Normal => vk::Format::R8G8B8A8_UNORM,
+        }
+    }
+
+    /// the color space textures of this policy end up in.
+    pub fn color_space(&self) -> ColorSpace {
+        ColorSpace::of(self.format())
+    }
+}
+
+/// How the alpha channel of color texels relates to the color channels.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AlphaMode {
+    /// color is already scaled by alpha, filters and blends without dark fringes.
+    Premultiplied,
+    /// color is stored independent of alpha, as most image files are.
+    Straight,
+}
+
 pub struct Texture {
     pub image: vk::Image,
     pub memory: vk::DeviceMemory,
+    pub format: vk::Format,
+    pub color_space: ColorSpace,
 }
 
 impl Texture {
-    pub fn create(image: vk::Image, memory: vk::DeviceMemory) -> Self {
-        Self { image, memory }
+    pub fn create(image: vk::Image, memory: vk::DeviceMemory, format: vk::Format) -> Self {
+        Self {
+            image,
+            memory,
+            format,
+            color_space: ColorSpace::of(format),
+        }
+    }
+
+    /// warn when the texture is bound to a slot expecting another color space.
+    /// returns whether the color spaces matched.
+    pub fn check(&self, expected: ColorSpace, slot: &str) -> bool {
+        if self.color_space != expected {
+            warn!(
+                "{} expects a {:?} texture but got a {:?} one ({:?}).",
+                slot, expected, self.color_space, self.format
+            );
+            return false;
+        }
+
+        true
     }
 
     pub unsafe fn create_view(
@@ -36,16 +124,64 @@ impl Texture {
         Ok(TextureView::create(device.create_image_view(&info, None)?))
     }
 
+    /// create an array view of the first level and `layers` layers, e.g. the attachment of a
+    /// multiview pass with a layer per view.
+    pub unsafe fn create_array_view(
+        &self,
+        device: &vulkanalia::Device,
+        aspects: vk::ImageAspectFlags,
+        layers: u32,
+    ) -> Result<TextureView> {
+        let subresource_range = vk::ImageSubresourceRange::builder()
+            .aspect_mask(aspects)
+            .base_mip_level(0)
+            .level_count(1)
+            .base_array_layer(0)
+            .layer_count(layers);
+
+        let info = vk::ImageViewCreateInfo::builder()
+            .image(self.image)
+            .view_type(vk::ImageViewType::_2D_ARRAY)
+            .format(self.format)
+            .subresource_range(subresource_range);
+
+        Ok(TextureView::create(device.create_image_view(&info, None)?))
+    }
+
+    /// create a view of some levels and layers, as a cube, array or plain 2d view.
+    pub unsafe fn create_subresource_view(
+        &self,
+        device: &vulkanalia::Device,
+        view_type: vk::ImageViewType,
+        base_mip_level: u32,
+        mip_levels: u32,
+        layers: u32,
+    ) -> Result<TextureView> {
+        let subresource_range = vk::ImageSubresourceRange::builder()
+            .aspect_mask(vk::ImageAspectFlags::COLOR)
+            .base_mip_level(base_mip_level)
+            .level_count(mip_levels)
+            .base_array_layer(0)
+            .layer_count(layers);
+
+        let info = vk::ImageViewCreateInfo::builder()
+            .image(self.image)
+            .view_type(view_type)
+            .format(self.format)
+            .subresource_range(subresource_range);
+
+        Ok(TextureView::create(device.create_image_view(&info, None)?))
+    }
+
     pub unsafe fn destroy(&self, device: &Device) {
         // destroy the image
         device.destroy_image(self.image, None);
 
         // free the memory
-        device.free_memory(self.memory, None);
+        allocation::free(device, self.memory);
     }
 }
 
-
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub struct TextureView {
     pub view: vk::ImageView,
@@ -61,3 +197,57 @@ impl TextureView {
         device.destroy_image_view(self.view, None);
     }
 }
+
+/// decode a png into rgba8 pixels, returns the width, height and pixels.
+#[cfg(feature = "image-loading")]
+pub fn read_png(path: &str) -> Result<(u32, u32, Vec<u8>)> {
+    // decode, expanding palettes and low bit depths into 8 bit channels
+    let mut decoder = png::Decoder::new(File::open(path)?);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels)?;
+    pixels.truncate(info.buffer_size());
+
+    // widen everything to rgba
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => pixels,
+        png::ColorType::Rgb => pixels
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => pixels
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        png::ColorType::Grayscale => pixels.iter().flat_map(|p| [*p, *p, *p, 255]).collect(),
+        png::ColorType::Indexed => return Err(anyhow!("Unexpanded palette in {}.", path)),
+    };
+
+    Ok((info.width, info.height, pixels))
+}
+
+/// without the image-loading feature pngs can't be decoded.
+#[cfg(not(feature = "image-loading"))]
+pub fn read_png(path: &str) -> Result<(u32, u32, Vec<u8>)> {
+    Err(anyhow!(
+        "Can't load {}, png support needs the image-loading feature.",
+        path
+    ))
+}
+
+/// scale the color of straight rgba8 pixels by their alpha.
+/// srgb colors are scaled in linear space, so the sampler decodes the expected values.
+pub fn premultiply(pixels: &mut [u8], color_space: ColorSpace) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        let alpha = pixel[3] as f32 / 255.0;
+        for channel in &mut pixel[..3] {
+            let value = *channel as f32 / 255.0;
+            let value = match color_space {
+                ColorSpace::Srgb => linear_to_srgb(srgb_to_linear(value) * alpha),
+                ColorSpace::Linear => value * alpha,
+            };
+            *channel = (value * 255.0).round() as u8;
+        }
+    }
+}
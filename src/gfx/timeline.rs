@@ -0,0 +1,390 @@
+#![allow(dead_code)]
+
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use anyhow::{anyhow, Result};
+use vulkanalia::prelude::v1_0::*;
+use vulkanalia::vk::KhrTimelineSemaphoreExtension;
+
+/// A counter the gpu advances as submissions complete, every submission signals the next
+/// value. a resource used by the submission with value `v` is safe to touch again once
+/// `completed() >= v`. backed by a timeline semaphore when the device supports them and by
+/// one fence per value in flight otherwise.
+pub struct Timeline {
+    semaphore: Option<vk::Semaphore>,
+    // the fallback, fences of the values not known to be complete in submission order
+    pending: RefCell<Vec<(u64, vk::Fence)>>,
+    unused: RefCell<Vec<vk::Fence>>,
+    submitted: Cell<u64>,
+    completed: Cell<u64>,
+    // run once the timeline is seen at their value
+    callbacks: RefCell<Vec<(u64, Callback)>>,
+}
+
+type Callback = Box<dyn FnOnce() + Send>;
+
+/// A gpu side wait of a submission for a timeline semaphore value, e.g. a frame waiting for
+/// an upload on another queue. see `Timeline::gpu_wait`.
+#[derive(Copy, Clone, Debug)]
+pub struct TimelineWait {
+    pub semaphore: vk::Semaphore,
+    pub value: u64,
+    pub stage: vk::PipelineStageFlags,
+}
+
+impl Timeline {
+    /// create a timeline at value 0, `semaphore` needs `VK_KHR_timeline_semaphore` enabled.
+    pub unsafe fn create(device: &vulkanalia::Device, semaphore: bool) -> Result<Self> {
+        let semaphore = if semaphore {
+            let mut type_info = vk::SemaphoreTypeCreateInfo::builder()
+                .semaphore_type(vk::SemaphoreType::TIMELINE)
+                .initial_value(0);
+            let info = vk::SemaphoreCreateInfo::builder().push_next(&mut type_info);
+            Some(device.create_semaphore(&info, None)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            semaphore,
+            pending: RefCell::new(vec![]),
+            unused: RefCell::new(vec![]),
+            submitted: Cell::new(0),
+            completed: Cell::new(0),
+            callbacks: RefCell::new(vec![]),
+        })
+    }
+
+    /// whether a timeline semaphore backs the timeline, cross queue waits stay on the gpu.
+    pub fn is_semaphore(&self) -> bool {
+        self.semaphore.is_some()
+    }
+
+    /// the value of the latest submission.
+    pub fn submitted(&self) -> u64 {
+        self.submitted.get()
+    }
+
+    /// the highest value whose submission completed together with all before it.
+    pub unsafe fn completed(&self, device: &vulkanalia::Device) -> Result<u64> {
+        match self.semaphore {
+            Some(semaphore) => self
+                .completed
+                .set(device.get_semaphore_counter_value_khr(semaphore)?),
+            None => {
+                // fences signal in any order, only a completed prefix counts
+                let mut pending = self.pending.borrow_mut();
+                let done = pending
+                    .iter()
+                    .take_while(|(_, f)| {
+                        device.get_fence_status(*f) == Ok(vk::SuccessCode::SUCCESS)
+                    })
+                    .count();
+                self.recycle(device, pending.drain(..done))?;
+            }
+        }
+        self.run_callbacks();
+        Ok(self.completed.get())
+    }
+
+    /// block until the timeline reached `value`, fails for values never submitted.
+    pub unsafe fn wait(&self, device: &vulkanalia::Device, value: u64) -> Result<()> {
+        if value <= self.completed.get() {
+            return Ok(());
+        }
+        if value > self.submitted.get() {
+            return Err(anyhow!("Timeline value {} was never submitted.", value));
+        }
+
+        match self.semaphore {
+            Some(semaphore) => {
+                let semaphores = &[semaphore];
+                let values = &[value];
+                let info = vk::SemaphoreWaitInfo::builder()
+                    .semaphores(semaphores)
+                    .values(values);
+                device.wait_semaphores_khr(&info, u64::MAX)?;
+                self.completed.set(value);
+            }
+            None => {
+                let mut pending = self.pending.borrow_mut();
+                let count = pending.iter().take_while(|(v, _)| *v <= value).count();
+                let fences = pending[..count].iter().map(|(_, f)| *f).collect::<Vec<_>>();
+                if !fences.is_empty() {
+                    device.wait_for_fences(&fences, true, u64::MAX)?;
+                }
+                self.recycle(device, pending.drain(..count))?;
+            }
+        }
+        self.run_callbacks();
+        Ok(())
+    }
+
+    /// the work submitted up to `value`, to poll, wait for or await.
+    pub fn work_done<'a>(&'a self, device: &'a vulkanalia::Device, value: u64) -> WorkDone<'a> {
+        WorkDone {
+            timeline: self,
+            device,
+            value,
+        }
+    }
+
+    /// run `callback` once the timeline is seen at `value`, by `completed` or `wait`. the
+    /// device polls its frame timeline every frame, other timelines run theirs when polled.
+    /// it runs right away for values that completed already, it is dropped unrun when the
+    /// timeline is destroyed first.
+    pub fn on_reached(&self, value: u64, callback: impl FnOnce() + Send + 'static) {
+        if value <= self.completed.get() {
+            callback();
+        } else {
+            self.callbacks
+                .borrow_mut()
+                .push((value, Box::new(callback)));
+        }
+    }
+
+    // callbacks may use the timeline, so they are taken out before they run
+    fn run_callbacks(&self) {
+        let completed = self.completed.get();
+        let due = {
+            let mut callbacks = self.callbacks.borrow_mut();
+            let (due, waiting) = callbacks.drain(..).partition(|(v, _)| *v <= completed);
+            *callbacks = waiting;
+            due
+        };
+        for (_, callback) in due {
+            callback();
+        }
+    }
+
+    /// a wait for `value` to pass to a submission on any queue. a fence backed timeline has
+    /// no semaphore to wait on, it blocks until the value is reached and returns none.
+    pub unsafe fn gpu_wait(
+        &self,
+        device: &vulkanalia::Device,
+        value: u64,
+        stage: vk::PipelineStageFlags,
+    ) -> Result<Option<TimelineWait>> {
+        match self.semaphore {
+            Some(semaphore) => Ok(Some(TimelineWait {
+                semaphore,
+                value,
+                stage,
+            })),
+            None => {
+                self.wait(device, value)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// submit `command_buffers` to `queue` and signal the next value, which is returned.
+    /// `waits` are binary semaphores like an acquired swapchain image, `timelines` come from
+    /// `gpu_wait` of other timelines.
+    pub unsafe fn submit(
+        &self,
+        device: &vulkanalia::Device,
+        queue: vk::Queue,
+        command_buffers: &[vk::CommandBuffer],
+        waits: &[(vk::Semaphore, vk::PipelineStageFlags)],
+        timelines: &[TimelineWait],
+        signals: &[vk::Semaphore],
+    ) -> Result<u64> {
+        let value = self.submitted.get() + 1;
+
+        // binary semaphores take a value of 0 next to the timeline ones
+        let mut wait_semaphores = waits.iter().map(|(s, _)| *s).collect::<Vec<_>>();
+        let mut wait_stages = waits.iter().map(|(_, s)| *s).collect::<Vec<_>>();
+        let mut wait_values = vec![0; waits.len()];
+        for wait in timelines {
+            wait_semaphores.push(wait.semaphore);
+            wait_stages.push(wait.stage);
+            wait_values.push(wait.value);
+        }
+
+        let mut signal_semaphores = signals.to_vec();
+        let mut signal_values = vec![0; signals.len()];
+        if let Some(semaphore) = self.semaphore {
+            signal_semaphores.push(semaphore);
+            signal_values.push(value);
+        }
+
+        let mut values = vk::TimelineSemaphoreSubmitInfo::builder()
+            .wait_semaphore_values(&wait_values)
+            .signal_semaphore_values(&signal_values);
+        let mut info = vk::SubmitInfo::builder()
+            .wait_semaphores(&wait_semaphores)
+            .wait_dst_stage_mask(&wait_stages)
+            .command_buffers(command_buffers)
+            .signal_semaphores(&signal_semaphores);
+        if self.semaphore.is_some() || !timelines.is_empty() {
+            info = info.push_next(&mut values);
+        }
+
+        // the fallback signals a fence of its own for every value
+        let fence = match self.semaphore {
+            Some(_) => vk::Fence::null(),
+            None => match self.unused.borrow_mut().pop() {
+                Some(fence) => fence,
+                None => device.create_fence(&vk::FenceCreateInfo::builder(), None)?,
+            },
+        };
+        device.queue_submit(queue, &[info], fence)?;
+        if !fence.is_null() {
+            self.pending.borrow_mut().push((value, fence));
+        }
+
+        self.submitted.set(value);
+        Ok(value)
+    }
+
+    // the fences of completed values are reset for the next submissions
+    unsafe fn recycle(
+        &self,
+        device: &vulkanalia::Device,
+        done: impl Iterator<Item = (u64, vk::Fence)>,
+    ) -> Result<()> {
+        let done = done.collect::<Vec<_>>();
+        if let Some((value, _)) = done.last() {
+            self.completed.set(*value);
+            let fences = done.iter().map(|(_, f)| *f).collect::<Vec<_>>();
+            device.reset_fences(&fences)?;
+            self.unused.borrow_mut().extend(fences);
+        }
+        Ok(())
+    }
+
+    pub unsafe fn destroy(&self, device: &vulkanalia::Device) {
+        self.callbacks.borrow_mut().clear();
+        if let Some(semaphore) = self.semaphore {
+            device.destroy_semaphore(semaphore, None);
+        }
+        self.pending
+            .borrow()
+            .iter()
+            .for_each(|(_, f)| device.destroy_fence(*f, None));
+        self.unused
+            .borrow()
+            .iter()
+            .for_each(|f| device.destroy_fence(*f, None));
+    }
+}
+
+/// The work submitted on a timeline up to a value, from `Timeline::work_done` or
+/// `Device::on_submitted_work_done`, e.g. for reading back a buffer or recycling what the
+/// work used. polling doesn't block, `wait` does. awaiting it needs the timeline to be polled
+/// while the future is pending, which the frames of the device do for its frame timeline.
+pub struct WorkDone<'a> {
+    timeline: &'a Timeline,
+    device: &'a vulkanalia::Device,
+    value: u64,
+}
+
+impl WorkDone<'_> {
+    /// the timeline value the work signals.
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// whether the work completed, values not submitted yet are not.
+    pub fn is_done(&self) -> Result<bool> {
+        unsafe { Ok(self.timeline.completed(self.device)? >= self.value) }
+    }
+
+    /// block until the work completed, fails for values never submitted.
+    pub fn wait(&self) -> Result<()> {
+        unsafe { self.timeline.wait(self.device, self.value) }
+    }
+
+    /// run `callback` once the work is seen complete, see `Timeline::on_reached`.
+    pub fn then(&self, callback: impl FnOnce() + Send + 'static) {
+        self.timeline.on_reached(self.value, callback);
+    }
+}
+
+impl Future for WorkDone<'_> {
+    type Output = Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.is_done() {
+            Ok(true) => Poll::Ready(Ok(())),
+            Ok(false) => {
+                // woken by the poll of the timeline that sees the value
+                let waker = cx.waker().clone();
+                self.then(move || waker.wake());
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+}
+
+/// What is left to do once submitted work completed, e.g. reading back the buffer it wrote,
+/// from `Device::map_async` or `Device::upload_texture_async`. it borrows the device, so it
+/// is awaited on the thread that owns it, while `Device::poll` or the frames poll the
+/// timeline. `wait` blocks instead.
+pub struct GpuFuture<'a, T> {
+    work: WorkDone<'a>,
+    finish: Option<Box<dyn FnOnce() -> Result<T> + 'a>>,
+    dropped: Option<Box<dyn FnOnce() + 'a>>,
+}
+
+impl<'a, T> GpuFuture<'a, T> {
+    /// run `finish` once `work` is done.
+    pub fn new(work: WorkDone<'a>, finish: impl FnOnce() -> Result<T> + 'a) -> Self {
+        Self {
+            work,
+            finish: Some(Box::new(finish)),
+            dropped: None,
+        }
+    }
+
+    /// run `dropped` instead of `finish` when the future is dropped before it finished, e.g.
+    /// to retire what `finish` would have handed out.
+    pub fn on_drop(mut self, dropped: impl FnOnce() + 'a) -> Self {
+        self.dropped = Some(Box::new(dropped));
+        self
+    }
+
+    /// the work the future waits for.
+    pub fn work(&self) -> &WorkDone<'a> {
+        &self.work
+    }
+
+    /// block until the work is done and finish it.
+    pub fn wait(mut self) -> Result<T> {
+        self.work.wait()?;
+        self.finish()
+    }
+
+    fn finish(&mut self) -> Result<T> {
+        match self.finish.take() {
+            Some(finish) => finish(),
+            None => Err(anyhow!("The gpu future was completed already.")),
+        }
+    }
+}
+
+impl<T> Drop for GpuFuture<'_, T> {
+    fn drop(&mut self) {
+        if let (Some(_), Some(dropped)) = (&self.finish, self.dropped.take()) {
+            dropped();
+        }
+    }
+}
+
+impl<T> Future for GpuFuture<'_, T> {
+    type Output = Result<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        match Pin::new(&mut this.work).poll(cx) {
+            Poll::Ready(Ok(())) => Poll::Ready(this.finish()),
+            Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
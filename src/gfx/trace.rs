@@ -0,0 +1,844 @@
+#![allow(dead_code)]
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::slice;
+
+use anyhow::{anyhow, Result};
+use vulkanalia::prelude::v1_0::*;
+
+use super::CommandPool;
+
+const MAGIC: &[u8; 4] = b"DTRC";
+const VERSION: u32 = 1;
+
+/// A command recorded into a command buffer, by the null driver or a capture.
+#[derive(Clone, Debug)]
+pub enum TraceCommand {
+    BeginRenderPass {
+        render_pass: vk::RenderPass,
+        framebuffer: vk::Framebuffer,
+        area: vk::Rect2D,
+        clear_values: Vec<vk::ClearValue>,
+        contents: vk::SubpassContents,
+    },
+    EndRenderPass,
+    NextSubpass {
+        contents: vk::SubpassContents,
+    },
+    BindPipeline {
+        bind_point: vk::PipelineBindPoint,
+        pipeline: vk::Pipeline,
+    },
+    BindDescriptorSets {
+        bind_point: vk::PipelineBindPoint,
+        layout: vk::PipelineLayout,
+        first_set: u32,
+        sets: Vec<vk::DescriptorSet>,
+        dynamic_offsets: Vec<u32>,
+    },
+    BindVertexBuffers {
+        first_binding: u32,
+        buffers: Vec<vk::Buffer>,
+        offsets: Vec<vk::DeviceSize>,
+    },
+    BindIndexBuffer {
+        buffer: vk::Buffer,
+        offset: vk::DeviceSize,
+        index_type: vk::IndexType,
+    },
+    PushConstants {
+        layout: vk::PipelineLayout,
+        stages: vk::ShaderStageFlags,
+        offset: u32,
+        data: Vec<u8>,
+    },
+    SetViewport {
+        first_viewport: u32,
+        viewports: Vec<vk::Viewport>,
+    },
+    SetScissor {
+        first_scissor: u32,
+        scissors: Vec<vk::Rect2D>,
+    },
+    Draw {
+        vertex_count: u32,
+        instance_count: u32,
+        first_vertex: u32,
+        first_instance: u32,
+    },
+    DrawIndexed {
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        vertex_offset: i32,
+        first_instance: u32,
+    },
+    DrawIndirect {
+        buffer: vk::Buffer,
+        offset: vk::DeviceSize,
+        draw_count: u32,
+        stride: u32,
+    },
+    Dispatch {
+        x: u32,
+        y: u32,
+        z: u32,
+    },
+    /// the memory barriers are listed with their source and destination access.
+    PipelineBarrier {
+        src_stage: vk::PipelineStageFlags,
+        dst_stage: vk::PipelineStageFlags,
+        dependency: vk::DependencyFlags,
+        memory_barriers: Vec<(vk::AccessFlags, vk::AccessFlags)>,
+        buffers: Vec<TraceBufferBarrier>,
+        images: Vec<TraceImageBarrier>,
+    },
+    CopyBuffer {
+        src: vk::Buffer,
+        dst: vk::Buffer,
+        regions: Vec<vk::BufferCopy>,
+    },
+    CopyBufferToImage {
+        buffer: vk::Buffer,
+        image: vk::Image,
+        layout: vk::ImageLayout,
+        regions: Vec<vk::BufferImageCopy>,
+    },
+    CopyImageToBuffer {
+        image: vk::Image,
+        layout: vk::ImageLayout,
+        buffer: vk::Buffer,
+        regions: Vec<vk::BufferImageCopy>,
+    },
+    BlitImage {
+        src: vk::Image,
+        src_layout: vk::ImageLayout,
+        dst: vk::Image,
+        dst_layout: vk::ImageLayout,
+        regions: Vec<vk::ImageBlit>,
+        filter: vk::Filter,
+    },
+    ClearColorImage {
+        image: vk::Image,
+        layout: vk::ImageLayout,
+        color: vk::ClearValue,
+        ranges: Vec<vk::ImageSubresourceRange>,
+    },
+    FillBuffer {
+        buffer: vk::Buffer,
+        offset: vk::DeviceSize,
+        size: vk::DeviceSize,
+        data: u32,
+    },
+    /// a labeled region of `VK_EXT_debug_utils`, see `Device::begin_label`.
+    BeginLabel {
+        name: String,
+        color: [f32; 4],
+    },
+    EndLabel,
+    /// secondary command buffers run in place, their own commands are not in the trace.
+    ExecuteCommands {
+        command_buffers: Vec<vk::CommandBuffer>,
+    },
+    ResetQueryPool {
+        pool: vk::QueryPool,
+        first_query: u32,
+        query_count: u32,
+    },
+    WriteTimestamp {
+        stage: vk::PipelineStageFlags,
+        pool: vk::QueryPool,
+        query: u32,
+    },
+    BeginQuery {
+        pool: vk::QueryPool,
+        query: u32,
+        flags: vk::QueryControlFlags,
+    },
+    EndQuery {
+        pool: vk::QueryPool,
+        query: u32,
+    },
+    CopyQueryPoolResults {
+        pool: vk::QueryPool,
+        first_query: u32,
+        query_count: u32,
+        buffer: vk::Buffer,
+        offset: vk::DeviceSize,
+        stride: vk::DeviceSize,
+        flags: vk::QueryResultFlags,
+    },
+}
+
+/// A buffer memory barrier of a `TraceCommand::PipelineBarrier`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TraceBufferBarrier {
+    pub buffer: vk::Buffer,
+    pub src_access: vk::AccessFlags,
+    pub dst_access: vk::AccessFlags,
+    pub src_queue_family: u32,
+    pub dst_queue_family: u32,
+    pub offset: vk::DeviceSize,
+    pub size: vk::DeviceSize,
+}
+
+/// An image memory barrier of a `TraceCommand::PipelineBarrier`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TraceImageBarrier {
+    pub image: vk::Image,
+    pub src_access: vk::AccessFlags,
+    pub dst_access: vk::AccessFlags,
+    pub old_layout: vk::ImageLayout,
+    pub new_layout: vk::ImageLayout,
+    pub src_queue_family: u32,
+    pub dst_queue_family: u32,
+    pub range: vk::ImageSubresourceRange,
+}
+
+/// One batch of a `vkQueueSubmit` with the commands its command buffers recorded.
+#[derive(Clone, Debug)]
+pub struct TraceSubmission {
+    pub command_buffers: Vec<vk::CommandBuffer>,
+    pub commands: Vec<TraceCommand>,
+    pub wait_semaphores: Vec<vk::Semaphore>,
+    pub signal_semaphores: Vec<vk::Semaphore>,
+    pub fence: vk::Fence,
+}
+
+/// The submissions of a capture in submission order, see `Device::start_capture`. the
+/// handles are those of the capturing device, so a trace replays in the session that
+/// captured it while its objects still exist. saved traces are for reading, `Display`
+/// writes one line per command.
+#[derive(Clone, Debug, Default)]
+pub struct FrameTrace {
+    pub submissions: Vec<TraceSubmission>,
+}
+
+impl From<&vk::BufferMemoryBarrier> for TraceBufferBarrier {
+    fn from(barrier: &vk::BufferMemoryBarrier) -> Self {
+        Self {
+            buffer: barrier.buffer,
+            src_access: barrier.src_access_mask,
+            dst_access: barrier.dst_access_mask,
+            src_queue_family: barrier.src_queue_family_index,
+            dst_queue_family: barrier.dst_queue_family_index,
+            offset: barrier.offset,
+            size: barrier.size,
+        }
+    }
+}
+
+impl From<&vk::ImageMemoryBarrier> for TraceImageBarrier {
+    fn from(barrier: &vk::ImageMemoryBarrier) -> Self {
+        Self {
+            image: barrier.image,
+            src_access: barrier.src_access_mask,
+            dst_access: barrier.dst_access_mask,
+            old_layout: barrier.old_layout,
+            new_layout: barrier.new_layout,
+            src_queue_family: barrier.src_queue_family_index,
+            dst_queue_family: barrier.dst_queue_family_index,
+            range: barrier.subresource_range,
+        }
+    }
+}
+
+impl TraceCommand {
+    /// record the command again into `command_buffer`.
+    pub unsafe fn record(&self, device: &vulkanalia::Device, command_buffer: vk::CommandBuffer) {
+        let cb = command_buffer;
+        match self {
+            TraceCommand::BeginRenderPass {
+                render_pass,
+                framebuffer,
+                area,
+                clear_values,
+                contents,
+            } => {
+                let info = vk::RenderPassBeginInfo::builder()
+                    .render_pass(*render_pass)
+                    .framebuffer(*framebuffer)
+                    .render_area(*area)
+                    .clear_values(clear_values);
+                device.cmd_begin_render_pass(cb, &info, *contents);
+            }
+            TraceCommand::EndRenderPass => device.cmd_end_render_pass(cb),
+            TraceCommand::NextSubpass { contents } => device.cmd_next_subpass(cb, *contents),
+            TraceCommand::BindPipeline {
+                bind_point,
+                pipeline,
+            } => device.cmd_bind_pipeline(cb, *bind_point, *pipeline),
+            TraceCommand::BindDescriptorSets {
+                bind_point,
+                layout,
+                first_set,
+                sets,
+                dynamic_offsets,
+            } => device.cmd_bind_descriptor_sets(
+                cb,
+                *bind_point,
+                *layout,
+                *first_set,
+                sets,
+                dynamic_offsets,
+            ),
+            TraceCommand::BindVertexBuffers {
+                first_binding,
+                buffers,
+                offsets,
+            } => device.cmd_bind_vertex_buffers(cb, *first_binding, buffers, offsets),
+            TraceCommand::BindIndexBuffer {
+                buffer,
+                offset,
+                index_type,
+            } => device.cmd_bind_index_buffer(cb, *buffer, *offset, *index_type),
+            TraceCommand::PushConstants {
+                layout,
+                stages,
+                offset,
+                data,
+            } => device.cmd_push_constants(cb, *layout, *stages, *offset, data),
+            TraceCommand::SetViewport {
+                first_viewport,
+                viewports,
+            } => device.cmd_set_viewport(cb, *first_viewport, viewports),
+            TraceCommand::SetScissor {
+                first_scissor,
+                scissors,
+            } => device.cmd_set_scissor(cb, *first_scissor, scissors),
+            TraceCommand::Draw {
+                vertex_count,
+                instance_count,
+                first_vertex,
+                first_instance,
+            } => device.cmd_draw(
+                cb,
+                *vertex_count,
+                *instance_count,
+                *first_vertex,
+                *first_instance,
+            ),
+            TraceCommand::DrawIndexed {
+                index_count,
+                instance_count,
+                first_index,
+                vertex_offset,
+                first_instance,
+            } => device.cmd_draw_indexed(
+                cb,
+                *index_count,
+                *instance_count,
+                *first_index,
+                *vertex_offset,
+                *first_instance,
+            ),
+            TraceCommand::DrawIndirect {
+                buffer,
+                offset,
+                draw_count,
+                stride,
+            } => device.cmd_draw_indirect(cb, *buffer, *offset, *draw_count, *stride),
+            TraceCommand::Dispatch { x, y, z } => device.cmd_dispatch(cb, *x, *y, *z),
+            TraceCommand::PipelineBarrier {
+                src_stage,
+                dst_stage,
+                dependency,
+                memory_barriers,
+                buffers,
+                images,
+            } => {
+                let memory_barriers = memory_barriers
+                    .iter()
+                    .map(|(src, dst)| {
+                        vk::MemoryBarrier::builder()
+                            .src_access_mask(*src)
+                            .dst_access_mask(*dst)
+                            .build()
+                    })
+                    .collect::<Vec<_>>();
+                let buffers = buffers
+                    .iter()
+                    .map(|b| {
+                        vk::BufferMemoryBarrier::builder()
+                            .buffer(b.buffer)
+                            .src_access_mask(b.src_access)
+                            .dst_access_mask(b.dst_access)
+                            .src_queue_family_index(b.src_queue_family)
+                            .dst_queue_family_index(b.dst_queue_family)
+                            .offset(b.offset)
+                            .size(b.size)
+                            .build()
+                    })
+                    .collect::<Vec<_>>();
+                let images = images
+                    .iter()
+                    .map(|b| {
+                        vk::ImageMemoryBarrier::builder()
+                            .image(b.image)
+                            .src_access_mask(b.src_access)
+                            .dst_access_mask(b.dst_access)
+                            .old_layout(b.old_layout)
+                            .new_layout(b.new_layout)
+                            .src_queue_family_index(b.src_queue_family)
+                            .dst_queue_family_index(b.dst_queue_family)
+                            .subresource_range(b.range)
+                            .build()
+                    })
+                    .collect::<Vec<_>>();
+                device.cmd_pipeline_barrier(
+                    cb,
+                    *src_stage,
+                    *dst_stage,
+                    *dependency,
+                    &memory_barriers,
+                    &buffers,
+                    &images,
+                );
+            }
+            TraceCommand::CopyBuffer { src, dst, regions } => {
+                device.cmd_copy_buffer(cb, *src, *dst, regions)
+            }
+            TraceCommand::CopyBufferToImage {
+                buffer,
+                image,
+                layout,
+                regions,
+            } => device.cmd_copy_buffer_to_image(cb, *buffer, *image, *layout, regions),
+            TraceCommand::CopyImageToBuffer {
+                image,
+                layout,
+                buffer,
+                regions,
+            } => device.cmd_copy_image_to_buffer(cb, *image, *layout, *buffer, regions),
+            TraceCommand::BlitImage {
+                src,
+                src_layout,
+                dst,
+                dst_layout,
+                regions,
+                filter,
+            } => device.cmd_blit_image(cb, *src, *src_layout, *dst, *dst_layout, regions, *filter),
+            TraceCommand::ClearColorImage {
+                image,
+                layout,
+                color,
+                ranges,
+            } => device.cmd_clear_color_image(cb, *image, *layout, &color.color, ranges),
+            TraceCommand::FillBuffer {
+                buffer,
+                offset,
+                size,
+                data,
+            } => device.cmd_fill_buffer(cb, *buffer, *offset, *size, *data),
+            // the labels are instance commands of an extension the device may lack
+            TraceCommand::BeginLabel { .. } | TraceCommand::EndLabel => {}
+            // the secondary buffers have to be alive and recorded still
+            TraceCommand::ExecuteCommands { command_buffers } => {
+                device.cmd_execute_commands(cb, command_buffers)
+            }
+            TraceCommand::ResetQueryPool {
+                pool,
+                first_query,
+                query_count,
+            } => device.cmd_reset_query_pool(cb, *pool, *first_query, *query_count),
+            TraceCommand::WriteTimestamp { stage, pool, query } => {
+                device.cmd_write_timestamp(cb, *stage, *pool, *query)
+            }
+            TraceCommand::BeginQuery { pool, query, flags } => {
+                device.cmd_begin_query(cb, *pool, *query, *flags)
+            }
+            TraceCommand::EndQuery { pool, query } => device.cmd_end_query(cb, *pool, *query),
+            TraceCommand::CopyQueryPoolResults {
+                pool,
+                first_query,
+                query_count,
+                buffer,
+                offset,
+                stride,
+                flags,
+            } => device.cmd_copy_query_pool_results(
+                cb,
+                *pool,
+                *first_query,
+                *query_count,
+                *buffer,
+                *offset,
+                *stride,
+                *flags,
+            ),
+        }
+    }
+}
+
+impl FrameTrace {
+    /// the commands of all submissions in the order they ran.
+    pub fn commands(&self) -> impl Iterator<Item = &TraceCommand> {
+        self.submissions.iter().flat_map(|s| s.commands.iter())
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer(MAGIC.to_vec());
+        VERSION.write(&mut writer);
+        self.submissions.write(&mut writer);
+        writer.0
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader(bytes);
+        if reader.take(4)? != MAGIC {
+            return Err(anyhow!("Not a frame trace."));
+        }
+        let version = u32::read(&mut reader)?;
+        if version > VERSION {
+            return Err(anyhow!("Unsupported frame trace version ({}).", version));
+        }
+        let submissions = Vec::read(&mut reader)?;
+        if !reader.0.is_empty() {
+            return Err(anyhow!("Frame trace has trailing bytes."));
+        }
+        Ok(Self { submissions })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, self.encode())?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::decode(&fs::read(path)?)
+    }
+
+    /// record every submission into a one time command buffer of `pool` and run it on
+    /// `queue`, in order and waiting for each. the semaphores and fences of the capture are
+    /// left out, they belong to frames that are over.
+    pub unsafe fn replay(
+        &self,
+        device: &vulkanalia::Device,
+        queue: vk::Queue,
+        pool: &CommandPool,
+    ) -> Result<()> {
+        for submission in &self.submissions {
+            let command_buffer = pool.begin_single(device)?;
+            for command in &submission.commands {
+                command.record(device, command_buffer.buffer);
+            }
+            pool.end_single(device, queue, command_buffer)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for FrameTrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, submission) in self.submissions.iter().enumerate() {
+            writeln!(
+                f,
+                "submission {}: {} command buffers, waits {:?}, signals {:?}, fence {:?}",
+                i,
+                submission.command_buffers.len(),
+                submission.wait_semaphores,
+                submission.signal_semaphores,
+                submission.fence
+            )?;
+            for command in &submission.commands {
+                writeln!(f, "    {:?}", command)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// the items behind a pointer and count of a vulkan call
+pub(super) unsafe fn items<'a, T>(pointer: *const T, count: u32) -> &'a [T] {
+    if count == 0 || pointer.is_null() {
+        &[]
+    } else {
+        slice::from_raw_parts(pointer, count as usize)
+    }
+}
+
+// little endian trace data
+struct Writer(Vec<u8>);
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8]> {
+        if self.0.len() < count {
+            return Err(anyhow!("Frame trace ends early."));
+        }
+        let (bytes, rest) = self.0.split_at(count);
+        self.0 = rest;
+        Ok(bytes)
+    }
+}
+
+// a value of the trace format
+trait Field: Sized {
+    fn write(&self, writer: &mut Writer);
+    fn read(reader: &mut Reader) -> Result<Self>;
+}
+
+macro_rules! numbers {
+    ($($ty:ty),*) => {$(
+        impl Field for $ty {
+            fn write(&self, writer: &mut Writer) {
+                writer.0.extend_from_slice(&self.to_le_bytes());
+            }
+
+            fn read(reader: &mut Reader) -> Result<Self> {
+                let bytes = reader.take(std::mem::size_of::<$ty>())?;
+                Ok(<$ty>::from_le_bytes(bytes.try_into().unwrap()))
+            }
+        }
+    )*};
+}
+
+numbers!(u8, u32, i32, u64, f32);
+
+impl<T: Field> Field for Vec<T> {
+    fn write(&self, writer: &mut Writer) {
+        (self.len() as u32).write(writer);
+        self.iter().for_each(|v| v.write(writer));
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self> {
+        let count = u32::read(reader)?;
+        // every item takes a byte at least, a broken count can't allocate much
+        if count as usize > reader.0.len() {
+            return Err(anyhow!("Frame trace ends early."));
+        }
+        (0..count).map(|_| T::read(reader)).collect()
+    }
+}
+
+impl<A: Field, B: Field> Field for (A, B) {
+    fn write(&self, writer: &mut Writer) {
+        self.0.write(writer);
+        self.1.write(writer);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self> {
+        Ok((A::read(reader)?, B::read(reader)?))
+    }
+}
+
+impl<T: Field + Copy + Default, const N: usize> Field for [T; N] {
+    fn write(&self, writer: &mut Writer) {
+        self.iter().for_each(|v| v.write(writer));
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self> {
+        let mut values = [T::default(); N];
+        for v in &mut values {
+            *v = T::read(reader)?;
+        }
+        Ok(values)
+    }
+}
+
+impl Field for String {
+    fn write(&self, writer: &mut Writer) {
+        (self.len() as u32).write(writer);
+        writer.0.extend_from_slice(self.as_bytes());
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self> {
+        let len = u32::read(reader)?;
+        String::from_utf8(reader.take(len as usize)?.to_vec())
+            .map_err(|_| anyhow!("Frame trace has a label that is not utf-8."))
+    }
+}
+
+// only the color of clear values is written, depth and stencil share its bits
+impl Field for vk::ClearValue {
+    fn write(&self, writer: &mut Writer) {
+        unsafe { self.color.uint32 }
+            .iter()
+            .for_each(|v| v.write(writer));
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self> {
+        let mut uint32 = [0; 4];
+        for v in &mut uint32 {
+            *v = u32::read(reader)?;
+        }
+        Ok(vk::ClearValue {
+            color: vk::ClearColorValue { uint32 },
+        })
+    }
+}
+
+macro_rules! handles {
+    ($($ty:ident),*) => {$(
+        impl Field for vk::$ty {
+            fn write(&self, writer: &mut Writer) {
+                (self.as_raw() as u64).write(writer);
+            }
+
+            fn read(reader: &mut Reader) -> Result<Self> {
+                Ok(vk::$ty::from_raw(u64::read(reader)? as _))
+            }
+        }
+    )*};
+}
+
+handles!(
+    Buffer,
+    CommandBuffer,
+    DescriptorSet,
+    Fence,
+    Framebuffer,
+    Image,
+    Pipeline,
+    PipelineLayout,
+    QueryPool,
+    RenderPass,
+    Semaphore
+);
+
+macro_rules! enums {
+    ($($ty:ident),*) => {$(
+        impl Field for vk::$ty {
+            fn write(&self, writer: &mut Writer) {
+                self.as_raw().write(writer);
+            }
+
+            fn read(reader: &mut Reader) -> Result<Self> {
+                Ok(vk::$ty::from_raw(i32::read(reader)?))
+            }
+        }
+    )*};
+}
+
+enums!(
+    Filter,
+    ImageLayout,
+    IndexType,
+    PipelineBindPoint,
+    SubpassContents
+);
+
+macro_rules! flags {
+    ($($ty:ident),*) => {$(
+        impl Field for vk::$ty {
+            fn write(&self, writer: &mut Writer) {
+                self.bits().write(writer);
+            }
+
+            fn read(reader: &mut Reader) -> Result<Self> {
+                Ok(vk::$ty::from_bits_truncate(u32::read(reader)?))
+            }
+        }
+    )*};
+}
+
+flags!(
+    AccessFlags,
+    DependencyFlags,
+    ImageAspectFlags,
+    PipelineStageFlags,
+    QueryControlFlags,
+    QueryResultFlags,
+    ShaderStageFlags
+);
+
+macro_rules! structs {
+    ($($ty:ty { $($field:ident),* })*) => {$(
+        impl Field for $ty {
+            fn write(&self, writer: &mut Writer) {
+                $(self.$field.write(writer);)*
+            }
+
+            fn read(reader: &mut Reader) -> Result<Self> {
+                Ok(Self {
+                    $($field: Field::read(reader)?,)*
+                })
+            }
+        }
+    )*};
+}
+
+structs! {
+    vk::Offset2D { x, y }
+    vk::Extent2D { width, height }
+    vk::Rect2D { offset, extent }
+    vk::Offset3D { x, y, z }
+    vk::Extent3D { width, height, depth }
+    vk::Viewport { x, y, width, height, min_depth, max_depth }
+    vk::BufferCopy { src_offset, dst_offset, size }
+    vk::ImageSubresourceLayers { aspect_mask, mip_level, base_array_layer, layer_count }
+    vk::ImageSubresourceRange {
+        aspect_mask, base_mip_level, level_count, base_array_layer, layer_count
+    }
+    vk::BufferImageCopy {
+        buffer_offset, buffer_row_length, buffer_image_height, image_subresource, image_offset,
+        image_extent
+    }
+    vk::ImageBlit { src_subresource, src_offsets, dst_subresource, dst_offsets }
+    TraceBufferBarrier {
+        buffer, src_access, dst_access, src_queue_family, dst_queue_family, offset, size
+    }
+    TraceImageBarrier {
+        image, src_access, dst_access, old_layout, new_layout, src_queue_family,
+        dst_queue_family, range
+    }
+    TraceSubmission { command_buffers, commands, wait_semaphores, signal_semaphores, fence }
+}
+
+// the commands are a tag and their fields in order, new commands take new tags
+macro_rules! commands {
+    ($($tag:literal $variant:ident { $($field:ident),* })*) => {
+        impl Field for TraceCommand {
+            fn write(&self, writer: &mut Writer) {
+                match self {
+                    $(TraceCommand::$variant { $($field),* } => {
+                        <u8 as Field>::write(&$tag, writer);
+                        $($field.write(writer);)*
+                    })*
+                }
+            }
+
+            fn read(reader: &mut Reader) -> Result<Self> {
+                Ok(match u8::read(reader)? {
+                    $($tag => TraceCommand::$variant { $($field: Field::read(reader)?),* },)*
+                    tag => return Err(anyhow!("Unknown frame trace command ({}).", tag)),
+                })
+            }
+        }
+    };
+}
+
+commands! {
+    0 BeginRenderPass { render_pass, framebuffer, area, clear_values, contents }
+    1 EndRenderPass {}
+    2 BindPipeline { bind_point, pipeline }
+    3 BindDescriptorSets { bind_point, layout, first_set, sets, dynamic_offsets }
+    4 BindVertexBuffers { first_binding, buffers, offsets }
+    5 BindIndexBuffer { buffer, offset, index_type }
+    6 PushConstants { layout, stages, offset, data }
+    7 SetViewport { first_viewport, viewports }
+    8 SetScissor { first_scissor, scissors }
+    9 Draw { vertex_count, instance_count, first_vertex, first_instance }
+    10 DrawIndexed { index_count, instance_count, first_index, vertex_offset, first_instance }
+    11 DrawIndirect { buffer, offset, draw_count, stride }
+    12 Dispatch { x, y, z }
+    13 PipelineBarrier { src_stage, dst_stage, dependency, memory_barriers, buffers, images }
+    14 CopyBuffer { src, dst, regions }
+    15 CopyBufferToImage { buffer, image, layout, regions }
+    16 CopyImageToBuffer { image, layout, buffer, regions }
+    17 BlitImage { src, src_layout, dst, dst_layout, regions, filter }
+    18 ClearColorImage { image, layout, color, ranges }
+    19 FillBuffer { buffer, offset, size, data }
+    20 BeginLabel { name, color }
+    21 EndLabel {}
+    22 NextSubpass { contents }
+    23 ExecuteCommands { command_buffers }
+    24 ResetQueryPool { pool, first_query, query_count }
+    25 WriteTimestamp { stage, pool, query }
+    26 BeginQuery { pool, query, flags }
+    27 EndQuery { pool, query }
+    28 CopyQueryPoolResults { pool, first_query, query_count, buffer, offset, stride, flags }
+}
@@ -0,0 +1,214 @@
+#![allow(dead_code)]
+
+use cgmath::{Matrix2, Matrix3, Matrix4, Vector2, Vector3, Vector4};
+
+/// The rules members of a uniform or storage block are laid out by. `Std140` is what uniform
+/// blocks use, it rounds arrays and structs up to 16 bytes. `Std430` is the tighter layout of
+/// storage blocks.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BlockLayout {
+    Std140,
+    Std430,
+}
+
+impl BlockLayout {
+    /// the alignment of a struct or array element whose members align to `align`.
+    pub fn base_align(&self, align: usize) -> usize {
+        match self {
+            BlockLayout::Std140 => align.next_multiple_of(16),
+            BlockLayout::Std430 => align,
+        }
+    }
+
+    /// the offsets of struct members of `(align, size)`, the alignment and the padded size
+    /// of the struct.
+    pub fn struct_layout(&self, members: &[(usize, usize)]) -> (Vec<usize>, usize, usize) {
+        let align = self.base_align(members.iter().map(|m| m.0).max().unwrap_or(1));
+        let mut offsets = vec![];
+        let mut end: usize = 0;
+        for (member_align, size) in members {
+            let offset = end.next_multiple_of(*member_align);
+            offsets.push(offset);
+            end = offset + size;
+        }
+        (offsets, align, end.next_multiple_of(align))
+    }
+}
+
+/// A type members of uniform and storage blocks can have, with its glsl alignment, size and
+/// bytes in either layout. scalars are `f32`, `i32`, `u32` and `bool`, vectors and matrices
+/// the cgmath types, arrays and structs of them `[T; N]` and `uniform_block!`. other types
+/// don't implement it and fail to compile, e.g. `[f32; 4]` reads as an array of four floats,
+/// a `vec4` is a `Vector4<f32>`.
+pub trait Uniform {
+    fn align(layout: BlockLayout) -> usize;
+
+    fn size(layout: BlockLayout) -> usize;
+
+    /// write the member to the start of `out`, which holds at least `size(layout)` bytes.
+    /// padding is left as it is.
+    fn write(&self, layout: BlockLayout, out: &mut [u8]);
+
+    /// the offsets of the members of structs, in declaration order.
+    fn offsets(layout: BlockLayout) -> Vec<usize> {
+        let _ = layout;
+        vec![]
+    }
+
+    /// the bytes of the whole block, padding is zero. `Buffer::write_bytes` takes them as is.
+    fn bytes(&self, layout: BlockLayout) -> Vec<u8>
+    where
+        Self: Sized,
+    {
+        let mut bytes = vec![0; Self::size(layout)];
+        self.write(layout, &mut bytes);
+        bytes
+    }
+}
+
+/// the alignment and size of the member `field` selects, for `uniform_block!`.
+pub fn member<B, F: Uniform>(_field: fn(&B) -> &F, layout: BlockLayout) -> (usize, usize) {
+    (F::align(layout), F::size(layout))
+}
+
+macro_rules! scalars {
+    ($($type:ty => $bytes:expr,)*) => {
+        $(
+            impl Uniform for $type {
+                fn align(_: BlockLayout) -> usize {
+                    4
+                }
+
+                fn size(_: BlockLayout) -> usize {
+                    4
+                }
+
+                fn write(&self, _: BlockLayout, out: &mut [u8]) {
+                    let bytes: fn(&$type) -> [u8; 4] = $bytes;
+                    out[..4].copy_from_slice(&bytes(self));
+                }
+            }
+        )*
+    };
+}
+
+scalars! {
+    f32 => |v| v.to_ne_bytes(),
+    i32 => |v| v.to_ne_bytes(),
+    u32 => |v| v.to_ne_bytes(),
+    bool => |v| (*v as u32).to_ne_bytes(),
+}
+
+// a three component vector aligns like one of four
+macro_rules! vectors {
+    ($($vector:ident $count:literal $align:literal [$($component:ident)*],)*) => {
+        $(
+            impl<T: Uniform + Copy> Uniform for $vector<T> {
+                fn align(layout: BlockLayout) -> usize {
+                    $align * T::align(layout)
+                }
+
+                fn size(layout: BlockLayout) -> usize {
+                    $count * T::size(layout)
+                }
+
+                fn write(&self, layout: BlockLayout, out: &mut [u8]) {
+                    let size = T::size(layout);
+                    for (i, component) in [$(self.$component),*].iter().enumerate() {
+                        component.write(layout, &mut out[i * size..]);
+                    }
+                }
+            }
+        )*
+    };
+}
+
+vectors! {
+    Vector2 2 2 [x y],
+    Vector3 3 4 [x y z],
+    Vector4 4 4 [x y z w],
+}
+
+// a matrix is an array of its column vectors
+macro_rules! matrices {
+    ($($matrix:ident $column:ident $count:literal [$($c:ident)*],)*) => {
+        $(
+            impl<T: Uniform + Copy> Uniform for $matrix<T> {
+                fn align(layout: BlockLayout) -> usize {
+                    <[$column<T>; $count]>::align(layout)
+                }
+
+                fn size(layout: BlockLayout) -> usize {
+                    <[$column<T>; $count]>::size(layout)
+                }
+
+                fn write(&self, layout: BlockLayout, out: &mut [u8]) {
+                    [$(self.$c),*].write(layout, out);
+                }
+            }
+        )*
+    };
+}
+
+matrices! {
+    Matrix2 Vector2 2 [x y],
+    Matrix3 Vector3 3 [x y z],
+    Matrix4 Vector4 4 [x y z w],
+}
+
+impl<T: Uniform, const N: usize> Uniform for [T; N] {
+    fn align(layout: BlockLayout) -> usize {
+        layout.base_align(T::align(layout))
+    }
+
+    fn size(layout: BlockLayout) -> usize {
+        N * array_stride::<T>(layout)
+    }
+
+    fn write(&self, layout: BlockLayout, out: &mut [u8]) {
+        let stride = array_stride::<T>(layout);
+        for (i, element) in self.iter().enumerate() {
+            element.write(layout, &mut out[i * stride..]);
+        }
+    }
+}
+
+// the distance between array elements, their size rounded up to their alignment
+fn array_stride<T: Uniform>(layout: BlockLayout) -> usize {
+    T::size(layout).next_multiple_of(<[T; 1]>::align(layout))
+}
+
+/// Implements `Uniform` for a struct from all of its fields, in the order the block declares
+/// them, `uniform_block!(Frame { view_proj, camera, lights })`. the rust layout doesn't
+/// matter, `Uniform::bytes` lays the fields out by the glsl rules. a missing field or one of a
+/// type that isn't `Uniform` fails to compile.
+#[macro_export]
+macro_rules! uniform_block {
+    ($block:path { $($field:ident),* $(,)? }) => {
+        impl $crate::gfx::Uniform for $block {
+            fn align(layout: $crate::gfx::BlockLayout) -> usize {
+                let members = [$($crate::gfx::member(|b: &$block| &b.$field, layout)),*];
+                layout.struct_layout(&members).1
+            }
+
+            fn size(layout: $crate::gfx::BlockLayout) -> usize {
+                let members = [$($crate::gfx::member(|b: &$block| &b.$field, layout)),*];
+                layout.struct_layout(&members).2
+            }
+
+            fn write(&self, layout: $crate::gfx::BlockLayout, out: &mut [u8]) {
+                let mut offsets = Self::offsets(layout).into_iter();
+                let $block { $($field),* } = self;
+                $(
+                    let offset = offsets.next().unwrap_or_default();
+                    $crate::gfx::Uniform::write($field, layout, &mut out[offset..]);
+                )*
+            }
+
+            fn offsets(layout: $crate::gfx::BlockLayout) -> Vec<usize> {
+                let members = [$($crate::gfx::member(|b: &$block| &b.$field, layout)),*];
+                layout.struct_layout(&members).0
+            }
+        }
+    };
+}
@@ -0,0 +1,467 @@
+#![allow(dead_code)]
+
+use std::collections::HashSet;
+
+use thiserror::Error;
+use vulkanalia::prelude::v1_0::*;
+
+use super::{
+    BindingResource, Blend, Feature, IndexBinding, PipelineTarget, RenderPassDescriptor,
+    RenderPipelineBuilder, SamplerDescriptor, VertexBufferLayout,
+};
+
+/// Why a resource description was refused before it reached vulkan. the create functions
+/// return it inside their `anyhow::Error`, `downcast_ref` gets it back.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum DescriptionError {
+    #[error("Buffers must not be empty.")]
+    EmptyBuffer,
+    #[error("Buffers need at least one usage.")]
+    NoBufferUsage,
+    #[error("A {size} byte uniform buffer exceeds the uniform range limit of {limit}.")]
+    UniformBufferTooLarge { size: u64, limit: u32 },
+    #[error("A {width}x{height} texture has no texels.")]
+    EmptyTexture { width: u32, height: u32 },
+    #[error("A {width}x{height} texture exceeds the limit of {limit}.")]
+    TextureTooLarge { width: u32, height: u32, limit: u32 },
+    #[error("Textures need at least one layer and at most {limit}, not {layers}.")]
+    BadLayerCount { layers: u32, limit: u32 },
+    #[error("Cube textures need square faces and a multiple of six layers.")]
+    BadCube,
+    #[error("Textures need at least one mip level and at most {limit}, not {levels}.")]
+    BadMipLevels { levels: u32, limit: u32 },
+    #[error("Multisampled textures can't have mip levels or layers.")]
+    MultisampledChain,
+    #[error("Textures need exactly one sample count, not {0:?}.")]
+    BadSampleCount(vk::SampleCountFlags),
+    #[error("Textures need at least one usage.")]
+    NoTextureUsage,
+    #[error("Expected {expected} bytes of pixels but got {actual}.")]
+    PixelCount { expected: u64, actual: u64 },
+    #[error("Binding {0} is declared twice.")]
+    DuplicateBinding(u32),
+    #[error("Binding {0} has no shader stages.")]
+    NoBindingStages(u32),
+    #[error("Sampler anisotropy must be between 1 and {limit}, not {value}.")]
+    BadAnisotropy { value: String, limit: String },
+    #[error("Sampler lods must satisfy 0 <= {min} <= {max}.")]
+    BadLodRange { min: String, max: String },
+    #[error("Vertex buffer binding {0} has two layouts.")]
+    DuplicateVertexBinding(u32),
+    #[error("Vertex attribute location {0} is read twice.")]
+    DuplicateLocation(u32),
+    #[error("Indices must be 16 or 32 bit, not {0}.")]
+    UnsupportedIndexType(String),
+    #[error("The index offset {offset} is not a multiple of the index size {size}.")]
+    MisalignedIndices { offset: u64, size: u64 },
+    #[error("Draws indices {first}..{end} of a buffer holding {available}.")]
+    IndexRange {
+        first: u64,
+        end: u64,
+        available: u64,
+    },
+    #[error("Binding {0} is not in the set layout.")]
+    UnknownBinding(u32),
+    #[error(
+        "Binding {binding} holds {expected} descriptors, a {resource} can't be written to it."
+    )]
+    WrongBindingResource {
+        binding: u32,
+        expected: String,
+        resource: String,
+    },
+    #[error("Render passes need at least one attachment.")]
+    NoAttachments,
+    #[error("A {width}x{height} render pass has no pixels.")]
+    EmptyRenderArea { width: u32, height: u32 },
+    #[error("Attachments of a pass must share a sample count, {0:?} differs.")]
+    MixedSamples(vk::SampleCountFlags),
+    #[error("Color attachment {0} is single sampled and can't be resolved.")]
+    SingleSampledResolve(usize),
+    #[error("View mask {0:#b} needs multiview, which the device doesn't support.")]
+    MultiviewUnsupported(u32),
+    #[error("Sets viewports {first}..{end} of the {limit} the device supports.")]
+    TooManyViewports { first: u32, end: u32, limit: u32 },
+    #[error("A pipeline layout of {sets} descriptor sets exceeds the limit of {limit}.")]
+    TooManySets { sets: u32, limit: u32 },
+    #[error("Push constants up to byte {end} exceed the limit of {limit}.")]
+    PushConstantsTooLarge { end: u32, limit: u32 },
+    #[error("The adapter doesn't have the {0:?} feature.")]
+    FeatureUnavailable(Feature),
+    #[error("The {0:?} feature was not enabled when the device was created.")]
+    FeatureNotEnabled(Feature),
+    #[error("Render pipelines need a vertex shader.")]
+    MissingVertexShader,
+    #[error("The {0:?} shader code is not SPIR-V.")]
+    NotSpirv(vk::ShaderStageFlags),
+    #[error("Depth is tested but the pass has no depth attachment.")]
+    NoDepthAttachment,
+    #[error("Depth is written without being tested.")]
+    DepthWriteWithoutTest,
+    #[error("{0:?} blending needs a fragment shader and a color attachment.")]
+    NothingToBlend(Blend),
+    #[error("The sample shading fraction must be between 0 and 1, not {0}.")]
+    BadSampleShading(String),
+    #[error("Subpass {subpass} uses color attachment {attachment}, which the pass doesn't have.")]
+    UnknownSubpassAttachment { subpass: usize, attachment: u32 },
+    #[error("Subpass {subpass} draws into color attachment {attachment} while reading it.")]
+    SubpassFeedback { subpass: usize, attachment: u32 },
+    #[error("The pass has no subpass {0}.")]
+    UnknownSubpass(u32),
+    #[error("Depth is written while the subpass reads it.")]
+    DepthReadOnly,
+}
+
+/// the number of mip levels of a full chain for the extent.
+pub fn max_mip_levels(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+/// check a buffer description the way `vkCreateBuffer` would. buffers used as uniform
+/// buffers only are bound whole, so they must fit the uniform range of the device.
+pub fn check_buffer(
+    size: vk::DeviceSize,
+    usage: vk::BufferUsageFlags,
+    limits: &vk::PhysicalDeviceLimits,
+) -> Result<(), DescriptionError> {
+    if size == 0 {
+        return Err(DescriptionError::EmptyBuffer);
+    }
+    if usage.is_empty() {
+        return Err(DescriptionError::NoBufferUsage);
+    }
+    let transfers = vk::BufferUsageFlags::TRANSFER_SRC | vk::BufferUsageFlags::TRANSFER_DST;
+    let limit = limits.max_uniform_buffer_range;
+    if usage & !transfers == vk::BufferUsageFlags::UNIFORM_BUFFER && size > limit as u64 {
+        return Err(DescriptionError::UniformBufferTooLarge { size, limit });
+    }
+    Ok(())
+}
+
+/// check a 2d texture description against the limits of the device it is created on.
+pub fn check_texture(
+    width: u32,
+    height: u32,
+    mip_levels: u32,
+    layers: u32,
+    flags: vk::ImageCreateFlags,
+    samples: vk::SampleCountFlags,
+    usage: vk::ImageUsageFlags,
+    limits: &vk::PhysicalDeviceLimits,
+) -> Result<(), DescriptionError> {
+    if width == 0 || height == 0 {
+        return Err(DescriptionError::EmptyTexture { width, height });
+    }
+
+    // cube faces are limited separately
+    let cube = flags.contains(vk::ImageCreateFlags::CUBE_COMPATIBLE);
+    let limit = if cube {
+        limits.max_image_dimension_cube
+    } else {
+        limits.max_image_dimension_2d
+    };
+    if width > limit || height > limit {
+        return Err(DescriptionError::TextureTooLarge {
+            width,
+            height,
+            limit,
+        });
+    }
+    if layers == 0 || layers > limits.max_image_array_layers {
+        return Err(DescriptionError::BadLayerCount {
+            layers,
+            limit: limits.max_image_array_layers,
+        });
+    }
+    if cube && (width != height || layers % 6 != 0) {
+        return Err(DescriptionError::BadCube);
+    }
+
+    let limit = max_mip_levels(width, height);
+    if mip_levels == 0 || mip_levels > limit {
+        return Err(DescriptionError::BadMipLevels {
+            levels: mip_levels,
+            limit,
+        });
+    }
+
+    if samples.bits().count_ones() != 1 {
+        return Err(DescriptionError::BadSampleCount(samples));
+    }
+    if samples != vk::SampleCountFlags::_1 && (mip_levels > 1 || layers > 1 || cube) {
+        return Err(DescriptionError::MultisampledChain);
+    }
+    if usage.is_empty() {
+        return Err(DescriptionError::NoTextureUsage);
+    }
+    Ok(())
+}
+
+/// check that `pixels` holds rgba8 texels for the extent, the size can't overflow.
+pub fn check_pixels(width: u32, height: u32, pixels: &[u8]) -> Result<(), DescriptionError> {
+    let expected = (width as u64 * height as u64).saturating_mul(4);
+    if pixels.len() as u64 != expected {
+        return Err(DescriptionError::PixelCount {
+            expected,
+            actual: pixels.len() as u64,
+        });
+    }
+    Ok(())
+}
+
+/// check a sampler description, `max_anisotropy` is 1 when the device can't filter
+/// anisotropically.
+pub fn check_sampler(
+    descriptor: &SamplerDescriptor,
+    max_anisotropy: f32,
+) -> Result<(), DescriptionError> {
+    // the floats are kept as text so the errors stay comparable
+    let value = descriptor.max_anisotropy;
+    if !(1.0..=max_anisotropy.max(1.0)).contains(&value) {
+        return Err(DescriptionError::BadAnisotropy {
+            value: value.to_string(),
+            limit: max_anisotropy.max(1.0).to_string(),
+        });
+    }
+    let (min, max) = (descriptor.min_lod, descriptor.max_lod);
+    if !(min >= 0.0 && min <= max) {
+        return Err(DescriptionError::BadLodRange {
+            min: min.to_string(),
+            max: max.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// check the bindings of a descriptor set layout.
+pub fn check_bindings(bindings: &[vk::DescriptorSetLayoutBinding]) -> Result<(), DescriptionError> {
+    let mut seen = HashSet::new();
+    for binding in bindings {
+        if !seen.insert(binding.binding) {
+            return Err(DescriptionError::DuplicateBinding(binding.binding));
+        }
+        // a binding without descriptors is only reserved, it needs no stages
+        if binding.descriptor_count > 0 && binding.stage_flags.is_empty() {
+            return Err(DescriptionError::NoBindingStages(binding.binding));
+        }
+    }
+    Ok(())
+}
+
+/// check a pipeline layout of `sets` descriptor sets fits the limits of the device, and its
+/// push constant ranges.
+pub fn check_pipeline_layout(
+    sets: u32,
+    push_constant_ranges: &[vk::PushConstantRange],
+    max_sets: u32,
+    max_push_constants: u32,
+) -> Result<(), DescriptionError> {
+    if sets > max_sets {
+        return Err(DescriptionError::TooManySets {
+            sets,
+            limit: max_sets,
+        });
+    }
+    for range in push_constant_ranges {
+        let end = range.offset.saturating_add(range.size);
+        if end > max_push_constants {
+            return Err(DescriptionError::PushConstantsTooLarge {
+                end,
+                limit: max_push_constants,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// check writes of resources against the bindings of the set layout they go to.
+pub fn check_writes(
+    bindings: &[(u32, vk::DescriptorType)],
+    writes: &[(u32, BindingResource)],
+) -> Result<(), DescriptionError> {
+    for (binding, resource) in writes {
+        let Some((_, type_)) = bindings.iter().find(|(b, _)| b == binding) else {
+            return Err(DescriptionError::UnknownBinding(*binding));
+        };
+        if !resource.accepts(*type_) {
+            return Err(DescriptionError::WrongBindingResource {
+                binding: *binding,
+                expected: format!("{:?}", type_),
+                resource: resource.name().into(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// the bytes of an index of `index_type`, only 16 and 32 bit indices are supported.
+pub fn index_size(index_type: vk::IndexType) -> Result<vk::DeviceSize, DescriptionError> {
+    match index_type {
+        vk::IndexType::UINT16 => Ok(2),
+        vk::IndexType::UINT32 => Ok(4),
+        _ => Err(DescriptionError::UnsupportedIndexType(format!(
+            "{:?}",
+            index_type
+        ))),
+    }
+}
+
+/// check an indexed draw reads only indices the bound buffer holds.
+pub fn check_draw_indexed(
+    indices: &IndexBinding,
+    first_index: u32,
+    index_count: u32,
+) -> Result<(), DescriptionError> {
+    let size = index_size(indices.index_type)?;
+    if indices.offset % size != 0 {
+        return Err(DescriptionError::MisalignedIndices {
+            offset: indices.offset,
+            size,
+        });
+    }
+    let available = indices.size.saturating_sub(indices.offset) / size;
+    let end = first_index as u64 + index_count as u64;
+    if end > available {
+        return Err(DescriptionError::IndexRange {
+            first: first_index as u64,
+            end,
+            available,
+        });
+    }
+    Ok(())
+}
+
+/// check the vertex buffers of a pipeline use distinct bindings and locations.
+pub fn check_vertex_layouts(layouts: &[VertexBufferLayout]) -> Result<(), DescriptionError> {
+    let mut bindings = HashSet::new();
+    let mut locations = HashSet::new();
+    for layout in layouts {
+        if !bindings.insert(layout.binding) {
+            return Err(DescriptionError::DuplicateVertexBinding(layout.binding));
+        }
+        for attribute in &layout.attributes {
+            if !locations.insert(attribute.location) {
+                return Err(DescriptionError::DuplicateLocation(attribute.location));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// check the attachments of a pass can share a render pass and framebuffer, view masks need
+/// `multiview`.
+pub fn check_render_pass(
+    descriptor: &RenderPassDescriptor,
+    multiview: bool,
+) -> Result<(), DescriptionError> {
+    if descriptor.view_mask != 0 && !multiview {
+        return Err(DescriptionError::MultiviewUnsupported(descriptor.view_mask));
+    }
+    let extent = descriptor.extent;
+    if extent.width == 0 || extent.height == 0 {
+        return Err(DescriptionError::EmptyRenderArea {
+            width: extent.width,
+            height: extent.height,
+        });
+    }
+
+    let samples = descriptor
+        .color
+        .iter()
+        .map(|a| a.samples)
+        .chain(descriptor.depth_stencil.iter().map(|a| a.samples))
+        .collect::<Vec<_>>();
+    let Some(first) = samples.first() else {
+        return Err(DescriptionError::NoAttachments);
+    };
+    if let Some(other) = samples.iter().find(|s| *s != first) {
+        return Err(DescriptionError::MixedSamples(*other));
+    }
+
+    for (i, attachment) in descriptor.color.iter().enumerate() {
+        if attachment.resolve.is_some() && attachment.samples == vk::SampleCountFlags::_1 {
+            return Err(DescriptionError::SingleSampledResolve(i));
+        }
+    }
+
+    // subpasses use attachments of the pass and don't read what they draw
+    let colors = descriptor.color.len() as u32;
+    for (subpass, s) in descriptor.subpasses.iter().enumerate() {
+        if let Some(attachment) = s.color.iter().chain(&s.input).find(|a| **a >= colors) {
+            return Err(DescriptionError::UnknownSubpassAttachment {
+                subpass,
+                attachment: *attachment,
+            });
+        }
+        if let Some(attachment) = s.color.iter().find(|a| s.input.contains(a)) {
+            return Err(DescriptionError::SubpassFeedback {
+                subpass,
+                attachment: *attachment,
+            });
+        }
+        if (s.depth_stencil || s.depth_input) && descriptor.depth_stencil.is_none() {
+            return Err(DescriptionError::NoDepthAttachment);
+        }
+    }
+    Ok(())
+}
+
+/// check viewports `first..first + count` exist, `limit` is `AdapterLimits::max_viewports`.
+pub fn check_viewports(first: u32, count: u32, limit: u32) -> Result<(), DescriptionError> {
+    let end = first.saturating_add(count);
+    if end > limit {
+        return Err(DescriptionError::TooManyViewports { first, end, limit });
+    }
+    Ok(())
+}
+
+/// check the state of a pipeline fits the pass it draws in: shaders are spir-v, depth needs
+/// an attachment and blending something to blend.
+pub fn check_render_pipeline(
+    pipeline: &RenderPipelineBuilder,
+    target: &PipelineTarget,
+) -> Result<(), DescriptionError> {
+    let Some(vertex) = pipeline.vertex else {
+        return Err(DescriptionError::MissingVertexShader);
+    };
+    let stages = [
+        (vk::ShaderStageFlags::VERTEX, Some(vertex)),
+        (vk::ShaderStageFlags::FRAGMENT, pipeline.fragment),
+    ];
+    for (stage, code) in stages {
+        if code.is_some_and(|code| !is_spirv(code)) {
+            return Err(DescriptionError::NotSpirv(stage));
+        }
+    }
+    check_vertex_layouts(&pipeline.buffers)?;
+
+    if target.samples.bits().count_ones() != 1 {
+        return Err(DescriptionError::BadSampleCount(target.samples));
+    }
+    if !(0.0..=1.0).contains(&pipeline.sample_shading) {
+        return Err(DescriptionError::BadSampleShading(
+            pipeline.sample_shading.to_string(),
+        ));
+    }
+
+    if pipeline.depth_write && !pipeline.depth_test {
+        return Err(DescriptionError::DepthWriteWithoutTest);
+    }
+    if pipeline.depth_test && !target.depth {
+        return Err(DescriptionError::NoDepthAttachment);
+    }
+    if pipeline.depth_write && target.depth_read_only {
+        return Err(DescriptionError::DepthReadOnly);
+    }
+
+    let blends = pipeline.blend != Blend::Replace;
+    if blends && (pipeline.fragment.is_none() || target.color_attachments == 0) {
+        return Err(DescriptionError::NothingToBlend(pipeline.blend));
+    }
+    Ok(())
+}
+
+// whole words starting with the spir-v magic number
+fn is_spirv(code: &[u8]) -> bool {
+    code.len() % 4 == 0 && code.len() >= 20 && code[..4] == 0x0723_0203u32.to_le_bytes()
+}
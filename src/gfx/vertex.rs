@@ -0,0 +1,144 @@
+#![allow(dead_code)]
+
+use std::mem::size_of;
+
+use vulkanalia::prelude::v1_0::*;
+
+use super::{check_vertex_layouts, DescriptionError};
+use crate::math::{Vec2, Vec3, Vec4};
+
+/// A type vertex fields can have, with the format shaders read it in. floats are read as
+/// they are, `u8` vectors as unsigned normalized and `i16` vectors as signed normalized,
+/// colors and packed normals, `u32` as integers.
+pub trait VertexAttribute: Copy {
+    const FORMAT: vk::Format;
+}
+
+macro_rules! attributes {
+    ($($type:ty => $format:ident,)*) => {
+        $(
+            impl VertexAttribute for $type {
+                const FORMAT: vk::Format = vk::Format::$format;
+            }
+        )*
+    };
+}
+
+attributes! {
+    f32 => R32_SFLOAT,
+    [f32; 2] => R32G32_SFLOAT,
+    [f32; 3] => R32G32B32_SFLOAT,
+    [f32; 4] => R32G32B32A32_SFLOAT,
+    Vec2 => R32G32_SFLOAT,
+    Vec3 => R32G32B32_SFLOAT,
+    Vec4 => R32G32B32A32_SFLOAT,
+    [u8; 2] => R8G8_UNORM,
+    [u8; 4] => R8G8B8A8_UNORM,
+    [i16; 2] => R16G16_SNORM,
+    [i16; 4] => R16G16B16A16_SNORM,
+    u32 => R32_UINT,
+    [u32; 2] => R32G32_UINT,
+    [u32; 4] => R32G32B32A32_UINT,
+}
+
+/// The vertex input of a single vertex buffer, its stride and the attributes read from it.
+/// `vertex_layout!` builds it from the fields of a `#[repr(C)]` struct, attributes get
+/// consecutive shader locations in field order.
+#[derive(Clone, Debug)]
+pub struct VertexBufferLayout {
+    pub binding: u32,
+    pub stride: u32,
+    pub input_rate: vk::VertexInputRate,
+    pub attributes: Vec<vk::VertexInputAttributeDescription>,
+}
+
+impl VertexBufferLayout {
+    /// a layout without attributes for buffers of `V` at binding 0, advancing per vertex.
+    pub fn of<V>() -> Self {
+        Self {
+            binding: 0,
+            stride: size_of::<V>() as u32,
+            input_rate: vk::VertexInputRate::VERTEX,
+            attributes: vec![],
+        }
+    }
+
+    /// read the field at `offset` of the vertex, `field` only names its type.
+    pub fn attribute<V, A: VertexAttribute>(mut self, offset: usize, _field: fn(&V) -> &A) -> Self {
+        let location = self
+            .attributes
+            .last()
+            .map_or(0, |attribute| attribute.location + 1);
+        self.attributes.push(
+            vk::VertexInputAttributeDescription::builder()
+                .binding(self.binding)
+                .location(location)
+                .format(A::FORMAT)
+                .offset(offset as u32)
+                .build(),
+        );
+        self
+    }
+
+    /// read from the vertex buffer bound at `binding`.
+    pub fn at_binding(mut self, binding: u32) -> Self {
+        self.binding = binding;
+        self.attributes.iter_mut().for_each(|a| a.binding = binding);
+        self
+    }
+
+    /// advance once per instance instead of once per vertex.
+    pub fn per_instance(mut self) -> Self {
+        self.input_rate = vk::VertexInputRate::INSTANCE;
+        self
+    }
+
+    /// move the attributes so the first is read at `location`, the layouts of the buffers of
+    /// a pipeline must not share locations.
+    pub fn starting_at(mut self, location: u32) -> Self {
+        let first = self.attributes.first().map_or(0, |a| a.location);
+        self.attributes
+            .iter_mut()
+            .for_each(|a| a.location = a.location - first + location);
+        self
+    }
+
+    pub fn binding_description(&self) -> vk::VertexInputBindingDescription {
+        vk::VertexInputBindingDescription::builder()
+            .binding(self.binding)
+            .stride(self.stride)
+            .input_rate(self.input_rate)
+            .build()
+    }
+}
+
+/// the binding and attribute descriptions of the vertex input state reading `layouts`, fails
+/// when two of them use the same binding or location.
+pub fn vertex_input_descriptions(
+    layouts: &[VertexBufferLayout],
+) -> Result<
+    (
+        Vec<vk::VertexInputBindingDescription>,
+        Vec<vk::VertexInputAttributeDescription>,
+    ),
+    DescriptionError,
+> {
+    check_vertex_layouts(layouts)?;
+    let bindings = layouts.iter().map(|l| l.binding_description()).collect();
+    let attributes = layouts
+        .iter()
+        .flat_map(|l| l.attributes.iter().copied())
+        .collect();
+    Ok((bindings, attributes))
+}
+
+/// The `VertexBufferLayout` of a `#[repr(C)]` vertex struct from the fields shaders read, in
+/// location order, `vertex_layout!(Vertex { position, texel, color })`. the field types must
+/// implement `VertexAttribute`, anything else fails to compile.
+#[macro_export]
+macro_rules! vertex_layout {
+    ($vertex:ty { $($field:ident),* $(,)? }) => {
+        $crate::gfx::VertexBufferLayout::of::<$vertex>()
+            $(.attribute(::std::mem::offset_of!($vertex, $field), |v: &$vertex| &v.$field))*
+    };
+}
@@ -1,29 +0,0 @@
-mod buffer;
-mod command;
-mod descriptor;
-mod device;
-mod entities;
-mod frame;
-mod model;
-mod pass;
-mod pipeline;
-mod queue;
-mod sampler;
-mod swap;
-mod shader;
-mod texture;
-
-pub use self::buffer::*;
-pub use self::command::*;
-pub use self::descriptor::*;
-pub use self::device::*;
-pub use self::entities::*;
-pub use self::frame::*;
-pub use self::model::*;
-pub use self::pass::*;
-pub use self::pipeline::*;
-pub use self::queue::*;
-pub use self::sampler::*;
-pub use self::shader::*;
-pub use self::swap::*;
-pub use self::texture::*;
\ No newline at end of file
@@ -0,0 +1,232 @@
+// SPDX-License-Identifier: MIT
+
+#![allow(dead_code, unused_variables, clippy::new_without_default)]
+
+use std::collections::HashSet;
+
+/// The buttons of a gamepad, named by their place on the pad.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl GamepadButton {
+    pub const ALL: [GamepadButton; 17] = [
+        GamepadButton::South,
+        GamepadButton::East,
+        GamepadButton::North,
+        GamepadButton::West,
+        GamepadButton::LeftBumper,
+        GamepadButton::RightBumper,
+        GamepadButton::LeftTrigger,
+        GamepadButton::RightTrigger,
+        GamepadButton::Select,
+        GamepadButton::Start,
+        GamepadButton::Mode,
+        GamepadButton::LeftThumb,
+        GamepadButton::RightThumb,
+        GamepadButton::DPadUp,
+        GamepadButton::DPadDown,
+        GamepadButton::DPadLeft,
+        GamepadButton::DPadRight,
+    ];
+}
+
+/// The analog inputs of a gamepad. sticks are in -1..1 with y up, triggers in 0..1.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl GamepadAxis {
+    pub const ALL: [GamepadAxis; 6] = [
+        GamepadAxis::LeftStickX,
+        GamepadAxis::LeftStickY,
+        GamepadAxis::RightStickX,
+        GamepadAxis::RightStickY,
+        GamepadAxis::LeftTrigger,
+        GamepadAxis::RightTrigger,
+    ];
+}
+
+/// The state of one connected gamepad as of the start of the frame.
+#[derive(Clone, Debug, Default)]
+pub struct GamepadState {
+    pub name: String,
+    pub down: HashSet<GamepadButton>,
+    pub previous: HashSet<GamepadButton>,
+    pub axes: [f32; 6],
+}
+
+impl GamepadState {
+    pub fn is_down(&self, button: GamepadButton) -> bool {
+        self.down.contains(&button)
+    }
+
+    pub fn pressed(&self, button: GamepadButton) -> bool {
+        self.down.contains(&button) && !self.previous.contains(&button)
+    }
+
+    pub fn released(&self, button: GamepadButton) -> bool {
+        !self.down.contains(&button) && self.previous.contains(&button)
+    }
+
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes[axis as usize]
+    }
+}
+
+/// The gamepads known to the input module, empty without the gamepad feature.
+pub struct Gamepads {
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+    pub states: Vec<Option<GamepadState>>,
+}
+
+impl Gamepads {
+    #[cfg(feature = "gamepad")]
+    pub fn new() -> Self {
+        // a missing backend only means no gamepads
+        let gilrs = match gilrs::Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(error) => {
+                tracing::warn!("gamepads are not available: {}", error);
+                None
+            }
+        };
+
+        Self {
+            gilrs,
+            states: vec![],
+        }
+    }
+
+    #[cfg(not(feature = "gamepad"))]
+    pub fn new() -> Self {
+        Self { states: vec![] }
+    }
+
+    /// the state of the gamepad in slot `pad`, if one is connected there.
+    pub fn get(&self, pad: usize) -> Option<&GamepadState> {
+        self.states.get(pad).and_then(|state| state.as_ref())
+    }
+
+    /// the slots of the connected gamepads.
+    pub fn connected(&self) -> impl Iterator<Item = usize> + '_ {
+        self.states
+            .iter()
+            .enumerate()
+            .filter_map(|(pad, state)| state.as_ref().map(|_| pad))
+    }
+
+    /// take a snapshot of all gamepads, called once at the start of a frame.
+    #[cfg(feature = "gamepad")]
+    pub fn poll(&mut self) {
+        let gilrs = match self.gilrs.as_mut() {
+            Some(gilrs) => gilrs,
+            None => return,
+        };
+
+        // events have to be drained for gilrs to update its state
+        while gilrs.next_event().is_some() {}
+
+        let mut states: Vec<Option<GamepadState>> = vec![];
+        for (id, gamepad) in gilrs.gamepads() {
+            let pad = usize::from(id);
+            if states.len() <= pad {
+                states.resize(pad + 1, None);
+            }
+
+            // keep what was down last frame to find presses and releases
+            let previous = self
+                .states
+                .get(pad)
+                .and_then(|state| state.as_ref())
+                .map(|state| state.down.clone())
+                .unwrap_or_default();
+
+            let down = GamepadButton::ALL
+                .iter()
+                .copied()
+                .filter(|button| gamepad.is_pressed(to_button(*button)))
+                .collect();
+
+            let mut axes = [0.0; 6];
+            for axis in GamepadAxis::ALL {
+                axes[axis as usize] = match axis {
+                    GamepadAxis::LeftStickX => gamepad.value(gilrs::Axis::LeftStickX),
+                    GamepadAxis::LeftStickY => gamepad.value(gilrs::Axis::LeftStickY),
+                    GamepadAxis::RightStickX => gamepad.value(gilrs::Axis::RightStickX),
+                    GamepadAxis::RightStickY => gamepad.value(gilrs::Axis::RightStickY),
+                    // most pads report triggers as analog buttons
+                    GamepadAxis::LeftTrigger => trigger(&gamepad, gilrs::Button::LeftTrigger2),
+                    GamepadAxis::RightTrigger => trigger(&gamepad, gilrs::Button::RightTrigger2),
+                };
+            }
+
+            states[pad] = Some(GamepadState {
+                name: gamepad.name().to_string(),
+                down,
+                previous,
+                axes,
+            });
+        }
+
+        self.states = states;
+    }
+
+    #[cfg(not(feature = "gamepad"))]
+    pub fn poll(&mut self) {}
+}
+
+#[cfg(feature = "gamepad")]
+fn to_button(button: GamepadButton) -> gilrs::Button {
+    match button {
+        GamepadButton::South => gilrs::Button::South,
+        GamepadButton::East => gilrs::Button::East,
+        GamepadButton::North => gilrs::Button::North,
+        GamepadButton::West => gilrs::Button::West,
+        GamepadButton::LeftBumper => gilrs::Button::LeftTrigger,
+        GamepadButton::RightBumper => gilrs::Button::RightTrigger,
+        GamepadButton::LeftTrigger => gilrs::Button::LeftTrigger2,
+        GamepadButton::RightTrigger => gilrs::Button::RightTrigger2,
+        GamepadButton::Select => gilrs::Button::Select,
+        GamepadButton::Start => gilrs::Button::Start,
+        GamepadButton::Mode => gilrs::Button::Mode,
+        GamepadButton::LeftThumb => gilrs::Button::LeftThumb,
+        GamepadButton::RightThumb => gilrs::Button::RightThumb,
+        GamepadButton::DPadUp => gilrs::Button::DPadUp,
+        GamepadButton::DPadDown => gilrs::Button::DPadDown,
+        GamepadButton::DPadLeft => gilrs::Button::DPadLeft,
+        GamepadButton::DPadRight => gilrs::Button::DPadRight,
+    }
+}
+
+#[cfg(feature = "gamepad")]
+fn trigger(gamepad: &gilrs::Gamepad, button: gilrs::Button) -> f32 {
+    gamepad
+        .button_data(button)
+        .map(|data| data.value())
+        .unwrap_or(0.0)
+}
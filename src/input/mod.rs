@@ -0,0 +1,7 @@
+// SPDX-License-Identifier: MIT
+
+mod gamepad;
+mod state;
+
+pub use self::gamepad::*;
+pub use self::state::*;
@@ -0,0 +1,230 @@
+// SPDX-License-Identifier: MIT
+
+#![allow(dead_code, unused_variables, clippy::new_without_default)]
+
+use std::collections::HashSet;
+
+use cgmath::Zero;
+use winit::event::{
+    DeviceEvent, ElementState, Event, KeyboardInput, MouseScrollDelta, WindowEvent,
+};
+
+use super::{GamepadAxis, GamepadButton, GamepadState, Gamepads};
+use crate::math::Vec2;
+
+pub use winit::event::MouseButton;
+
+/// A key, by its meaning in the current keyboard layout.
+pub type Key = winit::event::VirtualKeyCode;
+
+// the pixels in one line of scrolling for touchpads that report pixels.
+const PIXELS_PER_LINE: f32 = 20.0;
+
+/// The keyboard, mouse and gamepad state of the current frame. The event loop feeds every
+/// event to `handle_event` and calls `end_frame` after the frame was updated, queries in
+/// between see everything that happened since the previous frame.
+pub struct Input {
+    keys: HashSet<Key>,
+    pressed_keys: HashSet<Key>,
+    released_keys: HashSet<Key>,
+    buttons: HashSet<MouseButton>,
+    pressed_buttons: HashSet<MouseButton>,
+    released_buttons: HashSet<MouseButton>,
+    mouse_position: Option<Vec2>,
+    mouse_delta: Vec2,
+    scroll_delta: Vec2,
+    focused: bool,
+    gamepads: Gamepads,
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self {
+            keys: HashSet::new(),
+            pressed_keys: HashSet::new(),
+            released_keys: HashSet::new(),
+            buttons: HashSet::new(),
+            pressed_buttons: HashSet::new(),
+            released_buttons: HashSet::new(),
+            mouse_position: None,
+            mouse_delta: Vec2::zero(),
+            scroll_delta: Vec2::zero(),
+            focused: true,
+            gamepads: Gamepads::new(),
+        }
+    }
+
+    /// update the state from a window or device event.
+    pub fn handle_event<T>(&mut self, event: &Event<T>) {
+        match event {
+            // gamepads are polled once per frame
+            Event::NewEvents(_) => self.gamepads.poll(),
+            Event::WindowEvent { event, .. } => self.handle_window_event(event),
+            // raw motion keeps working when the cursor is grabbed or at the edge
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta },
+                ..
+            } if self.focused => {
+                self.mouse_delta += Vec2::new(delta.0 as f32, delta.1 as f32);
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_window_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state,
+                        virtual_keycode: Some(key),
+                        ..
+                    },
+                ..
+            } => match state {
+                ElementState::Pressed => {
+                    // repeats are not presses
+                    if self.keys.insert(*key) {
+                        self.pressed_keys.insert(*key);
+                    }
+                }
+                ElementState::Released => {
+                    if self.keys.remove(key) {
+                        self.released_keys.insert(*key);
+                    }
+                }
+            },
+            WindowEvent::MouseInput { state, button, .. } => match state {
+                ElementState::Pressed => {
+                    if self.buttons.insert(*button) {
+                        self.pressed_buttons.insert(*button);
+                    }
+                }
+                ElementState::Released => {
+                    if self.buttons.remove(button) {
+                        self.released_buttons.insert(*button);
+                    }
+                }
+            },
+            WindowEvent::CursorMoved { position, .. } => {
+                self.mouse_position = Some(Vec2::new(position.x as f32, position.y as f32));
+            }
+            WindowEvent::CursorLeft { .. } => self.mouse_position = None,
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.scroll_delta += match delta {
+                    MouseScrollDelta::LineDelta(x, y) => Vec2::new(*x, *y),
+                    MouseScrollDelta::PixelDelta(position) => {
+                        Vec2::new(position.x as f32, position.y as f32) / PIXELS_PER_LINE
+                    }
+                };
+            }
+            WindowEvent::Focused(focused) => {
+                self.focused = *focused;
+
+                // releases are not seen while unfocused, so nothing stays held
+                if !focused {
+                    self.released_keys.extend(self.keys.drain());
+                    self.released_buttons.extend(self.buttons.drain());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// forget what happened this frame, called after the frame was updated.
+    pub fn end_frame(&mut self) {
+        self.pressed_keys.clear();
+        self.released_keys.clear();
+        self.pressed_buttons.clear();
+        self.released_buttons.clear();
+        self.mouse_delta = Vec2::zero();
+        self.scroll_delta = Vec2::zero();
+    }
+
+    /// whether the window has the keyboard focus.
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// whether `key` is held.
+    pub fn is_key_down(&self, key: Key) -> bool {
+        self.keys.contains(&key)
+    }
+
+    /// whether `key` went down this frame.
+    pub fn key_pressed(&self, key: Key) -> bool {
+        self.pressed_keys.contains(&key)
+    }
+
+    /// whether `key` went up this frame.
+    pub fn key_released(&self, key: Key) -> bool {
+        self.released_keys.contains(&key)
+    }
+
+    /// whether `button` is held.
+    pub fn is_mouse_button_down(&self, button: MouseButton) -> bool {
+        self.buttons.contains(&button)
+    }
+
+    /// whether `button` went down this frame.
+    pub fn mouse_button_pressed(&self, button: MouseButton) -> bool {
+        self.pressed_buttons.contains(&button)
+    }
+
+    /// whether `button` went up this frame.
+    pub fn mouse_button_released(&self, button: MouseButton) -> bool {
+        self.released_buttons.contains(&button)
+    }
+
+    /// the cursor in physical pixels from the top left of the window, none when outside.
+    pub fn mouse_position(&self) -> Option<Vec2> {
+        self.mouse_position
+    }
+
+    /// the raw mouse motion this frame, not limited by the window or the cursor.
+    pub fn mouse_delta(&self) -> Vec2 {
+        self.mouse_delta
+    }
+
+    /// the scrolling this frame in lines, y is positive away from the user.
+    pub fn scroll_delta(&self) -> Vec2 {
+        self.scroll_delta
+    }
+
+    /// the slots of the connected gamepads, always empty without the gamepad feature.
+    pub fn gamepads(&self) -> impl Iterator<Item = usize> + '_ {
+        self.gamepads.connected()
+    }
+
+    /// the state of the gamepad in slot `pad`.
+    pub fn gamepad(&self, pad: usize) -> Option<&GamepadState> {
+        self.gamepads.get(pad)
+    }
+
+    /// whether `button` is held on the gamepad in slot `pad`.
+    pub fn is_gamepad_button_down(&self, pad: usize, button: GamepadButton) -> bool {
+        self.gamepad(pad).is_some_and(|state| state.is_down(button))
+    }
+
+    /// whether `button` went down this frame on the gamepad in slot `pad`.
+    pub fn gamepad_button_pressed(&self, pad: usize, button: GamepadButton) -> bool {
+        self.gamepad(pad).is_some_and(|state| state.pressed(button))
+    }
+
+    /// whether `button` went up this frame on the gamepad in slot `pad`.
+    pub fn gamepad_button_released(&self, pad: usize, button: GamepadButton) -> bool {
+        self.gamepad(pad)
+            .is_some_and(|state| state.released(button))
+    }
+
+    /// the value of `axis` on the gamepad in slot `pad`, 0 when not connected.
+    pub fn gamepad_axis(&self, pad: usize, axis: GamepadAxis) -> f32 {
+        self.gamepad(pad).map_or(0.0, |state| state.axis(axis))
+    }
+
+    /// whether `button` went down this frame on any gamepad.
+    pub fn any_gamepad_button_pressed(&self, button: GamepadButton) -> bool {
+        self.gamepads()
+            .any(|pad| self.gamepad_button_pressed(pad, button))
+    }
+}
@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: MIT
+
+//! The deimos engine. `deimos::prelude` is the supported api and follows semver, the module
+//! paths behind it may change between minor versions. `tests/public_api.rs` keeps a snapshot
+//! of the public items so breaking changes are made on purpose.
+//!
+//! There is a single vulkan backend, `gfx`. build against `gfx::Device` to draw to a window
+//! and `gfx::HeadlessDevice` for tests and tools, the renderers in `rendering` take the former.
+//! winit windows need the default `window` feature, without it winit isn't built and a
+//! `gfx::Device` presents to a `gfx::HeadlessWindow` only.
+
+#![allow(
+    dead_code,
+    clippy::manual_slice_size_calculation,
+    clippy::missing_safety_doc,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+// types in public signatures are exported too, `tests/public_api.rs` relies on it
+#![warn(unnameable_types)]
+
+pub mod debug;
+pub mod ecs;
+pub mod gfx;
+#[cfg(feature = "window")]
+pub mod input;
+pub mod math;
+pub mod prelude;
+pub mod rendering;
+#[cfg(feature = "window")]
+pub mod window;
+
+pub use deimos_format as format;
+
+/// The version of the crate the prelude belongs to.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
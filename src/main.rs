@@ -2,6 +2,7 @@
 
 #![allow(
     dead_code,
+    unused_imports,
     unused_variables,
     clippy::manual_slice_size_calculation,
     clippy::too_many_arguments,
@@ -10,19 +11,17 @@
 
 use anyhow::Result;
 use winit::dpi::LogicalSize;
-use winit::event::{ElementState, Event, VirtualKeyCode, WindowEvent};
+use winit::event::{Event, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
 use winit::window::WindowBuilder;
 
 mod app;
-mod gfx;
-mod graphics;
-mod rendering;
 
 #[rustfmt::skip]
 fn main() -> Result<()> {
 
-    // initialize logger
+    // initialize logger, the spans and events of the library are forwarded to it and
+    // filtered by RUST_LOG, e.g. RUST_LOG=deimos=debug for every bind and draw
     pretty_env_logger::init();
 
     // create window event loop
@@ -34,42 +33,47 @@ fn main() -> Result<()> {
         .with_inner_size(LogicalSize::new(640, 480))
         .build(&event_loop)?;
 
-    // assume not destroying and not minimized
-    let mut minimized = false;
+    // assume not destroying
     let mut destroying = false;
     
     // create app
     let mut app = unsafe { app::App::create(&window)? };
+
+    // create input state, fed by every event
+    let mut input = deimos::input::Input::new();
     
     // run event loop until destroying
     event_loop.run(move |event, _, control_flow| {
 
-        // the flow assumes polling
-        *control_flow = ControlFlow::Poll;
+        // the flow assumes polling, a minimized window waits for events instead
+        *control_flow = if app.graphics.is_minimized() { ControlFlow::Wait } else { ControlFlow::Poll };
+
+        // track keyboard, mouse and gamepads
+        input.handle_event(&event);
 
         // check event
         match event {
 
             // update app if is not being destroyed.
-            Event::MainEventsCleared if !destroying && !minimized => unsafe { app.update(&window) }.unwrap(),
+            Event::MainEventsCleared => {
+                if !destroying && !app.graphics.is_minimized() {
+                    unsafe { app.update(&window, &input) }.unwrap();
+                }
 
-            // mark the window as having been resized.
+                // the next frame starts with fresh presses and deltas
+                input.end_frame();
+            }
+
+            // let the device follow the window, a zero size means minimized
             Event::WindowEvent { event: WindowEvent::Resized(size), .. } => {
-                if size.width == 0 || size.height == 0 {
-                    
-                    // is minimized
-                    minimized = true;
-
-                } else {
-                    
-                    // is not minimized
-                    minimized = false;
-
-                    // mark window as being resized
-                    // app.graphics.resized = true;
-                }
+                app.graphics.resized(size.width, size.height);
             }
             
+            // a display of another scale changes the pixel size, the swapchain follows at once
+            Event::WindowEvent { event: WindowEvent::ScaleFactorChanged { scale_factor, new_inner_size }, .. } => {
+                app.graphics.scale_factor_changed(scale_factor, new_inner_size.width, new_inner_size.height);
+            }
+
             // check if close is being requested
             Event::WindowEvent { event: WindowEvent::CloseRequested, .. } => {
 
@@ -83,20 +87,6 @@ fn main() -> Result<()> {
                 unsafe { app.destroy(); }
             }
 
-            // handle keyboard events.
-            Event::WindowEvent { event: WindowEvent::KeyboardInput { input, .. }, .. } => {
-
-                // check if pressed
-                if input.state == ElementState::Pressed {
-
-                    // check key code 
-                    match input.virtual_keycode {
-                        Some(VirtualKeyCode::Left) if app.data.models > 1 => app.data.models -= 1,
-                        Some(VirtualKeyCode::Right) if app.data.models < 4 => app.data.models += 1,
-                        _ => { }
-                    }
-                }
-            }
             _ => {}
         }
     });
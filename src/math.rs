@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: MIT
+
+//! The math types of the engine, cgmath with `f32` components. they are `repr(C)` and lay
+//! out in uniform blocks and vertex buffers as glsl expects, see `gfx::Uniform` and
+//! `gfx::VertexAttribute`, so buffers take them as they are. the asset packs of `format`
+//! have math types of their own without dependencies, they convert through arrays.
+
+pub use cgmath::prelude::*;
+pub use cgmath::{vec2, vec3, vec4, Deg, Rad};
+
+pub type Vec2 = cgmath::Vector2<f32>;
+pub type Vec3 = cgmath::Vector3<f32>;
+pub type Vec4 = cgmath::Vector4<f32>;
+pub type Mat3 = cgmath::Matrix3<f32>;
+pub type Mat4 = cgmath::Matrix4<f32>;
+pub type Quat = cgmath::Quaternion<f32>;
@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: MIT
+
+//! The supported api, `use deimos::prelude::*` brings in everything an app needs.
+
+// device, adapters and resources
+pub use crate::gfx::{
+    AdapterInfo, AdapterLimits, AdapterSelection, AdapterType, BackgroundTask, Buffer,
+    BugReportSettings, Color, ColorSpace, CommandBuffer, DescriptionError, Device, DeviceEvent,
+    DeviceWindow, ExportedFrame, Feature, FrameExportSettings, FrameSink, GpuFuture,
+    GraphicsSettings, HeadlessDevice, HeadlessWindow, ObserverId, PowerPreference, PresentMode,
+    SamplerDescriptor, SurfaceFormat, SurfaceVertex, Texture, TextureImport, TexturePixels,
+    TextureView, Timeline, TimelineWait, Vertex, WorkDone,
+};
+
+// the adapters of a window and its frames
+pub use crate::gfx::{enumerate_adapters, Frame, PostFrame};
+
+// keyboard, mouse and gamepads
+#[cfg(feature = "window")]
+pub use crate::input::{GamepadAxis, GamepadButton, Input, Key, MouseButton};
+
+// window modes, fullscreen and the cursor
+#[cfg(feature = "window")]
+pub use crate::window::{
+    CursorMode, DisplayController, DisplayMode, DisplaySettings, WindowController, WindowMode,
+};
+
+// renderers and their materials
+pub use crate::rendering::{
+    AssetCache, BlendMode, Environment, ExternalTexture, Handle, Light, LineRenderer, LineStyle,
+    Material, Mesh, MeshId, ParticleRenderer, PbrMaterial, PbrRenderer, ReflectionProbe,
+    RenderTarget, Renderer, Sprite, SpriteRenderer, VideoFrame,
+};
+
+// post processing
+pub use crate::rendering::{
+    BloomPass, BloomSettings, FxaaPass, PostChain, PostPass, ToneMapPass, VignettePass,
+};
+
+// scene, animation and time
+pub use crate::rendering::{
+    AnimationClip, AnimationPlayer, Camera, CameraModifier, Clock, ParticleEmitter, ParticleSystem,
+    SceneGraph, Time, TimeChannel, Transform, TransformSync,
+};
+
+// math types
+pub use crate::math::{Mat3, Mat4, Quat, Vec2, Vec3, Vec4};
+
+// asset packs shared with offline tooling
+pub use crate::format::{MaterialDesc, MeshPack, TexturePack};
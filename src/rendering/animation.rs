@@ -0,0 +1,604 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+#[cfg(feature = "gltf")]
+use std::fs;
+#[cfg(feature = "gltf")]
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use cgmath::{InnerSpace, VectorSpace};
+
+use super::{slerp, Clock, SceneGraph, TimeChannel, Transform};
+use crate::math::{Quat, Vec3};
+
+/// How values between two keyframes are computed, as defined by gltf.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Interpolation {
+    /// keep the value of the previous keyframe.
+    Step,
+    /// straight lines, rotations along the shortest arc.
+    Linear,
+    /// hermite curves, every keyframe stores an in tangent, the value and an out tangent.
+    CubicSpline,
+}
+
+/// The values of a channel, cubic splines store three per keyframe.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnimationValues {
+    Translation(Vec<Vec3>),
+    Rotation(Vec<Quat>),
+    Scale(Vec<Vec3>),
+}
+
+/// Drives one property of one node.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationChannel {
+    pub node: usize,
+    pub interpolation: Interpolation,
+    /// increasing keyframe times in seconds.
+    pub times: Vec<f32>,
+    pub values: AnimationValues,
+}
+
+impl AnimationChannel {
+    /// apply the value at `time` to the transform, times outside the keyframes are clamped.
+    pub fn sample(&self, time: f32, transform: &mut Transform) {
+        if self.times.is_empty() {
+            return;
+        }
+
+        match &self.values {
+            AnimationValues::Translation(values) => {
+                if let Some(v) = self.sample_values(time, values, lerp_vec3, cubic_vec3) {
+                    transform.translation = v;
+                }
+            }
+            AnimationValues::Rotation(values) => {
+                if let Some(v) = self.sample_values(time, values, slerp, cubic_quat) {
+                    transform.rotation = v.normalize();
+                }
+            }
+            AnimationValues::Scale(values) => {
+                if let Some(v) = self.sample_values(time, values, lerp_vec3, cubic_vec3) {
+                    transform.scale = v;
+                }
+            }
+        }
+    }
+
+    fn sample_values<T: Copy>(
+        &self,
+        time: f32,
+        values: &[T],
+        lerp: fn(T, T, f32) -> T,
+        cubic: fn(T, T, T, T, f32, f32) -> T,
+    ) -> Option<T> {
+        // cubic splines surround each value by its tangents
+        let stride = match self.interpolation {
+            Interpolation::CubicSpline => 3,
+            _ => 1,
+        };
+        let offset = stride / 2;
+        let value = |key: usize| values.get(key * stride + offset).copied();
+
+        // the keyframes around the time
+        let last = self.times.len() - 1;
+        let next = self.times.partition_point(|t| *t <= time);
+        if next == 0 {
+            return value(0);
+        }
+        if next > last {
+            return value(last);
+        }
+        let previous = next - 1;
+
+        let start = self.times[previous];
+        let duration = self.times[next] - start;
+        let t = if duration > 0.0 {
+            ((time - start) / duration).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        match self.interpolation {
+            Interpolation::Step => value(previous),
+            Interpolation::Linear => Some(lerp(value(previous)?, value(next)?, t)),
+            Interpolation::CubicSpline => {
+                let out_tangent = *values.get(previous * 3 + 2)?;
+                let in_tangent = *values.get(next * 3)?;
+                Some(cubic(
+                    value(previous)?,
+                    out_tangent,
+                    in_tangent,
+                    value(next)?,
+                    duration,
+                    t,
+                ))
+            }
+        }
+    }
+}
+
+/// A named set of channels played together.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationClip {
+    pub name: String,
+    pub channels: Vec<AnimationChannel>,
+    /// the time of the last keyframe in seconds.
+    pub duration: f32,
+}
+
+impl AnimationClip {
+    pub fn new(name: &str, channels: Vec<AnimationChannel>) -> Self {
+        let duration = channels
+            .iter()
+            .filter_map(|c| c.times.last().copied())
+            .fold(0.0, f32::max);
+
+        Self {
+            name: name.to_string(),
+            channels,
+            duration,
+        }
+    }
+
+    /// apply all channels at `time` to the transforms, indexed by node.
+    pub fn sample(&self, time: f32, transforms: &mut [Transform]) {
+        for channel in self.channels.iter() {
+            if let Some(transform) = transforms.get_mut(channel.node) {
+                channel.sample(time, transform);
+            }
+        }
+    }
+}
+
+/// load the node hierarchy and all animations of a gltf or glb file.
+/// buffers must be embedded in the glb or stored next to the file, data uris are not supported.
+#[cfg(feature = "gltf")]
+pub fn load_gltf_animations(path: &str) -> Result<(SceneGraph, Vec<AnimationClip>)> {
+    let gltf = gltf::Gltf::open(path)?;
+    let buffers = read_gltf_buffers(&gltf, path)?;
+
+    let scene = SceneGraph::from_gltf(&gltf.document);
+
+    let clips = gltf
+        .animations()
+        .map(|animation| {
+            let channels = animation
+                .channels()
+                .filter_map(|channel| {
+                    let reader = channel.reader(|b| buffers.get(b.index()).map(|d| &d[..]));
+                    let times = reader.read_inputs()?.collect::<Vec<_>>();
+                    let values = match reader.read_outputs()? {
+                        gltf::animation::util::ReadOutputs::Translations(values) => {
+                            AnimationValues::Translation(values.map(Vec3::from).collect())
+                        }
+                        gltf::animation::util::ReadOutputs::Rotations(values) => {
+                            AnimationValues::Rotation(
+                                values
+                                    .into_f32()
+                                    .map(|[x, y, z, w]| Quat::new(w, x, y, z))
+                                    .collect(),
+                            )
+                        }
+                        gltf::animation::util::ReadOutputs::Scales(values) => {
+                            AnimationValues::Scale(values.map(Vec3::from).collect())
+                        }
+                        // morph targets are not supported
+                        gltf::animation::util::ReadOutputs::MorphTargetWeights(_) => return None,
+                    };
+                    let interpolation = match channel.sampler().interpolation() {
+                        gltf::animation::Interpolation::Step => Interpolation::Step,
+                        gltf::animation::Interpolation::Linear => Interpolation::Linear,
+                        gltf::animation::Interpolation::CubicSpline => Interpolation::CubicSpline,
+                    };
+
+                    Some(AnimationChannel {
+                        node: channel.target().node().index(),
+                        interpolation,
+                        times,
+                        values,
+                    })
+                })
+                .collect();
+
+            let name = animation
+                .name()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("animation {}", animation.index()));
+            AnimationClip::new(&name, channels)
+        })
+        .collect();
+
+    Ok((scene, clips))
+}
+
+/// the contents of the buffers of a gltf file at `path`, in the order of the document.
+#[cfg(feature = "gltf")]
+pub(crate) fn read_gltf_buffers(gltf: &gltf::Gltf, path: &str) -> Result<Vec<Vec<u8>>> {
+    let folder = Path::new(path).parent().unwrap_or(Path::new("."));
+    gltf.buffers()
+        .map(|buffer| match buffer.source() {
+            gltf::buffer::Source::Bin => gltf
+                .blob
+                .clone()
+                .ok_or_else(|| anyhow!("Missing binary chunk in {}.", path)),
+            gltf::buffer::Source::Uri(uri) if uri.starts_with("data:") => {
+                Err(anyhow!("Unsupported data uri buffer in {}.", path))
+            }
+            gltf::buffer::Source::Uri(uri) => Ok(fs::read(folder.join(uri))?),
+        })
+        .collect()
+}
+
+/// without the gltf feature gltf files can't be read.
+#[cfg(not(feature = "gltf"))]
+pub fn load_gltf_animations(path: &str) -> Result<(SceneGraph, Vec<AnimationClip>)> {
+    Err(anyhow!(
+        "Can't load {}, gltf support needs the gltf feature.",
+        path
+    ))
+}
+
+/// The playback position of a clip.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Playback {
+    clip: usize,
+    time: f32,
+}
+
+/// A fade from the current clip to another one.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Fade {
+    target: Playback,
+    elapsed: f32,
+    duration: f32,
+}
+
+/// Plays clips on the transforms of a scene graph, with crossfades between two clips.
+pub struct AnimationPlayer {
+    pub clips: Vec<AnimationClip>,
+    /// the playback rate, 1 is real time.
+    pub speed: f32,
+    /// whether clips start over at their end, otherwise they stop on the last keyframe.
+    pub looping: bool,
+    /// the clock channel `tick` follows.
+    pub channel: TimeChannel,
+    current: Option<Playback>,
+    fade: Option<Fade>,
+    playing: bool,
+}
+
+impl AnimationPlayer {
+    pub fn new(clips: Vec<AnimationClip>) -> Self {
+        Self {
+            clips,
+            speed: 1.0,
+            looping: true,
+            channel: TimeChannel::Game,
+            current: None,
+            fade: None,
+            playing: false,
+        }
+    }
+
+    /// the index of the first clip with a name.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.clips.iter().position(|c| c.name == name)
+    }
+
+    /// start a clip from the beginning, cancels any fade.
+    pub fn play(&mut self, clip: usize) {
+        if clip < self.clips.len() {
+            self.current = Some(Playback { clip, time: 0.0 });
+            self.fade = None;
+            self.playing = true;
+        }
+    }
+
+    /// fade from the current clip to another over `duration` seconds, both keep playing.
+    pub fn crossfade(&mut self, clip: usize, duration: f32) {
+        if clip >= self.clips.len() {
+            return;
+        }
+
+        // nothing to fade from
+        if self.current.is_none() || duration <= 0.0 {
+            self.play(clip);
+            return;
+        }
+
+        self.fade = Some(Fade {
+            target: Playback { clip, time: 0.0 },
+            elapsed: 0.0,
+            duration,
+        });
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn resume(&mut self) {
+        self.playing = self.current.is_some();
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// move the current clip to a time in seconds, a fade target keeps its own time.
+    pub fn seek(&mut self, time: f32) {
+        if let Some(current) = self.current {
+            let time = self.wrap(current.clip, time);
+            self.current = Some(Playback { time, ..current });
+        }
+    }
+
+    /// the current clip and its time.
+    pub fn position(&self) -> Option<(usize, f32)> {
+        self.current.map(|c| (c.clip, c.time))
+    }
+
+    /// advance the clips by the step of their channel.
+    pub fn tick(&mut self, clock: &Clock) {
+        self.update(clock.delta(self.channel));
+    }
+
+    /// advance the clips by `delta` seconds, scaled by the speed.
+    pub fn update(&mut self, delta: f32) {
+        if !self.playing {
+            return;
+        }
+
+        let delta = delta * self.speed;
+        if let Some(current) = self.current {
+            self.current = Some(self.advance(current, delta));
+        }
+
+        if let Some(fade) = self.fade {
+            let fade = Fade {
+                target: self.advance(fade.target, delta),
+                elapsed: fade.elapsed + delta.abs(),
+                ..fade
+            };
+
+            // the target takes over once faded in
+            if fade.elapsed >= fade.duration {
+                self.current = Some(fade.target);
+                self.fade = None;
+            } else {
+                self.fade = Some(fade);
+            }
+        }
+
+        // clips that don't loop stop at their end
+        if !self.looping && self.fade.is_none() {
+            if let Some(current) = self.current {
+                let duration = self.clips[current.clip].duration;
+                if current.time >= duration {
+                    self.playing = false;
+                }
+            }
+        }
+    }
+
+    /// write the animated transforms into the scene, nodes without channels stay at rest.
+    pub fn apply(&self, scene: &mut SceneGraph) {
+        let Some(current) = self.current else {
+            return;
+        };
+
+        let mut transforms = scene.rest_transforms();
+        self.clips[current.clip].sample(current.time, &mut transforms);
+
+        // blend towards the target clip
+        if let Some(fade) = self.fade {
+            let mut target = scene.rest_transforms();
+            self.clips[fade.target.clip].sample(fade.target.time, &mut target);
+
+            let weight = (fade.elapsed / fade.duration).clamp(0.0, 1.0);
+            for (transform, target) in transforms.iter_mut().zip(target.iter()) {
+                *transform = transform.blend(target, weight);
+            }
+        }
+
+        for (node, transform) in scene.nodes.iter_mut().zip(transforms) {
+            node.transform = transform;
+        }
+    }
+
+    fn advance(&self, playback: Playback, delta: f32) -> Playback {
+        Playback {
+            time: self.wrap(playback.clip, playback.time + delta),
+            ..playback
+        }
+    }
+
+    /// wrap or clamp a time into the clip.
+    fn wrap(&self, clip: usize, time: f32) -> f32 {
+        let duration = self.clips[clip].duration;
+        if duration <= 0.0 {
+            0.0
+        } else if self.looping {
+            time.rem_euclid(duration)
+        } else {
+            time.clamp(0.0, duration)
+        }
+    }
+}
+
+fn lerp_vec3(a: Vec3, b: Vec3, t: f32) -> Vec3 {
+    a.lerp(b, t)
+}
+
+/// the gltf cubic hermite spline, tangents are scaled by the keyframe duration.
+fn hermite(t: f32) -> (f32, f32, f32, f32) {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (
+        2.0 * t3 - 3.0 * t2 + 1.0,
+        t3 - 2.0 * t2 + t,
+        -2.0 * t3 + 3.0 * t2,
+        t3 - t2,
+    )
+}
+
+fn cubic_vec3(p0: Vec3, m0: Vec3, m1: Vec3, p1: Vec3, duration: f32, t: f32) -> Vec3 {
+    let (a, b, c, d) = hermite(t);
+    p0 * a + m0 * (b * duration) + p1 * c + m1 * (d * duration)
+}
+
+fn cubic_quat(p0: Quat, m0: Quat, m1: Quat, p1: Quat, duration: f32, t: f32) -> Quat {
+    let (a, b, c, d) = hermite(t);
+    p0 * a + m0 * (b * duration) + p1 * c + m1 * (d * duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slide(interpolation: Interpolation) -> AnimationChannel {
+        AnimationChannel {
+            node: 0,
+            interpolation,
+            times: vec![1.0, 3.0],
+            values: AnimationValues::Translation(vec![
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(2.0, 0.0, 0.0),
+            ]),
+        }
+    }
+
+    fn at(channel: &AnimationChannel, time: f32) -> Vec3 {
+        let mut transform = Transform::default();
+        channel.sample(time, &mut transform);
+        transform.translation
+    }
+
+    fn player(looping: bool) -> AnimationPlayer {
+        let mut player = AnimationPlayer::new(vec![
+            AnimationClip::new("slide", vec![slide(Interpolation::Linear)]),
+            AnimationClip::new("empty", vec![]),
+        ]);
+        player.looping = looping;
+        player
+    }
+
+    #[test]
+    fn channels_are_clamped_outside_their_keyframes() {
+        let linear = slide(Interpolation::Linear);
+        assert_eq!(at(&linear, -5.0), Vec3::new(0.0, 0.0, 0.0));
+        assert_eq!(at(&linear, 1.0), Vec3::new(0.0, 0.0, 0.0));
+        assert_eq!(at(&linear, 2.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(at(&linear, 3.0), Vec3::new(2.0, 0.0, 0.0));
+        assert_eq!(at(&linear, 10.0), Vec3::new(2.0, 0.0, 0.0));
+
+        let step = slide(Interpolation::Step);
+        assert_eq!(at(&step, 2.9), Vec3::new(0.0, 0.0, 0.0));
+        assert_eq!(at(&step, 3.0), Vec3::new(2.0, 0.0, 0.0));
+
+        // zero tangents ease in and out through the middle
+        let cubic = AnimationChannel {
+            interpolation: Interpolation::CubicSpline,
+            values: AnimationValues::Translation(vec![
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(2.0, 0.0, 0.0),
+                Vec3::new(0.0, 0.0, 0.0),
+            ]),
+            ..slide(Interpolation::CubicSpline)
+        };
+        assert_eq!(at(&cubic, 2.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(at(&cubic, 1.5).x < 0.5);
+    }
+
+    #[test]
+    fn empty_channels_and_clips_leave_transforms_alone() {
+        let empty = AnimationChannel {
+            times: vec![],
+            ..slide(Interpolation::Linear)
+        };
+        let moved = Transform {
+            translation: Vec3::new(5.0, 0.0, 0.0),
+            ..Transform::default()
+        };
+        let mut transform = moved;
+        empty.sample(1.0, &mut transform);
+        assert_eq!(transform, moved);
+
+        // values missing for a keyframe are skipped
+        let short = AnimationChannel {
+            values: AnimationValues::Translation(vec![Vec3::new(1.0, 0.0, 0.0)]),
+            ..slide(Interpolation::Linear)
+        };
+        let mut transform = moved;
+        short.sample(2.0, &mut transform);
+        assert_eq!(transform, moved);
+
+        let clip = AnimationClip::new("empty", vec![]);
+        assert_eq!(clip.duration, 0.0);
+        let mut transforms = vec![moved];
+        clip.sample(1.0, &mut transforms);
+        assert_eq!(transforms[0], moved);
+    }
+
+    #[test]
+    fn zero_and_negative_steps_are_handled() {
+        let mut looping = player(true);
+        looping.play(0);
+        looping.update(1.0);
+        looping.update(0.0);
+        assert_eq!(looping.position(), Some((0, 1.0)));
+
+        // stepping back wraps around the start of looping clips
+        looping.update(-2.0);
+        assert_eq!(looping.position(), Some((0, 2.0)));
+
+        let mut once = player(false);
+        once.play(0);
+        once.update(-1.0);
+        assert_eq!(once.position(), Some((0, 0.0)));
+        assert!(once.is_playing());
+        once.update(5.0);
+        assert_eq!(once.position(), Some((0, 3.0)));
+        assert!(!once.is_playing());
+
+        // clips without keyframes stay at their start
+        let mut empty = player(true);
+        empty.play(1);
+        empty.update(1.0);
+        assert_eq!(empty.position(), Some((1, 0.0)));
+    }
+
+    #[test]
+    fn crossfades_hand_over_to_their_target() {
+        let mut player = player(true);
+        // nothing plays yet, the clip starts right away
+        player.crossfade(1, 1.0);
+        assert_eq!(player.position(), Some((1, 0.0)));
+
+        player.play(0);
+        player.crossfade(1, 1.0);
+        player.update(0.5);
+        assert_eq!(player.position(), Some((0, 0.5)));
+        player.update(0.5);
+        assert_eq!(player.position(), Some((1, 0.0)));
+
+        // paused players don't move, unknown clips are ignored
+        player.pause();
+        player.update(1.0);
+        player.play(7);
+        assert_eq!(player.position(), Some((1, 0.0)));
+        assert!(!player.is_playing());
+    }
+}
@@ -0,0 +1,494 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use std::cell::{Ref, RefCell};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::mem::{self, size_of};
+use std::rc::Rc;
+use std::slice;
+use std::time::Duration;
+
+use anyhow::Result;
+use tracing::{debug, warn};
+
+use super::{read_mesh, FileWatcher, Mesh, SourceData, SourceKind};
+use crate::gfx;
+
+/// A shared reference to a cached asset, the cache frees the asset in `purge_unused` once
+/// no handle to it is left. a reloaded file swaps the asset behind its handles, so read it
+/// through the handle when recording, copies of its buffers or views go stale.
+pub struct Handle<T>(Rc<RefCell<T>>);
+
+impl<T> Handle<T> {
+    /// the asset, the borrow must end before the cache reloads it.
+    pub fn get(&self) -> Ref<'_, T> {
+        self.0.borrow()
+    }
+
+    /// the number of handles to the asset, not counting the cache.
+    pub fn count(&self) -> usize {
+        Rc::strong_count(&self.0) - 1
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Handle({:p})", Rc::as_ptr(&self.0))
+    }
+}
+
+/// Where a cached asset came from, the same key always yields the same asset.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AssetKey {
+    /// a file, the path is made absolute when it exists so spellings of it share the asset.
+    Path(String),
+    /// data handed over directly, keyed by a hash of its bytes.
+    Content(u64),
+}
+
+impl AssetKey {
+    pub fn path(path: &str) -> Self {
+        let path = fs::canonicalize(path)
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| path.to_string());
+        AssetKey::Path(path)
+    }
+
+    pub fn content(bytes: &[u8]) -> Self {
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        AssetKey::Content(hasher.finish())
+    }
+}
+
+/// The assets of one kind, keyed by `K`.
+pub struct Assets<K, T> {
+    entries: HashMap<K, Rc<RefCell<T>>>,
+}
+
+impl<K: Eq + Hash, T> Assets<K, T> {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    /// the number of cached assets, used or not.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// a handle to the asset of `key` when it is cached.
+    pub fn get(&self, key: &K) -> Option<Handle<T>> {
+        self.entries.get(key).map(|a| Handle(a.clone()))
+    }
+
+    /// a handle to the asset of `key`, `create` makes it when it isn't cached yet.
+    pub fn get_or_create(
+        &mut self,
+        key: K,
+        create: impl FnOnce() -> Result<T>,
+    ) -> Result<Handle<T>> {
+        if let Some(asset) = self.entries.get(&key) {
+            return Ok(Handle(asset.clone()));
+        }
+        let asset = Rc::new(RefCell::new(create()?));
+        self.entries.insert(key, asset.clone());
+        Ok(Handle(asset))
+    }
+
+    /// the keys of all cached assets.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.keys()
+    }
+
+    /// swap the asset of `key` for `asset` behind its handles, returns the previous one.
+    pub fn replace(&mut self, key: &K, asset: T) -> Option<T> {
+        self.entries.get(key).map(|a| a.replace(asset))
+    }
+
+    /// remove the assets no handle refers to anymore and return them with their keys.
+    pub fn take_unused(&mut self) -> Vec<(K, T)> {
+        let (unused, used): (Vec<_>, Vec<_>) = mem::take(&mut self.entries)
+            .into_iter()
+            .partition(|(_, a)| Rc::strong_count(a) == 1);
+        self.entries = used.into_iter().collect();
+        unused
+            .into_iter()
+            .filter_map(|(k, a)| Some((k, Rc::try_unwrap(a).ok()?.into_inner())))
+            .collect()
+    }
+
+    /// remove every asset, handles that are left keep theirs alive as far as the cache
+    /// is concerned, `destroy` still gets to release it.
+    pub fn clear(&mut self, mut destroy: impl FnMut(&T)) {
+        self.entries.drain().for_each(|(_, a)| destroy(&a.borrow()));
+    }
+}
+
+impl<K: Eq + Hash, T> Default for Assets<K, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A texture in the cache with the view that covers all of it.
+pub struct TextureAsset {
+    pub texture: gfx::Texture,
+    pub view: gfx::TextureView,
+}
+
+/// Textures, meshes and shaders shared by everything that loads them, each file or blob of
+/// data is uploaded once. assets stay cached while handles to them exist, `purge_unused`
+/// retires the rest through the device so frames in flight can finish with them. with hot
+/// reload enabled texture and mesh files are watched and `reload_changed` swaps in what
+/// was written to them.
+#[derive(Default)]
+pub struct AssetCache {
+    textures: Assets<(AssetKey, gfx::TextureImport), TextureAsset>,
+    meshes: Assets<AssetKey, Mesh>,
+    shaders: Assets<AssetKey, gfx::Shader>,
+    watcher: Option<FileWatcher>,
+    // the texture scale of the graphics settings files were loaded with
+    texture_scale: Option<f32>,
+}
+
+impl AssetCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the number of cached textures, meshes and shaders.
+    pub fn len(&self) -> usize {
+        self.textures.len() + self.meshes.len() + self.shaders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// watch the files of cached and future textures and meshes, they are checked every
+    /// `interval`. `None` stops watching.
+    pub fn set_hot_reload(&mut self, interval: Option<Duration>) {
+        self.watcher = interval.map(FileWatcher::new);
+        if let Some(watcher) = &self.watcher {
+            for (key, _) in self.textures.keys() {
+                if let AssetKey::Path(path) = key {
+                    watcher.watch(path, SourceKind::Texture);
+                }
+            }
+            for key in self.meshes.keys() {
+                if let AssetKey::Path(path) = key {
+                    watcher.watch(path, SourceKind::Mesh);
+                }
+            }
+        }
+    }
+
+    /// whether texture and mesh files are watched.
+    pub fn is_hot_reloading(&self) -> bool {
+        self.watcher.is_some()
+    }
+
+    /// the texture of a png, see `gfx::Device::load_texture`. a file is uploaded once per
+    /// `import` policy.
+    pub fn load_texture(
+        &mut self,
+        device: &gfx::Device,
+        path: &str,
+        import: gfx::TextureImport,
+    ) -> Result<Handle<TextureAsset>> {
+        let key = AssetKey::path(path);
+        self.watch(&key, SourceKind::Texture);
+        self.texture_scale
+            .get_or_insert(device.graphics_settings().texture_scale);
+        self.textures.get_or_create((key, import), || {
+            debug!(path, ?import, "load texture");
+            let (texture, view) = device.load_texture(path, import)?;
+            Ok(TextureAsset { texture, view })
+        })
+    }
+
+    /// the texture of rgba8 pixels, see `gfx::Device::upload_texture`.
+    pub fn upload_texture(
+        &mut self,
+        device: &gfx::Device,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        import: gfx::TextureImport,
+    ) -> Result<Handle<TextureAsset>> {
+        let key = content_key(&[width, height], pixels);
+        self.textures.get_or_create((key, import), || {
+            let (texture, view) = device.upload_texture(width, height, pixels, import)?;
+            Ok(TextureAsset { texture, view })
+        })
+    }
+
+    /// the mesh of a mesh file, see `read_mesh`.
+    pub fn load_mesh(&mut self, device: &gfx::Device, path: &str) -> Result<Handle<Mesh>> {
+        let key = AssetKey::path(path);
+        self.watch(&key, SourceKind::Mesh);
+        self.meshes.get_or_create(key, || {
+            debug!(path, "load mesh");
+            Mesh::from_pack(device, &read_mesh(path)?)
+        })
+    }
+
+    /// the mesh of a mesh pack, see `Mesh::from_pack`.
+    pub fn mesh_pack(
+        &mut self,
+        device: &gfx::Device,
+        pack: &deimos_format::MeshPack,
+    ) -> Result<Handle<Mesh>> {
+        let key = AssetKey::content(&pack.encode());
+        self.meshes
+            .get_or_create(key, || Mesh::from_pack(device, pack))
+    }
+
+    /// the mesh of unlit vertices, see `Mesh::create`.
+    pub fn mesh(
+        &mut self,
+        device: &gfx::Device,
+        vertices: &[gfx::Vertex],
+        indices: &[u32],
+    ) -> Result<Handle<Mesh>> {
+        let key = content_key(as_bytes(vertices), as_bytes(indices));
+        self.meshes
+            .get_or_create(key, || Mesh::create(device, vertices, indices))
+    }
+
+    /// the mesh of lit vertices, see `Mesh::create_surface`.
+    pub fn surface_mesh(
+        &mut self,
+        device: &gfx::Device,
+        vertices: &[gfx::SurfaceVertex],
+        indices: &[u32],
+    ) -> Result<Handle<Mesh>> {
+        let key = content_key(as_bytes(vertices), as_bytes(indices));
+        self.meshes
+            .get_or_create(key, || Mesh::create_surface(device, vertices, indices))
+    }
+
+    /// the shader module of a spir-v file.
+    pub fn load_shader(&mut self, device: &gfx::Device, path: &str) -> Result<Handle<gfx::Shader>> {
+        self.shaders.get_or_create(AssetKey::path(path), || {
+            debug!(path, "load shader");
+            unsafe { gfx::Shader::create(device.device(), &fs::read(path)?) }
+        })
+    }
+
+    /// the shader module of spir-v code.
+    pub fn shader(&mut self, device: &gfx::Device, code: &[u8]) -> Result<Handle<gfx::Shader>> {
+        self.shaders
+            .get_or_create(AssetKey::content(code), || unsafe {
+                gfx::Shader::create(device.device(), code)
+            })
+    }
+
+    /// upload the watched files written since the last call and swap them in behind the
+    /// handles, the previous resources are retired. call it between frames. a file that
+    /// can't be read keeps its asset, returns how many assets were reloaded.
+    pub fn reload_changed(&mut self, device: &gfx::Device) -> Result<usize> {
+        let Some(watcher) = &self.watcher else {
+            return Ok(0);
+        };
+
+        let mut count = 0;
+        for change in watcher.changes() {
+            let data = match change.data {
+                Ok(data) => data,
+                Err(e) => {
+                    warn!("Failed to reload {}: {}", change.path, e);
+                    continue;
+                }
+            };
+
+            let key = AssetKey::Path(change.path);
+            match data {
+                SourceData::Texture {
+                    width,
+                    height,
+                    pixels,
+                } => {
+                    let pixels = device.scale_texture(width, height, pixels);
+                    count += self.replace_texture(device, &key, pixels)?;
+                }
+                SourceData::Mesh(pack) => {
+                    if self.meshes.get(&key).is_some() {
+                        let mesh = Mesh::from_pack(device, &pack)?;
+                        if let Some(old) = self.meshes.replace(&key, mesh) {
+                            retire_mesh(device, old);
+                            count += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        if count > 0 {
+            debug!(count, "reload changed assets");
+        }
+        Ok(count)
+    }
+
+    /// load the texture files again when the texture scale of the graphics settings changed
+    /// since they were loaded, swapping them in like `reload_changed`. call it between
+    /// frames after `gfx::Device::set_graphics_settings`, returns how many were reloaded.
+    pub fn apply_graphics_settings(&mut self, device: &gfx::Device) -> Result<usize> {
+        let scale = device.graphics_settings().texture_scale;
+        if self.texture_scale.replace(scale).is_none_or(|s| s == scale) {
+            return Ok(0);
+        }
+
+        let mut paths = self
+            .textures
+            .keys()
+            .filter_map(|(k, _)| match k {
+                AssetKey::Path(path) => Some(path.clone()),
+                AssetKey::Content(_) => None,
+            })
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths.dedup();
+
+        let mut count = 0;
+        for path in paths {
+            let (width, height, pixels) = gfx::read_png(&path)?;
+            let pixels = device.scale_texture(width, height, pixels);
+            count += self.replace_texture(device, &AssetKey::Path(path), pixels)?;
+        }
+        debug!(count, scale, "reload textures at a new scale");
+        Ok(count)
+    }
+
+    // upload the pixels of a file for every import policy it was loaded with and swap them in,
+    // returns how many textures were replaced
+    fn replace_texture(
+        &mut self,
+        device: &gfx::Device,
+        key: &AssetKey,
+        (width, height, pixels): (u32, u32, Vec<u8>),
+    ) -> Result<usize> {
+        let keys = self
+            .textures
+            .keys()
+            .filter(|(k, _)| k == key)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut count = 0;
+        for key in keys {
+            let (texture, view) = device.upload_texture(width, height, &pixels, key.1)?;
+            if let AssetKey::Path(path) = &key.0 {
+                device.set_name(texture.image, path);
+                device.set_name(view.view, path);
+            }
+            if let Some(old) = self.textures.replace(&key, TextureAsset { texture, view }) {
+                retire_texture(device, old);
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// retire the assets no handle refers to anymore, returns how many were released.
+    pub fn purge_unused(&mut self, device: &gfx::Device) -> usize {
+        let textures = self.textures.take_unused();
+        let meshes = self.meshes.take_unused();
+        let shaders = self.shaders.take_unused();
+        let count = textures.len() + meshes.len() + shaders.len();
+
+        for ((key, _), asset) in textures {
+            // the file stays watched while another import policy uses it
+            if !self.textures.keys().any(|(k, _)| *k == key) {
+                self.unwatch(&key);
+            }
+            retire_texture(device, asset);
+        }
+        for (key, mesh) in meshes {
+            self.unwatch(&key);
+            retire_mesh(device, mesh);
+        }
+        shaders.into_iter().for_each(|(_, s)| device.retire(s));
+
+        debug!(count, "purge unused assets");
+        count
+    }
+
+    /// destroy every asset right away, handles must not be used afterwards. the device
+    /// must be idle.
+    pub fn destroy(&mut self, device: &gfx::Device) {
+        unsafe {
+            let vk_device = device.device();
+            self.textures.clear(|a| {
+                a.view.destroy(vk_device);
+                a.texture.destroy(vk_device);
+            });
+            self.meshes.clear(|m| m.destroy(device));
+            self.shaders.clear(|s| s.destroy(vk_device));
+        }
+        self.watcher = None;
+    }
+
+    fn watch(&self, key: &AssetKey, kind: SourceKind) {
+        if let (Some(watcher), AssetKey::Path(path)) = (&self.watcher, key) {
+            watcher.watch(path, kind);
+        }
+    }
+
+    fn unwatch(&self, key: &AssetKey) {
+        if let (Some(watcher), AssetKey::Path(path)) = (&self.watcher, key) {
+            watcher.unwatch(path);
+        }
+    }
+}
+
+fn retire_texture(device: &gfx::Device, asset: TextureAsset) {
+    device.retire(asset.view);
+    device.retire(asset.texture);
+}
+
+fn retire_mesh(device: &gfx::Device, mesh: Mesh) {
+    device.retire(mesh.vertices);
+    device.retire(mesh.indices);
+}
+
+// the key of data described by `header` and `bytes`, e.g. the extent of pixels
+fn content_key<H: Copy>(header: &[H], bytes: &[u8]) -> AssetKey {
+    let mut hasher = DefaultHasher::new();
+    as_bytes(header).hash(&mut hasher);
+    bytes.hash(&mut hasher);
+    AssetKey::Content(hasher.finish())
+}
+
+fn as_bytes<T: Copy>(data: &[T]) -> &[u8] {
+    unsafe { slice::from_raw_parts(data.as_ptr().cast::<u8>(), size_of::<T>() * data.len()) }
+}
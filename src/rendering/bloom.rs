@@ -0,0 +1,457 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use std::any::Any;
+
+use anyhow::Result;
+use tracing::debug;
+use vulkanalia::prelude::v1_0::*;
+
+use super::{constant_bytes, create_fullscreen_pipeline, create_render_pass, PostPass};
+use crate::gfx;
+
+// The push constant bytes shared by the blur steps.
+const STEP_CONSTANTS_SIZE: usize = 16;
+
+// Linear filtering does part of the blur, clamping keeps the edges from bleeding.
+const BLOOM_SAMPLER: gfx::SamplerDescriptor = gfx::SamplerDescriptor {
+    mipmap_mode: vk::SamplerMipmapMode::NEAREST,
+    max_lod: 0.0,
+    ..gfx::SamplerDescriptor::linear(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+};
+
+/// The bloom settings, the threshold is in hdr scene units.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BloomSettings {
+    pub threshold: f32,
+    /// the width of the soft transition below the threshold.
+    pub knee: f32,
+    pub intensity: f32,
+    /// the spread of the upsample filter in texels.
+    pub radius: f32,
+    /// the number of half resolution levels the blur goes through.
+    pub levels: usize,
+}
+
+impl Default for BloomSettings {
+    fn default() -> Self {
+        Self {
+            threshold: 1.0,
+            knee: 0.5,
+            intensity: 0.6,
+            radius: 1.0,
+            levels: 6,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct DownsamplePushConstants {
+    threshold: f32,
+    knee: f32,
+    prefilter: u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct UpsamplePushConstants {
+    radius: f32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct BloomPushConstants {
+    intensity: f32,
+}
+
+/// One level of the blur chain, half the size of the level above.
+struct BloomLevel {
+    texture: gfx::Texture,
+    view: gfx::TextureView,
+    framebuffer: gfx::FrameBuffer,
+    extent: vk::Extent2D,
+}
+
+/// Adds a blurred copy of the bright parts of the hdr input, must run before tone mapping.
+///
+/// The input is thresholded into half resolution and progressively downsampled, the
+/// levels are then upsampled back while adding onto each other and the largest level is
+/// composited over the input.
+pub struct BloomPass {
+    pub settings: BloomSettings,
+    // the downsample pass discards, the upsample pass adds onto the existing level
+    down_render_pass: vk::RenderPass,
+    up_render_pass: vk::RenderPass,
+    sampler: vk::Sampler,
+    pipeline_layout: vk::PipelineLayout,
+    downsample_pipeline: vk::Pipeline,
+    upsample_pipeline: vk::Pipeline,
+    // one descriptor set per blur step and swapchain image
+    steps: Vec<gfx::DescriptorSets>,
+    levels: Vec<BloomLevel>,
+    extent: vk::Extent2D,
+}
+
+impl BloomPass {
+    pub fn create(device: &gfx::Device, settings: BloomSettings) -> Result<Self> {
+        unsafe {
+            let vk_device = device.device();
+            let settings = BloomSettings {
+                levels: settings.levels.max(1),
+                ..settings
+            };
+
+            // create render passes and the filtering sampler
+            let down_render_pass = create_render_pass(vk_device, false)?;
+            let up_render_pass = create_render_pass(vk_device, true)?;
+            let sampler = device.sampler(&BLOOM_SAMPLER)?;
+
+            // every level is downsampled into once and all but the last are upsampled into
+            let bindings = &[
+                binding(0, vk::DescriptorType::SAMPLED_IMAGE),
+                binding(1, vk::DescriptorType::SAMPLER),
+            ];
+            let steps = (0..settings.levels * 2 - 1)
+                .map(|_| {
+                    gfx::DescriptorSets::create(vk_device, gfx::DescriptorSlot::Pass, bindings)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            // all step layouts are identical and so compatible with one pipeline layout
+            let push_constant_range = vk::PushConstantRange::builder()
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                .offset(0)
+                .size(STEP_CONSTANTS_SIZE as u32);
+
+            let pipeline_layout =
+                device.create_pipeline_layout(&[&steps[0]], &[push_constant_range.build()])?;
+
+            // create pipelines
+            let downsample_pipeline = create_fullscreen_pipeline(
+                device,
+                pipeline_layout,
+                down_render_pass,
+                include_bytes!("../../shaders/bloom_downsample_frag.spv"),
+                false,
+            )?;
+            device.set_name(downsample_pipeline, "bloom downsample");
+            let upsample_pipeline = create_fullscreen_pipeline(
+                device,
+                pipeline_layout,
+                up_render_pass,
+                include_bytes!("../../shaders/bloom_upsample_frag.spv"),
+                true,
+            )?;
+            device.set_name(upsample_pipeline, "bloom upsample");
+
+            // create the levels
+            let extent = device.extent();
+            let levels = create_levels(device, down_render_pass, extent, settings.levels)?;
+
+            Ok(Self {
+                settings,
+                down_render_pass,
+                up_render_pass,
+                sampler,
+                pipeline_layout,
+                downsample_pipeline,
+                upsample_pipeline,
+                steps,
+                levels,
+                extent,
+            })
+        }
+    }
+
+    unsafe fn resize(&mut self, device: &gfx::Device) -> Result<()> {
+        // levels may still be read by pending frames
+        device.wait_idle()?;
+
+        destroy_levels(device, &self.levels);
+        self.extent = device.extent();
+        self.levels = create_levels(
+            device,
+            self.down_render_pass,
+            self.extent,
+            self.settings.levels,
+        )?;
+
+        Ok(())
+    }
+
+    unsafe fn step(
+        &mut self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        step: usize,
+        input: vk::ImageView,
+        target: usize,
+        upsample: bool,
+        constants: &[u8],
+    ) -> Result<()> {
+        let vk_device = device.device();
+
+        // point the set at the input of this step
+        let set = self.steps[step].get(vk_device, index)?;
+        let image_info = &[vk::DescriptorImageInfo::builder()
+            .image_view(input)
+            .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)];
+        let sampler_info = &[vk::DescriptorImageInfo::builder().sampler(self.sampler)];
+        let writes = &[
+            vk::WriteDescriptorSet::builder()
+                .dst_set(set)
+                .dst_binding(0)
+                .descriptor_type(vk::DescriptorType::SAMPLED_IMAGE)
+                .image_info(image_info),
+            vk::WriteDescriptorSet::builder()
+                .dst_set(set)
+                .dst_binding(1)
+                .descriptor_type(vk::DescriptorType::SAMPLER)
+                .image_info(sampler_info),
+        ];
+        vk_device.update_descriptor_sets(writes, &[] as &[vk::CopyDescriptorSet]);
+
+        let level = &self.levels[target];
+        let (render_pass, pipeline) = if upsample {
+            (self.up_render_pass, self.upsample_pipeline)
+        } else {
+            (self.down_render_pass, self.downsample_pipeline)
+        };
+
+        // begin the pass on the target level
+        let render_area = vk::Rect2D::builder()
+            .offset(vk::Offset2D::default())
+            .extent(level.extent);
+        let info = vk::RenderPassBeginInfo::builder()
+            .render_pass(render_pass)
+            .framebuffer(level.framebuffer.buffer)
+            .render_area(render_area);
+        vk_device.cmd_begin_render_pass(command_buffer, &info, vk::SubpassContents::INLINE);
+
+        // viewport and scissor are dynamic
+        let viewport = vk::Viewport::builder()
+            .x(0.0)
+            .y(0.0)
+            .width(level.extent.width as f32)
+            .height(level.extent.height as f32)
+            .min_depth(0.0)
+            .max_depth(1.0);
+        vk_device.cmd_set_viewport(command_buffer, 0, &[viewport]);
+        vk_device.cmd_set_scissor(command_buffer, 0, &[render_area]);
+
+        debug!(?pipeline, "bind pipeline");
+        vk_device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
+        debug!(first = gfx::PASS_SET, ?set, "bind descriptor set");
+        vk_device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            self.pipeline_layout,
+            gfx::PASS_SET,
+            &[set],
+            &[],
+        );
+        vk_device.cmd_push_constants(
+            command_buffer,
+            self.pipeline_layout,
+            vk::ShaderStageFlags::FRAGMENT,
+            0,
+            constants,
+        );
+        debug!(vertices = 3, instances = 1, "draw");
+        vk_device.cmd_draw(command_buffer, 3, 1, 0, 0);
+
+        vk_device.cmd_end_render_pass(command_buffer);
+
+        Ok(())
+    }
+}
+
+impl PostPass for BloomPass {
+    fn name(&self) -> &str {
+        "bloom"
+    }
+
+    fn shader(&self) -> &'static [u8] {
+        include_bytes!("../../shaders/bloom_frag.spv")
+    }
+
+    fn constants(&self) -> Vec<u8> {
+        constant_bytes(&BloomPushConstants {
+            intensity: self.settings.intensity,
+        })
+    }
+
+    fn auxiliary(&self) -> Option<vk::ImageView> {
+        // the largest level holds the summed blur
+        Some(self.levels[0].view.view)
+    }
+
+    fn prepare(
+        &mut self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        input: vk::ImageView,
+        delta: f32,
+    ) -> Result<()> {
+        unsafe {
+            // follow the swapchain size
+            if self.extent != device.extent() {
+                self.resize(device)?;
+            }
+
+            let count = self.levels.len();
+
+            // threshold into the first level, then halve down the chain
+            for level in 0..count {
+                let constants = pad(constant_bytes(&DownsamplePushConstants {
+                    threshold: self.settings.threshold,
+                    knee: self.settings.knee.max(0.0001),
+                    prefilter: (level == 0) as u32,
+                }));
+                let source = if level == 0 {
+                    input
+                } else {
+                    self.levels[level - 1].view.view
+                };
+
+                self.step(
+                    device,
+                    command_buffer,
+                    index,
+                    level,
+                    source,
+                    level,
+                    false,
+                    &constants,
+                )?;
+            }
+
+            // add every level onto the one above it, from the smallest up
+            let constants = pad(constant_bytes(&UpsamplePushConstants {
+                radius: self.settings.radius,
+            }));
+            for level in (1..count).rev() {
+                let source = self.levels[level].view.view;
+                let step = count + (count - 1 - level);
+
+                self.step(
+                    device,
+                    command_buffer,
+                    index,
+                    step,
+                    source,
+                    level - 1,
+                    true,
+                    &constants,
+                )?;
+            }
+
+            Ok(())
+        }
+    }
+
+    fn destroy(&self, device: &gfx::Device) {
+        unsafe {
+            let vk_device = device.device();
+
+            // destroy levels
+            destroy_levels(device, &self.levels);
+
+            // destroy pipelines
+            vk_device.destroy_pipeline(self.downsample_pipeline, None);
+            vk_device.destroy_pipeline(self.upsample_pipeline, None);
+            vk_device.destroy_pipeline_layout(self.pipeline_layout, None);
+
+            // destroy descriptors
+            self.steps.iter().for_each(|s| s.destroy(vk_device));
+
+            // destroy sampler and passes
+            vk_device.destroy_render_pass(self.down_render_pass, None);
+            vk_device.destroy_render_pass(self.up_render_pass, None);
+        }
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// extend the constants to the pushed range size.
+fn pad(mut constants: Vec<u8>) -> Vec<u8> {
+    constants.resize(STEP_CONSTANTS_SIZE, 0);
+    constants
+}
+
+fn binding(index: u32, type_: vk::DescriptorType) -> vk::DescriptorSetLayoutBinding {
+    vk::DescriptorSetLayoutBinding::builder()
+        .binding(index)
+        .descriptor_type(type_)
+        .descriptor_count(1)
+        .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+        .build()
+}
+
+unsafe fn create_levels(
+    device: &gfx::Device,
+    render_pass: vk::RenderPass,
+    extent: vk::Extent2D,
+    count: usize,
+) -> Result<Vec<BloomLevel>> {
+    (0..count)
+        .map(|level| {
+            // every level halves the size of the one above, starting at half the screen
+            let extent = vk::Extent2D {
+                width: (extent.width >> (level + 1)).max(1),
+                height: (extent.height >> (level + 1)).max(1),
+            };
+
+            let texture = device.create_texture(
+                extent.width,
+                extent.height,
+                1,
+                gfx::SCENE_FORMAT,
+                vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
+            )?;
+            let view = texture.create_view(
+                device.device(),
+                gfx::SCENE_FORMAT,
+                vk::ImageAspectFlags::COLOR,
+                1,
+            )?;
+
+            // both bloom passes only differ in load operations and so share framebuffers
+            let framebuffer = gfx::FrameBuffer::create(
+                device.device(),
+                &render_pass,
+                &[view],
+                extent.width,
+                extent.height,
+            )?;
+
+            Ok(BloomLevel {
+                texture,
+                view,
+                framebuffer,
+                extent,
+            })
+        })
+        .collect()
+}
+
+unsafe fn destroy_levels(device: &gfx::Device, levels: &[BloomLevel]) {
+    let vk_device = device.device();
+
+    levels.iter().for_each(|l| {
+        l.framebuffer.destroy(vk_device);
+        l.view.destroy(vk_device);
+        l.texture.destroy(vk_device);
+    });
+}
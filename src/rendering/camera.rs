@@ -0,0 +1,432 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use cgmath::{InnerSpace, Rotation, Rotation3, SquareMatrix, Zero};
+
+use super::{slerp, Transform};
+use crate::math::{Mat4, Quat, Vec3};
+
+/// A perspective camera looking down its local -z axis with y up.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Camera {
+    pub position: Vec3,
+    pub rotation: Quat,
+    /// the vertical field of view in radians.
+    pub fov_y: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl Camera {
+    pub fn new(position: Vec3, rotation: Quat) -> Self {
+        Self {
+            position,
+            rotation,
+            ..Default::default()
+        }
+    }
+
+    /// a camera at `position` looking at `target`.
+    pub fn looking_at(position: Vec3, target: Vec3, up: Vec3) -> Self {
+        Self::new(position, look_rotation(target - position, up))
+    }
+
+    /// the world to view matrix.
+    pub fn view(&self) -> Mat4 {
+        let world = Mat4::from_translation(self.position) * Mat4::from(self.rotation);
+        world.invert().unwrap_or(Mat4::identity())
+    }
+
+    /// the view to clip matrix for vulkan, y down and depth in 0..1.
+    pub fn proj(&self, aspect: f32) -> Mat4 {
+        #[rustfmt::skip]
+        let correction = Mat4::new(
+            1.0,  0.0,       0.0, 0.0,
+            0.0, -1.0,       0.0, 0.0,
+            0.0,  0.0, 1.0 / 2.0, 0.0,
+            0.0,  0.0, 1.0 / 2.0, 1.0,
+        );
+
+        correction * cgmath::perspective(cgmath::Rad(self.fov_y), aspect, self.near, self.far)
+    }
+
+    /// the view and projection for a target of `width` by `height` pixels.
+    pub fn matrices(&self, width: u32, height: u32) -> (Mat4, Mat4) {
+        (self.view(), self.proj(width as f32 / height.max(1) as f32))
+    }
+
+    /// the direction the camera looks in.
+    pub fn forward(&self) -> Vec3 {
+        self.rotation.rotate_vector(Vec3::new(0.0, 0.0, -1.0))
+    }
+
+    /// apply modifiers in order, each one sees the result of the previous ones.
+    pub fn modified(self, modifiers: &mut [&mut dyn CameraModifier], delta: f32) -> Self {
+        modifiers
+            .iter_mut()
+            .fold(self, |camera, modifier| modifier.apply(camera, delta))
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            position: Vec3::zero(),
+            rotation: Quat::new(1.0, 0.0, 0.0, 0.0),
+            fov_y: 45f32.to_radians(),
+            near: 0.1,
+            far: 100.0,
+        }
+    }
+}
+
+/// Changes a camera every frame before its matrices are taken, see `Camera::modified`.
+/// Modifiers keep their own state, the camera handed in is the one of this frame. steps
+/// below 0 are taken as 0, like `Clock` does.
+pub trait CameraModifier {
+    fn apply(&mut self, camera: Camera, delta: f32) -> Camera;
+}
+
+/// Follows a target transform with critically damped springs, the camera keeps `offset`
+/// in the space of the target and turns with it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Follow {
+    pub target: Transform,
+    pub offset: Vec3,
+    /// roughly the time in seconds to catch up with the target, 0 follows rigidly.
+    pub smooth_time: f32,
+    /// whether the camera takes on the rotation of the target.
+    pub rotate: bool,
+    position: Option<Vec3>,
+    velocity: Vec3,
+}
+
+impl Follow {
+    pub fn new(target: Transform, offset: Vec3, smooth_time: f32) -> Self {
+        Self {
+            target,
+            offset,
+            smooth_time,
+            rotate: false,
+            position: None,
+            velocity: Vec3::zero(),
+        }
+    }
+
+    /// jump to the target on the next frame.
+    pub fn reset(&mut self) {
+        self.position = None;
+        self.velocity = Vec3::zero();
+    }
+}
+
+impl CameraModifier for Follow {
+    fn apply(&mut self, camera: Camera, delta: f32) -> Camera {
+        let goal = self.target.translation + self.target.rotation.rotate_vector(self.offset);
+
+        // the first frame starts at the goal
+        let current = self.position.unwrap_or(goal);
+        let position = smooth_damp(current, goal, &mut self.velocity, self.smooth_time, delta);
+        self.position = Some(position);
+
+        let rotation = if self.rotate {
+            slerp(
+                camera.rotation,
+                self.target.rotation,
+                smooth_factor(self.smooth_time, delta),
+            )
+        } else {
+            camera.rotation
+        };
+
+        Camera {
+            position,
+            rotation,
+            ..camera
+        }
+    }
+}
+
+/// Turns the camera towards a point, smoothed like `Follow`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LookAt {
+    pub target: Vec3,
+    pub up: Vec3,
+    /// roughly the time in seconds to turn towards the target, 0 turns at once.
+    pub smooth_time: f32,
+    /// the largest angle in radians between the view direction and the horizon, up to
+    /// half pi. keeps the camera from flipping over when the target passes above or below.
+    pub max_pitch: f32,
+    rotation: Option<Quat>,
+}
+
+impl LookAt {
+    pub fn new(target: Vec3) -> Self {
+        Self {
+            target,
+            up: Vec3::unit_y(),
+            smooth_time: 0.0,
+            max_pitch: 85f32.to_radians(),
+            rotation: None,
+        }
+    }
+}
+
+impl CameraModifier for LookAt {
+    fn apply(&mut self, camera: Camera, delta: f32) -> Camera {
+        let direction = self.target - camera.position;
+        if direction.magnitude2() < f32::EPSILON {
+            return camera;
+        }
+
+        // limit the pitch against the up axis
+        let up = self.up.normalize();
+        let direction = direction.normalize();
+        let pitch = direction.dot(up).clamp(-1.0, 1.0).asin();
+        let limit = self.max_pitch.clamp(0.0, 89.9f32.to_radians());
+        let direction = if pitch.abs() > limit {
+            let flat = direction - up * direction.dot(up);
+            let flat = if flat.magnitude2() < f32::EPSILON {
+                camera.forward() - up * camera.forward().dot(up)
+            } else {
+                flat
+            };
+            flat.normalize() * limit.cos() + up * limit.sin() * pitch.signum()
+        } else {
+            direction
+        };
+
+        let goal = look_rotation(direction, up);
+        let current = self.rotation.unwrap_or(goal);
+        let rotation = slerp(current, goal, smooth_factor(self.smooth_time, delta));
+        self.rotation = Some(rotation);
+
+        Camera { rotation, ..camera }
+    }
+}
+
+/// Procedural shake driven by trauma, which is added by events and decays over time.
+/// The shake grows with the square of the trauma, so small hits stay subtle.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Shake {
+    /// 0..1, see `add_trauma`.
+    pub trauma: f32,
+    /// the trauma lost per second.
+    pub decay: f32,
+    /// the offset in world units at full trauma.
+    pub max_offset: f32,
+    /// the yaw, pitch and roll in radians at full trauma.
+    pub max_angle: f32,
+    /// how fast the shake changes direction.
+    pub frequency: f32,
+    time: f32,
+}
+
+impl Shake {
+    pub fn new(max_offset: f32, max_angle: f32) -> Self {
+        Self {
+            trauma: 0.0,
+            decay: 1.0,
+            max_offset,
+            max_angle,
+            frequency: 15.0,
+            time: 0.0,
+        }
+    }
+
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+}
+
+impl CameraModifier for Shake {
+    fn apply(&mut self, camera: Camera, delta: f32) -> Camera {
+        let delta = delta.max(0.0);
+        self.time += delta;
+        let shake = self.trauma * self.trauma;
+        self.trauma = (self.trauma - self.decay * delta).max(0.0);
+
+        if shake <= 0.0 {
+            return camera;
+        }
+
+        // every channel reads its own stretch of the same noise
+        let t = self.time * self.frequency;
+        let offset = Vec3::new(noise(t, 0.0), noise(t, 1.0), noise(t, 2.0)) * self.max_offset;
+        let yaw = noise(t, 3.0) * self.max_angle;
+        let pitch = noise(t, 4.0) * self.max_angle;
+        let roll = noise(t, 5.0) * self.max_angle;
+
+        // offsets and angles are in view space
+        let rotation = camera.rotation
+            * Quat::from_angle_y(cgmath::Rad(yaw * shake))
+            * Quat::from_angle_x(cgmath::Rad(pitch * shake))
+            * Quat::from_angle_z(cgmath::Rad(roll * shake));
+
+        Camera {
+            position: camera.position + camera.rotation.rotate_vector(offset * shake),
+            rotation,
+            ..camera
+        }
+    }
+}
+
+/// move towards `target` like a critically damped spring, `velocity` is kept between calls.
+/// `smooth_time` is roughly the time to reach the target, 0 jumps to it at once. a step
+/// below 0 stays where it is.
+pub fn smooth_damp(
+    current: Vec3,
+    target: Vec3,
+    velocity: &mut Vec3,
+    smooth_time: f32,
+    delta: f32,
+) -> Vec3 {
+    if smooth_time <= 0.0 {
+        *velocity = Vec3::zero();
+        return target;
+    }
+
+    let delta = delta.max(0.0);
+    // a pade approximation of the exponential keeps this stable for large steps
+    let omega = 2.0 / smooth_time;
+    let x = omega * delta;
+    let exp = 1.0 / (1.0 + x + 0.48 * x * x + 0.235 * x * x * x);
+    let change = current - target;
+    let temp = (*velocity + change * omega) * delta;
+    *velocity = (*velocity - temp * omega) * exp;
+    target + (change + temp) * exp
+}
+
+/// the rotation looking down `direction`, with y as close to `up` as possible.
+pub fn look_rotation(direction: Vec3, up: Vec3) -> Quat {
+    // cgmath looks down +z, cameras down -z
+    Quat::look_at(-direction, up).invert()
+}
+
+// the fraction of the remaining distance to cover this frame, frame rate independent.
+fn smooth_factor(smooth_time: f32, delta: f32) -> f32 {
+    if smooth_time <= 0.0 {
+        1.0
+    } else {
+        1.0 - (-2.0 * delta.max(0.0) / smooth_time).exp()
+    }
+}
+
+// smooth noise in -1..1, a sum of sines with unrelated frequencies per channel.
+fn noise(t: f32, channel: f32) -> f32 {
+    let phase = channel * 12.9898;
+    ((t * 1.0 + phase).sin() * 0.5
+        + (t * 2.31 + phase * 1.7).sin() * 0.3
+        + (t * 4.73 + phase * 2.3).sin() * 0.2)
+        .clamp(-1.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn near(a: Vec3, b: Vec3) -> bool {
+        (a - b).magnitude() < 1e-4
+    }
+
+    #[test]
+    fn cameras_look_where_they_are_pointed() {
+        let camera = Camera::looking_at(Vec3::new(0.0, 0.0, 5.0), Vec3::zero(), Vec3::unit_y());
+        assert!(near(camera.forward(), Vec3::new(0.0, 0.0, -1.0)));
+        let origin = camera.view() * Vec3::zero().extend(1.0);
+        assert!(near(origin.truncate(), Vec3::new(0.0, 0.0, -5.0)));
+
+        // a zero height doesn't divide by zero
+        let (_, proj) = camera.matrices(16, 0);
+        assert!(proj.x.x.is_finite());
+    }
+
+    #[test]
+    fn smooth_damp_handles_zero_and_negative_steps() {
+        let target = Vec3::new(10.0, 0.0, 0.0);
+        let mut velocity = Vec3::zero();
+        assert_eq!(
+            smooth_damp(Vec3::zero(), target, &mut velocity, 0.0, 0.1),
+            target
+        );
+
+        for delta in [0.0, -1.0] {
+            let mut velocity = Vec3::zero();
+            let position = smooth_damp(Vec3::zero(), target, &mut velocity, 0.5, delta);
+            assert!(
+                near(position, Vec3::zero()),
+                "{:?} after {}",
+                position,
+                delta
+            );
+            assert!(near(velocity, Vec3::zero()));
+        }
+
+        // it gets there without overshooting
+        let mut position = Vec3::zero();
+        for _ in 0..300 {
+            position = smooth_damp(position, target, &mut velocity, 0.5, 1.0 / 60.0);
+            assert!(position.x <= target.x + 1e-4);
+        }
+        assert!(near(position, target));
+    }
+
+    #[test]
+    fn follow_starts_at_its_goal() {
+        let target = Transform {
+            translation: Vec3::new(1.0, 2.0, 3.0),
+            ..Transform::default()
+        };
+        let mut follow = Follow::new(target, Vec3::new(0.0, 0.0, 4.0), 0.5);
+        let camera = Camera::default().modified(&mut [&mut follow], 0.0);
+        assert!(near(camera.position, Vec3::new(1.0, 2.0, 7.0)));
+
+        // a step back in time doesn't move it
+        follow.target.translation = Vec3::new(5.0, 2.0, 3.0);
+        let camera = camera.modified(&mut [&mut follow], -0.5);
+        assert!(near(camera.position, Vec3::new(1.0, 2.0, 7.0)));
+    }
+
+    #[test]
+    fn look_at_limits_the_pitch() {
+        let mut look = LookAt::new(Vec3::new(0.0, 10.0, 0.0));
+        look.max_pitch = 45f32.to_radians();
+        let camera = Camera::default().modified(&mut [&mut look], 0.0);
+        let pitch = camera.forward().dot(Vec3::unit_y()).asin();
+        assert!((pitch - 45f32.to_radians()).abs() < 1e-3, "{}", pitch);
+
+        // a target on the camera leaves it alone
+        let mut look = LookAt::new(Vec3::zero());
+        assert_eq!(
+            Camera::default().modified(&mut [&mut look], 0.1),
+            Camera::default()
+        );
+    }
+
+    #[test]
+    fn shake_decays_with_time() {
+        let mut shake = Shake::new(1.0, 0.1);
+        assert_eq!(
+            Camera::default().modified(&mut [&mut shake], 0.1),
+            Camera::default()
+        );
+
+        shake.add_trauma(2.0);
+        assert_eq!(shake.trauma, 1.0);
+        let shaken = Camera::default().modified(&mut [&mut shake], 0.1);
+        assert_ne!(shaken, Camera::default());
+        assert!((shake.trauma - 0.9).abs() < 1e-6);
+
+        // negative steps don't add trauma back, zero steps don't take any
+        Camera::default().modified(&mut [&mut shake], -1.0);
+        Camera::default().modified(&mut [&mut shake], 0.0);
+        assert!((shake.trauma - 0.9).abs() < 1e-6);
+        Camera::default().modified(&mut [&mut shake], 5.0);
+        assert_eq!(shake.trauma, 0.0);
+    }
+}
@@ -0,0 +1,189 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use std::time::Instant;
+
+/// Which time an animation follows, see `Clock`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TimeChannel {
+    /// scaled and paused with the game.
+    #[default]
+    Game,
+    /// real time, keeps menus and overlays responsive while the game is paused.
+    Ui,
+}
+
+/// The step and total of one channel, in seconds.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Time {
+    pub delta: f32,
+    pub elapsed: f32,
+}
+
+impl Time {
+    fn advance(self, delta: f32) -> Self {
+        Self {
+            delta,
+            elapsed: self.elapsed + delta,
+        }
+    }
+}
+
+/// Frame times for the game, scaled for slow motion and stopped while paused, and for the
+/// ui, which always runs in real time. Ticked once per frame, animations read the channel
+/// they follow.
+#[derive(Copy, Clone, Debug)]
+pub struct Clock {
+    /// how fast game time runs, 1 is real time and 0 stands still without pausing.
+    pub scale: f32,
+    /// the longest step in seconds, longer frames like hitches or breakpoints are cut short
+    /// so nothing jumps when the game continues.
+    pub max_delta: f32,
+    paused: bool,
+    last: Option<Instant>,
+    game: Time,
+    ui: Time,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Self {
+            scale: 1.0,
+            max_delta: 0.25,
+            paused: false,
+            last: None,
+            game: Time::default(),
+            ui: Time::default(),
+        }
+    }
+
+    /// advance by the real time since the previous tick, the first tick steps by 0.
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        let delta = self
+            .last
+            .map(|last| now.duration_since(last).as_secs_f32())
+            .unwrap_or(0.0);
+        self.last = Some(now);
+        self.advance(delta);
+    }
+
+    /// advance by `delta` seconds of real time.
+    pub fn advance(&mut self, delta: f32) {
+        let delta = delta.clamp(0.0, self.max_delta.max(0.0));
+        let scaled = if self.paused {
+            0.0
+        } else {
+            delta * self.scale.max(0.0)
+        };
+
+        self.ui = self.ui.advance(delta);
+        self.game = self.game.advance(scaled);
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// the time of a channel as of the last tick.
+    pub fn time(&self, channel: TimeChannel) -> Time {
+        match channel {
+            TimeChannel::Game => self.game,
+            TimeChannel::Ui => self.ui,
+        }
+    }
+
+    /// the step of a channel in the last tick.
+    pub fn delta(&self, channel: TimeChannel) -> f32 {
+        self.time(channel).delta
+    }
+
+    /// the total of a channel, game time does not count pauses.
+    pub fn elapsed(&self, channel: TimeChannel) -> f32 {
+        self.time(channel).elapsed
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_and_negative_steps_stand_still() {
+        let mut clock = Clock::new();
+        clock.advance(0.1);
+        clock.advance(0.0);
+        assert_eq!(clock.delta(TimeChannel::Game), 0.0);
+        clock.advance(-1.0);
+        assert_eq!(
+            clock.time(TimeChannel::Ui),
+            Time {
+                delta: 0.0,
+                elapsed: 0.1
+            }
+        );
+        assert_eq!(clock.elapsed(TimeChannel::Game), 0.1);
+
+        // the first tick has no previous one to measure from
+        let mut clock = Clock::new();
+        clock.tick();
+        assert_eq!(clock.delta(TimeChannel::Ui), 0.0);
+    }
+
+    #[test]
+    fn steps_are_clamped_and_scaled() {
+        let mut clock = Clock::new();
+        clock.advance(10.0);
+        assert_eq!(clock.delta(TimeChannel::Ui), 0.25);
+
+        clock.scale = 0.5;
+        clock.advance(0.2);
+        assert_eq!(clock.delta(TimeChannel::Game), 0.1);
+        assert_eq!(clock.delta(TimeChannel::Ui), 0.2);
+
+        // negative scales and limits stop the clock instead of running it backwards
+        clock.scale = -1.0;
+        clock.advance(0.2);
+        assert_eq!(clock.delta(TimeChannel::Game), 0.0);
+        clock.max_delta = -1.0;
+        clock.advance(0.2);
+        assert_eq!(clock.delta(TimeChannel::Ui), 0.0);
+    }
+
+    #[test]
+    fn pauses_stop_game_time_only() {
+        let mut clock = Clock::new();
+        clock.pause();
+        clock.advance(0.1);
+        assert!(clock.is_paused());
+        assert_eq!(clock.elapsed(TimeChannel::Game), 0.0);
+        assert_eq!(clock.elapsed(TimeChannel::Ui), 0.1);
+
+        clock.toggle_pause();
+        clock.advance(0.1);
+        assert_eq!(clock.elapsed(TimeChannel::Game), 0.1);
+    }
+}
@@ -0,0 +1,389 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use cgmath::InnerSpace;
+
+use crate::gfx;
+use crate::math::{Vec2, Vec3};
+
+/// subdivision stops at this depth even if the tolerance is not met.
+const MAX_DEPTH: u32 = 16;
+
+/// How the control points of a curve are interpreted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CurveKind {
+    /// piecewise cubic bezier, 3n + 1 points (3n when closed) where every third point is on the curve.
+    Bezier,
+    /// uniform catmull-rom spline passing through every point.
+    CatmullRom,
+    /// uniform cubic b-spline, smooth but does not pass through the points.
+    BSpline,
+}
+
+/// A piecewise cubic curve, every segment is evaluated as a cubic bezier.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Curve {
+    pub kind: CurveKind,
+    pub points: Vec<Vec3>,
+    pub closed: bool,
+}
+
+impl Curve {
+    pub fn new(kind: CurveKind, points: Vec<Vec3>, closed: bool) -> Self {
+        Self {
+            kind,
+            points,
+            closed,
+        }
+    }
+
+    pub fn bezier(points: Vec<Vec3>) -> Self {
+        Self::new(CurveKind::Bezier, points, false)
+    }
+
+    pub fn catmull_rom(points: Vec<Vec3>) -> Self {
+        Self::new(CurveKind::CatmullRom, points, false)
+    }
+
+    pub fn bspline(points: Vec<Vec3>) -> Self {
+        Self::new(CurveKind::BSpline, points, false)
+    }
+
+    /// the number of cubic segments of the curve.
+    pub fn segments(&self) -> usize {
+        let n = self.points.len();
+        match (self.kind, self.closed) {
+            (CurveKind::Bezier, false) if n >= 4 => (n - 1) / 3,
+            (CurveKind::Bezier, true) if n >= 3 => n / 3,
+            (CurveKind::CatmullRom, false) if n >= 2 => n - 1,
+            (CurveKind::CatmullRom, true) if n >= 3 => n,
+            (CurveKind::BSpline, false) if n >= 4 => n - 3,
+            (CurveKind::BSpline, true) if n >= 3 => n,
+            _ => 0,
+        }
+    }
+
+    /// the bezier control points of a segment.
+    pub fn segment(&self, index: usize) -> [Vec3; 4] {
+        let n = self.points.len();
+        let point = |i: isize| -> Vec3 {
+            if self.closed {
+                self.points[i.rem_euclid(n as isize) as usize]
+            } else {
+                // open curves repeat their end points
+                self.points[i.clamp(0, n as isize - 1) as usize]
+            }
+        };
+
+        let i = index as isize;
+        match self.kind {
+            CurveKind::Bezier => [
+                point(3 * i),
+                point(3 * i + 1),
+                point(3 * i + 2),
+                point(3 * i + 3),
+            ],
+            CurveKind::CatmullRom => {
+                let (p0, p1, p2, p3) = (point(i - 1), point(i), point(i + 1), point(i + 2));
+                [p1, p1 + (p2 - p0) / 6.0, p2 - (p3 - p1) / 6.0, p2]
+            }
+            CurveKind::BSpline => {
+                // open splines start at the first full span
+                let i = if self.closed { i - 1 } else { i };
+                let (p0, p1, p2, p3) = (point(i), point(i + 1), point(i + 2), point(i + 3));
+                [
+                    (p0 + p1 * 4.0 + p2) / 6.0,
+                    (p1 * 2.0 + p2) / 3.0,
+                    (p1 + p2 * 2.0) / 3.0,
+                    (p1 + p2 * 4.0 + p3) / 6.0,
+                ]
+            }
+        }
+    }
+
+    /// evaluate the curve, t runs from 0 to 1 over all segments.
+    pub fn evaluate(&self, t: f32) -> Vec3 {
+        let (index, t) = self.locate(t);
+        match index {
+            Some(index) => bezier(&self.segment(index), t),
+            None => self
+                .points
+                .first()
+                .copied()
+                .unwrap_or(Vec3::new(0.0, 0.0, 0.0)),
+        }
+    }
+
+    /// the unnormalized derivative of the curve at t.
+    pub fn tangent(&self, t: f32) -> Vec3 {
+        let (index, t) = self.locate(t);
+        match index {
+            Some(index) => bezier_tangent(&self.segment(index), t),
+            None => Vec3::new(0.0, 0.0, 0.0),
+        }
+    }
+
+    /// flatten into a polyline, no point deviates more than tolerance from the curve.
+    /// closed curves do not repeat the first point, draw them as a ring.
+    pub fn tessellate(&self, tolerance: f32) -> Vec<Vec3> {
+        let segments = self.segments();
+        if segments == 0 {
+            return self.points.clone();
+        }
+
+        // guard against a zero tolerance subdividing to the depth limit everywhere
+        let tolerance = tolerance.max(1e-6);
+
+        let mut points = vec![self.segment(0)[0]];
+        for index in 0..segments {
+            subdivide(&self.segment(index), tolerance, 0, &mut points);
+        }
+
+        // the end of a closed curve is its start
+        if self.closed && points.len() > 1 {
+            points.pop();
+        }
+
+        points
+    }
+
+    /// tessellate into a flat ribbon facing normal, u runs along the curve and v across.
+    pub fn ribbon(
+        &self,
+        tolerance: f32,
+        width: f32,
+        normal: Vec3,
+        color: Vec3,
+    ) -> (Vec<gfx::Vertex>, Vec<u32>) {
+        let mut points = self.tessellate(tolerance);
+        if self.closed && points.len() > 2 {
+            points.push(points[0]);
+        }
+        if points.len() < 2 {
+            return (vec![], vec![]);
+        }
+
+        // arc length at every point, used for the texture coordinate
+        let mut lengths = vec![0.0];
+        for pair in points.windows(2) {
+            lengths.push(lengths[lengths.len() - 1] + (pair[1] - pair[0]).magnitude());
+        }
+        let total = lengths[lengths.len() - 1].max(f32::EPSILON);
+
+        let count = points.len();
+        let mut vertices = Vec::with_capacity(count * 2);
+        for i in 0..count {
+            // central difference, wrapping around on closed curves
+            let (before, after) = if self.closed {
+                (
+                    points[(i + count - 2) % (count - 1)],
+                    points[(i + 1) % (count - 1)],
+                )
+            } else {
+                (points[i.saturating_sub(1)], points[(i + 1).min(count - 1)])
+            };
+
+            let side = side_vector(after - before, normal) * (width * 0.5);
+            let u = lengths[i] / total;
+
+            vertices.push(gfx::Vertex::new(points[i] - side, Vec2::new(u, 0.0), color));
+            vertices.push(gfx::Vertex::new(points[i] + side, Vec2::new(u, 1.0), color));
+        }
+
+        // two counter clockwise triangles per step, seen from the normal side
+        let mut indices = Vec::with_capacity((count - 1) * 6);
+        for i in 0..count as u32 - 1 {
+            let a = i * 2;
+            indices.extend_from_slice(&[a, a + 1, a + 2, a + 1, a + 3, a + 2]);
+        }
+
+        (vertices, indices)
+    }
+
+    /// split t into a segment index and the local t within it.
+    fn locate(&self, t: f32) -> (Option<usize>, f32) {
+        let segments = self.segments();
+        if segments == 0 {
+            return (None, 0.0);
+        }
+
+        let scaled = t.clamp(0.0, 1.0) * segments as f32;
+        let index = (scaled as usize).min(segments - 1);
+
+        (Some(index), scaled - index as f32)
+    }
+}
+
+/// evaluate a cubic bezier.
+pub fn bezier(points: &[Vec3; 4], t: f32) -> Vec3 {
+    let s = 1.0 - t;
+    points[0] * (s * s * s)
+        + points[1] * (3.0 * s * s * t)
+        + points[2] * (3.0 * s * t * t)
+        + points[3] * (t * t * t)
+}
+
+/// the derivative of a cubic bezier.
+pub fn bezier_tangent(points: &[Vec3; 4], t: f32) -> Vec3 {
+    let s = 1.0 - t;
+    (points[1] - points[0]) * (3.0 * s * s)
+        + (points[2] - points[1]) * (6.0 * s * t)
+        + (points[3] - points[2]) * (3.0 * t * t)
+}
+
+/// adaptive de casteljau subdivision, pushes every point after the first.
+fn subdivide(points: &[Vec3; 4], tolerance: f32, depth: u32, out: &mut Vec<Vec3>) {
+    if depth >= MAX_DEPTH || is_flat(points, tolerance) {
+        out.push(points[3]);
+        return;
+    }
+
+    // split at the half
+    let ab = (points[0] + points[1]) * 0.5;
+    let bc = (points[1] + points[2]) * 0.5;
+    let cd = (points[2] + points[3]) * 0.5;
+    let abc = (ab + bc) * 0.5;
+    let bcd = (bc + cd) * 0.5;
+    let mid = (abc + bcd) * 0.5;
+
+    subdivide(&[points[0], ab, abc, mid], tolerance, depth + 1, out);
+    subdivide(&[mid, bcd, cd, points[3]], tolerance, depth + 1, out);
+}
+
+/// a segment is flat when both inner control points are within tolerance of the chord.
+fn is_flat(points: &[Vec3; 4], tolerance: f32) -> bool {
+    let chord = points[3] - points[0];
+    let length = chord.magnitude2();
+
+    let distance = |p: Vec3| -> f32 {
+        let offset = p - points[0];
+        if length <= f32::EPSILON {
+            return offset.magnitude();
+        }
+        let along = offset.dot(chord) / length;
+        (offset - chord * along.clamp(0.0, 1.0)).magnitude()
+    };
+
+    distance(points[1]) <= tolerance && distance(points[2]) <= tolerance
+}
+
+/// the direction across a ribbon, falls back to any perpendicular when the tangent is along the normal.
+fn side_vector(tangent: Vec3, normal: Vec3) -> Vec3 {
+    let side = tangent.cross(normal);
+    if side.magnitude2() > f32::EPSILON {
+        return side.normalize();
+    }
+
+    let fallback = if normal.x.abs() < 0.9 {
+        Vec3::unit_x()
+    } else {
+        Vec3::unit_y()
+    };
+    let side = fallback.cross(normal);
+    if side.magnitude2() > f32::EPSILON {
+        side.normalize()
+    } else {
+        Vec3::unit_x()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line() -> Curve {
+        Curve::bezier(vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(3.0, 0.0, 0.0),
+        ])
+    }
+
+    #[test]
+    fn empty_curves_have_no_segments() {
+        let empty = Curve::catmull_rom(vec![]);
+        assert_eq!(empty.segments(), 0);
+        assert_eq!(empty.evaluate(0.5), Vec3::new(0.0, 0.0, 0.0));
+        assert_eq!(empty.tangent(0.5), Vec3::new(0.0, 0.0, 0.0));
+        assert!(empty.tessellate(0.01).is_empty());
+        assert_eq!(
+            empty
+                .ribbon(0.01, 1.0, Vec3::unit_y(), Vec3::unit_x())
+                .0
+                .len(),
+            0
+        );
+
+        // too few points to make a segment, it stays at its first point
+        let point = Curve::bezier(vec![Vec3::new(1.0, 2.0, 3.0), Vec3::new(4.0, 5.0, 6.0)]);
+        assert_eq!(point.segments(), 0);
+        assert_eq!(point.evaluate(1.0), Vec3::new(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn evaluation_is_clamped_to_the_ends() {
+        let curve = line();
+        assert_eq!(curve.evaluate(0.0), Vec3::new(0.0, 0.0, 0.0));
+        assert_eq!(curve.evaluate(1.0), Vec3::new(3.0, 0.0, 0.0));
+        assert_eq!(curve.evaluate(-1.0), curve.evaluate(0.0));
+        assert_eq!(curve.evaluate(2.0), curve.evaluate(1.0));
+        assert!((curve.evaluate(0.5) - Vec3::new(1.5, 0.0, 0.0)).magnitude() < 1e-6);
+        assert!((curve.tangent(0.5) - Vec3::new(3.0, 0.0, 0.0)).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_its_points() {
+        let points = vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+        ];
+        let curve = Curve::catmull_rom(points.clone());
+        assert_eq!(curve.segments(), 2);
+        for (i, point) in points.iter().enumerate() {
+            let at = curve.evaluate(i as f32 / 2.0);
+            assert!((at - point).magnitude() < 1e-6, "{:?} != {:?}", at, point);
+        }
+
+        // closed curves wrap around and leave out the repeated start
+        let closed = Curve::new(CurveKind::CatmullRom, points, true);
+        assert_eq!(closed.segments(), 3);
+        let ring = closed.tessellate(0.01);
+        assert_eq!(ring[0], Vec3::new(0.0, 0.0, 0.0));
+        assert_ne!(ring[ring.len() - 1], ring[0]);
+    }
+
+    #[test]
+    fn tessellation_stays_within_the_tolerance() {
+        // a straight segment needs no subdivision
+        assert_eq!(line().tessellate(0.01).len(), 2);
+
+        let arc = Curve::bezier(vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+        ]);
+        let coarse = arc.tessellate(0.1);
+        let fine = arc.tessellate(0.001);
+        assert!(fine.len() > coarse.len());
+        for i in 0..=32 {
+            // every point of the curve is near the polyline
+            let at = arc.evaluate(i as f32 / 32.0);
+            let nearest = fine
+                .iter()
+                .map(|p| (p - at).magnitude())
+                .fold(f32::MAX, f32::min);
+            assert!(nearest < 0.05, "{:?} is {} away", at, nearest);
+        }
+
+        // a zero tolerance is bounded by the depth limit
+        assert!(arc.tessellate(0.0).len() <= (1 << MAX_DEPTH) + 1);
+    }
+}
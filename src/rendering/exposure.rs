@@ -0,0 +1,336 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use std::mem::size_of;
+
+use anyhow::Result;
+use tracing::debug;
+use vulkanalia::prelude::v1_0::*;
+
+use crate::gfx;
+
+// The number of bins, must match the exposure shaders.
+const BINS: usize = 256;
+// The number of workgroups building partial histograms.
+const GROUPS: u32 = 64;
+
+/// How the exposure follows the scene, luminance limits are in ev (log2 of luminance).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ExposureSettings {
+    pub min_ev: f32,
+    pub max_ev: f32,
+    /// how fast the exposure adapts, higher is faster.
+    pub speed: f32,
+    /// ev offset applied to the computed exposure.
+    pub compensation: f32,
+}
+
+impl Default for ExposureSettings {
+    fn default() -> Self {
+        Self {
+            min_ev: -8.0,
+            max_ev: 4.0,
+            speed: 1.5,
+            compensation: 0.0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct ExposurePushConstants {
+    min_log_luminance: f32,
+    log_luminance_range: f32,
+    adaptation: f32,
+    compensation: f32,
+    groups: u32,
+}
+
+/// The adapted luminance and the resulting exposure, must match the exposure shaders.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct ExposureState {
+    luminance: f32,
+    exposure: f32,
+}
+
+crate::pod!(ExposureState {
+    luminance: f32,
+    exposure: f32,
+});
+
+/// Computes the scene exposure from a luminance histogram of the hdr scene target.
+pub struct AutoExposure {
+    pub settings: ExposureSettings,
+    descriptors: gfx::DescriptorSets,
+    pipeline_layout: vk::PipelineLayout,
+    histogram_pipeline: vk::Pipeline,
+    average_pipeline: vk::Pipeline,
+    histogram: gfx::Buffer,
+    state: gfx::Buffer,
+}
+
+impl AutoExposure {
+    pub fn create(device: &gfx::Device, settings: ExposureSettings) -> Result<Self> {
+        unsafe {
+            let vk_device = device.device();
+
+            // scene, partial histograms and state
+            let bindings = &[
+                binding(0, vk::DescriptorType::SAMPLED_IMAGE),
+                binding(1, vk::DescriptorType::STORAGE_BUFFER),
+                binding(2, vk::DescriptorType::STORAGE_BUFFER),
+            ];
+            let descriptors =
+                gfx::DescriptorSets::create(vk_device, gfx::DescriptorSlot::Pass, bindings)?;
+
+            // create pipeline objects
+            let pipeline_layout = create_pipeline_layout(device, &descriptors)?;
+            let histogram_pipeline = create_pipeline(
+                device,
+                pipeline_layout,
+                include_bytes!("../../shaders/exposure_histogram_comp.spv"),
+            )?;
+            device.set_name(histogram_pipeline, "exposure histogram");
+            let average_pipeline = create_pipeline(
+                device,
+                pipeline_layout,
+                include_bytes!("../../shaders/exposure_average_comp.spv"),
+            )?;
+            device.set_name(average_pipeline, "exposure average");
+
+            // partial histograms, fully rewritten every frame
+            let histogram = device.create_buffer(
+                (size_of::<u32>() * BINS * GROUPS as usize) as vk::DeviceSize,
+                vk::BufferUsageFlags::STORAGE_BUFFER,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            )?;
+
+            // state starts at middle grey
+            let state = device.create_buffer(
+                size_of::<ExposureState>() as vk::DeviceSize,
+                vk::BufferUsageFlags::STORAGE_BUFFER,
+                vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+            )?;
+            state.write(
+                vk_device,
+                0,
+                &[ExposureState {
+                    luminance: 0.18,
+                    exposure: 1.0,
+                }],
+            )?;
+
+            Ok(Self {
+                settings,
+                descriptors,
+                pipeline_layout,
+                histogram_pipeline,
+                average_pipeline,
+                histogram,
+                state,
+            })
+        }
+    }
+
+    /// the buffer holding the current exposure, read by the tone mapping pass.
+    pub fn state(&self) -> &gfx::Buffer {
+        &self.state
+    }
+
+    /// record the histogram of input and the adaptation, must be called outside any render pass.
+    pub fn record(
+        &mut self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        input: vk::ImageView,
+        delta: f32,
+    ) -> Result<()> {
+        unsafe {
+            let vk_device = device.device();
+
+            // point the set of this image at the current input
+            let set = self.descriptors.get(vk_device, index)?;
+            self.write_set(device, set, input)?;
+
+            // the previous frame may still read the state when tone mapping
+            gfx::buffer_barrier(
+                vk_device,
+                command_buffer,
+                &[self.histogram.buffer, self.state.buffer],
+                &[
+                    gfx::BufferAccess::FragmentShaderRead,
+                    gfx::BufferAccess::ComputeRead,
+                    gfx::BufferAccess::ComputeWrite,
+                ],
+                &[
+                    gfx::BufferAccess::ComputeRead,
+                    gfx::BufferAccess::ComputeWrite,
+                ],
+            );
+
+            let min_log_luminance = self.settings.min_ev;
+            let constants = ExposurePushConstants {
+                min_log_luminance,
+                log_luminance_range: (self.settings.max_ev - min_log_luminance).max(f32::EPSILON),
+                adaptation: 1.0 - (-delta.max(0.0) * self.settings.speed).exp(),
+                compensation: self.settings.compensation,
+                groups: GROUPS,
+            };
+
+            let bytes = std::slice::from_raw_parts(
+                &constants as *const ExposurePushConstants as *const u8,
+                size_of::<ExposurePushConstants>(),
+            );
+
+            self.descriptors.bind(
+                vk_device,
+                command_buffer,
+                vk::PipelineBindPoint::COMPUTE,
+                self.pipeline_layout,
+                set,
+            );
+            vk_device.cmd_push_constants(
+                command_buffer,
+                self.pipeline_layout,
+                vk::ShaderStageFlags::COMPUTE,
+                0,
+                bytes,
+            );
+
+            // build the partial histograms
+            debug!(pipeline = ?self.histogram_pipeline, "bind pipeline");
+            vk_device.cmd_bind_pipeline(
+                command_buffer,
+                vk::PipelineBindPoint::COMPUTE,
+                self.histogram_pipeline,
+            );
+            debug!(x = GROUPS, y = 1, z = 1, "dispatch");
+            vk_device.cmd_dispatch(command_buffer, GROUPS, 1, 1);
+
+            self.histogram.barrier(
+                vk_device,
+                command_buffer,
+                &[gfx::BufferAccess::ComputeWrite],
+                &[gfx::BufferAccess::ComputeRead],
+            );
+
+            // merge and adapt
+            debug!(pipeline = ?self.average_pipeline, "bind pipeline");
+            vk_device.cmd_bind_pipeline(
+                command_buffer,
+                vk::PipelineBindPoint::COMPUTE,
+                self.average_pipeline,
+            );
+            debug!(x = 1, y = 1, z = 1, "dispatch");
+            vk_device.cmd_dispatch(command_buffer, 1, 1, 1);
+
+            // the exposure is read when tone mapping
+            self.state.barrier(
+                vk_device,
+                command_buffer,
+                &[gfx::BufferAccess::ComputeWrite],
+                &[gfx::BufferAccess::FragmentShaderRead],
+            );
+
+            Ok(())
+        }
+    }
+
+    unsafe fn write_set(
+        &self,
+        device: &gfx::Device,
+        set: vk::DescriptorSet,
+        input: vk::ImageView,
+    ) -> Result<()> {
+        self.descriptors.write(
+            device.device(),
+            set,
+            &[
+                (0, gfx::BindingResource::Texture(input)),
+                (1, gfx::BindingResource::buffer(&self.histogram)),
+                (2, gfx::BindingResource::buffer(&self.state)),
+            ],
+        )
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe {
+            let vk_device = device.device();
+
+            // destroy buffers
+            self.histogram.destroy(vk_device);
+            self.state.destroy(vk_device);
+
+            // destroy pipelines
+            vk_device.destroy_pipeline(self.histogram_pipeline, None);
+            vk_device.destroy_pipeline(self.average_pipeline, None);
+            vk_device.destroy_pipeline_layout(self.pipeline_layout, None);
+
+            // destroy descriptors
+            self.descriptors.destroy(vk_device);
+        }
+    }
+}
+
+fn binding(index: u32, type_: vk::DescriptorType) -> vk::DescriptorSetLayoutBinding {
+    vk::DescriptorSetLayoutBinding::builder()
+        .binding(index)
+        .descriptor_type(type_)
+        .descriptor_count(1)
+        .stage_flags(vk::ShaderStageFlags::COMPUTE)
+        .build()
+}
+
+unsafe fn create_pipeline_layout(
+    device: &gfx::Device,
+    descriptors: &gfx::DescriptorSets,
+) -> Result<vk::PipelineLayout> {
+    // Push Constant Ranges
+
+    let push_constant_range = vk::PushConstantRange::builder()
+        .stage_flags(vk::ShaderStageFlags::COMPUTE)
+        .offset(0)
+        .size(size_of::<ExposurePushConstants>() as u32);
+
+    // Layout
+
+    device.create_pipeline_layout(&[descriptors], &[push_constant_range.build()])
+}
+
+unsafe fn create_pipeline(
+    device: &gfx::Device,
+    layout: vk::PipelineLayout,
+    code: &[u8],
+) -> Result<vk::Pipeline> {
+    let vk_device = device.device();
+
+    // Stage
+
+    let shader = gfx::Shader::create(vk_device, code)?;
+    let stage = vk::PipelineShaderStageCreateInfo::builder()
+        .stage(vk::ShaderStageFlags::COMPUTE)
+        .module(shader.module)
+        .name(b"main\0");
+
+    // Create
+
+    let info = vk::ComputePipelineCreateInfo::builder()
+        .stage(stage)
+        .layout(layout);
+
+    let pipeline = vk_device
+        .create_compute_pipelines(vk::PipelineCache::null(), &[info], None)?
+        .0[0];
+
+    // clean up
+    shader.destroy(vk_device);
+
+    Ok(pipeline)
+}
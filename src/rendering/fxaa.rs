@@ -0,0 +1,62 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use std::any::Any;
+
+use super::{constant_bytes, PostPass};
+
+/// Fast approximate anti-aliasing, runs on tone mapped color.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FxaaPass {
+    /// the contrast needed to detect an edge, relative to the local maximum.
+    pub edge_threshold: f32,
+    /// the contrast below which dark areas are skipped.
+    pub edge_threshold_min: f32,
+    /// how much sub-pixel aliasing is removed, 0 is off and 1 is softest.
+    pub subpixel: f32,
+}
+
+impl Default for FxaaPass {
+    fn default() -> Self {
+        Self {
+            edge_threshold: 0.166,
+            edge_threshold_min: 0.0833,
+            subpixel: 0.75,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct FxaaPushConstants {
+    edge_threshold: f32,
+    edge_threshold_min: f32,
+    subpixel: f32,
+}
+
+impl PostPass for FxaaPass {
+    fn name(&self) -> &str {
+        "fxaa"
+    }
+
+    fn shader(&self) -> &'static [u8] {
+        include_bytes!("../../shaders/fxaa_frag.spv")
+    }
+
+    fn constants(&self) -> Vec<u8> {
+        constant_bytes(&FxaaPushConstants {
+            edge_threshold: self.edge_threshold,
+            edge_threshold_min: self.edge_threshold_min,
+            subpixel: self.subpixel,
+        })
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
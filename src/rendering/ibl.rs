@@ -0,0 +1,797 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use anyhow::Result;
+use tracing::debug;
+use vulkanalia::prelude::v1_0::*;
+
+use super::constant_bytes;
+use crate::gfx;
+
+/// The format of all environment maps, hdr and writable by compute shaders.
+pub const ENVIRONMENT_FORMAT: vk::Format = vk::Format::R16G16B16A16_SFLOAT;
+
+/// The number of prefiltered specular levels, the last one is fully rough.
+pub const PREFILTERED_LEVELS: u32 = 5;
+
+// The face sizes of the cube maps and the size of the brdf table.
+const ENVIRONMENT_SIZE: u32 = 256;
+const IRRADIANCE_SIZE: u32 = 32;
+const PREFILTERED_SIZE: u32 = 128;
+const BRDF_SIZE: u32 = 128;
+
+// The work group size of all preprocessing shaders.
+const GROUP_SIZE: u32 = 8;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct PrefilterPushConstants {
+    roughness: f32,
+    source_size: f32,
+}
+
+/// A compute pipeline with a single descriptor set layout, used while preprocessing.
+struct ComputeStage {
+    descriptors: gfx::DescriptorSets,
+    pipeline_layout: vk::PipelineLayout,
+    pipeline: vk::Pipeline,
+}
+
+/// A distant environment preprocessed for image based lighting, the irradiance and
+/// prefiltered specular cube maps plus the split sum brdf table.
+pub struct Environment {
+    environment: gfx::Texture,
+    environment_view: gfx::TextureView,
+    irradiance: gfx::Texture,
+    irradiance_view: gfx::TextureView,
+    prefiltered: gfx::Texture,
+    prefiltered_view: gfx::TextureView,
+    brdf: gfx::Texture,
+    brdf_view: gfx::TextureView,
+}
+
+impl Environment {
+    /// load an equirectangular png, colors are srgb and linearized on sampling.
+    pub fn load(device: &gfx::Device, path: &str) -> Result<Self> {
+        let (width, height, pixels) = gfx::read_png(path)?;
+        Self::create(device, width, height, &pixels)
+    }
+
+    /// a constant environment, gives the same ambient light from all directions.
+    pub fn uniform(device: &gfx::Device, color: [u8; 4]) -> Result<Self> {
+        Self::create(device, 1, 1, &color)
+    }
+
+    /// preprocess equirectangular srgb rgba8 pixels, longitude along x and y up at the top.
+    pub fn create(device: &gfx::Device, width: u32, height: u32, pixels: &[u8]) -> Result<Self> {
+        unsafe {
+            let vk_device = device.device();
+
+            // upload the source, mipmapped to avoid aliasing on small cube faces
+            let (source, source_view) =
+                device.upload_texture(width, height, pixels, gfx::TextureImport::Color)?;
+
+            // create the targets
+            let environment_levels = ENVIRONMENT_SIZE.ilog2() + 1;
+            let environment = device.create_cube_texture(
+                ENVIRONMENT_SIZE,
+                environment_levels,
+                ENVIRONMENT_FORMAT,
+                vk::ImageUsageFlags::STORAGE
+                    | vk::ImageUsageFlags::SAMPLED
+                    | vk::ImageUsageFlags::TRANSFER_SRC
+                    | vk::ImageUsageFlags::TRANSFER_DST,
+            )?;
+            let irradiance = device.create_cube_texture(
+                IRRADIANCE_SIZE,
+                1,
+                ENVIRONMENT_FORMAT,
+                vk::ImageUsageFlags::STORAGE | vk::ImageUsageFlags::SAMPLED,
+            )?;
+            let prefiltered = device.create_cube_texture(
+                PREFILTERED_SIZE,
+                PREFILTERED_LEVELS,
+                ENVIRONMENT_FORMAT,
+                vk::ImageUsageFlags::STORAGE | vk::ImageUsageFlags::SAMPLED,
+            )?;
+            let brdf = device.create_texture(
+                BRDF_SIZE,
+                BRDF_SIZE,
+                1,
+                ENVIRONMENT_FORMAT,
+                vk::ImageUsageFlags::STORAGE | vk::ImageUsageFlags::SAMPLED,
+            )?;
+
+            // the views used for shading
+            let environment_view = environment.create_subresource_view(
+                vk_device,
+                vk::ImageViewType::CUBE,
+                0,
+                environment_levels,
+                6,
+            )?;
+            let irradiance_view =
+                irradiance.create_subresource_view(vk_device, vk::ImageViewType::CUBE, 0, 1, 6)?;
+            let prefiltered_view = prefiltered.create_subresource_view(
+                vk_device,
+                vk::ImageViewType::CUBE,
+                0,
+                PREFILTERED_LEVELS,
+                6,
+            )?;
+            let brdf_view =
+                brdf.create_subresource_view(vk_device, vk::ImageViewType::_2D, 0, 1, 1)?;
+
+            // the views written by the shaders, one per level
+            let storage_view = |texture: &gfx::Texture, level: u32| {
+                texture.create_subresource_view(
+                    vk_device,
+                    vk::ImageViewType::_2D_ARRAY,
+                    level,
+                    1,
+                    6,
+                )
+            };
+            let environment_storage = storage_view(&environment, 0)?;
+            let irradiance_storage = storage_view(&irradiance, 0)?;
+            let prefiltered_storage = (0..PREFILTERED_LEVELS)
+                .map(|level| storage_view(&prefiltered, level))
+                .collect::<Result<Vec<_>>>()?;
+            let brdf_storage =
+                brdf.create_subresource_view(vk_device, vk::ImageViewType::_2D, 0, 1, 1)?;
+
+            // create the preprocessing stages
+            let source_sampler = device.sampler(&gfx::SamplerDescriptor::default())?;
+            let cube_sampler = device.sampler(&gfx::SamplerDescriptor::linear(
+                vk::SamplerAddressMode::CLAMP_TO_EDGE,
+            ))?;
+            let mut cube_stage = create_stage(
+                device,
+                include_bytes!("../../shaders/ibl_cube_comp.spv"),
+                &sampling_bindings(),
+                0,
+            )?;
+            device.set_name(cube_stage.pipeline, "ibl cube");
+            let mut irradiance_stage = create_stage(
+                device,
+                include_bytes!("../../shaders/ibl_irradiance_comp.spv"),
+                &sampling_bindings(),
+                0,
+            )?;
+            device.set_name(irradiance_stage.pipeline, "ibl irradiance");
+            let mut prefilter_stage = create_stage(
+                device,
+                include_bytes!("../../shaders/ibl_prefilter_comp.spv"),
+                &sampling_bindings(),
+                std::mem::size_of::<PrefilterPushConstants>() as u32,
+            )?;
+            device.set_name(prefilter_stage.pipeline, "ibl prefilter");
+            let mut brdf_stage = create_stage(
+                device,
+                include_bytes!("../../shaders/ibl_brdf_comp.spv"),
+                &[storage_binding(0)],
+                0,
+            )?;
+            device.set_name(brdf_stage.pipeline, "ibl brdf");
+
+            // write all sets up front, each dispatch has its own
+            let cube_set = cube_stage.descriptors.get(vk_device, 0)?;
+            write_sampling_set(
+                vk_device,
+                cube_set,
+                source_view.view,
+                source_sampler,
+                environment_storage.view,
+            );
+            let irradiance_set = irradiance_stage.descriptors.get(vk_device, 0)?;
+            write_sampling_set(
+                vk_device,
+                irradiance_set,
+                environment_view.view,
+                cube_sampler,
+                irradiance_storage.view,
+            );
+            let mut prefilter_sets = vec![];
+            for (level, storage) in prefiltered_storage.iter().enumerate() {
+                let set = prefilter_stage.descriptors.get(vk_device, level)?;
+                write_sampling_set(
+                    vk_device,
+                    set,
+                    environment_view.view,
+                    cube_sampler,
+                    storage.view,
+                );
+                prefilter_sets.push(set);
+            }
+            let brdf_set = brdf_stage.descriptors.get(vk_device, 0)?;
+            write_storage(vk_device, brdf_set, 0, brdf_storage.view);
+
+            device.execute(|device, command_buffer| {
+                // project the source onto the cube and fill its levels
+                transition(
+                    vk_device,
+                    command_buffer,
+                    environment.image,
+                    0,
+                    1,
+                    6,
+                    Transition::Write,
+                );
+                dispatch(
+                    vk_device,
+                    command_buffer,
+                    &cube_stage,
+                    cube_set,
+                    &[],
+                    ENVIRONMENT_SIZE,
+                    6,
+                );
+                record_cube_mipmaps(
+                    vk_device,
+                    command_buffer,
+                    environment.image,
+                    ENVIRONMENT_SIZE,
+                    environment_levels,
+                    vk::ImageLayout::GENERAL,
+                    vk::AccessFlags::SHADER_WRITE,
+                    vk::PipelineStageFlags::COMPUTE_SHADER,
+                );
+
+                // convolve the diffuse irradiance
+                transition(
+                    vk_device,
+                    command_buffer,
+                    irradiance.image,
+                    0,
+                    1,
+                    6,
+                    Transition::Write,
+                );
+                dispatch(
+                    vk_device,
+                    command_buffer,
+                    &irradiance_stage,
+                    irradiance_set,
+                    &[],
+                    IRRADIANCE_SIZE,
+                    6,
+                );
+                transition(
+                    vk_device,
+                    command_buffer,
+                    irradiance.image,
+                    0,
+                    1,
+                    6,
+                    Transition::Read,
+                );
+
+                // prefilter one roughness per level
+                transition(
+                    vk_device,
+                    command_buffer,
+                    prefiltered.image,
+                    0,
+                    PREFILTERED_LEVELS,
+                    6,
+                    Transition::Write,
+                );
+                for (level, set) in prefilter_sets.iter().enumerate() {
+                    let constants = PrefilterPushConstants {
+                        roughness: level as f32 / (PREFILTERED_LEVELS - 1) as f32,
+                        source_size: ENVIRONMENT_SIZE as f32,
+                    };
+                    dispatch(
+                        vk_device,
+                        command_buffer,
+                        &prefilter_stage,
+                        *set,
+                        &constant_bytes(&constants),
+                        (PREFILTERED_SIZE >> level).max(1),
+                        6,
+                    );
+                }
+                transition(
+                    vk_device,
+                    command_buffer,
+                    prefiltered.image,
+                    0,
+                    PREFILTERED_LEVELS,
+                    6,
+                    Transition::Read,
+                );
+
+                // integrate the brdf table
+                transition(
+                    vk_device,
+                    command_buffer,
+                    brdf.image,
+                    0,
+                    1,
+                    1,
+                    Transition::Write,
+                );
+                dispatch(
+                    vk_device,
+                    command_buffer,
+                    &brdf_stage,
+                    brdf_set,
+                    &[],
+                    BRDF_SIZE,
+                    1,
+                );
+                transition(
+                    vk_device,
+                    command_buffer,
+                    brdf.image,
+                    0,
+                    1,
+                    1,
+                    Transition::Read,
+                );
+
+                Ok(())
+            })?;
+
+            // cleanup everything only needed while preprocessing
+            for stage in [
+                &cube_stage,
+                &irradiance_stage,
+                &prefilter_stage,
+                &brdf_stage,
+            ] {
+                stage.destroy(vk_device);
+            }
+            environment_storage.destroy(vk_device);
+            irradiance_storage.destroy(vk_device);
+            prefiltered_storage
+                .iter()
+                .for_each(|v| v.destroy(vk_device));
+            brdf_storage.destroy(vk_device);
+            source_view.destroy(vk_device);
+            source.destroy(vk_device);
+
+            Ok(Self {
+                environment,
+                environment_view,
+                irradiance,
+                irradiance_view,
+                prefiltered,
+                prefiltered_view,
+                brdf,
+                brdf_view,
+            })
+        }
+    }
+
+    /// the environment as a mipmapped cube map, for drawing it as a sky.
+    pub fn environment_view(&self) -> vk::ImageView {
+        self.environment_view.view
+    }
+
+    /// the cosine convolved environment, irradiance over pi per normal direction.
+    pub fn irradiance_view(&self) -> vk::ImageView {
+        self.irradiance_view.view
+    }
+
+    /// the ggx prefiltered environment, roughness increases linearly over the levels.
+    pub fn prefiltered_view(&self) -> vk::ImageView {
+        self.prefiltered_view.view
+    }
+
+    /// the scale and bias of f0 in red and green, by n dot v along u and roughness along v.
+    pub fn brdf_view(&self) -> vk::ImageView {
+        self.brdf_view.view
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe {
+            let vk_device = device.device();
+
+            for (texture, view) in [
+                (&self.environment, &self.environment_view),
+                (&self.irradiance, &self.irradiance_view),
+                (&self.prefiltered, &self.prefiltered_view),
+                (&self.brdf, &self.brdf_view),
+            ] {
+                view.destroy(vk_device);
+                texture.destroy(vk_device);
+            }
+        }
+    }
+}
+
+impl ComputeStage {
+    unsafe fn destroy(&self, device: &vulkanalia::Device) {
+        device.destroy_pipeline(self.pipeline, None);
+        device.destroy_pipeline_layout(self.pipeline_layout, None);
+        self.descriptors.destroy(device);
+    }
+}
+
+/// The layouts an image moves through while preprocessing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Transition {
+    /// from undefined to writable by compute shaders.
+    Write,
+    /// from written by compute shaders to sampled by any shader.
+    Read,
+}
+
+unsafe fn transition(
+    device: &vulkanalia::Device,
+    command_buffer: vk::CommandBuffer,
+    image: vk::Image,
+    base_mip_level: u32,
+    mip_levels: u32,
+    layers: u32,
+    transition: Transition,
+) {
+    let (old_layout, new_layout, src_access, dst_access, src_stage, dst_stage) = match transition {
+        Transition::Write => (
+            vk::ImageLayout::UNDEFINED,
+            vk::ImageLayout::GENERAL,
+            vk::AccessFlags::empty(),
+            vk::AccessFlags::SHADER_WRITE,
+            vk::PipelineStageFlags::TOP_OF_PIPE,
+            vk::PipelineStageFlags::COMPUTE_SHADER,
+        ),
+        Transition::Read => (
+            vk::ImageLayout::GENERAL,
+            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            vk::AccessFlags::SHADER_WRITE,
+            vk::AccessFlags::SHADER_READ,
+            vk::PipelineStageFlags::COMPUTE_SHADER,
+            vk::PipelineStageFlags::COMPUTE_SHADER | vk::PipelineStageFlags::FRAGMENT_SHADER,
+        ),
+    };
+
+    let barrier = image_barrier(
+        image,
+        base_mip_level,
+        mip_levels,
+        layers,
+        old_layout,
+        new_layout,
+        src_access,
+        dst_access,
+    );
+    device.cmd_pipeline_barrier(
+        command_buffer,
+        src_stage,
+        dst_stage,
+        vk::DependencyFlags::empty(),
+        &[] as &[vk::MemoryBarrier],
+        &[] as &[vk::BufferMemoryBarrier],
+        &[barrier],
+    );
+}
+
+pub(crate) fn image_barrier(
+    image: vk::Image,
+    base_mip_level: u32,
+    mip_levels: u32,
+    layers: u32,
+    old_layout: vk::ImageLayout,
+    new_layout: vk::ImageLayout,
+    src_access: vk::AccessFlags,
+    dst_access: vk::AccessFlags,
+) -> vk::ImageMemoryBarrier {
+    let subresource = vk::ImageSubresourceRange::builder()
+        .aspect_mask(vk::ImageAspectFlags::COLOR)
+        .base_mip_level(base_mip_level)
+        .level_count(mip_levels)
+        .base_array_layer(0)
+        .layer_count(layers);
+
+    vk::ImageMemoryBarrier::builder()
+        .old_layout(old_layout)
+        .new_layout(new_layout)
+        .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .image(image)
+        .subresource_range(subresource)
+        .src_access_mask(src_access)
+        .dst_access_mask(dst_access)
+        .build()
+}
+
+/// blit the first level of all faces into the others, the first level was left in
+/// `first_layout` by writes of `first_access` in `first_stage`. all levels end up readable by
+/// shaders.
+pub(crate) unsafe fn record_cube_mipmaps(
+    device: &vulkanalia::Device,
+    command_buffer: vk::CommandBuffer,
+    image: vk::Image,
+    size: u32,
+    mip_levels: u32,
+    first_layout: vk::ImageLayout,
+    first_access: vk::AccessFlags,
+    first_stage: vk::PipelineStageFlags,
+) {
+    let barrier = |level: u32,
+                   old_layout: vk::ImageLayout,
+                   new_layout: vk::ImageLayout,
+                   src_access: vk::AccessFlags,
+                   dst_access: vk::AccessFlags| {
+        image_barrier(
+            image, level, 1, 6, old_layout, new_layout, src_access, dst_access,
+        )
+    };
+
+    // the first level is read, all others are written
+    let mut barriers = vec![barrier(
+        0,
+        first_layout,
+        vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        first_access,
+        vk::AccessFlags::TRANSFER_READ,
+    )];
+    if mip_levels > 1 {
+        barriers.push(image_barrier(
+            image,
+            1,
+            mip_levels - 1,
+            6,
+            vk::ImageLayout::UNDEFINED,
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            vk::AccessFlags::empty(),
+            vk::AccessFlags::TRANSFER_WRITE,
+        ));
+    }
+    device.cmd_pipeline_barrier(
+        command_buffer,
+        first_stage,
+        vk::PipelineStageFlags::TRANSFER,
+        vk::DependencyFlags::empty(),
+        &[] as &[vk::MemoryBarrier],
+        &[] as &[vk::BufferMemoryBarrier],
+        &barriers,
+    );
+
+    // blit every level into the next, a level becomes a source once written
+    for level in 1..mip_levels {
+        let src_size = (size >> (level - 1)).max(1) as i32;
+        let dst_size = (size >> level).max(1) as i32;
+        let subresource = |level: u32| {
+            vk::ImageSubresourceLayers::builder()
+                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                .mip_level(level)
+                .base_array_layer(0)
+                .layer_count(6)
+                .build()
+        };
+        let blit = vk::ImageBlit::builder()
+            .src_offsets([
+                vk::Offset3D { x: 0, y: 0, z: 0 },
+                vk::Offset3D {
+                    x: src_size,
+                    y: src_size,
+                    z: 1,
+                },
+            ])
+            .src_subresource(subresource(level - 1))
+            .dst_offsets([
+                vk::Offset3D { x: 0, y: 0, z: 0 },
+                vk::Offset3D {
+                    x: dst_size,
+                    y: dst_size,
+                    z: 1,
+                },
+            ])
+            .dst_subresource(subresource(level));
+        device.cmd_blit_image(
+            command_buffer,
+            image,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            image,
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            &[blit],
+            vk::Filter::LINEAR,
+        );
+
+        let src = barrier(
+            level,
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            vk::AccessFlags::TRANSFER_WRITE,
+            vk::AccessFlags::TRANSFER_READ,
+        );
+        device.cmd_pipeline_barrier(
+            command_buffer,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::DependencyFlags::empty(),
+            &[] as &[vk::MemoryBarrier],
+            &[] as &[vk::BufferMemoryBarrier],
+            &[src],
+        );
+    }
+
+    // all levels are sampled from now on
+    let read = image_barrier(
+        image,
+        0,
+        mip_levels,
+        6,
+        vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        vk::AccessFlags::TRANSFER_READ,
+        vk::AccessFlags::SHADER_READ,
+    );
+    device.cmd_pipeline_barrier(
+        command_buffer,
+        vk::PipelineStageFlags::TRANSFER,
+        vk::PipelineStageFlags::COMPUTE_SHADER | vk::PipelineStageFlags::FRAGMENT_SHADER,
+        vk::DependencyFlags::empty(),
+        &[] as &[vk::MemoryBarrier],
+        &[] as &[vk::BufferMemoryBarrier],
+        &[read],
+    );
+}
+
+/// dispatch enough groups to cover a square target of `size` with `layers` layers.
+unsafe fn dispatch(
+    device: &vulkanalia::Device,
+    command_buffer: vk::CommandBuffer,
+    stage: &ComputeStage,
+    set: vk::DescriptorSet,
+    constants: &[u8],
+    size: u32,
+    layers: u32,
+) {
+    debug!(pipeline = ?stage.pipeline, "bind pipeline");
+    device.cmd_bind_pipeline(
+        command_buffer,
+        vk::PipelineBindPoint::COMPUTE,
+        stage.pipeline,
+    );
+    stage.descriptors.bind(
+        device,
+        command_buffer,
+        vk::PipelineBindPoint::COMPUTE,
+        stage.pipeline_layout,
+        set,
+    );
+    if !constants.is_empty() {
+        device.cmd_push_constants(
+            command_buffer,
+            stage.pipeline_layout,
+            vk::ShaderStageFlags::COMPUTE,
+            0,
+            constants,
+        );
+    }
+
+    let groups = size.div_ceil(GROUP_SIZE);
+    debug!(x = groups, y = groups, z = layers, "dispatch");
+    device.cmd_dispatch(command_buffer, groups, groups, layers);
+}
+
+/// a source texture, its sampler and the storage image written.
+fn sampling_bindings() -> [vk::DescriptorSetLayoutBinding; 3] {
+    [
+        vk::DescriptorSetLayoutBinding::builder()
+            .binding(0)
+            .descriptor_type(vk::DescriptorType::SAMPLED_IMAGE)
+            .descriptor_count(1)
+            .stage_flags(vk::ShaderStageFlags::COMPUTE)
+            .build(),
+        vk::DescriptorSetLayoutBinding::builder()
+            .binding(1)
+            .descriptor_type(vk::DescriptorType::SAMPLER)
+            .descriptor_count(1)
+            .stage_flags(vk::ShaderStageFlags::COMPUTE)
+            .build(),
+        storage_binding(2),
+    ]
+}
+
+fn storage_binding(binding: u32) -> vk::DescriptorSetLayoutBinding {
+    vk::DescriptorSetLayoutBinding::builder()
+        .binding(binding)
+        .descriptor_type(vk::DescriptorType::STORAGE_IMAGE)
+        .descriptor_count(1)
+        .stage_flags(vk::ShaderStageFlags::COMPUTE)
+        .build()
+}
+
+unsafe fn write_sampling_set(
+    device: &vulkanalia::Device,
+    set: vk::DescriptorSet,
+    source: vk::ImageView,
+    sampler: vk::Sampler,
+    target: vk::ImageView,
+) {
+    let source_info = &[vk::DescriptorImageInfo::builder()
+        .image_view(source)
+        .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)];
+    let sampler_info = &[vk::DescriptorImageInfo::builder().sampler(sampler)];
+    let writes = [
+        vk::WriteDescriptorSet::builder()
+            .dst_set(set)
+            .dst_binding(0)
+            .descriptor_type(vk::DescriptorType::SAMPLED_IMAGE)
+            .image_info(source_info)
+            .build(),
+        vk::WriteDescriptorSet::builder()
+            .dst_set(set)
+            .dst_binding(1)
+            .descriptor_type(vk::DescriptorType::SAMPLER)
+            .image_info(sampler_info)
+            .build(),
+    ];
+    device.update_descriptor_sets(&writes, &[] as &[vk::CopyDescriptorSet]);
+
+    write_storage(device, set, 2, target);
+}
+
+unsafe fn write_storage(
+    device: &vulkanalia::Device,
+    set: vk::DescriptorSet,
+    binding: u32,
+    target: vk::ImageView,
+) {
+    let target_info = &[vk::DescriptorImageInfo::builder()
+        .image_view(target)
+        .image_layout(vk::ImageLayout::GENERAL)];
+    let write = vk::WriteDescriptorSet::builder()
+        .dst_set(set)
+        .dst_binding(binding)
+        .descriptor_type(vk::DescriptorType::STORAGE_IMAGE)
+        .image_info(target_info);
+    device.update_descriptor_sets(&[write], &[] as &[vk::CopyDescriptorSet]);
+}
+
+unsafe fn create_stage(
+    device: &gfx::Device,
+    code: &[u8],
+    bindings: &[vk::DescriptorSetLayoutBinding],
+    push_constants: u32,
+) -> Result<ComputeStage> {
+    let vk_device = device.device();
+    let descriptors = gfx::DescriptorSets::create(vk_device, gfx::DescriptorSlot::Pass, bindings)?;
+
+    // Push Constant Ranges
+    let push_constant_range = vk::PushConstantRange::builder()
+        .stage_flags(vk::ShaderStageFlags::COMPUTE)
+        .offset(0)
+        .size(push_constants);
+    let push_constant_ranges = if push_constants > 0 {
+        vec![push_constant_range.build()]
+    } else {
+        vec![]
+    };
+
+    // Layout
+    let pipeline_layout = device.create_pipeline_layout(&[&descriptors], &push_constant_ranges)?;
+
+    // Stages
+    let shader = gfx::Shader::create(vk_device, code)?;
+    let stage = vk::PipelineShaderStageCreateInfo::builder()
+        .stage(vk::ShaderStageFlags::COMPUTE)
+        .module(shader.module)
+        .name(b"main\0");
+
+    // Create
+    let info = vk::ComputePipelineCreateInfo::builder()
+        .stage(stage)
+        .layout(pipeline_layout);
+    let pipeline = vk_device
+        .create_compute_pipelines(vk::PipelineCache::null(), &[info], None)?
+        .0[0];
+
+    // cleanup
+    shader.destroy(vk_device);
+
+    Ok(ComputeStage {
+        descriptors,
+        pipeline_layout,
+        pipeline,
+    })
+}
@@ -0,0 +1,587 @@
+#![allow(
+    dead_code,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments
+)]
+
+use std::mem::size_of;
+
+use anyhow::{anyhow, Result};
+use tracing::debug;
+use vulkanalia::prelude::v1_0::*;
+
+use super::constant_bytes;
+use crate::gfx;
+
+/// The format of the images the compute image passes read and write, hdr and writable by
+/// compute shaders everywhere.
+pub const IMAGE_PASS_FORMAT: vk::Format = vk::Format::R16G16B16A16_SFLOAT;
+
+/// The work group size of the image pass shaders in both dimensions.
+pub const IMAGE_GROUP_SIZE: u32 = 8;
+
+/// The number of bins of `LuminanceHistogram`, must match the histogram shader.
+pub const HISTOGRAM_BINS: usize = 256;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct BlurPushConstants {
+    direction: [i32; 2],
+    radius: i32,
+    sigma: f32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct HistogramPushConstants {
+    min_log_luminance: f32,
+    log_luminance_range: f32,
+}
+
+/// the work groups to dispatch for one invocation per texel of `extent`, partial groups
+/// at the right and bottom edges included.
+pub fn dispatch_size(extent: vk::Extent2D, group_size: u32) -> (u32, u32) {
+    (
+        extent.width.div_ceil(group_size),
+        extent.height.div_ceil(group_size),
+    )
+}
+
+/// the extent a 2x downsample of `extent` writes, odd sizes round up and no side gets
+/// smaller than a texel.
+pub fn downsampled_extent(extent: vk::Extent2D) -> vk::Extent2D {
+    vk::Extent2D {
+        width: extent.width.div_ceil(2).max(1),
+        height: extent.height.div_ceil(2).max(1),
+    }
+}
+
+/// An image the compute image passes read and write. it stays in the general layout for
+/// its whole life, sampling it needs a `SAMPLED` usage and a general image layout in the
+/// descriptor.
+pub struct StorageImage {
+    texture: gfx::Texture,
+    view: gfx::TextureView,
+    extent: vk::Extent2D,
+}
+
+impl StorageImage {
+    /// create an image in `IMAGE_PASS_FORMAT` with `usage` on top of storage, e.g. to copy
+    /// the scene into it. the contents are undefined until a pass writes them.
+    pub fn create(
+        device: &gfx::Device,
+        extent: vk::Extent2D,
+        usage: vk::ImageUsageFlags,
+    ) -> Result<Self> {
+        unsafe {
+            let vk_device = device.device();
+            let texture = device.create_texture(
+                extent.width,
+                extent.height,
+                1,
+                IMAGE_PASS_FORMAT,
+                vk::ImageUsageFlags::STORAGE | usage,
+            )?;
+            let view = texture.create_view(
+                vk_device,
+                IMAGE_PASS_FORMAT,
+                vk::ImageAspectFlags::COLOR,
+                1,
+            )?;
+
+            // move into the general layout once, passes never change it
+            device.execute(|_, command_buffer| {
+                let barrier = image_barrier(
+                    texture.image,
+                    vk::ImageLayout::UNDEFINED,
+                    vk::AccessFlags::empty(),
+                    vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE,
+                );
+                vk_device.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::TOP_OF_PIPE,
+                    vk::PipelineStageFlags::COMPUTE_SHADER,
+                    vk::DependencyFlags::empty(),
+                    &[] as &[vk::MemoryBarrier],
+                    &[] as &[vk::BufferMemoryBarrier],
+                    &[barrier],
+                );
+                Ok(())
+            })?;
+
+            Ok(Self {
+                texture,
+                view,
+                extent,
+            })
+        }
+    }
+
+    pub fn image(&self) -> vk::Image {
+        self.texture.image
+    }
+
+    pub fn view(&self) -> vk::ImageView {
+        self.view.view
+    }
+
+    pub fn extent(&self) -> vk::Extent2D {
+        self.extent
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe {
+            let vk_device = device.device();
+            self.view.destroy(vk_device);
+            self.texture.destroy(vk_device);
+        }
+    }
+}
+
+/// make compute writes to a storage image visible to later compute and fragment shaders
+/// and order later writes after earlier reads. every pass ends with one on the image it
+/// wrote, images written some other way need one before a pass reads them.
+pub unsafe fn storage_image_barrier(
+    device: &vulkanalia::Device,
+    command_buffer: vk::CommandBuffer,
+    image: vk::Image,
+) {
+    let barrier = image_barrier(
+        image,
+        vk::ImageLayout::GENERAL,
+        vk::AccessFlags::SHADER_WRITE,
+        vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE,
+    );
+    device.cmd_pipeline_barrier(
+        command_buffer,
+        vk::PipelineStageFlags::COMPUTE_SHADER,
+        vk::PipelineStageFlags::COMPUTE_SHADER | vk::PipelineStageFlags::FRAGMENT_SHADER,
+        vk::DependencyFlags::empty(),
+        &[] as &[vk::MemoryBarrier],
+        &[] as &[vk::BufferMemoryBarrier],
+        &[barrier],
+    );
+}
+
+/// A compute pipeline reading and writing storage images through a single set.
+pub(super) struct ImageStage {
+    pub(super) descriptors: gfx::DescriptorSets,
+    pipeline_layout: vk::PipelineLayout,
+    pub(super) pipeline: vk::Pipeline,
+}
+
+/// A separable gaussian blur, a horizontal pass into a scratch image and a vertical one
+/// into the target. taps past the edges repeat the edge texels.
+pub struct GaussianBlur {
+    /// the taps on either side of a texel.
+    pub radius: u32,
+    /// the standard deviation of the weights in texels.
+    pub sigma: f32,
+    stage: ImageStage,
+}
+
+impl GaussianBlur {
+    pub fn create(device: &gfx::Device, radius: u32, sigma: f32) -> Result<Self> {
+        let stage = unsafe {
+            create_stage(
+                device,
+                include_bytes!("../../shaders/image_blur_comp.spv"),
+                &[storage_binding(0), storage_binding(1)],
+                size_of::<BlurPushConstants>() as u32,
+            )?
+        };
+        device.set_name(stage.pipeline, "gaussian blur");
+        Ok(Self {
+            radius,
+            sigma,
+            stage,
+        })
+    }
+
+    /// record a blur of `source` into `target`, both can be the same image. all three
+    /// images need the same extent. must be called outside any render pass, `index` is the
+    /// swapchain image the commands are for.
+    pub fn record(
+        &mut self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        source: &StorageImage,
+        scratch: &StorageImage,
+        target: &StorageImage,
+    ) -> Result<()> {
+        let extent = source.extent;
+        if scratch.extent != extent || target.extent != extent {
+            return Err(anyhow!(
+                "A blur of {}x{} texels needs a scratch and target of the same size.",
+                extent.width,
+                extent.height
+            ));
+        }
+
+        unsafe {
+            let vk_device = device.device();
+            let passes = [(source, scratch, [1, 0]), (scratch, target, [0, 1])];
+            for (pass, (from, to, direction)) in passes.into_iter().enumerate() {
+                // every pass of every image has its own set
+                let set = self.stage.descriptors.get(vk_device, index * 2 + pass)?;
+                write_images(&self.stage, vk_device, set, from, to)?;
+
+                let constants = BlurPushConstants {
+                    direction,
+                    radius: self.radius as i32,
+                    sigma: self.sigma.max(f32::EPSILON),
+                };
+                dispatch(
+                    vk_device,
+                    command_buffer,
+                    &self.stage,
+                    set,
+                    &constant_bytes(&constants),
+                    extent,
+                );
+                storage_image_barrier(vk_device, command_buffer, to.image());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe { self.stage.destroy(device.device()) }
+    }
+}
+
+/// Halves an image, every target texel is the average of a 2x2 footprint of the source.
+/// chained passes build a blur pyramid.
+pub struct Downsample {
+    stage: ImageStage,
+}
+
+impl Downsample {
+    pub fn create(device: &gfx::Device) -> Result<Self> {
+        let stage = unsafe {
+            create_stage(
+                device,
+                include_bytes!("../../shaders/image_downsample_comp.spv"),
+                &[storage_binding(0), storage_binding(1)],
+                0,
+            )?
+        };
+        device.set_name(stage.pipeline, "downsample");
+        Ok(Self { stage })
+    }
+
+    /// record a downsample of `source` into `target`, which needs the `downsampled_extent`
+    /// of the source. must be called outside any render pass.
+    pub fn record(
+        &mut self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        source: &StorageImage,
+        target: &StorageImage,
+    ) -> Result<()> {
+        let extent = downsampled_extent(source.extent);
+        if target.extent != extent {
+            return Err(anyhow!(
+                "A downsample of {}x{} texels needs a {}x{} target.",
+                source.extent.width,
+                source.extent.height,
+                extent.width,
+                extent.height
+            ));
+        }
+
+        unsafe {
+            let vk_device = device.device();
+            let set = self.stage.descriptors.get(vk_device, index)?;
+            write_images(&self.stage, vk_device, set, source, target)?;
+            dispatch(vk_device, command_buffer, &self.stage, set, &[], extent);
+            storage_image_barrier(vk_device, command_buffer, target.image());
+        }
+        Ok(())
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe { self.stage.destroy(device.device()) }
+    }
+}
+
+/// Counts the texels of an image into `HISTOGRAM_BINS` bins of log2 luminance between
+/// `min_ev` and `max_ev`. the first bin holds texels too dark to measure, everything
+/// outside the range lands in the first or last bin.
+pub struct LuminanceHistogram {
+    pub min_ev: f32,
+    pub max_ev: f32,
+    stage: ImageStage,
+    bins: gfx::Buffer,
+}
+
+impl LuminanceHistogram {
+    pub fn create(device: &gfx::Device, min_ev: f32, max_ev: f32) -> Result<Self> {
+        unsafe {
+            let stage = create_stage(
+                device,
+                include_bytes!("../../shaders/image_histogram_comp.spv"),
+                &[
+                    storage_binding(0),
+                    vk::DescriptorSetLayoutBinding::builder()
+                        .binding(1)
+                        .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                        .descriptor_count(1)
+                        .stage_flags(vk::ShaderStageFlags::COMPUTE)
+                        .build(),
+                ],
+                size_of::<HistogramPushConstants>() as u32,
+            )?;
+            device.set_name(stage.pipeline, "luminance histogram");
+
+            // cleared before every count, readable on the host once the commands finished
+            let bins = device.create_buffer(
+                (size_of::<u32>() * HISTOGRAM_BINS) as vk::DeviceSize,
+                vk::BufferUsageFlags::STORAGE_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
+                vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+            )?;
+
+            Ok(Self {
+                min_ev,
+                max_ev,
+                stage,
+                bins,
+            })
+        }
+    }
+
+    /// the buffer holding the bins as `u32`s, readable by compute and fragment shaders
+    /// after `record`.
+    pub fn bins(&self) -> &gfx::Buffer {
+        &self.bins
+    }
+
+    /// the bins of the last count, only valid once its commands finished.
+    pub fn read(&self, device: &gfx::Device) -> Result<Vec<u32>> {
+        unsafe { self.bins.read(device.device(), 0, HISTOGRAM_BINS) }
+    }
+
+    /// record counting the texels of `source`, must be called outside any render pass.
+    pub fn record(
+        &mut self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        source: &StorageImage,
+    ) -> Result<()> {
+        unsafe {
+            let vk_device = device.device();
+            let set = self.stage.descriptors.get(vk_device, index)?;
+            self.stage.descriptors.write(
+                vk_device,
+                set,
+                &[
+                    (0, gfx::BindingResource::StorageTexture(source.view())),
+                    (1, gfx::BindingResource::buffer(&self.bins)),
+                ],
+            )?;
+
+            // an earlier count may still be read
+            self.bins.barrier(
+                vk_device,
+                command_buffer,
+                &[
+                    gfx::BufferAccess::ComputeRead,
+                    gfx::BufferAccess::FragmentShaderRead,
+                    gfx::BufferAccess::HostRead,
+                ],
+                &[gfx::BufferAccess::TransferWrite],
+            );
+            vk_device.cmd_fill_buffer(command_buffer, self.bins.buffer, 0, self.bins.size, 0);
+            self.bins.barrier(
+                vk_device,
+                command_buffer,
+                &[gfx::BufferAccess::TransferWrite],
+                &[
+                    gfx::BufferAccess::ComputeRead,
+                    gfx::BufferAccess::ComputeWrite,
+                ],
+            );
+
+            let min_log_luminance = self.min_ev;
+            let constants = HistogramPushConstants {
+                min_log_luminance,
+                log_luminance_range: (self.max_ev - min_log_luminance).max(f32::EPSILON),
+            };
+            dispatch(
+                vk_device,
+                command_buffer,
+                &self.stage,
+                set,
+                &constant_bytes(&constants),
+                source.extent,
+            );
+
+            self.bins.barrier(
+                vk_device,
+                command_buffer,
+                &[gfx::BufferAccess::ComputeWrite],
+                &[
+                    gfx::BufferAccess::ComputeRead,
+                    gfx::BufferAccess::FragmentShaderRead,
+                    gfx::BufferAccess::HostRead,
+                ],
+            );
+        }
+        Ok(())
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe {
+            let vk_device = device.device();
+            self.bins.destroy(vk_device);
+            self.stage.destroy(vk_device);
+        }
+    }
+}
+
+impl ImageStage {
+    pub(super) unsafe fn destroy(&self, device: &vulkanalia::Device) {
+        device.destroy_pipeline(self.pipeline, None);
+        device.destroy_pipeline_layout(self.pipeline_layout, None);
+        self.descriptors.destroy(device);
+    }
+}
+
+fn image_barrier(
+    image: vk::Image,
+    old_layout: vk::ImageLayout,
+    src_access: vk::AccessFlags,
+    dst_access: vk::AccessFlags,
+) -> vk::ImageMemoryBarrier {
+    let subresource = vk::ImageSubresourceRange::builder()
+        .aspect_mask(vk::ImageAspectFlags::COLOR)
+        .base_mip_level(0)
+        .level_count(1)
+        .base_array_layer(0)
+        .layer_count(1);
+
+    vk::ImageMemoryBarrier::builder()
+        .old_layout(old_layout)
+        .new_layout(vk::ImageLayout::GENERAL)
+        .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .image(image)
+        .subresource_range(subresource)
+        .src_access_mask(src_access)
+        .dst_access_mask(dst_access)
+        .build()
+}
+
+/// dispatch one invocation per texel of `extent`.
+pub(super) unsafe fn dispatch(
+    device: &vulkanalia::Device,
+    command_buffer: vk::CommandBuffer,
+    stage: &ImageStage,
+    set: vk::DescriptorSet,
+    constants: &[u8],
+    extent: vk::Extent2D,
+) {
+    debug!(pipeline = ?stage.pipeline, "bind pipeline");
+    device.cmd_bind_pipeline(
+        command_buffer,
+        vk::PipelineBindPoint::COMPUTE,
+        stage.pipeline,
+    );
+    stage.descriptors.bind(
+        device,
+        command_buffer,
+        vk::PipelineBindPoint::COMPUTE,
+        stage.pipeline_layout,
+        set,
+    );
+    if !constants.is_empty() {
+        device.cmd_push_constants(
+            command_buffer,
+            stage.pipeline_layout,
+            vk::ShaderStageFlags::COMPUTE,
+            0,
+            constants,
+        );
+    }
+
+    let (x, y) = dispatch_size(extent, IMAGE_GROUP_SIZE);
+    debug!(x, y, z = 1, "dispatch");
+    device.cmd_dispatch(command_buffer, x, y, 1);
+}
+
+fn storage_binding(binding: u32) -> vk::DescriptorSetLayoutBinding {
+    vk::DescriptorSetLayoutBinding::builder()
+        .binding(binding)
+        .descriptor_type(vk::DescriptorType::STORAGE_IMAGE)
+        .descriptor_count(1)
+        .stage_flags(vk::ShaderStageFlags::COMPUTE)
+        .build()
+}
+
+unsafe fn write_images(
+    stage: &ImageStage,
+    device: &vulkanalia::Device,
+    set: vk::DescriptorSet,
+    source: &StorageImage,
+    target: &StorageImage,
+) -> Result<()> {
+    stage.descriptors.write(
+        device,
+        set,
+        &[
+            (0, gfx::BindingResource::StorageTexture(source.view())),
+            (1, gfx::BindingResource::StorageTexture(target.view())),
+        ],
+    )
+}
+
+pub(super) unsafe fn create_stage(
+    device: &gfx::Device,
+    code: &[u8],
+    bindings: &[vk::DescriptorSetLayoutBinding],
+    push_constants: u32,
+) -> Result<ImageStage> {
+    let vk_device = device.device();
+    let descriptors = gfx::DescriptorSets::create(vk_device, gfx::DescriptorSlot::Pass, bindings)?;
+
+    // Push Constant Ranges
+    let push_constant_range = vk::PushConstantRange::builder()
+        .stage_flags(vk::ShaderStageFlags::COMPUTE)
+        .offset(0)
+        .size(push_constants);
+    let push_constant_ranges = if push_constants > 0 {
+        vec![push_constant_range.build()]
+    } else {
+        vec![]
+    };
+
+    // Layout
+    let pipeline_layout = device.create_pipeline_layout(&[&descriptors], &push_constant_ranges)?;
+
+    // Stages
+    let shader = gfx::Shader::create(vk_device, code)?;
+    let stage = vk::PipelineShaderStageCreateInfo::builder()
+        .stage(vk::ShaderStageFlags::COMPUTE)
+        .module(shader.module)
+        .name(b"main\0");
+
+    // Create
+    let info = vk::ComputePipelineCreateInfo::builder()
+        .stage(stage)
+        .layout(pipeline_layout);
+    let pipeline = vk_device
+        .create_compute_pipelines(vk::PipelineCache::null(), &[info], None)?
+        .0[0];
+
+    // cleanup
+    shader.destroy(vk_device);
+
+    Ok(ImageStage {
+        descriptors,
+        pipeline_layout,
+        pipeline,
+    })
+}
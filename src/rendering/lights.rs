@@ -0,0 +1,164 @@
+#![allow(dead_code)]
+
+use std::cmp::Ordering;
+
+use cgmath::{InnerSpace, MetricSpace, Rad};
+
+use crate::math::{Vec3, Vec4};
+
+/// The number of lights the shaders evaluate, further lights are ignored.
+pub const MAX_LIGHTS: usize = 8;
+
+/// A light source, intensities are in scene units before exposure.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Light {
+    /// infinitely far away, `direction` is the way the light travels.
+    Directional {
+        direction: Vec3,
+        color: Vec3,
+        intensity: f32,
+    },
+    /// falls off with the squared distance, and smoothly to zero at `range` if not 0.
+    Point {
+        position: Vec3,
+        color: Vec3,
+        intensity: f32,
+        range: f32,
+    },
+    /// a point light limited to a cone around `direction`, full inside `inner_angle` and
+    /// fading to zero at `outer_angle`, both measured from the axis.
+    Spot {
+        position: Vec3,
+        direction: Vec3,
+        color: Vec3,
+        intensity: f32,
+        range: f32,
+        inner_angle: Rad<f32>,
+        outer_angle: Rad<f32>,
+    },
+}
+
+impl Light {
+    /// how strongly the light reaches `eye`, the brightest color channel over the squared
+    /// distance. directional lights reach everything and are infinitely important.
+    pub fn importance(&self, eye: Vec3) -> f32 {
+        match *self {
+            Light::Directional { .. } => f32::INFINITY,
+            Light::Point {
+                position,
+                color,
+                intensity,
+                ..
+            }
+            | Light::Spot {
+                position,
+                color,
+                intensity,
+                ..
+            } => {
+                let brightness = color.x.max(color.y).max(color.z) * intensity;
+                brightness / position.distance2(eye).max(0.0001)
+            }
+        }
+    }
+}
+
+/// Pick the lights the shaders evaluate when there are more than `max`, which is clamped to
+/// `MAX_LIGHTS`. directional lights are kept first, then the most important at `eye`, the
+/// kept lights stay in the order they were given.
+pub fn select_lights(lights: &[Light], eye: Vec3, max: usize) -> Vec<Light> {
+    let max = max.min(MAX_LIGHTS);
+    if lights.len() <= max {
+        return lights.to_vec();
+    }
+
+    let mut order = (0..lights.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| {
+        lights[*b]
+            .importance(eye)
+            .partial_cmp(&lights[*a].importance(eye))
+            .unwrap_or(Ordering::Equal)
+    });
+    order.truncate(max);
+    order.sort();
+    order.into_iter().map(|i| lights[i]).collect()
+}
+
+/// A light in the std140 uniform layout of the pbr shaders.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct LightData {
+    // direction or position, w is 0 for directional and 1 for point and spot lights
+    position: Vec4,
+    // color and intensity
+    color: Vec4,
+    // range, cosines of the outer and inner cone angle
+    params: Vec4,
+    // the direction of spot lights, w is 1 for spot lights
+    direction: Vec4,
+}
+
+crate::pod!(LightData {
+    position: Vec4,
+    color: Vec4,
+    params: Vec4,
+    direction: Vec4,
+});
+
+/// pack the lights into the uniform layout, unused entries are zeroed.
+pub(crate) fn light_data(lights: &[Light]) -> [LightData; MAX_LIGHTS] {
+    let zero = Vec4::new(0.0, 0.0, 0.0, 0.0);
+    let mut data = [LightData {
+        position: zero,
+        color: zero,
+        params: zero,
+        direction: zero,
+    }; MAX_LIGHTS];
+
+    for (slot, light) in data.iter_mut().zip(lights.iter()) {
+        *slot = match *light {
+            Light::Directional {
+                direction,
+                color,
+                intensity,
+            } => LightData {
+                position: direction.extend(0.0),
+                color: color.extend(intensity),
+                params: zero,
+                direction: zero,
+            },
+            Light::Point {
+                position,
+                color,
+                intensity,
+                range,
+            } => LightData {
+                position: position.extend(1.0),
+                color: color.extend(intensity),
+                params: Vec4::new(range, 0.0, 0.0, 0.0),
+                direction: zero,
+            },
+            Light::Spot {
+                position,
+                direction,
+                color,
+                intensity,
+                range,
+                inner_angle,
+                outer_angle,
+            } => {
+                // the fade needs the inner cone strictly inside the outer one
+                let outer = outer_angle.0.cos();
+                let inner = inner_angle.0.cos().max(outer + 0.0001);
+                LightData {
+                    position: position.extend(1.0),
+                    color: color.extend(intensity),
+                    params: Vec4::new(range, outer, inner, 0.0),
+                    direction: direction.normalize().extend(1.0),
+                }
+            }
+        };
+    }
+
+    data
+}
@@ -0,0 +1,527 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use std::mem::{align_of, size_of};
+use std::slice;
+
+use anyhow::Result;
+use tracing::debug;
+use vulkanalia::prelude::v1_0::*;
+
+use super::Curve;
+use crate::gfx;
+use crate::math::{Mat4, Vec2, Vec3, Vec4};
+
+// Segment flags, must match the line shaders.
+const HAS_PREV: u32 = 1;
+const HAS_NEXT: u32 = 2;
+
+/// How two segments of a polyline are connected.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LineJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+/// How the open ends of a polyline are drawn.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LineCap {
+    Butt,
+    Square,
+    Round,
+}
+
+/// A dash pattern, lengths are measured in world units along the line.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LineDash {
+    pub length: f32,
+    pub gap: f32,
+    pub offset: f32,
+}
+
+/// The style shared by all segments of a polyline.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LineStyle {
+    pub join: LineJoin,
+    pub cap: LineCap,
+    pub miter_limit: f32,
+    pub dash: Option<LineDash>,
+}
+
+impl Default for LineStyle {
+    fn default() -> Self {
+        Self {
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            miter_limit: 4.0,
+            dash: None,
+        }
+    }
+}
+
+/// A polyline point, the width is in logical pixels and scales with the display. points
+/// are in logical pixels too when drawn with `ScreenSpace::proj`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LinePoint {
+    pub position: Vec3,
+    pub width: f32,
+    pub color: Vec4,
+}
+
+impl LinePoint {
+    pub fn new(position: Vec3, width: f32, color: Vec4) -> Self {
+        Self {
+            position,
+            width,
+            color,
+        }
+    }
+}
+
+/// One instance of the line pipeline, a segment with its neighbours.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct LineSegment {
+    prev: Vec3,
+    start: Vec3,
+    end: Vec3,
+    next: Vec3,
+    start_color: Vec4,
+    end_color: Vec4,
+    // start width, end width, start distance, end distance
+    params: Vec4,
+    flags: u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct LinePushConstants {
+    view_proj: Mat4,
+    viewport: Vec2,
+    miter_limit: f32,
+    style: u32,
+    dash: Vec2,
+    dash_offset: f32,
+    padding: f32,
+}
+
+/// A range of segments drawn with the same style.
+#[derive(Copy, Clone, Debug)]
+struct LineBatch {
+    first: u32,
+    count: u32,
+    style: LineStyle,
+}
+
+/// Renders thick polylines by expanding each segment into a screen space quad.
+pub struct LineRenderer {
+    pipeline_layout: vk::PipelineLayout,
+    pipeline: vk::Pipeline,
+    segments: Vec<LineSegment>,
+    batches: Vec<LineBatch>,
+}
+
+impl LineRenderer {
+    pub fn create(device: &gfx::Device) -> Result<Self> {
+        unsafe {
+            // create pipeline objects
+            let (pipeline_layout, pipeline) = create_pipeline(device)?;
+            device.set_name(pipeline, "lines");
+
+            Ok(Self {
+                pipeline_layout,
+                pipeline,
+                segments: vec![],
+                batches: vec![],
+            })
+        }
+    }
+
+    /// queue an open polyline for the next recorded frame.
+    pub fn strip(&mut self, points: &[LinePoint], style: &LineStyle) {
+        self.polyline(points, false, style);
+    }
+
+    /// queue a closed polyline, the last point is joined to the first.
+    pub fn ring(&mut self, points: &[LinePoint], style: &LineStyle) {
+        self.polyline(points, true, style);
+    }
+
+    /// queue a single line, shorthand for a strip of two points.
+    pub fn line(&mut self, start: LinePoint, end: LinePoint, style: &LineStyle) {
+        self.polyline(&[start, end], false, style);
+    }
+
+    /// queue a tessellated curve with a constant width and color.
+    pub fn curve(
+        &mut self,
+        curve: &Curve,
+        tolerance: f32,
+        width: f32,
+        color: Vec4,
+        style: &LineStyle,
+    ) {
+        let points = curve
+            .tessellate(tolerance)
+            .into_iter()
+            .map(|position| LinePoint::new(position, width, color))
+            .collect::<Vec<_>>();
+
+        self.polyline(&points, curve.closed, style);
+    }
+
+    fn polyline(&mut self, points: &[LinePoint], closed: bool, style: &LineStyle) {
+        // closing needs at least a triangle
+        let closed = closed && points.len() > 2;
+        if points.len() < 2 {
+            return;
+        }
+
+        let first = self.segments.len() as u32;
+        let count = if closed {
+            points.len()
+        } else {
+            points.len() - 1
+        };
+
+        // distance along the line, used to lay out dashes
+        let mut distance = 0.0;
+
+        for i in 0..count {
+            let a = points[i];
+            let b = points[(i + 1) % points.len()];
+
+            // neighbours, missing ones are replaced by the end point itself
+            let (prev, has_prev) = if i > 0 {
+                (points[i - 1].position, true)
+            } else if closed {
+                (points[points.len() - 1].position, true)
+            } else {
+                (a.position, false)
+            };
+            let (next, has_next) = if i + 2 < points.len() {
+                (points[i + 2].position, true)
+            } else if closed {
+                (points[(i + 2) % points.len()].position, true)
+            } else {
+                (b.position, false)
+            };
+
+            let length = cgmath::InnerSpace::magnitude(b.position - a.position);
+
+            let mut flags = 0;
+            if has_prev {
+                flags |= HAS_PREV;
+            }
+            if has_next {
+                flags |= HAS_NEXT;
+            }
+
+            self.segments.push(LineSegment {
+                prev,
+                start: a.position,
+                end: b.position,
+                next,
+                start_color: a.color,
+                end_color: b.color,
+                params: Vec4::new(a.width, b.width, distance, distance + length),
+                flags,
+            });
+
+            distance += length;
+        }
+
+        self.batches.push(LineBatch {
+            first,
+            count: count as u32,
+            style: *style,
+        });
+    }
+
+    /// record all queued polylines and clear the queue, must be called in the frame render pass.
+    pub fn record(
+        &mut self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        view_proj: Mat4,
+    ) -> Result<()> {
+        unsafe {
+            // nothing queued
+            if self.segments.is_empty() {
+                self.batches.clear();
+                return Ok(());
+            }
+
+            // upload the segments into the frame's staging memory
+            let size = size_of::<LineSegment>() * self.segments.len();
+            let (buffer, offset, bytes) =
+                device.allocate(size as vk::DeviceSize, align_of::<LineSegment>() as u64)?;
+            let segments = slice::from_raw_parts(self.segments.as_ptr().cast::<u8>(), size);
+            bytes.copy_from_slice(segments);
+
+            let vk_device = device.device();
+            let extent = device.extent();
+
+            // widths are in logical pixels, so lines keep their size on hidpi displays
+            let logical = device.logical_extent();
+
+            // viewport and scissor are dynamic, the scissors follow the damage
+            let viewport = vk::Viewport::builder()
+                .x(0.0)
+                .y(0.0)
+                .width(extent.width as f32)
+                .height(extent.height as f32)
+                .min_depth(0.0)
+                .max_depth(1.0);
+            let scissors = device.scissors();
+
+            debug!(pipeline = ?self.pipeline, "bind pipeline");
+            vk_device.cmd_bind_pipeline(
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.pipeline,
+            );
+            vk_device.cmd_set_viewport(command_buffer, 0, &[viewport]);
+            vk_device.cmd_bind_vertex_buffers(command_buffer, 0, &[buffer], &[offset]);
+
+            for batch in &self.batches {
+                let dash = batch.style.dash.unwrap_or(LineDash {
+                    length: 0.0,
+                    gap: 0.0,
+                    offset: 0.0,
+                });
+
+                let constants = LinePushConstants {
+                    view_proj,
+                    viewport: Vec2::from(logical),
+                    miter_limit: batch.style.miter_limit,
+                    style: join_code(batch.style.join) | (cap_code(batch.style.cap) << 2),
+                    dash: Vec2::new(dash.length, dash.gap),
+                    dash_offset: dash.offset,
+                    padding: 0.0,
+                };
+
+                let bytes = std::slice::from_raw_parts(
+                    &constants as *const LinePushConstants as *const u8,
+                    size_of::<LinePushConstants>(),
+                );
+
+                vk_device.cmd_push_constants(
+                    command_buffer,
+                    self.pipeline_layout,
+                    vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+                    0,
+                    bytes,
+                );
+
+                // six vertices per segment quad
+                debug!(vertices = 6, instances = batch.count, "draw");
+                for scissor in &scissors {
+                    vk_device.cmd_set_scissor(command_buffer, 0, &[*scissor]);
+                    vk_device.cmd_draw(command_buffer, 6, batch.count, 0, batch.first);
+                }
+            }
+
+            // start over for the next frame
+            self.segments.clear();
+            self.batches.clear();
+
+            Ok(())
+        }
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe {
+            let vk_device = device.device();
+
+            // destroy pipeline
+            vk_device.destroy_pipeline(self.pipeline, None);
+            vk_device.destroy_pipeline_layout(self.pipeline_layout, None);
+        }
+    }
+}
+
+fn join_code(join: LineJoin) -> u32 {
+    match join {
+        LineJoin::Miter => 0,
+        LineJoin::Round => 1,
+        LineJoin::Bevel => 2,
+    }
+}
+
+fn cap_code(cap: LineCap) -> u32 {
+    match cap {
+        LineCap::Butt => 0,
+        LineCap::Square => 1,
+        LineCap::Round => 2,
+    }
+}
+
+unsafe fn create_pipeline(device: &gfx::Device) -> Result<(vk::PipelineLayout, vk::Pipeline)> {
+    let vk_device = device.device();
+
+    // Stages
+
+    let vert = include_bytes!("../../shaders/line_vert.spv");
+    let frag = include_bytes!("../../shaders/line_frag.spv");
+
+    let vertex_shader = gfx::Shader::create(vk_device, &vert[..])?;
+    let fragment_shader = gfx::Shader::create(vk_device, &frag[..])?;
+
+    let vert_stage = vk::PipelineShaderStageCreateInfo::builder()
+        .stage(vk::ShaderStageFlags::VERTEX)
+        .module(vertex_shader.module)
+        .name(b"main\0");
+
+    let frag_stage = vk::PipelineShaderStageCreateInfo::builder()
+        .stage(vk::ShaderStageFlags::FRAGMENT)
+        .module(fragment_shader.module)
+        .name(b"main\0");
+
+    // Vertex Input State, one segment per instance
+
+    let binding_descriptions = &[vk::VertexInputBindingDescription::builder()
+        .binding(0)
+        .stride(size_of::<LineSegment>() as u32)
+        .input_rate(vk::VertexInputRate::INSTANCE)
+        .build()];
+
+    let vec3 = size_of::<Vec3>() as u32;
+    let vec4 = size_of::<Vec4>() as u32;
+    let attributes = [
+        (vk::Format::R32G32B32_SFLOAT, 0),
+        (vk::Format::R32G32B32_SFLOAT, vec3),
+        (vk::Format::R32G32B32_SFLOAT, vec3 * 2),
+        (vk::Format::R32G32B32_SFLOAT, vec3 * 3),
+        (vk::Format::R32G32B32A32_SFLOAT, vec3 * 4),
+        (vk::Format::R32G32B32A32_SFLOAT, vec3 * 4 + vec4),
+        (vk::Format::R32G32B32A32_SFLOAT, vec3 * 4 + vec4 * 2),
+        (vk::Format::R32_UINT, vec3 * 4 + vec4 * 3),
+    ];
+    let attribute_descriptions = attributes
+        .iter()
+        .enumerate()
+        .map(|(location, (format, offset))| {
+            vk::VertexInputAttributeDescription::builder()
+                .binding(0)
+                .location(location as u32)
+                .format(*format)
+                .offset(*offset)
+                .build()
+        })
+        .collect::<Vec<_>>();
+
+    let vertex_input_state = vk::PipelineVertexInputStateCreateInfo::builder()
+        .vertex_binding_descriptions(binding_descriptions)
+        .vertex_attribute_descriptions(&attribute_descriptions);
+
+    // Input Assembly State
+
+    let input_assembly_state = vk::PipelineInputAssemblyStateCreateInfo::builder()
+        .topology(vk::PrimitiveTopology::TRIANGLE_LIST)
+        .primitive_restart_enable(false);
+
+    // Viewport State, set when recording
+
+    let viewport_state = vk::PipelineViewportStateCreateInfo::builder()
+        .viewport_count(1)
+        .scissor_count(1);
+
+    let dynamic_states = &[vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+    let dynamic_state =
+        vk::PipelineDynamicStateCreateInfo::builder().dynamic_states(dynamic_states);
+
+    // Rasterization State, quads face either way depending on the segment direction
+
+    let rasterization_state = vk::PipelineRasterizationStateCreateInfo::builder()
+        .depth_clamp_enable(false)
+        .rasterizer_discard_enable(false)
+        .polygon_mode(vk::PolygonMode::FILL)
+        .line_width(1.0)
+        .cull_mode(vk::CullModeFlags::NONE)
+        .front_face(vk::FrontFace::COUNTER_CLOCKWISE)
+        .depth_bias_enable(false);
+
+    // Multisample State
+
+    let multisample_state = vk::PipelineMultisampleStateCreateInfo::builder()
+        .sample_shading_enable(false)
+        .rasterization_samples(device.samples());
+
+    // Depth Stencil State, lines are tested but do not occlude
+
+    let depth_stencil_state = vk::PipelineDepthStencilStateCreateInfo::builder()
+        .depth_test_enable(true)
+        .depth_write_enable(false)
+        .depth_compare_op(vk::CompareOp::LESS_OR_EQUAL)
+        .depth_bounds_test_enable(false)
+        .stencil_test_enable(false);
+
+    // Color Blend State, the line shaders output premultiplied alpha
+
+    let attachment = vk::PipelineColorBlendAttachmentState::builder()
+        .color_write_mask(vk::ColorComponentFlags::all())
+        .blend_enable(true)
+        .src_color_blend_factor(vk::BlendFactor::ONE)
+        .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+        .color_blend_op(vk::BlendOp::ADD)
+        .src_alpha_blend_factor(vk::BlendFactor::ONE)
+        .dst_alpha_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+        .alpha_blend_op(vk::BlendOp::ADD);
+
+    let attachments = &[attachment];
+    let color_blend_state = vk::PipelineColorBlendStateCreateInfo::builder()
+        .logic_op_enable(false)
+        .logic_op(vk::LogicOp::COPY)
+        .attachments(attachments)
+        .blend_constants([0.0, 0.0, 0.0, 0.0]);
+
+    // Push Constant Ranges
+
+    let push_constant_range = vk::PushConstantRange::builder()
+        .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
+        .offset(0)
+        .size(size_of::<LinePushConstants>() as u32);
+
+    // Layout
+
+    let push_constant_ranges = &[push_constant_range];
+    let layout_info =
+        vk::PipelineLayoutCreateInfo::builder().push_constant_ranges(push_constant_ranges);
+
+    let pipeline_layout = vk_device.create_pipeline_layout(&layout_info, None)?;
+
+    // Create
+
+    let stages = &[vert_stage, frag_stage];
+    let info = vk::GraphicsPipelineCreateInfo::builder()
+        .stages(stages)
+        .vertex_input_state(&vertex_input_state)
+        .input_assembly_state(&input_assembly_state)
+        .viewport_state(&viewport_state)
+        .rasterization_state(&rasterization_state)
+        .multisample_state(&multisample_state)
+        .depth_stencil_state(&depth_stencil_state)
+        .color_blend_state(&color_blend_state)
+        .dynamic_state(&dynamic_state)
+        .layout(pipeline_layout)
+        .render_pass(device.render_pass())
+        .subpass(0);
+
+    let pipeline = vk_device
+        .create_graphics_pipelines(vk::PipelineCache::null(), &[info], None)?
+        .0[0];
+
+    // clean up
+    vertex_shader.destroy(vk_device);
+    fragment_shader.destroy(vk_device);
+
+    Ok((pipeline_layout, pipeline))
+}
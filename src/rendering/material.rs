@@ -0,0 +1,59 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use crate::math::Vec4;
+
+/// How a material is composited, this selects the render queue.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// drawn front to back with depth writes.
+    Opaque,
+    /// sorted back to front and blended, depth is tested but not written.
+    Transparent,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Material {
+    pub color: Vec4,
+    pub opacity: f32,
+    pub blend: BlendMode,
+}
+
+impl Material {
+    pub fn opaque(color: Vec4) -> Self {
+        Self {
+            color,
+            opacity: 1.0,
+            blend: BlendMode::Opaque,
+        }
+    }
+
+    pub fn transparent(color: Vec4, opacity: f32) -> Self {
+        Self {
+            color,
+            opacity,
+            blend: BlendMode::Transparent,
+        }
+    }
+
+    /// the color handed to the shaders, alpha carries the opacity.
+    pub fn tint(&self) -> Vec4 {
+        Vec4::new(
+            self.color.x,
+            self.color.y,
+            self.color.z,
+            self.color.w * self.opacity,
+        )
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material::opaque(Vec4::new(1.0, 1.0, 1.0, 1.0))
+    }
+}
@@ -7,28 +7,419 @@
 )]
 
 use std::fmt;
-use std::hash::Hash;
+use std::fs;
+use std::mem::size_of;
+use std::path::Path;
 
-use super::super::graphics::Buffer;
+use anyhow::{anyhow, Result};
+use cgmath::{InnerSpace, Zero};
+use deimos_format::MeshPack;
+use vulkanalia::prelude::v1_0::*;
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg(feature = "gltf")]
+use super::read_gltf_buffers;
+use crate::gfx;
+use crate::math::Vec3;
+#[cfg(any(feature = "obj", feature = "gltf"))]
+use crate::math::{Vec2, Vec4};
+
+#[derive(Copy, Clone)]
 pub struct Mesh {
-    vertices: Buffer,
-    indices: Buffer,
+    pub vertices: gfx::Buffer,
+    pub indices: gfx::Buffer,
+    /// 16 bit when all vertices can be reached with them, 32 bit otherwise.
+    pub index_type: vk::IndexType,
+    pub count: u32,
+    pub center: Vec3,
 }
 
 impl Mesh {
-    pub fn create(vertices:Buffer, indices:Buffer) -> Self {
-        Self { vertices, indices }
+    pub fn create(device: &gfx::Device, vertices: &[gfx::Vertex], indices: &[u32]) -> Result<Self> {
+        let center = bounds_center(vertices.iter().map(|v| v.position));
+        Self::create_from(device, vertices, indices, center)
+    }
+
+    /// create a mesh for the lit pipelines.
+    pub fn create_surface(
+        device: &gfx::Device,
+        vertices: &[gfx::SurfaceVertex],
+        indices: &[u32],
+    ) -> Result<Self> {
+        let center = bounds_center(vertices.iter().map(|v| v.position));
+        Self::create_from(device, vertices, indices, center)
+    }
+
+    /// create a mesh from a baked mesh pack of either vertex kind.
+    pub fn from_pack(device: &gfx::Device, pack: &deimos_format::MeshPack) -> Result<Self> {
+        let vec2 = |v: deimos_format::Vec2| <[f32; 2]>::from(v).into();
+        let vec3 = |v: deimos_format::Vec3| <[f32; 3]>::from(v).into();
+        match pack.kind {
+            deimos_format::VertexKind::Basic => {
+                let vertices = pack
+                    .vertices::<deimos_format::BasicVertex>()?
+                    .into_iter()
+                    .map(|v| gfx::Vertex::new(vec3(v.position), vec2(v.texel), vec3(v.color)))
+                    .collect::<Vec<_>>();
+                Self::create(device, &vertices, &pack.indices)
+            }
+            deimos_format::VertexKind::Surface => {
+                let vertices = pack
+                    .vertices::<deimos_format::SurfaceVertex>()?
+                    .into_iter()
+                    .map(|v| {
+                        let tangent = Into::<[f32; 4]>::into(v.tangent).into();
+                        gfx::SurfaceVertex::new(
+                            vec3(v.position),
+                            vec3(v.normal),
+                            tangent,
+                            vec2(v.texel),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                Self::create_surface(device, &vertices, &pack.indices)
+            }
+        }
+    }
+
+    fn create_from<V: gfx::Pod>(
+        device: &gfx::Device,
+        vertices: &[V],
+        indices: &[u32],
+        center: Vec3,
+    ) -> Result<Self> {
+        unsafe {
+            // create and fill the vertex buffer
+            let vertex_buffer = device.create_buffer_for(
+                (size_of::<V>() * vertices.len()) as vk::DeviceSize,
+                vk::BufferUsageFlags::VERTEX_BUFFER,
+            )?;
+            vertex_buffer.write(device.device(), 0, vertices)?;
+
+            // create and fill the index buffer, halved when the vertices allow it
+            let (index_type, index_buffer) = if vertices.len() <= u16::MAX as usize + 1 {
+                let indices = indices.iter().map(|i| *i as u16).collect::<Vec<_>>();
+                let buffer = device.create_buffer_for(
+                    (size_of::<u16>() * indices.len()) as vk::DeviceSize,
+                    vk::BufferUsageFlags::INDEX_BUFFER,
+                )?;
+                buffer.write(device.device(), 0, &indices)?;
+                (vk::IndexType::UINT16, buffer)
+            } else {
+                let buffer = device.create_buffer_for(
+                    (size_of::<u32>() * indices.len()) as vk::DeviceSize,
+                    vk::BufferUsageFlags::INDEX_BUFFER,
+                )?;
+                buffer.write(device.device(), 0, indices)?;
+                (vk::IndexType::UINT32, buffer)
+            };
+
+            // the center of the bounds is used as the sort origin
+            Ok(Self {
+                vertices: vertex_buffer,
+                indices: index_buffer,
+                index_type,
+                count: indices.len() as u32,
+                center,
+            })
+        }
+    }
+
+    /// the indices of all triangles, see `gfx::IndexBinding::draw`.
+    pub fn index_binding(&self) -> gfx::IndexBinding {
+        gfx::IndexBinding::new(&self.indices, self.index_type)
+    }
+
+    /// hand the buffers to the registry of `device`, which owns them from then on. returns the
+    /// ids the `Renderer` draws the mesh by.
+    pub fn register(self, device: &gfx::Device) -> MeshId {
+        MeshId {
+            vertices: device.register(self.vertices),
+            indices: device.register(self.indices),
+            index_type: self.index_type,
+            count: self.count,
+            center: self.center,
+        }
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe {
+            self.vertices.destroy(device.device());
+            self.indices.destroy(device.device());
+        }
+    }
+}
+
+/// A mesh whose buffers are kept by the registry of a device, see `Mesh::register`. the ids
+/// are copied freely, the device destroys the buffers with `destroy` or when it is destroyed
+/// itself.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MeshId {
+    pub vertices: gfx::BufferId,
+    pub indices: gfx::BufferId,
+    pub index_type: vk::IndexType,
+    pub count: u32,
+    pub center: Vec3,
+}
+
+impl MeshId {
+    /// destroy the buffers once the frames drawing them completed, drawing the mesh is
+    /// refused from now on.
+    pub fn destroy(&self, device: &gfx::Device) -> Result<()> {
+        device.destroy_resource(self.vertices)?;
+        device.destroy_resource(self.indices)
+    }
+}
+
+fn bounds_center(mut positions: impl Iterator<Item = Vec3>) -> Vec3 {
+    let first = match positions.next() {
+        Some(first) => first,
+        None => return Vec3::new(0.0, 0.0, 0.0),
+    };
+
+    let (min, max) = positions.fold((first, first), |(min, max), p| {
+        (
+            Vec3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+            Vec3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+        )
+    });
+
+    (min + max) * 0.5
+}
+
+/// read a mesh file into a pack of lit vertices, the extension picks the format: wavefront
+/// `.obj` with all its objects merged, `.gltf` or `.glb` with the primitives of the first mesh
+/// merged and anything else is a baked mesh pack, which keeps its vertex kind.
+pub fn read_mesh(path: &str) -> Result<MeshPack> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("obj") => read_obj(path),
+        Some("gltf" | "glb") => read_gltf_mesh(path),
+        _ => Ok(MeshPack::decode(&fs::read(path)?)?),
+    }
+}
+
+#[cfg(feature = "obj")]
+fn read_obj(path: &str) -> Result<MeshPack> {
+    let options = tobj::LoadOptions {
+        single_index: true,
+        triangulate: true,
+        ..Default::default()
+    };
+    let (models, _) = tobj::load_obj(path, &options)?;
+
+    let mut vertices = vec![];
+    let mut indices = vec![];
+    let mut normals = true;
+    for model in &models {
+        let mesh = &model.mesh;
+        let base = vertices.len() as u32;
+        let count = mesh.positions.len() / 3;
+        normals &= mesh.normals.len() == count * 3;
+        for i in 0..count {
+            let vec3 = |v: &[f32]| Vec3::new(v[i * 3], v[i * 3 + 1], v[i * 3 + 2]);
+            let normal = if mesh.normals.len() == count * 3 {
+                vec3(&mesh.normals)
+            } else {
+                Vec3::zero()
+            };
+            // obj texture coordinates start at the bottom
+            let texel = if mesh.texcoords.len() == count * 2 {
+                Vec2::new(mesh.texcoords[i * 2], 1.0 - mesh.texcoords[i * 2 + 1])
+            } else {
+                Vec2::zero()
+            };
+            vertices.push(gfx::SurfaceVertex::new(
+                vec3(&mesh.positions),
+                normal,
+                Vec4::zero(),
+                texel,
+            ));
+        }
+        indices.extend(mesh.indices.iter().map(|i| base + i));
     }
+
+    Ok(surface_pack(vertices, &indices, normals, false))
+}
+
+/// without the obj feature obj files can't be read.
+#[cfg(not(feature = "obj"))]
+fn read_obj(path: &str) -> Result<MeshPack> {
+    Err(anyhow!(
+        "Can't load {}, obj support needs the obj feature.",
+        path
+    ))
+}
+
+#[cfg(feature = "gltf")]
+fn read_gltf_mesh(path: &str) -> Result<MeshPack> {
+    let gltf = gltf::Gltf::open(path)?;
+    let buffers = read_gltf_buffers(&gltf, path)?;
+    let mesh = gltf
+        .meshes()
+        .next()
+        .ok_or_else(|| anyhow!("Missing mesh in {}.", path))?;
+
+    let mut vertices = vec![];
+    let mut indices = vec![];
+    let (mut normals, mut tangents) = (true, true);
+    for primitive in mesh.primitives() {
+        if primitive.mode() != gltf::mesh::Mode::Triangles {
+            return Err(anyhow!("Unsupported primitive mode in {}.", path));
+        }
+
+        let reader = primitive.reader(|b| buffers.get(b.index()).map(|d| &d[..]));
+        let positions = reader
+            .read_positions()
+            .ok_or_else(|| anyhow!("Missing positions in {}.", path))?
+            .collect::<Vec<_>>();
+        let count = positions.len();
+        let normal = reader.read_normals().map(|n| n.collect::<Vec<_>>());
+        let tangent = reader.read_tangents().map(|t| t.collect::<Vec<_>>());
+        let texel = reader
+            .read_tex_coords(0)
+            .map(|t| t.into_f32().collect::<Vec<_>>());
+        normals &= normal.is_some();
+        tangents &= tangent.is_some();
+
+        let base = vertices.len() as u32;
+        for (i, position) in positions.into_iter().enumerate() {
+            vertices.push(gfx::SurfaceVertex::new(
+                position.into(),
+                normal.as_ref().map_or(Vec3::zero(), |n| n[i].into()),
+                tangent.as_ref().map_or(Vec4::zero(), |t| t[i].into()),
+                texel.as_ref().map_or(Vec2::zero(), |t| t[i].into()),
+            ));
+        }
+        match reader.read_indices() {
+            Some(read) => indices.extend(read.into_u32().map(|i| base + i)),
+            None => indices.extend(base..base + count as u32),
+        }
+    }
+
+    Ok(surface_pack(vertices, &indices, normals, tangents))
+}
+
+/// without the gltf feature gltf files can't be read.
+#[cfg(not(feature = "gltf"))]
+fn read_gltf_mesh(path: &str) -> Result<MeshPack> {
+    Err(anyhow!(
+        "Can't load {}, gltf support needs the gltf feature.",
+        path
+    ))
 }
 
-// impl Default for Sampler {
-//     #[inline]
-//     fn default() -> Self {
-//         Sampler::create(vk::Sampler::null())
-//     }
-// }
+// pack lit vertices, filling in what the file didn't provide
+fn surface_pack(
+    mut vertices: Vec<gfx::SurfaceVertex>,
+    indices: &[u32],
+    normals: bool,
+    tangents: bool,
+) -> MeshPack {
+    if !normals {
+        compute_normals(&mut vertices, indices);
+    }
+    if !tangents {
+        compute_tangents(&mut vertices, indices);
+    }
+
+    let vertices = vertices
+        .iter()
+        .map(|v| deimos_format::SurfaceVertex {
+            position: Into::<[f32; 3]>::into(v.position).into(),
+            normal: Into::<[f32; 3]>::into(v.normal).into(),
+            tangent: Into::<[f32; 4]>::into(v.tangent).into(),
+            texel: Into::<[f32; 2]>::into(v.texel).into(),
+        })
+        .collect::<Vec<_>>();
+    MeshPack::from_vertices(&vertices, indices)
+}
+
+// smooth normals weighted by the area of the triangles sharing a vertex
+fn compute_normals(vertices: &mut [gfx::SurfaceVertex], indices: &[u32]) {
+    let mut normals = vec![Vec3::zero(); vertices.len()];
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [
+            triangle[0] as usize,
+            triangle[1] as usize,
+            triangle[2] as usize,
+        ];
+        let edge1 = vertices[b].position - vertices[a].position;
+        let edge2 = vertices[c].position - vertices[a].position;
+        let normal = edge1.cross(edge2);
+        for i in [a, b, c] {
+            normals[i] += normal;
+        }
+    }
+
+    for (vertex, normal) in vertices.iter_mut().zip(normals) {
+        vertex.normal = if normal.magnitude2() > 0.0 {
+            normal.normalize()
+        } else {
+            Vec3::unit_y()
+        };
+    }
+}
+
+/// fill in tangents from the texture coordinates, for meshes that come without them.
+pub fn compute_tangents(vertices: &mut [gfx::SurfaceVertex], indices: &[u32]) {
+    let mut tangents = vec![Vec3::new(0.0, 0.0, 0.0); vertices.len()];
+    let mut bitangents = vec![Vec3::new(0.0, 0.0, 0.0); vertices.len()];
+
+    // accumulate the texture space directions of every triangle
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [
+            triangle[0] as usize,
+            triangle[1] as usize,
+            triangle[2] as usize,
+        ];
+        let edge1 = vertices[b].position - vertices[a].position;
+        let edge2 = vertices[c].position - vertices[a].position;
+        let delta1 = vertices[b].texel - vertices[a].texel;
+        let delta2 = vertices[c].texel - vertices[a].texel;
+
+        // degenerate texture mapping
+        let determinant = delta1.x * delta2.y - delta2.x * delta1.y;
+        if determinant.abs() < f32::EPSILON {
+            continue;
+        }
+
+        let r = 1.0 / determinant;
+        let tangent = (edge1 * delta2.y - edge2 * delta1.y) * r;
+        let bitangent = (edge2 * delta1.x - edge1 * delta2.x) * r;
+
+        for i in [a, b, c] {
+            tangents[i] += tangent;
+            bitangents[i] += bitangent;
+        }
+    }
+
+    // orthogonalize against the normal and store the handedness
+    for (i, vertex) in vertices.iter_mut().enumerate() {
+        let n = vertex.normal;
+        let t = tangents[i] - n * n.dot(tangents[i]);
+        let t = if t.magnitude2() > 0.0 {
+            t.normalize()
+        } else {
+            // any direction perpendicular to the normal will do
+            let axis = if n.x.abs() < 0.9 {
+                Vec3::unit_x()
+            } else {
+                Vec3::unit_y()
+            };
+            n.cross(axis).normalize()
+        };
+        let w = if n.cross(t).dot(bitangents[i]) < 0.0 {
+            -1.0
+        } else {
+            1.0
+        };
+
+        vertex.tangent = t.extend(w);
+    }
+}
 
 impl fmt::Debug for Mesh {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
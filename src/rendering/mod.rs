@@ -1,7 +1,65 @@
+mod animation;
+mod assets;
+mod bloom;
+mod camera;
+mod clock;
+mod curves;
+mod exposure;
+mod fxaa;
+mod ibl;
+mod image_passes;
+mod lights;
+mod lines;
 mod material;
 mod mesh;
+mod particle_system;
+mod particles;
+mod pbr;
+mod post;
+mod primitives;
+mod probe;
+mod render_target;
 mod renderer;
+mod scene;
+mod screen;
+mod sprites;
+mod stereo;
+mod tonemap;
+mod transform_sync;
+mod variants;
+mod video;
+mod vignette;
+mod watch;
 
+pub use self::animation::*;
+pub use self::assets::*;
+pub use self::bloom::*;
+pub use self::camera::*;
+pub use self::clock::*;
+pub use self::curves::*;
+pub use self::exposure::*;
+pub use self::fxaa::*;
+pub use self::ibl::*;
+pub use self::image_passes::*;
+pub use self::lights::*;
+pub use self::lines::*;
 pub use self::material::*;
 pub use self::mesh::*;
-pub use self::renderer::*;
\ No newline at end of file
+pub use self::particle_system::*;
+pub use self::particles::*;
+pub use self::pbr::*;
+pub use self::post::*;
+pub use self::primitives::*;
+pub use self::probe::*;
+pub use self::render_target::*;
+pub use self::renderer::*;
+pub use self::scene::*;
+pub use self::screen::*;
+pub use self::sprites::*;
+pub use self::stereo::*;
+pub use self::tonemap::*;
+pub use self::transform_sync::*;
+pub use self::variants::*;
+pub use self::video::*;
+pub use self::vignette::*;
+pub use self::watch::*;
@@ -0,0 +1,727 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use std::cell::Cell;
+use std::mem::size_of;
+
+use anyhow::{anyhow, Result};
+use tracing::debug;
+use vulkanalia::prelude::v1_0::*;
+
+use super::{Clock, TimeChannel};
+use crate::gfx;
+use crate::math::{Mat4, Vec3, Vec4};
+
+/// How the particles of a system are composited.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ParticleBlend {
+    /// adds light, the order doesn't matter.
+    Additive,
+    /// premultiplied alpha, particles are drawn in the order they were spawned.
+    Alpha,
+}
+
+/// Where, how often and how particles are spawned and how they look over their life.
+/// Ranges are sampled uniformly per particle.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ParticleEmitter {
+    pub position: Vec3,
+    /// the half extents of the box particles spawn in, around `position`.
+    pub spread: Vec3,
+    /// particles spawned per second.
+    pub rate: f32,
+    /// the lifetime in seconds.
+    pub lifetime_min: f32,
+    pub lifetime_max: f32,
+    /// the initial velocity of each axis.
+    pub velocity_min: Vec3,
+    pub velocity_max: Vec3,
+    /// the acceleration applied to all particles.
+    pub gravity: Vec3,
+    /// the fraction of velocity lost per second.
+    pub drag: f32,
+    /// the world size of the billboard at the start and end of the lifetime.
+    pub size_start: f32,
+    pub size_end: f32,
+    /// straight alpha colors at the start and end of the lifetime.
+    pub color_start: Vec4,
+    pub color_end: Vec4,
+    pub blend: ParticleBlend,
+}
+
+impl ParticleEmitter {
+    pub fn new(position: Vec3, rate: f32) -> Self {
+        Self {
+            position,
+            rate,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        Self {
+            position: Vec3::new(0.0, 0.0, 0.0),
+            spread: Vec3::new(0.0, 0.0, 0.0),
+            rate: 10.0,
+            lifetime_min: 1.0,
+            lifetime_max: 2.0,
+            velocity_min: Vec3::new(-0.2, 0.5, -0.2),
+            velocity_max: Vec3::new(0.2, 1.0, 0.2),
+            gravity: Vec3::new(0.0, 0.0, 0.0),
+            drag: 0.0,
+            size_start: 0.1,
+            size_end: 0.0,
+            color_start: Vec4::new(1.0, 1.0, 1.0, 1.0),
+            color_end: Vec4::new(1.0, 1.0, 1.0, 0.0),
+            blend: ParticleBlend::Additive,
+        }
+    }
+}
+
+/// The emitter as seen by the shaders, std140.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct EmitterData {
+    origin: Vec4,
+    spread: Vec4,
+    // xyz velocity and w lifetime
+    velocity_min: Vec4,
+    velocity_max: Vec4,
+    // xyz gravity and w drag
+    gravity: Vec4,
+    color_start: Vec4,
+    color_end: Vec4,
+    // start size, end size and time step
+    size: Vec4,
+    // spawn count, capacity and seed
+    counts: [u32; 4],
+}
+
+crate::pod!(EmitterData {
+    origin: Vec4,
+    spread: Vec4,
+    velocity_min: Vec4,
+    velocity_max: Vec4,
+    gravity: Vec4,
+    color_start: Vec4,
+    color_end: Vec4,
+    size: Vec4,
+    counts: [u32; 4],
+});
+
+/// One particle in the storage buffers, must match the shaders.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct GpuParticle {
+    // xyz position and w age
+    position: Vec4,
+    // xyz velocity and w lifetime
+    velocity: Vec4,
+}
+
+/// The indirect draw the update writes, the instance count is the number of particles alive.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct Counter {
+    vertex_count: u32,
+    instance_count: u32,
+    first_vertex: u32,
+    first_instance: u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct ParticleSystemPushConstants {
+    view_proj: Mat4,
+    // the camera axes in world space
+    right: Vec4,
+    up: Vec4,
+}
+
+/// Particles simulated on the gpu, spawned by an emitter and drawn as camera facing spots.
+/// Every frame `update` advances the emitter, then `simulate` is recorded outside of a render
+/// pass, see `Device::update_with_prepare`, and `record` in the scene pass. The simulation
+/// compacts the survivors so the draw covers exactly the particles alive.
+pub struct ParticleSystem {
+    pub emitter: ParticleEmitter,
+    /// the simulation rate, 1 is real time and 0 freezes the particles.
+    pub speed: f32,
+    /// the clock channel `tick` follows.
+    pub channel: TimeChannel,
+    capacity: usize,
+    // fractional particles carried over to the next frame
+    pending: f32,
+    // the particles spawned and the time step of the next simulation
+    spawn: u32,
+    delta: f32,
+    seed: u32,
+    // the buffer holding the current particles, flipped by each recorded simulation
+    current: Cell<usize>,
+    particles: [gfx::Buffer; 2],
+    counter: gfx::Buffer,
+    // the emitter data of each swapchain image
+    uniforms: Vec<gfx::Buffer>,
+    descriptors: gfx::DescriptorSets,
+    update_layout: vk::PipelineLayout,
+    update_pipeline: vk::Pipeline,
+    draw_layout: vk::PipelineLayout,
+    additive_pipeline: vk::Pipeline,
+    alpha_pipeline: vk::Pipeline,
+}
+
+impl ParticleSystem {
+    /// create a system for at most `capacity` particles alive at once.
+    pub fn create(device: &gfx::Device, emitter: ParticleEmitter, capacity: usize) -> Result<Self> {
+        unsafe {
+            let vk_device = device.device();
+            let capacity = capacity.max(1);
+
+            // particles live on the gpu only
+            let size = (size_of::<GpuParticle>() * capacity) as vk::DeviceSize;
+            let particles = [
+                create_storage_buffer(device, size, vk::BufferUsageFlags::empty())?,
+                create_storage_buffer(device, size, vk::BufferUsageFlags::empty())?,
+            ];
+
+            // nothing is alive initially
+            let counter = create_storage_buffer(
+                device,
+                size_of::<Counter>() as vk::DeviceSize,
+                vk::BufferUsageFlags::INDIRECT_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
+            )?;
+            device.execute(|_, command_buffer| {
+                vk_device.cmd_fill_buffer(command_buffer, counter.buffer, 0, counter.size, 0);
+                Ok(())
+            })?;
+
+            let uniforms = (0..device.image_count())
+                .map(|_| create_uniform_buffer(device))
+                .collect::<Result<Vec<_>>>()?;
+
+            // both pipelines use the same set
+            let descriptors = create_descriptors(vk_device)?;
+            let (update_layout, update_pipeline) = create_update_pipeline(device, &descriptors)?;
+            device.set_name(update_pipeline, "particle update");
+            let draw_layout = create_draw_layout(device, &descriptors)?;
+            let additive_pipeline =
+                create_draw_pipeline(device, draw_layout, ParticleBlend::Additive)?;
+            device.set_name(additive_pipeline, "particles additive");
+            let alpha_pipeline = create_draw_pipeline(device, draw_layout, ParticleBlend::Alpha)?;
+            device.set_name(alpha_pipeline, "particles alpha");
+
+            Ok(Self {
+                emitter,
+                speed: 1.0,
+                channel: TimeChannel::Game,
+                capacity,
+                pending: 0.0,
+                spawn: 0,
+                delta: 0.0,
+                seed: 0,
+                current: Cell::new(0),
+                particles,
+                counter,
+                uniforms,
+                descriptors,
+                update_layout,
+                update_pipeline,
+                draw_layout,
+                additive_pipeline,
+                alpha_pipeline,
+            })
+        }
+    }
+
+    /// the maximum number of particles alive at once.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// advance the emitter by the step of its channel.
+    pub fn tick(&mut self, device: &gfx::Device, clock: &Clock) -> Result<()> {
+        self.update(device, clock.delta(self.channel))
+    }
+
+    /// advance the emitter by `delta` seconds scaled by the speed, the next `simulate` steps
+    /// the particles as far.
+    pub fn update(&mut self, device: &gfx::Device, delta: f32) -> Result<()> {
+        unsafe {
+            let vk_device = device.device();
+
+            // a recreated swapchain may have more images
+            while self.uniforms.len() < device.image_count() {
+                self.uniforms.push(create_uniform_buffer(device)?);
+            }
+            self.descriptors.get(vk_device, device.image_count() - 1)?;
+        }
+
+        // spawn whole particles only, the rest is kept for later frames
+        let delta = delta * self.speed.max(0.0);
+        self.pending += self.emitter.rate.max(0.0) * delta.max(0.0);
+        let spawn = (self.pending.floor() as usize).min(self.capacity);
+        self.pending = (self.pending - spawn as f32).min(self.capacity as f32);
+
+        self.spawn = spawn as u32;
+        self.delta = delta.max(0.0);
+        self.seed = self.seed.wrapping_add(self.spawn + 1);
+
+        Ok(())
+    }
+
+    /// record the simulation of the last `update`, must be called outside of a render pass
+    /// before `record`.
+    pub fn simulate(
+        &self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+    ) -> Result<()> {
+        unsafe {
+            let vk_device = device.device();
+            let set = self.set(index)?;
+            let delta = self.delta;
+
+            // upload the emitter, the image fence has been waited on already
+            let emitter = &self.emitter;
+            let data = EmitterData {
+                origin: emitter.position.extend(0.0),
+                spread: emitter.spread.extend(0.0),
+                velocity_min: emitter.velocity_min.extend(emitter.lifetime_min),
+                velocity_max: emitter.velocity_max.extend(emitter.lifetime_max),
+                gravity: emitter.gravity.extend(emitter.drag),
+                color_start: emitter.color_start,
+                color_end: emitter.color_end,
+                size: Vec4::new(emitter.size_start, emitter.size_end, delta, 0.0),
+                counts: [self.spawn, self.capacity as u32, self.seed, 0],
+            };
+            self.uniforms[index].write(vk_device, 0, &[data])?;
+
+            // read the current particles, write the next ones
+            let current = self.current.get();
+            let source = self.particles[current];
+            let target = self.particles[1 - current];
+            self.current.set(1 - current);
+
+            write_set(
+                vk_device,
+                set,
+                &self.uniforms[index],
+                &source,
+                &target,
+                &self.counter,
+            );
+
+            // the previous frame must be done drawing and updating
+            let barrier = vk::MemoryBarrier::builder()
+                .src_access_mask(
+                    vk::AccessFlags::SHADER_READ
+                        | vk::AccessFlags::SHADER_WRITE
+                        | vk::AccessFlags::INDIRECT_COMMAND_READ,
+                )
+                .dst_access_mask(vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE);
+            vk_device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::DRAW_INDIRECT
+                    | vk::PipelineStageFlags::VERTEX_SHADER
+                    | vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::DependencyFlags::empty(),
+                &[barrier],
+                &[] as &[vk::BufferMemoryBarrier],
+                &[] as &[vk::ImageMemoryBarrier],
+            );
+
+            // a single group updates, compacts and spawns
+            debug!(pipeline = ?self.update_pipeline, "bind pipeline");
+            vk_device.cmd_bind_pipeline(
+                command_buffer,
+                vk::PipelineBindPoint::COMPUTE,
+                self.update_pipeline,
+            );
+            self.descriptors.bind(
+                vk_device,
+                command_buffer,
+                vk::PipelineBindPoint::COMPUTE,
+                self.update_layout,
+                set,
+            );
+            debug!(x = 1, y = 1, z = 1, "dispatch");
+            vk_device.cmd_dispatch(command_buffer, 1, 1, 1);
+
+            // the draw reads the particles and its count back
+            gfx::buffer_barrier(
+                vk_device,
+                command_buffer,
+                &[target.buffer, self.counter.buffer],
+                &[gfx::BufferAccess::ComputeWrite],
+                &[
+                    gfx::BufferAccess::Indirect,
+                    gfx::BufferAccess::VertexShaderRead,
+                ],
+            );
+
+            Ok(())
+        }
+    }
+
+    /// record the particles alive after `simulate`, must be called in the scene pass of the
+    /// same frame.
+    pub fn record(
+        &self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        view: Mat4,
+        proj: Mat4,
+    ) -> Result<()> {
+        unsafe {
+            let vk_device = device.device();
+            let extent = device.extent();
+
+            // the set written by `simulate` points at the current particles
+            let set = self.set(index)?;
+
+            // viewport and scissor are dynamic, the scissor follows the damage
+            let viewport = vk::Viewport::builder()
+                .x(0.0)
+                .y(0.0)
+                .width(extent.width as f32)
+                .height(extent.height as f32)
+                .min_depth(0.0)
+                .max_depth(1.0);
+            let scissor = device.scissor();
+
+            let pipeline = match self.emitter.blend {
+                ParticleBlend::Additive => self.additive_pipeline,
+                ParticleBlend::Alpha => self.alpha_pipeline,
+            };
+            debug!(?pipeline, "bind pipeline");
+            vk_device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
+            vk_device.cmd_set_viewport(command_buffer, 0, &[viewport]);
+            vk_device.cmd_set_scissor(command_buffer, 0, &[scissor]);
+            self.descriptors.bind(
+                vk_device,
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.draw_layout,
+                set,
+            );
+
+            // the rows of the view rotation are the camera axes in world space
+            let constants = ParticleSystemPushConstants {
+                view_proj: proj * view,
+                right: Vec4::new(view.x.x, view.y.x, view.z.x, 0.0),
+                up: Vec4::new(view.x.y, view.y.y, view.z.y, 0.0),
+            };
+
+            let bytes = std::slice::from_raw_parts(
+                &constants as *const ParticleSystemPushConstants as *const u8,
+                size_of::<ParticleSystemPushConstants>(),
+            );
+
+            vk_device.cmd_push_constants(
+                command_buffer,
+                self.draw_layout,
+                vk::ShaderStageFlags::VERTEX,
+                0,
+                bytes,
+            );
+
+            // the update wrote the instance count
+            debug!(buffer = ?self.counter.buffer, draws = 1, "draw indirect");
+            vk_device.cmd_draw_indirect(
+                command_buffer,
+                self.counter.buffer,
+                0,
+                1,
+                size_of::<Counter>() as u32,
+            );
+
+            Ok(())
+        }
+    }
+
+    /// the set of an image, allocated by `update`.
+    fn set(&self, index: usize) -> Result<vk::DescriptorSet> {
+        self.descriptors
+            .allocated(index)
+            .ok_or_else(|| anyhow!("Particle system was not updated for image {}.", index))
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe {
+            let vk_device = device.device();
+
+            // destroy buffers
+            self.particles.iter().for_each(|b| b.destroy(vk_device));
+            self.counter.destroy(vk_device);
+            self.uniforms.iter().for_each(|b| b.destroy(vk_device));
+
+            // destroy pipelines
+            vk_device.destroy_pipeline(self.update_pipeline, None);
+            vk_device.destroy_pipeline_layout(self.update_layout, None);
+            vk_device.destroy_pipeline(self.additive_pipeline, None);
+            vk_device.destroy_pipeline(self.alpha_pipeline, None);
+            vk_device.destroy_pipeline_layout(self.draw_layout, None);
+
+            // destroy descriptors
+            self.descriptors.destroy(vk_device);
+        }
+    }
+}
+
+unsafe fn create_storage_buffer(
+    device: &gfx::Device,
+    size: vk::DeviceSize,
+    usage: vk::BufferUsageFlags,
+) -> Result<gfx::Buffer> {
+    device.create_buffer_for(size, vk::BufferUsageFlags::STORAGE_BUFFER | usage)
+}
+
+unsafe fn create_uniform_buffer(device: &gfx::Device) -> Result<gfx::Buffer> {
+    device.create_buffer_for(
+        size_of::<EmitterData>() as vk::DeviceSize,
+        vk::BufferUsageFlags::UNIFORM_BUFFER,
+    )
+}
+
+unsafe fn create_descriptors(device: &vulkanalia::Device) -> Result<gfx::DescriptorSets> {
+    let binding = |binding: u32, descriptor_type: vk::DescriptorType, stages| {
+        vk::DescriptorSetLayoutBinding::builder()
+            .binding(binding)
+            .descriptor_type(descriptor_type)
+            .descriptor_count(1)
+            .stage_flags(stages)
+            .build()
+    };
+
+    let both = vk::ShaderStageFlags::COMPUTE | vk::ShaderStageFlags::VERTEX;
+    let bindings = &[
+        binding(0, vk::DescriptorType::UNIFORM_BUFFER, both),
+        binding(
+            1,
+            vk::DescriptorType::STORAGE_BUFFER,
+            vk::ShaderStageFlags::COMPUTE,
+        ),
+        binding(2, vk::DescriptorType::STORAGE_BUFFER, both),
+        binding(
+            3,
+            vk::DescriptorType::STORAGE_BUFFER,
+            vk::ShaderStageFlags::COMPUTE,
+        ),
+    ];
+
+    gfx::DescriptorSets::create(device, gfx::DescriptorSlot::Object, bindings)
+}
+
+unsafe fn write_set(
+    device: &vulkanalia::Device,
+    set: vk::DescriptorSet,
+    uniform: &gfx::Buffer,
+    source: &gfx::Buffer,
+    target: &gfx::Buffer,
+    counter: &gfx::Buffer,
+) {
+    let info = |buffer: &gfx::Buffer| {
+        [vk::DescriptorBufferInfo::builder()
+            .buffer(buffer.buffer)
+            .offset(0)
+            .range(buffer.size)
+            .build()]
+    };
+    let uniform_info = info(uniform);
+    let source_info = info(source);
+    let target_info = info(target);
+    let counter_info = info(counter);
+
+    let write = |binding: u32, descriptor_type: vk::DescriptorType, info| {
+        vk::WriteDescriptorSet::builder()
+            .dst_set(set)
+            .dst_binding(binding)
+            .descriptor_type(descriptor_type)
+            .buffer_info(info)
+            .build()
+    };
+
+    let writes = &[
+        write(0, vk::DescriptorType::UNIFORM_BUFFER, &uniform_info),
+        write(1, vk::DescriptorType::STORAGE_BUFFER, &source_info),
+        write(2, vk::DescriptorType::STORAGE_BUFFER, &target_info),
+        write(3, vk::DescriptorType::STORAGE_BUFFER, &counter_info),
+    ];
+
+    device.update_descriptor_sets(writes, &[] as &[vk::CopyDescriptorSet]);
+}
+
+unsafe fn create_update_pipeline(
+    device: &gfx::Device,
+    descriptors: &gfx::DescriptorSets,
+) -> Result<(vk::PipelineLayout, vk::Pipeline)> {
+    let vk_device = device.device();
+
+    // Layout
+    let pipeline_layout = device.create_pipeline_layout(&[descriptors], &[])?;
+
+    // Stages
+    let code = include_bytes!("../../shaders/particle_update_comp.spv");
+    let shader = gfx::Shader::create(vk_device, &code[..])?;
+    let stage = vk::PipelineShaderStageCreateInfo::builder()
+        .stage(vk::ShaderStageFlags::COMPUTE)
+        .module(shader.module)
+        .name(b"main\0");
+
+    // Create
+    let info = vk::ComputePipelineCreateInfo::builder()
+        .stage(stage)
+        .layout(pipeline_layout);
+    let pipeline = vk_device
+        .create_compute_pipelines(vk::PipelineCache::null(), &[info], None)?
+        .0[0];
+
+    // clean up
+    shader.destroy(vk_device);
+
+    Ok((pipeline_layout, pipeline))
+}
+
+unsafe fn create_draw_layout(
+    device: &gfx::Device,
+    descriptors: &gfx::DescriptorSets,
+) -> Result<vk::PipelineLayout> {
+    let push_constant_range = vk::PushConstantRange::builder()
+        .stage_flags(vk::ShaderStageFlags::VERTEX)
+        .offset(0)
+        .size(size_of::<ParticleSystemPushConstants>() as u32);
+
+    device.create_pipeline_layout(&[descriptors], &[push_constant_range.build()])
+}
+
+unsafe fn create_draw_pipeline(
+    device: &gfx::Device,
+    pipeline_layout: vk::PipelineLayout,
+    blend: ParticleBlend,
+) -> Result<vk::Pipeline> {
+    let vk_device = device.device();
+
+    // Stages
+
+    let vert = include_bytes!("../../shaders/particle_system_vert.spv");
+    let frag = include_bytes!("../../shaders/particle_system_frag.spv");
+
+    let vertex_shader = gfx::Shader::create(vk_device, &vert[..])?;
+    let fragment_shader = gfx::Shader::create(vk_device, &frag[..])?;
+
+    let vert_stage = vk::PipelineShaderStageCreateInfo::builder()
+        .stage(vk::ShaderStageFlags::VERTEX)
+        .module(vertex_shader.module)
+        .name(b"main\0");
+
+    let frag_stage = vk::PipelineShaderStageCreateInfo::builder()
+        .stage(vk::ShaderStageFlags::FRAGMENT)
+        .module(fragment_shader.module)
+        .name(b"main\0");
+
+    // Vertex Input State, particles are read from the storage buffer
+
+    let vertex_input_state = vk::PipelineVertexInputStateCreateInfo::builder();
+
+    // Input Assembly State
+
+    let input_assembly_state = vk::PipelineInputAssemblyStateCreateInfo::builder()
+        .topology(vk::PrimitiveTopology::TRIANGLE_LIST)
+        .primitive_restart_enable(false);
+
+    // Viewport State, set when recording
+
+    let viewport_state = vk::PipelineViewportStateCreateInfo::builder()
+        .viewport_count(1)
+        .scissor_count(1);
+
+    let dynamic_states = &[vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+    let dynamic_state =
+        vk::PipelineDynamicStateCreateInfo::builder().dynamic_states(dynamic_states);
+
+    // Rasterization State, billboards always face the camera
+
+    let rasterization_state = vk::PipelineRasterizationStateCreateInfo::builder()
+        .depth_clamp_enable(false)
+        .rasterizer_discard_enable(false)
+        .polygon_mode(vk::PolygonMode::FILL)
+        .line_width(1.0)
+        .cull_mode(vk::CullModeFlags::NONE)
+        .front_face(vk::FrontFace::COUNTER_CLOCKWISE)
+        .depth_bias_enable(false);
+
+    // Multisample State
+
+    let multisample_state = vk::PipelineMultisampleStateCreateInfo::builder()
+        .sample_shading_enable(false)
+        .rasterization_samples(device.samples());
+
+    // Depth Stencil State, particles are tested but do not occlude
+
+    let depth_stencil_state = vk::PipelineDepthStencilStateCreateInfo::builder()
+        .depth_test_enable(true)
+        .depth_write_enable(false)
+        .depth_compare_op(vk::CompareOp::LESS_OR_EQUAL)
+        .depth_bounds_test_enable(false)
+        .stencil_test_enable(false);
+
+    // Color Blend State, the shaders output premultiplied alpha
+
+    let dst_color_blend_factor = match blend {
+        ParticleBlend::Additive => vk::BlendFactor::ONE,
+        ParticleBlend::Alpha => vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+    };
+
+    let attachment = vk::PipelineColorBlendAttachmentState::builder()
+        .color_write_mask(vk::ColorComponentFlags::all())
+        .blend_enable(true)
+        .src_color_blend_factor(vk::BlendFactor::ONE)
+        .dst_color_blend_factor(dst_color_blend_factor)
+        .color_blend_op(vk::BlendOp::ADD)
+        .src_alpha_blend_factor(vk::BlendFactor::ONE)
+        .dst_alpha_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+        .alpha_blend_op(vk::BlendOp::ADD);
+
+    let attachments = &[attachment];
+    let color_blend_state = vk::PipelineColorBlendStateCreateInfo::builder()
+        .logic_op_enable(false)
+        .logic_op(vk::LogicOp::COPY)
+        .attachments(attachments)
+        .blend_constants([0.0, 0.0, 0.0, 0.0]);
+
+    // Create
+
+    let stages = &[vert_stage, frag_stage];
+    let info = vk::GraphicsPipelineCreateInfo::builder()
+        .stages(stages)
+        .vertex_input_state(&vertex_input_state)
+        .input_assembly_state(&input_assembly_state)
+        .viewport_state(&viewport_state)
+        .rasterization_state(&rasterization_state)
+        .multisample_state(&multisample_state)
+        .depth_stencil_state(&depth_stencil_state)
+        .color_blend_state(&color_blend_state)
+        .dynamic_state(&dynamic_state)
+        .layout(pipeline_layout)
+        .render_pass(device.render_pass())
+        .subpass(0);
+
+    let pipeline = vk_device
+        .create_graphics_pipelines(vk::PipelineCache::null(), &[info], None)?
+        .0[0];
+
+    // clean up
+    vertex_shader.destroy(vk_device);
+    fragment_shader.destroy(vk_device);
+
+    Ok(pipeline)
+}
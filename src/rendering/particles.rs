@@ -0,0 +1,571 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use std::mem::size_of;
+
+use anyhow::Result;
+use tracing::debug;
+use vulkanalia::prelude::v1_0::*;
+
+use super::SpriteTexture;
+use crate::gfx;
+use crate::math::{Mat4, Vec2, Vec3, Vec4};
+
+// The number of particles the per-image buffers can hold initially.
+const INITIAL_CAPACITY: usize = 1024;
+
+// The scene depth is read texel by texel.
+const DEPTH_SAMPLER: gfx::SamplerDescriptor = gfx::SamplerDescriptor {
+    mag_filter: vk::Filter::NEAREST,
+    min_filter: vk::Filter::NEAREST,
+    ..gfx::SamplerDescriptor::linear(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+};
+
+/// How particles of a texture are drawn.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ParticleMaterial {
+    pub texture: vk::ImageView,
+    pub alpha: gfx::AlphaMode,
+    /// the distance in world units over which particles fade out in front of geometry,
+    /// 0 draws hard edges where particles intersect.
+    pub softness: f32,
+}
+
+impl ParticleMaterial {
+    pub fn new(texture: &SpriteTexture, softness: f32) -> Self {
+        Self {
+            texture: texture.view.view,
+            alpha: texture.alpha,
+            softness: softness.max(0.0),
+        }
+    }
+}
+
+/// A textured quad facing the camera, sized in world units.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Particle {
+    pub position: Vec3,
+    /// rotation around the view direction in radians.
+    pub rotation: f32,
+    pub size: Vec2,
+    /// straight alpha tint multiplied with the texture.
+    pub color: Vec4,
+}
+
+impl Particle {
+    pub fn new(position: Vec3, size: f32) -> Self {
+        Self {
+            position,
+            rotation: 0.0,
+            size: Vec2::new(size, size),
+            color: Vec4::new(1.0, 1.0, 1.0, 1.0),
+        }
+    }
+
+    pub fn rotated(self, rotation: f32) -> Self {
+        Self { rotation, ..self }
+    }
+
+    pub fn tinted(self, color: Vec4) -> Self {
+        Self { color, ..self }
+    }
+}
+
+/// One instance of the particle pipeline.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct ParticleInstance {
+    // position and rotation
+    placement: Vec4,
+    // size
+    extent: Vec4,
+    color: Vec4,
+}
+
+crate::pod!(ParticleInstance {
+    placement: Vec4,
+    extent: Vec4,
+    color: Vec4,
+});
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct ParticlePushConstants {
+    view_proj: Mat4,
+    // the camera axes in world space
+    right: Vec4,
+    up: Vec4,
+    // turns a depth buffer value into a view distance, see `depth_params`
+    depth: Vec2,
+    softness: f32,
+    straight: u32,
+}
+
+/// A range of particles drawn with the same material.
+#[derive(Copy, Clone, Debug)]
+struct ParticleBatch {
+    first: u32,
+    count: u32,
+    material: ParticleMaterial,
+}
+
+/// Renders camera facing particles in draw order, fading them out near the scene depth.
+/// Particles read the depth of the opaque scene, so they are recorded after
+/// `Device::begin_transparent_pass`.
+pub struct ParticleRenderer {
+    sampler: vk::Sampler,
+    depth_sampler: vk::Sampler,
+    pipeline_layout: vk::PipelineLayout,
+    pipeline: vk::Pipeline,
+    // the scene depth, one set per swapchain image
+    depth_descriptors: gfx::DescriptorSets,
+    // one descriptor set per batch and swapchain image
+    descriptors: Vec<gfx::DescriptorSets>,
+    buffers: Vec<gfx::Buffer>,
+    instances: Vec<ParticleInstance>,
+    batches: Vec<ParticleBatch>,
+}
+
+impl ParticleRenderer {
+    pub fn create(device: &gfx::Device) -> Result<Self> {
+        unsafe {
+            let vk_device = device.device();
+
+            // create the samplers and a first material slot to take the layout from
+            let sampler = device.sampler(&gfx::SamplerDescriptor::linear(
+                vk::SamplerAddressMode::CLAMP_TO_EDGE,
+            ))?;
+            let depth_sampler = device.sampler(&DEPTH_SAMPLER)?;
+            let depth_descriptors = create_depth_descriptors(vk_device)?;
+            let descriptors = vec![create_descriptors(vk_device)?];
+
+            // create pipeline objects
+            let (pipeline_layout, pipeline) =
+                create_pipeline(device, &depth_descriptors, &descriptors[0])?;
+            device.set_name(pipeline, "particles");
+
+            // create one instance buffer per swapchain image
+            let buffers = (0..device.image_count())
+                .map(|_| create_instance_buffer(device, INITIAL_CAPACITY))
+                .collect::<Result<Vec<_>>>()?;
+
+            Ok(Self {
+                sampler,
+                depth_sampler,
+                pipeline_layout,
+                pipeline,
+                depth_descriptors,
+                descriptors,
+                buffers,
+                instances: vec![],
+                batches: vec![],
+            })
+        }
+    }
+
+    /// queue a particle for the next recorded frame, particles are drawn in queue order.
+    pub fn draw(&mut self, material: &ParticleMaterial, particle: &Particle) {
+        let first = self.instances.len() as u32;
+
+        self.instances.push(ParticleInstance {
+            placement: particle.position.extend(particle.rotation),
+            extent: Vec4::new(particle.size.x, particle.size.y, 0.0, 0.0),
+            color: particle.color,
+        });
+
+        // extend the last batch when the material stays the same
+        match self.batches.last_mut() {
+            Some(batch) if batch.material == *material => batch.count += 1,
+            _ => self.batches.push(ParticleBatch {
+                first,
+                count: 1,
+                material: *material,
+            }),
+        }
+    }
+
+    /// record all queued particles and clear the queue, must be called in the transparent pass.
+    pub fn record(
+        &mut self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        view: Mat4,
+        proj: Mat4,
+    ) -> Result<()> {
+        unsafe {
+            // nothing queued
+            if self.instances.is_empty() {
+                self.batches.clear();
+                return Ok(());
+            }
+
+            let vk_device = device.device();
+
+            // a recreated swapchain may have more images
+            while self.buffers.len() <= index {
+                self.buffers
+                    .push(create_instance_buffer(device, INITIAL_CAPACITY)?);
+            }
+
+            // grow the image buffer if needed, the image fence has been waited on already
+            let size = (size_of::<ParticleInstance>() * self.instances.len()) as vk::DeviceSize;
+            if self.buffers[index].size < size {
+                self.buffers[index].destroy(vk_device);
+                self.buffers[index] =
+                    create_instance_buffer(device, self.instances.len().next_power_of_two())?;
+            }
+
+            // upload the instances
+            let buffer = &self.buffers[index];
+            buffer.write(vk_device, 0, &self.instances)?;
+
+            // every batch needs its own set
+            while self.descriptors.len() < self.batches.len() {
+                self.descriptors.push(create_descriptors(vk_device)?);
+            }
+
+            // the depth view changes with the swapchain
+            let depth_set = self.depth_descriptors.get(vk_device, index)?;
+            write_set(
+                vk_device,
+                depth_set,
+                device.depth_view(),
+                vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL,
+                self.depth_sampler,
+            );
+
+            let extent = device.extent();
+
+            // viewport and scissor are dynamic, the scissor follows the damage
+            let viewport = vk::Viewport::builder()
+                .x(0.0)
+                .y(0.0)
+                .width(extent.width as f32)
+                .height(extent.height as f32)
+                .min_depth(0.0)
+                .max_depth(1.0);
+            let scissor = device.scissor();
+
+            debug!(pipeline = ?self.pipeline, "bind pipeline");
+            vk_device.cmd_bind_pipeline(
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.pipeline,
+            );
+            vk_device.cmd_set_viewport(command_buffer, 0, &[viewport]);
+            vk_device.cmd_set_scissor(command_buffer, 0, &[scissor]);
+            vk_device.cmd_bind_vertex_buffers(command_buffer, 0, &[buffer.buffer], &[0]);
+            self.depth_descriptors.bind(
+                vk_device,
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.pipeline_layout,
+                depth_set,
+            );
+
+            // the rows of the view rotation are the camera axes in world space
+            let right = Vec4::new(view.x.x, view.y.x, view.z.x, 0.0);
+            let up = Vec4::new(view.x.y, view.y.y, view.z.y, 0.0);
+            let view_proj = proj * view;
+            let depth = depth_params(proj);
+
+            for (slot, batch) in self.batches.iter().enumerate() {
+                // point the batch set at its texture
+                let set = self.descriptors[slot].get(vk_device, index)?;
+                write_set(
+                    vk_device,
+                    set,
+                    batch.material.texture,
+                    vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                    self.sampler,
+                );
+
+                self.descriptors[slot].bind(
+                    vk_device,
+                    command_buffer,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    self.pipeline_layout,
+                    set,
+                );
+
+                let constants = ParticlePushConstants {
+                    view_proj,
+                    right,
+                    up,
+                    depth,
+                    softness: batch.material.softness,
+                    straight: (batch.material.alpha == gfx::AlphaMode::Straight) as u32,
+                };
+
+                let bytes = std::slice::from_raw_parts(
+                    &constants as *const ParticlePushConstants as *const u8,
+                    size_of::<ParticlePushConstants>(),
+                );
+
+                vk_device.cmd_push_constants(
+                    command_buffer,
+                    self.pipeline_layout,
+                    vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+                    0,
+                    bytes,
+                );
+
+                // six vertices per particle quad
+                debug!(vertices = 6, instances = batch.count, "draw");
+                vk_device.cmd_draw(command_buffer, 6, batch.count, 0, batch.first);
+            }
+
+            // start over for the next frame
+            self.instances.clear();
+            self.batches.clear();
+
+            Ok(())
+        }
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe {
+            let vk_device = device.device();
+
+            // destroy buffers
+            self.buffers.iter().for_each(|b| b.destroy(vk_device));
+
+            // destroy pipeline
+            vk_device.destroy_pipeline(self.pipeline, None);
+            vk_device.destroy_pipeline_layout(self.pipeline_layout, None);
+
+            // destroy descriptors and samplers
+            self.descriptors.iter().for_each(|d| d.destroy(vk_device));
+            self.depth_descriptors.destroy(vk_device);
+        }
+    }
+}
+
+/// the terms turning a depth buffer value `d` into the view distance `y / (d + x)`,
+/// valid for any perspective projection with a 0..1 depth range.
+fn depth_params(proj: Mat4) -> Vec2 {
+    Vec2::new(proj.z.z, proj.w.z)
+}
+
+fn image_bindings() -> [vk::DescriptorSetLayoutBinding; 2] {
+    [
+        vk::DescriptorSetLayoutBinding::builder()
+            .binding(0)
+            .descriptor_type(vk::DescriptorType::SAMPLED_IMAGE)
+            .descriptor_count(1)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build(),
+        vk::DescriptorSetLayoutBinding::builder()
+            .binding(1)
+            .descriptor_type(vk::DescriptorType::SAMPLER)
+            .descriptor_count(1)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build(),
+    ]
+}
+
+unsafe fn create_depth_descriptors(device: &vulkanalia::Device) -> Result<gfx::DescriptorSets> {
+    gfx::DescriptorSets::create(device, gfx::DescriptorSlot::Pass, &image_bindings())
+}
+
+unsafe fn create_descriptors(device: &vulkanalia::Device) -> Result<gfx::DescriptorSets> {
+    gfx::DescriptorSets::create(device, gfx::DescriptorSlot::Material, &image_bindings())
+}
+
+unsafe fn write_set(
+    device: &vulkanalia::Device,
+    set: vk::DescriptorSet,
+    view: vk::ImageView,
+    layout: vk::ImageLayout,
+    sampler: vk::Sampler,
+) {
+    let image_info = &[vk::DescriptorImageInfo::builder()
+        .image_view(view)
+        .image_layout(layout)];
+    let sampler_info = &[vk::DescriptorImageInfo::builder().sampler(sampler)];
+
+    let writes = &[
+        vk::WriteDescriptorSet::builder()
+            .dst_set(set)
+            .dst_binding(0)
+            .descriptor_type(vk::DescriptorType::SAMPLED_IMAGE)
+            .image_info(image_info),
+        vk::WriteDescriptorSet::builder()
+            .dst_set(set)
+            .dst_binding(1)
+            .descriptor_type(vk::DescriptorType::SAMPLER)
+            .image_info(sampler_info),
+    ];
+
+    device.update_descriptor_sets(writes, &[] as &[vk::CopyDescriptorSet]);
+}
+
+unsafe fn create_instance_buffer(device: &gfx::Device, capacity: usize) -> Result<gfx::Buffer> {
+    device.create_buffer(
+        (size_of::<ParticleInstance>() * capacity) as vk::DeviceSize,
+        vk::BufferUsageFlags::VERTEX_BUFFER,
+        vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+    )
+}
+
+unsafe fn create_pipeline(
+    device: &gfx::Device,
+    depth_descriptors: &gfx::DescriptorSets,
+    descriptors: &gfx::DescriptorSets,
+) -> Result<(vk::PipelineLayout, vk::Pipeline)> {
+    let vk_device = device.device();
+
+    // Stages, multisampled depth needs its own fetch
+
+    let vert = include_bytes!("../../shaders/particle_vert.spv");
+    let frag = if device.samples() == vk::SampleCountFlags::_1 {
+        &include_bytes!("../../shaders/particle_frag.spv")[..]
+    } else {
+        &include_bytes!("../../shaders/particle_ms_frag.spv")[..]
+    };
+
+    let vertex_shader = gfx::Shader::create(vk_device, &vert[..])?;
+    let fragment_shader = gfx::Shader::create(vk_device, frag)?;
+
+    let vert_stage = vk::PipelineShaderStageCreateInfo::builder()
+        .stage(vk::ShaderStageFlags::VERTEX)
+        .module(vertex_shader.module)
+        .name(b"main\0");
+
+    let frag_stage = vk::PipelineShaderStageCreateInfo::builder()
+        .stage(vk::ShaderStageFlags::FRAGMENT)
+        .module(fragment_shader.module)
+        .name(b"main\0");
+
+    // Vertex Input State, one particle per instance
+
+    let binding_descriptions = &[vk::VertexInputBindingDescription::builder()
+        .binding(0)
+        .stride(size_of::<ParticleInstance>() as u32)
+        .input_rate(vk::VertexInputRate::INSTANCE)
+        .build()];
+
+    let vec4 = size_of::<Vec4>() as u32;
+    let attribute_descriptions = (0..3)
+        .map(|location| {
+            vk::VertexInputAttributeDescription::builder()
+                .binding(0)
+                .location(location)
+                .format(vk::Format::R32G32B32A32_SFLOAT)
+                .offset(vec4 * location)
+                .build()
+        })
+        .collect::<Vec<_>>();
+
+    let vertex_input_state = vk::PipelineVertexInputStateCreateInfo::builder()
+        .vertex_binding_descriptions(binding_descriptions)
+        .vertex_attribute_descriptions(&attribute_descriptions);
+
+    // Input Assembly State
+
+    let input_assembly_state = vk::PipelineInputAssemblyStateCreateInfo::builder()
+        .topology(vk::PrimitiveTopology::TRIANGLE_LIST)
+        .primitive_restart_enable(false);
+
+    // Viewport State, set when recording
+
+    let viewport_state = vk::PipelineViewportStateCreateInfo::builder()
+        .viewport_count(1)
+        .scissor_count(1);
+
+    let dynamic_states = &[vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+    let dynamic_state =
+        vk::PipelineDynamicStateCreateInfo::builder().dynamic_states(dynamic_states);
+
+    // Rasterization State, particles always face the camera
+
+    let rasterization_state = vk::PipelineRasterizationStateCreateInfo::builder()
+        .depth_clamp_enable(false)
+        .rasterizer_discard_enable(false)
+        .polygon_mode(vk::PolygonMode::FILL)
+        .line_width(1.0)
+        .cull_mode(vk::CullModeFlags::NONE)
+        .front_face(vk::FrontFace::COUNTER_CLOCKWISE)
+        .depth_bias_enable(false);
+
+    // Multisample State
+
+    let multisample_state = vk::PipelineMultisampleStateCreateInfo::builder()
+        .sample_shading_enable(false)
+        .rasterization_samples(device.samples());
+
+    // Depth Stencil State, depth is read only while particles sample it
+
+    let depth_stencil_state = vk::PipelineDepthStencilStateCreateInfo::builder()
+        .depth_test_enable(true)
+        .depth_write_enable(false)
+        .depth_compare_op(vk::CompareOp::LESS_OR_EQUAL)
+        .depth_bounds_test_enable(false)
+        .stencil_test_enable(false);
+
+    // Color Blend State, the particle shaders output premultiplied alpha
+
+    let attachment = vk::PipelineColorBlendAttachmentState::builder()
+        .color_write_mask(vk::ColorComponentFlags::all())
+        .blend_enable(true)
+        .src_color_blend_factor(vk::BlendFactor::ONE)
+        .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+        .color_blend_op(vk::BlendOp::ADD)
+        .src_alpha_blend_factor(vk::BlendFactor::ONE)
+        .dst_alpha_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+        .alpha_blend_op(vk::BlendOp::ADD);
+
+    let attachments = &[attachment];
+    let color_blend_state = vk::PipelineColorBlendStateCreateInfo::builder()
+        .logic_op_enable(false)
+        .logic_op(vk::LogicOp::COPY)
+        .attachments(attachments)
+        .blend_constants([0.0, 0.0, 0.0, 0.0]);
+
+    // Push Constant Ranges
+
+    let push_constant_range = vk::PushConstantRange::builder()
+        .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
+        .offset(0)
+        .size(size_of::<ParticlePushConstants>() as u32);
+
+    // Layout
+
+    let pipeline_layout = device.create_pipeline_layout(
+        &[depth_descriptors, descriptors],
+        &[push_constant_range.build()],
+    )?;
+
+    // Create
+
+    let stages = &[vert_stage, frag_stage];
+    let info = vk::GraphicsPipelineCreateInfo::builder()
+        .stages(stages)
+        .vertex_input_state(&vertex_input_state)
+        .input_assembly_state(&input_assembly_state)
+        .viewport_state(&viewport_state)
+        .rasterization_state(&rasterization_state)
+        .multisample_state(&multisample_state)
+        .depth_stencil_state(&depth_stencil_state)
+        .color_blend_state(&color_blend_state)
+        .dynamic_state(&dynamic_state)
+        .layout(pipeline_layout)
+        .render_pass(device.render_pass())
+        .subpass(0);
+
+    let pipeline = vk_device
+        .create_graphics_pipelines(vk::PipelineCache::null(), &[info], None)?
+        .0[0];
+
+    // clean up
+    vertex_shader.destroy(vk_device);
+    fragment_shader.destroy(vk_device);
+
+    Ok((pipeline_layout, pipeline))
+}
@@ -0,0 +1,834 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use std::cmp::Ordering;
+use std::mem::size_of;
+
+use anyhow::Result;
+use cgmath::SquareMatrix;
+use tracing::debug;
+use vulkanalia::prelude::v1_0::*;
+
+use super::{
+    create_mesh_pipeline, light_data, select_lights, view_depth, BlendMode, Environment,
+    ExternalTexture, Light, LightData, Mesh, ReflectionProbe, RenderTarget, MAX_LIGHTS,
+    PREFILTERED_LEVELS,
+};
+use crate::gfx;
+use crate::math::{Mat4, Vec3, Vec4};
+
+// The material texture slots, in binding order.
+const MATERIAL_TEXTURES: usize = 5;
+
+/// A metallic roughness material as described by gltf, textures are multiplied by the factors.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PbrMaterial {
+    pub base_color: Vec4,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub emissive: Vec3,
+    pub normal_scale: f32,
+    pub occlusion_strength: f32,
+    pub blend: BlendMode,
+    /// srgb color with alpha.
+    pub base_color_texture: Option<vk::ImageView>,
+    /// linear, roughness in green and metallic in blue.
+    pub metallic_roughness_texture: Option<vk::ImageView>,
+    /// linear tangent space normals.
+    pub normal_texture: Option<vk::ImageView>,
+    /// linear, occlusion in red.
+    pub occlusion_texture: Option<vk::ImageView>,
+    /// srgb color.
+    pub emissive_texture: Option<vk::ImageView>,
+    /// lights the material in place of the environment of the renderer.
+    pub reflection: Option<ReflectionViews>,
+}
+
+/// The cube maps a material is lit with in place of the environment of its renderer, like
+/// the views of an `Environment` with the brdf table left to the renderer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ReflectionViews {
+    pub irradiance: vk::ImageView,
+    /// `PREFILTERED_LEVELS` levels, roughness increases linearly over them.
+    pub prefiltered: vk::ImageView,
+}
+
+impl PbrMaterial {
+    pub fn new(base_color: Vec4, metallic: f32, roughness: f32) -> Self {
+        Self {
+            base_color,
+            metallic,
+            roughness,
+            ..Default::default()
+        }
+    }
+
+    /// the factors of a baked material, its textures are bound with the `with_*` calls.
+    pub fn from_desc(desc: &deimos_format::MaterialDesc) -> Self {
+        Self {
+            base_color: <[f32; 4]>::from(desc.base_color).into(),
+            metallic: desc.metallic,
+            roughness: desc.roughness,
+            emissive: <[f32; 3]>::from(desc.emissive).into(),
+            normal_scale: desc.normal_scale,
+            occlusion_strength: desc.occlusion_strength,
+            blend: match desc.blend {
+                deimos_format::MaterialBlend::Opaque => BlendMode::Opaque,
+                deimos_format::MaterialBlend::Transparent => BlendMode::Transparent,
+            },
+            ..Default::default()
+        }
+    }
+
+    pub fn with_base_color_texture(self, texture: &gfx::Texture, view: gfx::TextureView) -> Self {
+        texture.check(gfx::ColorSpace::Srgb, "base color texture");
+        Self {
+            base_color_texture: Some(view.view),
+            ..self
+        }
+    }
+
+    pub fn with_metallic_roughness_texture(
+        self,
+        texture: &gfx::Texture,
+        view: gfx::TextureView,
+    ) -> Self {
+        texture.check(gfx::ColorSpace::Linear, "metallic roughness texture");
+        Self {
+            metallic_roughness_texture: Some(view.view),
+            ..self
+        }
+    }
+
+    pub fn with_normal_texture(self, texture: &gfx::Texture, view: gfx::TextureView) -> Self {
+        texture.check(gfx::ColorSpace::Linear, "normal texture");
+        Self {
+            normal_texture: Some(view.view),
+            ..self
+        }
+    }
+
+    pub fn with_occlusion_texture(self, texture: &gfx::Texture, view: gfx::TextureView) -> Self {
+        texture.check(gfx::ColorSpace::Linear, "occlusion texture");
+        Self {
+            occlusion_texture: Some(view.view),
+            ..self
+        }
+    }
+
+    pub fn with_emissive_texture(self, texture: &gfx::Texture, view: gfx::TextureView) -> Self {
+        texture.check(gfx::ColorSpace::Srgb, "emissive texture");
+        Self {
+            emissive_texture: Some(view.view),
+            ..self
+        }
+    }
+
+    /// show what a render target sees as emission, like a screen or a portal. the target
+    /// holds linear hdr color, so it is not checked for srgb.
+    pub fn with_render_target(self, target: &RenderTarget) -> Self {
+        Self {
+            emissive_texture: Some(target.view().view),
+            ..self
+        }
+    }
+
+    /// show decoded video frames as the base color, like a screen playing a video. yuv
+    /// frames are converted to linear color, so it is not checked for srgb.
+    pub fn with_external_texture(self, texture: &ExternalTexture) -> Self {
+        Self {
+            base_color_texture: Some(texture.view().view),
+            ..self
+        }
+    }
+
+    /// reflect the surroundings a probe captured instead of the environment, for surfaces
+    /// near the probe.
+    pub fn with_reflection_probe(self, probe: &ReflectionProbe) -> Self {
+        Self {
+            reflection: Some(ReflectionViews {
+                irradiance: probe.irradiance_view(),
+                prefiltered: probe.prefiltered_view(),
+            }),
+            ..self
+        }
+    }
+}
+
+impl Default for PbrMaterial {
+    /// the gltf defaults, a white rough metal.
+    fn default() -> Self {
+        Self {
+            base_color: Vec4::new(1.0, 1.0, 1.0, 1.0),
+            metallic: 1.0,
+            roughness: 1.0,
+            emissive: Vec3::new(0.0, 0.0, 0.0),
+            normal_scale: 1.0,
+            occlusion_strength: 1.0,
+            blend: BlendMode::Opaque,
+            base_color_texture: None,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            occlusion_texture: None,
+            emissive_texture: None,
+            reflection: None,
+        }
+    }
+}
+
+/// The std140 frame uniform block, must match the pbr shaders.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct FrameData {
+    view_proj: Mat4,
+    camera_position: Vec4,
+    ambient: Vec4,
+    // the highest prefiltered level
+    environment: Vec4,
+    light_count: [u32; 4],
+    lights: [LightData; MAX_LIGHTS],
+}
+
+crate::pod!(FrameData {
+    view_proj: Mat4,
+    camera_position: Vec4,
+    ambient: Vec4,
+    environment: Vec4,
+    light_count: [u32; 4],
+    lights: [LightData; MAX_LIGHTS],
+});
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct PbrPushConstants {
+    model: Mat4,
+    base_color: Vec4,
+    emissive: Vec4,
+    // metallic, roughness, normal scale and occlusion strength
+    factors: Vec4,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct PbrDrawItem {
+    mesh: Mesh,
+    material: PbrMaterial,
+    transform: Mat4,
+    depth: f32,
+}
+
+/// A stand-in for missing material textures.
+struct DefaultTexture {
+    texture: gfx::Texture,
+    view: gfx::TextureView,
+}
+
+/// The environment maps bound for image based lighting.
+#[derive(Copy, Clone, Debug)]
+struct EnvironmentViews {
+    irradiance: vk::ImageView,
+    prefiltered: vk::ImageView,
+    brdf: vk::ImageView,
+}
+
+impl EnvironmentViews {
+    fn of(environment: &Environment) -> Self {
+        Self {
+            irradiance: environment.irradiance_view(),
+            prefiltered: environment.prefiltered_view(),
+            brdf: environment.brdf_view(),
+        }
+    }
+}
+
+/// Renders meshes with surface vertices using the metallic roughness model.
+pub struct PbrRenderer {
+    /// the lights of the next recorded frame, see `set_max_lights` for how many are used.
+    pub lights: Vec<Light>,
+    max_lights: usize,
+    /// scales the image based lighting of the environment.
+    pub ambient: Vec3,
+    sampler: vk::Sampler,
+    environment_sampler: vk::Sampler,
+    frame_descriptors: gfx::DescriptorSets,
+    environment_descriptors: gfx::DescriptorSets,
+    // one environment set per draw lit by its own reflection and swapchain image
+    reflection_descriptors: Vec<gfx::DescriptorSets>,
+    // a white environment used until one is set
+    default_environment: Environment,
+    environment: EnvironmentViews,
+    // one descriptor set per draw and swapchain image
+    material_descriptors: Vec<gfx::DescriptorSets>,
+    // one persistently mapped frame block per swapchain image, written by `record` and again
+    // by `latch`
+    uniforms: Vec<gfx::MappedBuffer>,
+    white: DefaultTexture,
+    white_linear: DefaultTexture,
+    flat_normal: DefaultTexture,
+    pipeline_layout: vk::PipelineLayout,
+    opaque_pipeline: vk::Pipeline,
+    transparent_pipeline: vk::Pipeline,
+    opaque: Vec<PbrDrawItem>,
+    transparent: Vec<PbrDrawItem>,
+}
+
+impl PbrRenderer {
+    pub fn create(device: &gfx::Device) -> Result<Self> {
+        unsafe {
+            let vk_device = device.device();
+
+            // create the sampler and the descriptors
+            let sampler = device.texture_sampler(&gfx::SamplerDescriptor::default())?;
+            let frame_descriptors = gfx::DescriptorSets::create(
+                vk_device,
+                gfx::DescriptorSlot::Frame,
+                &[vk::DescriptorSetLayoutBinding::builder()
+                    .binding(0)
+                    .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+                    .descriptor_count(1)
+                    .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
+                    .build()],
+            )?;
+            let material_descriptors = vec![create_material_descriptors(vk_device)?];
+            let environment_sampler = device.sampler(&gfx::SamplerDescriptor::linear(
+                vk::SamplerAddressMode::CLAMP_TO_EDGE,
+            ))?;
+            let environment_descriptors = create_environment_descriptors(vk_device)?;
+
+            // a uniform white environment keeps `ambient` the uniform radiance around
+            let default_environment = Environment::uniform(device, [255, 255, 255, 255])?;
+            let environment = EnvironmentViews::of(&default_environment);
+
+            // create one frame uniform buffer per swapchain image
+            let uniforms = (0..device.image_count())
+                .map(|_| create_uniform_buffer(device))
+                .collect::<Result<Vec<_>>>()?;
+
+            // create the textures standing in for missing maps
+            let white =
+                create_default_texture(device, [255, 255, 255, 255], gfx::TextureImport::Color)?;
+            let white_linear =
+                create_default_texture(device, [255, 255, 255, 255], gfx::TextureImport::Data)?;
+            let flat_normal =
+                create_default_texture(device, [128, 128, 255, 255], gfx::TextureImport::Normal)?;
+
+            // layout is shared by both queues
+            let push_constant_range = vk::PushConstantRange::builder()
+                .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
+                .offset(0)
+                .size(size_of::<PbrPushConstants>() as u32);
+
+            let pipeline_layout = device.create_pipeline_layout(
+                &[
+                    &frame_descriptors,
+                    &environment_descriptors,
+                    &material_descriptors[0],
+                ],
+                &[push_constant_range.build()],
+            )?;
+
+            // one pipeline per queue
+            let opaque_pipeline = create_pipeline(device, pipeline_layout, BlendMode::Opaque)?;
+            device.set_name(opaque_pipeline, "pbr opaque");
+            let transparent_pipeline =
+                create_pipeline(device, pipeline_layout, BlendMode::Transparent)?;
+            device.set_name(transparent_pipeline, "pbr transparent");
+
+            Ok(Self {
+                lights: vec![],
+                max_lights: MAX_LIGHTS,
+                ambient: Vec3::new(0.03, 0.03, 0.03),
+                sampler,
+                environment_sampler,
+                frame_descriptors,
+                environment_descriptors,
+                reflection_descriptors: vec![],
+                default_environment,
+                environment,
+                material_descriptors,
+                uniforms,
+                white,
+                white_linear,
+                flat_normal,
+                pipeline_layout,
+                opaque_pipeline,
+                transparent_pipeline,
+                opaque: vec![],
+                transparent: vec![],
+            })
+        }
+    }
+
+    /// use at most `max` lights, clamped to `MAX_LIGHTS`. further lights are dropped by
+    /// `select_lights`, keeping directional lights and those nearest to the camera.
+    pub fn set_max_lights(&mut self, max: usize) {
+        self.max_lights = max.min(MAX_LIGHTS);
+    }
+
+    pub fn max_lights(&self) -> usize {
+        self.max_lights
+    }
+
+    /// light all meshes with an environment, `None` goes back to a uniform white one.
+    /// the environment must outlive the frames recorded with it.
+    pub fn set_environment(&mut self, environment: Option<&Environment>) {
+        self.environment = EnvironmentViews::of(environment.unwrap_or(&self.default_environment));
+    }
+
+    /// queue a mesh created with `Mesh::create_surface`, the blend mode selects the queue.
+    /// the transform is pushed with the draw like for `Renderer::draw`.
+    pub fn draw(&mut self, mesh: &Mesh, material: &PbrMaterial, transform: Mat4) {
+        let item = PbrDrawItem {
+            mesh: *mesh,
+            material: *material,
+            transform,
+            depth: 0.0,
+        };
+
+        match material.blend {
+            BlendMode::Opaque => self.opaque.push(item),
+            BlendMode::Transparent => self.transparent.push(item),
+        }
+    }
+
+    /// record both queues and clear them, must be called in the frame render pass.
+    pub fn record(
+        &mut self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        view: Mat4,
+        proj: Mat4,
+    ) -> Result<()> {
+        unsafe {
+            // nothing queued
+            if self.opaque.is_empty() && self.transparent.is_empty() {
+                return Ok(());
+            }
+
+            let vk_device = device.device();
+
+            // follow changed graphics settings
+            self.sampler = device.texture_sampler(&gfx::SamplerDescriptor::default())?;
+
+            // a recreated swapchain may have more images
+            while self.uniforms.len() <= index {
+                self.uniforms.push(create_uniform_buffer(device)?);
+            }
+
+            // upload the frame block
+            let frame = self.frame_data(view, proj);
+            let used = frame.light_count[0] as usize;
+            if used < self.lights.len() {
+                debug!(
+                    lights = self.lights.len(),
+                    used, "too many lights, using the most important"
+                );
+            }
+            self.uniforms[index].write(0, &[frame])?;
+
+            let frame_set = self.frame_descriptors.get(vk_device, index)?;
+            let buffer_info = &[vk::DescriptorBufferInfo::builder()
+                .buffer(self.uniforms[index].buffer())
+                .offset(0)
+                .range(size_of::<FrameData>() as vk::DeviceSize)];
+            let write = vk::WriteDescriptorSet::builder()
+                .dst_set(frame_set)
+                .dst_binding(0)
+                .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+                .buffer_info(buffer_info);
+            vk_device.update_descriptor_sets(&[write], &[] as &[vk::CopyDescriptorSet]);
+
+            let environment_set = self.environment_descriptors.get(vk_device, index)?;
+            self.write_environment_set(vk_device, environment_set, &self.environment);
+
+            // compute the view space distance of each draw
+            for item in self.opaque.iter_mut().chain(self.transparent.iter_mut()) {
+                item.depth = view_depth(&view, &item.transform, item.mesh.center);
+            }
+
+            // opaque front to back to reject hidden fragments early
+            self.opaque
+                .sort_by(|a, b| a.depth.partial_cmp(&b.depth).unwrap_or(Ordering::Equal));
+
+            // transparent back to front for correct blending
+            self.transparent
+                .sort_by(|a, b| b.depth.partial_cmp(&a.depth).unwrap_or(Ordering::Equal));
+
+            // every draw needs its own material set
+            let draws = self.opaque.len() + self.transparent.len();
+            while self.material_descriptors.len() < draws {
+                self.material_descriptors
+                    .push(create_material_descriptors(vk_device)?);
+            }
+            let reflected = self
+                .opaque
+                .iter()
+                .chain(self.transparent.iter())
+                .filter(|item| item.material.reflection.is_some())
+                .count();
+            while self.reflection_descriptors.len() < reflected {
+                self.reflection_descriptors
+                    .push(create_environment_descriptors(vk_device)?);
+            }
+
+            let extent = device.extent();
+
+            // viewport and scissor are dynamic, the scissor follows the damage
+            let viewport = vk::Viewport::builder()
+                .x(0.0)
+                .y(0.0)
+                .width(extent.width as f32)
+                .height(extent.height as f32)
+                .min_depth(0.0)
+                .max_depth(1.0);
+            let scissor = device.scissor();
+
+            vk_device.cmd_set_viewport(command_buffer, 0, &[viewport]);
+            vk_device.cmd_set_scissor(command_buffer, 0, &[scissor]);
+
+            let queues = [
+                (self.opaque_pipeline, &self.opaque),
+                (self.transparent_pipeline, &self.transparent),
+            ];
+
+            let (mut slot, mut reflection_slot) = (0, 0);
+            for (pipeline, items) in queues {
+                // skip empty queues
+                if items.is_empty() {
+                    continue;
+                }
+
+                debug!(?pipeline, "bind pipeline");
+                vk_device.cmd_bind_pipeline(
+                    command_buffer,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    pipeline,
+                );
+
+                for item in items.iter() {
+                    let material_set = self.material_descriptors[slot].get(vk_device, index)?;
+                    self.write_material_set(vk_device, material_set, &item.material);
+                    slot += 1;
+
+                    // reflections replace the environment but keep its brdf table
+                    let lighting_set = match item.material.reflection {
+                        Some(reflection) => {
+                            let set = self.reflection_descriptors[reflection_slot]
+                                .get(vk_device, index)?;
+                            let views = EnvironmentViews {
+                                irradiance: reflection.irradiance,
+                                prefiltered: reflection.prefiltered,
+                                brdf: self.environment.brdf,
+                            };
+                            self.write_environment_set(vk_device, set, &views);
+                            reflection_slot += 1;
+                            set
+                        }
+                        None => environment_set,
+                    };
+
+                    debug!(first = gfx::FRAME_SET, sets = ?&[frame_set, lighting_set, material_set], "bind descriptor sets");
+                    vk_device.cmd_bind_descriptor_sets(
+                        command_buffer,
+                        vk::PipelineBindPoint::GRAPHICS,
+                        self.pipeline_layout,
+                        gfx::FRAME_SET,
+                        &[frame_set, lighting_set, material_set],
+                        &[],
+                    );
+
+                    self.record_item(vk_device, command_buffer, item)?;
+                }
+            }
+
+            // start over for the next frame
+            self.opaque.clear();
+            self.transparent.clear();
+
+            Ok(())
+        }
+    }
+
+    unsafe fn write_material_set(
+        &self,
+        device: &vulkanalia::Device,
+        set: vk::DescriptorSet,
+        material: &PbrMaterial,
+    ) {
+        // missing maps fall back to textures that leave the factors unchanged
+        let views = [
+            material.base_color_texture.unwrap_or(self.white.view.view),
+            material
+                .metallic_roughness_texture
+                .unwrap_or(self.white_linear.view.view),
+            material
+                .normal_texture
+                .unwrap_or(self.flat_normal.view.view),
+            material
+                .occlusion_texture
+                .unwrap_or(self.white_linear.view.view),
+            material.emissive_texture.unwrap_or(self.white.view.view),
+        ];
+
+        let image_infos = views
+            .iter()
+            .map(|v| {
+                [vk::DescriptorImageInfo::builder()
+                    .image_view(*v)
+                    .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                    .build()]
+            })
+            .collect::<Vec<_>>();
+        let sampler_info = &[vk::DescriptorImageInfo::builder().sampler(self.sampler)];
+
+        let mut writes = image_infos
+            .iter()
+            .enumerate()
+            .map(|(binding, info)| {
+                vk::WriteDescriptorSet::builder()
+                    .dst_set(set)
+                    .dst_binding(binding as u32)
+                    .descriptor_type(vk::DescriptorType::SAMPLED_IMAGE)
+                    .image_info(info)
+                    .build()
+            })
+            .collect::<Vec<_>>();
+        writes.push(
+            vk::WriteDescriptorSet::builder()
+                .dst_set(set)
+                .dst_binding(MATERIAL_TEXTURES as u32)
+                .descriptor_type(vk::DescriptorType::SAMPLER)
+                .image_info(sampler_info)
+                .build(),
+        );
+
+        device.update_descriptor_sets(&writes, &[] as &[vk::CopyDescriptorSet]);
+    }
+
+    unsafe fn write_environment_set(
+        &self,
+        device: &vulkanalia::Device,
+        set: vk::DescriptorSet,
+        environment: &EnvironmentViews,
+    ) {
+        let views = [
+            environment.irradiance,
+            environment.prefiltered,
+            environment.brdf,
+        ];
+        let image_infos = views
+            .iter()
+            .map(|v| {
+                [vk::DescriptorImageInfo::builder()
+                    .image_view(*v)
+                    .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                    .build()]
+            })
+            .collect::<Vec<_>>();
+        let sampler_info = &[vk::DescriptorImageInfo::builder().sampler(self.environment_sampler)];
+
+        let mut writes = image_infos
+            .iter()
+            .enumerate()
+            .map(|(binding, info)| {
+                vk::WriteDescriptorSet::builder()
+                    .dst_set(set)
+                    .dst_binding(binding as u32)
+                    .descriptor_type(vk::DescriptorType::SAMPLED_IMAGE)
+                    .image_info(info)
+                    .build()
+            })
+            .collect::<Vec<_>>();
+        writes.push(
+            vk::WriteDescriptorSet::builder()
+                .dst_set(set)
+                .dst_binding(views.len() as u32)
+                .descriptor_type(vk::DescriptorType::SAMPLER)
+                .image_info(sampler_info)
+                .build(),
+        );
+
+        device.update_descriptor_sets(&writes, &[] as &[vk::CopyDescriptorSet]);
+    }
+
+    unsafe fn record_item(
+        &self,
+        device: &vulkanalia::Device,
+        command_buffer: vk::CommandBuffer,
+        item: &PbrDrawItem,
+    ) -> Result<()> {
+        let material = &item.material;
+        let constants = PbrPushConstants {
+            model: item.transform,
+            base_color: material.base_color,
+            emissive: material.emissive.extend(0.0),
+            factors: Vec4::new(
+                material.metallic,
+                material.roughness,
+                material.normal_scale,
+                material.occlusion_strength,
+            ),
+        };
+
+        let bytes = std::slice::from_raw_parts(
+            &constants as *const PbrPushConstants as *const u8,
+            size_of::<PbrPushConstants>(),
+        );
+
+        device.cmd_push_constants(
+            command_buffer,
+            self.pipeline_layout,
+            vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+            0,
+            bytes,
+        );
+        device.cmd_bind_vertex_buffers(command_buffer, 0, &[item.mesh.vertices.buffer], &[0]);
+        let indices = item.mesh.index_binding();
+        indices.bind(device, command_buffer);
+        indices.draw(device, command_buffer, 0, item.mesh.count, 1)
+    }
+
+    /// write the camera of the frame recorded for image `index` again, from the latch stage
+    /// of `Device::update_with_latch` right before the frame is submitted. the draws were
+    /// recorded and sorted with the view given to `record`, they are shaded and placed with
+    /// this one, which can follow input that came in while the frame was recorded.
+    pub fn latch(&self, index: usize, view: Mat4, proj: Mat4) -> Result<()> {
+        // nothing was recorded for the image yet
+        let Some(uniforms) = self.uniforms.get(index) else {
+            return Ok(());
+        };
+        uniforms.write(0, &[self.frame_data(view, proj)])
+    }
+
+    /// the frame block seen from `view`, with the lights that matter most there.
+    fn frame_data(&self, view: Mat4, proj: Mat4) -> FrameData {
+        let camera = view.invert().unwrap_or(Mat4::identity()).w;
+        let lights = select_lights(&self.lights, camera.truncate(), self.max_lights);
+        FrameData {
+            view_proj: proj * view,
+            camera_position: camera,
+            ambient: self.ambient.extend(1.0),
+            environment: Vec4::new((PREFILTERED_LEVELS - 1) as f32, 0.0, 0.0, 0.0),
+            light_count: [lights.len() as u32, 0, 0, 0],
+            lights: light_data(&lights),
+        }
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe {
+            let vk_device = device.device();
+
+            // destroy pipelines
+            vk_device.destroy_pipeline(self.opaque_pipeline, None);
+            vk_device.destroy_pipeline(self.transparent_pipeline, None);
+            vk_device.destroy_pipeline_layout(self.pipeline_layout, None);
+
+            // destroy default textures
+            for texture in [&self.white, &self.white_linear, &self.flat_normal] {
+                texture.view.destroy(vk_device);
+                texture.texture.destroy(vk_device);
+            }
+
+            // destroy the default environment
+            self.default_environment.destroy(device);
+
+            // destroy buffers, descriptors and samplers
+            self.uniforms.iter().for_each(|b| b.destroy(vk_device));
+            self.frame_descriptors.destroy(vk_device);
+            self.environment_descriptors.destroy(vk_device);
+            self.reflection_descriptors
+                .iter()
+                .for_each(|d| d.destroy(vk_device));
+            self.material_descriptors
+                .iter()
+                .for_each(|d| d.destroy(vk_device));
+        }
+    }
+}
+
+/// the irradiance and prefiltered cube maps, the brdf table and their sampler.
+unsafe fn create_environment_descriptors(
+    device: &vulkanalia::Device,
+) -> Result<gfx::DescriptorSets> {
+    let mut bindings = (0..3)
+        .map(|binding| {
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(binding)
+                .descriptor_type(vk::DescriptorType::SAMPLED_IMAGE)
+                .descriptor_count(1)
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                .build()
+        })
+        .collect::<Vec<_>>();
+    bindings.push(
+        vk::DescriptorSetLayoutBinding::builder()
+            .binding(3)
+            .descriptor_type(vk::DescriptorType::SAMPLER)
+            .descriptor_count(1)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build(),
+    );
+
+    gfx::DescriptorSets::create(device, gfx::DescriptorSlot::Pass, &bindings)
+}
+
+unsafe fn create_material_descriptors(device: &vulkanalia::Device) -> Result<gfx::DescriptorSets> {
+    let mut bindings = (0..MATERIAL_TEXTURES as u32)
+        .map(|binding| {
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(binding)
+                .descriptor_type(vk::DescriptorType::SAMPLED_IMAGE)
+                .descriptor_count(1)
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                .build()
+        })
+        .collect::<Vec<_>>();
+    bindings.push(
+        vk::DescriptorSetLayoutBinding::builder()
+            .binding(MATERIAL_TEXTURES as u32)
+            .descriptor_type(vk::DescriptorType::SAMPLER)
+            .descriptor_count(1)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build(),
+    );
+
+    gfx::DescriptorSets::create(device, gfx::DescriptorSlot::Material, &bindings)
+}
+
+unsafe fn create_uniform_buffer(device: &gfx::Device) -> Result<gfx::MappedBuffer> {
+    let buffer = device.create_buffer(
+        size_of::<FrameData>() as vk::DeviceSize,
+        vk::BufferUsageFlags::UNIFORM_BUFFER,
+        vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+    )?;
+    gfx::MappedBuffer::map(device.device(), buffer)
+}
+
+fn create_default_texture(
+    device: &gfx::Device,
+    pixel: [u8; 4],
+    import: gfx::TextureImport,
+) -> Result<DefaultTexture> {
+    let (texture, view) = device.upload_texture(1, 1, &pixel, import)?;
+    Ok(DefaultTexture { texture, view })
+}
+
+unsafe fn create_pipeline(
+    device: &gfx::Device,
+    layout: vk::PipelineLayout,
+    blend: BlendMode,
+) -> Result<vk::Pipeline> {
+    create_mesh_pipeline(
+        device,
+        layout,
+        blend,
+        include_bytes!("../../shaders/pbr_vert.spv"),
+        include_bytes!("../../shaders/pbr_frag.spv"),
+        &[gfx::SurfaceVertex::layout()],
+    )
+}
@@ -0,0 +1,744 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use std::any::Any;
+
+use anyhow::Result;
+use tracing::debug;
+use vulkanalia::prelude::v1_0::*;
+
+use crate::gfx;
+
+// The push constant bytes available to every pass.
+pub const POST_CONSTANTS_SIZE: usize = 128;
+
+// the color of the labeled region of each pass
+const PASS_LABEL: [f32; 4] = [0.7, 0.5, 0.9, 1.0];
+
+/// A fullscreen pass of the post processing chain.
+///
+/// The fragment shader reads the previous output at set 1 binding 0, the optional
+/// storage buffer is bound at binding 1, the optional auxiliary image at binding 2
+/// and a linear clamping sampler at binding 3.
+pub trait PostPass: Any {
+    /// the name used to look up the pass.
+    fn name(&self) -> &str;
+
+    /// the compiled fragment shader of the pass.
+    fn shader(&self) -> &'static [u8];
+
+    /// the bytes pushed to the fragment stage, at most `POST_CONSTANTS_SIZE`.
+    fn constants(&self) -> Vec<u8> {
+        vec![]
+    }
+
+    /// a storage buffer read by the shader.
+    fn storage(&self) -> Option<vk::Buffer> {
+        None
+    }
+
+    /// an additional image sampled by the shader.
+    fn auxiliary(&self) -> Option<vk::ImageView> {
+        None
+    }
+
+    /// record work needed before the pass draws, called outside any render pass.
+    fn prepare(
+        &mut self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        input: vk::ImageView,
+        delta: f32,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// destroy the objects owned by the pass.
+    fn destroy(&self, device: &gfx::Device) {}
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// A registered pass with the pipelines drawing it offscreen and into the swapchain.
+struct PostStage {
+    pass: Box<dyn PostPass>,
+    enabled: bool,
+    descriptors: gfx::DescriptorSets,
+    pipeline_layout: vk::PipelineLayout,
+    offscreen_pipeline: vk::Pipeline,
+    present_pipeline: vk::Pipeline,
+}
+
+/// An intermediate color target of the chain.
+struct PostTarget {
+    texture: gfx::Texture,
+    view: gfx::TextureView,
+    framebuffer: gfx::FrameBuffer,
+}
+
+/// Runs the enabled passes in order on the hdr scene target, ping-ponging between two
+/// intermediate targets, the last pass writes the swapchain image.
+pub struct PostChain {
+    render_pass: vk::RenderPass,
+    sampler: vk::Sampler,
+    stages: Vec<PostStage>,
+    copy: PostStage,
+    targets: Vec<PostTarget>,
+    extent: vk::Extent2D,
+}
+
+impl PostChain {
+    pub fn create(device: &gfx::Device) -> Result<Self> {
+        unsafe {
+            let vk_device = device.device();
+
+            // create the offscreen pass and the input sampler
+            let render_pass = create_render_pass(vk_device, false)?;
+            let sampler = device.sampler(&gfx::SamplerDescriptor::linear(
+                vk::SamplerAddressMode::CLAMP_TO_EDGE,
+            ))?;
+
+            // used when no pass is enabled
+            let copy = create_stage(device, render_pass, Box::new(CopyPass))?;
+
+            // create the intermediate targets
+            let extent = device.extent();
+            let targets = create_targets(device, render_pass, extent)?;
+
+            Ok(Self {
+                render_pass,
+                sampler,
+                stages: vec![],
+                copy,
+                targets,
+                extent,
+            })
+        }
+    }
+
+    /// register a pass at the end of the chain, passes run in registration order.
+    pub fn add<P: PostPass>(&mut self, device: &gfx::Device, pass: P) -> Result<()> {
+        let stage = unsafe { create_stage(device, self.render_pass, Box::new(pass))? };
+        self.stages.push(stage);
+        Ok(())
+    }
+
+    /// enable or disable all passes with a name.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) {
+        self.stages
+            .iter_mut()
+            .filter(|s| s.pass.name() == name)
+            .for_each(|s| s.enabled = enabled);
+    }
+
+    /// whether a pass with a name is registered and enabled.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.stages
+            .iter()
+            .any(|s| s.enabled && s.pass.name() == name)
+    }
+
+    /// the names of the enabled passes in the order they run.
+    pub fn names(&self) -> Vec<String> {
+        self.stages
+            .iter()
+            .filter(|s| s.enabled)
+            .map(|s| s.pass.name().to_string())
+            .collect()
+    }
+
+    /// the first registered pass of a type, used to change its settings.
+    pub fn get_mut<P: PostPass>(&mut self) -> Option<&mut P> {
+        self.stages
+            .iter_mut()
+            .find_map(|s| s.pass.as_any_mut().downcast_mut::<P>())
+    }
+
+    /// record all enabled passes, must be called after the scene pass and outside any render pass.
+    pub fn record(
+        &mut self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        delta: f32,
+    ) -> Result<()> {
+        unsafe {
+            // follow the swapchain size
+            if self.extent != device.extent() {
+                self.resize(device)?;
+            }
+
+            let enabled = self
+                .stages
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| s.enabled)
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+
+            // the scene goes through a copy when nothing else writes the swapchain
+            let count = enabled.len().max(1);
+            let mut input = device.scene_view();
+
+            // the first pass scales the scene up when it is rendered below the swapchain size
+            let address = vk::SamplerAddressMode::CLAMP_TO_EDGE;
+            let mut sampler = device.sampler(&match device.upscale_filter() {
+                vk::Filter::NEAREST => gfx::SamplerDescriptor::nearest(address),
+                _ => gfx::SamplerDescriptor::linear(address),
+            })?;
+
+            for step in 0..count {
+                let last = step + 1 == count;
+                let stage = match enabled.get(step) {
+                    Some(i) => &mut self.stages[*i],
+                    None => &mut self.copy,
+                };
+
+                // offscreen work of the pass
+                device.begin_label(command_buffer, stage.pass.name(), PASS_LABEL);
+                stage
+                    .pass
+                    .prepare(device, command_buffer, index, input, delta)?;
+
+                // point the set of this image at the current input
+                let set = stage.descriptors.get(device.device(), index)?;
+                write_set(device, stage.pass.as_ref(), set, input, sampler);
+
+                // the last pass writes the swapchain image, others alternate targets
+                let pipeline = if last {
+                    device.begin_present_pass(command_buffer, index);
+                    stage.present_pipeline
+                } else {
+                    let target = &self.targets[step % 2];
+                    begin_pass(
+                        device,
+                        command_buffer,
+                        self.render_pass,
+                        target,
+                        self.extent,
+                    );
+                    stage.offscreen_pipeline
+                };
+
+                draw(device, command_buffer, stage, pipeline, set);
+
+                if last {
+                    device.end_present_pass(command_buffer);
+                } else {
+                    device.device().cmd_end_render_pass(command_buffer);
+                    input = self.targets[step % 2].view.view;
+                    sampler = self.sampler;
+                }
+                device.end_label(command_buffer);
+            }
+
+            Ok(())
+        }
+    }
+
+    unsafe fn resize(&mut self, device: &gfx::Device) -> Result<()> {
+        // targets may still be read by pending frames
+        device.wait_idle()?;
+
+        destroy_targets(device, &self.targets);
+        self.extent = device.extent();
+        self.targets = create_targets(device, self.render_pass, self.extent)?;
+
+        Ok(())
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe {
+            let vk_device = device.device();
+
+            // destroy passes
+            self.stages
+                .iter()
+                .chain(std::iter::once(&self.copy))
+                .for_each(|s| destroy_stage(device, s));
+
+            // destroy targets
+            destroy_targets(device, &self.targets);
+
+            // destroy sampler and pass
+            vk_device.destroy_render_pass(self.render_pass, None);
+        }
+    }
+}
+
+/// Copies the input unchanged.
+struct CopyPass;
+
+impl PostPass for CopyPass {
+    fn name(&self) -> &str {
+        "copy"
+    }
+
+    fn shader(&self) -> &'static [u8] {
+        include_bytes!("../../shaders/copy_frag.spv")
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// view a plain value as the bytes pushed to a pass.
+pub fn constant_bytes<T: Copy>(value: &T) -> Vec<u8> {
+    unsafe {
+        std::slice::from_raw_parts(value as *const T as *const u8, std::mem::size_of::<T>())
+            .to_vec()
+    }
+}
+
+unsafe fn begin_pass(
+    device: &gfx::Device,
+    command_buffer: vk::CommandBuffer,
+    render_pass: vk::RenderPass,
+    target: &PostTarget,
+    extent: vk::Extent2D,
+) {
+    let render_area = vk::Rect2D::builder()
+        .offset(vk::Offset2D::default())
+        .extent(extent);
+
+    let info = vk::RenderPassBeginInfo::builder()
+        .render_pass(render_pass)
+        .framebuffer(target.framebuffer.buffer)
+        .render_area(render_area);
+
+    device
+        .device()
+        .cmd_begin_render_pass(command_buffer, &info, vk::SubpassContents::INLINE);
+}
+
+unsafe fn draw(
+    device: &gfx::Device,
+    command_buffer: vk::CommandBuffer,
+    stage: &PostStage,
+    pipeline: vk::Pipeline,
+    set: vk::DescriptorSet,
+) {
+    let vk_device = device.device();
+    let extent = device.extent();
+
+    // viewport and scissor are dynamic
+    let viewport = vk::Viewport::builder()
+        .x(0.0)
+        .y(0.0)
+        .width(extent.width as f32)
+        .height(extent.height as f32)
+        .min_depth(0.0)
+        .max_depth(1.0);
+    let scissor = vk::Rect2D::builder()
+        .offset(vk::Offset2D { x: 0, y: 0 })
+        .extent(extent);
+
+    vk_device.cmd_set_viewport(command_buffer, 0, &[viewport]);
+    vk_device.cmd_set_scissor(command_buffer, 0, &[scissor]);
+
+    debug!(?pipeline, "bind pipeline");
+    vk_device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
+    stage.descriptors.bind(
+        vk_device,
+        command_buffer,
+        vk::PipelineBindPoint::GRAPHICS,
+        stage.pipeline_layout,
+        set,
+    );
+
+    // push the pass settings
+    let mut constants = stage.pass.constants();
+    if !constants.is_empty() {
+        constants.truncate(POST_CONSTANTS_SIZE);
+        vk_device.cmd_push_constants(
+            command_buffer,
+            stage.pipeline_layout,
+            vk::ShaderStageFlags::FRAGMENT,
+            0,
+            &constants,
+        );
+    }
+
+    // one fullscreen triangle
+    debug!(vertices = 3, instances = 1, "draw");
+    vk_device.cmd_draw(command_buffer, 3, 1, 0, 0);
+}
+
+unsafe fn write_set(
+    device: &gfx::Device,
+    pass: &dyn PostPass,
+    set: vk::DescriptorSet,
+    input: vk::ImageView,
+    sampler: vk::Sampler,
+) {
+    let input_info = &[vk::DescriptorImageInfo::builder()
+        .image_view(input)
+        .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)];
+    let sampler_info = &[vk::DescriptorImageInfo::builder().sampler(sampler)];
+
+    let mut writes = vec![
+        vk::WriteDescriptorSet::builder()
+            .dst_set(set)
+            .dst_binding(0)
+            .descriptor_type(vk::DescriptorType::SAMPLED_IMAGE)
+            .image_info(input_info)
+            .build(),
+        vk::WriteDescriptorSet::builder()
+            .dst_set(set)
+            .dst_binding(3)
+            .descriptor_type(vk::DescriptorType::SAMPLER)
+            .image_info(sampler_info)
+            .build(),
+    ];
+
+    // optional bindings, left unwritten when the pass does not use them
+    let storage_info = pass.storage().map(|buffer| {
+        [vk::DescriptorBufferInfo::builder()
+            .buffer(buffer)
+            .offset(0)
+            .range(vk::WHOLE_SIZE as u64)
+            .build()]
+    });
+    if let Some(info) = &storage_info {
+        writes.push(
+            vk::WriteDescriptorSet::builder()
+                .dst_set(set)
+                .dst_binding(1)
+                .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                .buffer_info(info)
+                .build(),
+        );
+    }
+
+    let auxiliary_info = pass.auxiliary().map(|view| {
+        [vk::DescriptorImageInfo::builder()
+            .image_view(view)
+            .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+            .build()]
+    });
+    if let Some(info) = &auxiliary_info {
+        writes.push(
+            vk::WriteDescriptorSet::builder()
+                .dst_set(set)
+                .dst_binding(2)
+                .descriptor_type(vk::DescriptorType::SAMPLED_IMAGE)
+                .image_info(info)
+                .build(),
+        );
+    }
+
+    device
+        .device()
+        .update_descriptor_sets(&writes, &[] as &[vk::CopyDescriptorSet]);
+}
+
+unsafe fn create_stage(
+    device: &gfx::Device,
+    render_pass: vk::RenderPass,
+    pass: Box<dyn PostPass>,
+) -> Result<PostStage> {
+    let vk_device = device.device();
+
+    // input, storage, auxiliary image and sampler
+    let bindings = &[
+        binding(0, vk::DescriptorType::SAMPLED_IMAGE),
+        binding(1, vk::DescriptorType::STORAGE_BUFFER),
+        binding(2, vk::DescriptorType::SAMPLED_IMAGE),
+        binding(3, vk::DescriptorType::SAMPLER),
+    ];
+    let descriptors = gfx::DescriptorSets::create(vk_device, gfx::DescriptorSlot::Pass, bindings)?;
+
+    // Layout
+
+    let push_constant_range = vk::PushConstantRange::builder()
+        .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+        .offset(0)
+        .size(POST_CONSTANTS_SIZE as u32);
+
+    let pipeline_layout =
+        device.create_pipeline_layout(&[&descriptors], &[push_constant_range.build()])?;
+
+    // one pipeline per render pass the stage can draw in
+    let shader = pass.shader();
+    let offscreen_pipeline =
+        create_fullscreen_pipeline(device, pipeline_layout, render_pass, shader, false)?;
+    let present_pipeline = create_fullscreen_pipeline(
+        device,
+        pipeline_layout,
+        device.present_render_pass(),
+        shader,
+        false,
+    )?;
+    device.set_name(offscreen_pipeline, &format!("post {}", pass.name()));
+    device.set_name(present_pipeline, &format!("post {} present", pass.name()));
+
+    Ok(PostStage {
+        pass,
+        enabled: true,
+        descriptors,
+        pipeline_layout,
+        offscreen_pipeline,
+        present_pipeline,
+    })
+}
+
+fn binding(index: u32, type_: vk::DescriptorType) -> vk::DescriptorSetLayoutBinding {
+    vk::DescriptorSetLayoutBinding::builder()
+        .binding(index)
+        .descriptor_type(type_)
+        .descriptor_count(1)
+        .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+        .build()
+}
+
+unsafe fn destroy_stage(device: &gfx::Device, stage: &PostStage) {
+    let vk_device = device.device();
+
+    // destroy pass objects
+    stage.pass.destroy(device);
+
+    // destroy pipelines
+    vk_device.destroy_pipeline(stage.offscreen_pipeline, None);
+    vk_device.destroy_pipeline(stage.present_pipeline, None);
+    vk_device.destroy_pipeline_layout(stage.pipeline_layout, None);
+
+    // destroy descriptors
+    stage.descriptors.destroy(vk_device);
+}
+
+unsafe fn create_targets(
+    device: &gfx::Device,
+    render_pass: vk::RenderPass,
+    extent: vk::Extent2D,
+) -> Result<Vec<PostTarget>> {
+    (0..2)
+        .map(|_| {
+            let texture = device.create_texture(
+                extent.width,
+                extent.height,
+                1,
+                gfx::SCENE_FORMAT,
+                vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
+            )?;
+            let view = texture.create_view(
+                device.device(),
+                gfx::SCENE_FORMAT,
+                vk::ImageAspectFlags::COLOR,
+                1,
+            )?;
+            let framebuffer = gfx::FrameBuffer::create(
+                device.device(),
+                &render_pass,
+                &[view],
+                extent.width,
+                extent.height,
+            )?;
+
+            Ok(PostTarget {
+                texture,
+                view,
+                framebuffer,
+            })
+        })
+        .collect()
+}
+
+unsafe fn destroy_targets(device: &gfx::Device, targets: &[PostTarget]) {
+    let vk_device = device.device();
+
+    targets.iter().for_each(|t| {
+        t.framebuffer.destroy(vk_device);
+        t.view.destroy(vk_device);
+        t.texture.destroy(vk_device);
+    });
+}
+
+/// a single color attachment pass producing a texture read by the next pass.
+/// when `load` is set the previous contents are kept, the texture must be readable already.
+pub unsafe fn create_render_pass(
+    device: &vulkanalia::Device,
+    load: bool,
+) -> Result<vk::RenderPass> {
+    let (load_op, initial_layout) = if load {
+        (
+            vk::AttachmentLoadOp::LOAD,
+            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        )
+    } else {
+        (vk::AttachmentLoadOp::DONT_CARE, vk::ImageLayout::UNDEFINED)
+    };
+
+    // Attachments
+    let color_attachment = vk::AttachmentDescription::builder()
+        .format(gfx::SCENE_FORMAT)
+        .samples(vk::SampleCountFlags::_1)
+        .load_op(load_op)
+        .store_op(vk::AttachmentStoreOp::STORE)
+        .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+        .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+        .initial_layout(initial_layout)
+        .final_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+
+    // Subpasses
+
+    let color_attachment_ref = vk::AttachmentReference::builder()
+        .attachment(0)
+        .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+
+    let color_attachments = &[color_attachment_ref];
+    let subpass = vk::SubpassDescription::builder()
+        .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+        .color_attachments(color_attachments);
+
+    // Dependencies, earlier reads finish before writing and later reads wait for the result
+
+    let dependency = vk::SubpassDependency::builder()
+        .src_subpass(vk::SUBPASS_EXTERNAL)
+        .dst_subpass(0)
+        .src_stage_mask(
+            vk::PipelineStageFlags::FRAGMENT_SHADER | vk::PipelineStageFlags::COMPUTE_SHADER,
+        )
+        .src_access_mask(vk::AccessFlags::empty())
+        .dst_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+        .dst_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE);
+
+    let read_dependency = vk::SubpassDependency::builder()
+        .src_subpass(0)
+        .dst_subpass(vk::SUBPASS_EXTERNAL)
+        .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+        .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+        .dst_stage_mask(
+            vk::PipelineStageFlags::FRAGMENT_SHADER | vk::PipelineStageFlags::COMPUTE_SHADER,
+        )
+        .dst_access_mask(vk::AccessFlags::SHADER_READ);
+
+    // Create
+
+    let attachments = &[color_attachment];
+    let subpasses = &[subpass];
+    let dependencies = &[dependency, read_dependency];
+    let info = vk::RenderPassCreateInfo::builder()
+        .attachments(attachments)
+        .subpasses(subpasses)
+        .dependencies(dependencies);
+
+    Ok(device.create_render_pass(&info, None)?)
+}
+
+/// a pipeline drawing a fullscreen triangle with the given fragment shader.
+/// `additive` adds the output to the target instead of replacing it.
+pub unsafe fn create_fullscreen_pipeline(
+    device: &gfx::Device,
+    layout: vk::PipelineLayout,
+    render_pass: vk::RenderPass,
+    fragment: &[u8],
+    additive: bool,
+) -> Result<vk::Pipeline> {
+    let vk_device = device.device();
+
+    // Stages
+
+    let vert = include_bytes!("../../shaders/fullscreen_vert.spv");
+
+    let vertex_shader = gfx::Shader::create(vk_device, &vert[..])?;
+    let fragment_shader = gfx::Shader::create(vk_device, fragment)?;
+
+    let vert_stage = vk::PipelineShaderStageCreateInfo::builder()
+        .stage(vk::ShaderStageFlags::VERTEX)
+        .module(vertex_shader.module)
+        .name(b"main\0");
+
+    let frag_stage = vk::PipelineShaderStageCreateInfo::builder()
+        .stage(vk::ShaderStageFlags::FRAGMENT)
+        .module(fragment_shader.module)
+        .name(b"main\0");
+
+    // Vertex Input State, generated in the shader
+
+    let vertex_input_state = vk::PipelineVertexInputStateCreateInfo::builder();
+
+    // Input Assembly State
+
+    let input_assembly_state = vk::PipelineInputAssemblyStateCreateInfo::builder()
+        .topology(vk::PrimitiveTopology::TRIANGLE_LIST)
+        .primitive_restart_enable(false);
+
+    // Viewport State, set when recording
+
+    let viewport_state = vk::PipelineViewportStateCreateInfo::builder()
+        .viewport_count(1)
+        .scissor_count(1);
+
+    let dynamic_states = &[vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+    let dynamic_state =
+        vk::PipelineDynamicStateCreateInfo::builder().dynamic_states(dynamic_states);
+
+    // Rasterization State
+
+    let rasterization_state = vk::PipelineRasterizationStateCreateInfo::builder()
+        .depth_clamp_enable(false)
+        .rasterizer_discard_enable(false)
+        .polygon_mode(vk::PolygonMode::FILL)
+        .line_width(1.0)
+        .cull_mode(vk::CullModeFlags::NONE)
+        .front_face(vk::FrontFace::COUNTER_CLOCKWISE)
+        .depth_bias_enable(false);
+
+    // Multisample State, post processing is single sampled
+
+    let multisample_state = vk::PipelineMultisampleStateCreateInfo::builder()
+        .sample_shading_enable(false)
+        .rasterization_samples(vk::SampleCountFlags::_1);
+
+    // Color Blend State
+
+    let attachment = vk::PipelineColorBlendAttachmentState::builder()
+        .color_write_mask(vk::ColorComponentFlags::all())
+        .blend_enable(additive)
+        .src_color_blend_factor(vk::BlendFactor::ONE)
+        .dst_color_blend_factor(vk::BlendFactor::ONE)
+        .color_blend_op(vk::BlendOp::ADD)
+        .src_alpha_blend_factor(vk::BlendFactor::ONE)
+        .dst_alpha_blend_factor(vk::BlendFactor::ZERO)
+        .alpha_blend_op(vk::BlendOp::ADD);
+
+    let attachments = &[attachment];
+    let color_blend_state = vk::PipelineColorBlendStateCreateInfo::builder()
+        .logic_op_enable(false)
+        .logic_op(vk::LogicOp::COPY)
+        .attachments(attachments)
+        .blend_constants([0.0, 0.0, 0.0, 0.0]);
+
+    // Create
+
+    let stages = &[vert_stage, frag_stage];
+    let info = vk::GraphicsPipelineCreateInfo::builder()
+        .stages(stages)
+        .vertex_input_state(&vertex_input_state)
+        .input_assembly_state(&input_assembly_state)
+        .viewport_state(&viewport_state)
+        .rasterization_state(&rasterization_state)
+        .multisample_state(&multisample_state)
+        .color_blend_state(&color_blend_state)
+        .dynamic_state(&dynamic_state)
+        .layout(layout)
+        .render_pass(render_pass)
+        .subpass(0);
+
+    let pipeline = vk_device
+        .create_graphics_pipelines(vk::PipelineCache::null(), &[info], None)?
+        .0[0];
+
+    // clean up
+    vertex_shader.destroy(vk_device);
+    fragment_shader.destroy(vk_device);
+
+    Ok(pipeline)
+}
@@ -0,0 +1,179 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use anyhow::Result;
+use cgmath::InnerSpace;
+
+use super::{compute_tangents, Mesh};
+use crate::gfx;
+use crate::math::{Vec2, Vec3, Vec4};
+
+/// A mesh generated on the cpu, for examples, prototypes and debug drawing without assets.
+/// shapes are centered on the origin with y up, triangles wind counter clockwise seen from
+/// outside and texture coordinates run from the top left. tessellation counts below the
+/// least a shape needs are raised to it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Primitive {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub texels: Vec<Vec2>,
+    pub indices: Vec<u32>,
+}
+
+impl Primitive {
+    /// a cube with edges of `size`, each face split into `subdivisions` by `subdivisions`
+    /// quads and covered by the whole texture.
+    pub fn cube(size: f32, subdivisions: u32) -> Self {
+        // the normal, right and down of each face, textures read upright on the sides
+        let faces = [
+            (Vec3::unit_z(), Vec3::unit_x(), -Vec3::unit_y()),
+            (-Vec3::unit_z(), -Vec3::unit_x(), -Vec3::unit_y()),
+            (Vec3::unit_x(), -Vec3::unit_z(), -Vec3::unit_y()),
+            (-Vec3::unit_x(), Vec3::unit_z(), -Vec3::unit_y()),
+            (Vec3::unit_y(), Vec3::unit_x(), Vec3::unit_z()),
+            (-Vec3::unit_y(), Vec3::unit_x(), -Vec3::unit_z()),
+        ];
+        let mut primitive = Self::default();
+        for (normal, right, down) in faces {
+            let center = normal * (size * 0.5);
+            primitive.face(center, right * size, down * size, subdivisions);
+        }
+        primitive
+    }
+
+    /// a plane of `size` in x and z facing up, split into `subdivisions` quads along each.
+    pub fn plane(size: Vec2, subdivisions: (u32, u32)) -> Self {
+        let mut primitive = Self::default();
+        let (right, down) = (Vec3::unit_x() * size.x, Vec3::unit_z() * size.y);
+        let (columns, rows) = (subdivisions.0.max(1), subdivisions.1.max(1));
+        primitive.grid(columns, rows, |u, v| {
+            let position = right * (u - 0.5) + down * (v - 0.5);
+            (position, Vec3::unit_y(), Vec2::new(u, v))
+        });
+        primitive
+    }
+
+    /// a sphere of `radius` with `segments` around the equator and `rings` from pole to
+    /// pole, the texture wraps around the equator.
+    pub fn sphere(radius: f32, segments: u32, rings: u32) -> Self {
+        let mut primitive = Self::default();
+        primitive.grid(segments.max(3), rings.max(2), |u, v| {
+            let normal = direction(u * TAU, v * PI);
+            (normal * radius, normal, Vec2::new(u, v))
+        });
+        primitive
+    }
+
+    /// a torus around the y axis, `radius` from its center to the middle of the tube of
+    /// `tube` radius. `segments` go around the center and `sides` around the tube.
+    pub fn torus(radius: f32, tube: f32, segments: u32, sides: u32) -> Self {
+        let mut primitive = Self::default();
+        primitive.grid(segments.max(3), sides.max(3), |u, v| {
+            // the tube starts at its top and turns outward first
+            let angle = u * TAU;
+            let outward = Vec3::new(angle.cos(), 0.0, -angle.sin());
+            let turn = v * TAU;
+            let normal = outward * turn.sin() + Vec3::unit_y() * turn.cos();
+            (outward * radius + normal * tube, normal, Vec2::new(u, v))
+        });
+        primitive
+    }
+
+    /// a capsule of `radius` and `height` from tip to tip along y, at least a sphere.
+    /// `segments` go around it and `rings` make each half sphere, the texture wraps around
+    /// it and runs down evenly over its length.
+    pub fn capsule(radius: f32, height: f32, segments: u32, rings: u32) -> Self {
+        let rings = rings.max(1);
+        let half = (height * 0.5 - radius).max(0.0);
+        // the length down the side from the top, the texture spreads over it
+        let length = PI * radius + 2.0 * half;
+        let mut primitive = Self::default();
+        // the rows of both half spheres, their equators bound the cylinder in between
+        let rows = 2 * rings + 1;
+        primitive.grid(segments.max(3), rows, |u, v| {
+            let row = (v * rows as f32).round() as u32;
+            let (polar, center) = if row <= rings {
+                (row as f32 / rings as f32 * FRAC_PI_2, half)
+            } else {
+                let row = row - rings - 1;
+                (FRAC_PI_2 + row as f32 / rings as f32 * FRAC_PI_2, -half)
+            };
+            let normal = direction(u * TAU, polar);
+            let down = polar * radius + if center < 0.0 { 2.0 * half } else { 0.0 };
+            let position = normal * radius + Vec3::unit_y() * center;
+            (position, normal, Vec2::new(u, down / length))
+        });
+        primitive
+    }
+
+    /// the vertices for the lit pipelines, with tangents from the texture coordinates.
+    pub fn surface_vertices(&self) -> Vec<gfx::SurfaceVertex> {
+        let mut vertices = (0..self.positions.len())
+            .map(|i| {
+                let tangent = Vec4::new(0.0, 0.0, 0.0, 1.0);
+                gfx::SurfaceVertex::new(self.positions[i], self.normals[i], tangent, self.texels[i])
+            })
+            .collect::<Vec<_>>();
+        compute_tangents(&mut vertices, &self.indices);
+        vertices
+    }
+
+    /// the vertices for the unlit pipelines, all of `color`.
+    pub fn vertices(&self, color: Vec3) -> Vec<gfx::Vertex> {
+        (0..self.positions.len())
+            .map(|i| gfx::Vertex::new(self.positions[i], self.texels[i], color))
+            .collect()
+    }
+
+    /// upload the shape into a mesh for the lit pipelines.
+    pub fn upload(&self, device: &gfx::Device) -> Result<Mesh> {
+        Mesh::create_surface(device, &self.surface_vertices(), &self.indices)
+    }
+
+    /// upload the shape into a mesh for the unlit pipelines, all of `color`.
+    pub fn upload_unlit(&self, device: &gfx::Device, color: Vec3) -> Result<Mesh> {
+        Mesh::create(device, &self.vertices(color), &self.indices)
+    }
+
+    // a square face around `center` spanning `right` and `down`, facing `down x right`
+    fn face(&mut self, center: Vec3, right: Vec3, down: Vec3, subdivisions: u32) {
+        let normal = down.cross(right).normalize();
+        let subdivisions = subdivisions.max(1);
+        self.grid(subdivisions, subdivisions, |u, v| {
+            let position = center + right * (u - 0.5) + down * (v - 0.5);
+            (position, normal, Vec2::new(u, v))
+        });
+    }
+
+    // a grid of `columns` by `rows` quads, `vertex` places the vertex at `u` and `v` in 0..=1.
+    // the quads face the cross product of the directions of growing v and growing u
+    fn grid(&mut self, columns: u32, rows: u32, vertex: impl Fn(f32, f32) -> (Vec3, Vec3, Vec2)) {
+        let base = self.positions.len() as u32;
+        for row in 0..=rows {
+            for column in 0..=columns {
+                let (u, v) = (column as f32 / columns as f32, row as f32 / rows as f32);
+                let (position, normal, texel) = vertex(u, v);
+                self.positions.push(position);
+                self.normals.push(normal);
+                self.texels.push(texel);
+            }
+        }
+
+        let stride = columns + 1;
+        for row in 0..rows {
+            for column in 0..columns {
+                let a = base + row * stride + column;
+                let b = a + stride;
+                self.indices.extend([a, b, a + 1, a + 1, b, b + 1]);
+            }
+        }
+    }
+}
+
+// the unit vector `azimuth` around y from x toward -z and `polar` down from +y
+fn direction(azimuth: f32, polar: f32) -> Vec3 {
+    Vec3::new(
+        polar.sin() * azimuth.cos(),
+        polar.cos(),
+        -polar.sin() * azimuth.sin(),
+    )
+}
@@ -0,0 +1,402 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use anyhow::{Context, Result};
+use vulkanalia::prelude::v1_0::*;
+
+use super::{image_barrier, record_cube_mipmaps, Camera, PREFILTERED_LEVELS};
+use crate::gfx;
+use crate::math::{Mat4, Vec3};
+
+/// The number of faces of a cube map.
+pub const CUBE_FACES: usize = 6;
+
+// The direction each face looks in and its up, in the vulkan face order. the faces come out
+// mirrored horizontally against the cube layout, see `ReflectionProbe`.
+const FACE_DIRECTIONS: [([f32; 3], [f32; 3]); CUBE_FACES] = [
+    ([1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+    ([-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+    ([0.0, 1.0, 0.0], [0.0, 0.0, -1.0]),
+    ([0.0, -1.0, 0.0], [0.0, 0.0, 1.0]),
+    ([0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+    ([0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+];
+
+/// the camera drawing cube face `face` seen from `position`, a square 90 degree view. its
+/// image is the face mirrored horizontally.
+pub fn cube_face_camera(position: Vec3, face: usize, near: f32, far: f32) -> Camera {
+    let (forward, up) = FACE_DIRECTIONS[face % CUBE_FACES];
+    Camera {
+        fov_y: 90f32.to_radians(),
+        near,
+        far,
+        ..Camera::looking_at(position, position + Vec3::from(forward), Vec3::from(up))
+    }
+}
+
+/// A cube map of the scene around a point, for reflections on nearby surfaces. every
+/// `record` draws the six faces and filters them down into mips, materials are lit by it
+/// with `PbrMaterial::with_reflection_probe`.
+///
+/// The faces are drawn upright into a scene target and blitted into the cube mirrored. the
+/// scene pipelines cull by winding, which a mirrored projection would turn around.
+pub struct ReflectionProbe {
+    /// names the passes in errors.
+    pub name: String,
+    pub position: Vec3,
+    pub near: f32,
+    pub far: f32,
+    size: u32,
+    mip_levels: u32,
+    attachments: gfx::SceneAttachments,
+    cube: gfx::Texture,
+    cube_view: gfx::TextureView,
+    prefiltered_view: gfx::TextureView,
+    irradiance_view: gfx::TextureView,
+}
+
+impl ReflectionProbe {
+    /// a probe with faces of `size` texels, at least enough for every prefiltered level.
+    /// it shows black until recorded.
+    pub fn create(device: &gfx::Device, name: &str, size: u32, position: Vec3) -> Result<Self> {
+        let size = size.max(1 << (PREFILTERED_LEVELS - 1));
+        let mip_levels = u32::BITS - size.leading_zeros();
+
+        unsafe {
+            let vk_device = device.device();
+            let attachments = device
+                .create_scene_attachments(size, size)
+                .with_context(|| format!("Failed to create reflection probe {}.", name))?;
+            let cube = device.create_cube_texture(
+                size,
+                mip_levels,
+                gfx::SCENE_FORMAT,
+                vk::ImageUsageFlags::SAMPLED
+                    | vk::ImageUsageFlags::TRANSFER_SRC
+                    | vk::ImageUsageFlags::TRANSFER_DST,
+            )?;
+
+            // the whole chain, the levels lit materials sample and the last level as a
+            // rough stand-in for irradiance
+            let cube_view =
+                cube.create_subresource_view(vk_device, vk::ImageViewType::CUBE, 0, mip_levels, 6)?;
+            let prefiltered_view = cube.create_subresource_view(
+                vk_device,
+                vk::ImageViewType::CUBE,
+                0,
+                PREFILTERED_LEVELS,
+                6,
+            )?;
+            let irradiance_view = cube.create_subresource_view(
+                vk_device,
+                vk::ImageViewType::CUBE,
+                mip_levels - 1,
+                1,
+                6,
+            )?;
+
+            // a probe sampled before it was recorded shows black
+            device.execute(|_, command_buffer| {
+                let clear = image_barrier(
+                    cube.image,
+                    0,
+                    mip_levels,
+                    6,
+                    vk::ImageLayout::UNDEFINED,
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    vk::AccessFlags::empty(),
+                    vk::AccessFlags::TRANSFER_WRITE,
+                );
+                vk_device.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::TOP_OF_PIPE,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::DependencyFlags::empty(),
+                    &[] as &[vk::MemoryBarrier],
+                    &[] as &[vk::BufferMemoryBarrier],
+                    &[clear],
+                );
+
+                let color = vk::ClearColorValue {
+                    float32: [0.0, 0.0, 0.0, 1.0],
+                };
+                let range = vk::ImageSubresourceRange::builder()
+                    .aspect_mask(vk::ImageAspectFlags::COLOR)
+                    .base_mip_level(0)
+                    .level_count(mip_levels)
+                    .base_array_layer(0)
+                    .layer_count(6)
+                    .build();
+                vk_device.cmd_clear_color_image(
+                    command_buffer,
+                    cube.image,
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    &color,
+                    &[range],
+                );
+
+                let read = image_barrier(
+                    cube.image,
+                    0,
+                    mip_levels,
+                    6,
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                    vk::AccessFlags::TRANSFER_WRITE,
+                    vk::AccessFlags::SHADER_READ,
+                );
+                vk_device.cmd_pipeline_barrier(
+                    command_buffer,
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::PipelineStageFlags::FRAGMENT_SHADER,
+                    vk::DependencyFlags::empty(),
+                    &[] as &[vk::MemoryBarrier],
+                    &[] as &[vk::BufferMemoryBarrier],
+                    &[read],
+                );
+                Ok(())
+            })?;
+
+            device.set_name(cube.image, name);
+
+            Ok(Self {
+                name: name.to_string(),
+                position,
+                near: 0.1,
+                far: 100.0,
+                size,
+                mip_levels,
+                attachments,
+                cube,
+                cube_view,
+                prefiltered_view,
+                irradiance_view,
+            })
+        }
+    }
+
+    /// the size of a face in texels.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn mip_levels(&self) -> u32 {
+        self.mip_levels
+    }
+
+    /// the cube texture, in the shader read only layout outside of `record`.
+    pub fn texture(&self) -> &gfx::Texture {
+        &self.cube
+    }
+
+    /// the cube with all its levels, e.g. to draw it as a sky.
+    pub fn view(&self) -> vk::ImageView {
+        self.cube_view.view
+    }
+
+    /// the first `PREFILTERED_LEVELS` levels, sampled by rough materials like a prefiltered
+    /// environment.
+    pub fn prefiltered_view(&self) -> vk::ImageView {
+        self.prefiltered_view.view
+    }
+
+    /// the last level, the average of each face, sampled like an irradiance map.
+    pub fn irradiance_view(&self) -> vk::ImageView {
+        self.irradiance_view.view
+    }
+
+    /// the camera of face `face`, see `cube_face_camera`.
+    pub fn face_camera(&self, face: usize) -> Camera {
+        cube_face_camera(self.position, face, self.near, self.far)
+    }
+
+    /// the view and projection of face `face`.
+    pub fn matrices(&self, face: usize) -> (Mat4, Mat4) {
+        self.face_camera(face).matrices(self.size, self.size)
+    }
+
+    /// record the six faces and their mips, must be called outside of any render pass
+    /// before the scene samples the probe, e.g. in `prepare`. `draw` records like the scene
+    /// closure once per face, with the view and projection of the face. every face gets an
+    /// index of its own, `index * CUBE_FACES + face`, so renderers keep the uploads of the
+    /// faces apart.
+    pub fn record<F>(
+        &self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        mut draw: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&gfx::Device, vk::CommandBuffer, usize, Mat4, Mat4) -> Result<()>,
+    {
+        unsafe {
+            let vk_device = device.device();
+
+            // earlier frames may still sample the cube, the first level is written by blits
+            let write = image_barrier(
+                self.cube.image,
+                0,
+                self.mip_levels,
+                6,
+                vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                vk::AccessFlags::empty(),
+                vk::AccessFlags::TRANSFER_WRITE,
+            );
+            vk_device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::FRAGMENT_SHADER | vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[] as &[vk::MemoryBarrier],
+                &[] as &[vk::BufferMemoryBarrier],
+                &[write],
+            );
+
+            for face in 0..CUBE_FACES {
+                let (view, proj) = self.matrices(face);
+                device
+                    .begin_target_pass(command_buffer, &self.attachments, &gfx::PassLoad::default())
+                    .with_context(|| format!("Failed to begin reflection probe {}.", self.name))?;
+                let result = draw(
+                    device,
+                    command_buffer,
+                    index * CUBE_FACES + face,
+                    view,
+                    proj,
+                )
+                .with_context(|| {
+                    format!(
+                        "Failed to record face {} of reflection probe {}.",
+                        face, self.name
+                    )
+                });
+
+                // the pass ends even when drawing failed so the device leaves the target
+                device.end_target_pass(command_buffer);
+                result?;
+
+                self.record_face_copy(vk_device, command_buffer, face as u32);
+            }
+
+            record_cube_mipmaps(
+                vk_device,
+                command_buffer,
+                self.cube.image,
+                self.size,
+                self.mip_levels,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                vk::AccessFlags::TRANSFER_WRITE,
+                vk::PipelineStageFlags::TRANSFER,
+            );
+        }
+        Ok(())
+    }
+
+    // blit the resolved face into its layer of the first level, mirrored
+    unsafe fn record_face_copy(
+        &self,
+        device: &vulkanalia::Device,
+        command_buffer: vk::CommandBuffer,
+        face: u32,
+    ) {
+        let resolve = self.attachments.resolve_texture.image;
+        let barrier = |old, new, src, dst| image_barrier(resolve, 0, 1, 1, old, new, src, dst);
+
+        let src = barrier(
+            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+            vk::AccessFlags::TRANSFER_READ,
+        );
+        device.cmd_pipeline_barrier(
+            command_buffer,
+            vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::DependencyFlags::empty(),
+            &[] as &[vk::MemoryBarrier],
+            &[] as &[vk::BufferMemoryBarrier],
+            &[src],
+        );
+
+        let size = self.size as i32;
+        let subresource = |layer: u32| {
+            vk::ImageSubresourceLayers::builder()
+                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                .mip_level(0)
+                .base_array_layer(layer)
+                .layer_count(1)
+                .build()
+        };
+        let blit = vk::ImageBlit::builder()
+            .src_offsets([
+                vk::Offset3D { x: 0, y: 0, z: 0 },
+                vk::Offset3D {
+                    x: size,
+                    y: size,
+                    z: 1,
+                },
+            ])
+            .src_subresource(subresource(0))
+            .dst_offsets([
+                vk::Offset3D {
+                    x: size,
+                    y: 0,
+                    z: 0,
+                },
+                vk::Offset3D {
+                    x: 0,
+                    y: size,
+                    z: 1,
+                },
+            ])
+            .dst_subresource(subresource(face));
+        device.cmd_blit_image(
+            command_buffer,
+            resolve,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            self.cube.image,
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            &[blit],
+            vk::Filter::NEAREST,
+        );
+
+        // the next face resolves into the target once the blit has read it
+        let back = barrier(
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            vk::AccessFlags::TRANSFER_READ,
+            vk::AccessFlags::empty(),
+        );
+        device.cmd_pipeline_barrier(
+            command_buffer,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+            vk::DependencyFlags::empty(),
+            &[] as &[vk::MemoryBarrier],
+            &[] as &[vk::BufferMemoryBarrier],
+            &[back],
+        );
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe {
+            let vk_device = device.device();
+            for view in [
+                &self.cube_view,
+                &self.prefiltered_view,
+                &self.irradiance_view,
+            ] {
+                view.destroy(vk_device);
+            }
+            self.cube.destroy(vk_device);
+            self.attachments.destroy(vk_device);
+        }
+    }
+}
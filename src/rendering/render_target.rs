@@ -0,0 +1,100 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use anyhow::{Context, Result};
+use vulkanalia::prelude::v1_0::*;
+
+use super::Camera;
+use crate::gfx;
+use crate::math::Mat4;
+
+/// An offscreen color and depth pair the scene is drawn into from its own camera, for
+/// mirrors, portals or minimaps. The resolved color can be bound as a texture in a later
+/// material, it holds linear hdr color like the scene target.
+///
+/// Renderers upload their draws per swapchain image, so a target is drawn with renderers of
+/// its own rather than the ones recording the main scene.
+pub struct RenderTarget {
+    /// names the pass in errors.
+    pub name: String,
+    pub camera: Camera,
+    /// what each pass does with the previous contents, cleared to opaque black by default.
+    pub load: gfx::PassLoad,
+    attachments: gfx::SceneAttachments,
+}
+
+impl RenderTarget {
+    pub fn create(
+        device: &gfx::Device,
+        name: &str,
+        width: u32,
+        height: u32,
+        camera: Camera,
+    ) -> Result<Self> {
+        let attachments = device
+            .create_scene_attachments(width.max(1), height.max(1))
+            .with_context(|| format!("Failed to create render target {}.", name))?;
+
+        Ok(Self {
+            name: name.to_string(),
+            camera,
+            load: gfx::PassLoad::default(),
+            attachments,
+        })
+    }
+
+    pub fn extent(&self) -> vk::Extent2D {
+        self.attachments.extent
+    }
+
+    /// the resolved color texture, in the shader read only layout outside of `record`.
+    pub fn texture(&self) -> &gfx::Texture {
+        &self.attachments.resolve_texture
+    }
+
+    pub fn view(&self) -> gfx::TextureView {
+        self.attachments.resolve_texture_view
+    }
+
+    /// the view and projection of the camera for the target.
+    pub fn matrices(&self) -> (Mat4, Mat4) {
+        let extent = self.extent();
+        self.camera.matrices(extent.width, extent.height)
+    }
+
+    /// record the pass into the target, must be called outside of any render pass before the
+    /// scene samples the target, e.g. in the `prepare` closure of `update_with_prepare`.
+    /// `draw` records like the scene closure and gets the view and projection of the camera.
+    pub fn record<F>(
+        &self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        draw: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(&gfx::Device, vk::CommandBuffer, usize, Mat4, Mat4) -> Result<()>,
+    {
+        let (view, proj) = self.matrices();
+
+        device
+            .begin_target_pass(command_buffer, &self.attachments, &self.load)
+            .with_context(|| format!("Failed to begin render target {}.", self.name))?;
+        let result = draw(device, command_buffer, index, view, proj)
+            .with_context(|| format!("Failed to record render target {}.", self.name));
+
+        // the pass ends even when drawing failed so the device leaves the target
+        device.end_target_pass(command_buffer);
+
+        result
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe { self.attachments.destroy(device.device()) };
+    }
+}
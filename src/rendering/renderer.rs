@@ -6,15 +6,524 @@
     clippy::unnecessary_wraps
 )]
 
-use::anyhow::Result;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::mem::size_of;
 
-pub struct Renderer {
+use ::anyhow::{bail, Result};
+use cgmath::{EuclideanSpace, Point3, Transform};
+use tracing::debug;
+use vulkanalia::prelude::v1_0::*;
+
+use super::{BlendMode, Camera, Material, MeshId, PostChain};
+use crate::gfx;
+use crate::math::{Mat4, Vec3, Vec4};
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct MeshPushConstants {
+    model: Mat4,
+    color: Vec4,
+}
 
+/// The most cameras `record_views` draws a frame with, each reads its own frame uniform.
+const MAX_VIEWS: usize = 4;
 
+/// A queued draw, the depth is the view space distance used for sorting.
+#[derive(Copy, Clone, Debug)]
+struct DrawItem {
+    mesh: MeshId,
+    material: Material,
+    transform: Mat4,
+    depth: f32,
+}
+
+/// Draws meshes in an opaque and a transparent queue. either record the queues in the scene
+/// closure of `Device::update`, or let the renderer run the whole frame between `begin_frame`
+/// and `end_frame`.
+pub struct Renderer {
+    pipeline_layout: vk::PipelineLayout,
+    opaque_pipeline: vk::Pipeline,
+    transparent_pipeline: vk::Pipeline,
+    opaque: Vec<DrawItem>,
+    transparent: Vec<DrawItem>,
+    camera: Camera,
+    present: Option<PostChain>,
+    // the view projection of each view of each swapchain image, see `frame_slot`
+    frame_descriptors: gfx::DescriptorSets,
+    uniforms: Vec<gfx::MappedBuffer>,
 }
 
 impl Renderer {
-    pub fn create()->Result<Self> {
-        Ok(Self{})
+    pub fn create(device: &gfx::Device) -> Result<Self> {
+        unsafe {
+            // the camera is read from a uniform written right before submit
+            let frame_descriptors = gfx::DescriptorSets::create(
+                device.device(),
+                gfx::DescriptorSlot::Frame,
+                &[vk::DescriptorSetLayoutBinding::builder()
+                    .binding(0)
+                    .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+                    .descriptor_count(1)
+                    .stage_flags(vk::ShaderStageFlags::VERTEX)
+                    .build()],
+            )?;
+
+            // layout is shared by both queues
+            let pipeline_layout = create_pipeline_layout(device, &frame_descriptors)?;
+
+            // one pipeline per queue
+            let opaque_pipeline = create_pipeline(device, pipeline_layout, BlendMode::Opaque)?;
+            device.set_name(opaque_pipeline, "mesh opaque");
+            let transparent_pipeline =
+                create_pipeline(device, pipeline_layout, BlendMode::Transparent)?;
+            device.set_name(transparent_pipeline, "mesh transparent");
+
+            Ok(Self {
+                pipeline_layout,
+                opaque_pipeline,
+                transparent_pipeline,
+                opaque: vec![],
+                transparent: vec![],
+                camera: Camera::default(),
+                present: None,
+                frame_descriptors,
+                uniforms: vec![],
+            })
+        }
+    }
+
+    /// start a frame seen from `camera`, draws queued until `end_frame` are part of it.
+    pub fn begin_frame(&mut self, camera: Camera) {
+        self.camera = camera;
+        self.opaque.clear();
+        self.transparent.clear();
+    }
+
+    /// acquire, record, submit and present the frame begun by `begin_frame`. the scene is
+    /// copied to the swapchain as is, apps with post processing record in `Device::update`.
+    pub fn end_frame(
+        &mut self,
+        device: &mut gfx::Device,
+        window: &dyn gfx::DeviceWindow,
+    ) -> Result<()> {
+        self.end_frame_with_latch(device, window, |_, _, _| Ok(()))
+    }
+
+    /// end the frame like `end_frame`, `latch` runs right before it is submitted, see
+    /// `Device::update_with_latch`. it may move the camera of the frame, e.g. to follow input
+    /// that came in while the frame was recorded, the renderer writes the camera to its frame
+    /// uniform after it. the latch also writes what other renderers read from mapped memory,
+    /// e.g. `PbrRenderer::latch`.
+    pub fn end_frame_with_latch<L>(
+        &mut self,
+        device: &mut gfx::Device,
+        window: &dyn gfx::DeviceWindow,
+        latch: L,
+    ) -> Result<()>
+    where
+        L: FnOnce(&gfx::Device, usize, &mut Camera) -> Result<()>,
+    {
+        // the copy to the swapchain is only created for apps running frames this way
+        let mut present = match self.present.take() {
+            Some(present) => present,
+            None => PostChain::create(device)?,
+        };
+
+        // the latch writes the uniforms of the renderer recording the scene
+        let camera = self.camera;
+        let renderer = RefCell::new(self);
+        let result = device.update_with_latch(
+            window,
+            |_, _, _| Ok(()),
+            |device, command_buffer, index| {
+                let extent = device.extent();
+                let (view, proj) = camera.matrices(extent.width, extent.height);
+                let mut renderer = renderer.borrow_mut();
+                renderer.record(device, command_buffer, index, view, proj)
+            },
+            |device, command_buffer, index| present.record(device, command_buffer, index, 0.0),
+            |device, index| {
+                let mut camera = camera;
+                latch(device, index, &mut camera)?;
+                let extent = device.extent();
+                let (view, proj) = camera.matrices(extent.width, extent.height);
+                renderer.borrow().latch(index, view, proj)
+            },
+        );
+
+        // keep the copy even when the frame failed, `destroy` releases it
+        renderer.into_inner().present = Some(present);
+        result
+    }
+
+    /// write the camera of the frame recorded for image `index` again, from the latch stage
+    /// of `Device::update_with_latch` right before the frame is submitted, like
+    /// `PbrRenderer::latch`. the draws were sorted with the view given to `record`, they are
+    /// placed with this one. the cameras of `record_views` are kept.
+    pub fn latch(&self, index: usize, view: Mat4, proj: Mat4) -> Result<()> {
+        // nothing was recorded for the image yet
+        let Some(uniform) = self.uniforms.get(frame_slot(index, 0)) else {
+            return Ok(());
+        };
+        uniform.write(0, &[proj * view])
+    }
+
+    /// queue a registered mesh, the material blend mode selects the queue it is drawn in. the
+    /// transform is pushed with the draw, so a mesh can be queued at any number of transforms.
+    /// recording refuses meshes destroyed in the meantime.
+    pub fn draw(&mut self, mesh: &MeshId, material: &Material, transform: Mat4) {
+        let item = DrawItem {
+            mesh: *mesh,
+            material: *material,
+            transform,
+            depth: 0.0,
+        };
+
+        match material.blend {
+            BlendMode::Opaque => self.opaque.push(item),
+            BlendMode::Transparent => self.transparent.push(item),
+        }
+    }
+
+    /// record both queues and clear them, must be called in the frame render pass.
+    pub fn record(
+        &mut self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        view: Mat4,
+        proj: Mat4,
+    ) -> Result<()> {
+        let area = vk::Rect2D {
+            offset: vk::Offset2D::default(),
+            extent: device.extent(),
+        };
+        let slot = frame_slot(index, 0);
+        unsafe { self.record_view(device, command_buffer, slot, area, view, proj)? };
+
+        // start over for the next frame
+        self.opaque.clear();
+        self.transparent.clear();
+
+        Ok(())
+    }
+
+    /// record both queues once per camera into its rectangle of the pass and clear them, for
+    /// split screen. the projections follow the aspect of each rectangle, at most four cameras
+    /// are drawn.
+    pub fn record_views(
+        &mut self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        views: &[(Camera, vk::Rect2D)],
+    ) -> Result<()> {
+        if views.len() > MAX_VIEWS {
+            bail!(
+                "Split screen draws at most {} views, {} were given.",
+                MAX_VIEWS,
+                views.len()
+            );
+        }
+
+        for (i, (camera, area)) in views.iter().enumerate() {
+            let (view, proj) = camera.matrices(area.extent.width, area.extent.height);
+            let slot = frame_slot(index, i);
+            unsafe { self.record_view(device, command_buffer, slot, *area, view, proj)? };
+        }
+
+        // start over for the next frame
+        self.opaque.clear();
+        self.transparent.clear();
+
+        Ok(())
     }
-}
\ No newline at end of file
+
+    // record both queues seen from `view` into `area`, the camera goes to the uniform `slot`
+    unsafe fn record_view(
+        &mut self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        slot: usize,
+        area: vk::Rect2D,
+        view: Mat4,
+        proj: Mat4,
+    ) -> Result<()> {
+        // compute the view space distance of each draw
+        for item in self.opaque.iter_mut().chain(self.transparent.iter_mut()) {
+            item.depth = view_depth(&view, &item.transform, item.mesh.center);
+        }
+
+        // opaque front to back to reject hidden fragments early
+        self.opaque
+            .sort_by(|a, b| a.depth.partial_cmp(&b.depth).unwrap_or(Ordering::Equal));
+
+        // transparent back to front for correct blending
+        self.transparent
+            .sort_by(|a, b| b.depth.partial_cmp(&a.depth).unwrap_or(Ordering::Equal));
+
+        // viewport and scissor are dynamic, the scissor follows the damage
+        let viewport = vk::Viewport::builder()
+            .x(area.offset.x as f32)
+            .y(area.offset.y as f32)
+            .width(area.extent.width as f32)
+            .height(area.extent.height as f32)
+            .min_depth(0.0)
+            .max_depth(1.0)
+            .build();
+        let scissor = intersect(area, device.scissor());
+        device.set_viewports(command_buffer, 0, &[(viewport, scissor)])?;
+
+        self.bind_camera(device, command_buffer, slot, proj * view)?;
+
+        let queues = [
+            (self.opaque_pipeline, &self.opaque),
+            (self.transparent_pipeline, &self.transparent),
+        ];
+
+        for (pipeline, items) in queues {
+            // skip empty queues
+            if items.is_empty() {
+                continue;
+            }
+
+            debug!(?pipeline, "bind pipeline");
+            let vk_device = device.device();
+            vk_device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
+
+            for item in items.iter() {
+                self.record_item(device, command_buffer, item)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // write the camera to the uniform `slot` and bind it
+    unsafe fn bind_camera(
+        &mut self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        slot: usize,
+        view_proj: Mat4,
+    ) -> Result<()> {
+        let vk_device = device.device();
+
+        // a recreated swapchain may have more images
+        while self.uniforms.len() <= slot {
+            self.uniforms.push(create_uniform_buffer(device)?);
+        }
+        let uniform = &self.uniforms[slot];
+        uniform.write(0, &[view_proj])?;
+
+        let set = self.frame_descriptors.get(vk_device, slot)?;
+        let buffer = gfx::BindingResource::Buffer {
+            buffer: uniform.buffer(),
+            offset: 0,
+            range: size_of::<Mat4>() as vk::DeviceSize,
+        };
+        self.frame_descriptors
+            .write(vk_device, set, &[(0, buffer)])?;
+
+        debug!(first = gfx::FRAME_SET, ?set, "bind descriptor sets");
+        vk_device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            self.pipeline_layout,
+            gfx::FRAME_SET,
+            &[set],
+            &[],
+        );
+        Ok(())
+    }
+
+    unsafe fn record_item(
+        &self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        item: &DrawItem,
+    ) -> Result<()> {
+        let constants = MeshPushConstants {
+            model: item.transform,
+            color: item.material.tint(),
+        };
+
+        let bytes = std::slice::from_raw_parts(
+            &constants as *const MeshPushConstants as *const u8,
+            size_of::<MeshPushConstants>(),
+        );
+
+        device.device().cmd_push_constants(
+            command_buffer,
+            self.pipeline_layout,
+            vk::ShaderStageFlags::VERTEX,
+            0,
+            bytes,
+        );
+
+        // bound through an encoder, which resolves the ids and keeps the buffers alive until
+        // the frame completed
+        let mut encoder = device.encoder(command_buffer);
+        encoder.bind_vertex_buffer_ids(0, &[(item.mesh.vertices, 0)])?;
+        encoder.bind_index_buffer_id(item.mesh.indices, item.mesh.index_type)?;
+        let indices = device.with_resource(item.mesh.indices, |b| {
+            gfx::IndexBinding::new(b, item.mesh.index_type)
+        })?;
+        indices.draw(device.device(), command_buffer, 0, item.mesh.count, 1)
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe {
+            let vk_device = device.device();
+
+            // destroy pipelines
+            vk_device.destroy_pipeline(self.opaque_pipeline, None);
+            vk_device.destroy_pipeline(self.transparent_pipeline, None);
+            vk_device.destroy_pipeline_layout(self.pipeline_layout, None);
+
+            // destroy the camera uniforms
+            self.uniforms.iter().for_each(|b| b.destroy(vk_device));
+            self.frame_descriptors.destroy(vk_device);
+        }
+
+        // destroy the copy to the swapchain
+        if let Some(present) = &self.present {
+            present.destroy(device);
+        }
+    }
+}
+
+// the uniform holding the camera of view `view` of swapchain image `index`
+fn frame_slot(index: usize, view: usize) -> usize {
+    index * MAX_VIEWS + view
+}
+
+// the part of `a` inside `b`, empty when they don't overlap
+fn intersect(a: vk::Rect2D, b: vk::Rect2D) -> vk::Rect2D {
+    let x0 = a.offset.x.max(b.offset.x);
+    let y0 = a.offset.y.max(b.offset.y);
+    let x1 = (a.offset.x + a.extent.width as i32).min(b.offset.x + b.extent.width as i32);
+    let y1 = (a.offset.y + a.extent.height as i32).min(b.offset.y + b.extent.height as i32);
+    vk::Rect2D {
+        offset: vk::Offset2D { x: x0, y: y0 },
+        extent: vk::Extent2D {
+            width: (x1 - x0).max(0) as u32,
+            height: (y1 - y0).max(0) as u32,
+        },
+    }
+}
+
+/// the distance in front of the camera of the transformed mesh center.
+pub fn view_depth(view: &Mat4, transform: &Mat4, center: Vec3) -> f32 {
+    let center = (view * transform).transform_point(Point3::from_vec(center));
+
+    // right handed view space looks down negative z
+    -center.z
+}
+
+unsafe fn create_pipeline_layout(
+    device: &gfx::Device,
+    frame: &gfx::DescriptorSets,
+) -> Result<vk::PipelineLayout> {
+    // Push Constant Ranges
+
+    let push_constant_range = vk::PushConstantRange::builder()
+        .stage_flags(vk::ShaderStageFlags::VERTEX)
+        .offset(0)
+        .size(size_of::<MeshPushConstants>() as u32);
+
+    // Layout
+
+    device.create_pipeline_layout(&[frame], &[push_constant_range.build()])
+}
+
+unsafe fn create_uniform_buffer(device: &gfx::Device) -> Result<gfx::MappedBuffer> {
+    let buffer = device.create_buffer(
+        size_of::<Mat4>() as vk::DeviceSize,
+        vk::BufferUsageFlags::UNIFORM_BUFFER,
+        vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+    )?;
+    gfx::MappedBuffer::map(device.device(), buffer)
+}
+
+unsafe fn create_pipeline(
+    device: &gfx::Device,
+    layout: vk::PipelineLayout,
+    blend: BlendMode,
+) -> Result<vk::Pipeline> {
+    create_mesh_pipeline(
+        device,
+        layout,
+        blend,
+        include_bytes!("../../shaders/mesh_vert.spv"),
+        include_bytes!("../../shaders/mesh_frag.spv"),
+        &[gfx::Vertex::layout()],
+    )
+}
+
+/// create a pipeline drawing indexed meshes in the scene pass, `blend` selects the queue state.
+/// `buffers` are the layouts of the vertex buffers read, per vertex or per instance.
+pub unsafe fn create_mesh_pipeline(
+    device: &gfx::Device,
+    layout: vk::PipelineLayout,
+    blend: BlendMode,
+    vertex: &[u8],
+    fragment: &[u8],
+    buffers: &[gfx::VertexBufferLayout],
+) -> Result<vk::Pipeline> {
+    create_mesh_pipeline_for(
+        device,
+        device.render_pass(),
+        device.samples(),
+        layout,
+        blend,
+        vertex,
+        fragment,
+        buffers,
+    )
+}
+
+/// create a mesh pipeline like `create_mesh_pipeline` for another render pass than the scene
+/// pass, e.g. one from `Device::render_pass_for`, drawing with `samples` per pixel.
+pub unsafe fn create_mesh_pipeline_for(
+    device: &gfx::Device,
+    render_pass: vk::RenderPass,
+    samples: vk::SampleCountFlags,
+    layout: vk::PipelineLayout,
+    blend: BlendMode,
+    vertex: &[u8],
+    fragment: &[u8],
+    buffers: &[gfx::VertexBufferLayout],
+) -> Result<vk::Pipeline> {
+    let mut pipeline = mesh_pipeline_builder(layout, blend)
+        .vertex_shader(vertex)
+        .fragment_shader(fragment);
+    pipeline.buffers = buffers.to_vec();
+
+    let target = gfx::PipelineTarget {
+        render_pass,
+        subpass: 0,
+        samples,
+        color_attachments: 1,
+        depth: true,
+        depth_read_only: false,
+    };
+    pipeline.build_for(device.device(), &target)
+}
+
+/// the state of the pipelines drawing meshes of `blend`, without shaders and vertex buffers.
+pub fn mesh_pipeline_builder<'a>(
+    layout: vk::PipelineLayout,
+    blend: BlendMode,
+) -> gfx::RenderPipelineBuilder<'a> {
+    // transparent surfaces show their back faces, they are tested but do not write depth
+    let pipeline = match blend {
+        BlendMode::Opaque => gfx::RenderPipelineBuilder::new(layout).depth(true),
+        BlendMode::Transparent => gfx::RenderPipelineBuilder::new(layout)
+            .depth_read_only()
+            .cull(vk::CullModeFlags::NONE)
+            .blend(gfx::Blend::Alpha),
+    };
+    pipeline.sample_shading(0.2)
+}
@@ -0,0 +1,183 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use cgmath::{InnerSpace, SquareMatrix, VectorSpace};
+
+use crate::math::{Mat4, Quat, Vec3};
+
+/// A translation, rotation and scale, applied as scale first and translation last like gltf.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Transform {
+    pub fn new(translation: Vec3, rotation: Quat, scale: Vec3) -> Self {
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    /// the transform as a matrix, translation * rotation * scale.
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_translation(self.translation)
+            * Mat4::from(self.rotation)
+            * Mat4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+    }
+
+    /// interpolate each component, rotations along the shortest arc.
+    pub fn blend(&self, other: &Transform, weight: f32) -> Self {
+        Self {
+            translation: self.translation.lerp(other.translation, weight),
+            rotation: slerp(self.rotation, other.rotation, weight),
+            scale: self.scale.lerp(other.scale, weight),
+        }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            translation: Vec3::new(0.0, 0.0, 0.0),
+            rotation: Quat::new(1.0, 0.0, 0.0, 0.0),
+            scale: Vec3::new(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+/// A named node of a hierarchy, `rest` is the transform without any animation applied.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SceneNode {
+    pub name: String,
+    pub parent: Option<usize>,
+    pub rest: Transform,
+    pub transform: Transform,
+}
+
+/// A hierarchy of nodes, nodes are addressed by their index.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SceneGraph {
+    pub nodes: Vec<SceneNode>,
+}
+
+impl SceneGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// add a node at rest, `parent` must already exist. returns the index of the node.
+    pub fn add(&mut self, name: &str, parent: Option<usize>, rest: Transform) -> usize {
+        self.nodes.push(SceneNode {
+            name: name.to_string(),
+            parent,
+            rest,
+            transform: rest,
+        });
+        self.nodes.len() - 1
+    }
+
+    /// the index of the first node with a name.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.nodes.iter().position(|n| n.name == name)
+    }
+
+    /// the current local transform of every node.
+    pub fn transforms(&self) -> Vec<Transform> {
+        self.nodes.iter().map(|n| n.transform).collect()
+    }
+
+    /// the rest transform of every node.
+    pub fn rest_transforms(&self) -> Vec<Transform> {
+        self.nodes.iter().map(|n| n.rest).collect()
+    }
+
+    /// put every node back at rest.
+    pub fn reset(&mut self) {
+        self.nodes.iter_mut().for_each(|n| n.transform = n.rest);
+    }
+
+    /// the world matrix of every node, parents may be stored after their children.
+    pub fn world_matrices(&self) -> Vec<Mat4> {
+        let mut worlds: Vec<Option<Mat4>> = vec![None; self.nodes.len()];
+        for index in 0..self.nodes.len() {
+            self.resolve(index, &mut worlds);
+        }
+        worlds
+            .into_iter()
+            .map(|w| w.unwrap_or(Mat4::identity()))
+            .collect()
+    }
+
+    fn resolve(&self, index: usize, worlds: &mut [Option<Mat4>]) -> Mat4 {
+        if let Some(world) = worlds[index] {
+            return world;
+        }
+
+        // cycles can't come from gltf, treat a parent out of range as the root
+        let local = self.nodes[index].transform.matrix();
+        let world = match self.nodes[index].parent {
+            Some(parent) if parent < self.nodes.len() && parent != index => {
+                self.resolve(parent, worlds) * local
+            }
+            _ => local,
+        };
+
+        worlds[index] = Some(world);
+        world
+    }
+
+    /// build the node hierarchy of a gltf document, node indices match the document.
+    #[cfg(feature = "gltf")]
+    pub fn from_gltf(document: &gltf::Document) -> Self {
+        let mut nodes = document
+            .nodes()
+            .map(|node| {
+                let (translation, rotation, scale) = node.transform().decomposed();
+                let rest = Transform::new(
+                    translation.into(),
+                    Quat::new(rotation[3], rotation[0], rotation[1], rotation[2]),
+                    scale.into(),
+                );
+                SceneNode {
+                    name: node
+                        .name()
+                        .map(str::to_string)
+                        .unwrap_or_else(|| format!("node {}", node.index())),
+                    parent: None,
+                    rest,
+                    transform: rest,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // parents are only known through their children
+        for node in document.nodes() {
+            for child in node.children() {
+                nodes[child.index()].parent = Some(node.index());
+            }
+        }
+
+        Self { nodes }
+    }
+}
+
+/// spherical interpolation along the shortest arc, normalized.
+pub fn slerp(a: Quat, b: Quat, t: f32) -> Quat {
+    let b = if a.dot(b) < 0.0 { -b } else { b };
+
+    // nearly identical rotations would divide by zero
+    if a.dot(b) > 0.9995 {
+        return a.nlerp(b, t);
+    }
+
+    a.slerp(b, t).normalize()
+}
@@ -0,0 +1,165 @@
+use vulkanalia::prelude::v1_0::*;
+
+use crate::gfx;
+use crate::math::{Mat4, Vec2};
+
+/// The coordinates of 2d drawing for tools and ui, logical pixels of the window with the
+/// origin at its top left, x to the right and y down. `proj` is the view projection sprites
+/// and lines take to draw in them, line widths are logical pixels already and sprites placed
+/// with `Sprite::pixels` keep their texture upright. z orders the drawing, 0 is in front of
+/// the whole scene and 1 at the far plane.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ScreenSpace {
+    /// the size of the window in logical pixels.
+    pub size: Vec2,
+    /// physical pixels per logical pixel.
+    pub scale_factor: f32,
+}
+
+impl ScreenSpace {
+    /// the screen of a swapchain of `extent` physical pixels on a display of `scale_factor`.
+    pub fn new(extent: vk::Extent2D, scale_factor: f64) -> Self {
+        let (width, height) = gfx::logical_extent(extent, scale_factor);
+        Self {
+            size: Vec2::new(width.max(1.0), height.max(1.0)),
+            scale_factor: if scale_factor > 0.0 {
+                scale_factor as f32
+            } else {
+                1.0
+            },
+        }
+    }
+
+    /// the screen of the window `device` presents to, the same at any render scale.
+    pub fn of(device: &gfx::Device) -> Self {
+        Self::new(device.swapchain_extent(), device.scale_factor())
+    }
+
+    /// the orthographic projection from logical pixels to vulkan clip space.
+    pub fn proj(&self) -> Mat4 {
+        let (sx, sy) = (2.0 / self.size.x, 2.0 / self.size.y);
+        #[rustfmt::skip]
+        let proj = Mat4::new(
+             sx,  0.0, 0.0, 0.0,
+            0.0,   sy, 0.0, 0.0,
+            0.0,  0.0, 1.0, 0.0,
+           -1.0, -1.0, 0.0, 1.0,
+        );
+        proj
+    }
+
+    /// the normalized device coordinates of `point` in logical pixels, -1..1 over the window.
+    pub fn to_ndc(&self, point: Vec2) -> Vec2 {
+        Vec2::new(
+            point.x / self.size.x * 2.0 - 1.0,
+            point.y / self.size.y * 2.0 - 1.0,
+        )
+    }
+
+    /// the logical pixel at normalized device coordinates `ndc`.
+    pub fn from_ndc(&self, ndc: Vec2) -> Vec2 {
+        Vec2::new(
+            (ndc.x + 1.0) * 0.5 * self.size.x,
+            (ndc.y + 1.0) * 0.5 * self.size.y,
+        )
+    }
+
+    /// the logical pixel at `physical` pixels, e.g. a cursor position from winit.
+    pub fn from_physical(&self, physical: Vec2) -> Vec2 {
+        physical / self.scale_factor
+    }
+
+    /// the physical pixel at `point` in logical pixels, fractional at fractional scales.
+    pub fn to_physical(&self, point: Vec2) -> Vec2 {
+        point * self.scale_factor
+    }
+
+    /// the physical pixels covered by the rectangle at `min` of `size` logical pixels, grown
+    /// to whole pixels, e.g. to report it as damage.
+    pub fn physical_rect(&self, min: Vec2, size: Vec2) -> vk::Rect2D {
+        let (min, max) = (self.to_physical(min), self.to_physical(min + size));
+        let (x0, y0) = (min.x.floor().max(0.0) as i32, min.y.floor().max(0.0) as i32);
+        let (x1, y1) = (max.x.ceil().max(0.0) as i32, max.y.ceil().max(0.0) as i32);
+        vk::Rect2D {
+            offset: vk::Offset2D { x: x0, y: y0 },
+            extent: vk::Extent2D {
+                width: (x1 - x0).max(0) as u32,
+                height: (y1 - y0).max(0) as u32,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Vec4;
+
+    // a window of 800x600 logical pixels on a display of scale 1.5
+    fn screen() -> ScreenSpace {
+        ScreenSpace::new(
+            vk::Extent2D {
+                width: 1200,
+                height: 900,
+            },
+            1.5,
+        )
+    }
+
+    #[test]
+    fn corners_map_to_the_edges_of_clip_space() {
+        let screen = screen();
+        assert_eq!(screen.size, Vec2::new(800.0, 600.0));
+        assert_eq!(screen.to_ndc(Vec2::new(0.0, 0.0)), Vec2::new(-1.0, -1.0));
+        assert_eq!(screen.to_ndc(Vec2::new(800.0, 600.0)), Vec2::new(1.0, 1.0));
+        assert_eq!(screen.to_ndc(Vec2::new(800.0, 0.0)), Vec2::new(1.0, -1.0));
+
+        // the projection agrees, the top left is at -1 in vulkan clip space
+        let proj = screen.proj();
+        assert_eq!(
+            proj * Vec4::new(0.0, 0.0, 0.0, 1.0),
+            Vec4::new(-1.0, -1.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            proj * Vec4::new(800.0, 600.0, 0.5, 1.0),
+            Vec4::new(1.0, 1.0, 0.5, 1.0)
+        );
+    }
+
+    #[test]
+    fn points_survive_the_trip_through_ndc() {
+        let screen = screen();
+        for point in [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(200.0, 150.0),
+            Vec2::new(400.0, 600.0),
+        ] {
+            assert_eq!(screen.from_ndc(screen.to_ndc(point)), point);
+        }
+        assert_eq!(
+            screen.from_physical(screen.to_physical(Vec2::new(10.0, 20.0))),
+            Vec2::new(10.0, 20.0)
+        );
+    }
+
+    #[test]
+    fn physical_rects_grow_to_whole_pixels() {
+        let screen = screen();
+
+        // 1..3 logical pixels are 1.5..4.5 physical ones
+        let rect = screen.physical_rect(Vec2::new(1.0, 1.0), Vec2::new(2.0, 2.0));
+        assert_eq!(rect.offset, vk::Offset2D { x: 1, y: 1 });
+        assert_eq!(
+            rect.extent,
+            vk::Extent2D {
+                width: 4,
+                height: 4
+            }
+        );
+
+        // nothing reaches left of or above the window
+        let rect = screen.physical_rect(Vec2::new(-1.0, -1.0), Vec2::new(2.0, 2.0));
+        assert_eq!(rect.offset, vk::Offset2D { x: 0, y: 0 });
+        assert_eq!(rect.extent.width, 2);
+    }
+}
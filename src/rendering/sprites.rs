@@ -0,0 +1,551 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use std::mem::size_of;
+
+use anyhow::Result;
+use tracing::debug;
+use vulkanalia::prelude::v1_0::*;
+
+use crate::gfx;
+use crate::math::{Mat4, Vec2, Vec3, Vec4};
+
+// The number of sprites the per-image buffers can hold initially.
+const INITIAL_CAPACITY: usize = 256;
+
+/// A color texture for sprites, premultiplied on import unless created straight.
+pub struct SpriteTexture {
+    pub texture: gfx::Texture,
+    pub view: gfx::TextureView,
+    pub alpha: gfx::AlphaMode,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl SpriteTexture {
+    /// load a png with straight alpha, `alpha` selects how it is stored on the gpu.
+    pub fn load(device: &gfx::Device, path: &str, alpha: gfx::AlphaMode) -> Result<Self> {
+        let (width, height, pixels) = gfx::read_png(path)?;
+        Self::create(device, width, height, pixels, alpha)
+    }
+
+    /// create from straight srgb rgba8 pixels, `alpha` selects how it is stored on the gpu.
+    pub fn create(
+        device: &gfx::Device,
+        width: u32,
+        height: u32,
+        mut pixels: Vec<u8>,
+        alpha: gfx::AlphaMode,
+    ) -> Result<Self> {
+        let import = gfx::TextureImport::Color;
+
+        // converting before upload keeps the mipmaps and filtering free of fringes
+        if alpha == gfx::AlphaMode::Premultiplied {
+            gfx::premultiply(&mut pixels, import.color_space());
+        }
+
+        let (texture, view) = device.upload_texture(width, height, &pixels, import)?;
+
+        Ok(Self {
+            texture,
+            view,
+            alpha,
+            width,
+            height,
+        })
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe {
+            let vk_device = device.device();
+
+            self.view.destroy(vk_device);
+            self.texture.destroy(vk_device);
+        }
+    }
+}
+
+/// A textured quad, sized in world units, or in logical pixels with `ScreenSpace`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sprite {
+    pub position: Vec3,
+    /// rotation around the pivot in radians.
+    pub rotation: f32,
+    pub size: Vec2,
+    /// the point the sprite is placed and rotated at, 0..1 over the quad.
+    pub pivot: Vec2,
+    /// the texture rectangle, min and max in 0..1.
+    pub texels: Vec4,
+    /// straight alpha tint multiplied with the texture.
+    pub color: Vec4,
+}
+
+impl Sprite {
+    /// a sprite showing the whole texture, centered on `position`.
+    pub fn new(position: Vec3, size: Vec2) -> Self {
+        Self {
+            position,
+            rotation: 0.0,
+            size,
+            pivot: Vec2::new(0.5, 0.5),
+            texels: Vec4::new(0.0, 0.0, 1.0, 1.0),
+            color: Vec4::new(1.0, 1.0, 1.0, 1.0),
+        }
+    }
+
+    /// a sprite of `size` logical pixels with its top left corner at `min`, for drawing
+    /// with `ScreenSpace::proj`. its height is negative, y grows down the screen, and the
+    /// pivot is its top left corner. rotations turn it clockwise.
+    pub fn pixels(min: Vec2, size: Vec2) -> Self {
+        Self {
+            size: Vec2::new(size.x, -size.y),
+            pivot: Vec2::new(0.0, 1.0),
+            ..Self::new(min.extend(0.0), size)
+        }
+    }
+
+    pub fn rotated(self, rotation: f32) -> Self {
+        Self { rotation, ..self }
+    }
+
+    pub fn tinted(self, color: Vec4) -> Self {
+        Self { color, ..self }
+    }
+}
+
+/// One instance of the sprite pipeline.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct SpriteInstance {
+    // position and rotation
+    placement: Vec4,
+    // size and pivot
+    extent: Vec4,
+    texels: Vec4,
+    color: Vec4,
+}
+
+crate::pod!(SpriteInstance {
+    placement: Vec4,
+    extent: Vec4,
+    texels: Vec4,
+    color: Vec4,
+});
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct SpritePushConstants {
+    view_proj: Mat4,
+    straight: u32,
+    padding: [u32; 3],
+}
+
+/// A range of sprites drawn with the same texture.
+#[derive(Copy, Clone, Debug)]
+struct SpriteBatch {
+    first: u32,
+    count: u32,
+    view: vk::ImageView,
+    alpha: gfx::AlphaMode,
+}
+
+/// Renders textured quads in draw order, blending with premultiplied alpha.
+pub struct SpriteRenderer {
+    sampler: vk::Sampler,
+    pipeline_layout: vk::PipelineLayout,
+    pipeline: vk::Pipeline,
+    // one descriptor set per batch and swapchain image
+    descriptors: Vec<gfx::DescriptorSets>,
+    buffers: Vec<gfx::Buffer>,
+    instances: Vec<SpriteInstance>,
+    batches: Vec<SpriteBatch>,
+}
+
+impl SpriteRenderer {
+    pub fn create(device: &gfx::Device) -> Result<Self> {
+        unsafe {
+            let vk_device = device.device();
+
+            // create the sampler and a first descriptor slot to take the layout from
+            let sampler = device.sampler(&gfx::SamplerDescriptor::linear(
+                vk::SamplerAddressMode::CLAMP_TO_EDGE,
+            ))?;
+            let descriptors = vec![create_descriptors(vk_device)?];
+
+            // create pipeline objects
+            let (pipeline_layout, pipeline) = create_pipeline(device, &descriptors[0])?;
+            device.set_name(pipeline, "sprites");
+
+            // create one instance buffer per swapchain image
+            let buffers = (0..device.image_count())
+                .map(|_| create_instance_buffer(device, INITIAL_CAPACITY))
+                .collect::<Result<Vec<_>>>()?;
+
+            Ok(Self {
+                sampler,
+                pipeline_layout,
+                pipeline,
+                descriptors,
+                buffers,
+                instances: vec![],
+                batches: vec![],
+            })
+        }
+    }
+
+    /// queue a sprite for the next recorded frame, sprites are drawn in queue order.
+    pub fn draw(&mut self, texture: &SpriteTexture, sprite: &Sprite) {
+        let first = self.instances.len() as u32;
+
+        self.instances.push(SpriteInstance {
+            placement: sprite.position.extend(sprite.rotation),
+            extent: Vec4::new(sprite.size.x, sprite.size.y, sprite.pivot.x, sprite.pivot.y),
+            texels: sprite.texels,
+            color: sprite.color,
+        });
+
+        // extend the last batch when the texture stays the same
+        match self.batches.last_mut() {
+            Some(batch) if batch.view == texture.view.view => batch.count += 1,
+            _ => self.batches.push(SpriteBatch {
+                first,
+                count: 1,
+                view: texture.view.view,
+                alpha: texture.alpha,
+            }),
+        }
+    }
+
+    /// record all queued sprites and clear the queue, must be called in the frame render pass.
+    pub fn record(
+        &mut self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        view_proj: Mat4,
+    ) -> Result<()> {
+        unsafe {
+            // nothing queued
+            if self.instances.is_empty() {
+                self.batches.clear();
+                return Ok(());
+            }
+
+            let vk_device = device.device();
+
+            // a recreated swapchain may have more images
+            while self.buffers.len() <= index {
+                self.buffers
+                    .push(create_instance_buffer(device, INITIAL_CAPACITY)?);
+            }
+
+            // grow the image buffer if needed, the image fence has been waited on already
+            let size = (size_of::<SpriteInstance>() * self.instances.len()) as vk::DeviceSize;
+            if self.buffers[index].size < size {
+                self.buffers[index].destroy(vk_device);
+                self.buffers[index] =
+                    create_instance_buffer(device, self.instances.len().next_power_of_two())?;
+            }
+
+            // upload the instances
+            let buffer = &self.buffers[index];
+            buffer.write(vk_device, 0, &self.instances)?;
+
+            // every batch needs its own set
+            while self.descriptors.len() < self.batches.len() {
+                self.descriptors.push(create_descriptors(vk_device)?);
+            }
+
+            let extent = device.extent();
+
+            // viewport and scissor are dynamic, the scissors follow the damage
+            let viewport = vk::Viewport::builder()
+                .x(0.0)
+                .y(0.0)
+                .width(extent.width as f32)
+                .height(extent.height as f32)
+                .min_depth(0.0)
+                .max_depth(1.0);
+            let scissors = device.scissors();
+
+            debug!(pipeline = ?self.pipeline, "bind pipeline");
+            vk_device.cmd_bind_pipeline(
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.pipeline,
+            );
+            vk_device.cmd_set_viewport(command_buffer, 0, &[viewport]);
+            vk_device.cmd_bind_vertex_buffers(command_buffer, 0, &[buffer.buffer], &[0]);
+
+            for (slot, batch) in self.batches.iter().enumerate() {
+                // point the batch set at its texture
+                let set = self.descriptors[slot].get(vk_device, index)?;
+                write_set(vk_device, set, batch.view, self.sampler);
+
+                self.descriptors[slot].bind(
+                    vk_device,
+                    command_buffer,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    self.pipeline_layout,
+                    set,
+                );
+
+                let constants = SpritePushConstants {
+                    view_proj,
+                    straight: (batch.alpha == gfx::AlphaMode::Straight) as u32,
+                    padding: [0; 3],
+                };
+
+                let bytes = std::slice::from_raw_parts(
+                    &constants as *const SpritePushConstants as *const u8,
+                    size_of::<SpritePushConstants>(),
+                );
+
+                vk_device.cmd_push_constants(
+                    command_buffer,
+                    self.pipeline_layout,
+                    vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+                    0,
+                    bytes,
+                );
+
+                // six vertices per sprite quad
+                debug!(vertices = 6, instances = batch.count, "draw");
+                for scissor in &scissors {
+                    vk_device.cmd_set_scissor(command_buffer, 0, &[*scissor]);
+                    vk_device.cmd_draw(command_buffer, 6, batch.count, 0, batch.first);
+                }
+            }
+
+            // start over for the next frame
+            self.instances.clear();
+            self.batches.clear();
+
+            Ok(())
+        }
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe {
+            let vk_device = device.device();
+
+            // destroy buffers
+            self.buffers.iter().for_each(|b| b.destroy(vk_device));
+
+            // destroy pipeline
+            vk_device.destroy_pipeline(self.pipeline, None);
+            vk_device.destroy_pipeline_layout(self.pipeline_layout, None);
+
+            // destroy descriptors and sampler
+            self.descriptors.iter().for_each(|d| d.destroy(vk_device));
+        }
+    }
+}
+
+unsafe fn create_descriptors(device: &vulkanalia::Device) -> Result<gfx::DescriptorSets> {
+    let bindings = &[
+        vk::DescriptorSetLayoutBinding::builder()
+            .binding(0)
+            .descriptor_type(vk::DescriptorType::SAMPLED_IMAGE)
+            .descriptor_count(1)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build(),
+        vk::DescriptorSetLayoutBinding::builder()
+            .binding(1)
+            .descriptor_type(vk::DescriptorType::SAMPLER)
+            .descriptor_count(1)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build(),
+    ];
+
+    gfx::DescriptorSets::create(device, gfx::DescriptorSlot::Material, bindings)
+}
+
+unsafe fn write_set(
+    device: &vulkanalia::Device,
+    set: vk::DescriptorSet,
+    view: vk::ImageView,
+    sampler: vk::Sampler,
+) {
+    let image_info = &[vk::DescriptorImageInfo::builder()
+        .image_view(view)
+        .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)];
+    let sampler_info = &[vk::DescriptorImageInfo::builder().sampler(sampler)];
+
+    let writes = &[
+        vk::WriteDescriptorSet::builder()
+            .dst_set(set)
+            .dst_binding(0)
+            .descriptor_type(vk::DescriptorType::SAMPLED_IMAGE)
+            .image_info(image_info),
+        vk::WriteDescriptorSet::builder()
+            .dst_set(set)
+            .dst_binding(1)
+            .descriptor_type(vk::DescriptorType::SAMPLER)
+            .image_info(sampler_info),
+    ];
+
+    device.update_descriptor_sets(writes, &[] as &[vk::CopyDescriptorSet]);
+}
+
+unsafe fn create_instance_buffer(device: &gfx::Device, capacity: usize) -> Result<gfx::Buffer> {
+    device.create_buffer(
+        (size_of::<SpriteInstance>() * capacity) as vk::DeviceSize,
+        vk::BufferUsageFlags::VERTEX_BUFFER,
+        vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+    )
+}
+
+unsafe fn create_pipeline(
+    device: &gfx::Device,
+    descriptors: &gfx::DescriptorSets,
+) -> Result<(vk::PipelineLayout, vk::Pipeline)> {
+    let vk_device = device.device();
+
+    // Stages
+
+    let vert = include_bytes!("../../shaders/sprite_vert.spv");
+    let frag = include_bytes!("../../shaders/sprite_frag.spv");
+
+    let vertex_shader = gfx::Shader::create(vk_device, &vert[..])?;
+    let fragment_shader = gfx::Shader::create(vk_device, &frag[..])?;
+
+    let vert_stage = vk::PipelineShaderStageCreateInfo::builder()
+        .stage(vk::ShaderStageFlags::VERTEX)
+        .module(vertex_shader.module)
+        .name(b"main\0");
+
+    let frag_stage = vk::PipelineShaderStageCreateInfo::builder()
+        .stage(vk::ShaderStageFlags::FRAGMENT)
+        .module(fragment_shader.module)
+        .name(b"main\0");
+
+    // Vertex Input State, one sprite per instance
+
+    let binding_descriptions = &[vk::VertexInputBindingDescription::builder()
+        .binding(0)
+        .stride(size_of::<SpriteInstance>() as u32)
+        .input_rate(vk::VertexInputRate::INSTANCE)
+        .build()];
+
+    let vec4 = size_of::<Vec4>() as u32;
+    let attribute_descriptions = (0..4)
+        .map(|location| {
+            vk::VertexInputAttributeDescription::builder()
+                .binding(0)
+                .location(location)
+                .format(vk::Format::R32G32B32A32_SFLOAT)
+                .offset(vec4 * location)
+                .build()
+        })
+        .collect::<Vec<_>>();
+
+    let vertex_input_state = vk::PipelineVertexInputStateCreateInfo::builder()
+        .vertex_binding_descriptions(binding_descriptions)
+        .vertex_attribute_descriptions(&attribute_descriptions);
+
+    // Input Assembly State
+
+    let input_assembly_state = vk::PipelineInputAssemblyStateCreateInfo::builder()
+        .topology(vk::PrimitiveTopology::TRIANGLE_LIST)
+        .primitive_restart_enable(false);
+
+    // Viewport State, set when recording
+
+    let viewport_state = vk::PipelineViewportStateCreateInfo::builder()
+        .viewport_count(1)
+        .scissor_count(1);
+
+    let dynamic_states = &[vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+    let dynamic_state =
+        vk::PipelineDynamicStateCreateInfo::builder().dynamic_states(dynamic_states);
+
+    // Rasterization State, sprites may be mirrored by negative sizes
+
+    let rasterization_state = vk::PipelineRasterizationStateCreateInfo::builder()
+        .depth_clamp_enable(false)
+        .rasterizer_discard_enable(false)
+        .polygon_mode(vk::PolygonMode::FILL)
+        .line_width(1.0)
+        .cull_mode(vk::CullModeFlags::NONE)
+        .front_face(vk::FrontFace::COUNTER_CLOCKWISE)
+        .depth_bias_enable(false);
+
+    // Multisample State
+
+    let multisample_state = vk::PipelineMultisampleStateCreateInfo::builder()
+        .sample_shading_enable(false)
+        .rasterization_samples(device.samples());
+
+    // Depth Stencil State, sprites are tested but do not occlude
+
+    let depth_stencil_state = vk::PipelineDepthStencilStateCreateInfo::builder()
+        .depth_test_enable(true)
+        .depth_write_enable(false)
+        .depth_compare_op(vk::CompareOp::LESS_OR_EQUAL)
+        .depth_bounds_test_enable(false)
+        .stencil_test_enable(false);
+
+    // Color Blend State, the sprite shaders output premultiplied alpha
+
+    let attachment = vk::PipelineColorBlendAttachmentState::builder()
+        .color_write_mask(vk::ColorComponentFlags::all())
+        .blend_enable(true)
+        .src_color_blend_factor(vk::BlendFactor::ONE)
+        .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+        .color_blend_op(vk::BlendOp::ADD)
+        .src_alpha_blend_factor(vk::BlendFactor::ONE)
+        .dst_alpha_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+        .alpha_blend_op(vk::BlendOp::ADD);
+
+    let attachments = &[attachment];
+    let color_blend_state = vk::PipelineColorBlendStateCreateInfo::builder()
+        .logic_op_enable(false)
+        .logic_op(vk::LogicOp::COPY)
+        .attachments(attachments)
+        .blend_constants([0.0, 0.0, 0.0, 0.0]);
+
+    // Push Constant Ranges
+
+    let push_constant_range = vk::PushConstantRange::builder()
+        .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
+        .offset(0)
+        .size(size_of::<SpritePushConstants>() as u32);
+
+    // Layout
+
+    let pipeline_layout =
+        device.create_pipeline_layout(&[descriptors], &[push_constant_range.build()])?;
+
+    // Create
+
+    let stages = &[vert_stage, frag_stage];
+    let info = vk::GraphicsPipelineCreateInfo::builder()
+        .stages(stages)
+        .vertex_input_state(&vertex_input_state)
+        .input_assembly_state(&input_assembly_state)
+        .viewport_state(&viewport_state)
+        .rasterization_state(&rasterization_state)
+        .multisample_state(&multisample_state)
+        .depth_stencil_state(&depth_stencil_state)
+        .color_blend_state(&color_blend_state)
+        .dynamic_state(&dynamic_state)
+        .layout(pipeline_layout)
+        .render_pass(device.render_pass())
+        .subpass(0);
+
+    let pipeline = vk_device
+        .create_graphics_pipelines(vk::PipelineCache::null(), &[info], None)?
+        .0[0];
+
+    // clean up
+    vertex_shader.destroy(vk_device);
+    fragment_shader.destroy(vk_device);
+
+    Ok((pipeline_layout, pipeline))
+}
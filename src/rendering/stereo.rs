@@ -0,0 +1,433 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use std::cmp::Ordering;
+use std::mem::size_of;
+
+use anyhow::{anyhow, Result};
+use cgmath::Rotation;
+use tracing::debug;
+use vulkanalia::prelude::v1_0::*;
+
+use super::{create_mesh_pipeline_for, view_depth, BlendMode, Camera, Material, Mesh};
+use crate::gfx;
+use crate::math::{Mat4, Vec3, Vec4};
+
+/// the views of a stereo pass, the left eye is drawn to layer 0 and the right to layer 1.
+pub const STEREO_VIEW_MASK: u32 = 0b11;
+
+/// A head with two eyes `eye_distance` apart along the local x axis of `camera`, which sits
+/// between them.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StereoCamera {
+    pub camera: Camera,
+    pub eye_distance: f32,
+}
+
+impl StereoCamera {
+    /// eyes at the average human distance of 64mm.
+    pub fn new(camera: Camera) -> Self {
+        Self {
+            camera,
+            eye_distance: 0.064,
+        }
+    }
+
+    /// the left and right eye cameras.
+    pub fn eyes(&self) -> [Camera; 2] {
+        let offset =
+            self.camera
+                .rotation
+                .rotate_vector(Vec3::new(self.eye_distance / 2.0, 0.0, 0.0));
+        let eye = |position| Camera {
+            position,
+            ..self.camera
+        };
+        [
+            eye(self.camera.position - offset),
+            eye(self.camera.position + offset),
+        ]
+    }
+
+    /// the view projection of each eye for a target of `width` by `height` pixels.
+    pub fn view_projs(&self, width: u32, height: u32) -> [Mat4; 2] {
+        self.eyes().map(|eye| {
+            let (view, proj) = eye.matrices(width, height);
+            proj * view
+        })
+    }
+}
+
+/// The std140 uniform block of the multiview mesh shader, indexed by `gl_ViewIndex`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct ViewsData {
+    view_proj: [Mat4; 2],
+}
+
+crate::pod!(ViewsData {
+    view_proj: [Mat4; 2]
+});
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct StereoPushConstants {
+    model: Mat4,
+    color: Vec4,
+}
+
+/// A color and depth target with a layer per eye, both drawn in a single multiview pass.
+/// the color ends up readable by shaders, e.g. to hand each layer to a headset compositor.
+pub struct StereoTarget {
+    extent: vk::Extent2D,
+    depth_format: vk::Format,
+    color: gfx::Texture,
+    color_view: gfx::TextureView,
+    depth: gfx::Texture,
+    depth_view: gfx::TextureView,
+}
+
+impl StereoTarget {
+    /// create the layered attachments, fails when the device can't draw multiview passes.
+    pub fn create(device: &gfx::Device, width: u32, height: u32) -> Result<Self> {
+        if !device.supports_multiview() {
+            return Err(anyhow!("Stereo targets need VK_KHR_multiview."));
+        }
+
+        unsafe {
+            let vk_device = device.device();
+            let (width, height) = (width.max(1), height.max(1));
+
+            let color = device.create_array_texture(
+                width,
+                height,
+                2,
+                gfx::SCENE_FORMAT,
+                vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
+            )?;
+            let color_view = color.create_array_view(vk_device, vk::ImageAspectFlags::COLOR, 2)?;
+
+            let depth_format = device.depth_format()?;
+            let depth = device.create_array_texture(
+                width,
+                height,
+                2,
+                depth_format,
+                vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+            )?;
+            let depth_view = depth.create_array_view(vk_device, vk::ImageAspectFlags::DEPTH, 2)?;
+
+            Ok(Self {
+                extent: vk::Extent2D { width, height },
+                depth_format,
+                color,
+                color_view,
+                depth,
+                depth_view,
+            })
+        }
+    }
+
+    pub fn extent(&self) -> vk::Extent2D {
+        self.extent
+    }
+
+    /// the color of both eyes, in the shader read only layout once a pass ended.
+    pub fn texture(&self) -> &gfx::Texture {
+        &self.color
+    }
+
+    /// the array view of both color layers.
+    pub fn view(&self) -> gfx::TextureView {
+        self.color_view
+    }
+
+    /// the multiview pass drawing both eyes, cleared to opaque black.
+    pub fn descriptor(&self) -> gfx::RenderPassDescriptor {
+        gfx::RenderPassDescriptor::new(self.extent.width, self.extent.height)
+            .color(gfx::ColorAttachment::new(
+                &self.color_view,
+                gfx::SCENE_FORMAT,
+            ))
+            .depth_stencil(gfx::DepthAttachment::new(&self.depth_view, self.depth_format).discard())
+            .multiview(STEREO_VIEW_MASK)
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe {
+            let vk_device = device.device();
+            device.release_view(self.color_view.view);
+            device.release_view(self.depth_view.view);
+            self.color_view.destroy(vk_device);
+            self.color.destroy(vk_device);
+            self.depth_view.destroy(vk_device);
+            self.depth.destroy(vk_device);
+        }
+    }
+}
+
+/// A queued draw, the depth is the view space distance from between the eyes.
+#[derive(Copy, Clone, Debug)]
+struct StereoDrawItem {
+    mesh: Mesh,
+    material: Material,
+    transform: Mat4,
+    depth: f32,
+}
+
+/// Draws meshes like `Renderer` into both eyes of a `StereoTarget` at once, the vertex
+/// shader picks the view projection of the eye by `gl_ViewIndex`.
+pub struct StereoRenderer {
+    frame_descriptors: gfx::DescriptorSets,
+    // one views block per swapchain image
+    uniforms: Vec<gfx::Buffer>,
+    pipeline_layout: vk::PipelineLayout,
+    opaque_pipeline: vk::Pipeline,
+    transparent_pipeline: vk::Pipeline,
+    opaque: Vec<StereoDrawItem>,
+    transparent: Vec<StereoDrawItem>,
+}
+
+impl StereoRenderer {
+    /// create the pipelines for the multiview pass of `target`.
+    pub fn create(device: &gfx::Device, target: &StereoTarget) -> Result<Self> {
+        unsafe {
+            let vk_device = device.device();
+
+            let frame_descriptors = gfx::DescriptorSets::create(
+                vk_device,
+                gfx::DescriptorSlot::Frame,
+                &[vk::DescriptorSetLayoutBinding::builder()
+                    .binding(0)
+                    .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+                    .descriptor_count(1)
+                    .stage_flags(vk::ShaderStageFlags::VERTEX)
+                    .build()],
+            )?;
+
+            let uniforms = (0..device.image_count())
+                .map(|_| create_uniform_buffer(device))
+                .collect::<Result<Vec<_>>>()?;
+
+            // layout is shared by both queues
+            let push_constant_range = vk::PushConstantRange::builder()
+                .stage_flags(vk::ShaderStageFlags::VERTEX)
+                .offset(0)
+                .size(size_of::<StereoPushConstants>() as u32);
+            let pipeline_layout = device
+                .create_pipeline_layout(&[&frame_descriptors], &[push_constant_range.build()])?;
+
+            // one pipeline per queue
+            let render_pass = device.render_pass_for(&target.descriptor())?;
+            let opaque_pipeline =
+                create_pipeline(device, render_pass, pipeline_layout, BlendMode::Opaque)?;
+            device.set_name(opaque_pipeline, "stereo opaque");
+            let transparent_pipeline =
+                create_pipeline(device, render_pass, pipeline_layout, BlendMode::Transparent)?;
+            device.set_name(transparent_pipeline, "stereo transparent");
+
+            Ok(Self {
+                frame_descriptors,
+                uniforms,
+                pipeline_layout,
+                opaque_pipeline,
+                transparent_pipeline,
+                opaque: vec![],
+                transparent: vec![],
+            })
+        }
+    }
+
+    /// queue a mesh, the material blend mode selects the queue it is drawn in. the transform
+    /// is pushed with the draw like for `Renderer::draw`.
+    pub fn draw(&mut self, mesh: &Mesh, material: &Material, transform: Mat4) {
+        let item = StereoDrawItem {
+            mesh: *mesh,
+            material: *material,
+            transform,
+            depth: 0.0,
+        };
+
+        match material.blend {
+            BlendMode::Opaque => self.opaque.push(item),
+            BlendMode::Transparent => self.transparent.push(item),
+        }
+    }
+
+    /// record the multiview pass of `target` seen by `camera` and clear the queues, must be
+    /// called outside of any render pass.
+    pub fn record(
+        &mut self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        target: &StereoTarget,
+        camera: &StereoCamera,
+    ) -> Result<()> {
+        unsafe {
+            let vk_device = device.device();
+
+            // a recreated swapchain may have more images
+            while self.uniforms.len() <= index {
+                self.uniforms.push(create_uniform_buffer(device)?);
+            }
+
+            // upload the views block
+            let extent = target.extent();
+            let views = ViewsData {
+                view_proj: camera.view_projs(extent.width, extent.height),
+            };
+            let uniforms = self.uniforms[index];
+            uniforms.write(vk_device, 0, &[views])?;
+
+            let frame_set = self.frame_descriptors.get(vk_device, index)?;
+            let buffer_info = &[vk::DescriptorBufferInfo::builder()
+                .buffer(uniforms.buffer)
+                .offset(0)
+                .range(size_of::<ViewsData>() as vk::DeviceSize)];
+            let write = vk::WriteDescriptorSet::builder()
+                .dst_set(frame_set)
+                .dst_binding(0)
+                .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+                .buffer_info(buffer_info);
+            vk_device.update_descriptor_sets(&[write], &[] as &[vk::CopyDescriptorSet]);
+
+            // both eyes share the order, sorted from between them
+            let view = camera.camera.view();
+            for item in self.opaque.iter_mut().chain(self.transparent.iter_mut()) {
+                item.depth = view_depth(&view, &item.transform, item.mesh.center);
+            }
+            self.opaque
+                .sort_by(|a, b| a.depth.partial_cmp(&b.depth).unwrap_or(Ordering::Equal));
+            self.transparent
+                .sort_by(|a, b| b.depth.partial_cmp(&a.depth).unwrap_or(Ordering::Equal));
+
+            device.begin_render_pass(command_buffer, &target.descriptor())?;
+
+            // every view covers the whole layer
+            let area = vk::Rect2D {
+                offset: vk::Offset2D::default(),
+                extent,
+            };
+            let viewport = vk::Viewport::builder()
+                .width(extent.width as f32)
+                .height(extent.height as f32)
+                .min_depth(0.0)
+                .max_depth(1.0)
+                .build();
+            device.set_viewports(command_buffer, 0, &[(viewport, area)])?;
+
+            let queues = [
+                (self.opaque_pipeline, &self.opaque),
+                (self.transparent_pipeline, &self.transparent),
+            ];
+
+            for (pipeline, items) in queues {
+                // skip empty queues
+                if items.is_empty() {
+                    continue;
+                }
+
+                debug!(?pipeline, "bind pipeline");
+                vk_device.cmd_bind_pipeline(
+                    command_buffer,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    pipeline,
+                );
+                vk_device.cmd_bind_descriptor_sets(
+                    command_buffer,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    self.pipeline_layout,
+                    gfx::FRAME_SET,
+                    &[frame_set],
+                    &[],
+                );
+
+                for item in items.iter() {
+                    self.record_item(vk_device, command_buffer, item)?;
+                }
+            }
+
+            vk_device.cmd_end_render_pass(command_buffer);
+        }
+
+        // start over for the next frame
+        self.opaque.clear();
+        self.transparent.clear();
+
+        Ok(())
+    }
+
+    unsafe fn record_item(
+        &self,
+        device: &vulkanalia::Device,
+        command_buffer: vk::CommandBuffer,
+        item: &StereoDrawItem,
+    ) -> Result<()> {
+        let constants = StereoPushConstants {
+            model: item.transform,
+            color: item.material.tint(),
+        };
+
+        let bytes = std::slice::from_raw_parts(
+            &constants as *const StereoPushConstants as *const u8,
+            size_of::<StereoPushConstants>(),
+        );
+
+        device.cmd_push_constants(
+            command_buffer,
+            self.pipeline_layout,
+            vk::ShaderStageFlags::VERTEX,
+            0,
+            bytes,
+        );
+        device.cmd_bind_vertex_buffers(command_buffer, 0, &[item.mesh.vertices.buffer], &[0]);
+        let indices = item.mesh.index_binding();
+        indices.bind(device, command_buffer);
+        indices.draw(device, command_buffer, 0, item.mesh.count, 1)
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe {
+            let vk_device = device.device();
+
+            // destroy pipelines
+            vk_device.destroy_pipeline(self.opaque_pipeline, None);
+            vk_device.destroy_pipeline(self.transparent_pipeline, None);
+            vk_device.destroy_pipeline_layout(self.pipeline_layout, None);
+            self.uniforms.iter().for_each(|b| b.destroy(vk_device));
+            self.frame_descriptors.destroy(vk_device);
+        }
+    }
+}
+
+unsafe fn create_uniform_buffer(device: &gfx::Device) -> Result<gfx::Buffer> {
+    device.create_buffer(
+        size_of::<ViewsData>() as vk::DeviceSize,
+        vk::BufferUsageFlags::UNIFORM_BUFFER,
+        vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+    )
+}
+
+unsafe fn create_pipeline(
+    device: &gfx::Device,
+    render_pass: vk::RenderPass,
+    layout: vk::PipelineLayout,
+    blend: BlendMode,
+) -> Result<vk::Pipeline> {
+    create_mesh_pipeline_for(
+        device,
+        render_pass,
+        vk::SampleCountFlags::_1,
+        layout,
+        blend,
+        include_bytes!("../../shaders/mesh_multiview_vert.spv"),
+        include_bytes!("../../shaders/mesh_frag.spv"),
+        &[gfx::Vertex::layout()],
+    )
+}
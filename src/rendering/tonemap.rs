@@ -0,0 +1,63 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use std::any::Any;
+
+use anyhow::Result;
+use vulkanalia::prelude::v1_0::*;
+
+use super::{AutoExposure, ExposureSettings, PostPass};
+use crate::gfx;
+
+/// Maps hdr color into display range with the auto exposure and an aces curve.
+pub struct ToneMapPass {
+    pub exposure: AutoExposure,
+}
+
+impl ToneMapPass {
+    pub fn create(device: &gfx::Device, settings: ExposureSettings) -> Result<Self> {
+        Ok(Self {
+            exposure: AutoExposure::create(device, settings)?,
+        })
+    }
+}
+
+impl PostPass for ToneMapPass {
+    fn name(&self) -> &str {
+        "tonemap"
+    }
+
+    fn shader(&self) -> &'static [u8] {
+        include_bytes!("../../shaders/tonemap_frag.spv")
+    }
+
+    fn storage(&self) -> Option<vk::Buffer> {
+        Some(self.exposure.state().buffer)
+    }
+
+    fn prepare(
+        &mut self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        input: vk::ImageView,
+        delta: f32,
+    ) -> Result<()> {
+        // measure what is about to be tone mapped
+        self.exposure
+            .record(device, command_buffer, index, input, delta)
+    }
+
+    fn destroy(&self, device: &gfx::Device) {
+        self.exposure.destroy(device);
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,271 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use cgmath::VectorSpace;
+
+use super::{slerp, SceneGraph, Transform};
+use crate::math::{Quat, Vec3};
+
+/// The pose of a rigid body after a physics step, bodies don't scale.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BodyPose {
+    pub translation: Vec3,
+    pub rotation: Quat,
+}
+
+impl BodyPose {
+    pub fn new(translation: Vec3, rotation: Quat) -> Self {
+        Self {
+            translation,
+            rotation,
+        }
+    }
+
+    fn blend(&self, other: &BodyPose, weight: f32) -> Self {
+        Self {
+            translation: self.translation.lerp(other.translation, weight),
+            rotation: slerp(self.rotation, other.rotation, weight),
+        }
+    }
+}
+
+/// A body bound to a scene node, see `TransformSync::bind`. handles are dense and given out
+/// in order, so a physics integration can keep its own bodies in a parallel array.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SyncHandle(usize);
+
+impl SyncHandle {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// Carries the poses of an external physics engine onto scene nodes. Bodies are bound to
+/// nodes once, then `update` runs fixed ticks in which the engine steps and writes its poses
+/// by handle, and `write` puts the poses interpolated between the last two ticks into the
+/// scene. Everything is stored by handle, so no tick or frame looks a node up.
+#[derive(Clone, Debug)]
+pub struct TransformSync {
+    /// the length of a physics tick in seconds.
+    pub fixed_delta: f32,
+    /// the most ticks run in one update, a slow frame drops time instead of falling behind.
+    pub max_ticks: usize,
+    nodes: Vec<usize>,
+    scales: Vec<Vec3>,
+    previous: Vec<BodyPose>,
+    current: Vec<BodyPose>,
+    accumulator: f32,
+}
+
+impl TransformSync {
+    pub fn new(fixed_delta: f32) -> Self {
+        Self {
+            fixed_delta,
+            max_ticks: 8,
+            nodes: vec![],
+            scales: vec![],
+            previous: vec![],
+            current: vec![],
+            accumulator: 0.0,
+        }
+    }
+
+    /// bind a body to a node, the body starts at the current transform of the node and the
+    /// node keeps its scale.
+    pub fn bind(&mut self, scene: &SceneGraph, node: usize) -> SyncHandle {
+        let transform = scene.nodes[node].transform;
+        let pose = BodyPose::new(transform.translation, transform.rotation);
+        self.nodes.push(node);
+        self.scales.push(transform.scale);
+        self.previous.push(pose);
+        self.current.push(pose);
+        SyncHandle(self.nodes.len() - 1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// the node a body is bound to.
+    pub fn node(&self, handle: SyncHandle) -> usize {
+        self.nodes[handle.0]
+    }
+
+    /// the pose of the last tick.
+    pub fn pose(&self, handle: SyncHandle) -> BodyPose {
+        self.current[handle.0]
+    }
+
+    /// move a body without interpolating from where it was.
+    pub fn teleport(&mut self, handle: SyncHandle, pose: BodyPose) {
+        self.previous[handle.0] = pose;
+        self.current[handle.0] = pose;
+    }
+
+    /// run the ticks that fit into `delta` seconds, `tick` steps the physics by the fixed
+    /// delta and applies the new poses. returns the number of ticks run.
+    pub fn update(&mut self, delta: f32, mut tick: impl FnMut(f32, &mut PoseBatch)) -> usize {
+        if self.fixed_delta <= 0.0 {
+            return 0;
+        }
+
+        self.accumulator += delta.max(0.0);
+        let mut ticks = 0;
+        while self.accumulator >= self.fixed_delta && ticks < self.max_ticks {
+            self.previous.copy_from_slice(&self.current);
+            tick(
+                self.fixed_delta,
+                &mut PoseBatch {
+                    poses: &mut self.current,
+                },
+            );
+            self.accumulator -= self.fixed_delta;
+            ticks += 1;
+        }
+
+        // time that didn't fit is dropped, what is left stays below a tick
+        if ticks == self.max_ticks {
+            self.accumulator %= self.fixed_delta;
+        }
+
+        ticks
+    }
+
+    /// how far the frame is between the previous and the last tick, 0..1.
+    pub fn alpha(&self) -> f32 {
+        if self.fixed_delta <= 0.0 {
+            1.0
+        } else {
+            (self.accumulator / self.fixed_delta).clamp(0.0, 1.0)
+        }
+    }
+
+    /// the transform of a body for the frame.
+    pub fn interpolated(&self, handle: SyncHandle) -> Transform {
+        self.transform(handle.0, self.alpha())
+    }
+
+    /// write the transforms of all bodies for the frame into their nodes.
+    pub fn write(&self, scene: &mut SceneGraph) {
+        let alpha = self.alpha();
+        for (slot, node) in self.nodes.iter().enumerate() {
+            scene.nodes[*node].transform = self.transform(slot, alpha);
+        }
+    }
+
+    fn transform(&self, slot: usize, alpha: f32) -> Transform {
+        let pose = self.previous[slot].blend(&self.current[slot], alpha);
+        Transform::new(pose.translation, pose.rotation, self.scales[slot])
+    }
+}
+
+/// The poses written by one tick of `TransformSync::update`.
+pub struct PoseBatch<'a> {
+    poses: &'a mut [BodyPose],
+}
+
+impl PoseBatch<'_> {
+    /// the pose a body had after the previous tick.
+    pub fn get(&self, handle: SyncHandle) -> BodyPose {
+        self.poses[handle.0]
+    }
+
+    pub fn set(&mut self, handle: SyncHandle, pose: BodyPose) {
+        self.poses[handle.0] = pose;
+    }
+
+    /// set the poses of many bodies.
+    pub fn apply(&mut self, poses: &[(SyncHandle, BodyPose)]) {
+        for (handle, pose) in poses {
+            self.poses[handle.0] = *pose;
+        }
+    }
+
+    /// set the poses of the bodies in the order they were bound, starting with the first.
+    pub fn apply_ordered(&mut self, poses: impl IntoIterator<Item = BodyPose>) {
+        for (slot, pose) in self.poses.iter_mut().zip(poses) {
+            *slot = pose;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scene() -> SceneGraph {
+        let mut scene = SceneGraph::new();
+        let rest = Transform {
+            scale: Vec3::new(2.0, 2.0, 2.0),
+            ..Transform::default()
+        };
+        scene.add("body", None, rest);
+        scene
+    }
+
+    // moves every body one unit along x per tick
+    fn slide(delta: f32, batch: &mut PoseBatch) {
+        let handle = SyncHandle(0);
+        let pose = batch.get(handle);
+        batch.set(
+            handle,
+            BodyPose::new(pose.translation + Vec3::unit_x(), pose.rotation),
+        );
+    }
+
+    #[test]
+    fn zero_and_negative_steps_run_no_ticks() {
+        let mut sync = TransformSync::new(0.1);
+        sync.bind(&scene(), 0);
+        assert_eq!(sync.update(0.0, slide), 0);
+        assert_eq!(sync.update(-1.0, slide), 0);
+        assert_eq!(sync.alpha(), 0.0);
+
+        // without a tick length nothing runs
+        let mut stopped = TransformSync::new(0.0);
+        stopped.bind(&scene(), 0);
+        assert_eq!(stopped.update(1.0, slide), 0);
+        assert_eq!(stopped.alpha(), 1.0);
+    }
+
+    #[test]
+    fn frames_are_interpolated_between_ticks() {
+        let mut scene = scene();
+        let mut sync = TransformSync::new(0.1);
+        let body = sync.bind(&scene, 0);
+        assert_eq!(sync.update(0.15, slide), 1);
+        assert!((sync.alpha() - 0.5).abs() < 1e-4);
+        assert_eq!(sync.pose(body).translation, Vec3::unit_x());
+
+        sync.write(&mut scene);
+        let transform = scene.nodes[0].transform;
+        assert!((transform.translation.x - 0.5).abs() < 1e-4);
+        assert_eq!(transform.scale, Vec3::new(2.0, 2.0, 2.0));
+
+        // a teleport doesn't blend from where the body was
+        let pose = BodyPose::new(Vec3::new(0.0, 5.0, 0.0), Quat::new(1.0, 0.0, 0.0, 0.0));
+        sync.teleport(body, pose);
+        assert_eq!(sync.interpolated(body).translation, pose.translation);
+    }
+
+    #[test]
+    fn long_frames_drop_the_time_beyond_the_tick_limit() {
+        let mut sync = TransformSync::new(0.1);
+        sync.max_ticks = 4;
+        let body = sync.bind(&scene(), 0);
+        assert_eq!(sync.update(10.0, slide), 4);
+        assert_eq!(sync.pose(body).translation, Vec3::new(4.0, 0.0, 0.0));
+        assert!(sync.alpha() < 1.0);
+        assert_eq!(sync.update(0.0, slide), 0);
+        assert_eq!(sync.pose(body).translation, Vec3::new(4.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use vulkanalia::prelude::v1_0::*;
+
+use super::{mesh_pipeline_builder, BlendMode, Material};
+use crate::gfx;
+
+/// The pipeline state of a material, what tells its pipeline variants apart.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MaterialState {
+    pub blend: BlendMode,
+    /// the mesh is deformed by joints and drawn with the skinned shaders.
+    pub skinned: bool,
+    /// the samples per pixel of the pass it is drawn in.
+    pub samples: vk::SampleCountFlags,
+}
+
+impl MaterialState {
+    pub fn new(material: &Material, skinned: bool, samples: vk::SampleCountFlags) -> Self {
+        Self {
+            blend: material.blend,
+            skinned,
+            samples,
+        }
+    }
+}
+
+/// The spir-v shaders and vertex buffers of the pipelines drawing one kind of mesh.
+#[derive(Clone, Debug)]
+pub struct MaterialShaders {
+    pub vertex: Vec<u8>,
+    pub fragment: Vec<u8>,
+    pub buffers: Vec<gfx::VertexBufferLayout>,
+}
+
+/// The pipelines of the material states drawn with one layout, created the first time a
+/// state is drawn or ahead of time with `prewarm`. the first pipeline of each shaders and
+/// sample count allows derivatives and the others of them derive from it, they only differ
+/// in their blend state.
+pub struct MaterialPipelines {
+    layout: vk::PipelineLayout,
+    shaders: MaterialShaders,
+    skinned: Option<MaterialShaders>,
+    // the pass each sample count draws in
+    targets: HashMap<vk::SampleCountFlags, gfx::PipelineTarget>,
+    pipelines: HashMap<MaterialState, vk::Pipeline>,
+    // the base pipelines by whether they are skinned and their samples
+    bases: HashMap<(bool, vk::SampleCountFlags), vk::Pipeline>,
+}
+
+impl MaterialPipelines {
+    pub fn new(layout: vk::PipelineLayout, shaders: MaterialShaders) -> Self {
+        Self {
+            layout,
+            shaders,
+            skinned: None,
+            targets: HashMap::new(),
+            pipelines: HashMap::new(),
+            bases: HashMap::new(),
+        }
+    }
+
+    /// draw skinned states with `shaders`, without them they are refused.
+    pub fn with_skinned(mut self, shaders: MaterialShaders) -> Self {
+        self.skinned = Some(shaders);
+        self
+    }
+
+    /// draw the states of `target.samples` in the pass of `target`, e.g.
+    /// `PipelineTarget::scene(device)`. pipelines created for an earlier pass of the samples
+    /// are kept, the new pass must be compatible with it.
+    pub fn add_target(&mut self, target: gfx::PipelineTarget) {
+        self.targets.insert(target.samples, target);
+    }
+
+    /// the pipeline of `state`, none until it was created.
+    pub fn get(&self, state: &MaterialState) -> Option<vk::Pipeline> {
+        self.pipelines.get(state).copied()
+    }
+
+    /// the pipeline of `state`, created when it is drawn for the first time.
+    pub fn pipeline(
+        &mut self,
+        device: &vulkanalia::Device,
+        state: &MaterialState,
+    ) -> Result<vk::Pipeline> {
+        if let Some(pipeline) = self.get(state) {
+            return Ok(pipeline);
+        }
+
+        let shaders = match (state.skinned, &self.skinned) {
+            (false, _) => &self.shaders,
+            (true, Some(skinned)) => skinned,
+            (true, None) => return Err(anyhow!("Skinned materials need skinned shaders.")),
+        };
+        let Some(target) = self.targets.get(&state.samples) else {
+            return Err(anyhow!(
+                "No pass draws materials with {:?} samples.",
+                state.samples
+            ));
+        };
+
+        let mut builder = mesh_pipeline_builder(self.layout, state.blend)
+            .vertex_shader(&shaders.vertex)
+            .fragment_shader(&shaders.fragment);
+        builder.buffers = shaders.buffers.clone();
+        let family = (state.skinned, state.samples);
+        builder = match self.bases.get(&family) {
+            Some(base) => builder.derive_from(*base),
+            None => builder.allow_derivatives(),
+        };
+
+        let pipeline = builder.build_for(device, target)?;
+        self.bases.entry(family).or_insert(pipeline);
+        self.pipelines.insert(*state, pipeline);
+        Ok(pipeline)
+    }
+
+    /// create the pipelines of `states` that don't exist yet, e.g. while a level loads, so
+    /// their first draw doesn't wait for the driver.
+    pub fn prewarm(&mut self, device: &vulkanalia::Device, states: &[MaterialState]) -> Result<()> {
+        for state in states {
+            self.pipeline(device, state)?;
+        }
+        Ok(())
+    }
+
+    /// the pipeline the one of `state` derives from, none for base pipelines and states
+    /// without a pipeline.
+    pub fn base(&self, state: &MaterialState) -> Option<vk::Pipeline> {
+        let pipeline = self.get(state)?;
+        let base = self.bases.get(&(state.skinned, state.samples)).copied();
+        base.filter(|b| *b != pipeline)
+    }
+
+    /// the number of pipelines created.
+    pub fn len(&self) -> usize {
+        self.pipelines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pipelines.is_empty()
+    }
+
+    /// destroy the pipelines, the layout belongs to the caller.
+    pub unsafe fn destroy(&mut self, device: &vulkanalia::Device) {
+        for (_, pipeline) in self.pipelines.drain() {
+            device.destroy_pipeline(pipeline, None);
+        }
+        self.bases.clear();
+    }
+}
@@ -0,0 +1,631 @@
+#![allow(dead_code, clippy::too_many_arguments)]
+
+use std::mem::size_of;
+
+use anyhow::{anyhow, Result};
+use tracing::debug;
+use vulkanalia::prelude::v1_0::*;
+
+use super::image_passes::{create_stage, dispatch, ImageStage};
+use super::{constant_bytes, IMAGE_PASS_FORMAT};
+use crate::gfx;
+
+/// The color matrix yuv frames were encoded with.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum YuvMatrix {
+    /// standard definition video.
+    Bt601,
+    /// high definition video, most decoders produce it.
+    #[default]
+    Bt709,
+}
+
+impl YuvMatrix {
+    // the luma weights of red and blue
+    fn weights(&self) -> (f32, f32) {
+        match self {
+            YuvMatrix::Bt601 => (0.299, 0.114),
+            YuvMatrix::Bt709 => (0.2126, 0.0722),
+        }
+    }
+}
+
+/// The values yuv frames use of the 8 bits of a sample.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum YuvRange {
+    /// luma in 16..=235 and chroma in 16..=240, what video is broadcast and stored in.
+    #[default]
+    Limited,
+    /// every value, e.g. jpeg or screen captures.
+    Full,
+}
+
+/// The planes of a decoded video frame, rows are packed without padding.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VideoPlanes<'a> {
+    /// gamma encoded rgba8 pixels, uploaded without conversion.
+    Rgba8(&'a [u8]),
+    /// a luma plane and a plane of interleaved u and v at half the width and height.
+    Nv12 { y: &'a [u8], uv: &'a [u8] },
+    /// a luma plane and separate u and v planes at half the width and height.
+    I420 {
+        y: &'a [u8],
+        u: &'a [u8],
+        v: &'a [u8],
+    },
+}
+
+/// A decoded video frame on the cpu, see `ExternalTexture`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VideoFrame<'a> {
+    pub width: u32,
+    pub height: u32,
+    pub planes: VideoPlanes<'a>,
+    pub matrix: YuvMatrix,
+    pub range: YuvRange,
+}
+
+impl<'a> VideoFrame<'a> {
+    pub fn rgba8(width: u32, height: u32, pixels: &'a [u8]) -> Self {
+        Self::new(width, height, VideoPlanes::Rgba8(pixels))
+    }
+
+    pub fn nv12(width: u32, height: u32, y: &'a [u8], uv: &'a [u8]) -> Self {
+        Self::new(width, height, VideoPlanes::Nv12 { y, uv })
+    }
+
+    pub fn i420(width: u32, height: u32, y: &'a [u8], u: &'a [u8], v: &'a [u8]) -> Self {
+        Self::new(width, height, VideoPlanes::I420 { y, u, v })
+    }
+
+    // bt709 in limited range like most video
+    fn new(width: u32, height: u32, planes: VideoPlanes<'a>) -> Self {
+        Self {
+            width,
+            height,
+            planes,
+            matrix: YuvMatrix::default(),
+            range: YuvRange::default(),
+        }
+    }
+
+    pub fn with_color(self, matrix: YuvMatrix, range: YuvRange) -> Self {
+        Self {
+            matrix,
+            range,
+            ..self
+        }
+    }
+
+    /// whether the frame needs the yuv conversion shader.
+    pub fn is_yuv(&self) -> bool {
+        !matches!(self.planes, VideoPlanes::Rgba8(_))
+    }
+
+    /// the size of the chroma planes, half the frame rounded up.
+    pub fn chroma_extent(&self) -> (u32, u32) {
+        (self.width.div_ceil(2), self.height.div_ceil(2))
+    }
+
+    /// refuse frames without area or with planes of the wrong size.
+    pub fn check(&self) -> Result<()> {
+        if self.width == 0 || self.height == 0 {
+            return Err(anyhow!("A video frame needs an area."));
+        }
+        let texels = self.width as usize * self.height as usize;
+        let (chroma_width, chroma_height) = self.chroma_extent();
+        let chroma = chroma_width as usize * chroma_height as usize;
+        let planes = match self.planes {
+            VideoPlanes::Rgba8(pixels) => vec![("rgba", pixels.len(), texels * 4)],
+            VideoPlanes::Nv12 { y, uv } => {
+                vec![("y", y.len(), texels), ("uv", uv.len(), chroma * 2)]
+            }
+            VideoPlanes::I420 { y, u, v } => vec![
+                ("y", y.len(), texels),
+                ("u", u.len(), chroma),
+                ("v", v.len(), chroma),
+            ],
+        };
+        for (name, len, expected) in planes {
+            if len != expected {
+                return Err(anyhow!(
+                    "The {} plane of a {}x{} video frame needs {} bytes, not {}.",
+                    name,
+                    self.width,
+                    self.height,
+                    expected,
+                    len
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// the bytes uploaded for the frame, the luma plane followed by interleaved chroma for
+    /// yuv frames.
+    pub fn staged_bytes(&self) -> Vec<u8> {
+        match self.planes {
+            VideoPlanes::Rgba8(pixels) => pixels.to_vec(),
+            VideoPlanes::Nv12 { y, uv } => [y, uv].concat(),
+            VideoPlanes::I420 { y, u, v } => {
+                let mut bytes = Vec::with_capacity(y.len() + u.len() * 2);
+                bytes.extend_from_slice(y);
+                bytes.extend(u.iter().zip(v).flat_map(|(u, v)| [*u, *v]));
+                bytes
+            }
+        }
+    }
+}
+
+/// the rows of the matrix from normalized (y, u, v, 1) to gamma encoded rgb.
+pub fn yuv_to_rgb(matrix: YuvMatrix, range: YuvRange) -> [[f32; 4]; 3] {
+    let (kr, kb) = matrix.weights();
+    let kg = 1.0 - kr - kb;
+
+    // stretch the limited range to 0..1 and center chroma at 0
+    let (luma_scale, luma_offset, chroma_scale) = match range {
+        YuvRange::Limited => (255.0 / 219.0, 16.0 / 255.0, 255.0 / 224.0),
+        YuvRange::Full => (1.0, 0.0, 1.0),
+    };
+    let chroma_offset = 128.0 / 255.0;
+
+    // the chroma weights of each channel
+    let red = (0.0, 2.0 * (1.0 - kr));
+    let green = (-2.0 * kb * (1.0 - kb) / kg, -2.0 * kr * (1.0 - kr) / kg);
+    let blue = (2.0 * (1.0 - kb), 0.0);
+
+    [red, green, blue].map(|(u, v): (f32, f32)| {
+        let (u, v) = (u * chroma_scale, v * chroma_scale);
+        [
+            luma_scale,
+            u,
+            v,
+            -luma_scale * luma_offset - (u + v) * chroma_offset,
+        ]
+    })
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct YuvPushConstants {
+    rows: [[f32; 4]; 3],
+}
+
+/// The luma and chroma planes of yuv frames and the pass converting them.
+struct YuvPlanes {
+    luma: gfx::Texture,
+    luma_view: gfx::TextureView,
+    chroma: gfx::Texture,
+    chroma_view: gfx::TextureView,
+    stage: ImageStage,
+    set: vk::DescriptorSet,
+}
+
+/// A texture showing decoded video frames, e.g. from a video decoder or a camera. rgba
+/// frames are copied as they are, yuv frames are converted to linear rgb by a compute
+/// shader. frames are copied from the cpu, zero copy imports like dmabufs are not supported.
+/// sample it in materials with `PbrMaterial::with_external_texture`.
+pub struct ExternalTexture {
+    width: u32,
+    height: u32,
+    yuv: bool,
+    texture: gfx::Texture,
+    view: gfx::TextureView,
+    planes: Option<YuvPlanes>,
+    // a staging buffer per swapchain image, written while the image's last frame completed
+    staging: Vec<Option<gfx::Buffer>>,
+}
+
+impl ExternalTexture {
+    /// create a texture of the size and kind of `frame` and upload the frame, waiting for the
+    /// device. later frames of the same size and kind are recorded with `record_frame`.
+    pub fn import(device: &gfx::Device, frame: &VideoFrame) -> Result<Self> {
+        frame.check()?;
+        let (width, height) = (frame.width, frame.height);
+        debug!(
+            width,
+            height,
+            yuv = frame.is_yuv(),
+            "import external texture"
+        );
+
+        unsafe {
+            let vk_device = device.device();
+            let aspects = vk::ImageAspectFlags::COLOR;
+            let (texture, view, planes) = if frame.is_yuv() {
+                let usage = vk::ImageUsageFlags::STORAGE | vk::ImageUsageFlags::SAMPLED;
+                let texture = device.create_texture(width, height, 1, IMAGE_PASS_FORMAT, usage)?;
+                let view = texture.create_view(vk_device, IMAGE_PASS_FORMAT, aspects, 1)?;
+                (texture, view, Some(create_planes(device, frame, &view)?))
+            } else {
+                let format = vk::Format::R8G8B8A8_SRGB;
+                let usage = vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED;
+                let texture = device.create_texture(width, height, 1, format, usage)?;
+                let view = texture.create_view(vk_device, format, aspects, 1)?;
+                (texture, view, None)
+            };
+            device.set_name(texture.image, "external texture");
+
+            let external = Self {
+                width,
+                height,
+                yuv: frame.is_yuv(),
+                texture,
+                view,
+                planes,
+                staging: vec![],
+            };
+
+            // the first frame goes through a staging buffer of its own, the per image ones
+            // may still be read by frames in flight
+            let staging = create_staging(device, frame)?;
+            let uploaded = device.execute(|device, command_buffer| {
+                external.record_upload(device, command_buffer, &staging, frame)
+            });
+            staging.destroy(vk_device);
+            uploaded?;
+            Ok(external)
+        }
+    }
+
+    pub fn extent(&self) -> vk::Extent2D {
+        vk::Extent2D {
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// the view materials sample, in the shader read only layout outside of `record_frame`.
+    pub fn view(&self) -> gfx::TextureView {
+        self.view
+    }
+
+    /// the texture the view belongs to, linear hdr color for yuv frames and srgb otherwise.
+    pub fn texture(&self) -> &gfx::Texture {
+        &self.texture
+    }
+
+    /// record showing `frame` from now on, e.g. in the prepare closure of
+    /// `Device::update_with_prepare`. the frame needs the size and kind of the imported one.
+    /// must be called outside any render pass, `index` is the swapchain image the commands
+    /// are for.
+    pub fn record_frame(
+        &mut self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        index: usize,
+        frame: &VideoFrame,
+    ) -> Result<()> {
+        frame.check()?;
+        if (frame.width, frame.height, frame.is_yuv()) != (self.width, self.height, self.yuv) {
+            return Err(anyhow!(
+                "A {}x{} external texture can't show a {}x{} frame of another kind.",
+                self.width,
+                self.height,
+                frame.width,
+                frame.height
+            ));
+        }
+
+        if self.staging.len() <= index {
+            self.staging.resize(index + 1, None);
+        }
+        let staging = match self.staging[index] {
+            Some(staging) => staging,
+            None => {
+                let staging = unsafe { create_staging(device, frame)? };
+                self.staging[index] = Some(staging);
+                staging
+            }
+        };
+        unsafe { self.record_upload(device, command_buffer, &staging, frame) }
+    }
+
+    pub fn destroy(&self, device: &gfx::Device) {
+        unsafe {
+            let vk_device = device.device();
+            self.staging
+                .iter()
+                .flatten()
+                .for_each(|b| b.destroy(vk_device));
+            if let Some(planes) = &self.planes {
+                planes.stage.destroy(vk_device);
+                planes.luma_view.destroy(vk_device);
+                planes.luma.destroy(vk_device);
+                planes.chroma_view.destroy(vk_device);
+                planes.chroma.destroy(vk_device);
+            }
+            self.view.destroy(vk_device);
+            self.texture.destroy(vk_device);
+        }
+    }
+
+    // copy the frame into the staging buffer, then into the texture or the planes and convert
+    unsafe fn record_upload(
+        &self,
+        device: &gfx::Device,
+        command_buffer: vk::CommandBuffer,
+        staging: &gfx::Buffer,
+        frame: &VideoFrame,
+    ) -> Result<()> {
+        let vk_device = device.device();
+        staging.write_bytes(vk_device, 0, &frame.staged_bytes())?;
+
+        let Some(planes) = &self.planes else {
+            // rgba frames are copied as they are, earlier frames may still be sampled
+            let image = self.texture.image;
+            let to_transfer = layout_barrier(
+                image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                vk::AccessFlags::empty(),
+                vk::AccessFlags::TRANSFER_WRITE,
+            );
+            barrier(
+                vk_device,
+                command_buffer,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::PipelineStageFlags::TRANSFER,
+                to_transfer,
+            );
+            copy_plane(vk_device, command_buffer, staging, 0, image, self.extent());
+            let to_shader = transition(
+                to_transfer,
+                vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                vk::AccessFlags::SHADER_READ,
+            );
+            barrier(
+                vk_device,
+                command_buffer,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                to_shader,
+            );
+            return Ok(());
+        };
+
+        // the planes are overwritten after the previous conversion read them
+        let (chroma_width, chroma_height) = frame.chroma_extent();
+        let chroma_extent = vk::Extent2D {
+            width: chroma_width,
+            height: chroma_height,
+        };
+        let luma_bytes = self.width as vk::DeviceSize * self.height as vk::DeviceSize;
+        for (image, offset, extent) in [
+            (planes.luma.image, 0, self.extent()),
+            (planes.chroma.image, luma_bytes, chroma_extent),
+        ] {
+            let to_transfer = layout_barrier(
+                image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                vk::AccessFlags::empty(),
+                vk::AccessFlags::TRANSFER_WRITE,
+            );
+            barrier(
+                vk_device,
+                command_buffer,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::PipelineStageFlags::TRANSFER,
+                to_transfer,
+            );
+            copy_plane(vk_device, command_buffer, staging, offset, image, extent);
+            let to_shader = transition(
+                to_transfer,
+                vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                vk::AccessFlags::SHADER_READ,
+            );
+            barrier(
+                vk_device,
+                command_buffer,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                to_shader,
+            );
+        }
+
+        // convert into the texture once earlier frames stopped sampling it
+        let to_general = layout_barrier(
+            self.texture.image,
+            vk::ImageLayout::GENERAL,
+            vk::AccessFlags::empty(),
+            vk::AccessFlags::SHADER_WRITE,
+        );
+        barrier(
+            vk_device,
+            command_buffer,
+            vk::PipelineStageFlags::FRAGMENT_SHADER,
+            vk::PipelineStageFlags::COMPUTE_SHADER,
+            to_general,
+        );
+        let constants = YuvPushConstants {
+            rows: yuv_to_rgb(frame.matrix, frame.range),
+        };
+        dispatch(
+            vk_device,
+            command_buffer,
+            &planes.stage,
+            planes.set,
+            &constant_bytes(&constants),
+            self.extent(),
+        );
+        let to_shader = transition(
+            to_general,
+            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            vk::AccessFlags::SHADER_READ,
+        );
+        barrier(
+            vk_device,
+            command_buffer,
+            vk::PipelineStageFlags::COMPUTE_SHADER,
+            vk::PipelineStageFlags::FRAGMENT_SHADER,
+            to_shader,
+        );
+        Ok(())
+    }
+}
+
+// the plane textures of yuv frames and the conversion pass writing `target`
+unsafe fn create_planes(
+    device: &gfx::Device,
+    frame: &VideoFrame,
+    target: &gfx::TextureView,
+) -> Result<YuvPlanes> {
+    let vk_device = device.device();
+    let aspects = vk::ImageAspectFlags::COLOR;
+    let usage = vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED;
+    let (chroma_width, chroma_height) = frame.chroma_extent();
+
+    let luma_format = vk::Format::R8_UNORM;
+    let luma = device.create_texture(frame.width, frame.height, 1, luma_format, usage)?;
+    let luma_view = luma.create_view(vk_device, luma_format, aspects, 1)?;
+    let chroma_format = vk::Format::R8G8_UNORM;
+    let chroma = device.create_texture(chroma_width, chroma_height, 1, chroma_format, usage)?;
+    let chroma_view = chroma.create_view(vk_device, chroma_format, aspects, 1)?;
+
+    let binding = |binding, type_| {
+        vk::DescriptorSetLayoutBinding::builder()
+            .binding(binding)
+            .descriptor_type(type_)
+            .descriptor_count(1)
+            .stage_flags(vk::ShaderStageFlags::COMPUTE)
+            .build()
+    };
+    let mut stage = create_stage(
+        device,
+        include_bytes!("../../shaders/video_yuv_comp.spv"),
+        &[
+            binding(0, vk::DescriptorType::SAMPLED_IMAGE),
+            binding(1, vk::DescriptorType::SAMPLED_IMAGE),
+            binding(2, vk::DescriptorType::STORAGE_IMAGE),
+        ],
+        size_of::<YuvPushConstants>() as u32,
+    )?;
+    device.set_name(stage.pipeline, "yuv conversion");
+
+    // the views never change, a single set serves every frame
+    let set = stage.descriptors.get(vk_device, 0)?;
+    stage.descriptors.write(
+        vk_device,
+        set,
+        &[
+            (0, gfx::BindingResource::Texture(luma_view.view)),
+            (1, gfx::BindingResource::Texture(chroma_view.view)),
+            (2, gfx::BindingResource::StorageTexture(target.view)),
+        ],
+    )?;
+
+    Ok(YuvPlanes {
+        luma,
+        luma_view,
+        chroma,
+        chroma_view,
+        stage,
+        set,
+    })
+}
+
+unsafe fn create_staging(device: &gfx::Device, frame: &VideoFrame) -> Result<gfx::Buffer> {
+    let (chroma_width, chroma_height) = frame.chroma_extent();
+    let texels = frame.width as vk::DeviceSize * frame.height as vk::DeviceSize;
+    let size = if frame.is_yuv() {
+        texels + chroma_width as vk::DeviceSize * chroma_height as vk::DeviceSize * 2
+    } else {
+        texels * 4
+    };
+    device.create_buffer(
+        size,
+        vk::BufferUsageFlags::TRANSFER_SRC,
+        vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+    )
+}
+
+unsafe fn copy_plane(
+    device: &vulkanalia::Device,
+    command_buffer: vk::CommandBuffer,
+    staging: &gfx::Buffer,
+    offset: vk::DeviceSize,
+    image: vk::Image,
+    extent: vk::Extent2D,
+) {
+    let subresource = vk::ImageSubresourceLayers::builder()
+        .aspect_mask(vk::ImageAspectFlags::COLOR)
+        .mip_level(0)
+        .base_array_layer(0)
+        .layer_count(1);
+    let region = vk::BufferImageCopy::builder()
+        .buffer_offset(offset)
+        .buffer_row_length(0)
+        .buffer_image_height(0)
+        .image_subresource(subresource)
+        .image_offset(vk::Offset3D::default())
+        .image_extent(vk::Extent3D {
+            width: extent.width,
+            height: extent.height,
+            depth: 1,
+        });
+    device.cmd_copy_buffer_to_image(
+        command_buffer,
+        staging.buffer,
+        image,
+        vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+        &[region],
+    );
+}
+
+// a barrier discarding the contents of `image`, every upload overwrites all of it
+fn layout_barrier(
+    image: vk::Image,
+    new_layout: vk::ImageLayout,
+    src_access: vk::AccessFlags,
+    dst_access: vk::AccessFlags,
+) -> vk::ImageMemoryBarrier {
+    let subresource = vk::ImageSubresourceRange::builder()
+        .aspect_mask(vk::ImageAspectFlags::COLOR)
+        .base_mip_level(0)
+        .level_count(1)
+        .base_array_layer(0)
+        .layer_count(1);
+
+    vk::ImageMemoryBarrier::builder()
+        .old_layout(vk::ImageLayout::UNDEFINED)
+        .new_layout(new_layout)
+        .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .image(image)
+        .subresource_range(subresource)
+        .src_access_mask(src_access)
+        .dst_access_mask(dst_access)
+        .build()
+}
+
+// the barrier after `previous`, from its layout into `new_layout`
+fn transition(
+    previous: vk::ImageMemoryBarrier,
+    new_layout: vk::ImageLayout,
+    dst_access: vk::AccessFlags,
+) -> vk::ImageMemoryBarrier {
+    vk::ImageMemoryBarrier {
+        old_layout: previous.new_layout,
+        new_layout,
+        src_access_mask: previous.dst_access_mask,
+        dst_access_mask: dst_access,
+        ..previous
+    }
+}
+
+unsafe fn barrier(
+    device: &vulkanalia::Device,
+    command_buffer: vk::CommandBuffer,
+    src_stage: vk::PipelineStageFlags,
+    dst_stage: vk::PipelineStageFlags,
+    barrier: vk::ImageMemoryBarrier,
+) {
+    device.cmd_pipeline_barrier(
+        command_buffer,
+        src_stage,
+        dst_stage,
+        vk::DependencyFlags::empty(),
+        &[] as &[vk::MemoryBarrier],
+        &[] as &[vk::BufferMemoryBarrier],
+        &[barrier],
+    );
+}
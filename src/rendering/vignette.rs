@@ -0,0 +1,67 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use std::any::Any;
+
+use super::{constant_bytes, PostPass};
+use crate::math::Vec3;
+
+/// Darkens the screen towards its corners.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct VignettePass {
+    pub color: Vec3,
+    /// how much the corners are darkened, 0 is off.
+    pub intensity: f32,
+    /// the distance from the center where darkening starts, 1 is the corner.
+    pub radius: f32,
+    /// the width of the transition.
+    pub smoothness: f32,
+}
+
+impl Default for VignettePass {
+    fn default() -> Self {
+        Self {
+            color: Vec3::new(0.0, 0.0, 0.0),
+            intensity: 0.4,
+            radius: 0.6,
+            smoothness: 0.4,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct VignettePushConstants {
+    color: Vec3,
+    intensity: f32,
+    radius: f32,
+    smoothness: f32,
+}
+
+impl PostPass for VignettePass {
+    fn name(&self) -> &str {
+        "vignette"
+    }
+
+    fn shader(&self) -> &'static [u8] {
+        include_bytes!("../../shaders/vignette_frag.spv")
+    }
+
+    fn constants(&self) -> Vec<u8> {
+        constant_bytes(&VignettePushConstants {
+            color: self.color,
+            intensity: self.intensity,
+            radius: self.radius,
+            smoothness: self.smoothness,
+        })
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,171 @@
+#![allow(
+    dead_code,
+    unused_variables,
+    clippy::manual_slice_size_calculation,
+    clippy::too_many_arguments,
+    clippy::unnecessary_wraps
+)]
+
+use std::collections::HashMap;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use deimos_format::MeshPack;
+use tracing::debug;
+
+use super::read_mesh;
+use crate::gfx;
+
+/// What a watched file holds, decides how it is decoded.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SourceKind {
+    /// a png, see `gfx::read_png`.
+    Texture,
+    /// a mesh file, see `read_mesh`.
+    Mesh,
+}
+
+/// The decoded contents of a source file, ready to be uploaded.
+pub enum SourceData {
+    Texture {
+        width: u32,
+        height: u32,
+        pixels: Vec<u8>,
+    },
+    Mesh(MeshPack),
+}
+
+impl SourceData {
+    /// decode the file at `path` as `kind`.
+    pub fn read(path: &str, kind: SourceKind) -> Result<Self> {
+        match kind {
+            SourceKind::Texture => {
+                let (width, height, pixels) = gfx::read_png(path)?;
+                Ok(SourceData::Texture {
+                    width,
+                    height,
+                    pixels,
+                })
+            }
+            SourceKind::Mesh => Ok(SourceData::Mesh(read_mesh(path)?)),
+        }
+    }
+}
+
+/// A watched file that was written, with its new contents or why they couldn't be read.
+pub struct FileChange {
+    pub path: String,
+    pub kind: SourceKind,
+    pub data: Result<SourceData>,
+}
+
+struct WatchedFile {
+    kind: SourceKind,
+    modified: Option<SystemTime>,
+}
+
+/// Polls the modification time of source files on a thread of its own and decodes the
+/// files that changed there, so the frame only pays for the upload. a file that is written
+/// in several steps may be decoded half written, the next poll picks up the rest.
+pub struct FileWatcher {
+    files: Arc<Mutex<HashMap<String, WatchedFile>>>,
+    changes: Receiver<FileChange>,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FileWatcher {
+    /// start watching, files are checked every `interval`.
+    pub fn new(interval: Duration) -> Self {
+        let files = Arc::new(Mutex::new(HashMap::new()));
+        let running = Arc::new(AtomicBool::new(true));
+        let (sender, changes) = mpsc::channel();
+
+        let thread = {
+            let files = files.clone();
+            let running = running.clone();
+            thread::Builder::new()
+                .name("file watcher".into())
+                .spawn(move || poll(&files, &running, &sender, interval))
+                .expect("Failed to spawn the file watcher thread.")
+        };
+
+        Self {
+            files,
+            changes,
+            running,
+            thread: Some(thread),
+        }
+    }
+
+    /// report changes of the file at `path` from now on.
+    pub fn watch(&self, path: &str, kind: SourceKind) {
+        let modified = modified(path);
+        let mut files = self.files.lock().unwrap();
+        files.insert(path.to_string(), WatchedFile { kind, modified });
+    }
+
+    /// stop reporting changes of the file at `path`.
+    pub fn unwatch(&self, path: &str) {
+        self.files.lock().unwrap().remove(path);
+    }
+
+    /// whether the file at `path` is watched.
+    pub fn is_watched(&self, path: &str) -> bool {
+        self.files.lock().unwrap().contains_key(path)
+    }
+
+    /// the changes decoded since the last call, without blocking.
+    pub fn changes(&self) -> Vec<FileChange> {
+        self.changes.try_iter().collect()
+    }
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn poll(
+    files: &Mutex<HashMap<String, WatchedFile>>,
+    running: &AtomicBool,
+    sender: &Sender<FileChange>,
+    interval: Duration,
+) {
+    while running.load(Ordering::Relaxed) {
+        thread::sleep(interval);
+
+        // find the files written since the last poll, a deleted file keeps its time so
+        // saving by replacing the file is seen once the new one is there
+        let mut changed = vec![];
+        for (path, file) in files.lock().unwrap().iter_mut() {
+            let modified = modified(path);
+            if modified.is_some() && modified != file.modified {
+                file.modified = modified;
+                changed.push((path.clone(), file.kind));
+            }
+        }
+
+        // decode outside of the lock so watching new files doesn't wait for it
+        for (path, kind) in changed {
+            debug!(path, ?kind, "source file changed");
+            let data = SourceData::read(&path, kind);
+            if sender.send(FileChange { path, kind, data }).is_err() {
+                return;
+            }
+        }
+    }
+}
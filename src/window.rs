@@ -0,0 +1,338 @@
+// SPDX-License-Identifier: MIT
+
+//! Window modes: windowed, borderless fullscreen on a monitor, or exclusive fullscreen in one
+//! of its video modes. `DisplayController` switches the window and tells the device, the
+//! swapchain is recreated for the new size before the next frame. `WindowController` grabs
+//! and hides the cursor and changes the title and icon.
+
+use anyhow::{anyhow, Result};
+use tracing::debug;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::monitor::{MonitorHandle, VideoMode};
+use winit::window::{CursorGrabMode, Fullscreen, Icon, Window};
+
+use crate::gfx;
+
+/// How the window covers the screen.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum WindowMode {
+    /// a decorated window of its own size.
+    #[default]
+    Windowed,
+    /// an undecorated window covering the monitor at its desktop resolution.
+    Borderless,
+    /// the monitor switches to a video mode of its own while the window has focus.
+    Exclusive,
+}
+
+/// A video mode of a monitor, the resolution and rate exclusive fullscreen switches to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DisplayMode {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_rate_millihertz: u32,
+    pub bit_depth: u16,
+}
+
+impl DisplayMode {
+    pub fn of(mode: &VideoMode) -> Self {
+        let size = mode.size();
+        Self {
+            width: size.width,
+            height: size.height,
+            refresh_rate_millihertz: mode.refresh_rate_millihertz(),
+            bit_depth: mode.bit_depth(),
+        }
+    }
+
+    // largest first, then the fastest and deepest
+    fn rank(&self) -> (u64, u32, u16) {
+        (
+            self.width as u64 * self.height as u64,
+            self.refresh_rate_millihertz,
+            self.bit_depth,
+        )
+    }
+}
+
+/// The mode of the window, the monitor it is shown on and the video mode of exclusive
+/// fullscreen.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DisplaySettings {
+    pub mode: WindowMode,
+    /// the index of the monitor in `Window::available_monitors`, none is the monitor the
+    /// window is on.
+    pub monitor: Option<usize>,
+    /// the video mode wanted for exclusive fullscreen, none is the best the monitor has.
+    pub video_mode: Option<DisplayMode>,
+}
+
+impl DisplaySettings {
+    pub fn windowed() -> Self {
+        Self::default()
+    }
+
+    pub fn borderless() -> Self {
+        Self {
+            mode: WindowMode::Borderless,
+            ..Default::default()
+        }
+    }
+
+    pub fn exclusive(video_mode: Option<DisplayMode>) -> Self {
+        Self {
+            mode: WindowMode::Exclusive,
+            video_mode,
+            ..Default::default()
+        }
+    }
+
+    pub fn on_monitor(mut self, monitor: usize) -> Self {
+        self.monitor = Some(monitor);
+        self
+    }
+}
+
+/// the index of the mode in `modes` closest to `wanted`. the size must match, then the
+/// nearest refresh rate and bit depth win. without a wanted mode the largest, fastest one
+/// is chosen. none when there are no modes or none of the wanted size.
+pub fn choose_display_mode(modes: &[DisplayMode], wanted: Option<&DisplayMode>) -> Option<usize> {
+    let indexed = modes.iter().enumerate();
+    match wanted {
+        Some(wanted) => indexed
+            .filter(|(_, m)| (m.width, m.height) == (wanted.width, wanted.height))
+            .min_by_key(|(_, m)| {
+                (
+                    m.refresh_rate_millihertz
+                        .abs_diff(wanted.refresh_rate_millihertz),
+                    m.bit_depth.abs_diff(wanted.bit_depth),
+                )
+            })
+            .map(|(i, _)| i),
+        None => indexed.max_by_key(|(_, m)| m.rank()).map(|(i, _)| i),
+    }
+}
+
+/// Switches a window between the modes of `DisplaySettings`. the windowed size and position
+/// are kept while fullscreen and restored when going back.
+#[derive(Debug, Default)]
+pub struct DisplayController {
+    settings: DisplaySettings,
+    windowed: Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>,
+}
+
+impl DisplayController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the settings applied last.
+    pub fn settings(&self) -> DisplaySettings {
+        self.settings
+    }
+
+    /// the monitors the window can be shown on, `DisplaySettings::monitor` indexes these.
+    pub fn monitors(window: &Window) -> Vec<MonitorHandle> {
+        window.available_monitors().collect()
+    }
+
+    /// the video modes of `monitor` for exclusive fullscreen.
+    pub fn display_modes(monitor: &MonitorHandle) -> Vec<DisplayMode> {
+        monitor.video_modes().map(|m| DisplayMode::of(&m)).collect()
+    }
+
+    /// put the window in the mode of `settings` and have the device recreate its swapchain
+    /// for the new size. fails when the monitor or video mode doesn't exist, the window
+    /// stays as it was.
+    pub fn apply(
+        &mut self,
+        window: &Window,
+        device: &mut gfx::Device,
+        settings: DisplaySettings,
+    ) -> Result<()> {
+        let fullscreen = fullscreen(window, &settings)?;
+
+        // remember where the window was to come back to it
+        if self.settings.mode == WindowMode::Windowed && fullscreen.is_some() {
+            let position = window.outer_position().unwrap_or_default();
+            self.windowed = Some((position, window.inner_size()));
+        }
+
+        debug!(?settings, "change display mode");
+        window.set_fullscreen(fullscreen);
+        if settings.mode == WindowMode::Windowed {
+            if let Some((position, size)) = self.windowed.take() {
+                window.set_outer_position(position);
+                window.set_inner_size(size);
+            }
+        }
+        self.settings = settings;
+
+        let size = window.inner_size();
+        device.display_mode_changed(size.width, size.height);
+        Ok(())
+    }
+
+    /// go borderless fullscreen on the current monitor from windowed, and back to windowed
+    /// from either fullscreen mode.
+    pub fn toggle_fullscreen(&mut self, window: &Window, device: &mut gfx::Device) -> Result<()> {
+        let settings = match self.settings.mode {
+            WindowMode::Windowed => DisplaySettings {
+                mode: WindowMode::Borderless,
+                ..self.settings
+            },
+            WindowMode::Borderless | WindowMode::Exclusive => DisplaySettings {
+                mode: WindowMode::Windowed,
+                ..self.settings
+            },
+        };
+        self.apply(window, device, settings)
+    }
+}
+
+// the fullscreen state of winit for `settings`
+fn fullscreen(window: &Window, settings: &DisplaySettings) -> Result<Option<Fullscreen>> {
+    if settings.mode == WindowMode::Windowed {
+        return Ok(None);
+    }
+
+    let monitor = match settings.monitor {
+        Some(index) => Some(
+            window
+                .available_monitors()
+                .nth(index)
+                .ok_or_else(|| anyhow!("There is no monitor {}.", index))?,
+        ),
+        None => window.current_monitor(),
+    };
+
+    match settings.mode {
+        WindowMode::Windowed => Ok(None),
+        WindowMode::Borderless => Ok(Some(Fullscreen::Borderless(monitor))),
+        WindowMode::Exclusive => {
+            let monitor = monitor.ok_or_else(|| anyhow!("The window is on no monitor."))?;
+            let modes = monitor.video_modes().collect::<Vec<_>>();
+            let described = modes.iter().map(DisplayMode::of).collect::<Vec<_>>();
+            let index =
+                choose_display_mode(&described, settings.video_mode.as_ref()).ok_or_else(|| {
+                    anyhow!(
+                        "Monitor {:?} has no video mode like {:?}.",
+                        monitor.name(),
+                        settings.video_mode
+                    )
+                })?;
+            Ok(Some(Fullscreen::Exclusive(modes[index].clone())))
+        }
+    }
+}
+
+/// What the cursor does over the window.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CursorMode {
+    /// shown and free to leave the window.
+    #[default]
+    Normal,
+    /// hidden over the window, free to leave it.
+    Hidden,
+    /// shown and kept inside the window.
+    Confined,
+    /// hidden and held in place, only mouse deltas move, for first person cameras. falls
+    /// back to confined where the platform can't lock.
+    Locked,
+}
+
+impl CursorMode {
+    /// the grab of winit for the mode.
+    pub fn grab(&self) -> CursorGrabMode {
+        match self {
+            CursorMode::Normal | CursorMode::Hidden => CursorGrabMode::None,
+            CursorMode::Confined => CursorGrabMode::Confined,
+            CursorMode::Locked => CursorGrabMode::Locked,
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        matches!(self, CursorMode::Normal | CursorMode::Confined)
+    }
+}
+
+/// Changes the cursor, title and icon of a window. the cursor is released while the window
+/// has no focus and grabbed again when it comes back, see `follow_focus`.
+#[derive(Debug, Default)]
+pub struct WindowController {
+    cursor: CursorMode,
+    unfocused: bool,
+}
+
+impl WindowController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the cursor mode set last, also while it is released for lack of focus.
+    pub fn cursor_mode(&self) -> CursorMode {
+        self.cursor
+    }
+
+    /// whether the window had the focus when `follow_focus` was called last.
+    pub fn is_focused(&self) -> bool {
+        !self.unfocused
+    }
+
+    /// grab and hide the cursor as `mode` says. fails when the platform can grab in no way,
+    /// the cursor stays as it was.
+    pub fn set_cursor_mode(&mut self, window: &Window, mode: CursorMode) -> Result<()> {
+        debug!(?mode, "set cursor mode");
+        if !self.unfocused {
+            apply_cursor(window, mode)?;
+        }
+        self.cursor = mode;
+        Ok(())
+    }
+
+    /// release the cursor when the window lost the focus and grab it again when it came back,
+    /// called every frame with e.g. `Input::is_focused`.
+    pub fn follow_focus(&mut self, window: &Window, focused: bool) -> Result<()> {
+        if focused != self.unfocused {
+            return Ok(());
+        }
+        self.unfocused = !focused;
+        let mode = if focused {
+            self.cursor
+        } else {
+            CursorMode::Normal
+        };
+        apply_cursor(window, mode)
+    }
+
+    pub fn set_title(&self, window: &Window, title: &str) {
+        window.set_title(title);
+    }
+
+    /// the icon of the window in the title bar and task bar, none is the platform default.
+    pub fn set_icon(&self, window: &Window, icon: Option<Icon>) {
+        window.set_window_icon(icon);
+    }
+}
+
+/// an icon of `width` by `height` rgba8 pixels for `WindowController::set_icon`.
+pub fn window_icon(width: u32, height: u32, pixels: Vec<u8>) -> Result<Icon> {
+    Icon::from_rgba(pixels, width, height).map_err(|e| anyhow!("Can't make a window icon: {}.", e))
+}
+
+/// an icon from a png file for `WindowController::set_icon`.
+pub fn load_window_icon(path: &str) -> Result<Icon> {
+    let (width, height, pixels) = gfx::read_png(path)?;
+    window_icon(width, height, pixels)
+}
+
+// grab and show the cursor for `mode`, locking falls back to confining
+fn apply_cursor(window: &Window, mode: CursorMode) -> Result<()> {
+    let grabbed = window.set_cursor_grab(mode.grab()).or_else(|e| match mode {
+        CursorMode::Locked => window.set_cursor_grab(CursorGrabMode::Confined),
+        _ => Err(e),
+    });
+    grabbed.map_err(|e| anyhow!("Can't grab the cursor as {:?}: {}.", mode, e))?;
+    window.set_cursor_visible(mode.is_visible());
+    Ok(())
+}
@@ -0,0 +1,273 @@
+// SPDX-License-Identifier: MIT
+
+//! The hooks told about every device memory allocation and free of a device.
+
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use deimos::gfx::{
+    Allocation, AllocationHooks, AllocationKind, DeletionQueue, HeadlessDevice, NullBackend,
+};
+use vulkanalia::prelude::v1_0::*;
+
+#[derive(Clone, Default)]
+struct Recorder {
+    events: Arc<Mutex<Vec<(bool, Allocation)>>>,
+    // allocations past the budget are refused and not recorded
+    budget: Option<vk::DeviceSize>,
+    // how long recording a free takes, so frees from several threads overlap
+    delay: Duration,
+}
+
+impl Recorder {
+    fn events(&self) -> Vec<(bool, Allocation)> {
+        self.events.lock().unwrap().clone()
+    }
+
+    fn bytes(&self) -> vk::DeviceSize {
+        self.events()
+            .iter()
+            .map(|(allocated, a)| {
+                if *allocated {
+                    a.size as i64
+                } else {
+                    -(a.size as i64)
+                }
+            })
+            .sum::<i64>() as vk::DeviceSize
+    }
+}
+
+impl AllocationHooks for Recorder {
+    fn allocated(&mut self, allocation: &Allocation) -> Result<()> {
+        match self.budget {
+            Some(budget) if self.bytes() + allocation.size > budget => {
+                Err(anyhow!("Over the budget of {} bytes.", budget))
+            }
+            _ => {
+                self.events.lock().unwrap().push((true, allocation.clone()));
+                Ok(())
+            }
+        }
+    }
+
+    fn freed(&mut self, allocation: &Allocation) {
+        thread::sleep(self.delay);
+        self.events
+            .lock()
+            .unwrap()
+            .push((false, allocation.clone()));
+    }
+}
+
+fn buffer(gpu: &HeadlessDevice, size: vk::DeviceSize) -> Result<deimos::gfx::Buffer> {
+    gpu.create_buffer(
+        size,
+        vk::BufferUsageFlags::TRANSFER_DST,
+        vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+    )
+}
+
+#[test]
+fn hooks_see_allocations_and_frees_with_their_names() -> Result<()> {
+    let backend = NullBackend::new();
+    let gpu = HeadlessDevice::create_null(&backend)?;
+    let recorder = Recorder::default();
+    gpu.set_allocation_hooks(Some(Box::new(recorder.clone())));
+
+    let vertices = buffer(&gpu, 1000)?;
+    gpu.set_name(vertices.buffer, "vertices");
+    let texture = gpu.create_texture(
+        64,
+        64,
+        1,
+        vk::Format::R8G8B8A8_UNORM,
+        vk::ImageUsageFlags::SAMPLED,
+    )?;
+    let stats = gpu.allocation_stats();
+    assert_eq!(stats.allocations, 2);
+    assert_eq!(stats.bytes, stats.total_bytes);
+
+    // the largest comes first, names given after the allocation stick to it
+    let live = gpu.live_allocations();
+    assert_eq!(live[0].kind, AllocationKind::Texture);
+    assert_eq!(live[0].resource, texture.image.as_raw());
+    assert_eq!(live[1].name.as_deref(), Some("vertices"));
+    assert!(live[1].size >= 1000);
+
+    unsafe {
+        vertices.destroy(gpu.device());
+        texture.destroy(gpu.device());
+    }
+    let events = recorder.events();
+    let kinds = events
+        .iter()
+        .map(|(allocated, a)| (*allocated, a.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            (true, AllocationKind::Buffer),
+            (true, AllocationKind::Texture),
+            (false, AllocationKind::Buffer),
+            (false, AllocationKind::Texture),
+        ]
+    );
+    assert_eq!(events[2].1.name.as_deref(), Some("vertices"));
+    assert_eq!(gpu.allocation_stats().allocations, 0);
+
+    gpu.destroy();
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    Ok(())
+}
+
+#[test]
+fn allocations_past_a_budget_are_refused() -> Result<()> {
+    let backend = NullBackend::new();
+    let gpu = HeadlessDevice::create_null(&backend)?;
+    let recorder = Recorder {
+        budget: Some(4096),
+        ..Default::default()
+    };
+    gpu.set_allocation_hooks(Some(Box::new(recorder.clone())));
+
+    let small = buffer(&gpu, 1024)?;
+    let error = buffer(&gpu, 8192).err().unwrap();
+    assert_eq!(error.to_string(), "Over the budget of 4096 bytes.");
+
+    // the refused memory and its buffer are gone again
+    assert_eq!(gpu.allocation_stats().allocations, 1);
+    assert_eq!(backend.live_objects(vk::ObjectType::BUFFER), 1);
+    assert_eq!(backend.live_objects(vk::ObjectType::DEVICE_MEMORY), 1);
+    assert_eq!(recorder.bytes(), gpu.live_allocations()[0].size);
+    assert_eq!(gpu.allocation_stats().total_bytes, recorder.bytes());
+    // the hooks aren't told about freeing what they refused
+    assert_eq!(recorder.events().len(), 1);
+
+    // without hooks nothing is refused
+    gpu.set_allocation_hooks(None);
+    let large = buffer(&gpu, 8192)?;
+    unsafe {
+        small.destroy(gpu.device());
+        large.destroy(gpu.device());
+    }
+    gpu.destroy();
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    Ok(())
+}
+
+#[test]
+fn retired_resources_are_freed_when_the_queue_destroys_them() -> Result<()> {
+    let backend = NullBackend::new();
+    let gpu = HeadlessDevice::create_null(&backend)?;
+    let timeline = gpu.create_timeline()?;
+    let recorder = Recorder::default();
+    gpu.set_allocation_hooks(Some(Box::new(recorder.clone())));
+    unsafe {
+        let device = gpu.device();
+        let deletion = DeletionQueue::default();
+
+        // retired memory still counts until the submission using it completed
+        let value = timeline.submitted() + 1;
+        deletion.retire(value, buffer(&gpu, 256)?);
+        assert_eq!(deletion.collect(device, &timeline)?, 0);
+        assert_eq!(gpu.allocation_stats().allocations, 1);
+
+        timeline.submit(device, gpu.queue(), &[], &[], &[], &[])?;
+        timeline.wait(device, value)?;
+        assert_eq!(deletion.collect(device, &timeline)?, 1);
+        assert_eq!(gpu.allocation_stats().allocations, 0);
+        assert!(!recorder.events()[1].0);
+        timeline.destroy(device);
+    }
+    gpu.destroy();
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    Ok(())
+}
+
+// holds the free it is told about until it is released, or for a while when nobody does
+struct Gate {
+    recorder: Recorder,
+    entered: mpsc::Sender<()>,
+    release: mpsc::Receiver<()>,
+}
+
+impl AllocationHooks for Gate {
+    fn allocated(&mut self, allocation: &Allocation) -> Result<()> {
+        self.recorder.allocated(allocation)
+    }
+
+    fn freed(&mut self, allocation: &Allocation) {
+        let _ = self.entered.send(());
+        let _ = self.release.recv_timeout(Duration::from_secs(1));
+        self.recorder.freed(allocation);
+    }
+}
+
+#[test]
+fn hooks_see_frees_from_every_thread() -> Result<()> {
+    let backend = NullBackend::new();
+    let gpu = HeadlessDevice::create_null(&backend)?;
+    let recorder = Recorder {
+        delay: Duration::from_millis(1),
+        ..Default::default()
+    };
+    gpu.set_allocation_hooks(Some(Box::new(recorder.clone())));
+    let mut buffers = (0..32)
+        .map(|_| buffer(&gpu, 256))
+        .collect::<Result<Vec<_>>>()?;
+
+    // resources are destroyed with the bare device, which any thread can share
+    let device = gpu.device();
+    thread::scope(|scope| {
+        for chunk in buffers.chunks_mut(8) {
+            scope.spawn(move || unsafe {
+                for buffer in chunk {
+                    buffer.destroy(device);
+                }
+            });
+        }
+    });
+    assert_eq!(recorder.events().len(), 64);
+    assert_eq!(recorder.bytes(), 0);
+    assert_eq!(gpu.allocation_stats().allocations, 0);
+
+    gpu.destroy();
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    Ok(())
+}
+
+#[test]
+fn hooks_removed_while_they_run_stay_removed() -> Result<()> {
+    let backend = NullBackend::new();
+    let gpu = HeadlessDevice::create_null(&backend)?;
+    let recorder = Recorder::default();
+    let (entered, on_entered) = mpsc::channel();
+    let (release, on_release) = mpsc::channel();
+    gpu.set_allocation_hooks(Some(Box::new(Gate {
+        recorder: recorder.clone(),
+        entered,
+        release: on_release,
+    })));
+
+    let first = buffer(&gpu, 256)?;
+    let device = gpu.device();
+    thread::scope(|scope| -> Result<()> {
+        scope.spawn(|| unsafe { first.destroy(device) });
+        on_entered.recv()?;
+        gpu.set_allocation_hooks(None);
+        release.send(())?;
+        Ok(())
+    })?;
+    assert_eq!(recorder.events().len(), 2);
+
+    // the hooks that were running finished, the next allocation has none
+    let second = buffer(&gpu, 256)?;
+    unsafe { second.destroy(gpu.device()) };
+    assert_eq!(recorder.events().len(), 2);
+    gpu.destroy();
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    Ok(())
+}
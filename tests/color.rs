@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: MIT
+
+//! Colors parsed from hex, converted between hsv, hsl and srgb, and handed to the device.
+
+use deimos::gfx::Color;
+use vulkanalia::vk;
+
+fn assert_close(a: Color, b: Color) {
+    let a: [f32; 4] = a.into();
+    let b: [f32; 4] = b.into();
+    for (a, b) in a.iter().zip(b) {
+        assert!((a - b).abs() < 1e-3, "{:?} != {:?}", a, b);
+    }
+}
+
+#[test]
+fn hex_colors_parse_in_every_length() {
+    assert_eq!(Color::from_hex("#ff0000").unwrap(), Color::RED);
+    assert_eq!(Color::from_hex("0f0").unwrap(), Color::GREEN);
+    assert_eq!(
+        Color::from_hex("#0000ff80").unwrap(),
+        Color::rgba(0.0, 0.0, 1.0, 128.0 / 255.0)
+    );
+
+    // wrong lengths and digits are refused
+    for hex in ["#ff00", "#gg0000", "", "#ff00000", "#ffé0"] {
+        assert!(Color::from_hex(hex).is_err(), "{}", hex);
+    }
+}
+
+#[test]
+fn hsv_and_hsl_round_trip() {
+    assert_close(Color::from_hsv(0.0, 1.0, 1.0), Color::RED);
+    assert_close(Color::from_hsv(120.0, 1.0, 1.0), Color::GREEN);
+    assert_close(Color::from_hsl(240.0, 1.0, 0.5), Color::BLUE);
+    assert_close(Color::from_hsl(-60.0, 1.0, 0.5), Color::MAGENTA);
+
+    for color in [Color::ORANGE, Color::TEAL, Color::PINK, Color::GRAY] {
+        let (h, s, v) = color.to_hsv();
+        assert_close(Color::from_hsv(h, s, v), color);
+        let (h, s, l) = color.to_hsl();
+        assert_close(Color::from_hsl(h, s, l), color);
+    }
+}
+
+#[test]
+fn srgb_decodes_and_lerps_component_wise() {
+    // the midpoint of srgb is about a fifth of the light
+    let gray = Color::GRAY.to_linear();
+    assert!((gray.r - 0.214).abs() < 1e-3);
+    assert_eq!(gray.a, 1.0);
+    assert_close(gray.to_srgb(), Color::GRAY);
+
+    assert_close(
+        Color::BLACK.lerp(Color::WHITE.with_alpha(0.0), 0.25),
+        Color::rgba(0.25, 0.25, 0.25, 0.75),
+    );
+
+    // the same values reach clears and vertex colors
+    let clear: vk::ClearValue = Color::CORNFLOWER_BLUE.into();
+    assert_eq!(
+        unsafe { clear.color.float32 },
+        <[f32; 4]>::from(Color::CORNFLOWER_BLUE)
+    );
+}
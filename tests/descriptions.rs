@@ -0,0 +1,639 @@
+// SPDX-License-Identifier: MIT
+
+//! Generated buffer, texture and descriptor set descriptions. the checks are compared with a
+//! plain model of the vulkan rules on mock limits, then the same kind of descriptions go
+//! through the create functions of a real device: anything the model refuses comes back as a
+//! `DescriptionError`, the rest is created, and the validation layer stays quiet. the device
+//! part is skipped without a vulkan driver like the other headless tests.
+
+use anyhow::Result;
+use deimos::gfx::{
+    buffer_memory_properties, check_bindings, check_buffer, check_pipeline_layout, check_pixels,
+    check_texture, max_mip_levels, slot_set_layouts, vertex_input_descriptions, AdapterInfo,
+    AdapterLimits, AdapterSelection, AdapterType, DepthStencilClear, DescriptionError,
+    DescriptorSets, DescriptorSlot, HeadlessDevice, LoadOp, PassLoad, PowerPreference,
+    TextureImport, Vertex,
+};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestCaseError, TestRunner};
+use vulkanalia::prelude::v1_0::*;
+
+const BUFFER_USAGES: &[vk::BufferUsageFlags] = &[
+    vk::BufferUsageFlags::TRANSFER_SRC,
+    vk::BufferUsageFlags::TRANSFER_DST,
+    vk::BufferUsageFlags::UNIFORM_BUFFER,
+    vk::BufferUsageFlags::STORAGE_BUFFER,
+    vk::BufferUsageFlags::INDEX_BUFFER,
+    vk::BufferUsageFlags::VERTEX_BUFFER,
+];
+
+// usages every driver supports for rgba8 unorm with optimal tiling
+const TEXTURE_USAGES: &[vk::ImageUsageFlags] = &[
+    vk::ImageUsageFlags::TRANSFER_SRC,
+    vk::ImageUsageFlags::TRANSFER_DST,
+    vk::ImageUsageFlags::SAMPLED,
+    vk::ImageUsageFlags::COLOR_ATTACHMENT,
+];
+
+const DESCRIPTOR_TYPES: &[vk::DescriptorType] = &[
+    vk::DescriptorType::UNIFORM_BUFFER,
+    vk::DescriptorType::STORAGE_BUFFER,
+    vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+    vk::DescriptorType::SAMPLED_IMAGE,
+    vk::DescriptorType::SAMPLER,
+];
+
+const STAGES: &[vk::ShaderStageFlags] = &[
+    vk::ShaderStageFlags::VERTEX,
+    vk::ShaderStageFlags::FRAGMENT,
+    vk::ShaderStageFlags::COMPUTE,
+];
+
+fn mock_limits() -> vk::PhysicalDeviceLimits {
+    vk::PhysicalDeviceLimits {
+        max_image_dimension_2d: 4096,
+        max_image_dimension_cube: 2048,
+        max_image_array_layers: 256,
+        max_uniform_buffer_range: 65536,
+        ..Default::default()
+    }
+}
+
+fn flags<T: Copy + std::fmt::Debug>(
+    all: &'static [T],
+    empty: T,
+    or: fn(T, T) -> T,
+) -> impl Strategy<Value = T> {
+    proptest::sample::subsequence(all, 0..=all.len())
+        .prop_map(move |picked| picked.into_iter().fold(empty, or))
+}
+
+fn buffer_usage() -> impl Strategy<Value = vk::BufferUsageFlags> {
+    flags(BUFFER_USAGES, vk::BufferUsageFlags::empty(), |a, b| a | b)
+}
+
+fn texture_usage() -> impl Strategy<Value = vk::ImageUsageFlags> {
+    flags(TEXTURE_USAGES, vk::ImageUsageFlags::empty(), |a, b| a | b)
+}
+
+fn stages() -> impl Strategy<Value = vk::ShaderStageFlags> {
+    flags(STAGES, vk::ShaderStageFlags::empty(), |a, b| a | b)
+}
+
+// extents around the interesting edges, `limit` is that of the device
+fn dimension(limit: u32) -> impl Strategy<Value = u32> {
+    prop_oneof![
+        Just(0),
+        1..=40u32,
+        limit - 1..=limit.saturating_add(1),
+        any::<u32>(),
+    ]
+}
+
+fn samples() -> impl Strategy<Value = vk::SampleCountFlags> {
+    prop_oneof![
+        Just(vk::SampleCountFlags::_1),
+        Just(vk::SampleCountFlags::_4),
+        Just(vk::SampleCountFlags::_1 | vk::SampleCountFlags::_4),
+        Just(vk::SampleCountFlags::empty()),
+    ]
+}
+
+fn binding() -> impl Strategy<Value = vk::DescriptorSetLayoutBinding> {
+    (
+        0..6u32,
+        proptest::sample::select(DESCRIPTOR_TYPES),
+        0..4u32,
+        stages(),
+    )
+        .prop_map(|(binding, type_, count, stages)| {
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(binding)
+                .descriptor_type(type_)
+                .descriptor_count(count)
+                .stage_flags(stages)
+                .build()
+        })
+}
+
+fn slot() -> impl Strategy<Value = DescriptorSlot> {
+    proptest::sample::select(DescriptorSlot::ALL.to_vec())
+}
+
+// the model the checks are compared with, written from the vulkan spec
+
+fn model_mip_levels(width: u32, height: u32) -> u32 {
+    let mut size = width.max(height);
+    let mut levels = 1;
+    while size > 1 {
+        size /= 2;
+        levels += 1;
+    }
+    levels
+}
+
+#[derive(Clone, Debug)]
+struct TextureDesc {
+    width: u32,
+    height: u32,
+    mip_levels: u32,
+    layers: u32,
+    cube: bool,
+    samples: vk::SampleCountFlags,
+    usage: vk::ImageUsageFlags,
+}
+
+impl TextureDesc {
+    fn flags(&self) -> vk::ImageCreateFlags {
+        if self.cube {
+            vk::ImageCreateFlags::CUBE_COMPATIBLE
+        } else {
+            vk::ImageCreateFlags::empty()
+        }
+    }
+
+    fn check(&self, limits: &vk::PhysicalDeviceLimits) -> Result<(), DescriptionError> {
+        check_texture(
+            self.width,
+            self.height,
+            self.mip_levels,
+            self.layers,
+            self.flags(),
+            self.samples,
+            self.usage,
+            limits,
+        )
+    }
+
+    fn is_valid(&self, limits: &vk::PhysicalDeviceLimits) -> bool {
+        let limit = if self.cube {
+            limits.max_image_dimension_cube
+        } else {
+            limits.max_image_dimension_2d
+        };
+        let single = self.samples == vk::SampleCountFlags::_1;
+        (1..=limit).contains(&self.width)
+            && (1..=limit).contains(&self.height)
+            && (1..=limits.max_image_array_layers).contains(&self.layers)
+            && (!self.cube || (self.width == self.height && self.layers % 6 == 0))
+            && (1..=model_mip_levels(self.width, self.height)).contains(&self.mip_levels)
+            && [vk::SampleCountFlags::_1, vk::SampleCountFlags::_4].contains(&self.samples)
+            && (single || (self.mip_levels == 1 && self.layers == 1 && !self.cube))
+            && !self.usage.is_empty()
+    }
+}
+
+fn texture_desc(limit: u32) -> impl Strategy<Value = TextureDesc> {
+    (
+        dimension(limit),
+        dimension(limit),
+        prop_oneof![Just(0), 1..=4u32, 10..=14u32, any::<u32>()],
+        prop_oneof![Just(1), Just(0), Just(6), 2..=300u32],
+        any::<bool>(),
+        samples(),
+        texture_usage(),
+    )
+        .prop_map(
+            |(width, height, mip_levels, layers, cube, samples, usage)| TextureDesc {
+                width,
+                height,
+                mip_levels,
+                layers,
+                cube,
+                samples,
+                usage,
+            },
+        )
+}
+
+// uniform buffers without other shader usages are bound whole
+fn buffer_is_valid(
+    size: u64,
+    usage: vk::BufferUsageFlags,
+    limits: &vk::PhysicalDeviceLimits,
+) -> bool {
+    let transfers = vk::BufferUsageFlags::TRANSFER_SRC | vk::BufferUsageFlags::TRANSFER_DST;
+    let uniform_only = usage & !transfers == vk::BufferUsageFlags::UNIFORM_BUFFER;
+    size > 0
+        && !usage.is_empty()
+        && !(uniform_only && size > limits.max_uniform_buffer_range as u64)
+}
+
+fn bindings_are_valid(bindings: &[vk::DescriptorSetLayoutBinding]) -> bool {
+    bindings.iter().enumerate().all(|(i, b)| {
+        bindings[..i].iter().all(|other| other.binding != b.binding)
+            && (b.descriptor_count == 0 || !b.stage_flags.is_empty())
+    })
+}
+
+fn adapter(index: usize, kind: AdapterType) -> AdapterInfo {
+    AdapterInfo {
+        index,
+        name: format!("adapter {}", index),
+        vendor: "Unknown",
+        vendor_id: 0,
+        device_id: index as u32,
+        kind,
+        api_version: "1.0.0".into(),
+        driver_version: 0,
+        limits: AdapterLimits {
+            max_image_dimension_2d: 4096,
+            max_push_constants_size: 128,
+            max_bound_descriptor_sets: 4,
+            max_uniform_buffer_range: 16384,
+            max_sampler_anisotropy: 16.0,
+            max_color_samples: vk::SampleCountFlags::_1,
+            max_viewports: 1,
+        },
+        features: Default::default(),
+    }
+}
+
+fn adapter_type() -> impl Strategy<Value = AdapterType> {
+    proptest::sample::select(vec![
+        AdapterType::Discrete,
+        AdapterType::Integrated,
+        AdapterType::Virtual,
+        AdapterType::Cpu,
+        AdapterType::Other,
+    ])
+}
+
+fn selection() -> impl Strategy<Value = AdapterSelection> {
+    prop_oneof![
+        Just(AdapterSelection::First),
+        (0..6usize).prop_map(AdapterSelection::Index),
+        Just(AdapterSelection::Power(PowerPreference::HighPerformance)),
+        Just(AdapterSelection::Power(PowerPreference::LowPower)),
+    ]
+}
+
+// a create call either succeeds on a valid description or refuses it with a typed error
+fn expect<T>(valid: bool, result: Result<T>) -> Result<Option<T>, TestCaseError> {
+    match result {
+        Ok(value) if valid => Ok(Some(value)),
+        Ok(_) => Err(TestCaseError::fail("an invalid description was created")),
+        Err(error) if valid => Err(TestCaseError::fail(format!(
+            "a valid description failed: {}",
+            error
+        ))),
+        Err(error) if error.downcast_ref::<DescriptionError>().is_some() => Ok(None),
+        Err(error) => Err(TestCaseError::fail(format!("an untyped error: {}", error))),
+    }
+}
+
+proptest! {
+    #[test]
+    fn textures_are_checked_like_the_model(desc in texture_desc(4096)) {
+        let limits = mock_limits();
+        prop_assert_eq!(desc.check(&limits).is_ok(), desc.is_valid(&limits), "{:?}", desc);
+    }
+
+    #[test]
+    fn buffers_are_checked_like_the_model(
+        size in prop_oneof![Just(0), 1..=1u64 << 40, 65535..=65537u64, Just(u64::MAX)],
+        usage in buffer_usage(),
+    ) {
+        let limits = mock_limits();
+        prop_assert_eq!(
+            check_buffer(size, usage, &limits).is_ok(),
+            buffer_is_valid(size, usage, &limits)
+        );
+    }
+
+    #[test]
+    fn buffer_memory_follows_the_whole_usage(usage in buffer_usage()) {
+        // read by the gpu and uploaded with copies, or written by shaders
+        let reads = vk::BufferUsageFlags::UNIFORM_BUFFER
+            | vk::BufferUsageFlags::INDEX_BUFFER
+            | vk::BufferUsageFlags::VERTEX_BUFFER;
+        let device_local = usage.contains(vk::BufferUsageFlags::STORAGE_BUFFER)
+            || (usage.contains(vk::BufferUsageFlags::TRANSFER_DST)
+                && !usage.contains(vk::BufferUsageFlags::TRANSFER_SRC)
+                && usage.intersects(reads));
+        let properties = buffer_memory_properties(usage);
+        prop_assert_eq!(
+            properties.contains(vk::MemoryPropertyFlags::DEVICE_LOCAL),
+            device_local,
+            "{:?}",
+            usage
+        );
+        prop_assert_eq!(
+            properties.contains(vk::MemoryPropertyFlags::HOST_VISIBLE),
+            !device_local
+        );
+    }
+
+    #[test]
+    fn pixel_counts_never_overflow(
+        width in dimension(u32::MAX),
+        height in dimension(u32::MAX),
+        extra in prop_oneof![Just(0usize), 1..8usize],
+    ) {
+        let expected = width as u128 * height as u128 * 4;
+        let len = (expected.min(4096) as usize).saturating_sub(extra);
+        let result = check_pixels(width, height, &vec![0; len]);
+        prop_assert_eq!(result.is_ok(), len as u128 == expected);
+    }
+
+    #[test]
+    fn mip_levels_match_the_model(width in dimension(u32::MAX), height in dimension(u32::MAX)) {
+        prop_assert_eq!(max_mip_levels(width, height), model_mip_levels(width, height));
+    }
+
+    #[test]
+    fn bindings_are_checked_like_the_model(bindings in vec(binding(), 0..6)) {
+        prop_assert_eq!(check_bindings(&bindings).is_ok(), bindings_are_valid(&bindings));
+    }
+
+    #[test]
+    fn adapter_selection_stays_in_bounds(
+        kinds in vec(adapter_type(), 0..5),
+        selection in selection(),
+    ) {
+        let adapters = kinds
+            .iter()
+            .enumerate()
+            .map(|(i, kind)| adapter(i, *kind))
+            .collect::<Vec<_>>();
+        let chosen = selection.choose(&adapters);
+        match selection {
+            AdapterSelection::Index(index) => {
+                prop_assert_eq!(chosen, (index < adapters.len()).then_some(index))
+            }
+            _ => prop_assert_eq!(chosen.is_some(), !adapters.is_empty()),
+        }
+    }
+}
+
+#[test]
+fn vertex_layouts_follow_the_fields() {
+    // the engine vertex matches the layout shared with the tooling
+    let layout = deimos::vertex_layout!(Vertex {
+        position,
+        texel,
+        color
+    });
+    assert_eq!(layout.binding_description(), Vertex::binding_description());
+    assert_eq!(layout.attributes, Vertex::attribute_descriptions());
+
+    // packed fields are read normalized
+    #[repr(C)]
+    #[derive(Copy, Clone)]
+    struct Packed {
+        position: [f32; 3],
+        normal: [i16; 4],
+        color: [u8; 4],
+        bone: u32,
+    }
+    let layout = deimos::vertex_layout!(Packed {
+        position,
+        normal,
+        color,
+        bone
+    });
+    assert_eq!(layout.stride, 28);
+    let formats = layout
+        .attributes
+        .iter()
+        .map(|a| (a.location, a.format, a.offset))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        formats,
+        [
+            (0, vk::Format::R32G32B32_SFLOAT, 0),
+            (1, vk::Format::R16G16B16A16_SNORM, 12),
+            (2, vk::Format::R8G8B8A8_UNORM, 20),
+            (3, vk::Format::R32_UINT, 24),
+        ]
+    );
+}
+
+#[test]
+fn instance_buffers_follow_the_vertex_buffer() {
+    #[repr(C)]
+    #[derive(Copy, Clone)]
+    struct Instance {
+        offset: [f32; 3],
+        tint: [u8; 4],
+    }
+
+    // per instance data at the next binding and locations
+    let vertices = Vertex::layout();
+    let instances = deimos::vertex_layout!(Instance { offset, tint })
+        .at_binding(1)
+        .starting_at(3)
+        .per_instance();
+    let (bindings, attributes) =
+        vertex_input_descriptions(&[vertices.clone(), instances.clone()]).unwrap();
+    let rates = bindings
+        .iter()
+        .map(|b| (b.binding, b.stride, b.input_rate))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rates,
+        [
+            (0, 32, vk::VertexInputRate::VERTEX),
+            (1, 16, vk::VertexInputRate::INSTANCE),
+        ]
+    );
+    let locations = attributes
+        .iter()
+        .map(|a| (a.binding, a.location))
+        .collect::<Vec<_>>();
+    assert_eq!(locations, [(0, 0), (0, 1), (0, 2), (1, 3), (1, 4)]);
+
+    // the buffers must not overlap
+    let error = vertex_input_descriptions(&[vertices.clone(), instances.clone().at_binding(0)]);
+    assert_eq!(error, Err(DescriptionError::DuplicateVertexBinding(0)));
+    let error = vertex_input_descriptions(&[vertices, instances.starting_at(2)]);
+    assert_eq!(error, Err(DescriptionError::DuplicateLocation(2)));
+}
+
+#[test]
+fn pipeline_layouts_stay_within_the_limits() {
+    let range = |offset, size| vk::PushConstantRange {
+        stage_flags: vk::ShaderStageFlags::VERTEX,
+        offset,
+        size,
+    };
+
+    // four sets and 128 bytes of push constants are what every device has
+    assert_eq!(check_pipeline_layout(4, &[range(0, 128)], 4, 128), Ok(()));
+    assert_eq!(
+        check_pipeline_layout(5, &[], 4, 128),
+        Err(DescriptionError::TooManySets { sets: 5, limit: 4 })
+    );
+    assert_eq!(
+        check_pipeline_layout(1, &[range(0, 64), range(96, 64)], 4, 128),
+        Err(DescriptionError::PushConstantsTooLarge {
+            end: 160,
+            limit: 128
+        })
+    );
+}
+
+#[test]
+fn pass_loads_map_onto_their_attachments() {
+    // the default clears color to opaque black and depth to the far plane
+    let load = PassLoad::default();
+    assert_eq!(load.ops(), [vk::AttachmentLoadOp::CLEAR; 2]);
+    assert!(!load.loads());
+    let [color, depth] = load.clear_values();
+    unsafe {
+        assert_eq!(color.color.float32, [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(
+            (depth.depth_stencil.depth, depth.depth_stencil.stencil),
+            (1.0, 0)
+        );
+    }
+
+    // each attachment gets its own op and value
+    let load = PassLoad::clear([0.2, 0.4, 0.6, 1.0]).with_depth_stencil(LoadOp::Clear(
+        DepthStencilClear {
+            depth: 0.0,
+            stencil: 7,
+        },
+    ));
+    let [color, depth] = load.clear_values();
+    unsafe {
+        assert_eq!(color.color.float32, [0.2, 0.4, 0.6, 1.0]);
+        assert_eq!(
+            (depth.depth_stencil.depth, depth.depth_stencil.stencil),
+            (0.0, 7)
+        );
+    }
+
+    let load = PassLoad {
+        color: LoadOp::DontCare,
+        depth_stencil: LoadOp::Load,
+    };
+    assert_eq!(
+        load.ops(),
+        [vk::AttachmentLoadOp::DONT_CARE, vk::AttachmentLoadOp::LOAD]
+    );
+    assert!(load.loads());
+
+    // an attachment without a clear value keeps its contents
+    let load = PassLoad::new(None, Some(0.5));
+    assert_eq!(
+        load.ops(),
+        [vk::AttachmentLoadOp::LOAD, vk::AttachmentLoadOp::CLEAR]
+    );
+    let [_, depth] = load.clear_values();
+    unsafe {
+        assert_eq!(depth.depth_stencil.depth, 0.5);
+    }
+    assert_eq!(
+        PassLoad::new(Some([0.0, 0.0, 0.0, 1.0]), Some(1.0)),
+        PassLoad::default()
+    );
+}
+
+#[test]
+fn generated_descriptions_never_reach_the_device_invalid() {
+    let Some(gpu) = HeadlessDevice::create(AdapterSelection::First).unwrap() else {
+        assert!(
+            std::env::var_os("DEIMOS_REQUIRE_GPU").is_none(),
+            "DEIMOS_REQUIRE_GPU is set but there is no vulkan device"
+        );
+        eprintln!("skipping, there is no vulkan device");
+        return;
+    };
+    let device = gpu.device();
+    let limits = unsafe {
+        gpu.instance()
+            .get_physical_device_properties(gpu.physical())
+            .limits
+    };
+    let mut runner = TestRunner::new(Config::with_cases(64));
+
+    // buffers, kept small so valid ones fit any device
+    let buffers = (prop_oneof![Just(0), 1..=1u64 << 16], buffer_usage());
+    runner
+        .run(&buffers, |(size, usage)| {
+            let valid = buffer_is_valid(size, usage, &limits);
+            let result = gpu.create_buffer(size, usage, vk::MemoryPropertyFlags::DEVICE_LOCAL);
+            if let Some(buffer) = expect(valid, result)? {
+                unsafe { buffer.destroy(device) };
+            }
+            Ok(())
+        })
+        .unwrap();
+
+    // single sampled 2d textures, the valid ones are small
+    let textures = texture_desc(limits.max_image_dimension_2d)
+        .prop_map(|desc| TextureDesc {
+            layers: 1,
+            cube: false,
+            samples: vk::SampleCountFlags::_1,
+            ..desc
+        })
+        .prop_filter("too large to allocate", |d| {
+            !d.is_valid(&limits) || d.width.max(d.height) <= 64
+        });
+    runner
+        .run(&textures, |desc| {
+            let result = gpu.create_texture(
+                desc.width,
+                desc.height,
+                desc.mip_levels,
+                vk::Format::R8G8B8A8_UNORM,
+                desc.usage,
+            );
+            if let Some(texture) = expect(desc.is_valid(&limits), result)? {
+                unsafe { texture.destroy(device) };
+            }
+            Ok(())
+        })
+        .unwrap();
+
+    // uploads with the right and wrong amount of pixels
+    let uploads = (0..=24u32, 0..=24u32, prop_oneof![Just(0usize), 1..4usize]);
+    runner
+        .run(&uploads, |(width, height, missing)| {
+            let expected = (width * height * 4) as usize;
+            let pixels = vec![0x80; expected.saturating_sub(missing)];
+            let valid = width > 0 && height > 0 && pixels.len() == expected;
+            let result = gpu.upload_texture(width, height, &pixels, TextureImport::Color);
+            if let Some((texture, view)) = expect(valid, result)? {
+                unsafe {
+                    view.destroy(device);
+                    texture.destroy(device);
+                }
+            }
+            Ok(())
+        })
+        .unwrap();
+
+    // set layouts and the pipeline layout order of their slots
+    let sets = vec((slot(), vec(binding(), 0..6)), 0..5);
+    runner
+        .run(&sets, |sets| {
+            let mut created = vec![];
+            for (slot, bindings) in &sets {
+                let result = unsafe { DescriptorSets::create(device, *slot, bindings) };
+                created.extend(expect(bindings_are_valid(bindings), result)?);
+            }
+
+            let unique = created
+                .iter()
+                .enumerate()
+                .all(|(i, s)| created[..i].iter().all(|o| o.slot != s.slot));
+            let refs = created.iter().collect::<Vec<_>>();
+            let layouts = slot_set_layouts(&refs, vk::DescriptorSetLayout::null());
+            prop_assert_eq!(layouts.is_ok(), unique);
+            if let Ok(layouts) = layouts {
+                let highest = created.iter().map(|s| s.slot.index() as usize + 1).max();
+                prop_assert_eq!(layouts.len(), highest.unwrap_or(0));
+            }
+
+            created.iter().for_each(|s| unsafe { s.destroy(device) });
+            Ok(())
+        })
+        .unwrap();
+
+    assert_eq!(gpu.validation_errors(), 0);
+    gpu.destroy();
+}
@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: MIT
+
+//! The draws extracted from ecs components, hidden entities are left out.
+
+use deimos::ecs::{extract, MaterialHandle, MeshHandle, Transform, Visibility};
+use deimos::gfx::Buffer;
+use deimos::math::{Mat4, Vec3, Vec4};
+use deimos::rendering::{Material, Mesh};
+use vulkanalia::prelude::v1_0::*;
+
+fn mesh(count: u32) -> MeshHandle {
+    let buffer = Buffer::create(vk::Buffer::null(), vk::DeviceMemory::null(), 0);
+    MeshHandle(Mesh {
+        vertices: buffer,
+        indices: buffer,
+        index_type: vk::IndexType::UINT16,
+        count,
+        center: Vec3::new(0.0, 0.0, 0.0),
+    })
+}
+
+fn at(x: f32) -> Transform {
+    Transform {
+        translation: Vec3::new(x, 0.0, 0.0),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn visible_entities_are_queued() {
+    let red = MaterialHandle(Material::opaque(Vec4::new(1.0, 0.0, 0.0, 1.0)));
+    let blue = MaterialHandle(Material::opaque(Vec4::new(0.0, 0.0, 1.0, 1.0)));
+    let (cube, sphere) = (mesh(36), mesh(960));
+    let (left, right, behind) = (at(-1.0), at(1.0), at(5.0));
+    let hidden = Visibility::Hidden;
+    let visible = Visibility::Visible;
+
+    let entities = vec![
+        (&cube, &red, &left, None),
+        (&sphere, &blue, &behind, Some(&hidden)),
+        (&sphere, &red, &right, Some(&visible)),
+    ];
+    let mut draws = vec![];
+    assert_eq!(extract(&mut draws, entities), 2);
+
+    let queued = draws
+        .iter()
+        .map(|(mesh, material, transform)| (mesh.count, *material, *transform))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        queued,
+        vec![
+            (36, red.0, Mat4::from_translation(Vec3::new(-1.0, 0.0, 0.0))),
+            (960, red.0, Mat4::from_translation(Vec3::new(1.0, 0.0, 0.0))),
+        ]
+    );
+}
+
+#[cfg(feature = "hecs")]
+#[test]
+fn hecs_worlds_are_walked() {
+    use deimos::ecs::extract_world;
+
+    let red = MaterialHandle(Material::opaque(Vec4::new(1.0, 0.0, 0.0, 1.0)));
+    let mut world = hecs::World::new();
+    world.spawn((mesh(36), red, at(0.0)));
+    world.spawn((mesh(36), red, at(1.0), Visibility::Hidden));
+    // no material, not drawn
+    world.spawn((mesh(36), at(2.0)));
+
+    let mut draws: Vec<(Mesh, Material, Mat4)> = vec![];
+    assert_eq!(extract_world(&world, &mut draws), 1);
+    assert_eq!(draws[0].2, Mat4::from_translation(Vec3::new(0.0, 0.0, 0.0)));
+}
@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: MIT
+
+//! The observers of device events, called for their kind in the order they were registered.
+
+use std::sync::{Arc, Mutex};
+
+use deimos::gfx::{DeviceEvent, DeviceEventKind, DeviceEvents};
+use vulkanalia::vk;
+
+#[test]
+fn observers_are_called_in_order() {
+    let calls = Arc::new(Mutex::new(vec![]));
+    let mut events = DeviceEvents::new();
+    for name in ["ui", "post", "debug"] {
+        let calls = calls.clone();
+        events.observe(DeviceEventKind::SwapchainRecreated, move |e| {
+            calls.lock().unwrap().push((name, *e))
+        });
+    }
+
+    let recreated = DeviceEvent::SwapchainRecreated {
+        format: vk::Format::B8G8R8A8_SRGB,
+        extent: vk::Extent2D {
+            width: 800,
+            height: 600,
+        },
+    };
+    events.emit(recreated);
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec![("ui", recreated), ("post", recreated), ("debug", recreated)]
+    );
+}
+
+#[test]
+fn observers_only_see_their_kind_until_removed() {
+    let frames = Arc::new(Mutex::new(vec![]));
+    let mut events = DeviceEvents::new();
+    let begin = {
+        let frames = frames.clone();
+        events.observe(DeviceEventKind::FrameBegin, move |e| {
+            frames.lock().unwrap().push(*e)
+        })
+    };
+    let end = {
+        let frames = frames.clone();
+        events.observe(DeviceEventKind::FrameEnd, move |e| {
+            frames.lock().unwrap().push(*e)
+        })
+    };
+    assert_eq!(events.len(), 2);
+
+    events.emit(DeviceEvent::FrameBegin { slot: 0, index: 2 });
+    events.emit(DeviceEvent::Resized {
+        width: 0,
+        height: 0,
+    });
+    events.emit(DeviceEvent::FrameEnd { slot: 0, index: 2 });
+    assert_eq!(
+        *frames.lock().unwrap(),
+        vec![
+            DeviceEvent::FrameBegin { slot: 0, index: 2 },
+            DeviceEvent::FrameEnd { slot: 0, index: 2 },
+        ]
+    );
+
+    // a removed observer isn't called anymore and can't be removed twice
+    assert!(events.remove(begin));
+    assert!(!events.remove(begin));
+    events.emit(DeviceEvent::FrameBegin { slot: 1, index: 0 });
+    assert_eq!(frames.lock().unwrap().len(), 2);
+    assert!(events.remove(end));
+    assert!(events.is_empty());
+}
@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: MIT
+
+//! Frame export without a window: which rendered frames the capture clock picks and the
+//! timestamps they get. copying frames back needs a swapchain and is left to the demo.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use deimos::gfx::{ExportedFrame, FrameClock, FrameExportSettings, FrameSink};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn frames_are_captured_once_per_interval() {
+    let mut clock = FrameClock::new(10.0);
+    assert_eq!(clock.interval(), ms(100));
+
+    // the first frame starts the capture, faster frames wait for the next interval
+    assert_eq!(clock.tick(ms(0)), Some((0, ms(0))));
+    assert_eq!(clock.tick(ms(16)), None);
+    assert_eq!(clock.tick(ms(99)), None);
+    assert_eq!(clock.tick(ms(116)), Some((1, ms(100))));
+
+    // slow frames skip the intervals they missed, the timestamps stay on the grid
+    assert_eq!(clock.tick(ms(350)), Some((3, ms(300))));
+    assert_eq!(clock.tick(ms(360)), None);
+}
+
+#[test]
+fn closures_are_sinks() -> Result<()> {
+    let mut indices = vec![];
+    {
+        let mut sink = |frame: &ExportedFrame| {
+            indices.push(frame.index);
+            Ok(())
+        };
+        let frame = |index| ExportedFrame {
+            index,
+            timestamp: ms(index * 100),
+            width: 1,
+            height: 1,
+            pixels: vec![0, 0, 0, 255],
+        };
+        sink.write(&frame(0))?;
+        sink.write(&frame(2))?;
+        sink.finish()?;
+    }
+    assert_eq!(indices, [0, 2]);
+
+    // captures run at least once a second
+    assert_eq!(FrameExportSettings::new(0.0).framerate, 1.0);
+    assert_eq!(FrameExportSettings::default().framerate, 30.0);
+    Ok(())
+}
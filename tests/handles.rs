@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: MIT
+
+//! Typed handles into a pool: lookups through them, how a destroyed resource invalidates
+//! its handle even after the slot was reused, and the registry of device resources by id.
+
+use deimos::gfx::{
+    Buffer, BufferId, Handle, HandlePool, ResourceCounts, ResourceRegistry, Retired, Texture,
+    TextureView, TextureViewId,
+};
+use vulkanalia::vk::{self, Handle as _};
+
+#[test]
+fn handles_reach_their_resource() {
+    let mut pool = HandlePool::new();
+    let a = pool.insert("albedo");
+    let b = pool.insert("depth");
+    assert_ne!(a, b);
+    assert_eq!(pool.len(), 2);
+    assert_eq!(*pool.get(a).unwrap(), "albedo");
+
+    // copies of a handle are the same handle
+    let copy = b;
+    *pool.get_mut(copy).unwrap() = "shadow";
+    assert_eq!(*pool.get(b).unwrap(), "shadow");
+
+    let all = pool.iter().map(|(h, v)| (h, *v)).collect::<Vec<_>>();
+    assert_eq!(all, vec![(a, "albedo"), (b, "shadow")]);
+}
+
+#[test]
+fn destroyed_handles_are_errors() {
+    let mut pool = HandlePool::new();
+    let a: Handle<u32> = pool.insert(1);
+    assert_eq!(pool.remove(a).unwrap(), 1);
+    assert!(!pool.contains(a));
+    assert!(pool.get(a).is_err());
+    assert!(pool.remove(a).is_err());
+
+    // the slot is reused, the old handle still doesn't reach the new resource
+    let b = pool.insert(2);
+    assert_eq!(b.index(), a.index());
+    assert_ne!(b.generation(), a.generation());
+    assert!(pool.get_mut(a).is_err());
+    assert_eq!(*pool.get(b).unwrap(), 2);
+
+    // draining invalidates everything
+    assert_eq!(pool.drain(), vec![2]);
+    assert!(pool.is_empty());
+    assert!(pool.get(b).is_err());
+}
+
+#[test]
+fn registry_keeps_resources_by_kind() {
+    let mut registry = ResourceRegistry::new();
+    let buffer: BufferId = registry.insert(Buffer::create(
+        vk::Buffer::from_raw(1),
+        vk::DeviceMemory::from_raw(2),
+        256,
+    ));
+    let view: TextureViewId = registry.insert(TextureView::create(vk::ImageView::from_raw(3)));
+    registry.insert(Texture::create(
+        vk::Image::from_raw(4),
+        vk::DeviceMemory::from_raw(5),
+        vk::Format::R8G8B8A8_SRGB,
+    ));
+    assert_eq!(registry.get(buffer).unwrap().size, 256);
+    assert_eq!(
+        registry.counts(),
+        ResourceCounts {
+            buffers: 1,
+            textures: 1,
+            views: 1,
+            shaders: 0,
+        }
+    );
+
+    // a removed resource is handed back to be destroyed, its id no longer reaches anything
+    let removed = registry.remove(view).unwrap();
+    assert_eq!(removed.view, vk::ImageView::from_raw(3));
+    assert!(!registry.contains(view));
+    assert_eq!(registry.counts().total(), 2);
+
+    // what is left goes with the device
+    let drained = registry.drain();
+    assert!(matches!(
+        drained.as_slice(),
+        [Retired::Texture(_), Retired::Buffer(_)]
+    ));
+    assert!(registry.get(buffer).is_err());
+}
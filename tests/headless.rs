@@ -0,0 +1,727 @@
+// SPDX-License-Identifier: MIT
+
+//! Integration tests on a real gpu without a window. they run wherever a vulkan driver is
+//! installed, lavapipe is enough, and are skipped otherwise. set `DEIMOS_REQUIRE_GPU` to
+//! fail instead of skipping, e.g. on ci where lavapipe is installed on purpose.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::mem::size_of;
+
+use anyhow::Result;
+use deimos::gfx::{self, AdapterSelection, HeadlessDevice, Shader, TextureImport};
+use vulkanalia::prelude::v1_0::*;
+
+const TRIANGLE_VERT: &[u8] = include_bytes!("shaders/triangle_vert.spv");
+const SOLID_FRAG: &[u8] = include_bytes!("shaders/solid_frag.spv");
+const DOUBLE_COMP: &[u8] = include_bytes!("shaders/double_comp.spv");
+const FULLSCREEN_VERT: &[u8] = include_bytes!("../shaders/fullscreen_vert.spv");
+const COPY_FRAG: &[u8] = include_bytes!("../shaders/copy_frag.spv");
+
+const FORMAT: vk::Format = vk::Format::R8G8B8A8_UNORM;
+const CLEAR: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+fn gpu() -> Option<HeadlessDevice> {
+    let gpu = HeadlessDevice::create(AdapterSelection::First).unwrap();
+    if gpu.is_none() {
+        assert!(
+            std::env::var_os("DEIMOS_REQUIRE_GPU").is_none(),
+            "DEIMOS_REQUIRE_GPU is set but there is no vulkan device"
+        );
+        eprintln!("skipping, there is no vulkan device");
+    }
+    gpu
+}
+
+fn host_buffer(
+    gpu: &HeadlessDevice,
+    size: usize,
+    usage: vk::BufferUsageFlags,
+) -> Result<gfx::Buffer> {
+    gpu.create_buffer(
+        size as vk::DeviceSize,
+        usage,
+        vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+    )
+}
+
+fn hash(pixels: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    pixels.hash(&mut hasher);
+    hasher.finish()
+}
+
+// a color target that is left ready to be copied from after its pass
+struct Target {
+    width: u32,
+    height: u32,
+    texture: gfx::Texture,
+    view: gfx::TextureView,
+    render_pass: vk::RenderPass,
+    framebuffer: vk::Framebuffer,
+}
+
+impl Target {
+    unsafe fn create(gpu: &HeadlessDevice, width: u32, height: u32) -> Result<Self> {
+        let device = gpu.device();
+        let texture = gpu.create_texture(
+            width,
+            height,
+            1,
+            FORMAT,
+            vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSFER_SRC,
+        )?;
+        let view = texture.create_view(device, FORMAT, vk::ImageAspectFlags::COLOR, 1)?;
+
+        let attachment = vk::AttachmentDescription::builder()
+            .format(FORMAT)
+            .samples(vk::SampleCountFlags::_1)
+            .load_op(vk::AttachmentLoadOp::CLEAR)
+            .store_op(vk::AttachmentStoreOp::STORE)
+            .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .final_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL);
+        let color_ref = vk::AttachmentReference::builder()
+            .attachment(0)
+            .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+        let color_refs = &[color_ref];
+        let subpass = vk::SubpassDescription::builder()
+            .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+            .color_attachments(color_refs);
+
+        // the copy after the pass waits for the color writes
+        let dependency = vk::SubpassDependency::builder()
+            .src_subpass(0)
+            .dst_subpass(vk::SUBPASS_EXTERNAL)
+            .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+            .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+            .dst_stage_mask(vk::PipelineStageFlags::TRANSFER)
+            .dst_access_mask(vk::AccessFlags::TRANSFER_READ);
+        let attachments = &[attachment];
+        let subpasses = &[subpass];
+        let dependencies = &[dependency];
+        let info = vk::RenderPassCreateInfo::builder()
+            .attachments(attachments)
+            .subpasses(subpasses)
+            .dependencies(dependencies);
+        let render_pass = device.create_render_pass(&info, None)?;
+
+        let views = &[view.view];
+        let info = vk::FramebufferCreateInfo::builder()
+            .render_pass(render_pass)
+            .attachments(views)
+            .width(width)
+            .height(height)
+            .layers(1);
+        let framebuffer = device.create_framebuffer(&info, None)?;
+
+        Ok(Self {
+            width,
+            height,
+            texture,
+            view,
+            render_pass,
+            framebuffer,
+        })
+    }
+
+    // clear the target and record `draw` inside its pass with a full viewport
+    unsafe fn record<F>(&self, device: &Device, command_buffer: vk::CommandBuffer, draw: F)
+    where
+        F: FnOnce(&Device, vk::CommandBuffer),
+    {
+        let extent = vk::Extent2D {
+            width: self.width,
+            height: self.height,
+        };
+        let area = vk::Rect2D::builder().extent(extent).build();
+        let clear_values = &[vk::ClearValue {
+            color: vk::ClearColorValue { float32: CLEAR },
+        }];
+        let info = vk::RenderPassBeginInfo::builder()
+            .render_pass(self.render_pass)
+            .framebuffer(self.framebuffer)
+            .render_area(area)
+            .clear_values(clear_values);
+        device.cmd_begin_render_pass(command_buffer, &info, vk::SubpassContents::INLINE);
+
+        let viewport = vk::Viewport::builder()
+            .width(self.width as f32)
+            .height(self.height as f32)
+            .max_depth(1.0);
+        device.cmd_set_viewport(command_buffer, 0, &[viewport]);
+        device.cmd_set_scissor(command_buffer, 0, &[area]);
+        draw(device, command_buffer);
+
+        device.cmd_end_render_pass(command_buffer);
+    }
+
+    // copy the pixels of the target back, rows are tightly packed rgba8
+    unsafe fn read(&self, gpu: &HeadlessDevice) -> Result<Vec<u8>> {
+        let size = (self.width * self.height * 4) as usize;
+        let readback = host_buffer(gpu, size, vk::BufferUsageFlags::TRANSFER_DST)?;
+        gpu.execute(|device, command_buffer| {
+            let layers = vk::ImageSubresourceLayers::builder()
+                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                .layer_count(1);
+            let region = vk::BufferImageCopy::builder()
+                .image_subresource(layers)
+                .image_extent(vk::Extent3D {
+                    width: self.width,
+                    height: self.height,
+                    depth: 1,
+                });
+            device.cmd_copy_image_to_buffer(
+                command_buffer,
+                self.texture.image,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                readback.buffer,
+                &[region],
+            );
+            Ok(())
+        })?;
+        let pixels = readback.read(gpu.device(), 0, size);
+        readback.destroy(gpu.device());
+        pixels
+    }
+
+    fn pixel<'a>(&self, pixels: &'a [u8], x: u32, y: u32) -> &'a [u8] {
+        let start = ((y * self.width + x) * 4) as usize;
+        &pixels[start..start + 4]
+    }
+
+    unsafe fn destroy(&self, device: &Device) {
+        device.destroy_framebuffer(self.framebuffer, None);
+        device.destroy_render_pass(self.render_pass, None);
+        self.view.destroy(device);
+        self.texture.destroy(device);
+    }
+}
+
+// a pipeline without vertex input that draws into a `Target`
+unsafe fn create_pipeline(
+    device: &Device,
+    render_pass: vk::RenderPass,
+    layout: vk::PipelineLayout,
+    vert: &[u8],
+    frag: &[u8],
+) -> Result<vk::Pipeline> {
+    let vert = Shader::create(device, vert)?;
+    let frag = Shader::create(device, frag)?;
+    let stages = &[
+        vk::PipelineShaderStageCreateInfo::builder()
+            .stage(vk::ShaderStageFlags::VERTEX)
+            .module(vert.module)
+            .name(b"main\0"),
+        vk::PipelineShaderStageCreateInfo::builder()
+            .stage(vk::ShaderStageFlags::FRAGMENT)
+            .module(frag.module)
+            .name(b"main\0"),
+    ];
+
+    let vertex_input = vk::PipelineVertexInputStateCreateInfo::builder();
+    let input_assembly = vk::PipelineInputAssemblyStateCreateInfo::builder()
+        .topology(vk::PrimitiveTopology::TRIANGLE_LIST);
+    let viewport = vk::PipelineViewportStateCreateInfo::builder()
+        .viewport_count(1)
+        .scissor_count(1);
+    let rasterization = vk::PipelineRasterizationStateCreateInfo::builder()
+        .polygon_mode(vk::PolygonMode::FILL)
+        .line_width(1.0)
+        .cull_mode(vk::CullModeFlags::NONE);
+    let multisample = vk::PipelineMultisampleStateCreateInfo::builder()
+        .rasterization_samples(vk::SampleCountFlags::_1);
+    let blend_attachment = vk::PipelineColorBlendAttachmentState::builder()
+        .color_write_mask(vk::ColorComponentFlags::all());
+    let blend_attachments = &[blend_attachment];
+    let blend = vk::PipelineColorBlendStateCreateInfo::builder().attachments(blend_attachments);
+    let dynamic_states = &[vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+    let dynamic = vk::PipelineDynamicStateCreateInfo::builder().dynamic_states(dynamic_states);
+
+    let info = vk::GraphicsPipelineCreateInfo::builder()
+        .stages(stages)
+        .vertex_input_state(&vertex_input)
+        .input_assembly_state(&input_assembly)
+        .viewport_state(&viewport)
+        .rasterization_state(&rasterization)
+        .multisample_state(&multisample)
+        .color_blend_state(&blend)
+        .dynamic_state(&dynamic)
+        .layout(layout)
+        .render_pass(render_pass)
+        .subpass(0);
+    let pipeline = device
+        .create_graphics_pipelines(vk::PipelineCache::null(), &[info], None)?
+        .0[0];
+
+    vert.destroy(device);
+    frag.destroy(device);
+    Ok(pipeline)
+}
+
+// render the solid triangle into a new target of `width` by `height` and read it back
+unsafe fn render_triangle(gpu: &HeadlessDevice, width: u32, height: u32) -> Result<Vec<u8>> {
+    let device = gpu.device();
+    let target = Target::create(gpu, width, height)?;
+    let layout = device.create_pipeline_layout(&vk::PipelineLayoutCreateInfo::builder(), None)?;
+    let pipeline = create_pipeline(
+        device,
+        target.render_pass,
+        layout,
+        TRIANGLE_VERT,
+        SOLID_FRAG,
+    )?;
+
+    gpu.execute(|device, command_buffer| {
+        target.record(device, command_buffer, |device, command_buffer| {
+            device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
+            device.cmd_draw(command_buffer, 3, 1, 0, 0);
+        });
+        Ok(())
+    })?;
+    let pixels = target.read(gpu)?;
+
+    // the middle is covered, the corners only cleared
+    let red = [255, 0, 0, 255];
+    let blue = [0, 0, 255, 255];
+    assert_eq!(target.pixel(&pixels, width / 2, height / 2), red);
+    assert_eq!(target.pixel(&pixels, 0, 0), blue);
+    assert_eq!(target.pixel(&pixels, width - 1, height - 1), blue);
+
+    device.destroy_pipeline(pipeline, None);
+    device.destroy_pipeline_layout(layout, None);
+    target.destroy(device);
+    Ok(pixels)
+}
+
+#[test]
+fn buffer_roundtrips_through_device_memory() -> Result<()> {
+    let Some(gpu) = gpu() else {
+        return Ok(());
+    };
+    unsafe {
+        let values = (0..1024u32)
+            .map(|i| i.wrapping_mul(2654435761))
+            .collect::<Vec<_>>();
+        let size = values.len() * size_of::<u32>();
+
+        // host -> device local -> host
+        let upload = host_buffer(&gpu, size, vk::BufferUsageFlags::TRANSFER_SRC)?;
+        upload.write(gpu.device(), 0, &values)?;
+        let local = gpu.create_buffer(
+            size as vk::DeviceSize,
+            vk::BufferUsageFlags::TRANSFER_SRC | vk::BufferUsageFlags::TRANSFER_DST,
+            vk::MemoryPropertyFlags::DEVICE_LOCAL,
+        )?;
+        let download = host_buffer(&gpu, size, vk::BufferUsageFlags::TRANSFER_DST)?;
+        gpu.execute(|device, command_buffer| {
+            let region = vk::BufferCopy::builder().size(size as vk::DeviceSize);
+            device.cmd_copy_buffer(command_buffer, upload.buffer, local.buffer, &[region]);
+            let barrier = vk::MemoryBarrier::builder()
+                .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                .dst_access_mask(vk::AccessFlags::TRANSFER_READ);
+            device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[barrier],
+                &[] as &[vk::BufferMemoryBarrier],
+                &[] as &[vk::ImageMemoryBarrier],
+            );
+            device.cmd_copy_buffer(command_buffer, local.buffer, download.buffer, &[region]);
+            Ok(())
+        })?;
+        assert_eq!(download.read::<u32>(gpu.device(), 0, values.len())?, values);
+
+        // reads at an offset see the same data
+        let tail = download.read::<u32>(gpu.device(), 512 * 4, 512)?;
+        assert_eq!(tail, values[512..]);
+
+        upload.destroy(gpu.device());
+        local.destroy(gpu.device());
+        download.destroy(gpu.device());
+    }
+    assert_eq!(gpu.validation_errors(), 0);
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn texture_is_uploaded_sampled_and_read_back() -> Result<()> {
+    let Some(gpu) = gpu() else {
+        return Ok(());
+    };
+    unsafe {
+        let device = gpu.device();
+        let (width, height) = (16, 8);
+
+        // a checker of distinct opaque texels, copy.frag writes alpha one
+        let pixels = (0..width * height)
+            .flat_map(|i| {
+                let (x, y) = (i % width, i / width);
+                [
+                    (x * 16) as u8,
+                    (y * 32) as u8,
+                    if (x + y) % 2 == 0 { 255 } else { 0 },
+                    255,
+                ]
+            })
+            .collect::<Vec<_>>();
+        let (texture, view) = gpu.upload_texture(width, height, &pixels, TextureImport::Data)?;
+
+        // nearest sampling of level 0 reproduces the texels exactly
+        let info = vk::SamplerCreateInfo::builder()
+            .mag_filter(vk::Filter::NEAREST)
+            .min_filter(vk::Filter::NEAREST)
+            .mipmap_mode(vk::SamplerMipmapMode::NEAREST)
+            .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .max_lod(0.0);
+        let sampler = device.create_sampler(&info, None)?;
+
+        // copy.frag reads its texture and sampler from set 1
+        let bindings = &[
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(0)
+                .descriptor_type(vk::DescriptorType::SAMPLED_IMAGE)
+                .descriptor_count(1)
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT),
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(3)
+                .descriptor_type(vk::DescriptorType::SAMPLER)
+                .descriptor_count(1)
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT),
+        ];
+        let info = vk::DescriptorSetLayoutCreateInfo::builder().bindings(bindings);
+        let set_layout = device.create_descriptor_set_layout(&info, None)?;
+        let empty_layout = device
+            .create_descriptor_set_layout(&vk::DescriptorSetLayoutCreateInfo::builder(), None)?;
+        let set_layouts = &[empty_layout, set_layout];
+        let info = vk::PipelineLayoutCreateInfo::builder().set_layouts(set_layouts);
+        let layout = device.create_pipeline_layout(&info, None)?;
+
+        let sizes = &[
+            vk::DescriptorPoolSize::builder()
+                .type_(vk::DescriptorType::SAMPLED_IMAGE)
+                .descriptor_count(1),
+            vk::DescriptorPoolSize::builder()
+                .type_(vk::DescriptorType::SAMPLER)
+                .descriptor_count(1),
+        ];
+        let info = vk::DescriptorPoolCreateInfo::builder()
+            .pool_sizes(sizes)
+            .max_sets(1);
+        let pool = device.create_descriptor_pool(&info, None)?;
+        let layouts = &[set_layout];
+        let info = vk::DescriptorSetAllocateInfo::builder()
+            .descriptor_pool(pool)
+            .set_layouts(layouts);
+        let set = device.allocate_descriptor_sets(&info)?[0];
+
+        let image_info = &[vk::DescriptorImageInfo::builder()
+            .image_view(view.view)
+            .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)];
+        let sampler_info = &[vk::DescriptorImageInfo::builder().sampler(sampler)];
+        let writes = &[
+            vk::WriteDescriptorSet::builder()
+                .dst_set(set)
+                .dst_binding(0)
+                .descriptor_type(vk::DescriptorType::SAMPLED_IMAGE)
+                .image_info(image_info),
+            vk::WriteDescriptorSet::builder()
+                .dst_set(set)
+                .dst_binding(3)
+                .descriptor_type(vk::DescriptorType::SAMPLER)
+                .image_info(sampler_info),
+        ];
+        device.update_descriptor_sets(writes, &[] as &[vk::CopyDescriptorSet]);
+
+        // draw the texture over a target of the same size
+        let target = Target::create(&gpu, width, height)?;
+        let pipeline = create_pipeline(
+            device,
+            target.render_pass,
+            layout,
+            FULLSCREEN_VERT,
+            COPY_FRAG,
+        )?;
+        gpu.execute(|device, command_buffer| {
+            target.record(device, command_buffer, |device, command_buffer| {
+                device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
+                device.cmd_bind_descriptor_sets(
+                    command_buffer,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    layout,
+                    1,
+                    &[set],
+                    &[],
+                );
+                device.cmd_draw(command_buffer, 3, 1, 0, 0);
+            });
+            Ok(())
+        })?;
+        assert_eq!(target.read(&gpu)?, pixels);
+
+        device.destroy_pipeline(pipeline, None);
+        device.destroy_pipeline_layout(layout, None);
+        device.destroy_descriptor_pool(pool, None);
+        device.destroy_descriptor_set_layout(set_layout, None);
+        device.destroy_descriptor_set_layout(empty_layout, None);
+        device.destroy_sampler(sampler, None);
+        target.destroy(device);
+        view.destroy(device);
+        texture.destroy(device);
+    }
+    assert_eq!(gpu.validation_errors(), 0);
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn texture_upload_rejects_wrong_pixel_count() {
+    let Some(gpu) = gpu() else {
+        return;
+    };
+    assert!(gpu
+        .upload_texture(4, 4, &[0; 4 * 4 * 4 - 1], TextureImport::Color)
+        .is_err());
+    gpu.destroy();
+}
+
+#[test]
+fn triangle_renders_the_same_pixels_every_time() -> Result<()> {
+    let Some(gpu) = gpu() else {
+        return Ok(());
+    };
+    unsafe {
+        let first = render_triangle(&gpu, 64, 64)?;
+        let second = render_triangle(&gpu, 64, 64)?;
+        assert_eq!(hash(&first), hash(&second));
+    }
+    assert_eq!(gpu.validation_errors(), 0);
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn targets_survive_a_resize_loop() -> Result<()> {
+    let Some(gpu) = gpu() else {
+        return Ok(());
+    };
+    unsafe {
+        // like window resizes, every size gets new attachments and the old ones go away
+        for step in 0..48u32 {
+            let width = 16 + (step * 37) % 240;
+            let height = 16 + (step * 53) % 180;
+            let pixels = render_triangle(&gpu, width, height)?;
+            assert_eq!(pixels.len(), (width * height * 4) as usize);
+        }
+    }
+    assert_eq!(gpu.validation_errors(), 0);
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn compute_runs_without_a_swapchain() -> Result<()> {
+    let Some(gpu) = gpu() else {
+        return Ok(());
+    };
+    unsafe {
+        let device = gpu.device();
+        let values = (0..1000u32).collect::<Vec<_>>();
+        let size = values.len() * size_of::<u32>();
+        let buffer = host_buffer(&gpu, size, vk::BufferUsageFlags::STORAGE_BUFFER)?;
+        buffer.write(device, 0, &values)?;
+
+        let bindings = &[vk::DescriptorSetLayoutBinding::builder()
+            .binding(0)
+            .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+            .descriptor_count(1)
+            .stage_flags(vk::ShaderStageFlags::COMPUTE)];
+        let info = vk::DescriptorSetLayoutCreateInfo::builder().bindings(bindings);
+        let set_layout = device.create_descriptor_set_layout(&info, None)?;
+        let ranges = &[vk::PushConstantRange::builder()
+            .stage_flags(vk::ShaderStageFlags::COMPUTE)
+            .size(4)];
+        let set_layouts = &[set_layout];
+        let info = vk::PipelineLayoutCreateInfo::builder()
+            .set_layouts(set_layouts)
+            .push_constant_ranges(ranges);
+        let layout = device.create_pipeline_layout(&info, None)?;
+
+        let shader = Shader::create(device, DOUBLE_COMP)?;
+        let stage = vk::PipelineShaderStageCreateInfo::builder()
+            .stage(vk::ShaderStageFlags::COMPUTE)
+            .module(shader.module)
+            .name(b"main\0");
+        let info = vk::ComputePipelineCreateInfo::builder()
+            .stage(stage)
+            .layout(layout);
+        let pipeline = device
+            .create_compute_pipelines(vk::PipelineCache::null(), &[info], None)?
+            .0[0];
+        shader.destroy(device);
+
+        let sizes = &[vk::DescriptorPoolSize::builder()
+            .type_(vk::DescriptorType::STORAGE_BUFFER)
+            .descriptor_count(1)];
+        let info = vk::DescriptorPoolCreateInfo::builder()
+            .pool_sizes(sizes)
+            .max_sets(1);
+        let pool = device.create_descriptor_pool(&info, None)?;
+        let info = vk::DescriptorSetAllocateInfo::builder()
+            .descriptor_pool(pool)
+            .set_layouts(set_layouts);
+        let set = device.allocate_descriptor_sets(&info)?[0];
+        let buffer_info = &[vk::DescriptorBufferInfo::builder()
+            .buffer(buffer.buffer)
+            .range(vk::WHOLE_SIZE as vk::DeviceSize)];
+        let write = vk::WriteDescriptorSet::builder()
+            .dst_set(set)
+            .dst_binding(0)
+            .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+            .buffer_info(buffer_info);
+        device.update_descriptor_sets(&[write], &[] as &[vk::CopyDescriptorSet]);
+
+        // two dispatches double every value twice, the barrier orders them
+        gpu.execute(|device, command_buffer| {
+            let count = values.len() as u32;
+            device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::COMPUTE, pipeline);
+            device.cmd_bind_descriptor_sets(
+                command_buffer,
+                vk::PipelineBindPoint::COMPUTE,
+                layout,
+                0,
+                &[set],
+                &[],
+            );
+            device.cmd_push_constants(
+                command_buffer,
+                layout,
+                vk::ShaderStageFlags::COMPUTE,
+                0,
+                &count.to_ne_bytes(),
+            );
+            for pass in 0..2 {
+                if pass > 0 {
+                    let barrier = vk::MemoryBarrier::builder()
+                        .src_access_mask(vk::AccessFlags::SHADER_WRITE)
+                        .dst_access_mask(
+                            vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE,
+                        );
+                    device.cmd_pipeline_barrier(
+                        command_buffer,
+                        vk::PipelineStageFlags::COMPUTE_SHADER,
+                        vk::PipelineStageFlags::COMPUTE_SHADER,
+                        vk::DependencyFlags::empty(),
+                        &[barrier],
+                        &[] as &[vk::BufferMemoryBarrier],
+                        &[] as &[vk::ImageMemoryBarrier],
+                    );
+                }
+                device.cmd_dispatch(command_buffer, count.div_ceil(64), 1, 1);
+            }
+
+            // the host reads the results after the submission
+            let barrier = vk::MemoryBarrier::builder()
+                .src_access_mask(vk::AccessFlags::SHADER_WRITE)
+                .dst_access_mask(vk::AccessFlags::HOST_READ);
+            device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::PipelineStageFlags::HOST,
+                vk::DependencyFlags::empty(),
+                &[barrier],
+                &[] as &[vk::BufferMemoryBarrier],
+                &[] as &[vk::ImageMemoryBarrier],
+            );
+            Ok(())
+        })?;
+
+        let expected = values.iter().map(|v| v * 4).collect::<Vec<_>>();
+        assert_eq!(buffer.read::<u32>(device, 0, values.len())?, expected);
+
+        device.destroy_pipeline(pipeline, None);
+        device.destroy_pipeline_layout(layout, None);
+        device.destroy_descriptor_pool(pool, None);
+        device.destroy_descriptor_set_layout(set_layout, None);
+        buffer.destroy(device);
+    }
+    assert_eq!(gpu.validation_errors(), 0);
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn timelines_order_submissions_on_every_backing() -> Result<()> {
+    let Some(gpu) = gpu() else {
+        return Ok(());
+    };
+    // fences everywhere, then semaphores where supported, also mixed
+    let mut backings = vec![(false, false)];
+    if gpu.supports_timeline_semaphores() {
+        backings.extend([(true, true), (true, false), (false, true)]);
+    }
+
+    unsafe {
+        let device = gpu.device();
+        let pool = gfx::CommandPool::create(
+            device,
+            gpu.queue_family(),
+            vk::CommandPoolCreateFlags::TRANSIENT,
+        )?;
+        let fill = |offset: usize, value: u32, buffer: vk::Buffer| -> Result<vk::CommandBuffer> {
+            let command_buffer = pool.allocate(device, vk::CommandBufferLevel::PRIMARY, 1)?[0];
+            let info = vk::CommandBufferBeginInfo::builder()
+                .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+            device.begin_command_buffer(command_buffer.buffer, &info)?;
+            let offset = (offset * size_of::<u32>()) as vk::DeviceSize;
+            device.cmd_fill_buffer(command_buffer.buffer, buffer, offset, 4, value);
+            device.end_command_buffer(command_buffer.buffer)?;
+            Ok(command_buffer.buffer)
+        };
+
+        for (first_semaphore, second_semaphore) in backings {
+            let first = gfx::Timeline::create(device, first_semaphore)?;
+            let second = gfx::Timeline::create(device, second_semaphore)?;
+            let buffer = host_buffer(&gpu, 16, vk::BufferUsageFlags::TRANSFER_DST)?;
+            buffer.write(device, 0, &[0u32; 4])?;
+
+            // each submission signals the next value
+            for value in 1..=3u32 {
+                let command_buffer = fill(value as usize, value, buffer.buffer)?;
+                let submitted =
+                    first.submit(device, gpu.queue(), &[command_buffer], &[], &[], &[])?;
+                assert_eq!(submitted, value as u64);
+            }
+            assert_eq!(first.submitted(), 3);
+
+            // the other timeline waits for all of them
+            let transfer = vk::PipelineStageFlags::TRANSFER;
+            let waits = first
+                .gpu_wait(device, 3, transfer)?
+                .into_iter()
+                .collect::<Vec<_>>();
+            let command_buffer = fill(0, 42, buffer.buffer)?;
+            let value = second.submit(device, gpu.queue(), &[command_buffer], &[], &waits, &[])?;
+            second.wait(device, value)?;
+            first.wait(device, 3)?;
+
+            assert_eq!(first.completed(device)?, 3);
+            assert_eq!(second.completed(device)?, 1);
+            assert_eq!(buffer.read::<u32>(device, 0, 4)?, [42, 1, 2, 3]);
+            assert!(first.wait(device, 4).is_err());
+
+            first.destroy(device);
+            second.destroy(device);
+            buffer.destroy(device);
+        }
+        pool.destroy(device);
+    }
+    assert_eq!(gpu.validation_errors(), 0);
+    gpu.destroy();
+    Ok(())
+}
@@ -0,0 +1,28 @@
+// SPDX-License-Identifier: MIT
+
+//! The sizes the compute image passes work with. recording them needs a windowed device
+//! and is left to the demo.
+
+use deimos::rendering::{dispatch_size, downsampled_extent, IMAGE_GROUP_SIZE};
+use vulkanalia::prelude::v1_0::*;
+
+fn extent(width: u32, height: u32) -> vk::Extent2D {
+    vk::Extent2D { width, height }
+}
+
+#[test]
+fn dispatches_cover_partial_groups() {
+    assert_eq!(dispatch_size(extent(64, 32), IMAGE_GROUP_SIZE), (8, 4));
+    assert_eq!(dispatch_size(extent(65, 33), IMAGE_GROUP_SIZE), (9, 5));
+    assert_eq!(dispatch_size(extent(1, 1), IMAGE_GROUP_SIZE), (1, 1));
+    assert_eq!(dispatch_size(extent(0, 7), IMAGE_GROUP_SIZE), (0, 1));
+    assert_eq!(dispatch_size(extent(1920, 1080), 16), (120, 68));
+}
+
+#[test]
+fn downsamples_round_up_to_a_texel() {
+    assert_eq!(downsampled_extent(extent(64, 32)), extent(32, 16));
+    assert_eq!(downsampled_extent(extent(65, 33)), extent(33, 17));
+    assert_eq!(downsampled_extent(extent(1, 1)), extent(1, 1));
+    assert_eq!(downsampled_extent(extent(2, 1)), extent(1, 1));
+}
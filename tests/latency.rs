@@ -0,0 +1,68 @@
+// SPDX-License-Identifier: MIT
+
+//! Frame latencies from display timing or completion estimates, and the refreshes frames
+//! missed.
+
+use std::time::Duration;
+
+use deimos::gfx::{LatencySource, LatencyTracker};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn estimated_latency_counts_missed_refreshes() {
+    let mut tracker = LatencyTracker::new(8);
+    tracker.set_refresh(Some(ms(10)));
+
+    // frames 0 and 1 complete a refresh apart, frame 2 two refreshes late
+    for (id, begun, completed) in [(0, 0, 15), (1, 10, 25), (2, 20, 50)] {
+        tracker.begin(id, ms(begun));
+        tracker.presented(id, ms(begun + 2));
+        let latency = tracker.completed(id, ms(completed)).unwrap();
+        assert_eq!(latency.source, LatencySource::Estimated);
+        assert_eq!(latency.latency, ms(completed - begun));
+    }
+
+    let stats = tracker.stats();
+    assert_eq!(stats.frames, 3);
+    assert_eq!(stats.missed_vsyncs, 2);
+    assert_eq!(stats.last.unwrap().missed_vsyncs, 2);
+    assert_eq!(stats.max, ms(30));
+    assert_eq!(stats.average, ms(20));
+
+    // a pause isn't a miss
+    tracker.pause();
+    tracker.begin(3, ms(1000));
+    let latency = tracker.completed(3, ms(1005)).unwrap();
+    assert_eq!(latency.missed_vsyncs, 0);
+}
+
+#[test]
+fn display_times_are_aligned_to_presents() {
+    let mut tracker = LatencyTracker::new(8);
+    let display = 5_000_000_000u64;
+
+    // the engine clock runs 5s ahead, frame 0 could have been shown right when presented
+    tracker.begin(0, ms(0));
+    tracker.presented(0, ms(4));
+    let first = tracker
+        .displayed(0, display + 12_000_000, display + 4_000_000)
+        .unwrap();
+    assert_eq!(first.source, LatencySource::Display);
+    assert_eq!(first.latency, ms(12));
+
+    // frames the engine skipped reporting are dropped, unknown frames ignored
+    tracker.begin(1, ms(16));
+    tracker.presented(1, ms(20));
+    tracker.begin(2, ms(32));
+    tracker.presented(2, ms(36));
+    assert!(tracker.displayed(7, display, display).is_none());
+    let third = tracker
+        .displayed(2, display + 45_000_000, display + 38_000_000)
+        .unwrap();
+    assert_eq!(third.latency, ms(13));
+    assert_eq!(tracker.stats().frames, 2);
+    assert!(tracker.completed(1, ms(50)).is_none());
+}
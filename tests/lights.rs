@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: MIT
+
+//! The lights the pbr shaders evaluate when a scene has more than fit, directional lights
+//! first and then the lights nearest to the camera.
+
+use cgmath::{vec3, Deg};
+use deimos::rendering::{select_lights, Light, MAX_LIGHTS};
+
+fn point(x: f32, intensity: f32) -> Light {
+    Light::Point {
+        position: vec3(x, 0.0, 0.0),
+        color: vec3(1.0, 1.0, 1.0),
+        intensity,
+        range: 0.0,
+    }
+}
+
+#[test]
+fn few_lights_are_kept_as_given() {
+    let lights = vec![point(3.0, 1.0), point(1.0, 1.0)];
+    assert_eq!(select_lights(&lights, vec3(0.0, 0.0, 0.0), 4), lights);
+    assert!(select_lights(&lights, vec3(0.0, 0.0, 0.0), 0).is_empty());
+}
+
+#[test]
+fn directional_and_near_lights_win() {
+    let sun = Light::Directional {
+        direction: vec3(0.0, -1.0, 0.0),
+        color: vec3(1.0, 1.0, 1.0),
+        intensity: 0.1,
+    };
+    let spot = Light::Spot {
+        position: vec3(2.0, 0.0, 0.0),
+        direction: vec3(-1.0, 0.0, 0.0),
+        color: vec3(1.0, 1.0, 1.0),
+        intensity: 1.0,
+        range: 0.0,
+        inner_angle: Deg(10.0).into(),
+        outer_angle: Deg(20.0).into(),
+    };
+    let lights = vec![
+        point(10.0, 1.0),
+        spot,
+        point(1.0, 1.0),
+        sun,
+        point(5.0, 100.0),
+    ];
+
+    // the far point light is the least important, the others keep their order
+    assert_eq!(
+        select_lights(&lights, vec3(0.0, 0.0, 0.0), 4),
+        vec![spot, point(1.0, 1.0), sun, point(5.0, 100.0)]
+    );
+    assert_eq!(
+        select_lights(&lights, vec3(0.0, 0.0, 0.0), 2),
+        vec![sun, point(5.0, 100.0)]
+    );
+}
+
+#[test]
+fn the_maximum_is_clamped_to_what_shaders_evaluate() {
+    let lights = (0..MAX_LIGHTS * 2)
+        .map(|i| point(i as f32, 1.0))
+        .collect::<Vec<_>>();
+    let selected = select_lights(&lights, vec3(0.0, 0.0, 0.0), usize::MAX);
+    assert_eq!(selected, lights[..MAX_LIGHTS]);
+}
@@ -0,0 +1,2542 @@
+// SPDX-License-Identifier: MIT
+
+//! Tests on the null driver, they need no vulkan driver and run everywhere. the driver
+//! records what the device does and refuses invalid calls, the pixels are not rendered.
+
+use std::future::Future;
+use std::mem::size_of;
+use std::pin::pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+use anyhow::Result;
+use cgmath::SquareMatrix;
+use deimos::gfx::{
+    self, buffer_barrier, check_render_pass, pod_bytes, slot_set_layouts, BindingResource, Blend,
+    BufferAccess, ColorAttachment, CommandError, CommandPool, DeletionQueue, DepthAttachment,
+    DescriptionError, DescriptorSets, DescriptorSlot, Encoder, Feature, GpuFuture, HeadlessDevice,
+    HeadlessWindow, IndexBinding, LoadOp, MappedBuffer, NullBackend, PipelineTarget, QueryKind,
+    QueryResolve, RenderPassDescriptor, RenderPipelineBuilder, ResourceRegistry, ResourceUsage,
+    SamplerDescriptor, SecondaryPool, Shader, StagingBelt, SubmitGraph, Subpass, TextureImport,
+    TexturePixels, TraceCommand,
+};
+use deimos::math::{Mat4, Vec2, Vec3, Vec4};
+use deimos::rendering::{AssetKey, Assets, Camera, Material, Mesh, Renderer};
+use vulkanalia::prelude::v1_0::*;
+
+const TRIANGLE_VERT: &[u8] = include_bytes!("shaders/triangle_vert.spv");
+const SOLID_FRAG: &[u8] = include_bytes!("shaders/solid_frag.spv");
+
+const FORMAT: vk::Format = vk::Format::R8G8B8A8_UNORM;
+
+fn null() -> Result<(NullBackend, HeadlessDevice)> {
+    let backend = NullBackend::new();
+    let gpu = HeadlessDevice::create_null(&backend)?;
+    Ok((backend, gpu))
+}
+
+// a device presenting to a headless window on its own null driver
+fn windowed(width: u32, height: u32) -> Result<(NullBackend, HeadlessWindow, gfx::Device)> {
+    let backend = NullBackend::new();
+    let window = HeadlessWindow::new(width, height);
+    let device = gfx::Device::create_null(&backend, &window)?;
+    Ok((backend, window, device))
+}
+
+// a frame that only clears the swapchain image
+fn present(device: &mut gfx::Device, window: &HeadlessWindow) -> Result<()> {
+    present_after(device, window, |_, _| Ok(()))
+}
+
+// a frame recording `prepare` before it clears the swapchain image
+fn present_after(
+    device: &mut gfx::Device,
+    window: &HeadlessWindow,
+    prepare: impl FnOnce(&gfx::Device, vk::CommandBuffer) -> Result<()>,
+) -> Result<()> {
+    device.update_with_prepare(
+        window,
+        |device, command_buffer, _| prepare(device, command_buffer),
+        |_, _, _| Ok(()),
+        |device, command_buffer, index| {
+            device.begin_present_pass(command_buffer, index);
+            device.end_present_pass(command_buffer);
+            Ok(())
+        },
+    )
+}
+
+fn host_buffer(
+    gpu: &HeadlessDevice,
+    size: usize,
+    usage: vk::BufferUsageFlags,
+) -> Result<deimos::gfx::Buffer> {
+    gpu.create_buffer(
+        size as vk::DeviceSize,
+        usage,
+        vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+    )
+}
+
+// a single color attachment pass with its framebuffer over a new texture
+unsafe fn create_target(
+    gpu: &HeadlessDevice,
+    width: u32,
+    height: u32,
+) -> Result<(
+    deimos::gfx::Texture,
+    deimos::gfx::TextureView,
+    vk::RenderPass,
+    vk::Framebuffer,
+)> {
+    let device = gpu.device();
+    let texture = gpu.create_texture(
+        width,
+        height,
+        1,
+        FORMAT,
+        vk::ImageUsageFlags::COLOR_ATTACHMENT,
+    )?;
+    let view = texture.create_view(device, FORMAT, vk::ImageAspectFlags::COLOR, 1)?;
+
+    let attachment = vk::AttachmentDescription::builder()
+        .format(FORMAT)
+        .samples(vk::SampleCountFlags::_1)
+        .load_op(vk::AttachmentLoadOp::CLEAR)
+        .store_op(vk::AttachmentStoreOp::STORE)
+        .initial_layout(vk::ImageLayout::UNDEFINED)
+        .final_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+    let color_ref = vk::AttachmentReference::builder()
+        .attachment(0)
+        .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+    let color_refs = &[color_ref];
+    let subpass = vk::SubpassDescription::builder()
+        .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+        .color_attachments(color_refs);
+    let attachments = &[attachment];
+    let subpasses = &[subpass];
+    let info = vk::RenderPassCreateInfo::builder()
+        .attachments(attachments)
+        .subpasses(subpasses);
+    let render_pass = device.create_render_pass(&info, None)?;
+
+    let views = &[view.view];
+    let info = vk::FramebufferCreateInfo::builder()
+        .render_pass(render_pass)
+        .attachments(views)
+        .width(width)
+        .height(height)
+        .layers(1);
+    let framebuffer = device.create_framebuffer(&info, None)?;
+    Ok((texture, view, render_pass, framebuffer))
+}
+
+// wakes the blocked thread, the least an executor does
+struct Unpark(Thread);
+
+impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(Unpark(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+        thread::park();
+    }
+}
+
+unsafe fn create_pipeline(
+    device: &Device,
+    render_pass: vk::RenderPass,
+    layout: vk::PipelineLayout,
+) -> Result<vk::Pipeline> {
+    let vert = Shader::create(device, TRIANGLE_VERT)?;
+    let frag = Shader::create(device, SOLID_FRAG)?;
+    let stages = &[
+        vk::PipelineShaderStageCreateInfo::builder()
+            .stage(vk::ShaderStageFlags::VERTEX)
+            .module(vert.module)
+            .name(b"main\0"),
+        vk::PipelineShaderStageCreateInfo::builder()
+            .stage(vk::ShaderStageFlags::FRAGMENT)
+            .module(frag.module)
+            .name(b"main\0"),
+    ];
+    let vertex_input = vk::PipelineVertexInputStateCreateInfo::builder();
+    let input_assembly = vk::PipelineInputAssemblyStateCreateInfo::builder()
+        .topology(vk::PrimitiveTopology::TRIANGLE_LIST);
+    let viewport = vk::PipelineViewportStateCreateInfo::builder()
+        .viewport_count(1)
+        .scissor_count(1);
+    let rasterization = vk::PipelineRasterizationStateCreateInfo::builder()
+        .polygon_mode(vk::PolygonMode::FILL)
+        .line_width(1.0);
+    let multisample = vk::PipelineMultisampleStateCreateInfo::builder()
+        .rasterization_samples(vk::SampleCountFlags::_1);
+    let blend_attachment = vk::PipelineColorBlendAttachmentState::builder()
+        .color_write_mask(vk::ColorComponentFlags::all());
+    let blend_attachments = &[blend_attachment];
+    let blend = vk::PipelineColorBlendStateCreateInfo::builder().attachments(blend_attachments);
+    let info = vk::GraphicsPipelineCreateInfo::builder()
+        .stages(stages)
+        .vertex_input_state(&vertex_input)
+        .input_assembly_state(&input_assembly)
+        .viewport_state(&viewport)
+        .rasterization_state(&rasterization)
+        .multisample_state(&multisample)
+        .color_blend_state(&blend)
+        .layout(layout)
+        .render_pass(render_pass)
+        .subpass(0);
+    let pipeline = device
+        .create_graphics_pipelines(vk::PipelineCache::null(), &[info], None)?
+        .0[0];
+
+    vert.destroy(device);
+    frag.destroy(device);
+    Ok(pipeline)
+}
+
+#[test]
+fn triangle_pass_is_recorded() -> Result<()> {
+    let (backend, gpu) = null()?;
+    assert!(gpu.is_validated());
+    unsafe {
+        let device = gpu.device();
+        let (texture, view, render_pass, framebuffer) = create_target(&gpu, 64, 32)?;
+        let layout =
+            device.create_pipeline_layout(&vk::PipelineLayoutCreateInfo::builder(), None)?;
+        let pipeline = create_pipeline(device, render_pass, layout)?;
+
+        backend.clear();
+        gpu.execute(|device, command_buffer| {
+            let area = vk::Rect2D::builder()
+                .extent(vk::Extent2D {
+                    width: 64,
+                    height: 32,
+                })
+                .build();
+            let clear_values = &[vk::ClearValue {
+                color: vk::ClearColorValue {
+                    float32: [0.0, 0.0, 1.0, 1.0],
+                },
+            }];
+            let info = vk::RenderPassBeginInfo::builder()
+                .render_pass(render_pass)
+                .framebuffer(framebuffer)
+                .render_area(area)
+                .clear_values(clear_values);
+            device.cmd_begin_render_pass(command_buffer, &info, vk::SubpassContents::INLINE);
+            device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
+            let viewport = vk::Viewport::builder()
+                .width(64.0)
+                .height(32.0)
+                .max_depth(1.0);
+            device.cmd_set_viewport(command_buffer, 0, &[viewport]);
+            device.cmd_draw(command_buffer, 3, 1, 0, 0);
+            device.cmd_end_render_pass(command_buffer);
+            Ok(())
+        })?;
+
+        let submissions = backend.submissions();
+        assert_eq!(submissions.len(), 1);
+        let commands = &submissions[0].commands;
+        assert_eq!(commands.len(), 5);
+        assert!(matches!(
+            &commands[0],
+            TraceCommand::BeginRenderPass { render_pass: r, area, clear_values, .. }
+                if *r == render_pass && area.extent.width == 64 && clear_values.len() == 1
+        ));
+        assert!(
+            matches!(commands[1], TraceCommand::BindPipeline { pipeline: p, .. } if p == pipeline)
+        );
+        assert!(
+            matches!(&commands[2], TraceCommand::SetViewport { viewports, .. } if viewports[0].height == 32.0)
+        );
+        assert!(matches!(
+            commands[3],
+            TraceCommand::Draw {
+                vertex_count: 3,
+                instance_count: 1,
+                ..
+            }
+        ));
+        assert!(matches!(commands[4], TraceCommand::EndRenderPass));
+
+        device.destroy_pipeline(pipeline, None);
+        device.destroy_pipeline_layout(layout, None);
+        device.destroy_framebuffer(framebuffer, None);
+        device.destroy_render_pass(render_pass, None);
+        view.destroy(device);
+        texture.destroy(device);
+    }
+    assert_eq!(backend.errors(), Vec::<String>::new());
+    assert_eq!(gpu.validation_errors(), 0);
+    gpu.destroy();
+    assert!(backend.errors().is_empty());
+    Ok(())
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Instance {
+    placement: [f32; 4],
+    layer: u32,
+    flags: u32,
+}
+
+deimos::pod!(Instance {
+    placement: [f32; 4],
+    layer: u32,
+    flags: u32,
+});
+
+#[test]
+fn plain_structs_round_trip_through_buffers() -> Result<()> {
+    let (backend, gpu) = null()?;
+    let instances = [
+        Instance {
+            placement: [1.0, 2.0, 3.0, 0.5],
+            layer: 2,
+            flags: 0x8000_0001,
+        },
+        Instance {
+            placement: [-1.0; 4],
+            layer: 0,
+            flags: 0,
+        },
+    ];
+
+    // the bytes are the fields, without gaps
+    let bytes = pod_bytes(&instances);
+    assert_eq!(bytes.len(), 2 * 24);
+    assert_eq!(bytes[16..20], 2u32.to_ne_bytes());
+    unsafe {
+        let buffer = host_buffer(&gpu, bytes.len(), vk::BufferUsageFlags::STORAGE_BUFFER)?;
+        buffer.write(gpu.device(), 0, &instances)?;
+        assert_eq!(
+            buffer.read::<Instance>(gpu.device(), 0, 2)?,
+            instances.to_vec()
+        );
+        assert_eq!(buffer.read::<u8>(gpu.device(), 0, bytes.len())?, bytes);
+
+        // packed bytes land where they are written
+        buffer.write_bytes(gpu.device(), 16, &5u32.to_ne_bytes())?;
+        assert_eq!(buffer.read::<Instance>(gpu.device(), 0, 1)?[0].layer, 5);
+
+        // writes past the end are refused before anything is mapped
+        assert!(buffer.write_bytes(gpu.device(), 46, &[0; 4]).is_err());
+        assert!(buffer.write(gpu.device(), u64::MAX, &[0u8]).is_err());
+        buffer.destroy(gpu.device());
+    }
+    assert!(backend.errors().is_empty());
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn mapped_buffers_are_written_without_mapping_again() -> Result<()> {
+    let (backend, gpu) = null()?;
+    unsafe {
+        let buffer = host_buffer(&gpu, 16, vk::BufferUsageFlags::UNIFORM_BUFFER)?;
+        let mapped = MappedBuffer::map(gpu.device(), buffer)?;
+        assert_eq!(mapped.buffer(), buffer.buffer);
+        assert_eq!(mapped.size(), buffer.size);
+
+        // each write lands where it goes, the last one wins
+        mapped.write(0, &[1u32, 2, 3, 4])?;
+        mapped.write(8, &[7u32])?;
+        mapped.write_bytes(12, &9u32.to_ne_bytes())?;
+
+        // writes past the end are refused
+        assert!(mapped.write(12, &[0u32; 2]).is_err());
+        assert!(mapped.write_bytes(u64::MAX, &[0]).is_err());
+
+        let buffer = mapped.unmap(gpu.device());
+        assert_eq!(buffer.read::<u32>(gpu.device(), 0, 4)?, vec![1, 2, 7, 9]);
+        buffer.destroy(gpu.device());
+    }
+    assert!(backend.errors().is_empty());
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn buffer_copies_and_fills_run_on_submit() -> Result<()> {
+    let (backend, gpu) = null()?;
+    unsafe {
+        let values = (0..256u32).collect::<Vec<_>>();
+        let size = values.len() * size_of::<u32>();
+        let upload = host_buffer(&gpu, size, vk::BufferUsageFlags::TRANSFER_SRC)?;
+        upload.write(gpu.device(), 0, &values)?;
+        let download = host_buffer(&gpu, size, vk::BufferUsageFlags::TRANSFER_DST)?;
+
+        // copy the first half and fill the rest
+        gpu.execute(|device, command_buffer| {
+            let region = vk::BufferCopy::builder().size(size as vk::DeviceSize / 2);
+            device.cmd_copy_buffer(command_buffer, upload.buffer, download.buffer, &[region]);
+            device.cmd_fill_buffer(
+                command_buffer,
+                download.buffer,
+                size as vk::DeviceSize / 2,
+                vk::WHOLE_SIZE as vk::DeviceSize,
+                7,
+            );
+            Ok(())
+        })?;
+
+        let read = download.read::<u32>(gpu.device(), 0, values.len())?;
+        assert_eq!(read[..128], values[..128]);
+        assert!(read[128..].iter().all(|v| *v == 7));
+
+        upload.destroy(gpu.device());
+        download.destroy(gpu.device());
+    }
+    assert!(backend.errors().is_empty());
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn failed_recordings_free_their_command_buffer() -> Result<()> {
+    let (backend, gpu) = null()?;
+    let error = gpu
+        .execute(|_, _| Err(anyhow::anyhow!("Nothing to record.")))
+        .unwrap_err();
+    assert_eq!(error.to_string(), "Nothing to record.");
+    assert_eq!(backend.live_objects(vk::ObjectType::COMMAND_BUFFER), 0);
+    assert!(backend.submissions().is_empty());
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn encoders_clear_buffers_with_fills() -> Result<()> {
+    let (backend, gpu) = null()?;
+    unsafe {
+        let values = vec![9u32; 16];
+        let buffer = host_buffer(&gpu, 64, vk::BufferUsageFlags::TRANSFER_DST)?;
+        buffer.write(gpu.device(), 0, &values)?;
+
+        // a range, then everything from an offset on
+        let mut problems = vec![];
+        gpu.execute(|device, command_buffer| {
+            let mut encoder = Encoder::new(device, command_buffer);
+            encoder.clear_buffer(&buffer, 4, Some(8))?;
+            for (offset, size) in [(2, None), (0, Some(6)), (64, None), (32, Some(64))] {
+                if let Err(error) = encoder.clear_buffer(&buffer, offset, size) {
+                    problems.push(error.to_string());
+                }
+            }
+            encoder.clear_buffer(&buffer, 48, None)?;
+            Ok(())
+        })?;
+
+        let read = buffer.read::<u32>(gpu.device(), 0, values.len())?;
+        assert_eq!(read[..4], [9, 0, 0, 9]);
+        assert!(read[4..12].iter().all(|v| *v == 9));
+        assert!(read[12..].iter().all(|v| *v == 0));
+
+        // misaligned and out of range clears were refused before reaching the driver
+        assert_eq!(
+            problems,
+            [
+                "clear_buffer: the offset 2 is not a multiple of 4".to_string(),
+                "clear_buffer: the size 6 is not a positive multiple of 4".to_string(),
+                format!(
+                    "clear_buffer: the offset 64 is past the 64 bytes of buffer {:?}",
+                    buffer.buffer
+                ),
+                format!(
+                    "clear_buffer: 64 bytes from 32 are past the 64 bytes of buffer {:?}",
+                    buffer.buffer
+                ),
+            ]
+        );
+        buffer.destroy(gpu.device());
+    }
+    assert!(backend.errors().is_empty());
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn encoders_refuse_ids_of_destroyed_buffers() -> Result<()> {
+    let (backend, gpu) = null()?;
+    unsafe {
+        let mut registry = ResourceRegistry::new();
+        let buffer = host_buffer(&gpu, 64, vk::BufferUsageFlags::TRANSFER_DST)?;
+        buffer.write(gpu.device(), 0, &[9u32; 16])?;
+        let id = registry.insert(buffer);
+
+        gpu.execute(|device, command_buffer| {
+            let mut encoder = Encoder::new(device, command_buffer).with_registry(&registry);
+            encoder.clear_buffer_id(id, 0, Some(16))?;
+            Ok(())
+        })?;
+        let buffer = registry.remove(id)?;
+        let read = buffer.read::<u32>(gpu.device(), 0, 16)?;
+        assert_eq!(read[..5], [0, 0, 0, 0, 9]);
+
+        // the id outlived its buffer, and without a registry no id resolves
+        let mut problems = vec![];
+        gpu.execute(|device, command_buffer| {
+            let mut encoder = Encoder::new(device, command_buffer).with_registry(&registry);
+            problems.push(encoder.clear_buffer_id(id, 0, None).unwrap_err());
+            problems.push(encoder.bind_vertex_buffer_ids(0, &[(id, 0)]).unwrap_err());
+            let mut encoder = Encoder::new(device, command_buffer);
+            problems.push(
+                encoder
+                    .bind_index_buffer_id(id, vk::IndexType::UINT16)
+                    .unwrap_err(),
+            );
+            Ok(())
+        })?;
+        let problems = problems.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            problems,
+            vec![
+                format!("clear_buffer: buffer {:?} was destroyed", id),
+                format!("bind_vertex_buffers: buffer {:?} was destroyed", id),
+                format!("bind_index_buffer: no registry resolves buffer {:?}", id),
+            ]
+        );
+        let read = buffer.read::<u32>(gpu.device(), 0, 16)?;
+        assert_eq!(read[4..], [9; 12]);
+        buffer.destroy(gpu.device());
+    }
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn device_encoders_resolve_registered_buffers() -> Result<()> {
+    let (backend, window, mut device) = windowed(32, 32)?;
+    let id = device.create_registered_buffer(64, vk::BufferUsageFlags::TRANSFER_DST)?;
+    let buffer = device.with_resource(id, |b: &deimos::gfx::Buffer| b.buffer)?;
+
+    // the device lends its registry to the encoders it hands out
+    present_after(&mut device, &window, |device, command_buffer| unsafe {
+        Ok(device
+            .encoder(command_buffer)
+            .clear_buffer_id(id, 0, None)?)
+    })?;
+    assert!(backend
+        .commands()
+        .iter()
+        .any(|c| matches!(c, TraceCommand::FillBuffer { buffer: b, .. } if *b == buffer)));
+
+    // a destroyed buffer is refused from then on
+    device.destroy_resource(id)?;
+    let mut refused = None;
+    present_after(&mut device, &window, |device, command_buffer| unsafe {
+        refused = device
+            .encoder(command_buffer)
+            .clear_buffer_id(id, 0, None)
+            .err();
+        Ok(())
+    })?;
+    assert_eq!(
+        refused.map(|e| e.to_string()),
+        Some(format!("clear_buffer: buffer {:?} was destroyed", id))
+    );
+
+    device.destroy();
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    Ok(())
+}
+
+#[test]
+fn timestamps_resolve_with_and_without_waiting() -> Result<()> {
+    let backend = NullBackend::new();
+    let gpu = HeadlessDevice::create_null_with_features(&backend, &[Feature::TimestampQuery])?;
+    unsafe {
+        let set = gpu.create_query_set(QueryKind::Timestamp, 4)?;
+        let buffer = host_buffer(&gpu, 128, vk::BufferUsageFlags::TRANSFER_DST)?;
+        buffer.write(gpu.device(), 0, &[0xffu8; 128])?;
+
+        // two of the four are written, waiting only for those
+        gpu.execute(|device, command_buffer| {
+            let mut encoder = Encoder::new(device, command_buffer);
+            encoder.reset_query_set(&set, 0..4)?;
+            encoder.write_timestamp(&set, 0, vk::PipelineStageFlags::TOP_OF_PIPE)?;
+            encoder.write_timestamp(&set, 1, vk::PipelineStageFlags::BOTTOM_OF_PIPE)?;
+            encoder.resolve_query_set(&set, 0..2, &buffer, 0, QueryResolve::Wait)?;
+            encoder.resolve_query_set(&set, 0..4, &buffer, 16, QueryResolve::Available)?;
+            Ok(())
+        })?;
+
+        let bytes = buffer.read::<u8>(gpu.device(), 0, 80)?;
+        let waited = QueryResolve::Wait.results(&bytes[..16]);
+        assert!(waited.iter().all(Option::is_some));
+        assert!(waited[1] > waited[0]);
+        let available = QueryResolve::Available.results(&bytes[16..]);
+        assert_eq!(available[..2], waited[..]);
+        assert_eq!(available[2..], [None, None]);
+        // unavailable results are left as they were
+        assert_eq!(bytes[48..56], [0xff; 8]);
+
+        set.destroy(gpu.device());
+        buffer.destroy(gpu.device());
+    }
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn occlusion_queries_reset_on_the_host_resolve_partially() -> Result<()> {
+    let (backend, gpu) = null()?;
+    assert!(gpu.supports_host_query_reset());
+    unsafe {
+        let set = gpu.create_query_set(QueryKind::Occlusion, 2)?;
+        let buffer = host_buffer(&gpu, 64, vk::BufferUsageFlags::TRANSFER_DST)?;
+        gpu.reset_queries(&set, 0..2)?;
+        assert!(gpu.reset_queries(&set, 1..3).is_err());
+
+        // the second query is never ended, partial results write 0 for it
+        gpu.execute(|device, command_buffer| {
+            let mut encoder = Encoder::new(device, command_buffer);
+            encoder.begin_query(&set, 0)?;
+            encoder.end_query(&set, 0)?;
+            encoder.resolve_query_set(&set, 0..2, &buffer, 0, QueryResolve::Partial)?;
+            encoder.resolve_query_set(&set, 0..2, &buffer, 32, QueryResolve::Available)?;
+            Ok(())
+        })?;
+
+        let words = buffer.read::<u64>(gpu.device(), 0, 8)?;
+        assert_eq!(words[..4], [0, 1, 0, 0]);
+        let bytes = buffer.read::<u8>(gpu.device(), 32, 32)?;
+        assert_eq!(QueryResolve::Available.results(&bytes), [Some(0), None]);
+
+        set.destroy(gpu.device());
+        buffer.destroy(gpu.device());
+    }
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn concurrent_sharing_needs_distinct_families() -> Result<()> {
+    let (backend, gpu) = null()?;
+    unsafe {
+        // the null device has a single family, its resources stay exclusive
+        let buffer = host_buffer(&gpu, 64, vk::BufferUsageFlags::TRANSFER_DST)?;
+        assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+
+        for families in [[0, 1], [0, 0]] {
+            let info = vk::BufferCreateInfo::builder()
+                .size(64)
+                .usage(vk::BufferUsageFlags::TRANSFER_DST)
+                .sharing_mode(vk::SharingMode::CONCURRENT)
+                .queue_family_indices(&families);
+            let concurrent = gpu.device().create_buffer(&info, None)?;
+            gpu.device().destroy_buffer(concurrent, None);
+        }
+        assert_eq!(
+            backend.errors(),
+            [
+                "vkCreateBuffer: there is no queue family 1.",
+                "vkCreateBuffer: concurrent sharing across [0, 0] needs two distinct families.",
+            ]
+        );
+        buffer.destroy(gpu.device());
+    }
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn query_misuse_is_refused() -> Result<()> {
+    let (backend, gpu) = null()?;
+    unsafe {
+        // timestamps weren't enabled
+        assert!(gpu.create_query_set(QueryKind::Timestamp, 2).is_err());
+        assert!(gpu.create_query_set(QueryKind::Occlusion, 0).is_err());
+
+        let set = gpu.create_query_set(QueryKind::Occlusion, 4)?;
+        let buffer = host_buffer(&gpu, 64, vk::BufferUsageFlags::TRANSFER_DST)?;
+        let mut problems = vec![];
+        gpu.execute(|device, command_buffer| {
+            let mut encoder = Encoder::new(device, command_buffer);
+            encoder.reset_query_set(&set, 0..4)?;
+            let mut check = |result: Result<(), CommandError>| {
+                if let Err(error) = result {
+                    problems.push(error.to_string());
+                }
+            };
+            check(encoder.reset_query_set(&set, 2..2));
+            check(encoder.write_timestamp(&set, 0, vk::PipelineStageFlags::TOP_OF_PIPE));
+            check(encoder.begin_query(&set, 4));
+            check(encoder.resolve_query_set(&set, 0..2, &buffer, 4, QueryResolve::Wait));
+            check(encoder.resolve_query_set(&set, 0..4, &buffer, 8, QueryResolve::Available));
+            // nothing writes the query the copy waits for
+            encoder.resolve_query_set(&set, 3..4, &buffer, 0, QueryResolve::Wait)?;
+            Ok(())
+        })?;
+
+        assert_eq!(
+            problems,
+            [
+                format!(
+                    "reset_query_set: queries 2..2 are not in the 4 of query set {:?}",
+                    set.pool
+                ),
+                format!(
+                    "write_timestamp: query set {:?} holds no timestamps",
+                    set.pool
+                ),
+                format!(
+                    "begin_query: queries 4..5 are not in the 4 of query set {:?}",
+                    set.pool
+                ),
+                "resolve_query_set: the offset 4 is not a multiple of 8".to_string(),
+                format!(
+                    "resolve_query_set: 64 bytes from 8 are past the 64 bytes of buffer {:?}",
+                    buffer.buffer
+                ),
+            ]
+        );
+        let errors = backend.errors();
+        assert_eq!(
+            errors,
+            ["vkCmdCopyQueryPoolResults: waits for query 3 that nothing wrote."]
+        );
+
+        set.destroy(gpu.device());
+        buffer.destroy(gpu.device());
+    }
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn misuse_is_reported() -> Result<()> {
+    let (backend, gpu) = null()?;
+    unsafe {
+        // a draw outside of any render pass
+        gpu.execute(|device, command_buffer| {
+            device.cmd_draw(command_buffer, 3, 1, 0, 0);
+            Ok(())
+        })?;
+        let errors = backend.errors();
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.contains("outside of a render pass")),
+            "{:?}",
+            errors
+        );
+        assert!(gpu.validation_errors() > 0);
+
+        // a copy from a destroyed buffer
+        backend.clear();
+        let buffer = host_buffer(
+            &gpu,
+            64,
+            vk::BufferUsageFlags::TRANSFER_SRC | vk::BufferUsageFlags::TRANSFER_DST,
+        )?;
+        buffer.destroy(gpu.device());
+        gpu.execute(|device, command_buffer| {
+            let region = vk::BufferCopy::builder().size(16);
+            device.cmd_copy_buffer(command_buffer, buffer.buffer, buffer.buffer, &[region]);
+            Ok(())
+        })?;
+        let errors = backend.errors();
+        assert!(
+            errors.iter().any(|e| e.contains("was destroyed")),
+            "{:?}",
+            errors
+        );
+
+        // a buffer without the usage of its copy
+        backend.clear();
+        let src = host_buffer(&gpu, 64, vk::BufferUsageFlags::UNIFORM_BUFFER)?;
+        let dst = host_buffer(&gpu, 64, vk::BufferUsageFlags::TRANSFER_DST)?;
+        gpu.execute(|device, command_buffer| {
+            let region = vk::BufferCopy::builder().size(128);
+            device.cmd_copy_buffer(command_buffer, src.buffer, dst.buffer, &[region]);
+            Ok(())
+        })?;
+        let errors = backend.errors();
+        assert!(
+            errors.iter().any(|e| e.contains("TRANSFER_SRC")),
+            "{:?}",
+            errors
+        );
+        assert!(
+            errors.iter().any(|e| e.contains("out of the 64 bytes")),
+            "{:?}",
+            errors
+        );
+        src.destroy(gpu.device());
+
+        // the destination is never destroyed
+        backend.clear();
+        assert_eq!(backend.live_objects(vk::ObjectType::BUFFER), 1);
+    }
+    gpu.destroy();
+    let errors = backend.errors();
+    assert!(
+        errors
+            .iter()
+            .any(|e| e.contains("1 BUFFER objects were not destroyed")),
+        "{:?}",
+        errors
+    );
+    assert!(
+        errors
+            .iter()
+            .any(|e| e.contains("1 DEVICE_MEMORY objects were not destroyed")),
+        "{:?}",
+        errors
+    );
+    Ok(())
+}
+
+#[test]
+fn submits_wait_for_the_submits_they_come_after() -> Result<()> {
+    for backend in [
+        NullBackend::new(),
+        NullBackend::without_timeline_semaphores(),
+    ] {
+        let gpu = HeadlessDevice::create_null(&backend)?;
+        let shadows = gpu.create_timeline()?;
+        let frames = gpu.create_timeline()?;
+        unsafe {
+            let device = gpu.device();
+            frames.submit(device, gpu.queue(), &[], &[], &[], &[])?;
+
+            // the main pass waits for the shadows, the overlay for nothing
+            let mut graph = SubmitGraph::new();
+            let shadow = graph.add(&shadows, gpu.queue(), &[]);
+            let main = graph.add(&frames, gpu.queue(), &[]);
+            let overlay = graph.add(&frames, gpu.queue(), &[]);
+            graph.after(main, shadow, vk::PipelineStageFlags::FRAGMENT_SHADER)?;
+            assert!(graph
+                .after(shadow, overlay, vk::PipelineStageFlags::ALL_COMMANDS)
+                .is_err());
+            assert_eq!(graph.leaves(), [main, overlay]);
+            assert_eq!(graph.submit(device)?, [1, 2, 3]);
+
+            // only semaphore backed timelines wait on the gpu
+            let submissions = backend.submissions();
+            assert_eq!(submissions.len(), 4);
+            let waits = submissions[2].wait_semaphores.len();
+            assert_eq!(waits, gpu.supports_timeline_semaphores() as usize);
+            assert!(submissions[3].wait_semaphores.is_empty());
+
+            frames.wait(device, 3)?;
+            shadows.destroy(device);
+            frames.destroy(device);
+        }
+        assert_eq!(backend.errors(), Vec::<String>::new());
+        gpu.destroy();
+    }
+    Ok(())
+}
+
+#[test]
+fn timelines_complete_with_and_without_semaphores() -> Result<()> {
+    for backend in [
+        NullBackend::new(),
+        NullBackend::without_timeline_semaphores(),
+    ] {
+        let gpu = HeadlessDevice::create_null(&backend)?;
+        let timeline = gpu.create_timeline()?;
+        unsafe {
+            let device = gpu.device();
+            for _ in 0..3 {
+                timeline.submit(device, gpu.queue(), &[], &[], &[], &[])?;
+            }
+            timeline.wait(device, 2)?;
+            assert!(timeline.completed(device)? >= 2);
+            timeline.wait(device, 3)?;
+            assert_eq!(timeline.completed(device)?, 3);
+            assert!(timeline.wait(device, 4).is_err());
+            timeline.destroy(device);
+        }
+        assert_eq!(timeline.is_semaphore(), gpu.supports_timeline_semaphores());
+        assert_eq!(backend.submissions().len(), 3);
+        gpu.destroy();
+        assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    }
+    Ok(())
+}
+
+#[test]
+fn submitted_work_is_polled_awaited_and_called_back() -> Result<()> {
+    for backend in [
+        NullBackend::new(),
+        NullBackend::without_timeline_semaphores(),
+    ] {
+        let gpu = HeadlessDevice::create_null(&backend)?;
+        let timeline = gpu.create_timeline()?;
+        let device = gpu.device();
+        let called = Arc::new(AtomicUsize::new(0));
+
+        // work that isn't submitted yet is pending, its callbacks wait for a poll that sees it
+        let work = timeline.work_done(device, 1);
+        let mut context = Context::from_waker(Waker::noop());
+        assert!(!work.is_done()?);
+        assert!(pin!(timeline.work_done(device, 1))
+            .poll(&mut context)
+            .is_pending());
+        let count = called.clone();
+        work.then(move || {
+            count.fetch_add(1, Ordering::Relaxed);
+        });
+        unsafe { timeline.submit(device, gpu.queue(), &[], &[], &[], &[])? };
+        assert_eq!(called.load(Ordering::Relaxed), 0);
+        assert!(work.is_done()?);
+        assert_eq!(called.load(Ordering::Relaxed), 1);
+        assert!(matches!(
+            pin!(timeline.work_done(device, 1)).poll(&mut context),
+            Poll::Ready(Ok(()))
+        ));
+
+        // completed values call back right away, values never submitted can't be waited for
+        let count = called.clone();
+        timeline.on_reached(1, move || {
+            count.fetch_add(1, Ordering::Relaxed);
+        });
+        assert_eq!(called.load(Ordering::Relaxed), 2);
+        assert!(timeline.work_done(device, 2).wait().is_err());
+
+        unsafe { timeline.destroy(device) };
+        gpu.destroy();
+        assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    }
+    Ok(())
+}
+
+#[test]
+fn buffer_maps_are_refused_past_the_buffer() -> Result<()> {
+    let (backend, window, mut device) = windowed(32, 32)?;
+    let buffer = device.create_buffer(
+        16,
+        vk::BufferUsageFlags::TRANSFER_DST,
+        vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+    )?;
+    unsafe { buffer.write(device.device(), 0, &[1u32, 2, 3, 4])? };
+    present(&mut device, &window)?;
+
+    // the values are read once the frame completed
+    let read = device.map_async::<u32>(&buffer, 8, 2)?;
+    assert_eq!(block_on(read)?, [3, 4]);
+
+    // reads past the end or overflowing are refused before waiting
+    let error = device.map_async::<u32>(&buffer, 4, 4).err().unwrap();
+    assert_eq!(
+        error.to_string(),
+        "Reading 4 values of 4 bytes at 4 overruns the buffer of 16 bytes."
+    );
+    assert!(device.map_async::<u32>(&buffer, u64::MAX, 1).is_err());
+    assert!(device.map_async::<u32>(&buffer, 0, usize::MAX).is_err());
+    assert!(unsafe { buffer.read::<u32>(device.device(), 12, 2) }.is_err());
+
+    unsafe { buffer.destroy(device.device()) };
+    device.destroy();
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    Ok(())
+}
+
+#[test]
+fn dropped_texture_uploads_are_retired() -> Result<()> {
+    let (backend, window, mut device) = windowed(32, 32)?;
+    let allocations = device.allocation_stats().allocations;
+    let images = backend.live_objects(vk::ObjectType::IMAGE);
+
+    // the future owns the texture, dropping it hands the texture to the deletion queue
+    let upload = device.upload_texture_async(4, 4, &[255; 64], TextureImport::Color)?;
+    drop(upload);
+    present(&mut device, &window)?;
+    present(&mut device, &window)?;
+    assert_eq!(device.allocation_stats().allocations, allocations);
+    assert_eq!(backend.live_objects(vk::ObjectType::IMAGE), images);
+
+    device.destroy();
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    Ok(())
+}
+
+#[test]
+fn devices_submit_from_another_thread() -> Result<()> {
+    // a window's device moves to a render thread the same way
+    fn send<T: Send>() {}
+    send::<deimos::gfx::Device>();
+
+    let backend = NullBackend::new();
+    let gpu = HeadlessDevice::create_null(&backend)?;
+    let gpu = thread::spawn(move || -> Result<HeadlessDevice> {
+        let timeline = gpu.create_timeline()?;
+        let device = gpu.device();
+        unsafe { timeline.submit(device, gpu.queue(), &[], &[], &[], &[])? };
+        timeline.work_done(device, 1).wait()?;
+        unsafe { timeline.destroy(device) };
+        Ok(gpu)
+    })
+    .join()
+    .unwrap()?;
+    gpu.destroy();
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    Ok(())
+}
+
+#[test]
+fn staging_belt_rings_start_over_once_submitted() -> Result<()> {
+    let (backend, gpu) = null()?;
+    let timeline = gpu.create_timeline()?;
+    unsafe {
+        let device = gpu.device();
+        let mut belt = StagingBelt::create(gpu.instance(), gpu.physical(), device, 2, 256)?;
+
+        // allocations bump through the ring at their alignment
+        let (buffer, offset, bytes) = belt.allocate(device, &timeline, 0, 100, 4)?;
+        assert_eq!((offset, bytes.len()), (0, 100));
+        bytes.fill(3);
+        let (same, offset, bytes) = belt.allocate(device, &timeline, 0, 16, 64)?;
+        assert_eq!((same, offset), (buffer, 128));
+        bytes.fill(5);
+
+        // the bytes are what the gpu reads
+        let dst = host_buffer(&gpu, 144, vk::BufferUsageFlags::TRANSFER_DST)?;
+        gpu.execute(|device, command_buffer| {
+            let region = vk::BufferCopy::builder().size(144);
+            device.cmd_copy_buffer(command_buffer, buffer, dst.buffer, &[region]);
+            Ok(())
+        })?;
+        let read = dst.read::<u8>(device, 0, 144)?;
+        assert!(read[..100].iter().all(|b| *b == 3));
+        assert!(read[128..].iter().all(|b| *b == 5));
+
+        // a full ring grows, the other frame is untouched
+        let (grown, offset, _) = belt.allocate(device, &timeline, 0, 200, 4)?;
+        assert_ne!(grown, buffer);
+        assert_eq!(offset, 0);
+        assert_eq!(belt.capacity(0), 256 + 512);
+        assert_eq!(belt.allocate(device, &timeline, 1, 8, 4)?.1, 0);
+
+        // once the frame's submission completed its ring starts over
+        let value = timeline.submit(device, gpu.queue(), &[], &[], &[], &[])?;
+        belt.finish(0, value);
+        let (first, offset, _) = belt.allocate(device, &timeline, 0, 64, 4)?;
+        assert_eq!((first, offset), (buffer, 0));
+
+        assert!(belt.allocate(device, &timeline, 0, 0, 4).is_err());
+        assert!(belt.allocate(device, &timeline, 0, 8, 3).is_err());
+
+        dst.destroy(device);
+        belt.destroy(device);
+        timeline.destroy(device);
+    }
+    gpu.destroy();
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    Ok(())
+}
+
+#[test]
+fn unused_assets_are_retired_until_their_frame_completed() -> Result<()> {
+    let (backend, gpu) = null()?;
+    let timeline = gpu.create_timeline()?;
+    unsafe {
+        let device = gpu.device();
+        let mut shaders = Assets::new();
+        let deletion = DeletionQueue::default();
+
+        // the same code is one shader however often it is asked for
+        let create = || Shader::create(device, TRIANGLE_VERT);
+        let vert = shaders.get_or_create(AssetKey::content(TRIANGLE_VERT), create)?;
+        let again = shaders.get_or_create(AssetKey::content(TRIANGLE_VERT), || unreachable!())?;
+        let frag = shaders.get_or_create(AssetKey::content(SOLID_FRAG), || {
+            Shader::create(device, SOLID_FRAG)
+        })?;
+        assert_eq!(vert, again);
+        assert_ne!(vert, frag);
+        assert_eq!((shaders.len(), vert.count()), (2, 2));
+
+        // only assets without handles are purged
+        drop((vert, again));
+        let unused = shaders.take_unused();
+        assert_eq!((unused.len(), shaders.len()), (1, 1));
+
+        // a retired shader lives until the submission that may use it completed
+        let value = timeline.submitted() + 1;
+        unused
+            .into_iter()
+            .for_each(|(_, s)| deletion.retire(value, s));
+        assert_eq!(deletion.collect(device, &timeline)?, 0);
+        timeline.submit(device, gpu.queue(), &[], &[], &[], &[])?;
+        timeline.wait(device, value)?;
+        assert_eq!(deletion.collect(device, &timeline)?, 1);
+        assert!(deletion.is_empty());
+
+        shaders.clear(|s| s.destroy(device));
+        drop(frag);
+        timeline.destroy(device);
+    }
+    gpu.destroy();
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    Ok(())
+}
+
+#[test]
+fn equal_sampler_descriptors_share_a_sampler() -> Result<()> {
+    let (backend, gpu) = null()?;
+    let clamp = vk::SamplerAddressMode::CLAMP_TO_EDGE;
+
+    let linear = gpu.sampler(&SamplerDescriptor::linear(clamp))?;
+    assert_eq!(gpu.sampler(&SamplerDescriptor::linear(clamp))?, linear);
+    let shadow = SamplerDescriptor::linear(clamp).with_compare(vk::CompareOp::LESS);
+    assert_ne!(gpu.sampler(&shadow)?, linear);
+    assert_ne!(gpu.sampler(&SamplerDescriptor::nearest(clamp))?, linear);
+    assert_ne!(gpu.sampler(&SamplerDescriptor::default())?, linear);
+
+    // the headless device filters without anisotropy
+    let error = gpu
+        .sampler(&SamplerDescriptor::default().with_anisotropy(4.0))
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref(),
+        Some(DescriptionError::BadAnisotropy { .. })
+    ));
+    let error = gpu
+        .sampler(&SamplerDescriptor::default().with_lod(2.0, 1.0))
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref(),
+        Some(DescriptionError::BadLodRange { .. })
+    ));
+
+    // the device destroys its samplers
+    gpu.destroy();
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    Ok(())
+}
+
+#[test]
+fn resources_are_written_to_the_bindings_they_match() -> Result<()> {
+    let (backend, gpu) = null()?;
+    unsafe {
+        let device = gpu.device();
+        let binding = |binding, type_| {
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(binding)
+                .descriptor_type(type_)
+                .descriptor_count(1)
+                .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+                .build()
+        };
+        let mut sets = DescriptorSets::create(
+            device,
+            DescriptorSlot::Material,
+            &[
+                binding(0, vk::DescriptorType::UNIFORM_BUFFER),
+                binding(1, vk::DescriptorType::SAMPLER),
+                binding(2, vk::DescriptorType::SAMPLED_IMAGE),
+                binding(3, vk::DescriptorType::STORAGE_IMAGE),
+                binding(4, vk::DescriptorType::STORAGE_BUFFER),
+            ],
+        )?;
+
+        let uniforms = host_buffer(&gpu, 256, vk::BufferUsageFlags::UNIFORM_BUFFER)?;
+        let storage = host_buffer(&gpu, 256, vk::BufferUsageFlags::STORAGE_BUFFER)?;
+        let texture = gpu.create_texture(
+            4,
+            4,
+            1,
+            FORMAT,
+            vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::STORAGE,
+        )?;
+        let view = texture.create_view(device, FORMAT, vk::ImageAspectFlags::COLOR, 1)?;
+        let sampler = gpu.sampler(&SamplerDescriptor::default())?;
+
+        // every kind of resource goes to its own binding
+        let set = sets.get(device, 0)?;
+        sets.write(
+            device,
+            set,
+            &[
+                (0, BindingResource::buffer(&uniforms)),
+                (1, BindingResource::Sampler(sampler)),
+                (2, BindingResource::Texture(view.view)),
+                (3, BindingResource::StorageTexture(view.view)),
+                (
+                    4,
+                    BindingResource::Buffer {
+                        buffer: storage.buffer,
+                        offset: 128,
+                        range: 128,
+                    },
+                ),
+            ],
+        )?;
+        assert_eq!(backend.errors(), Vec::<String>::new());
+
+        // resources that don't fit the layout are refused
+        let error = sets
+            .write(device, set, &[(5, BindingResource::Sampler(sampler))])
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref(),
+            Some(&DescriptionError::UnknownBinding(5))
+        );
+        let error = sets
+            .write(device, set, &[(0, BindingResource::Sampler(sampler))])
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(DescriptionError::WrongBindingResource { binding: 0, .. })
+        ));
+
+        // the set is bound at the index of its slot
+        let empty = device
+            .create_descriptor_set_layout(&vk::DescriptorSetLayoutCreateInfo::builder(), None)?;
+        let set_layouts = slot_set_layouts(&[&sets], empty)?;
+        assert_eq!(set_layouts, [empty, empty, sets.layout]);
+        let info = vk::PipelineLayoutCreateInfo::builder().set_layouts(&set_layouts);
+        let layout = device.create_pipeline_layout(&info, None)?;
+
+        backend.clear();
+        gpu.execute(|device, command_buffer| {
+            sets.bind(
+                device,
+                command_buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                layout,
+                set,
+            );
+            Ok(())
+        })?;
+        let submissions = backend.submissions();
+        assert!(matches!(
+            &submissions[0].commands[0],
+            TraceCommand::BindDescriptorSets { first_set: 2, sets: bound, .. } if bound == &[set]
+        ));
+
+        device.destroy_pipeline_layout(layout, None);
+        device.destroy_descriptor_set_layout(empty, None);
+        sets.destroy(device);
+        view.destroy(device);
+        texture.destroy(device);
+        uniforms.destroy(device);
+        storage.destroy(device);
+    }
+    assert_eq!(backend.errors(), Vec::<String>::new());
+    gpu.destroy();
+    assert!(backend.errors().is_empty());
+    Ok(())
+}
+
+#[test]
+fn storage_buffers_written_by_compute_are_read_as_vertices() -> Result<()> {
+    let (backend, gpu) = null()?;
+    unsafe {
+        let device = gpu.device();
+        let usage = vk::BufferUsageFlags::STORAGE_BUFFER
+            | vk::BufferUsageFlags::VERTEX_BUFFER
+            | vk::BufferUsageFlags::INDIRECT_BUFFER;
+        let vertices = host_buffer(&gpu, 1024, usage)?;
+        let arguments = host_buffer(&gpu, 16, usage)?;
+
+        // compute writes both through storage bindings
+        let binding = |binding| {
+            vk::DescriptorSetLayoutBinding::builder()
+                .binding(binding)
+                .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                .descriptor_count(1)
+                .stage_flags(vk::ShaderStageFlags::COMPUTE)
+                .build()
+        };
+        let mut sets =
+            DescriptorSets::create(device, DescriptorSlot::Object, &[binding(0), binding(1)])?;
+        let set = sets.get(device, 0)?;
+        sets.write(
+            device,
+            set,
+            &[
+                (0, BindingResource::buffer(&vertices)),
+                (1, BindingResource::buffer(&arguments)),
+            ],
+        )?;
+
+        backend.clear();
+        gpu.execute(|device, command_buffer| {
+            buffer_barrier(
+                device,
+                command_buffer,
+                &[vertices.buffer, arguments.buffer],
+                &[BufferAccess::ComputeWrite],
+                &[BufferAccess::Vertex, BufferAccess::Indirect],
+            );
+            vertices.barrier(
+                device,
+                command_buffer,
+                &[BufferAccess::Vertex],
+                &[BufferAccess::ComputeWrite],
+            );
+            Ok(())
+        })?;
+
+        // the accesses of all uses are merged into a barrier per buffer
+        let submissions = backend.submissions();
+        let TraceCommand::PipelineBarrier {
+            src_stage,
+            dst_stage,
+            memory_barriers,
+            buffers,
+            ..
+        } = &submissions[0].commands[0]
+        else {
+            panic!("no barrier was recorded");
+        };
+        assert_eq!(*src_stage, vk::PipelineStageFlags::COMPUTE_SHADER);
+        assert_eq!(
+            *dst_stage,
+            vk::PipelineStageFlags::VERTEX_INPUT | vk::PipelineStageFlags::DRAW_INDIRECT
+        );
+        assert!(memory_barriers.is_empty());
+        assert_eq!(buffers.len(), 2);
+        assert_eq!(buffers[1].buffer, arguments.buffer);
+        assert_eq!(buffers[1].src_access, vk::AccessFlags::SHADER_WRITE);
+        assert_eq!(
+            buffers[1].dst_access,
+            vk::AccessFlags::VERTEX_ATTRIBUTE_READ | vk::AccessFlags::INDIRECT_COMMAND_READ
+        );
+        assert!(matches!(
+            &submissions[0].commands[1],
+            TraceCommand::PipelineBarrier { buffers, .. }
+                if buffers.len() == 1 && buffers[0].dst_access == vk::AccessFlags::SHADER_WRITE
+        ));
+
+        sets.destroy(device);
+        vertices.destroy(device);
+        arguments.destroy(device);
+    }
+    assert_eq!(backend.errors(), Vec::<String>::new());
+    gpu.destroy();
+    assert!(backend.errors().is_empty());
+    Ok(())
+}
+
+#[test]
+fn indexed_draws_stay_inside_their_index_buffer() -> Result<()> {
+    let (backend, gpu) = null()?;
+    unsafe {
+        let device = gpu.device();
+        let (texture, view, render_pass, framebuffer) = create_target(&gpu, 16, 16)?;
+        let layout =
+            device.create_pipeline_layout(&vk::PipelineLayoutCreateInfo::builder(), None)?;
+        let pipeline = create_pipeline(device, render_pass, layout)?;
+
+        // two triangles of 16 bit indices
+        let buffer = host_buffer(&gpu, 12, vk::BufferUsageFlags::INDEX_BUFFER)?;
+        buffer.write(device, 0, &[0u16, 1, 2, 2, 1, 3])?;
+        let indices = IndexBinding::new(&buffer, vk::IndexType::UINT16);
+
+        let mut results = vec![];
+        gpu.execute(|device, command_buffer| {
+            let clear_values = &[vk::ClearValue::default()];
+            let info = vk::RenderPassBeginInfo::builder()
+                .render_pass(render_pass)
+                .framebuffer(framebuffer)
+                .render_area(vk::Rect2D::default())
+                .clear_values(clear_values);
+            device.cmd_begin_render_pass(command_buffer, &info, vk::SubpassContents::INLINE);
+            device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
+
+            indices.bind(device, command_buffer);
+            results.push(indices.draw(device, command_buffer, 0, 6, 1));
+            results.push(indices.draw(device, command_buffer, 3, 6, 1));
+            results.push(indices.with_offset(1).draw(device, command_buffer, 0, 3, 1));
+
+            // read as 32 bit the buffer holds only 3, the driver catches what skips the check
+            let wide = IndexBinding::new(&buffer, vk::IndexType::UINT32);
+            results.push(wide.draw(device, command_buffer, 0, 6, 1));
+            wide.bind(device, command_buffer);
+            device.cmd_draw_indexed(command_buffer, 6, 1, 0, 0, 0);
+
+            device.cmd_end_render_pass(command_buffer);
+            Ok(())
+        })?;
+
+        assert!(results[0].is_ok());
+        let errors = results
+            .iter()
+            .skip(1)
+            .map(|r| r.as_ref().unwrap_err().downcast_ref::<DescriptionError>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                Some(&DescriptionError::IndexRange {
+                    first: 3,
+                    end: 9,
+                    available: 6
+                }),
+                Some(&DescriptionError::MisalignedIndices { offset: 1, size: 2 }),
+                Some(&DescriptionError::IndexRange {
+                    first: 0,
+                    end: 6,
+                    available: 3
+                }),
+            ]
+        );
+
+        // only the unchecked draw reached the driver out of range
+        let submissions = backend.submissions();
+        let draws = submissions[0]
+            .commands
+            .iter()
+            .filter(|c| matches!(c, TraceCommand::DrawIndexed { .. }))
+            .count();
+        assert_eq!(draws, 2);
+        assert_eq!(
+            backend.errors(),
+            ["vkCmdDrawIndexed: draws indices 0..6 of a buffer with 3."]
+        );
+        backend.clear();
+
+        device.destroy_pipeline(pipeline, None);
+        device.destroy_pipeline_layout(layout, None);
+        device.destroy_framebuffer(framebuffer, None);
+        device.destroy_render_pass(render_pass, None);
+        view.destroy(device);
+        texture.destroy(device);
+        buffer.destroy(device);
+    }
+    gpu.destroy();
+    assert!(backend.errors().is_empty());
+    Ok(())
+}
+
+#[test]
+fn encoders_refuse_misuse_before_recording() -> Result<()> {
+    let (backend, gpu) = null()?;
+    unsafe {
+        let device = gpu.device();
+        let (texture, view, render_pass, framebuffer) = create_target(&gpu, 16, 16)?;
+        let layout =
+            device.create_pipeline_layout(&vk::PipelineLayoutCreateInfo::builder(), None)?;
+        let pipeline = create_pipeline(device, render_pass, layout)?;
+        let vertices = host_buffer(&gpu, 64, vk::BufferUsageFlags::VERTEX_BUFFER)?;
+        let retired = host_buffer(&gpu, 64, vk::BufferUsageFlags::VERTEX_BUFFER)?;
+        let queue = DeletionQueue::default();
+        queue.retire(1, retired);
+
+        let mut problems = vec![];
+        gpu.execute(|device, command_buffer| {
+            let mut encoder = Encoder::new(device, command_buffer).with_retired(&queue);
+            let mut check = |result: Result<(), CommandError>| {
+                if let Err(error) = result {
+                    problems.push(error.to_string());
+                }
+            };
+
+            check(encoder.draw(3, 1, 0, 0));
+            let clear_values = &[vk::ClearValue::default()];
+            let info = vk::RenderPassBeginInfo::builder()
+                .render_pass(render_pass)
+                .framebuffer(framebuffer)
+                .render_area(vk::Rect2D::default())
+                .clear_values(clear_values);
+            check(encoder.begin_render_pass(&info));
+            check(encoder.draw(3, 1, 0, 0));
+            check(encoder.bind_pipeline(vk::PipelineBindPoint::GRAPHICS, pipeline));
+            check(encoder.bind_vertex_buffers(0, &[(&retired, 0)]));
+            check(encoder.bind_vertex_buffers(0, &[(&vertices, 0)]));
+            check(encoder.draw(3, 1, 0, 0));
+            check(encoder.draw_indexed(0, 3, 1));
+            check(encoder.dispatch(1, 1, 1));
+            check(encoder.end_render_pass());
+            check(encoder.end_render_pass());
+            check(encoder.finish());
+            Ok(())
+        })?;
+
+        assert_eq!(
+            problems,
+            [
+                "draw: draws outside of a render pass".to_string(),
+                "draw: draws without a graphics pipeline".to_string(),
+                format!(
+                    "bind_vertex_buffers: buffer {:?} was retired",
+                    retired.buffer
+                ),
+                "draw_indexed: draws indexed without an index buffer".to_string(),
+                "dispatch: dispatches inside a render pass".to_string(),
+                "end_render_pass: there is no render pass to end".to_string(),
+            ]
+        );
+
+        // only the valid commands were recorded, the driver saw no misuse
+        assert_eq!(backend.errors(), Vec::<String>::new());
+        let commands = backend.commands();
+        assert_eq!(commands.len(), 5);
+        assert!(matches!(
+            commands[3],
+            TraceCommand::Draw {
+                vertex_count: 3,
+                ..
+            }
+        ));
+
+        device.destroy_pipeline(pipeline, None);
+        device.destroy_pipeline_layout(layout, None);
+        device.destroy_framebuffer(framebuffer, None);
+        device.destroy_render_pass(render_pass, None);
+        view.destroy(device);
+        texture.destroy(device);
+        vertices.destroy(device);
+        queue.destroy(device);
+    }
+    gpu.destroy();
+    assert!(backend.errors().is_empty());
+    Ok(())
+}
+
+#[test]
+fn buffers_in_flight_are_destroyed_once_their_frame_completed() -> Result<()> {
+    let (backend, gpu) = null()?;
+    unsafe {
+        let device = gpu.device();
+        let usage = ResourceUsage::default();
+        let deletion = DeletionQueue::default();
+        let used = host_buffer(&gpu, 64, vk::BufferUsageFlags::VERTEX_BUFFER)?;
+        let unused = host_buffer(&gpu, 64, vk::BufferUsageFlags::VERTEX_BUFFER)?;
+
+        // the encoder marks what it binds as used by frame 3
+        gpu.execute(|device, command_buffer| {
+            let mut encoder = Encoder::new(device, command_buffer).with_usage(&usage, 3);
+            encoder.bind_vertex_buffers(0, &[(&used, 0)])?;
+            Ok(())
+        })?;
+        assert_eq!(usage.last_use(used.buffer), Some(3));
+        assert_eq!(usage.last_use(unused.buffer), None);
+
+        // with frame 2 completed the used buffer waits, the other goes right away
+        let buffers = backend.live_objects(vk::ObjectType::BUFFER);
+        assert!(usage.destroy(device, &deletion, 2, used));
+        assert!(!usage.destroy(device, &deletion, 2, unused));
+        assert_eq!(backend.live_objects(vk::ObjectType::BUFFER), buffers - 1);
+        assert_eq!(deletion.len(), 1);
+        assert!(deletion.is_retired(used.buffer));
+
+        // completed uses are forgotten
+        usage.record(unused.buffer, 4);
+        usage.forget_completed(4);
+        assert_eq!(usage.last_use(unused.buffer), None);
+
+        deletion.destroy(device);
+        assert_eq!(backend.live_objects(vk::ObjectType::BUFFER), buffers - 2);
+    }
+    assert_eq!(backend.errors(), Vec::<String>::new());
+    gpu.destroy();
+    assert!(backend.errors().is_empty());
+    Ok(())
+}
+
+#[test]
+fn renderers_draw_meshes_by_their_registered_buffers() -> Result<()> {
+    let (backend, window, mut device) = windowed(32, 32)?;
+    let mut renderer = Renderer::create(&device)?;
+    let white = Vec3::new(1.0, 1.0, 1.0);
+    let vertices = [
+        gfx::Vertex::new(Vec3::new(-0.5, -0.5, 0.0), Vec2::new(0.0, 0.0), white),
+        gfx::Vertex::new(Vec3::new(0.5, -0.5, 0.0), Vec2::new(1.0, 0.0), white),
+        gfx::Vertex::new(Vec3::new(0.0, 0.5, 0.0), Vec2::new(0.5, 1.0), white),
+    ];
+    let mesh = Mesh::create(&device, &vertices, &[0, 1, 2])?.register(&device);
+    assert_eq!(device.resource_counts().buffers, 2);
+    let buffer = device.with_resource(mesh.vertices, |b| b.buffer)?;
+
+    let material = Material::opaque(Vec4::new(1.0, 1.0, 1.0, 1.0));
+    renderer.begin_frame(Camera::default());
+    renderer.draw(&mesh, &material, Mat4::identity());
+    renderer.end_frame(&mut device, &window)?;
+    let commands = backend.commands();
+    assert!(commands.iter().any(|c| matches!(
+        c,
+        TraceCommand::BindVertexBuffers { buffers, .. } if *buffers == [buffer]
+    )));
+    assert!(commands
+        .iter()
+        .any(|c| matches!(c, TraceCommand::DrawIndexed { index_count: 3, .. })));
+
+    // the registry owns the buffers, they go once the frame drawing them completed
+    let buffers = backend.live_objects(vk::ObjectType::BUFFER);
+    mesh.destroy(&device)?;
+    assert_eq!(device.resource_counts().buffers, 0);
+    present(&mut device, &window)?;
+    assert_eq!(backend.live_objects(vk::ObjectType::BUFFER), buffers - 2);
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+
+    // drawing the destroyed mesh is refused
+    renderer.begin_frame(Camera::default());
+    renderer.draw(&mesh, &material, Mat4::identity());
+    let error = renderer.end_frame(&mut device, &window).unwrap_err();
+    assert!(error.to_string().contains("was destroyed"), "{}", error);
+
+    renderer.destroy(&device);
+    device.destroy();
+    Ok(())
+}
+
+#[test]
+fn renderers_draw_with_the_camera_the_latch_leaves() -> Result<()> {
+    let (backend, window, mut device) = windowed(32, 32)?;
+    let mut renderer = Renderer::create(&device)?;
+    let white = Vec3::new(1.0, 1.0, 1.0);
+    let vertices = [
+        gfx::Vertex::new(Vec3::new(-0.5, -0.5, 0.0), Vec2::new(0.0, 0.0), white),
+        gfx::Vertex::new(Vec3::new(0.5, -0.5, 0.0), Vec2::new(1.0, 0.0), white),
+        gfx::Vertex::new(Vec3::new(0.0, 0.5, 0.0), Vec2::new(0.5, 1.0), white),
+    ];
+    let mesh = Mesh::create(&device, &vertices, &[0, 1, 2])?.register(&device);
+    let material = Material::opaque(Vec4::new(1.0, 1.0, 1.0, 1.0));
+
+    // the camera moves after the frame was recorded
+    let recorded = Camera::default();
+    let mut latched = recorded;
+    latched.position = Vec3::new(1.0, 2.0, 3.0);
+    renderer.begin_frame(recorded);
+    renderer.draw(&mesh, &material, Mat4::identity());
+    renderer.end_frame_with_latch(&mut device, &window, |_, _, camera| {
+        *camera = latched;
+        Ok(())
+    })?;
+
+    // the submitted draw reads the latched camera from its frame uniform
+    let commands = backend.commands();
+    let set = commands
+        .iter()
+        .find_map(|c| match c {
+            TraceCommand::BindDescriptorSets {
+                first_set: 0, sets, ..
+            } => sets.first().copied(),
+            _ => None,
+        })
+        .expect("the frame uniform is bound");
+    let (view, proj) = latched.matrices(32, 32);
+    assert_eq!(
+        backend.descriptor_bytes(set, 0),
+        Some(pod_bytes(&[proj * view]).to_vec())
+    );
+
+    // the transform is pushed with the draw
+    assert!(commands.iter().any(|c| matches!(
+        c,
+        TraceCommand::PushConstants { data, .. }
+            if data[..size_of::<Mat4>()] == *pod_bytes(&[Mat4::identity()])
+    )));
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+
+    mesh.destroy(&device)?;
+    renderer.destroy(&device);
+    device.destroy();
+    Ok(())
+}
+
+#[test]
+fn render_passes_follow_the_descriptor_attachments() -> Result<()> {
+    let (backend, gpu) = null()?;
+    unsafe {
+        let device = gpu.device();
+        let color = |format| -> Result<_> {
+            let texture = gpu.create_texture(
+                16,
+                16,
+                1,
+                format,
+                vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
+            )?;
+            let view = texture.create_view(device, format, vk::ImageAspectFlags::COLOR, 1)?;
+            Ok((texture, view))
+        };
+        let (first, first_view) = color(FORMAT)?;
+        let (second, second_view) = color(FORMAT)?;
+        let (hdr, hdr_view) = color(vk::Format::R16G16B16A16_SFLOAT)?;
+
+        // any views, cleared to their own colors
+        let descriptor = RenderPassDescriptor::new(16, 16)
+            .color(ColorAttachment::new(&first_view, FORMAT).with_load(LoadOp::Clear([1.0; 4])))
+            .color(ColorAttachment::new(
+                &hdr_view,
+                vk::Format::R16G16B16A16_SFLOAT,
+            ));
+        let layout =
+            device.create_pipeline_layout(&vk::PipelineLayoutCreateInfo::builder(), None)?;
+        let render_pass = gpu.render_pass_for(&descriptor)?;
+        backend.clear();
+        gpu.execute(|_, command_buffer| {
+            assert_eq!(
+                gpu.begin_render_pass(command_buffer, &descriptor)?,
+                render_pass
+            );
+            device.cmd_end_render_pass(command_buffer);
+            Ok(())
+        })?;
+        let submissions = backend.submissions();
+        match &submissions[0].commands[0] {
+            TraceCommand::BeginRenderPass {
+                render_pass: r,
+                clear_values,
+                ..
+            } => {
+                assert_eq!(*r, render_pass);
+                assert_eq!(clear_values.len(), 2);
+                assert_eq!(clear_values[0].color.float32, [1.0; 4]);
+            }
+            other => panic!("expected the pass to begin, got {:?}", other),
+        }
+
+        // other views with the same formats and ops share the render pass, other ops don't
+        let other_views = RenderPassDescriptor::new(16, 16)
+            .color(ColorAttachment::new(&second_view, FORMAT))
+            .color(ColorAttachment::new(
+                &hdr_view,
+                vk::Format::R16G16B16A16_SFLOAT,
+            ));
+        assert_eq!(gpu.render_pass_for(&other_views)?, render_pass);
+        let loaded = RenderPassDescriptor::new(16, 16)
+            .color(ColorAttachment::new(&first_view, FORMAT).with_load(LoadOp::Load));
+        assert_ne!(gpu.render_pass_for(&loaded)?, render_pass);
+        gpu.execute(|_, command_buffer| {
+            gpu.begin_render_pass(command_buffer, &other_views)?;
+            device.cmd_end_render_pass(command_buffer);
+            gpu.begin_render_pass(command_buffer, &descriptor)?;
+            device.cmd_end_render_pass(command_buffer);
+            Ok(())
+        })?;
+        assert_eq!(gpu.render_pass_counts(), (2, 2));
+
+        // pipelines of the render pass draw into the textures
+        let pipeline = create_pipeline(device, gpu.render_pass_for(&loaded)?, layout)?;
+        device.destroy_pipeline(pipeline, None);
+
+        // descriptors that can't be a pass are refused
+        let error = gpu
+            .render_pass_for(&RenderPassDescriptor::new(16, 16))
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<DescriptionError>(),
+            Some(&DescriptionError::NoAttachments)
+        );
+        let mixed = RenderPassDescriptor::new(16, 16)
+            .color(ColorAttachment::new(&first_view, FORMAT))
+            .color(
+                ColorAttachment::new(&second_view, FORMAT)
+                    .multisampled(vk::SampleCountFlags::_4, &hdr_view),
+            );
+        let error = gpu.render_pass_for(&mixed).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<DescriptionError>(),
+            Some(&DescriptionError::MixedSamples(vk::SampleCountFlags::_4))
+        );
+
+        // released views take their framebuffers along
+        let framebuffers = backend.live_objects(vk::ObjectType::FRAMEBUFFER);
+        gpu.release_view(hdr_view.view);
+        assert_eq!(
+            backend.live_objects(vk::ObjectType::FRAMEBUFFER),
+            framebuffers - 2
+        );
+
+        device.destroy_pipeline_layout(layout, None);
+        for (texture, view) in [(first, first_view), (second, second_view), (hdr, hdr_view)] {
+            view.destroy(device);
+            texture.destroy(device);
+        }
+    }
+    assert_eq!(backend.errors(), Vec::<String>::new());
+    gpu.destroy();
+    assert!(backend.errors().is_empty());
+    Ok(())
+}
+
+#[test]
+fn split_screen_sets_viewports_past_the_first() -> Result<()> {
+    let (backend, gpu) = null()?;
+    let limit = gpu.adapter().limits.max_viewports;
+    assert!(limit > 1);
+    let half = |x: i32| {
+        let rect = vk::Rect2D {
+            offset: vk::Offset2D { x, y: 0 },
+            extent: vk::Extent2D {
+                width: 32,
+                height: 32,
+            },
+        };
+        let viewport = vk::Viewport::builder()
+            .x(x as f32)
+            .width(32.0)
+            .height(32.0)
+            .max_depth(1.0)
+            .build();
+        (viewport, rect)
+    };
+
+    // both halves at once, from index 1 on
+    backend.clear();
+    gpu.execute(|_, command_buffer| gpu.set_viewports(command_buffer, 1, &[half(0), half(32)]))?;
+    let submissions = backend.submissions();
+    assert!(matches!(
+        &submissions[0].commands[0],
+        TraceCommand::SetViewport { first_viewport: 1, viewports } if viewports[1].x == 32.0
+    ));
+    assert!(matches!(
+        &submissions[0].commands[1],
+        TraceCommand::SetScissor { first_scissor: 1, scissors } if scissors.len() == 2
+    ));
+
+    // viewports past the limit are refused before recording, raw calls are reported
+    gpu.execute(|device, command_buffer| {
+        let error = gpu
+            .set_viewports(command_buffer, limit - 1, &[half(0), half(32)])
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<DescriptionError>(),
+            Some(&DescriptionError::TooManyViewports {
+                first: limit - 1,
+                end: limit + 1,
+                limit
+            })
+        );
+        assert!(backend.errors().is_empty());
+        unsafe { device.cmd_set_viewport(command_buffer, limit, &[half(0).0]) };
+        Ok(())
+    })?;
+    assert_eq!(
+        backend.errors(),
+        vec![format!(
+            "vkCmdSetViewport: sets viewports {}..{} of the {} the device has.",
+            limit,
+            limit + 1,
+            limit
+        )]
+    );
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn texture_upload_copies_and_blits_the_mips() -> Result<()> {
+    let (backend, gpu) = null()?;
+    let (width, height) = (16, 8);
+    let pixels = vec![255; (width * height * 4) as usize];
+    let (texture, view) = gpu.upload_texture(width, height, &pixels, TextureImport::Color)?;
+
+    // the first level is copied and each of the 4 others blitted from the one before
+    let commands = backend.commands();
+    let copy = commands
+        .iter()
+        .position(|c| matches!(c, TraceCommand::CopyBufferToImage { .. }))
+        .unwrap();
+    let blits = commands
+        .iter()
+        .filter_map(|c| match c {
+            TraceCommand::BlitImage {
+                src, dst, regions, ..
+            } if *src == texture.image && *dst == texture.image => {
+                Some(regions[0].dst_subresource.mip_level)
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(blits, [1, 2, 3, 4]);
+    assert!(commands[copy..]
+        .iter()
+        .any(|c| matches!(c, TraceCommand::BlitImage { .. })));
+
+    unsafe {
+        view.destroy(gpu.device());
+        texture.destroy(gpu.device());
+    }
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn objects_are_named_and_regions_labeled() -> Result<()> {
+    let (backend, gpu) = null()?;
+    unsafe {
+        let buffer = host_buffer(&gpu, 64, vk::BufferUsageFlags::TRANSFER_DST)?;
+        gpu.set_name(buffer.buffer, "particles");
+        assert_eq!(
+            backend.object_name(buffer.buffer).as_deref(),
+            Some("particles")
+        );
+
+        gpu.execute(|device, command_buffer| {
+            gpu.begin_label(command_buffer, "clear", [1.0, 0.0, 0.0, 1.0]);
+            device.cmd_fill_buffer(command_buffer, buffer.buffer, 0, 64, 0);
+            gpu.end_label(command_buffer);
+            Ok(())
+        })?;
+        let commands = backend.commands();
+        assert!(matches!(
+            &commands[0],
+            TraceCommand::BeginLabel { name, color } if name == "clear" && color[0] == 1.0
+        ));
+        assert!(matches!(commands[2], TraceCommand::EndLabel));
+        assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+
+        // regions have to be balanced within a command buffer
+        gpu.execute(|_, command_buffer| {
+            gpu.end_label(command_buffer);
+            gpu.begin_label(command_buffer, "open", [0.0; 4]);
+            Ok(())
+        })?;
+        let errors = backend.errors();
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.contains("there is no label region to end")),
+            "{:?}",
+            errors
+        );
+        assert!(
+            errors
+                .iter()
+                .any(|e| e.contains("1 label regions were not ended")),
+            "{:?}",
+            errors
+        );
+
+        buffer.destroy(gpu.device());
+    }
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn renderdoc_captures_need_renderdoc() -> Result<()> {
+    // tests are not launched from RenderDoc, the captures do nothing
+    assert!(!deimos::debug::is_available());
+    deimos::debug::capture_on_validation_error(1);
+    deimos::debug::trigger_capture();
+    deimos::debug::start_capture();
+
+    let (backend, gpu) = null()?;
+    gpu.execute(|_, _| Ok(()))?;
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+
+    deimos::debug::end_capture();
+    deimos::debug::capture_on_validation_error(0);
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn multiview_passes_draw_every_layer_at_once() -> Result<()> {
+    let (backend, gpu) = null()?;
+    assert!(gpu.supports_multiview());
+    unsafe {
+        let device = gpu.device();
+        let layered = |format, usage, aspects| -> Result<_> {
+            let texture = gpu.create_array_texture(16, 16, 2, format, usage)?;
+            let view = texture.create_array_view(device, aspects, 2)?;
+            Ok((texture, view))
+        };
+        let (color, color_view) = layered(
+            FORMAT,
+            vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
+            vk::ImageAspectFlags::COLOR,
+        )?;
+        let (depth, depth_view) = layered(
+            vk::Format::D32_SFLOAT,
+            vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+            vk::ImageAspectFlags::DEPTH,
+        )?;
+
+        // both eyes in one pass, the view mask makes it a render pass of its own
+        let descriptor = RenderPassDescriptor::new(16, 16)
+            .color(ColorAttachment::new(&color_view, FORMAT))
+            .depth_stencil(DepthAttachment::new(&depth_view, vk::Format::D32_SFLOAT))
+            .multiview(0b11);
+        let single = RenderPassDescriptor::new(16, 16)
+            .color(ColorAttachment::new(&color_view, FORMAT))
+            .depth_stencil(DepthAttachment::new(&depth_view, vk::Format::D32_SFLOAT));
+        let render_pass = gpu.render_pass_for(&descriptor)?;
+        assert_ne!(gpu.render_pass_for(&single)?, render_pass);
+        gpu.execute(|_, command_buffer| {
+            gpu.begin_render_pass(command_buffer, &descriptor)?;
+            device.cmd_end_render_pass(command_buffer);
+            Ok(())
+        })?;
+
+        // devices without multiview refuse view masks
+        assert_eq!(
+            check_render_pass(&descriptor, false),
+            Err(DescriptionError::MultiviewUnsupported(0b11))
+        );
+
+        gpu.release_view(color_view.view);
+        gpu.release_view(depth_view.view);
+        for (texture, view) in [(color, color_view), (depth, depth_view)] {
+            view.destroy(device);
+            texture.destroy(device);
+        }
+    }
+    assert_eq!(backend.errors(), Vec::<String>::new());
+    gpu.destroy();
+    assert!(backend.errors().is_empty());
+    Ok(())
+}
+
+#[test]
+fn features_must_be_enabled_to_be_used() -> Result<()> {
+    let bc7 = vk::Format::BC7_UNORM_BLOCK;
+    let usage = vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::TRANSFER_DST;
+
+    // the null driver has bc but not etc2 textures
+    let (_backend, gpu) = null()?;
+    assert!(gpu
+        .adapter()
+        .features
+        .contains(&Feature::TextureCompressionBc));
+    assert!(!gpu
+        .adapter()
+        .features
+        .contains(&Feature::TextureCompressionEtc2));
+    assert!(gpu.features().is_empty());
+    let error = gpu.create_texture(16, 16, 1, bc7, usage).err().unwrap();
+    assert_eq!(
+        error.downcast_ref(),
+        Some(&DescriptionError::FeatureNotEnabled(
+            Feature::TextureCompressionBc
+        ))
+    );
+    assert!(gpu.require_feature(Feature::TimestampQuery).is_err());
+    gpu.destroy();
+
+    let backend = NullBackend::new();
+    let error =
+        HeadlessDevice::create_null_with_features(&backend, &[Feature::TextureCompressionEtc2])
+            .err()
+            .unwrap();
+    assert_eq!(
+        error.downcast_ref(),
+        Some(&DescriptionError::FeatureUnavailable(
+            Feature::TextureCompressionEtc2
+        ))
+    );
+
+    let features = [Feature::TextureCompressionBc, Feature::TimestampQuery];
+    let gpu = HeadlessDevice::create_null_with_features(&backend, &features)?;
+    gpu.require_feature(Feature::TimestampQuery)?;
+    let texture = gpu.create_texture(16, 16, 1, bc7, usage)?;
+    unsafe { texture.destroy(gpu.device()) };
+    gpu.destroy();
+    assert_eq!(backend.errors(), Vec::<String>::new());
+    Ok(())
+}
+
+#[test]
+fn textures_are_read_back_tightly_packed() -> Result<()> {
+    let (backend, gpu) = null()?;
+    let (width, height) = (5, 3);
+    let pixels = vec![255; (width * height * 4) as usize];
+    let (texture, view) = gpu.upload_texture(width, height, &pixels, TextureImport::Data)?;
+
+    // the copy leaves the texture readable by shaders, the rows have no padding
+    let layout = vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL;
+    let read = gpu.read_pixels(&texture, width, height, layout)?;
+    assert_eq!(
+        (read.width, read.height, read.format),
+        (width, height, FORMAT)
+    );
+    assert_eq!(read.data.len(), (width * height * 4) as usize);
+    let copies = backend
+        .commands()
+        .into_iter()
+        .filter_map(|c| match c {
+            TraceCommand::CopyImageToBuffer { image, regions, .. } if image == texture.image => {
+                Some(regions[0].buffer_row_length)
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(copies, [0]);
+
+    unsafe {
+        view.destroy(gpu.device());
+        texture.destroy(gpu.device());
+    }
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn read_pixels_convert_to_rgba8() -> Result<()> {
+    let pixels = |format, data| TexturePixels {
+        width: 1,
+        height: 1,
+        format,
+        data,
+    };
+    let bgra = pixels(vk::Format::B8G8R8A8_SRGB, vec![1, 2, 3, 4]);
+    assert_eq!(bgra.to_rgba8()?, [3, 2, 1, 4]);
+
+    // half floats are clamped, 1.0 is 0x3c00 and -2.0 is 0xc000
+    let halfs = [0x3c00u16, 0x3800, 0xc000, 0x7c00]
+        .iter()
+        .flat_map(|h| h.to_ne_bytes())
+        .collect();
+    let hdr = pixels(vk::Format::R16G16B16A16_SFLOAT, halfs);
+    assert_eq!(hdr.to_rgba8()?, [255, 128, 0, 255]);
+
+    let red = pixels(vk::Format::R8_UNORM, vec![7]);
+    assert_eq!(red.to_rgba8()?, [7, 0, 0, 255]);
+    assert!(pixels(vk::Format::BC7_UNORM_BLOCK, vec![0; 16])
+        .to_rgba8()
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn pipelines_are_built_for_what_their_pass_has() -> Result<()> {
+    let (backend, gpu) = null()?;
+    unsafe {
+        let device = gpu.device();
+        let texture =
+            gpu.create_texture(16, 16, 1, FORMAT, vk::ImageUsageFlags::COLOR_ATTACHMENT)?;
+        let view = texture.create_view(device, FORMAT, vk::ImageAspectFlags::COLOR, 1)?;
+        let descriptor =
+            RenderPassDescriptor::new(16, 16).color(ColorAttachment::new(&view, FORMAT));
+        let target = PipelineTarget::new(gpu.render_pass_for(&descriptor)?, &descriptor);
+        let layout =
+            device.create_pipeline_layout(&vk::PipelineLayoutCreateInfo::builder(), None)?;
+
+        // the defaults are enough for a pipeline, the shader modules don't outlive it
+        let pipeline = RenderPipelineBuilder::new(layout)
+            .vertex_shader(TRIANGLE_VERT)
+            .fragment_shader(SOLID_FRAG)
+            .blend(Blend::Alpha)
+            .build_for(device, &target)?;
+        assert_eq!(backend.live_objects(vk::ObjectType::PIPELINE), 1);
+        assert_eq!(backend.live_objects(vk::ObjectType::SHADER_MODULE), 0);
+        device.destroy_pipeline(pipeline, None);
+
+        // combinations the pass can't draw are refused before vulkan sees them
+        let refused = |builder: RenderPipelineBuilder| {
+            let error = builder.build_for(device, &target).unwrap_err();
+            error.downcast_ref::<DescriptionError>().cloned()
+        };
+        let builder = RenderPipelineBuilder::new(layout);
+        assert_eq!(
+            refused(builder.clone()),
+            Some(DescriptionError::MissingVertexShader)
+        );
+        let builder = builder.vertex_shader(TRIANGLE_VERT);
+        assert_eq!(
+            refused(builder.clone().fragment_shader(&[1, 2, 3])),
+            Some(DescriptionError::NotSpirv(vk::ShaderStageFlags::FRAGMENT))
+        );
+        assert_eq!(
+            refused(builder.clone().depth(true)),
+            Some(DescriptionError::NoDepthAttachment)
+        );
+        assert_eq!(
+            refused(builder.clone().blend(Blend::Additive)),
+            Some(DescriptionError::NothingToBlend(Blend::Additive))
+        );
+        let mut writes = builder.clone();
+        writes.depth_write = true;
+        assert_eq!(
+            refused(writes),
+            Some(DescriptionError::DepthWriteWithoutTest)
+        );
+        assert_eq!(backend.live_objects(vk::ObjectType::PIPELINE), 0);
+
+        device.destroy_pipeline_layout(layout, None);
+        view.destroy(device);
+        texture.destroy(device);
+    }
+    assert_eq!(backend.errors(), Vec::<String>::new());
+    gpu.destroy();
+    assert!(backend.errors().is_empty());
+    Ok(())
+}
+
+#[test]
+fn subpasses_read_what_earlier_subpasses_drew() -> Result<()> {
+    let (backend, gpu) = null()?;
+    unsafe {
+        let device = gpu.device();
+        let texture = |format, usage, aspects| -> Result<_> {
+            let texture = gpu.create_texture(16, 16, 1, format, usage)?;
+            let view = texture.create_view(device, format, aspects, 1)?;
+            Ok((texture, view))
+        };
+        let gbuffer_usage =
+            vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::INPUT_ATTACHMENT;
+        let (albedo, albedo_view) = texture(FORMAT, gbuffer_usage, vk::ImageAspectFlags::COLOR)?;
+        let (lit, lit_view) = texture(
+            FORMAT,
+            vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
+            vk::ImageAspectFlags::COLOR,
+        )?;
+        let (depth, depth_view) = texture(
+            vk::Format::D32_SFLOAT,
+            vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT | vk::ImageUsageFlags::INPUT_ATTACHMENT,
+            vk::ImageAspectFlags::DEPTH,
+        )?;
+
+        // a g-buffer, then lighting reading it and the depth at each pixel
+        let descriptor = RenderPassDescriptor::new(16, 16)
+            .color(ColorAttachment::new(&albedo_view, FORMAT).discard())
+            .color(ColorAttachment::new(&lit_view, FORMAT))
+            .depth_stencil(DepthAttachment::new(&depth_view, vk::Format::D32_SFLOAT))
+            .subpass(Subpass::new().color(0).depth_stencil())
+            .subpass(
+                Subpass::new()
+                    .color(1)
+                    .input(0)
+                    .depth_stencil()
+                    .depth_input(),
+            );
+        let render_pass = gpu.render_pass_for(&descriptor)?;
+        assert_ne!(
+            gpu.render_pass_for(&RenderPassDescriptor {
+                subpasses: vec![],
+                ..descriptor.clone()
+            })?,
+            render_pass
+        );
+
+        // pipelines are made for one subpass, lighting tests the depth it reads but can't
+        // write it
+        let layout =
+            device.create_pipeline_layout(&vk::PipelineLayoutCreateInfo::builder(), None)?;
+        let builder = RenderPipelineBuilder::new(layout)
+            .vertex_shader(TRIANGLE_VERT)
+            .fragment_shader(SOLID_FRAG);
+        let gbuffer = PipelineTarget::for_subpass(render_pass, &descriptor, 0)?;
+        let lighting = PipelineTarget::for_subpass(render_pass, &descriptor, 1)?;
+        let fill = builder.clone().depth(true).build_for(device, &gbuffer)?;
+        let light = builder
+            .clone()
+            .depth_read_only()
+            .build_for(device, &lighting)?;
+        let error = builder
+            .clone()
+            .depth(true)
+            .build_for(device, &lighting)
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<DescriptionError>(),
+            Some(&DescriptionError::DepthReadOnly)
+        );
+        assert_eq!(
+            PipelineTarget::for_subpass(render_pass, &descriptor, 2).unwrap_err(),
+            DescriptionError::UnknownSubpass(2)
+        );
+
+        // the encoder moves on and wants the pipeline of the next subpass
+        backend.clear();
+        gpu.execute(|_, command_buffer| {
+            let mut encoder = Encoder::new(device, command_buffer);
+            assert!(encoder.next_subpass().is_err());
+            gpu.begin_render_pass(command_buffer, &descriptor)?;
+            device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, fill);
+            device.cmd_next_subpass(command_buffer, vk::SubpassContents::INLINE);
+            device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, light);
+            device.cmd_end_render_pass(command_buffer);
+            Ok(())
+        })?;
+        assert!(backend
+            .commands()
+            .iter()
+            .any(|c| matches!(c, TraceCommand::NextSubpass { .. })));
+        assert_eq!(backend.errors(), Vec::<String>::new());
+
+        // the driver wants every subpass run, and no more
+        gpu.execute(|_, command_buffer| {
+            gpu.begin_render_pass(command_buffer, &descriptor)?;
+            device.cmd_end_render_pass(command_buffer);
+            Ok(())
+        })?;
+        assert_eq!(backend.errors().len(), 1);
+        backend.clear();
+
+        // subpasses refer to attachments of the pass and don't read what they draw
+        let unknown = descriptor.clone().subpass(Subpass::new().input(2));
+        assert_eq!(
+            check_render_pass(&unknown, false),
+            Err(DescriptionError::UnknownSubpassAttachment {
+                subpass: 2,
+                attachment: 2
+            })
+        );
+        let feedback = descriptor.clone().subpass(Subpass::new().color(1).input(1));
+        assert_eq!(
+            check_render_pass(&feedback, false),
+            Err(DescriptionError::SubpassFeedback {
+                subpass: 2,
+                attachment: 1
+            })
+        );
+
+        device.destroy_pipeline(fill, None);
+        device.destroy_pipeline(light, None);
+        device.destroy_pipeline_layout(layout, None);
+        for view in [&albedo_view, &lit_view, &depth_view] {
+            gpu.release_view(view.view);
+        }
+        for (texture, view) in [(albedo, albedo_view), (lit, lit_view), (depth, depth_view)] {
+            view.destroy(device);
+            texture.destroy(device);
+        }
+    }
+    assert_eq!(backend.errors(), Vec::<String>::new());
+    gpu.destroy();
+    assert!(backend.errors().is_empty());
+    Ok(())
+}
+
+#[test]
+fn passes_execute_draws_recorded_on_other_threads() -> Result<()> {
+    let (backend, gpu) = null()?;
+    unsafe {
+        let device = gpu.device();
+        let texture =
+            gpu.create_texture(16, 16, 1, FORMAT, vk::ImageUsageFlags::COLOR_ATTACHMENT)?;
+        let view = texture.create_view(device, FORMAT, vk::ImageAspectFlags::COLOR, 1)?;
+        let descriptor =
+            RenderPassDescriptor::new(16, 16).color(ColorAttachment::new(&view, FORMAT));
+        let render_pass = gpu.render_pass_for(&descriptor)?;
+        let layout =
+            device.create_pipeline_layout(&vk::PipelineLayoutCreateInfo::builder(), None)?;
+        let pipeline = RenderPipelineBuilder::new(layout)
+            .vertex_shader(TRIANGLE_VERT)
+            .fragment_shader(SOLID_FRAG)
+            .build_for(device, &PipelineTarget::new(render_pass, &descriptor))?;
+        let mut pools = (0..2)
+            .map(|_| SecondaryPool::create(device, gpu.queue_family()))
+            .collect::<Result<Vec<_>>>()?;
+
+        // every thread records its draws with a pool of its own, the pass executes them
+        let mut framebuffer = vk::Framebuffer::null();
+        gpu.execute(|_, command_buffer| {
+            let inheritance = gpu.begin_secondary_render_pass(command_buffer, &descriptor)?;
+            assert_eq!(inheritance.render_pass, render_pass);
+            framebuffer = inheritance.framebuffer;
+            let secondaries = std::thread::scope(|scope| {
+                let threads = pools
+                    .iter_mut()
+                    .map(|pool| {
+                        scope.spawn(move || -> Result<vk::CommandBuffer> {
+                            let secondary = pool.begin(device, &inheritance)?;
+                            let mut encoder = Encoder::continuing(device, secondary, &inheritance);
+                            encoder.bind_pipeline(vk::PipelineBindPoint::GRAPHICS, pipeline)?;
+                            encoder.draw(3, 1, 0, 0)?;
+                            assert!(encoder.end_render_pass().is_err());
+                            encoder.finish()?;
+                            pool.end(device, secondary)?;
+                            Ok(secondary)
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                threads
+                    .into_iter()
+                    .map(|t| t.join().unwrap())
+                    .collect::<Result<Vec<_>>>()
+            })?;
+            let mut encoder = Encoder::new(device, command_buffer);
+            encoder.execute_commands(&secondaries)?;
+            device.cmd_end_render_pass(command_buffer);
+            Ok(())
+        })?;
+        assert_eq!(backend.errors(), Vec::<String>::new());
+        assert!(backend.commands().iter().any(|c| matches!(
+            c,
+            TraceCommand::ExecuteCommands { command_buffers } if command_buffers.len() == 2
+        )));
+        assert_eq!(pools[0].buffers().len(), 1);
+
+        // a pass draws inline or executes secondaries, not both
+        let secondary = pools[0].buffers()[0];
+        gpu.execute(|_, command_buffer| {
+            gpu.begin_secondary_render_pass(command_buffer, &descriptor)?;
+            device.cmd_bind_pipeline(command_buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
+            device.cmd_draw(command_buffer, 3, 1, 0, 0);
+            device.cmd_end_render_pass(command_buffer);
+            gpu.begin_render_pass(command_buffer, &descriptor)?;
+            device.cmd_execute_commands(command_buffer, &[secondary]);
+            device.cmd_end_render_pass(command_buffer);
+            Ok(())
+        })?;
+        assert_eq!(backend.errors().len(), 2);
+        backend.clear();
+
+        // secondaries aren't submitted on their own, and the encoder keeps draws out of
+        // passes of secondaries
+        gpu.execute(|_, command_buffer| {
+            let mut encoder = Encoder::new(device, command_buffer);
+            encoder.bind_pipeline(vk::PipelineBindPoint::GRAPHICS, pipeline)?;
+            let clear_values = descriptor.clear_values();
+            let info = vk::RenderPassBeginInfo::builder()
+                .render_pass(render_pass)
+                .framebuffer(framebuffer)
+                .render_area(vk::Rect2D {
+                    offset: vk::Offset2D::default(),
+                    extent: descriptor.extent,
+                })
+                .clear_values(&clear_values);
+            encoder
+                .begin_render_pass_with(&info, vk::SubpassContents::SECONDARY_COMMAND_BUFFERS)?;
+            assert!(encoder.draw(3, 1, 0, 0).is_err());
+            encoder.end_render_pass()?;
+            encoder.finish()?;
+            Ok(())
+        })?;
+        assert_eq!(backend.errors(), Vec::<String>::new());
+        let submit = vk::SubmitInfo::builder().command_buffers(std::slice::from_ref(&secondary));
+        device.queue_submit(gpu.queue(), &[submit], vk::Fence::null())?;
+        assert_eq!(backend.errors().len(), 1);
+        backend.clear();
+
+        gpu.wait_idle()?;
+        for pool in &mut pools {
+            pool.reset(device)?;
+            assert!(pool.buffers().is_empty());
+            pool.destroy(device);
+        }
+        device.destroy_pipeline(pipeline, None);
+        device.destroy_pipeline_layout(layout, None);
+        gpu.release_view(view.view);
+        view.destroy(device);
+        texture.destroy(device);
+    }
+    assert_eq!(backend.errors(), Vec::<String>::new());
+    gpu.destroy();
+    assert!(backend.errors().is_empty());
+    Ok(())
+}
+
+#[test]
+fn device_work_is_awaited_on_any_executor() -> Result<()> {
+    let (backend, gpu) = null()?;
+    let timeline = gpu.create_timeline()?;
+    let buffer = host_buffer(&gpu, 16, vk::BufferUsageFlags::TRANSFER_DST)?;
+    unsafe {
+        let device = gpu.device();
+        let flags = vk::CommandPoolCreateFlags::empty();
+        let pool = CommandPool::create(device, gpu.queue_family(), flags)?;
+        let command_buffer = pool.allocate(device, vk::CommandBufferLevel::PRIMARY, 1)?[0].buffer;
+        device.begin_command_buffer(command_buffer, &vk::CommandBufferBeginInfo::builder())?;
+        device.cmd_fill_buffer(command_buffer, buffer.buffer, 0, 16, 7);
+        device.end_command_buffer(command_buffer)?;
+
+        // the read waits for the fill, polling never blocks
+        let work = timeline.work_done(device, 1);
+        let mut read = pin!(GpuFuture::new(work, || buffer.read::<u32>(device, 0, 4)));
+        let mut context = Context::from_waker(Waker::noop());
+        assert!(read.as_mut().poll(&mut context).is_pending());
+        timeline.submit(device, gpu.queue(), &[command_buffer], &[], &[], &[])?;
+        assert!(matches!(
+            read.as_mut().poll(&mut context),
+            Poll::Ready(Ok(values)) if values == [7; 4]
+        ));
+        let work = timeline.work_done(device, 1);
+        assert_eq!(GpuFuture::new(work, || Ok(1)).wait()?, 1);
+
+        // pipelines are built on a thread of their own that wakes the task
+        let (texture, view, render_pass, framebuffer) = create_target(&gpu, 16, 16)?;
+        let layout =
+            device.create_pipeline_layout(&vk::PipelineLayoutCreateInfo::builder(), None)?;
+        let target = PipelineTarget {
+            render_pass,
+            subpass: 0,
+            samples: vk::SampleCountFlags::_1,
+            color_attachments: 1,
+            depth: false,
+            depth_read_only: false,
+        };
+        let builder = RenderPipelineBuilder::new(layout)
+            .vertex_shader(TRIANGLE_VERT)
+            .fragment_shader(SOLID_FRAG);
+        let pipeline = block_on(builder.build_async(device, target)?)?;
+        assert!(!pipeline.is_null());
+        let other = builder.build_async(device, target)?.wait()?;
+        assert_ne!(other, pipeline);
+
+        // the check runs before the thread is started
+        let error = builder
+            .depth(true)
+            .build_async(device, target)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.downcast_ref::<DescriptionError>(),
+            Some(&DescriptionError::NoDepthAttachment)
+        );
+
+        for pipeline in [pipeline, other] {
+            device.destroy_pipeline(pipeline, None);
+        }
+        device.destroy_pipeline_layout(layout, None);
+        device.destroy_framebuffer(framebuffer, None);
+        device.destroy_render_pass(render_pass, None);
+        view.destroy(device);
+        texture.destroy(device);
+        pool.destroy(device);
+        buffer.destroy(device);
+        timeline.destroy(device);
+    }
+    assert_eq!(backend.errors(), Vec::<String>::new());
+    gpu.destroy();
+    assert!(backend.errors().is_empty());
+    Ok(())
+}
+
+#[test]
+fn devices_present_frames_to_headless_windows() -> Result<()> {
+    let (backend, window, mut device) = windowed(64, 48)?;
+    assert_eq!(
+        device.extent(),
+        vk::Extent2D {
+            width: 64,
+            height: 48
+        }
+    );
+    for _ in 0..3 {
+        present(&mut device, &window)?;
+    }
+    assert_eq!(backend.submissions().len(), 3);
+
+    // the swapchain follows the window once the size settled
+    let window = HeadlessWindow::new(32, 32);
+    device.resized(32, 32);
+    device.set_resize_debounce(Duration::ZERO);
+    present(&mut device, &window)?;
+    present(&mut device, &window)?;
+    assert_eq!(
+        device.extent(),
+        vk::Extent2D {
+            width: 32,
+            height: 32
+        }
+    );
+
+    device.destroy();
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    assert_eq!(backend.live_objects(vk::ObjectType::DEVICE_MEMORY), 0);
+    Ok(())
+}
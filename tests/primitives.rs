@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: MIT
+
+//! The shapes generated on the cpu, checked for their size, facing and texture coordinates.
+
+use cgmath::InnerSpace;
+use deimos::math::{Vec2, Vec3};
+use deimos::rendering::Primitive;
+
+// every triangle that isn't degenerate winds counter clockwise seen from the side its
+// normals point to
+fn assert_faces_out(primitive: &Primitive) {
+    assert_eq!(primitive.normals.len(), primitive.positions.len());
+    assert_eq!(primitive.texels.len(), primitive.positions.len());
+    assert_eq!(primitive.indices.len() % 3, 0);
+    for triangle in primitive.indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| triangle[i] as usize);
+        let p = &primitive.positions;
+        let face = (p[b] - p[a]).cross(p[c] - p[a]);
+        if face.magnitude() < 1e-6 {
+            continue;
+        }
+        let normal = primitive.normals[a] + primitive.normals[b] + primitive.normals[c];
+        assert!(face.dot(normal) > 0.0, "{:?} faces in", triangle);
+    }
+    for normal in &primitive.normals {
+        assert!((normal.magnitude() - 1.0).abs() < 1e-4);
+    }
+    for texel in &primitive.texels {
+        assert!((0.0..=1.0).contains(&texel.x) && (0.0..=1.0).contains(&texel.y));
+    }
+}
+
+fn extent(primitive: &Primitive) -> (Vec3, Vec3) {
+    let first = primitive.positions[0];
+    primitive
+        .positions
+        .iter()
+        .fold((first, first), |(min, max), p| {
+            (
+                Vec3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+                Vec3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+            )
+        })
+}
+
+fn assert_near(a: Vec3, b: Vec3) {
+    assert!((a - b).magnitude() < 1e-4, "{:?} != {:?}", a, b);
+}
+
+#[test]
+fn cubes_and_planes_are_split_into_quads() {
+    let cube = Primitive::cube(2.0, 3);
+    assert_faces_out(&cube);
+    assert_eq!(cube.positions.len(), 6 * 16);
+    assert_eq!(cube.indices.len(), 6 * 9 * 6);
+    let (min, max) = extent(&cube);
+    assert_near(min, Vec3::new(-1.0, -1.0, -1.0));
+    assert_near(max, Vec3::new(1.0, 1.0, 1.0));
+
+    // the front face reads upright, its top left is the start of the texture
+    let corner = cube
+        .positions
+        .iter()
+        .position(|p| (p - Vec3::new(-1.0, 1.0, 1.0)).magnitude() < 1e-4)
+        .unwrap();
+    assert_eq!(cube.texels[corner], Vec2::new(0.0, 0.0));
+
+    let plane = Primitive::plane(Vec2::new(4.0, 2.0), (4, 2));
+    assert_faces_out(&plane);
+    assert_eq!(plane.positions.len(), 5 * 3);
+    assert_eq!(plane.indices.len(), 8 * 6);
+    assert!(plane.normals.iter().all(|n| *n == Vec3::unit_y()));
+    let (min, max) = extent(&plane);
+    assert_near(min, Vec3::new(-2.0, 0.0, -1.0));
+    assert_near(max, Vec3::new(2.0, 0.0, 1.0));
+}
+
+#[test]
+fn round_shapes_follow_their_tessellation() {
+    let sphere = Primitive::sphere(0.5, 16, 8);
+    assert_faces_out(&sphere);
+    assert_eq!(sphere.positions.len(), 17 * 9);
+    for (position, normal) in sphere.positions.iter().zip(&sphere.normals) {
+        assert_near(*position, normal * 0.5);
+    }
+
+    let torus = Primitive::torus(1.0, 0.25, 24, 12);
+    assert_faces_out(&torus);
+    assert_eq!(torus.indices.len(), 24 * 12 * 6);
+    let (min, max) = extent(&torus);
+    assert_near(min, Vec3::new(-1.25, -0.25, -1.25));
+    assert_near(max, Vec3::new(1.25, 0.25, 1.25));
+
+    // too few counts are raised to what the shape needs
+    let coarse = Primitive::sphere(1.0, 0, 0);
+    assert_faces_out(&coarse);
+    assert_eq!(coarse.positions.len(), 4 * 3);
+}
+
+#[test]
+fn capsules_are_spheres_stretched_along_y() {
+    let capsule = Primitive::capsule(0.5, 3.0, 12, 4);
+    assert_faces_out(&capsule);
+    let (min, max) = extent(&capsule);
+    assert_near(min, Vec3::new(-0.5, -1.5, -0.5));
+    assert_near(max, Vec3::new(0.5, 1.5, 0.5));
+    assert_eq!(capsule.positions.len(), 13 * 10);
+
+    // the texture runs down evenly, the cylinder takes the length it has
+    let equators = capsule
+        .positions
+        .iter()
+        .zip(&capsule.texels)
+        .filter(|(p, t)| t.x == 0.0 && (p.x - 0.5).abs() < 1e-4)
+        .map(|(p, t)| (p.y, t.y))
+        .collect::<Vec<_>>();
+    let length = std::f32::consts::PI * 0.5 + 2.0;
+    let top = std::f32::consts::FRAC_PI_2 * 0.5 / length;
+    assert_eq!(equators.len(), 2);
+    assert!((equators[0].0 - 1.0).abs() < 1e-4 && (equators[0].1 - top).abs() < 1e-4);
+    assert!((equators[1].0 + 1.0).abs() < 1e-4 && (equators[1].1 - (1.0 - top)).abs() < 1e-4);
+
+    // without room for a cylinder it is a sphere
+    let sphere = Primitive::capsule(0.5, 0.5, 12, 4);
+    let (min, max) = extent(&sphere);
+    assert_near(min, Vec3::new(-0.5, -0.5, -0.5));
+    assert_near(max, Vec3::new(0.5, 0.5, 0.5));
+}
+
+#[test]
+fn primitives_become_vertices_of_either_kind() {
+    let cube = Primitive::cube(1.0, 1);
+    let lit = cube.surface_vertices();
+    assert_eq!(lit.len(), cube.positions.len());
+    for vertex in &lit {
+        // tangents lie in the faces
+        assert!(vertex.tangent.truncate().dot(vertex.normal).abs() < 1e-4);
+        assert_eq!(vertex.tangent.w.abs(), 1.0);
+    }
+
+    let color = Vec3::new(1.0, 0.5, 0.0);
+    let unlit = cube.vertices(color);
+    assert!(unlit.iter().all(|v| v.color == color));
+    assert_eq!(unlit[3].texel, cube.texels[3]);
+}
@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: MIT
+
+//! The cameras of reflection probe faces. drawing the faces needs a windowed device and is
+//! left to the demo.
+
+use cgmath::{vec3, InnerSpace, Vector3, Vector4};
+use deimos::rendering::{cube_face_camera, CUBE_FACES};
+
+// the major axis and the directions of s and t of each face in the vulkan cube layout
+const FACES: [([f32; 3], [f32; 3], [f32; 3]); CUBE_FACES] = [
+    ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, -1.0, 0.0]),
+    ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, -1.0, 0.0]),
+    ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+    ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+    ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+    ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+];
+
+#[test]
+fn faces_are_drawn_mirrored_against_the_cube_layout() {
+    let position = vec3(1.0, 2.0, -3.0);
+    for (face, (major, s, t)) in FACES.into_iter().enumerate() {
+        let camera = cube_face_camera(position, face, 0.1, 10.0);
+        let (view, proj) = camera.matrices(64, 64);
+        assert!((camera.forward() - Vector3::from(major)).magnitude() < 1e-5);
+
+        for (a, b) in [(0.5, 0.5), (-0.5, 0.5), (0.5, -0.5), (-0.25, -0.75)] {
+            let direction = Vector3::from(major) + Vector3::from(s) * a + Vector3::from(t) * b;
+            let clip: Vector4<f32> = proj * view * (position + direction).extend(1.0);
+
+            // the blit into the cube mirrors x, y already matches
+            let (x, y) = (clip.x / clip.w, clip.y / clip.w);
+            assert!((x + a).abs() < 1e-4, "face {} x {} for s {}", face, x, a);
+            assert!((y - b).abs() < 1e-4, "face {} y {} for t {}", face, y, b);
+        }
+    }
+}
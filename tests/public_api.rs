@@ -0,0 +1,482 @@
+// SPDX-License-Identifier: MIT
+
+//! A snapshot of the public items of the public modules with their signatures. when this
+//! fails either put the item back or, for a deliberate change, regenerate the snapshot and
+//! bump the version following semver: `UPDATE_PUBLIC_API=1 cargo test --test public_api`.
+//!
+//! The items are read from the sources, not from rustdoc, which has no stable output to
+//! compare against. so the snapshot is only as good as these rules:
+//! - the public modules are the `pub mod` lines of `lib.rs`. in modules split over files
+//!   the items of a file count when `mod.rs` re-exports them with `pub use self::file::..`,
+//!   by name or glob, and re-exports marked `#[doc(hidden)]` don't count.
+//! - an item is public when its line starts with `pub `, `pub(crate)` and friends never are.
+//!   members count for the public types of the crate, trait impls and macros are left out.
+//! - a `#[cfg(..)]` right above an item, or above the `pub mod` of its module, is kept with
+//!   its signature. the snapshot is the same for every feature set, so the test passes with
+//!   and without default features, and items behind a feature show up in both.
+//! - types named in public signatures but not exported themselves aren't found here, the
+//!   `unnameable_types` lint of the crate refuses those.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::PathBuf;
+
+const SNAPSHOT: &str = "tests/public_api.txt";
+
+const KINDS: &[&str] = &["fn", "struct", "enum", "trait", "type", "const", "static"];
+
+fn root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+// the `#[cfg(..)]` attributes between `lines[index]` and the docs above it
+fn cfg_above(lines: &[&str], index: usize) -> Option<String> {
+    let cfgs = lines[..index]
+        .iter()
+        .rev()
+        .map(|line| line.trim())
+        .take_while(|line| line.starts_with("#[") || line.starts_with("///"))
+        .filter(|line| line.starts_with("#[cfg("))
+        .collect::<Vec<_>>();
+    (!cfgs.is_empty()).then(|| cfgs.into_iter().rev().collect::<Vec<_>>().join(" "))
+}
+
+fn with_cfg(signature: String, cfg: Option<&str>) -> String {
+    match cfg {
+        Some(cfg) => format!("{} {}", signature, cfg),
+        None => signature,
+    }
+}
+
+// the files of `mod.rs` and the names each exports, none for a glob. hidden re-exports are
+// left out
+fn exports(source: &str) -> BTreeMap<String, Option<BTreeSet<String>>> {
+    let lines = source.lines().collect::<Vec<_>>();
+    let mut exports = BTreeMap::new();
+    for (index, line) in lines.iter().enumerate() {
+        let Some(path) = line.strip_prefix("pub use self::") else {
+            continue;
+        };
+        let hidden = lines[..index]
+            .iter()
+            .rev()
+            .take_while(|line| line.starts_with("#["))
+            .any(|line| *line == "#[doc(hidden)]");
+        if hidden {
+            continue;
+        }
+
+        // the use may be split over several lines
+        let statement = lines[index..]
+            .iter()
+            .map(|line| line.trim())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let statement = &statement[..statement.find(';').unwrap()];
+        let statement = &statement["pub use self::".len()..];
+        let file = identifier(path).to_string();
+        let names = &statement[file.len() + "::".len()..];
+        let names = match (names.find('{'), names.rfind('}')) {
+            (Some(open), Some(close)) => Some(
+                names[open + 1..close]
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|n| !n.is_empty())
+                    .map(String::from)
+                    .collect::<BTreeSet<_>>(),
+            ),
+            _ if names == "*" => None,
+            _ => Some(BTreeSet::from([names.to_string()])),
+        };
+        let entry = exports.entry(file).or_insert(Some(BTreeSet::new()));
+        match (entry, names) {
+            (Some(all), Some(names)) => all.extend(names),
+            (entry, _) => *entry = None,
+        }
+    }
+    exports
+}
+
+// a source file of a public module
+struct Source {
+    prefix: String,
+    cfg: Option<String>,
+    text: String,
+    // the names the module exports from the file, none for all
+    names: Option<BTreeSet<String>>,
+}
+
+impl Source {
+    fn exports(&self, name: &str) -> bool {
+        self.names.as_ref().is_none_or(|names| names.contains(name))
+    }
+}
+
+// the sources of the `pub mod` lines of `lib.rs` and `lib.rs` itself
+fn public_sources() -> Vec<Source> {
+    let read = |path: PathBuf| fs::read_to_string(path).unwrap();
+    let lib = read(root().join("src/lib.rs"));
+    let lines = lib.lines().collect::<Vec<_>>();
+    let mut sources = vec![Source {
+        prefix: "deimos".to_string(),
+        cfg: None,
+        text: lib.clone(),
+        names: None,
+    }];
+    for (index, line) in lines.iter().enumerate() {
+        let Some(module) = line.strip_prefix("pub mod ") else {
+            continue;
+        };
+        let module = identifier(module);
+        if module == "prelude" {
+            continue;
+        }
+        let cfg = cfg_above(&lines, index);
+        let dir = root().join("src").join(module);
+        if !dir.is_dir() {
+            sources.push(Source {
+                prefix: module.to_string(),
+                cfg,
+                text: read(root().join("src").join(format!("{}.rs", module))),
+                names: None,
+            });
+            continue;
+        }
+
+        let text = read(dir.join("mod.rs"));
+        for (file, names) in exports(&text) {
+            sources.push(Source {
+                prefix: module.to_string(),
+                cfg: cfg.clone(),
+                text: read(dir.join(format!("{}.rs", file))),
+                names,
+            });
+        }
+        sources.push(Source {
+            prefix: module.to_string(),
+            cfg,
+            text,
+            names: None,
+        });
+    }
+    sources
+}
+
+fn identifier(text: &str) -> &str {
+    let end = text
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(text.len());
+    &text[..end]
+}
+
+// `pub [unsafe] [const] <kind> <name>` at the start of `line`
+fn declaration(line: &str) -> Option<(&'static str, &str)> {
+    let mut rest = line.strip_prefix("pub ")?;
+    for qualifier in ["unsafe ", "const fn", "async "] {
+        if qualifier == "const fn" && rest.starts_with(qualifier) {
+            rest = &rest["const ".len()..];
+        } else if let Some(stripped) = rest.strip_prefix(qualifier) {
+            rest = stripped;
+        }
+    }
+    KINDS.iter().find_map(|kind| {
+        let name = rest.strip_prefix(kind)?.strip_prefix(' ')?;
+        Some((*kind, identifier(name)))
+    })
+}
+
+// the type an `impl` line at column zero is for, trait impls are left out
+fn impl_target(line: &str) -> Option<&str> {
+    let mut rest = line.strip_prefix("impl")?;
+    if rest.starts_with('<') {
+        let mut depth = 0;
+        let end = rest.find(|c| {
+            match c {
+                '<' => depth += 1,
+                '>' => depth -= 1,
+                _ => {}
+            }
+            depth == 0
+        })?;
+        rest = &rest[end + 1..];
+    }
+    let rest = rest.trim_start();
+    (!rest.contains(" for ")).then(|| identifier(rest))
+}
+
+// the declaration starting at `lines[0]` on one line, without its body or value. signatures
+// rustfmt split over several lines are joined
+fn signature(kind: &str, lines: &[&str]) -> String {
+    let mut text = String::new();
+    for line in lines {
+        text.push(' ');
+        text.push_str(line.trim());
+        let end = line.trim_end();
+        if end.ends_with('{') || end.ends_with(';') || end.ends_with('}') || kind != "fn" {
+            break;
+        }
+    }
+
+    let cut = match kind {
+        "const" | "static" => text.find(" = ").or_else(|| text.find(';')),
+        _ => text.find('{').or_else(|| text.find(';')),
+    };
+    let text = &text[..cut.unwrap_or(text.len())];
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    text.replace("( ", "(")
+        .replace(", )", ")")
+        .replace(",)", ")")
+        .trim_end_matches(',')
+        .to_string()
+}
+
+// the types declared `pub` and exported in `source`, only their inherent impls are part of
+// the api
+fn public_types(source: &Source, types: &mut BTreeSet<String>) {
+    for line in source.text.lines() {
+        if let Some((kind, name)) = declaration(line) {
+            if ["struct", "enum", "trait", "type"].contains(&kind) && source.exports(name) {
+                types.insert(name.to_string());
+            }
+        }
+    }
+}
+
+fn module_items(source: &Source, types: &BTreeSet<String>, items: &mut BTreeSet<String>) {
+    let prefix = &source.prefix;
+    // the struct, enum or impl the indented lines belong to
+    let mut context: Option<(String, bool)> = None;
+    let lines = source.text.lines().collect::<Vec<_>>();
+    for (index, line) in lines.iter().enumerate() {
+        // the cfg of the module and of the item
+        let cfg = [source.cfg.clone(), cfg_above(&lines, index)]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let cfg = (!cfg.is_empty()).then_some(cfg.as_str());
+
+        if line.starts_with('}') {
+            context = None;
+            continue;
+        }
+
+        if !line.starts_with(' ') {
+            if let Some((kind, name)) = declaration(line) {
+                if !source.exports(name) {
+                    context = None;
+                    continue;
+                }
+                let signature = with_cfg(signature(kind, &lines[index..]), cfg);
+                items.insert(format!("{}::{}: {}", prefix, name, signature));
+                let open = line.trim_end().ends_with('{');
+                context = match kind {
+                    "struct" | "enum" if open => Some((name.to_string(), kind == "enum")),
+                    _ => None,
+                };
+            } else if let Some(target) = impl_target(line) {
+                context = types.contains(target).then(|| (target.to_string(), false));
+            }
+            continue;
+        }
+
+        // members are indented by one level, deeper lines are bodies
+        let Some((owner, is_enum)) = &context else {
+            continue;
+        };
+        let Some(member) = line.strip_prefix("    ").filter(|m| !m.starts_with(' ')) else {
+            continue;
+        };
+        if let Some((kind, name)) = declaration(member) {
+            let signature = with_cfg(signature(kind, &lines[index..]), cfg);
+            items.insert(format!("{}::{}::{}: {}", prefix, owner, name, signature));
+        } else if member.starts_with("pub ") {
+            let field = member.trim_end().trim_end_matches(',');
+            let name = identifier(&field["pub ".len()..]);
+            let field = with_cfg(field.to_string(), cfg);
+            items.insert(format!("{}::{}::{}: {}", prefix, owner, name, field));
+        } else if *is_enum && member.starts_with(|c: char| c.is_ascii_uppercase()) {
+            let variant = member.trim_end().trim_end_matches(['{', ',']).trim_end();
+            let variant = with_cfg(variant.to_string(), cfg);
+            items.insert(format!(
+                "{}::{}::{}: {}",
+                prefix,
+                owner,
+                identifier(member),
+                variant
+            ));
+        }
+    }
+}
+
+fn prelude_items(source: &str, items: &mut BTreeSet<String>) {
+    // the names between the braces of each `pub use`
+    let lines = source.lines().collect::<Vec<_>>();
+    for (index, line) in lines.iter().enumerate() {
+        if !line.starts_with("pub use ") {
+            continue;
+        }
+        let statement = lines[index..].join(" ");
+        let block = &statement["pub use ".len()..statement.find(';').unwrap()];
+        let names = match (block.find('{'), block.rfind('}')) {
+            (Some(open), Some(close)) => &block[open + 1..close],
+            _ => block.rsplit("::").next().unwrap(),
+        };
+        let cfg = cfg_above(&lines, index);
+        for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            items.insert(with_cfg(format!("prelude::{}", name), cfg.as_deref()));
+        }
+    }
+}
+
+fn public_api() -> String {
+    let sources = public_sources();
+
+    let mut types = BTreeSet::new();
+    for source in &sources {
+        public_types(source, &mut types);
+    }
+
+    let mut items = BTreeSet::new();
+    for source in &sources {
+        module_items(source, &types, &mut items);
+    }
+    prelude_items(
+        &fs::read_to_string(root().join("src/prelude.rs")).unwrap(),
+        &mut items,
+    );
+    items.into_iter().map(|item| item + "\n").collect()
+}
+
+#[test]
+fn public_api_matches_snapshot() {
+    let current = public_api();
+    let path = root().join(SNAPSHOT);
+    if std::env::var_os("UPDATE_PUBLIC_API").is_some() {
+        fs::write(&path, &current).unwrap();
+        return;
+    }
+
+    let snapshot = fs::read_to_string(&path).unwrap_or_default();
+    let before = snapshot.lines().collect::<BTreeSet<_>>();
+    let after = current.lines().collect::<BTreeSet<_>>();
+    let removed = before.difference(&after).collect::<Vec<_>>();
+    let added = after.difference(&before).collect::<Vec<_>>();
+    assert!(
+        removed.is_empty() && added.is_empty(),
+        "the public api changed.\nremoved: {:#?}\nadded: {:#?}",
+        removed,
+        added
+    );
+}
+
+#[test]
+fn snapshot_keeps_signatures_of_public_types() {
+    let source = "pub struct Open {
+    pub size: u64,
+}
+
+pub(crate) struct Hidden;
+
+impl Open {
+    pub fn resize<F>(
+        &mut self,
+        size: u64,
+        done: F,
+    ) -> bool
+    where
+        F: FnOnce(),
+    {
+        true
+    }
+}
+
+impl Hidden {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+pub const LIMIT: u32 = 4;
+";
+    let source = Source {
+        prefix: "gfx".to_string(),
+        cfg: None,
+        text: source.to_string(),
+        names: None,
+    };
+    let mut types = BTreeSet::new();
+    public_types(&source, &mut types);
+    let mut items = BTreeSet::new();
+    module_items(&source, &types, &mut items);
+    assert_eq!(
+        items.into_iter().collect::<Vec<_>>(),
+        [
+            "gfx::LIMIT: pub const LIMIT: u32",
+            "gfx::Open: pub struct Open",
+            "gfx::Open::resize: pub fn resize<F>(&mut self, size: u64, done: F) -> bool where F: FnOnce()",
+            "gfx::Open::size: pub size: u64",
+        ]
+    );
+}
+
+#[test]
+fn snapshot_follows_exports_and_keeps_cfgs() {
+    let module = "mod device;
+mod null;
+
+pub use self::device::{Device, Frame};
+#[doc(hidden)]
+pub use self::null::*;
+";
+    let exports = exports(module);
+    assert_eq!(exports.len(), 1);
+
+    let source = "pub struct Device;
+
+impl Device {
+    /// the window.
+    #[cfg(feature = \"window\")]
+    pub fn window(&self) {}
+}
+
+/// left out, `mod.rs` doesn't export it.
+pub struct Internal;
+
+impl Internal {
+    pub fn new() -> Self {
+        Self
+    }
+}
+";
+    let source = Source {
+        prefix: "gfx".to_string(),
+        cfg: Some("#[cfg(feature = \"gfx\")]".to_string()),
+        text: source.to_string(),
+        names: exports["device"].clone(),
+    };
+    let mut types = BTreeSet::new();
+    public_types(&source, &mut types);
+    let mut items = BTreeSet::new();
+    module_items(&source, &types, &mut items);
+    assert_eq!(
+        items.into_iter().collect::<Vec<_>>(),
+        [
+            "gfx::Device: pub struct Device #[cfg(feature = \"gfx\")]",
+            "gfx::Device::window: pub fn window(&self) #[cfg(feature = \"gfx\")] #[cfg(feature = \"window\")]",
+        ]
+    );
+}
+
+#[test]
+fn prelude_covers_an_app() {
+    // the demo needs nothing beyond the prelude to set up a frame
+    use deimos::prelude::*;
+
+    let selection = AdapterSelection::Power(PowerPreference::LowPower);
+    let mut clock = Clock::default();
+    clock.pause();
+    assert!(clock.is_paused());
+    assert_eq!(selection.choose(&[]), None);
+    assert!(!deimos::VERSION.is_empty());
+}
@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: MIT
+
+//! The queue families a device picks for its dedicated compute and transfer queues and
+//! shares its resources across.
+
+use deimos::gfx::{dedicated_queue_families, shared_queue_families};
+use vulkanalia::prelude::v1_0::*;
+
+fn family(flags: vk::QueueFlags, queue_count: u32) -> vk::QueueFamilyProperties {
+    vk::QueueFamilyProperties {
+        queue_flags: flags,
+        queue_count,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn dedicated_families_exclude_graphics() {
+    let all = vk::QueueFlags::GRAPHICS | vk::QueueFlags::COMPUTE | vk::QueueFlags::TRANSFER;
+    let compute = vk::QueueFlags::COMPUTE | vk::QueueFlags::TRANSFER;
+    let transfer = vk::QueueFlags::TRANSFER | vk::QueueFlags::SPARSE_BINDING;
+
+    // a desktop gpu with async compute and a dma engine
+    let families = [family(all, 16), family(transfer, 2), family(compute, 8)];
+    assert_eq!(dedicated_queue_families(&families), (Some(2), Some(1)));
+
+    // a single family does everything, graphics takes the work
+    assert_eq!(dedicated_queue_families(&[family(all, 1)]), (None, None));
+
+    // families without queues don't count
+    let families = [family(all, 1), family(compute, 0), family(transfer, 1)];
+    assert_eq!(dedicated_queue_families(&families), (None, Some(2)));
+}
+
+#[test]
+fn resources_are_shared_across_distinct_families() {
+    assert_eq!(shared_queue_families(0, Some(2), Some(1)), [0, 2, 1]);
+    assert_eq!(shared_queue_families(0, None, Some(2)), [0, 2]);
+
+    // graphics alone owns everything, resources stay exclusive
+    assert!(shared_queue_families(0, None, None).is_empty());
+    assert!(shared_queue_families(1, Some(1), None).is_empty());
+}
@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: MIT
+
+//! The pixel coordinates of 2d drawing, logical pixels from the top left of the window.
+
+use cgmath::{vec2, vec4};
+use deimos::math::Vec2;
+use deimos::rendering::{ScreenSpace, Sprite};
+use vulkanalia::prelude::v1_0::*;
+
+fn screen_of(width: u32, height: u32, scale_factor: f64) -> ScreenSpace {
+    ScreenSpace::new(vk::Extent2D { width, height }, scale_factor)
+}
+
+fn close(a: Vec2, b: Vec2) -> bool {
+    (a.x - b.x).abs() < 1e-4 && (a.y - b.y).abs() < 1e-4
+}
+
+#[test]
+fn pixels_map_to_the_window_corners() {
+    let screen = screen_of(1600, 1200, 2.0);
+    assert_eq!(screen.size, vec2(800.0, 600.0));
+
+    // the top left is -1, -1 in vulkan, y grows down
+    assert!(close(screen.to_ndc(vec2(0.0, 0.0)), vec2(-1.0, -1.0)));
+    assert!(close(screen.to_ndc(vec2(800.0, 600.0)), vec2(1.0, 1.0)));
+    assert!(close(screen.to_ndc(vec2(400.0, 150.0)), vec2(0.0, -0.5)));
+    assert!(close(screen.from_ndc(vec2(0.0, -0.5)), vec2(400.0, 150.0)));
+
+    // the projection agrees and keeps z as the depth
+    let clip = screen.proj() * vec4(400.0, 150.0, 0.25, 1.0);
+    assert!(close(clip.truncate().truncate(), vec2(0.0, -0.5)));
+    assert_eq!((clip.z, clip.w), (0.25, 1.0));
+}
+
+#[test]
+fn physical_pixels_follow_the_scale_factor() {
+    let screen = screen_of(1500, 900, 1.5);
+    assert_eq!(screen.from_physical(vec2(300.0, 150.0)), vec2(200.0, 100.0));
+    assert_eq!(screen.to_physical(vec2(200.0, 100.0)), vec2(300.0, 150.0));
+
+    // rectangles grow to the physical pixels they touch
+    let rect = screen.physical_rect(vec2(10.5, 0.0), vec2(1.0, 2.0));
+    assert_eq!(rect.offset, vk::Offset2D { x: 15, y: 0 });
+    assert_eq!(
+        rect.extent,
+        vk::Extent2D {
+            width: 3,
+            height: 3
+        }
+    );
+
+    // a missing scale factor is taken as 1
+    assert_eq!(screen_of(0, 0, 0.0).size, vec2(1.0, 1.0));
+    assert_eq!(screen_of(640, 480, 0.0).scale_factor, 1.0);
+}
+
+#[test]
+fn pixel_sprites_hang_from_their_top_left() {
+    let sprite = Sprite::pixels(vec2(20.0, 30.0), vec2(64.0, 32.0));
+    assert_eq!(sprite.position.truncate(), vec2(20.0, 30.0));
+
+    // the corners as the sprite shader places them, the unit square around the pivot
+    let corner = |x: f32, y: f32| {
+        sprite.position.truncate()
+            + vec2(
+                (x - sprite.pivot.x) * sprite.size.x,
+                (y - sprite.pivot.y) * sprite.size.y,
+            )
+    };
+    assert_eq!(corner(0.0, 1.0), vec2(20.0, 30.0));
+    assert_eq!(corner(1.0, 0.0), vec2(84.0, 62.0));
+}
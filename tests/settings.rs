@@ -0,0 +1,144 @@
+// SPDX-License-Identifier: MIT
+
+//! Graphics settings applied to texture samplers and to the size textures are loaded at, and
+//! the validation settings devices are created with.
+
+use deimos::gfx::{
+    shrink_pixels, GraphicsSettings, SamplerDescriptor, ValidationSettings, ValidationSeverity,
+    VALIDATION_VARIABLE,
+};
+use vulkanalia::vk;
+
+#[test]
+fn settings_are_checked() {
+    assert!(GraphicsSettings::default().check().is_ok());
+    for settings in [
+        GraphicsSettings {
+            anisotropy: 0.5,
+            ..Default::default()
+        },
+        GraphicsSettings {
+            mip_lod_bias: f32::NAN,
+            ..Default::default()
+        },
+        GraphicsSettings {
+            texture_scale: 0.0,
+            ..Default::default()
+        },
+        GraphicsSettings {
+            texture_scale: 2.0,
+            ..Default::default()
+        },
+    ] {
+        assert!(settings.check().is_err(), "{:?}", settings);
+    }
+}
+
+#[test]
+fn texture_samplers_follow_the_settings() {
+    let settings = GraphicsSettings {
+        anisotropy: 8.0,
+        mip_lod_bias: 0.5,
+        texture_scale: 1.0,
+    };
+
+    let linear = settings.texture_sampler(&SamplerDescriptor::default(), 16.0);
+    assert_eq!(linear.max_anisotropy, 8.0);
+    assert_eq!(linear.mip_lod_bias, 0.5);
+    assert_eq!(linear.info().anisotropy_enable, vk::TRUE);
+
+    // the adapter limits anisotropy, nearest filtering has none
+    let limited = settings.texture_sampler(&SamplerDescriptor::default(), 4.0);
+    assert_eq!(limited.max_anisotropy, 4.0);
+    let nearest = SamplerDescriptor::nearest(vk::SamplerAddressMode::REPEAT);
+    let nearest = settings.texture_sampler(&nearest, 16.0);
+    assert_eq!(nearest.max_anisotropy, 1.0);
+    assert_eq!(nearest.info().anisotropy_enable, vk::FALSE);
+
+    // a different bias is a different sampler
+    assert_ne!(linear, SamplerDescriptor::default().with_anisotropy(8.0));
+}
+
+#[test]
+fn textures_shrink_by_the_texture_scale() {
+    let settings = GraphicsSettings {
+        texture_scale: 0.5,
+        ..Default::default()
+    };
+    assert_eq!(settings.texture_extent(256, 100), (128, 50));
+    assert_eq!(settings.texture_extent(1, 3), (1, 2));
+
+    // each pixel of the half size image averages four
+    let pixels = [
+        [0, 0, 0, 255],
+        [100, 0, 0, 255],
+        [0, 200, 0, 255],
+        [0, 0, 40, 255],
+    ]
+    .concat();
+    assert_eq!(shrink_pixels(2, 2, &pixels, 1, 1), vec![25, 50, 10, 255]);
+    assert_eq!(
+        shrink_pixels(2, 2, &pixels, 2, 1),
+        [[0, 100, 0, 255], [50, 0, 20, 255]].concat()
+    );
+}
+
+#[test]
+fn validation_settings_parse_the_environment_values() {
+    use ValidationSeverity::*;
+    assert_eq!(
+        ValidationSettings::parse("off").unwrap(),
+        ValidationSettings::disabled()
+    );
+    assert_eq!(
+        ValidationSettings::parse(" 0 ").unwrap(),
+        ValidationSettings::disabled()
+    );
+    assert_eq!(
+        ValidationSettings::parse("TRUE").unwrap(),
+        ValidationSettings::enabled(Verbose)
+    );
+    assert_eq!(
+        ValidationSettings::parse("Warning").unwrap(),
+        ValidationSettings::enabled(Warning)
+    );
+    assert_eq!(
+        ValidationSettings::parse("error").unwrap(),
+        ValidationSettings::enabled(Error)
+    );
+    let error = ValidationSettings::parse("loud").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Unknown validation setting `loud`, expected on, off or a severity."
+    );
+
+    // the only test of this binary reading the variable
+    std::env::set_var(VALIDATION_VARIABLE, "info");
+    assert_eq!(
+        ValidationSettings::from_env(),
+        ValidationSettings::enabled(Info)
+    );
+    std::env::set_var(VALIDATION_VARIABLE, "loud");
+    assert_eq!(
+        ValidationSettings::from_env(),
+        ValidationSettings::default()
+    );
+    std::env::remove_var(VALIDATION_VARIABLE);
+    assert_eq!(
+        ValidationSettings::from_env().enabled,
+        cfg!(debug_assertions)
+    );
+}
+
+#[test]
+fn validation_severities_include_the_ones_above() {
+    use vk::DebugUtilsMessageSeverityFlagsEXT as F;
+    assert_eq!(ValidationSeverity::Verbose.flags(), F::all());
+    assert_eq!(
+        ValidationSeverity::Info.flags(),
+        F::INFO | F::WARNING | F::ERROR
+    );
+    assert_eq!(ValidationSeverity::Warning.flags(), F::WARNING | F::ERROR);
+    assert_eq!(ValidationSeverity::Error.flags(), F::ERROR);
+    assert!(ValidationSeverity::Warning < ValidationSeverity::Error);
+}
@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: MIT
+
+//! The surface formats and present modes a device reports for settings menus.
+
+use deimos::gfx::{present_modes, ColorSpace, PresentMode, SurfaceFormat};
+use vulkanalia::vk;
+
+#[test]
+fn present_modes_are_translated() {
+    let modes = [
+        vk::PresentModeKHR::MAILBOX,
+        vk::PresentModeKHR::FIFO,
+        vk::PresentModeKHR::SHARED_DEMAND_REFRESH,
+        vk::PresentModeKHR::FIFO,
+        vk::PresentModeKHR::IMMEDIATE,
+    ];
+
+    // modes of extensions and duplicates are left out, the order is kept
+    assert_eq!(
+        present_modes(&modes),
+        vec![
+            PresentMode::Mailbox,
+            PresentMode::Fifo,
+            PresentMode::Immediate
+        ]
+    );
+    for mode in [
+        PresentMode::Fifo,
+        PresentMode::FifoRelaxed,
+        PresentMode::Mailbox,
+        PresentMode::Immediate,
+    ] {
+        assert_eq!(PresentMode::from_vk(mode.to_vk()), Some(mode));
+    }
+}
+
+#[test]
+fn surface_formats_know_their_encoding() {
+    let srgb = SurfaceFormat::from(vk::SurfaceFormatKHR {
+        format: vk::Format::B8G8R8A8_SRGB,
+        color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR,
+    });
+    assert_eq!(srgb.format, vk::Format::B8G8R8A8_SRGB);
+    assert_eq!(srgb.encoding(), ColorSpace::Srgb);
+
+    let unorm = SurfaceFormat {
+        format: vk::Format::A2B10G10R10_UNORM_PACK32,
+        color_space: vk::ColorSpaceKHR::HDR10_ST2084_EXT,
+    };
+    assert_eq!(unorm.encoding(), ColorSpace::Linear);
+}
@@ -0,0 +1,228 @@
+// SPDX-License-Identifier: MIT
+
+//! Tests of frame traces, captured on the null driver and on a real gpu where there is one.
+
+use std::mem::size_of;
+
+use anyhow::Result;
+use deimos::gfx::{
+    AdapterSelection, Buffer, FrameTrace, HeadlessDevice, NullBackend, TraceBufferBarrier,
+    TraceCommand, TraceSubmission,
+};
+use vulkanalia::prelude::v1_0::*;
+
+const VALUES: usize = 64;
+const SIZE: vk::DeviceSize = (VALUES * size_of::<u32>()) as vk::DeviceSize;
+
+fn host_buffer(gpu: &HeadlessDevice, usage: vk::BufferUsageFlags) -> Result<Buffer> {
+    gpu.create_buffer(
+        SIZE,
+        usage,
+        vk::MemoryPropertyFlags::HOST_COHERENT | vk::MemoryPropertyFlags::HOST_VISIBLE,
+    )
+}
+
+// copy the first half of `src` to `dst` and fill the rest with 7
+fn copy_and_fill(gpu: &HeadlessDevice, src: &Buffer, dst: &Buffer) -> Result<()> {
+    gpu.execute(|device, command_buffer| unsafe {
+        let region = vk::BufferCopy::builder().size(SIZE / 2);
+        device.cmd_copy_buffer(command_buffer, src.buffer, dst.buffer, &[region]);
+        let barrier = vk::BufferMemoryBarrier::builder()
+            .buffer(dst.buffer)
+            .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+            .dst_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .size(vk::WHOLE_SIZE as vk::DeviceSize);
+        device.cmd_pipeline_barrier(
+            command_buffer,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::DependencyFlags::empty(),
+            &[] as &[vk::MemoryBarrier],
+            &[barrier],
+            &[] as &[vk::ImageMemoryBarrier],
+        );
+        device.cmd_fill_buffer(command_buffer, dst.buffer, SIZE / 2, SIZE / 2, 7);
+        Ok(())
+    })
+}
+
+// capture `copy_and_fill`, clear the destination and check the replay writes it again
+fn capture_and_replay(gpu: &HeadlessDevice) -> Result<FrameTrace> {
+    unsafe {
+        let values = (0..VALUES as u32).collect::<Vec<_>>();
+        let src = host_buffer(gpu, vk::BufferUsageFlags::TRANSFER_SRC)?;
+        src.write(gpu.device(), 0, &values)?;
+        let dst = host_buffer(gpu, vk::BufferUsageFlags::TRANSFER_DST)?;
+
+        gpu.start_capture();
+        copy_and_fill(gpu, &src, &dst)?;
+        let trace = gpu.stop_capture();
+
+        dst.write(gpu.device(), 0, &[0u32; VALUES])?;
+        gpu.replay(&trace)?;
+        let read = dst.read::<u32>(gpu.device(), 0, VALUES)?;
+        assert_eq!(read[..VALUES / 2], values[..VALUES / 2]);
+        assert!(read[VALUES / 2..].iter().all(|v| *v == 7));
+
+        src.destroy(gpu.device());
+        dst.destroy(gpu.device());
+        Ok(trace)
+    }
+}
+
+fn assert_copy_and_fill(trace: &FrameTrace) {
+    assert_eq!(trace.submissions.len(), 1);
+    let commands = trace.commands().collect::<Vec<_>>();
+    assert_eq!(commands.len(), 3, "{}", trace);
+    assert!(
+        matches!(commands[0], TraceCommand::CopyBuffer { regions, .. } if regions[0].size == SIZE / 2)
+    );
+    assert!(matches!(
+        commands[1],
+        TraceCommand::PipelineBarrier { buffers, .. } if buffers[0].dst_access == vk::AccessFlags::TRANSFER_WRITE
+    ));
+    assert!(matches!(
+        commands[2],
+        TraceCommand::FillBuffer { offset, data: 7, .. } if *offset == SIZE / 2
+    ));
+}
+
+#[test]
+fn null_capture_replays_the_commands() -> Result<()> {
+    let backend = NullBackend::new();
+    let gpu = HeadlessDevice::create_null(&backend)?;
+    let trace = capture_and_replay(&gpu)?;
+    assert_copy_and_fill(&trace);
+
+    // the replay records the captured commands again
+    let replayed = format!("{:?}", backend.submissions().last().unwrap().commands);
+    assert_eq!(replayed, format!("{:?}", trace.submissions[0].commands));
+
+    gpu.destroy();
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    Ok(())
+}
+
+#[test]
+fn gpu_capture_replays_the_commands() -> Result<()> {
+    let Some(gpu) = HeadlessDevice::create(AdapterSelection::First)? else {
+        assert!(
+            std::env::var_os("DEIMOS_REQUIRE_GPU").is_none(),
+            "DEIMOS_REQUIRE_GPU is set but there is no vulkan device"
+        );
+        eprintln!("skipping, there is no vulkan device");
+        return Ok(());
+    };
+    let trace = capture_and_replay(&gpu)?;
+    assert_copy_and_fill(&trace);
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn traces_survive_encoding() -> Result<()> {
+    let trace = FrameTrace {
+        submissions: vec![TraceSubmission {
+            command_buffers: vec![vk::CommandBuffer::from_raw(1)],
+            commands: vec![
+                TraceCommand::BeginRenderPass {
+                    render_pass: vk::RenderPass::from_raw(2),
+                    framebuffer: vk::Framebuffer::from_raw(3),
+                    area: vk::Rect2D {
+                        offset: vk::Offset2D { x: 1, y: -2 },
+                        extent: vk::Extent2D {
+                            width: 32,
+                            height: 16,
+                        },
+                    },
+                    clear_values: vec![vk::ClearValue {
+                        color: vk::ClearColorValue {
+                            float32: [0.0, 0.5, 1.0, 1.0],
+                        },
+                    }],
+                    contents: vk::SubpassContents::INLINE,
+                },
+                TraceCommand::PushConstants {
+                    layout: vk::PipelineLayout::from_raw(4),
+                    stages: vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+                    offset: 16,
+                    data: vec![1, 2, 3, 4],
+                },
+                TraceCommand::DrawIndexed {
+                    index_count: 36,
+                    instance_count: 2,
+                    first_index: 0,
+                    vertex_offset: -4,
+                    first_instance: 1,
+                },
+                TraceCommand::PipelineBarrier {
+                    src_stage: vk::PipelineStageFlags::TRANSFER,
+                    dst_stage: vk::PipelineStageFlags::VERTEX_INPUT,
+                    dependency: vk::DependencyFlags::BY_REGION,
+                    memory_barriers: vec![(
+                        vk::AccessFlags::TRANSFER_WRITE,
+                        vk::AccessFlags::VERTEX_ATTRIBUTE_READ,
+                    )],
+                    buffers: vec![TraceBufferBarrier {
+                        buffer: vk::Buffer::from_raw(5),
+                        size: 64,
+                        ..Default::default()
+                    }],
+                    images: vec![],
+                },
+                TraceCommand::EndRenderPass,
+                TraceCommand::BeginLabel {
+                    name: "bloom".to_string(),
+                    color: [0.5, 0.25, 1.0, 1.0],
+                },
+                TraceCommand::EndLabel,
+            ],
+            wait_semaphores: vec![vk::Semaphore::from_raw(6)],
+            signal_semaphores: vec![],
+            fence: vk::Fence::from_raw(7),
+        }],
+    };
+
+    let decoded = FrameTrace::decode(&trace.encode())?;
+    assert_eq!(format!("{:?}", decoded), format!("{:?}", trace));
+
+    let path = std::env::temp_dir().join(format!("deimos-trace-{}.dtrc", std::process::id()));
+    trace.save(&path)?;
+    let loaded = FrameTrace::load(&path);
+    std::fs::remove_file(&path)?;
+    assert_eq!(format!("{:?}", loaded?), format!("{:?}", trace));
+
+    // one line per submission and per command
+    let dump = trace.to_string();
+    assert_eq!(dump.lines().count(), 8);
+    assert!(dump.starts_with("submission 0: 1 command buffers"));
+    assert!(dump.contains("    DrawIndexed { index_count: 36"));
+    Ok(())
+}
+
+#[test]
+fn broken_traces_are_refused() {
+    let trace = FrameTrace {
+        submissions: vec![TraceSubmission {
+            command_buffers: vec![],
+            commands: vec![TraceCommand::Dispatch { x: 1, y: 2, z: 3 }],
+            wait_semaphores: vec![],
+            signal_semaphores: vec![],
+            fence: vk::Fence::null(),
+        }],
+    };
+    let bytes = trace.encode();
+
+    let error = |bytes: &[u8]| FrameTrace::decode(bytes).unwrap_err().to_string();
+    assert_eq!(error(b"PNG\0...."), "Not a frame trace.");
+    assert_eq!(error(&bytes[..bytes.len() - 1]), "Frame trace ends early.");
+    assert_eq!(
+        error(&[&bytes[..], &[0]].concat()),
+        "Frame trace has trailing bytes."
+    );
+    let mut newer = bytes.clone();
+    newer[4] = 9;
+    assert_eq!(error(&newer), "Unsupported frame trace version (9).");
+}
@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: MIT
+
+//! Uniform and storage block layouts against the offsets glsl gives the same blocks, and the
+//! bytes written for them.
+
+use cgmath::{vec2, vec3, Matrix3, Vector2, Vector3};
+use deimos::gfx::{BlockLayout, Uniform};
+
+#[derive(Copy, Clone)]
+struct Mixed {
+    a: f32,
+    b: Vector3<f32>,
+    c: f32,
+    d: [f32; 3],
+    e: Matrix3<f32>,
+    f: Vector2<f32>,
+}
+
+deimos::uniform_block!(Mixed { a, b, c, d, e, f });
+
+#[derive(Copy, Clone)]
+struct Scalar {
+    value: f32,
+}
+
+deimos::uniform_block!(Scalar { value });
+
+#[derive(Copy, Clone)]
+struct Nested {
+    count: u32,
+    items: [Scalar; 2],
+    last: bool,
+}
+
+deimos::uniform_block!(Nested { count, items, last });
+
+fn f32_at(bytes: &[u8], offset: usize) -> f32 {
+    f32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+#[test]
+fn members_are_placed_like_glsl_places_them() {
+    // vec3 takes four floats of alignment but three of size, arrays and matrices round their
+    // elements up to a vec4 in std140 only
+    assert_eq!(
+        Mixed::offsets(BlockLayout::Std140),
+        [0, 16, 28, 32, 80, 128]
+    );
+    assert_eq!(Mixed::size(BlockLayout::Std140), 144);
+    assert_eq!(Mixed::offsets(BlockLayout::Std430), [0, 16, 28, 32, 48, 96]);
+    assert_eq!(Mixed::size(BlockLayout::Std430), 112);
+
+    // structs are rounded up to a vec4 in std140, also as array elements
+    assert_eq!(Scalar::size(BlockLayout::Std140), 16);
+    assert_eq!(Scalar::size(BlockLayout::Std430), 4);
+    assert_eq!(Nested::offsets(BlockLayout::Std140), [0, 16, 48]);
+    assert_eq!(Nested::size(BlockLayout::Std140), 64);
+    assert_eq!(Nested::offsets(BlockLayout::Std430), [0, 4, 12]);
+    assert_eq!(Nested::size(BlockLayout::Std430), 16);
+}
+
+#[test]
+fn bytes_follow_the_offsets_with_zero_padding() {
+    let mixed = Mixed {
+        a: 1.0,
+        b: vec3(2.0, 3.0, 4.0),
+        c: 5.0,
+        d: [6.0, 7.0, 8.0],
+        e: Matrix3::from_cols(
+            vec3(9.0, 10.0, 11.0),
+            vec3(12.0, 13.0, 14.0),
+            vec3(15.0, 16.0, 17.0),
+        ),
+        f: vec2(18.0, 19.0),
+    };
+
+    let bytes = mixed.bytes(BlockLayout::Std140);
+    assert_eq!(bytes.len(), 144);
+    assert_eq!(f32_at(&bytes, 0), 1.0);
+    assert_eq!(f32_at(&bytes, 4), 0.0);
+    assert_eq!(f32_at(&bytes, 24), 4.0);
+    assert_eq!(f32_at(&bytes, 28), 5.0);
+    assert_eq!(f32_at(&bytes, 48), 7.0);
+    assert_eq!(f32_at(&bytes, 96), 12.0);
+    assert_eq!(f32_at(&bytes, 108), 0.0);
+    assert_eq!(f32_at(&bytes, 132), 19.0);
+
+    let bytes = mixed.bytes(BlockLayout::Std430);
+    assert_eq!(bytes.len(), 112);
+    assert_eq!(f32_at(&bytes, 36), 7.0);
+    assert_eq!(f32_at(&bytes, 64), 12.0);
+    assert_eq!(f32_at(&bytes, 100), 19.0);
+
+    // bools are 32 bit
+    let nested = Nested {
+        count: 2,
+        items: [Scalar { value: 0.5 }, Scalar { value: 0.25 }],
+        last: true,
+    };
+    let bytes = nested.bytes(BlockLayout::Std140);
+    assert_eq!(bytes[..4], 2u32.to_ne_bytes());
+    assert_eq!(f32_at(&bytes, 32), 0.25);
+    assert_eq!(bytes[48..52], 1u32.to_ne_bytes());
+}
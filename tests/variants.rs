@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: MIT
+
+//! The pipeline variants of material states, created lazily and derived from a base.
+
+use anyhow::Result;
+use deimos::gfx::{
+    ColorAttachment, DepthAttachment, HeadlessDevice, NullBackend, PipelineTarget,
+    RenderPassDescriptor, RenderPipelineBuilder,
+};
+use deimos::rendering::{BlendMode, Material, MaterialPipelines, MaterialShaders, MaterialState};
+use vulkanalia::prelude::v1_0::*;
+
+const TRIANGLE_VERT: &[u8] = include_bytes!("shaders/triangle_vert.spv");
+const SOLID_FRAG: &[u8] = include_bytes!("shaders/solid_frag.spv");
+
+const FORMAT: vk::Format = vk::Format::R8G8B8A8_UNORM;
+const DEPTH: vk::Format = vk::Format::D32_SFLOAT;
+
+fn shaders() -> MaterialShaders {
+    MaterialShaders {
+        vertex: TRIANGLE_VERT.to_vec(),
+        fragment: SOLID_FRAG.to_vec(),
+        buffers: vec![],
+    }
+}
+
+#[test]
+fn material_states_get_their_pipelines_lazily() -> Result<()> {
+    let backend = NullBackend::new();
+    let gpu = HeadlessDevice::create_null(&backend)?;
+    unsafe {
+        let device = gpu.device();
+        let color = gpu.create_texture(16, 16, 1, FORMAT, vk::ImageUsageFlags::COLOR_ATTACHMENT)?;
+        let color_view = color.create_view(device, FORMAT, vk::ImageAspectFlags::COLOR, 1)?;
+        let depth = gpu.create_texture(
+            16,
+            16,
+            1,
+            DEPTH,
+            vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+        )?;
+        let depth_view = depth.create_view(device, DEPTH, vk::ImageAspectFlags::DEPTH, 1)?;
+        let descriptor = RenderPassDescriptor::new(16, 16)
+            .color(ColorAttachment::new(&color_view, FORMAT))
+            .depth_stencil(DepthAttachment::new(&depth_view, DEPTH));
+        let target = PipelineTarget::new(gpu.render_pass_for(&descriptor)?, &descriptor);
+        let layout =
+            device.create_pipeline_layout(&vk::PipelineLayoutCreateInfo::builder(), None)?;
+
+        let mut pipelines = MaterialPipelines::new(layout, shaders());
+        pipelines.add_target(target);
+        let opaque = MaterialState::new(&Material::default(), false, vk::SampleCountFlags::_1);
+        let transparent = MaterialState {
+            blend: BlendMode::Transparent,
+            ..opaque
+        };
+        assert!(pipelines.get(&opaque).is_none());
+
+        // the first of the shaders is the base the other variants derive from
+        let base = pipelines.pipeline(device, &opaque)?;
+        assert_eq!(pipelines.pipeline(device, &opaque)?, base);
+        pipelines.prewarm(device, &[opaque, transparent])?;
+        assert_eq!(pipelines.len(), 2);
+        assert_eq!(pipelines.base(&opaque), None);
+        assert_eq!(pipelines.base(&transparent), Some(base));
+        assert_eq!(backend.live_objects(vk::ObjectType::PIPELINE), 2);
+
+        // other sample counts and skinned meshes need their pass and shaders
+        let msaa = MaterialState {
+            samples: vk::SampleCountFlags::_4,
+            ..opaque
+        };
+        let skinned = MaterialState {
+            skinned: true,
+            ..opaque
+        };
+        let error = pipelines.pipeline(device, &msaa).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "No pass draws materials with _4 samples."
+        );
+        let error = pipelines.pipeline(device, &skinned).unwrap_err();
+        assert_eq!(error.to_string(), "Skinned materials need skinned shaders.");
+
+        let mut pipelines = pipelines.with_skinned(shaders());
+        pipelines.add_target(PipelineTarget {
+            samples: vk::SampleCountFlags::_4,
+            ..target
+        });
+        pipelines.prewarm(device, &[msaa, skinned])?;
+        assert_eq!(pipelines.base(&msaa), None);
+        assert_eq!(pipelines.base(&skinned), None);
+        assert_eq!(pipelines.len(), 4);
+
+        pipelines.destroy(device);
+        assert!(pipelines.is_empty());
+        assert_eq!(backend.live_objects(vk::ObjectType::PIPELINE), 0);
+        device.destroy_pipeline_layout(layout, None);
+        depth_view.destroy(device);
+        color_view.destroy(device);
+        depth.destroy(device);
+        color.destroy(device);
+    }
+    assert_eq!(backend.errors(), Vec::<String>::new());
+    gpu.destroy();
+    Ok(())
+}
+
+#[test]
+fn derivatives_need_a_base_that_allows_them() -> Result<()> {
+    let backend = NullBackend::new();
+    let gpu = HeadlessDevice::create_null(&backend)?;
+    unsafe {
+        let device = gpu.device();
+        let color = gpu.create_texture(16, 16, 1, FORMAT, vk::ImageUsageFlags::COLOR_ATTACHMENT)?;
+        let view = color.create_view(device, FORMAT, vk::ImageAspectFlags::COLOR, 1)?;
+        let descriptor =
+            RenderPassDescriptor::new(16, 16).color(ColorAttachment::new(&view, FORMAT));
+        let target = PipelineTarget::new(gpu.render_pass_for(&descriptor)?, &descriptor);
+        let layout =
+            device.create_pipeline_layout(&vk::PipelineLayoutCreateInfo::builder(), None)?;
+        let builder = RenderPipelineBuilder::new(layout)
+            .vertex_shader(TRIANGLE_VERT)
+            .fragment_shader(SOLID_FRAG);
+
+        let plain = builder.build_for(device, &target)?;
+        let derived = builder
+            .clone()
+            .derive_from(plain)
+            .build_for(device, &target)?;
+        let errors = backend.errors();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("forbids derivatives"), "{}", errors[0]);
+        backend.clear();
+
+        let base = builder
+            .clone()
+            .allow_derivatives()
+            .build_for(device, &target)?;
+        let derivative = builder.derive_from(base).build_for(device, &target)?;
+        assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+
+        for pipeline in [plain, derived, base, derivative] {
+            device.destroy_pipeline(pipeline, None);
+        }
+        device.destroy_pipeline_layout(layout, None);
+        view.destroy(device);
+        color.destroy(device);
+    }
+    gpu.destroy();
+    assert!(backend.errors().is_empty(), "{:?}", backend.errors());
+    Ok(())
+}
@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: MIT
+
+//! Decoded video frames for external textures: the planes they need and the matrix the
+//! conversion shader turns yuv into rgb with.
+
+use deimos::rendering::{yuv_to_rgb, VideoFrame, YuvMatrix, YuvRange};
+
+// what the shader computes for 8 bit samples, before decoding srgb
+fn convert(matrix: YuvMatrix, range: YuvRange, yuv: [u8; 3]) -> [f32; 3] {
+    let yuv = [yuv[0], yuv[1], yuv[2]].map(|s| s as f32 / 255.0);
+    yuv_to_rgb(matrix, range)
+        .map(|row| (row[0] * yuv[0] + row[1] * yuv[1] + row[2] * yuv[2] + row[3]).clamp(0.0, 1.0))
+}
+
+fn assert_near(actual: [f32; 3], expected: [f32; 3]) {
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() < 0.01, "{:?} is not {:?}", actual, expected);
+    }
+}
+
+#[test]
+fn yuv_converts_to_rgb() {
+    let (bt709, bt601) = (YuvMatrix::Bt709, YuvMatrix::Bt601);
+    let (limited, full) = (YuvRange::Limited, YuvRange::Full);
+
+    // grays have centered chroma, limited range stretches 16..=235
+    for matrix in [bt709, bt601] {
+        assert_near(convert(matrix, limited, [16, 128, 128]), [0.0, 0.0, 0.0]);
+        assert_near(convert(matrix, limited, [235, 128, 128]), [1.0, 1.0, 1.0]);
+        assert_near(convert(matrix, full, [255, 128, 128]), [1.0, 1.0, 1.0]);
+        assert_near(convert(matrix, full, [128, 128, 128]), [0.5, 0.5, 0.5]);
+    }
+
+    // the primaries as encoders write them
+    assert_near(convert(bt709, limited, [63, 102, 240]), [1.0, 0.0, 0.0]);
+    assert_near(convert(bt709, limited, [173, 42, 26]), [0.0, 1.0, 0.0]);
+    assert_near(convert(bt601, limited, [41, 240, 110]), [0.0, 0.0, 1.0]);
+}
+
+#[test]
+fn frames_need_complete_planes() {
+    let y = [0u8; 6];
+    let uv = [0u8; 4];
+    assert!(VideoFrame::nv12(3, 2, &y, &uv).check().is_ok());
+    assert!(VideoFrame::nv12(3, 2, &y, &uv[..2]).check().is_err());
+    assert!(VideoFrame::nv12(0, 2, &[], &uv).check().is_err());
+    assert!(VideoFrame::rgba8(1, 1, &[0; 4]).check().is_ok());
+    assert!(VideoFrame::rgba8(1, 1, &[0; 3]).check().is_err());
+
+    // odd sizes round the chroma planes up
+    let frame = VideoFrame::i420(3, 3, &[0; 9], &[0; 4], &[0; 4]);
+    assert_eq!(frame.chroma_extent(), (2, 2));
+    assert!(frame.check().is_ok());
+    assert!(frame.is_yuv());
+}
+
+#[test]
+fn chroma_is_interleaved_for_upload() {
+    let y = [1, 2, 3, 4];
+    let frame = VideoFrame::i420(2, 2, &y, &[10], &[20]);
+    assert_eq!(frame.staged_bytes(), vec![1, 2, 3, 4, 10, 20]);
+
+    let frame = VideoFrame::nv12(2, 2, &y, &[10, 20]);
+    assert_eq!(frame.staged_bytes(), vec![1, 2, 3, 4, 10, 20]);
+    assert!(!VideoFrame::rgba8(1, 1, &[0; 4]).is_yuv());
+}
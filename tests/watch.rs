@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: MIT
+
+//! Hot reload of source files: the watcher reports written files with their decoded contents
+//! and the mesh reader turns the supported files into packs. no device is needed, uploading
+//! the data is what the asset cache adds on top.
+
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use deimos::format::{BasicVertex, MeshPack};
+use deimos::rendering::{FileChange, FileWatcher, SourceData, SourceKind};
+
+// a file of its own in the temp folder for every test
+fn temp_file(name: &str) -> PathBuf {
+    let folder = std::env::temp_dir().join(format!("deimos-watch-{}", std::process::id()));
+    fs::create_dir_all(&folder).unwrap();
+    folder.join(name)
+}
+
+fn triangle(scale: f32) -> MeshPack {
+    let vertex = |x: f32, y: f32| BasicVertex {
+        position: [x * scale, y * scale, 0.0].into(),
+        ..Default::default()
+    };
+    MeshPack::from_vertices(
+        &[vertex(0.0, 0.0), vertex(1.0, 0.0), vertex(0.0, 1.0)],
+        &[0, 1, 2],
+    )
+}
+
+fn wait_for_change(watcher: &FileWatcher) -> Option<FileChange> {
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(5) {
+        if let Some(change) = watcher.changes().pop() {
+            return Some(change);
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    None
+}
+
+#[test]
+fn written_files_are_reported_decoded() -> Result<()> {
+    let path = temp_file("triangle.mesh");
+    let path = path.to_str().unwrap();
+    fs::write(path, triangle(1.0).encode())?;
+
+    let watcher = FileWatcher::new(Duration::from_millis(5));
+    watcher.watch(path, SourceKind::Mesh);
+    assert!(watcher.is_watched(path));
+
+    // nothing changed yet
+    thread::sleep(Duration::from_millis(50));
+    assert!(watcher.changes().is_empty());
+
+    // a rewrite comes with the new contents
+    fs::write(path, triangle(2.0).encode())?;
+    let change = wait_for_change(&watcher).expect("the change was not reported");
+    assert_eq!(
+        (change.path.as_str(), change.kind),
+        (path, SourceKind::Mesh)
+    );
+    match change.data? {
+        SourceData::Mesh(pack) => assert_eq!(pack, triangle(2.0)),
+        SourceData::Texture { .. } => panic!("a mesh was decoded as a texture"),
+    }
+
+    // a broken save is reported as such, the next good one follows
+    fs::write(path, b"half written")?;
+    assert!(wait_for_change(&watcher).unwrap().data.is_err());
+    fs::write(path, triangle(3.0).encode())?;
+    assert!(wait_for_change(&watcher).unwrap().data.is_ok());
+
+    // unwatched files are quiet
+    watcher.unwatch(path);
+    fs::write(path, triangle(4.0).encode())?;
+    thread::sleep(Duration::from_millis(50));
+    assert!(watcher.changes().is_empty());
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[cfg(feature = "obj")]
+#[test]
+fn obj_files_read_into_lit_packs() -> Result<()> {
+    use deimos::format::{SurfaceVertex, VertexKind};
+    use deimos::rendering::read_mesh;
+
+    let path = temp_file("quad.obj");
+    fs::write(
+        &path,
+        "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+         vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\n\
+         f 1/1 2/2 3/3 4/4\n",
+    )?;
+
+    // the quad is triangulated and gets normals and tangents
+    let pack = read_mesh(path.to_str().unwrap())?;
+    assert_eq!(pack.kind, VertexKind::Surface);
+    assert_eq!(pack.indices.len(), 6);
+    for vertex in pack.vertices::<SurfaceVertex>()? {
+        assert_eq!(<[f32; 3]>::from(vertex.normal), [0.0, 0.0, 1.0]);
+        assert_eq!(<[f32; 4]>::from(vertex.tangent)[..3], [1.0, 0.0, 0.0]);
+    }
+
+    // anything else is a mesh pack
+    let path = temp_file("quad.mesh");
+    fs::write(&path, triangle(1.0).encode())?;
+    assert_eq!(read_mesh(path.to_str().unwrap())?, triangle(1.0));
+    fs::write(&path, b"not a pack")?;
+    assert!(read_mesh(path.to_str().unwrap()).is_err());
+    Ok(())
+}
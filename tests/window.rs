@@ -0,0 +1,222 @@
+// SPDX-License-Identifier: MIT
+
+//! Window modes without a window: which video mode exclusive fullscreen picks, what the
+//! settings select, the logical size of a swapchain on scaled displays and the size the
+//! scene renders at below the swapchain size, damage clipped to the swapchain and when it is cleared rectangle by rectangle, what recreating a swapchain rebuilds, the cursor modes and window icons. switching a
+//! real window or grabbing its cursor needs a display and is left to the demo.
+
+use deimos::gfx::{
+    clip_rects, logical_extent, render_extent, scale_rect, scissored_clears, SwapchainRebuild,
+    SwapchainShape,
+};
+use deimos::window::{
+    choose_display_mode, window_icon, CursorMode, DisplayMode, DisplaySettings, WindowController,
+    WindowMode,
+};
+use vulkanalia::prelude::v1_0::*;
+use winit::window::CursorGrabMode;
+
+fn mode(width: u32, height: u32, hz: u32, bit_depth: u16) -> DisplayMode {
+    DisplayMode {
+        width,
+        height,
+        refresh_rate_millihertz: hz * 1000,
+        bit_depth,
+    }
+}
+
+#[test]
+fn exclusive_fullscreen_picks_the_closest_video_mode() {
+    let modes = [
+        mode(1280, 720, 60, 32),
+        mode(1920, 1080, 60, 32),
+        mode(1920, 1080, 144, 32),
+        mode(1920, 1080, 120, 24),
+        mode(2560, 1440, 60, 32),
+    ];
+
+    // without a wish the largest, then fastest mode
+    assert_eq!(choose_display_mode(&modes, None), Some(4));
+
+    // the size must match, the refresh rate and depth come as close as they can
+    assert_eq!(
+        choose_display_mode(&modes, Some(&mode(1920, 1080, 120, 32))),
+        Some(3)
+    );
+    assert_eq!(
+        choose_display_mode(&modes, Some(&mode(1920, 1080, 75, 32))),
+        Some(1)
+    );
+    assert_eq!(
+        choose_display_mode(&modes, Some(&mode(1280, 720, 144, 32))),
+        Some(0)
+    );
+
+    // sizes the monitor doesn't have and monitors without modes have no choice
+    assert_eq!(
+        choose_display_mode(&modes, Some(&mode(800, 600, 60, 32))),
+        None
+    );
+    assert_eq!(choose_display_mode(&[], None), None);
+}
+
+#[test]
+fn settings_describe_the_window_mode() {
+    assert_eq!(DisplaySettings::default().mode, WindowMode::Windowed);
+    assert_eq!(DisplaySettings::windowed(), DisplaySettings::default());
+
+    let borderless = DisplaySettings::borderless().on_monitor(1);
+    assert_eq!(
+        (borderless.mode, borderless.monitor, borderless.video_mode),
+        (WindowMode::Borderless, Some(1), None)
+    );
+
+    let wanted = mode(1920, 1080, 144, 32);
+    let exclusive = DisplaySettings::exclusive(Some(wanted));
+    assert_eq!(
+        (exclusive.mode, exclusive.monitor, exclusive.video_mode),
+        (WindowMode::Exclusive, None, Some(wanted))
+    );
+}
+
+#[test]
+fn logical_extents_divide_by_the_scale_factor() {
+    let extent = vk::Extent2D {
+        width: 2560,
+        height: 1440,
+    };
+    assert_eq!(logical_extent(extent, 1.0), (2560.0, 1440.0));
+    assert_eq!(logical_extent(extent, 2.0), (1280.0, 720.0));
+    assert_eq!(logical_extent(extent, 1.25), (2048.0, 1152.0));
+
+    // a scale the platform failed to report counts as 1
+    assert_eq!(logical_extent(extent, 0.0), (2560.0, 1440.0));
+}
+
+#[test]
+fn the_scene_renders_at_the_render_scale() {
+    let extent = vk::Extent2D {
+        width: 1920,
+        height: 1080,
+    };
+    let scene = render_extent(extent, 0.75);
+    assert_eq!((scene.width, scene.height), (1440, 810));
+    assert_eq!(render_extent(extent, 1.0), extent);
+
+    // a tiny scale keeps a pixel
+    let tiny = render_extent(extent, 0.0001);
+    assert_eq!((tiny.width, tiny.height), (1, 1));
+
+    // damage in swapchain pixels grows to cover the same area of the scene
+    let rect = vk::Rect2D {
+        offset: vk::Offset2D { x: 101, y: 10 },
+        extent: vk::Extent2D {
+            width: 10,
+            height: 1070,
+        },
+    };
+    let scaled = scale_rect(rect, extent, scene);
+    assert_eq!((scaled.offset.x, scaled.offset.y), (75, 7));
+    assert_eq!((scaled.extent.width, scaled.extent.height), (9, 803));
+    assert_eq!(scale_rect(rect, extent, extent), rect);
+}
+
+#[test]
+fn damage_is_clipped_to_the_swapchain() {
+    let rect = |x, y, width, height| vk::Rect2D {
+        offset: vk::Offset2D { x, y },
+        extent: vk::Extent2D { width, height },
+    };
+    let extent = vk::Extent2D {
+        width: 800,
+        height: 600,
+    };
+
+    // rectangles reaching past an edge are cut, those outside dropped
+    let damage = [
+        rect(-10, -10, 20, 20),
+        rect(790, 100, 20, 20),
+        rect(900, 0, 10, 10),
+        rect(10, 10, 0, 5),
+    ];
+    assert_eq!(
+        clip_rects(&damage, extent),
+        vec![rect(0, 0, 10, 10), rect(790, 100, 10, 20)]
+    );
+}
+
+#[test]
+fn scattered_damage_is_cleared_by_rectangle() {
+    let rect = |x, y, width, height| vk::Rect2D {
+        offset: vk::Offset2D { x, y },
+        extent: vk::Extent2D { width, height },
+    };
+
+    // a toolbar and a status bar leave most of their bounds untouched
+    let damage = [rect(0, 0, 1280, 40), rect(0, 680, 1280, 40)];
+    assert!(scissored_clears(&damage, rect(0, 0, 1280, 720)));
+
+    // neighbours cover their bounds, a single rectangle is its bounds
+    let damage = [rect(0, 0, 640, 720), rect(640, 0, 640, 720)];
+    assert!(!scissored_clears(&damage, rect(0, 0, 1280, 720)));
+    assert!(!scissored_clears(&damage[..1], rect(0, 0, 640, 720)));
+}
+
+#[test]
+fn recreation_rebuilds_what_changed() {
+    let shape = |width, height, image_count| SwapchainShape {
+        format: vk::Format::B8G8R8A8_SRGB,
+        extent: vk::Extent2D { width, height },
+        image_count,
+    };
+    let old = shape(1280, 720, 3);
+    let target = render_extent(old.extent, 1.0);
+
+    // an out of date swapchain of the same size keeps everything but its views
+    let rebuild = SwapchainRebuild::between(old, old, target, 1.0);
+    assert_eq!(rebuild, SwapchainRebuild::default());
+
+    // a resize reallocates the scene target and the copies
+    let rebuild = SwapchainRebuild::between(old, shape(1920, 1080, 3), target, 1.0);
+    assert!(rebuild.scene_target && rebuild.readbacks && !rebuild.commands);
+
+    // more images need more command objects and copies, the target stays
+    let rebuild = SwapchainRebuild::between(old, shape(1280, 720, 4), target, 1.0);
+    assert!(!rebuild.scene_target && rebuild.readbacks && rebuild.commands);
+
+    // a target left at an old render scale follows the current one
+    let rebuild = SwapchainRebuild::between(old, old, target, 0.5);
+    assert!(rebuild.scene_target && !rebuild.readbacks);
+}
+
+#[test]
+fn cursor_modes_grab_and_hide() {
+    let modes = [
+        CursorMode::Normal,
+        CursorMode::Hidden,
+        CursorMode::Confined,
+        CursorMode::Locked,
+    ];
+    let grabs = modes.map(|m| (m.grab(), m.is_visible()));
+    assert_eq!(
+        grabs,
+        [
+            (CursorGrabMode::None, true),
+            (CursorGrabMode::None, false),
+            (CursorGrabMode::Confined, true),
+            (CursorGrabMode::Locked, false),
+        ]
+    );
+
+    // a new window has its focus and a normal cursor
+    let controller = WindowController::new();
+    assert_eq!(controller.cursor_mode(), CursorMode::Normal);
+    assert!(controller.is_focused());
+}
+
+#[test]
+fn window_icons_need_all_pixels() {
+    assert!(window_icon(2, 2, vec![255; 16]).is_ok());
+    assert!(window_icon(2, 2, vec![255; 12]).is_err());
+    assert!(window_icon(2, 2, vec![255; 15]).is_err());
+}